@@ -1 +1,5 @@
-error-unknown-1 = Unknown error
\ No newline at end of file
+error-unknown-1 = Unknown error
+error-body-limit-1 = The submitted data is too large. Try a shorter description or a smaller file.
+error-body-limit-2 = The submitted data could not be checked. Please try again.
+error-rsvps-6 = This event is full. You can join the waitlist and keep your place in line.
+error-rsvps-9 = This event still has spots left. RSVP going instead of joining the waitlist.
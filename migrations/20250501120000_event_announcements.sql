@@ -0,0 +1,7 @@
+CREATE TABLE event_announcements (
+    post_aturi VARCHAR(1024) PRIMARY KEY,
+    event_aturi VARCHAR(1024) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_event_announcements_event ON event_announcements (event_aturi);
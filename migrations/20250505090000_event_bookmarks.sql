@@ -0,0 +1,7 @@
+CREATE TABLE event_bookmarks (
+    did VARCHAR(256) NOT NULL,
+    event_aturi VARCHAR(1024) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (did, event_aturi)
+);
+CREATE INDEX idx_event_bookmarks_did_created ON event_bookmarks (did, created_at DESC);
@@ -0,0 +1,11 @@
+CREATE TABLE integrity_reports (
+    id BIGSERIAL PRIMARY KEY,
+    total_events BIGINT NOT NULL,
+    total_rsvps BIGINT NOT NULL,
+    total_handles BIGINT NOT NULL,
+    orphaned_rsvps BIGINT NOT NULL,
+    handles_without_pds BIGINT NOT NULL,
+    unparseable_events BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_integrity_reports_created_at ON integrity_reports (created_at DESC);
\ No newline at end of file
@@ -0,0 +1,6 @@
+ALTER TABLE events ADD COLUMN starts_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+ALTER TABLE events ADD COLUMN ends_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+ALTER TABLE events ADD COLUMN mode VARCHAR(256) DEFAULT NULL;
+ALTER TABLE events ADD COLUMN status VARCHAR(256) DEFAULT NULL;
+ALTER TABLE events ADD COLUMN locations JSON NOT NULL DEFAULT '[]';
+CREATE INDEX idx_events_starts_at ON events (starts_at);
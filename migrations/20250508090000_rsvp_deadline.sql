@@ -0,0 +1,2 @@
+ALTER TABLE events ADD COLUMN rsvp_closes_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+ALTER TABLE rsvps ADD COLUMN late BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE import_progress (
+    did VARCHAR(256) NOT NULL,
+    collection VARCHAR(1024) NOT NULL,
+    cursor VARCHAR(1024),
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (did, collection)
+);
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE denylist ADD COLUMN category VARCHAR(64) NOT NULL DEFAULT 'other';
+ALTER TABLE denylist ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+ALTER TABLE denylist ADD COLUMN appeal_url TEXT DEFAULT NULL;
+CREATE INDEX idx_denylist_expires_at ON denylist (expires_at);
\ No newline at end of file
@@ -0,0 +1,2 @@
+CREATE INDEX idx_oauth_requests_expires_at ON oauth_requests (expires_at);
+CREATE INDEX idx_oauth_sessions_not_after ON oauth_sessions (not_after);
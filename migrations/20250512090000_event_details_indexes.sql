@@ -0,0 +1,2 @@
+CREATE INDEX idx_events_ends_at ON events (ends_at);
+CREATE INDEX idx_events_status ON events (status);
@@ -0,0 +1,8 @@
+CREATE TABLE event_locations (
+    event_aturi VARCHAR(1024) NOT NULL,
+    country VARCHAR(256) NOT NULL,
+    region VARCHAR(256),
+    locality VARCHAR(256)
+);
+CREATE INDEX idx_event_locations_event_aturi ON event_locations (event_aturi);
+CREATE INDEX idx_event_locations_place ON event_locations (country, region, locality);
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE events ADD COLUMN latitude DOUBLE PRECISION DEFAULT NULL;
+ALTER TABLE events ADD COLUMN longitude DOUBLE PRECISION DEFAULT NULL;
+CREATE INDEX idx_events_coordinates ON events (latitude, longitude) WHERE latitude IS NOT NULL;
\ No newline at end of file
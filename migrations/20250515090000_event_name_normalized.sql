@@ -0,0 +1,4 @@
+ALTER TABLE events ADD COLUMN name_normalized TEXT NOT NULL DEFAULT '';
+-- lower() doesn't strip diacritics, run reparse_events to finish the backfill
+UPDATE events SET name_normalized = lower(name);
+CREATE INDEX idx_events_did_name_normalized ON events (did, name_normalized);
\ No newline at end of file
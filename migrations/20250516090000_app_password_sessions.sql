@@ -0,0 +1,9 @@
+CREATE TABLE app_password_sessions (
+    did varchar(512) PRIMARY KEY,
+    pds varchar(512) NOT NULL,
+    access_token text NOT NULL,
+    refresh_token text NOT NULL,
+    access_token_expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
\ No newline at end of file
@@ -0,0 +1,17 @@
+CREATE TABLE import_plans (
+    id VARCHAR(64) PRIMARY KEY,
+    did VARCHAR(256) NOT NULL,
+    source VARCHAR(1024) NOT NULL,
+    items JSON NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    confirmed_at TIMESTAMP WITH TIME ZONE
+);
+CREATE INDEX idx_import_plans_did ON import_plans (did, created_at DESC);
+
+CREATE TABLE import_source_events (
+    did VARCHAR(256) NOT NULL,
+    uid VARCHAR(1024) NOT NULL,
+    aturi VARCHAR(1024) NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (did, uid)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+CREATE INDEX idx_events_updated_at_aturi ON events (updated_at DESC, aturi DESC);
\ No newline at end of file
@@ -0,0 +1,2 @@
+CREATE INDEX idx_rsvps_updated_at_aturi ON rsvps (updated_at DESC, aturi DESC);
+CREATE INDEX idx_handles_updated_at_did ON handles (updated_at DESC, did DESC);
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE media (
+    aturi VARCHAR(1024) PRIMARY KEY,
+    cid VARCHAR(256) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    name VARCHAR(1024) NOT NULL,
+    blob_cid VARCHAR(256) NOT NULL,
+    mime_type VARCHAR(256) NOT NULL,
+    size BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_media_did_created ON media (did, created_at DESC);
+CREATE INDEX idx_media_did_blob_cid ON media (did, blob_cid);
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE rsvp_backfills (
+    event_aturi VARCHAR(1024) PRIMARY KEY,
+    post_aturi VARCHAR(1024) NOT NULL,
+    like_count INTEGER NOT NULL,
+    rsvp_count INTEGER NOT NULL,
+    source VARCHAR(32) NOT NULL,
+    cursor VARCHAR(1024) DEFAULT NULL,
+    repos_checked INTEGER NOT NULL DEFAULT 0,
+    rsvps_found INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    completed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+CREATE INDEX idx_rsvp_backfills_pending ON rsvp_backfills (updated_at) WHERE completed_at IS NULL;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE handles ADD COLUMN resolved_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+CREATE INDEX idx_handles_resolved_at ON handles (resolved_at ASC NULLS FIRST);
\ No newline at end of file
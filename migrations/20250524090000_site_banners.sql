@@ -0,0 +1,10 @@
+CREATE TABLE site_banners (
+    id VARCHAR(64) PRIMARY KEY,
+    message TEXT NOT NULL,
+    level VARCHAR(16) NOT NULL DEFAULT 'info',
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE,
+    created_by VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_site_banners_schedule ON site_banners (starts_at, ends_at);
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE handles ADD COLUMN display_name VARCHAR(640);
+ALTER TABLE handles ADD COLUMN description TEXT;
+ALTER TABLE handles ADD COLUMN avatar VARCHAR(2048);
+ALTER TABLE handles ADD COLUMN profile_fetched_at TIMESTAMP WITH TIME ZONE;
\ No newline at end of file
@@ -0,0 +1,16 @@
+CREATE TABLE event_migrations (
+    legacy_aturi VARCHAR(1024) PRIMARY KEY,
+    migrated_aturi VARCHAR(1024) NOT NULL,
+    migrated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_event_migrations_migrated ON event_migrations (migrated_aturi);
+
+-- Events migrated before migrations were recorded keep their record key
+INSERT INTO event_migrations (legacy_aturi, migrated_aturi)
+SELECT legacy.aturi, migrated.aturi
+FROM events AS legacy
+JOIN events AS migrated
+    ON migrated.did = legacy.did
+    AND migrated.lexicon = 'community.lexicon.calendar.event'
+    AND split_part(migrated.aturi, '/', 5) = split_part(legacy.aturi, '/', 5)
+WHERE legacy.lexicon = 'events.smokesignal.calendar.event';
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE event_link_clicks (
+    event_aturi VARCHAR(1024) NOT NULL,
+    url TEXT NOT NULL,
+    clicks BIGINT NOT NULL DEFAULT 0,
+    last_clicked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (event_aturi, url)
+);
\ No newline at end of file
@@ -0,0 +1,13 @@
+CREATE TABLE reports (
+    id VARCHAR(64) PRIMARY KEY,
+    reporter_did VARCHAR(256) NOT NULL,
+    event_aturi VARCHAR(1024) NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    resolved_by VARCHAR(256),
+    resolution VARCHAR(64)
+);
+CREATE UNIQUE INDEX idx_reports_open_reporter ON reports (reporter_did, event_aturi) WHERE resolved_at IS NULL;
+CREATE INDEX idx_reports_event ON reports (event_aturi);
+CREATE INDEX idx_reports_open ON reports (created_at) WHERE resolved_at IS NULL;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE oauth_sessions ADD COLUMN user_agent VARCHAR(512);
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE oauth_signing_keys (
+    kid VARCHAR(64) PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    retiring_at TIMESTAMP WITH TIME ZONE,
+    retire_after TIMESTAMP WITH TIME ZONE
+);
\ No newline at end of file
@@ -0,0 +1,13 @@
+CREATE TABLE import_jobs (
+    did VARCHAR(256) PRIMARY KEY,
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    events INTEGER NOT NULL DEFAULT 0,
+    rsvps INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    completed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+CREATE INDEX idx_import_jobs_pending ON import_jobs (updated_at) WHERE status IN ('pending', 'running');
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE import_jobs ADD COLUMN collection VARCHAR(512) DEFAULT NULL;
+ALTER TABLE import_jobs ADD COLUMN cursor VARCHAR(1024) DEFAULT NULL;
+ALTER TABLE import_jobs ADD COLUMN last_record VARCHAR(1024) DEFAULT NULL;
+DROP TABLE import_progress;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE rsvp_history (
+    id BIGSERIAL PRIMARY KEY,
+    rsvp_aturi VARCHAR(1024) NOT NULL,
+    event_aturi VARCHAR(1024) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    previous_status VARCHAR(32) DEFAULT NULL,
+    status VARCHAR(32) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_rsvp_history_event ON rsvp_history (event_aturi, created_at);
\ No newline at end of file
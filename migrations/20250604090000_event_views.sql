@@ -0,0 +1,7 @@
+CREATE TABLE event_views (
+    event_aturi VARCHAR(1024) NOT NULL,
+    day DATE NOT NULL,
+    referrer VARCHAR(256) NOT NULL DEFAULT '',
+    views BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (event_aturi, day, referrer)
+);
\ No newline at end of file
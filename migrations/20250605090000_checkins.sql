@@ -0,0 +1,7 @@
+CREATE TABLE checkins (
+    event_aturi VARCHAR(1024) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    checked_in_by VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (event_aturi, did)
+);
\ No newline at end of file
@@ -0,0 +1,16 @@
+CREATE TABLE calendars (
+    aturi VARCHAR(1024) PRIMARY KEY,
+    cid VARCHAR(256) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    name VARCHAR(1024) NOT NULL,
+    description TEXT,
+    record JSON NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_calendars_did ON calendars (did, updated_at DESC);
+CREATE TABLE calendar_events (
+    calendar_aturi VARCHAR(1024) NOT NULL REFERENCES calendars (aturi) ON DELETE CASCADE,
+    event_aturi VARCHAR(1024) NOT NULL,
+    PRIMARY KEY (calendar_aturi, event_aturi)
+);
+CREATE INDEX idx_calendar_events_event ON calendar_events (event_aturi);
\ No newline at end of file
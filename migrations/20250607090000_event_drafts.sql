@@ -0,0 +1,5 @@
+CREATE TABLE event_drafts (
+    did VARCHAR(256) PRIMARY KEY,
+    form JSON NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
\ No newline at end of file
@@ -0,0 +1,21 @@
+CREATE TABLE event_tags (
+    event_aturi VARCHAR(1024) NOT NULL,
+    tag VARCHAR(64) NOT NULL,
+    PRIMARY KEY (event_aturi, tag)
+);
+CREATE INDEX idx_event_tags_tag ON event_tags (tag);
+INSERT INTO event_tags (event_aturi, tag)
+SELECT DISTINCT aturi, tag
+FROM (
+    SELECT
+        events.aturi,
+        trim(BOTH '-' FROM regexp_replace(lower(value #>> '{}'), '[^[:alnum:]]+', '-', 'g')) AS tag
+    FROM
+        events,
+        json_array_elements(
+            CASE WHEN json_typeof(events.record->'tags') = 'array'
+            THEN events.record->'tags' ELSE '[]'::json END
+        ) AS value
+    WHERE json_typeof(value) = 'string'
+) AS tags
+WHERE tag <> '' AND length(tag) <= 64;
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE follows (
+    did VARCHAR(256) NOT NULL,
+    subject_did VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (did, subject_did)
+);
+CREATE INDEX idx_follows_subject_did ON follows (subject_did);
\ No newline at end of file
@@ -0,0 +1 @@
+CREATE INDEX idx_events_did_starts_at ON events (did, starts_at);
\ No newline at end of file
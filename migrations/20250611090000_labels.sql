@@ -0,0 +1,7 @@
+CREATE TABLE labels (
+    subject VARCHAR(1024) NOT NULL,
+    src VARCHAR(256) NOT NULL,
+    val VARCHAR(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (subject, src, val)
+);
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE event_updates (
+    id BIGSERIAL PRIMARY KEY,
+    event_aturi VARCHAR(1024) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_event_updates_event ON event_updates (event_aturi, created_at DESC);
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE notifications (
+    id BIGSERIAL PRIMARY KEY,
+    did VARCHAR(256) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    subject TEXT NOT NULL,
+    text TEXT NOT NULL,
+    html TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    read_at TIMESTAMP WITH TIME ZONE
+);
+CREATE INDEX idx_notifications_did_created ON notifications (did, created_at DESC);
\ No newline at end of file
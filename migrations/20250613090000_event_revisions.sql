@@ -0,0 +1,10 @@
+CREATE TABLE event_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    event_aturi VARCHAR(1024) NOT NULL,
+    cid VARCHAR(256) NOT NULL,
+    previous_record JSON NOT NULL,
+    record JSON NOT NULL,
+    changes TEXT[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ()
+);
+CREATE INDEX idx_event_revisions_event ON event_revisions (event_aturi, created_at DESC);
\ No newline at end of file
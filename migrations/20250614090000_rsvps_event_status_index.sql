@@ -0,0 +1 @@
+CREATE INDEX idx_rsvps_event_aturi_status ON rsvps (event_aturi, status);
\ No newline at end of file
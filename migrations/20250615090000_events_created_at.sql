@@ -0,0 +1,3 @@
+ALTER TABLE events ADD COLUMN created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW ();
+UPDATE events SET created_at = updated_at;
+CREATE INDEX idx_events_created_at_aturi ON events (created_at DESC, aturi);
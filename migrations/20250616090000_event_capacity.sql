@@ -0,0 +1,8 @@
+ALTER TABLE events ADD COLUMN rsvp_capacity INTEGER DEFAULT NULL;
+CREATE TABLE event_waitlist (
+    event_aturi VARCHAR(1024) NOT NULL,
+    did VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (event_aturi, did)
+);
+CREATE INDEX idx_event_waitlist_event_created ON event_waitlist (event_aturi, created_at);
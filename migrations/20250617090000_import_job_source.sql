@@ -0,0 +1 @@
+ALTER TABLE import_jobs ADD COLUMN source VARCHAR(32) NOT NULL DEFAULT 'records';
\ No newline at end of file
@@ -0,0 +1,103 @@
+//! Bluesky posts that announce new events.
+//!
+//! Organizers can opt in to announcing an event when they create it. The
+//! announcement is an `app.bsky.feed.post` record in the organizer's own
+//! repository with the event name and a link facet over the event URL.
+
+use chrono::{DateTime, Utc};
+
+use crate::atproto::lexicon::app::bsky::feed::post::{ByteSlice, Facet, FacetFeature, Post};
+
+/// The most characters in a post. Bluesky counts graphemes, which are never
+/// more than characters, so posts within this many characters always fit.
+pub const MAX_POST_CHARS: usize = 300;
+
+/// Composes the post that announces an event. Long event names are cut so
+/// that the link to the event always fits.
+pub fn announcement_post(
+    name: &str,
+    event_url: &str,
+    language: &str,
+    created_at: DateTime<Utc>,
+) -> Post {
+    let name = name.trim();
+    let available = MAX_POST_CHARS.saturating_sub(event_url.chars().count() + 2);
+
+    let mut text = if name.chars().count() > available {
+        let mut shortened: String = name.chars().take(available.saturating_sub(1)).collect();
+        shortened.truncate(shortened.trim_end().len());
+        shortened.push('…');
+        shortened
+    } else {
+        name.to_string()
+    };
+    text.push_str("\n\n");
+
+    let byte_start = text.len();
+    text.push_str(event_url);
+    let byte_end = text.len();
+
+    Post::Current {
+        text,
+        facets: vec![Facet {
+            index: ByteSlice {
+                byte_start,
+                byte_end,
+            },
+            features: vec![FacetFeature::Link {
+                uri: event_url.to_string(),
+            }],
+        }],
+        langs: vec![language.to_string()],
+        created_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENT_URL: &str = "https://smokesignal.events/did:plc:abc/3lbs";
+
+    fn created_at() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-05-23T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_announcement_post() {
+        let post = announcement_post("Café meetup ☕ ", EVENT_URL, "en-US", created_at());
+
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            serde_json::json!({
+                "$type": "app.bsky.feed.post",
+                "text": "Café meetup ☕\n\nhttps://smokesignal.events/did:plc:abc/3lbs",
+                "facets": [{
+                    "index": { "byteStart": 18, "byteEnd": 61 },
+                    "features": [{
+                        "$type": "app.bsky.richtext.facet#link",
+                        "uri": EVENT_URL,
+                    }],
+                }],
+                "langs": ["en-US"],
+                "createdAt": "2025-05-23T09:00:00.000Z",
+            })
+        );
+    }
+
+    #[test]
+    fn test_announcement_post_long_name() {
+        let name = "é".repeat(400);
+        let Post::Current { text, facets, .. } =
+            announcement_post(&name, EVENT_URL, "en-US", created_at());
+
+        assert_eq!(text.chars().count(), MAX_POST_CHARS);
+        assert!(text.ends_with(&format!("…\n\n{}", EVENT_URL)));
+        assert_eq!(
+            &text[facets[0].index.byte_start..facets[0].index.byte_end],
+            EVENT_URL
+        );
+    }
+}
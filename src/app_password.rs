@@ -0,0 +1,239 @@
+//! App password sessions for accounts whose PDS does not support OAuth.
+//!
+//! A session is created with `com.atproto.server.createSession` and its
+//! tokens are stored encrypted with AES-256-GCM. The app password itself is
+//! never stored. Access tokens are refreshed when they are about to expire,
+//! and a session whose refresh token is no longer accepted is removed.
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::{
+    app_password_errors::AppPasswordError,
+    atproto::{
+        auth::AppPasswordSessionProvider,
+        client::{create_session, refresh_session, CreateSessionRequest, Session},
+    },
+    config::AppPasswordKey,
+    encoding::FromBase64,
+    storage::{
+        app_password::{
+            app_password_session_delete, app_password_session_get, app_password_session_upsert,
+            AppPasswordSessionParams,
+        },
+        StoragePool,
+    },
+};
+
+const NONCE_LENGTH: usize = 12;
+
+/// Access tokens are refreshed when they expire within this many seconds.
+const REFRESH_WINDOW_SECS: i64 = 300;
+
+#[derive(Deserialize)]
+struct TokenExpiration {
+    exp: i64,
+}
+
+/// Encrypts a session token. The result is the nonce followed by the
+/// ciphertext, encoded as unpadded URL safe base64.
+pub fn encrypt_token(key: &AppPasswordKey, token: &str) -> Result<String, AppPasswordError> {
+    let cipher = Aes256Gcm::new(key.as_ref().into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, token.as_bytes())
+        .map_err(|_| AppPasswordError::EncryptFailed)?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(general_purpose::URL_SAFE_NO_PAD.encode(sealed))
+}
+
+/// Decrypts a session token encrypted with [`encrypt_token`].
+pub fn decrypt_token(key: &AppPasswordKey, value: &str) -> Result<String, AppPasswordError> {
+    let sealed = general_purpose::URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| AppPasswordError::DecryptFailed)?;
+    if sealed.len() <= NONCE_LENGTH {
+        return Err(AppPasswordError::DecryptFailed);
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    let nonce: [u8; NONCE_LENGTH] = nonce
+        .try_into()
+        .map_err(|_| AppPasswordError::DecryptFailed)?;
+    let cipher = Aes256Gcm::new(key.as_ref().into());
+
+    let plaintext = cipher
+        .decrypt(&Nonce::from(nonce), ciphertext)
+        .map_err(|_| AppPasswordError::DecryptFailed)?;
+
+    String::from_utf8(plaintext).map_err(|_| AppPasswordError::DecryptFailed)
+}
+
+/// Reads the expiration of a JWT without verifying it. Tokens without a
+/// readable `exp` claim are treated as already expired.
+fn token_expires_at(token: &str) -> DateTime<Utc> {
+    token
+        .split('.')
+        .nth(1)
+        .and_then(|claims| TokenExpiration::from_base64(claims).ok())
+        .and_then(|claims| DateTime::from_timestamp(claims.exp, 0))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+async fn store_session(
+    pool: &StoragePool,
+    key: &AppPasswordKey,
+    pds: &str,
+    session: &Session,
+) -> Result<(), AppPasswordError> {
+    let access_token = encrypt_token(key, &session.access_jwt)?;
+    let refresh_token = encrypt_token(key, &session.refresh_jwt)?;
+
+    app_password_session_upsert(
+        pool,
+        AppPasswordSessionParams {
+            did: &session.did,
+            pds,
+            access_token: &access_token,
+            refresh_token: &refresh_token,
+            access_token_expires_at: token_expires_at(&session.access_jwt),
+        },
+    )
+    .await
+    .map_err(AppPasswordError::StorageFailed)
+}
+
+/// Signs in to the PDS of `did` with an app password and stores the session.
+pub async fn app_password_sign_in(
+    http_client: &reqwest::Client,
+    pool: &StoragePool,
+    key: &AppPasswordKey,
+    did: &str,
+    pds: &str,
+    password: &str,
+) -> Result<Session, AppPasswordError> {
+    let session = create_session(
+        http_client,
+        pds,
+        &CreateSessionRequest {
+            identifier: did.to_string(),
+            password: password.to_string(),
+        },
+    )
+    .await
+    .map_err(|err| AppPasswordError::SignInFailed(err.to_string()))?;
+
+    if session.did != did {
+        return Err(AppPasswordError::DidMismatch(
+            did.to_string(),
+            session.did.clone(),
+        ));
+    }
+
+    store_session(pool, key, pds, &session).await?;
+
+    Ok(session)
+}
+
+/// Returns the app password session of `did`, refreshing its access token
+/// when it is about to expire. Returns `None` when there is no session, or
+/// when the PDS no longer accepts its refresh token.
+pub async fn app_password_session(
+    http_client: &reqwest::Client,
+    pool: &StoragePool,
+    key: &AppPasswordKey,
+    did: &str,
+) -> Result<Option<AppPasswordSessionProvider>, AppPasswordError> {
+    let Some(stored) = app_password_session_get(pool, did)
+        .await
+        .map_err(AppPasswordError::StorageFailed)?
+    else {
+        return Ok(None);
+    };
+
+    if stored.access_token_expires_at > Utc::now() + Duration::seconds(REFRESH_WINDOW_SECS) {
+        return Ok(Some(AppPasswordSessionProvider {
+            access_token: decrypt_token(key, &stored.access_token)?,
+            pds: stored.pds,
+        }));
+    }
+
+    let refresh_token = decrypt_token(key, &stored.refresh_token)?;
+
+    let session = match refresh_session(http_client, &stored.pds, &refresh_token).await {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(
+                did,
+                error = ?err,
+                "removing app password session that could not be refreshed"
+            );
+            app_password_session_delete(pool, did)
+                .await
+                .map_err(AppPasswordError::StorageFailed)?;
+            return Ok(None);
+        }
+    };
+
+    store_session(pool, key, &stored.pds, &session).await?;
+
+    Ok(Some(AppPasswordSessionProvider {
+        access_token: session.access_jwt,
+        pds: stored.pds,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key() -> AppPasswordKey {
+        AppPasswordKey::try_from(general_purpose::STANDARD.encode([7u8; 32])).unwrap()
+    }
+
+    #[test]
+    fn test_token_round_trip() {
+        let key = test_key();
+
+        let sealed = encrypt_token(&key, "access-token").unwrap();
+        assert!(!sealed.contains("access-token"));
+        assert_eq!(decrypt_token(&key, &sealed).unwrap(), "access-token");
+
+        // Each encryption uses a new nonce
+        assert_ne!(encrypt_token(&key, "access-token").unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_other_key() {
+        let sealed = encrypt_token(&test_key(), "access-token").unwrap();
+
+        let other_key =
+            AppPasswordKey::try_from(general_purpose::STANDARD.encode([8u8; 32])).unwrap();
+        assert!(matches!(
+            decrypt_token(&other_key, &sealed),
+            Err(AppPasswordError::DecryptFailed)
+        ));
+        assert!(matches!(
+            decrypt_token(&test_key(), "short"),
+            Err(AppPasswordError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn test_token_expires_at() {
+        let claims =
+            general_purpose::URL_SAFE_NO_PAD.encode(r#"{"sub":"did:plc:abc","exp":1747000000}"#);
+        let token = format!("eyJhbGciOiJIUzI1NiJ9.{}.signature", claims);
+        assert_eq!(token_expires_at(&token).timestamp(), 1747000000);
+
+        assert_eq!(token_expires_at("not-a-jwt"), DateTime::UNIX_EPOCH);
+    }
+}
@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+use crate::storage::errors::StorageError;
+
+/// Represents errors that can occur while creating or using app password
+/// sessions.
+///
+/// App password sessions let accounts whose PDS does not support OAuth write
+/// records through Smoke Signal. Their tokens are stored encrypted.
+#[derive(Debug, Error)]
+pub enum AppPasswordError {
+    /// Error when a session token cannot be encrypted.
+    ///
+    /// This error occurs when the configured key cannot be used to encrypt
+    /// a token before it is stored.
+    #[error("error-app-password-1 Unable to encrypt session token")]
+    EncryptFailed,
+
+    /// Error when a stored session token cannot be decrypted.
+    ///
+    /// This error occurs when the stored value is malformed or was encrypted
+    /// with a different APP_PASSWORD_KEY.
+    #[error("error-app-password-2 Unable to decrypt session token")]
+    DecryptFailed,
+
+    /// Error when the PDS does not create a session for the app password.
+    ///
+    /// This error occurs when the app password is wrong or revoked, or the
+    /// PDS cannot be reached.
+    #[error("error-app-password-3 Unable to sign in with app password: {0}")]
+    SignInFailed(String),
+
+    /// Error when the session created by the PDS is for another account.
+    ///
+    /// This error occurs when the DID returned by the PDS does not match the
+    /// DID of the account that provided the app password.
+    #[error("error-app-password-4 Session DID {1} does not match {0}")]
+    DidMismatch(String, String),
+
+    /// Error when an app password session cannot be read or written.
+    ///
+    /// This error occurs when the database operation storing or loading the
+    /// session fails.
+    #[error("error-app-password-5 Unable to store app password session: {0:?}")]
+    StorageFailed(StorageError),
+}
@@ -0,0 +1,237 @@
+//! Queries of a Bluesky AppView, which indexes the posts and likes of the
+//! network so they don't have to be collected from each repository.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use tracing::Instrument;
+
+use crate::atproto::errors::ClientError;
+use crate::atproto::xrpc::SimpleError;
+use crate::http::utils::URLBuilder;
+
+const HTTP_CLIENT_TIMEOUT_SECS: u64 = 8;
+
+/// The most posts `app.bsky.feed.getPosts` returns in one request.
+pub const MAX_POSTS_PER_REQUEST: usize = 25;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PostView {
+    pub uri: String,
+    #[serde(default)]
+    pub like_count: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GetPostsOutput {
+    pub posts: Vec<PostView>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LikeActor {
+    pub did: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Like {
+    pub actor: LikeActor,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GetLikesOutput {
+    pub cursor: Option<String>,
+    pub likes: Vec<Like>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileView {
+    pub did: String,
+    pub handle: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileViewBasic {
+    pub did: String,
+    pub handle: String,
+    pub display_name: Option<String>,
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadPostView {
+    pub uri: String,
+    pub author: ProfileViewBasic,
+    pub record: serde_json::Value,
+    #[serde(default)]
+    pub reply_count: u32,
+    #[serde(default)]
+    pub like_count: u32,
+    pub indexed_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ThreadViewPost {
+    pub post: ThreadPostView,
+    #[serde(default)]
+    pub replies: Vec<ThreadItem>,
+}
+
+/// A post of a thread. Posts that were deleted or that the AppView won't
+/// show are kept in the thread as placeholders.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "$type")]
+pub enum ThreadItem {
+    #[serde(rename = "app.bsky.feed.defs#threadViewPost")]
+    Post(Box<ThreadViewPost>),
+    #[serde(other)]
+    Unavailable,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GetPostThreadOutput {
+    pub thread: ThreadItem,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+enum AppViewResponse<T> {
+    Output(T),
+    Error(SimpleError),
+}
+
+/// Gets the number of likes of posts, at most `MAX_POSTS_PER_REQUEST` at a
+/// time. Posts that the AppView doesn't know are left out.
+pub async fn post_like_counts(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    uris: &[String],
+) -> Result<Vec<(String, u32)>> {
+    let mut url_builder = URLBuilder::new(appview_hostname);
+    url_builder.path("/xrpc/app.bsky.feed.getPosts");
+    for uri in uris.iter().take(MAX_POSTS_PER_REQUEST) {
+        url_builder.param("uris", uri);
+    }
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("post_like_counts"))
+        .await?;
+
+    let output = appview_response::<GetPostsOutput>(http_response).await?;
+
+    Ok(output
+        .posts
+        .into_iter()
+        .map(|post| (post.uri, post.like_count))
+        .collect())
+}
+
+/// Lists the DIDs of accounts that liked a post, a page at a time. Returns
+/// the cursor of the next page, if there is one.
+pub async fn post_likers(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    uri: &str,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<(Vec<String>, Option<String>)> {
+    let mut url_builder = URLBuilder::new(appview_hostname);
+    url_builder.path("/xrpc/app.bsky.feed.getLikes");
+    url_builder.param("uri", uri);
+    url_builder.param("limit", &limit.to_string());
+    if let Some(cursor) = cursor {
+        url_builder.param("cursor", cursor);
+    }
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("post_likers"))
+        .await?;
+
+    let output = appview_response::<GetLikesOutput>(http_response).await?;
+
+    let dids = output
+        .likes
+        .into_iter()
+        .map(|like| like.actor.did)
+        .collect::<Vec<_>>();
+
+    // An empty page ends the list even when a cursor is returned.
+    let cursor = output.cursor.filter(|_| !dids.is_empty());
+
+    Ok((dids, cursor))
+}
+
+/// Gets the Bluesky profile of an account with `app.bsky.actor.getProfile`.
+pub async fn get_profile(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    actor: &str,
+) -> Result<ProfileView> {
+    let mut url_builder = URLBuilder::new(appview_hostname);
+    url_builder.path("/xrpc/app.bsky.actor.getProfile");
+    url_builder.param("actor", actor);
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("get_profile"))
+        .await?;
+
+    appview_response::<ProfileView>(http_response).await
+}
+
+/// Gets a post and its replies, `depth` levels deep, with
+/// `app.bsky.feed.getPostThread`. The posts it replies to are left out.
+pub async fn get_post_thread(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    uri: &str,
+    depth: u32,
+) -> Result<ThreadItem> {
+    let mut url_builder = URLBuilder::new(appview_hostname);
+    url_builder.path("/xrpc/app.bsky.feed.getPostThread");
+    url_builder.param("uri", uri);
+    url_builder.param("depth", &depth.to_string());
+    url_builder.param("parentHeight", "0");
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("get_post_thread"))
+        .await?;
+
+    let output = appview_response::<GetPostThreadOutput>(http_response).await?;
+
+    Ok(output.thread)
+}
+
+async fn appview_response<T: serde::de::DeserializeOwned>(
+    http_response: reqwest::Response,
+) -> Result<T> {
+    match http_response.json::<AppViewResponse<T>>().await {
+        Ok(AppViewResponse::Output(output)) => Ok(output),
+        Ok(AppViewResponse::Error(err)) => {
+            Err(ClientError::ServerError(err.error_message()).into())
+        }
+        Err(err) => Err(ClientError::AppViewResponseFailure(err).into()),
+    }
+}
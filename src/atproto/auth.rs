@@ -3,7 +3,10 @@ use p256::SecretKey;
 pub trait OAuthSessionProvider {
     fn oauth_access_token(&self) -> String;
     fn oauth_issuer(&self) -> String;
-    fn dpop_secret(&self) -> SecretKey;
+
+    /// The key used to sign DPoP proofs. Sessions without a key send their
+    /// access token as a plain bearer token.
+    fn dpop_secret(&self) -> Option<SecretKey>;
 }
 
 pub struct SimpleOAuthSessionProvider {
@@ -21,7 +24,28 @@ impl OAuthSessionProvider for SimpleOAuthSessionProvider {
         self.issuer.clone()
     }
 
-    fn dpop_secret(&self) -> SecretKey {
-        self.dpop_secret.clone()
+    fn dpop_secret(&self) -> Option<SecretKey> {
+        Some(self.dpop_secret.clone())
+    }
+}
+
+/// A session created with an app password (`com.atproto.server.createSession`)
+/// for accounts whose PDS does not support OAuth.
+pub struct AppPasswordSessionProvider {
+    pub access_token: String,
+    pub pds: String,
+}
+
+impl OAuthSessionProvider for AppPasswordSessionProvider {
+    fn oauth_access_token(&self) -> String {
+        self.access_token.clone()
+    }
+
+    fn oauth_issuer(&self) -> String {
+        self.pds.clone()
+    }
+
+    fn dpop_secret(&self) -> Option<SecretKey> {
+        None
     }
 }
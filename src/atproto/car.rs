@@ -0,0 +1,488 @@
+//! Reads the records of a repository from a CAR file, as returned by
+//! `com.atproto.sync.getRepo`.
+//!
+//! A CAR file holds the blocks of a repository: its commit, the nodes of the
+//! Merkle Search Tree (MST) that maps record paths to record CIDs, and the
+//! records themselves, all encoded as DAG-CBOR. Records are converted to the
+//! JSON form XRPC returns them in, so they deserialize into the same lexicon
+//! types as records from `listRecords`.
+//!
+//! The commit signature isn't checked, since the file comes from the PDS of
+//! the repository, the same as records listed one page at a time. Blocks are
+//! checked against their CIDs, which also means the tree can't loop.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose, Engine as _};
+use ciborium::value::Value as CborValue;
+use sha2::{Digest, Sha256};
+
+use crate::atproto::errors::CarError;
+
+/// The CBOR tag of a CID link in DAG-CBOR.
+const CBOR_TAG_CID: u64 = 42;
+
+/// The multihash code of SHA-256, the only hash repositories use.
+const MULTIHASH_SHA2_256: u64 = 0x12;
+
+/// A record of a repository, with its path split into collection and rkey.
+#[derive(Debug, Clone)]
+pub struct RepoRecord {
+    pub collection: String,
+    pub rkey: String,
+    pub cid: String,
+    pub value: serde_json::Value,
+}
+
+impl RepoRecord {
+    pub fn aturi(&self, did: &str) -> String {
+        format!("at://{}/{}/{}", did, self.collection, self.rkey)
+    }
+}
+
+/// The blocks of a CAR file, by CID. Blocks borrow from the file.
+pub struct Car<'a> {
+    roots: Vec<String>,
+    blocks: HashMap<String, &'a [u8]>,
+}
+
+impl<'a> Car<'a> {
+    pub fn read(bytes: &'a [u8]) -> Result<Self, CarError> {
+        let mut offset = 0;
+
+        let header_length = read_varint(bytes, &mut offset)?;
+        let header_end = section_end(offset, header_length, bytes.len())?;
+        let header = decode_cbor(&bytes[offset..header_end])?;
+        offset = header_end;
+
+        let roots = match map_get(&header, "roots") {
+            Some(CborValue::Array(values)) => values
+                .iter()
+                .map(cid_from_link)
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(CarError::MissingRoot),
+        };
+
+        let mut blocks = HashMap::new();
+        while offset < bytes.len() {
+            let block_length = read_varint(bytes, &mut offset)?;
+            let block_end = section_end(offset, block_length, bytes.len())?;
+            let block = &bytes[offset..block_end];
+
+            let mut data_offset = 0;
+            let cid = read_cid(block, &mut data_offset)?;
+            let data = &block[data_offset..];
+
+            if Sha256::digest(data)[..] != *cid.digest {
+                return Err(CarError::BlockHashMismatch(cid.to_string()));
+            }
+
+            blocks.insert(cid.to_string(), data);
+            offset = block_end;
+        }
+
+        Ok(Self { roots, blocks })
+    }
+
+    fn decode(&self, cid: &str) -> Result<CborValue, CarError> {
+        let data = self
+            .blocks
+            .get(cid)
+            .ok_or_else(|| CarError::MissingBlock(cid.to_string()))?;
+        decode_cbor(data)
+    }
+
+    /// Lists the records of the given collections in the repository of `did`.
+    pub fn records(&self, did: &str, collections: &[&str]) -> Result<Vec<RepoRecord>, CarError> {
+        let root = self.roots.first().ok_or(CarError::MissingRoot)?;
+        let commit = self.decode(root)?;
+
+        match map_get(&commit, "did") {
+            Some(CborValue::Text(commit_did)) if commit_did == did => {}
+            Some(CborValue::Text(commit_did)) => {
+                return Err(CarError::RepositoryMismatch(
+                    commit_did.clone(),
+                    did.to_string(),
+                ))
+            }
+            _ => return Err(CarError::InvalidBlock("commit has no DID".to_string())),
+        }
+
+        let data = map_get(&commit, "data")
+            .ok_or_else(|| CarError::InvalidBlock("commit has no data".to_string()))?;
+
+        let mut records = vec![];
+        let mut nodes = vec![cid_from_link(data)?];
+
+        while let Some(node_cid) = nodes.pop() {
+            let node = self.decode(&node_cid)?;
+
+            if let Some(left) = map_get(&node, "l").filter(|value| !value.is_null()) {
+                nodes.push(cid_from_link(left)?);
+            }
+
+            let entries = match map_get(&node, "e") {
+                Some(CborValue::Array(values)) => values,
+                _ => {
+                    return Err(CarError::InvalidBlock(
+                        "tree node has no entries".to_string(),
+                    ))
+                }
+            };
+
+            // Each key only stores what differs from the key before it in
+            // the same node.
+            let mut key: Vec<u8> = vec![];
+            for entry in entries {
+                let prefix_length = match map_get(entry, "p") {
+                    Some(CborValue::Integer(value)) => usize::try_from(i128::from(*value))
+                        .map_err(|err| CarError::InvalidBlock(err.to_string()))?,
+                    _ => {
+                        return Err(CarError::InvalidBlock(
+                            "tree entry has no prefix".to_string(),
+                        ))
+                    }
+                };
+                let suffix = match map_get(entry, "k") {
+                    Some(CborValue::Bytes(value)) => value,
+                    _ => return Err(CarError::InvalidBlock("tree entry has no key".to_string())),
+                };
+                if prefix_length > key.len() {
+                    return Err(CarError::InvalidBlock(
+                        "tree entry prefix is too long".to_string(),
+                    ));
+                }
+                key.truncate(prefix_length);
+                key.extend_from_slice(suffix);
+
+                if let Some(right) = map_get(entry, "t").filter(|value| !value.is_null()) {
+                    nodes.push(cid_from_link(right)?);
+                }
+
+                let path = String::from_utf8_lossy(&key);
+                let Some((collection, rkey)) = path.split_once('/') else {
+                    continue;
+                };
+                if !collections.contains(&collection) {
+                    continue;
+                }
+
+                let record_cid = map_get(entry, "v")
+                    .ok_or_else(|| CarError::InvalidBlock("tree entry has no value".to_string()))
+                    .and_then(cid_from_link)?;
+                let value = cbor_to_json(self.decode(&record_cid)?)?;
+
+                records.push(RepoRecord {
+                    collection: collection.to_string(),
+                    rkey: rkey.to_string(),
+                    cid: record_cid,
+                    value,
+                });
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+/// A CID within a block, with its multihash digest.
+struct Cid<'a> {
+    bytes: &'a [u8],
+    digest: &'a [u8],
+}
+
+impl std::fmt::Display for Cid<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "b{}", base32_lower(self.bytes))
+    }
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<usize, CarError> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*offset).ok_or(CarError::Truncated)?;
+        *offset += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return usize::try_from(value).map_err(|_| CarError::InvalidVarint);
+        }
+    }
+    Err(CarError::InvalidVarint)
+}
+
+fn section_end(offset: usize, length: usize, total: usize) -> Result<usize, CarError> {
+    offset
+        .checked_add(length)
+        .filter(|end| *end <= total)
+        .ok_or(CarError::Truncated)
+}
+
+fn read_cid<'a>(bytes: &'a [u8], offset: &mut usize) -> Result<Cid<'a>, CarError> {
+    let start = *offset;
+
+    let version = read_varint(bytes, offset)?;
+    if version != 1 {
+        return Err(CarError::UnsupportedCid(format!("version {}", version)));
+    }
+    let _codec = read_varint(bytes, offset)?;
+    let hash_code = read_varint(bytes, offset)?;
+    if hash_code as u64 != MULTIHASH_SHA2_256 {
+        return Err(CarError::UnsupportedCid(format!("hash {:#x}", hash_code)));
+    }
+    let digest_length = read_varint(bytes, offset)?;
+    let digest_end = section_end(*offset, digest_length, bytes.len())?;
+    let digest = &bytes[*offset..digest_end];
+    *offset = digest_end;
+
+    Ok(Cid {
+        bytes: &bytes[start..digest_end],
+        digest,
+    })
+}
+
+fn decode_cbor(bytes: &[u8]) -> Result<CborValue, CarError> {
+    ciborium::de::from_reader(bytes).map_err(|err| CarError::InvalidBlock(err.to_string()))
+}
+
+fn map_get<'v>(value: &'v CborValue, key: &str) -> Option<&'v CborValue> {
+    match value {
+        CborValue::Map(entries) => entries
+            .iter()
+            .find(|(entry_key, _)| entry_key.as_text() == Some(key))
+            .map(|(_, entry_value)| entry_value),
+        _ => None,
+    }
+}
+
+/// Reads a DAG-CBOR link, which is a CID behind a zero byte.
+fn cid_from_link(value: &CborValue) -> Result<String, CarError> {
+    match value {
+        CborValue::Tag(CBOR_TAG_CID, inner) => match inner.as_ref() {
+            CborValue::Bytes(bytes) if bytes.first() == Some(&0) => {
+                let mut offset = 1;
+                Ok(read_cid(bytes, &mut offset)?.to_string())
+            }
+            _ => Err(CarError::InvalidBlock("malformed link".to_string())),
+        },
+        _ => Err(CarError::InvalidBlock("expected a link".to_string())),
+    }
+}
+
+/// Converts a DAG-CBOR value to the JSON form of the AT Protocol data model,
+/// where links are `{"$link": cid}` and bytes are `{"$bytes": base64}`.
+fn cbor_to_json(value: CborValue) -> Result<serde_json::Value, CarError> {
+    Ok(match value {
+        CborValue::Null => serde_json::Value::Null,
+        CborValue::Bool(value) => serde_json::Value::Bool(value),
+        CborValue::Integer(value) => i64::try_from(i128::from(value))
+            .map(serde_json::Value::from)
+            .map_err(|err| CarError::InvalidBlock(err.to_string()))?,
+        CborValue::Float(value) => serde_json::Number::from_f64(value)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        CborValue::Text(value) => serde_json::Value::String(value),
+        CborValue::Bytes(value) => serde_json::json!({
+            "$bytes": general_purpose::STANDARD_NO_PAD.encode(value)
+        }),
+        CborValue::Tag(CBOR_TAG_CID, _) => serde_json::json!({ "$link": cid_from_link(&value)? }),
+        CborValue::Array(values) => serde_json::Value::Array(
+            values
+                .into_iter()
+                .map(cbor_to_json)
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        CborValue::Map(entries) => {
+            let mut object = serde_json::Map::with_capacity(entries.len());
+            for (key, value) in entries {
+                let CborValue::Text(key) = key else {
+                    return Err(CarError::InvalidBlock("map key isn't text".to_string()));
+                };
+                object.insert(key, cbor_to_json(value)?);
+            }
+            serde_json::Value::Object(object)
+        }
+        _ => return Err(CarError::InvalidBlock("unsupported value".to_string())),
+    })
+}
+
+fn base32_lower(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+    let mut output = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: &CborValue) -> Vec<u8> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn cid_bytes(data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x01, 0x71, 0x12, 0x20];
+        bytes.extend_from_slice(&Sha256::digest(data));
+        bytes
+    }
+
+    fn link(data: &[u8]) -> CborValue {
+        let mut bytes = vec![0];
+        bytes.extend(cid_bytes(data));
+        CborValue::Tag(CBOR_TAG_CID, Box::new(CborValue::Bytes(bytes)))
+    }
+
+    fn text_map(entries: Vec<(&str, CborValue)>) -> CborValue {
+        CborValue::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (CborValue::Text(key.to_string()), value))
+                .collect(),
+        )
+    }
+
+    fn write_section(car: &mut Vec<u8>, section: &[u8]) {
+        let mut length = section.len();
+        loop {
+            let byte = (length & 0x7f) as u8;
+            length >>= 7;
+            if length == 0 {
+                car.push(byte);
+                break;
+            }
+            car.push(byte | 0x80);
+        }
+        car.extend_from_slice(section);
+    }
+
+    fn tree_entry(prefix: i64, key: &str, value: &[u8]) -> CborValue {
+        text_map(vec![
+            ("p", CborValue::Integer(prefix.into())),
+            ("k", CborValue::Bytes(key.as_bytes().to_vec())),
+            ("v", link(value)),
+            ("t", CborValue::Null),
+        ])
+    }
+
+    fn test_car(did: &str) -> Vec<u8> {
+        let event = encode(&text_map(vec![
+            (
+                "$type",
+                CborValue::Text("community.lexicon.calendar.event".to_string()),
+            ),
+            ("name", CborValue::Text("Meetup".to_string())),
+        ]));
+        let post = encode(&text_map(vec![(
+            "$type",
+            CborValue::Text("app.bsky.feed.post".to_string()),
+        )]));
+
+        let node = encode(&text_map(vec![
+            ("l", CborValue::Null),
+            (
+                "e",
+                CborValue::Array(vec![
+                    tree_entry(0, "app.bsky.feed.post/3kaaaaaaaaaa2", &post),
+                    tree_entry(0, "community.lexicon.calendar.event/3kaaaaaaaaaa2", &event),
+                    tree_entry(44, "b2", &event),
+                ]),
+            ),
+        ]));
+
+        let commit = encode(&text_map(vec![
+            ("did", CborValue::Text(did.to_string())),
+            ("version", CborValue::Integer(3.into())),
+            ("data", link(&node)),
+        ]));
+
+        let header = encode(&text_map(vec![
+            ("version", CborValue::Integer(1.into())),
+            ("roots", CborValue::Array(vec![link(&commit)])),
+        ]));
+
+        let mut car = vec![];
+        write_section(&mut car, &header);
+        for block in [&commit, &node, &event, &post] {
+            let mut section = cid_bytes(block);
+            section.extend_from_slice(block);
+            write_section(&mut car, &section);
+        }
+        car
+    }
+
+    #[test]
+    fn reads_records_of_collections() {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let bytes = test_car(did);
+        let car = Car::read(&bytes).unwrap();
+
+        let records = car
+            .records(did, &["community.lexicon.calendar.event"])
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].rkey, "3kaaaaaaaaaa2");
+        assert_eq!(records[1].rkey, "3kaaaaaaaaab2");
+        assert_eq!(
+            records[0].aturi(did),
+            format!(
+                "at://{}/community.lexicon.calendar.event/3kaaaaaaaaaa2",
+                did
+            )
+        );
+        assert_eq!(records[0].value["name"], "Meetup");
+        assert!(records[0].cid.starts_with("bafyrei"));
+    }
+
+    #[test]
+    fn rejects_other_repositories() {
+        let bytes = test_car("did:plc:d5c1ed6d01421a67b96f68fa");
+        let car = Car::read(&bytes).unwrap();
+
+        assert!(matches!(
+            car.records("did:plc:other", &["community.lexicon.calendar.event"]),
+            Err(CarError::RepositoryMismatch(_, _))
+        ));
+    }
+
+    #[test]
+    fn rejects_tampered_blocks() {
+        let mut bytes = test_car("did:plc:d5c1ed6d01421a67b96f68fa");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(matches!(
+            Car::read(&bytes),
+            Err(CarError::BlockHashMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn converts_links_and_bytes() {
+        let value = cbor_to_json(text_map(vec![
+            ("ref", link(b"blob")),
+            ("data", CborValue::Bytes(vec![1, 2, 3])),
+        ]))
+        .unwrap();
+
+        assert!(value["ref"]["$link"]
+            .as_str()
+            .unwrap()
+            .starts_with("bafyrei"));
+        assert_eq!(value["data"]["$bytes"], "AQID");
+    }
+}
@@ -1,6 +1,7 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
 use anyhow::Result;
+use p256::SecretKey;
 use reqwest_chain::ChainMiddleware;
 use reqwest_middleware::ClientBuilder;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -9,14 +10,18 @@ use tracing::Instrument;
 // Standard timeout for all HTTP client operations
 const HTTP_CLIENT_TIMEOUT_SECS: u64 = 8;
 
+// Downloading a whole repository takes longer than other requests
+const REPO_DOWNLOAD_TIMEOUT_SECS: u64 = 60;
+
 use crate::atproto::auth::OAuthSessionProvider;
 use crate::atproto::errors::ClientError;
-use crate::atproto::lexicon::com::atproto::repo::StrongRef;
+use crate::atproto::lexicon::com::atproto::repo::{Blob, StrongRef};
 use crate::atproto::xrpc::SimpleError;
 use crate::http::handle_oauth_login::pkce_challenge;
 use crate::http::utils::URLBuilder;
 use crate::jose::jwt::{Claims, Header, JoseClaims};
 use crate::jose::mint_token;
+use crate::jose_errors::JoseError;
 use crate::oauth::dpop::DpopRetry;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -68,6 +73,103 @@ pub struct PutRecordRequest<T: DeserializeOwned> {
     pub swap_record: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteRecordRequest {
+    pub repo: String,
+    pub collection: String,
+
+    #[serde(rename = "rkey")]
+    pub record_key: String,
+
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        rename = "swapRecord"
+    )]
+    pub swap_record: Option<String>,
+
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        rename = "swapCommit"
+    )]
+    pub swap_commit: Option<String>,
+}
+
+/// One of the operations of an applyWrites request.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "$type", bound = "T: Serialize + DeserializeOwned")]
+pub enum ApplyWrite<T: DeserializeOwned> {
+    #[serde(rename = "com.atproto.repo.applyWrites#create")]
+    Create {
+        collection: String,
+
+        #[serde(skip_serializing_if = "Option::is_none", default, rename = "rkey")]
+        record_key: Option<String>,
+
+        value: T,
+    },
+
+    #[serde(rename = "com.atproto.repo.applyWrites#update")]
+    Update {
+        collection: String,
+
+        #[serde(rename = "rkey")]
+        record_key: String,
+
+        value: T,
+    },
+
+    #[serde(rename = "com.atproto.repo.applyWrites#delete")]
+    Delete {
+        collection: String,
+
+        #[serde(rename = "rkey")]
+        record_key: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+pub struct ApplyWritesRequest<T: DeserializeOwned> {
+    pub repo: String,
+
+    pub validate: bool,
+
+    pub writes: Vec<ApplyWrite<T>>,
+
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        rename = "swapCommit"
+    )]
+    pub swap_commit: Option<String>,
+}
+
+/// The most writes a PDS accepts in one applyWrites request.
+pub const MAX_APPLY_WRITES: usize = 200;
+
+/// The outcome of one write of an applyWrites request, in the order of the
+/// writes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "$type")]
+pub enum ApplyWriteResult {
+    #[serde(rename = "com.atproto.repo.applyWrites#createResult")]
+    Create { uri: String, cid: String },
+
+    #[serde(rename = "com.atproto.repo.applyWrites#updateResult")]
+    Update { uri: String, cid: String },
+
+    #[serde(rename = "com.atproto.repo.applyWrites#deleteResult")]
+    Delete {},
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplyWritesOutput {
+    #[serde(default)]
+    pub results: Vec<ApplyWriteResult>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum CreateRecordResponse {
@@ -82,6 +184,18 @@ pub enum PutRecordResponse {
     Error(SimpleError),
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploadBlobOutput {
+    pub blob: Blob,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum UploadBlobResponse {
+    Output(UploadBlobOutput),
+    Error(SimpleError),
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ListRecordsParams {
     pub repo: String,
@@ -107,62 +221,196 @@ pub struct ListRecordsResponse<T> {
     pub records: Vec<ListRecord<T>>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateSessionRequest {
+    pub identifier: String,
+    pub password: String,
+}
+
+/// The tokens returned by `com.atproto.server.createSession` and
+/// `com.atproto.server.refreshSession`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub access_jwt: String,
+    pub refresh_jwt: String,
+    pub handle: String,
+    pub did: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SessionResponse {
+    Session(Session),
+    Error(SimpleError),
+}
+
+/// The header that asks a PDS to forward a request to another service, such
+/// as an AppView or labeler, on behalf of the user.
+pub const ATPROTO_PROXY_HEADER: &str = "atproto-proxy";
+
+/// A service that a PDS forwards requests to, named by the DID of the service
+/// and the id of an entry in the services of its DID document. It is written
+/// as `did:web:api.bsky.app#bsky_appview`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceProxy {
+    pub did: String,
+    pub service_id: String,
+}
+
+impl ServiceProxy {
+    pub fn new(did: &str, service_id: &str) -> Self {
+        Self {
+            did: did.to_string(),
+            service_id: service_id.trim_start_matches('#').to_string(),
+        }
+    }
+
+    /// The value of the `atproto-proxy` header for the service.
+    pub fn header_value(&self) -> String {
+        format!("{}#{}", self.did, self.service_id)
+    }
+}
+
+impl FromStr for ServiceProxy {
+    type Err = ClientError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once('#') {
+            Some((did, service_id))
+                if did.starts_with("did:")
+                    && !service_id.is_empty()
+                    && !service_id.contains('#') =>
+            {
+                Ok(Self::new(did, service_id))
+            }
+            _ => Err(ClientError::InvalidServiceProxy(value.to_string())),
+        }
+    }
+}
+
 pub struct OAuthPdsClient<'a> {
     pub http_client: &'a reqwest::Client,
     pub pds: &'a str,
 }
 
-impl OAuthPdsClient<'_> {
-    pub async fn create_record<T: DeserializeOwned + Serialize>(
-        &self,
-        oauth_session: &impl OAuthSessionProvider,
-        record: CreateRecordRequest<T>,
-    ) -> Result<StrongRef, anyhow::Error> {
-        let mut url_builder = URLBuilder::new(self.pds);
-        url_builder.path("/xrpc/com.atproto.repo.createRecord");
-        let url = url_builder.build();
+/// Builds requests that prove possession of a DPoP key. Each request gets a
+/// proof for its method and URL, and is retried with the nonce the server
+/// asks for. Requests to a PDS also send the DPoP bound access token, which
+/// each proof is tied to.
+pub struct DpopRequestBuilder<'a> {
+    http_client: &'a reqwest::Client,
+    dpop_secret_key: &'a SecretKey,
+    issuer: Option<String>,
+    access_token: Option<String>,
+}
 
-        let dpop_secret_key = oauth_session.dpop_secret();
-        let dpop_public_key = dpop_secret_key.public_key();
-        let oauth_issuer = oauth_session.oauth_issuer();
-        let oauth_access_token = oauth_session.oauth_access_token();
+impl<'a> DpopRequestBuilder<'a> {
+    pub fn new(http_client: &'a reqwest::Client, dpop_secret_key: &'a SecretKey) -> Self {
+        Self {
+            http_client,
+            dpop_secret_key,
+            issuer: None,
+            access_token: None,
+        }
+    }
+
+    /// Sets the issuer of the session, which proofs sent to a PDS include.
+    pub fn issuer(mut self, issuer: String) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    /// Sends an access token with each request.
+    pub fn access_token(mut self, access_token: String) -> Self {
+        self.access_token = Some(access_token);
+        self
+    }
 
+    pub fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest_middleware::RequestBuilder, JoseError> {
         let now = chrono::Utc::now();
 
         let dpop_proof_header = Header {
             type_: Some("dpop+jwt".to_string()),
             algorithm: Some("ES256".to_string()),
-            json_web_key: Some(dpop_public_key.to_jwk()),
+            json_web_key: Some(self.dpop_secret_key.public_key().to_jwk()),
             ..Default::default()
         };
 
         let dpop_proof_claim = Claims::new(JoseClaims {
-            issuer: Some(oauth_issuer.clone()),
+            issuer: self.issuer.clone(),
             issued_at: Some(now.timestamp() as u64),
             expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
             json_web_token_id: Some(ulid::Ulid::new().to_string()),
-            http_method: Some("POST".to_string()),
-            http_uri: Some(url.clone()),
-            auth: Some(pkce_challenge(&oauth_access_token)),
-
+            http_method: Some(method.to_string()),
+            http_uri: Some(url.to_string()),
+            auth: self.access_token.as_deref().map(pkce_challenge),
             ..Default::default()
         });
-        let dpop_proof_token = mint_token(&dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)?;
+        let dpop_proof_token =
+            mint_token(self.dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)?;
 
         let dpop_retry = DpopRetry::new(
-            dpop_proof_header.clone(),
-            dpop_proof_claim.clone(),
-            dpop_secret_key.clone(),
+            dpop_proof_header,
+            dpop_proof_claim,
+            self.dpop_secret_key.clone(),
         );
 
         let dpop_retry_client = ClientBuilder::new(self.http_client.clone())
-            .with(ChainMiddleware::new(dpop_retry.clone()))
+            .with(ChainMiddleware::new(dpop_retry))
             .build();
 
-        let http_response = dpop_retry_client
-            .post(url)
-            .header("Authorization", &format!("DPoP {}", oauth_access_token))
-            .header("DPoP", dpop_proof_token.as_str())
+        let request = dpop_retry_client
+            .request(method, url)
+            .header("DPoP", dpop_proof_token.as_str());
+
+        Ok(match &self.access_token {
+            Some(access_token) => request.header("Authorization", format!("DPoP {}", access_token)),
+            None => request,
+        })
+    }
+}
+
+impl OAuthPdsClient<'_> {
+    /// Starts a request authorized by a session. OAuth sessions send a DPoP
+    /// bound access token and retry with the server provided nonce, while
+    /// app password sessions send a bearer token.
+    fn authorized_request(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest_middleware::RequestBuilder, anyhow::Error> {
+        let oauth_access_token = oauth_session.oauth_access_token();
+
+        let Some(dpop_secret_key) = oauth_session.dpop_secret() else {
+            return Ok(ClientBuilder::new(self.http_client.clone())
+                .build()
+                .request(method, url)
+                .header("Authorization", &format!("Bearer {}", oauth_access_token)));
+        };
+
+        Ok(DpopRequestBuilder::new(self.http_client, &dpop_secret_key)
+            .issuer(oauth_session.oauth_issuer())
+            .access_token(oauth_access_token)
+            .request(method, url)?)
+    }
+
+    pub async fn create_record<T: DeserializeOwned + Serialize>(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        record: CreateRecordRequest<T>,
+    ) -> Result<StrongRef, anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path("/xrpc/com.atproto.repo.createRecord");
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
             .json(&record)
             .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
             .send()
@@ -187,54 +435,15 @@ impl OAuthPdsClient<'_> {
 
     pub async fn put_record<T: DeserializeOwned + Serialize>(
         &self,
-        oauth_session: &impl OAuthSessionProvider,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
         record: PutRecordRequest<T>,
     ) -> Result<StrongRef, anyhow::Error> {
         let mut url_builder = URLBuilder::new(self.pds);
         url_builder.path("/xrpc/com.atproto.repo.putRecord");
         let url = url_builder.build();
 
-        let dpop_secret_key = oauth_session.dpop_secret();
-        let dpop_public_key = dpop_secret_key.public_key();
-        let oauth_issuer = oauth_session.oauth_issuer();
-        let oauth_access_token = oauth_session.oauth_access_token();
-
-        let now = chrono::Utc::now();
-
-        let dpop_proof_header = Header {
-            type_: Some("dpop+jwt".to_string()),
-            algorithm: Some("ES256".to_string()),
-            json_web_key: Some(dpop_public_key.to_jwk()),
-            ..Default::default()
-        };
-
-        let dpop_proof_claim = Claims::new(JoseClaims {
-            issuer: Some(oauth_issuer.clone()),
-            issued_at: Some(now.timestamp() as u64),
-            expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
-            json_web_token_id: Some(ulid::Ulid::new().to_string()),
-            http_method: Some("POST".to_string()),
-            http_uri: Some(url.clone()),
-            auth: Some(pkce_challenge(&oauth_access_token)),
-
-            ..Default::default()
-        });
-        let dpop_proof_token = mint_token(&dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)?;
-
-        let dpop_retry = DpopRetry::new(
-            dpop_proof_header.clone(),
-            dpop_proof_claim.clone(),
-            dpop_secret_key.clone(),
-        );
-
-        let dpop_retry_client = ClientBuilder::new(self.http_client.clone())
-            .with(ChainMiddleware::new(dpop_retry.clone()))
-            .build();
-
-        let http_response = dpop_retry_client
-            .post(url)
-            .header("Authorization", &format!("DPoP {}", oauth_access_token))
-            .header("DPoP", dpop_proof_token.as_str())
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
             .json(&record)
             .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
             .send()
@@ -247,6 +456,10 @@ impl OAuthPdsClient<'_> {
 
         match put_record_respoonse {
             Ok(PutRecordResponse::StrongRef(strong_ref)) => Ok(strong_ref),
+            // The swap record didn't match the current record
+            Ok(PutRecordResponse::Error(err)) if err.error.as_deref() == Some("InvalidSwap") => {
+                Err(ClientError::InvalidSwap.into())
+            }
             Ok(PutRecordResponse::Error(err)) => {
                 Err(ClientError::ServerError(err.error_message()).into())
             }
@@ -254,9 +467,168 @@ impl OAuthPdsClient<'_> {
         }
     }
 
+    pub async fn delete_record(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        record: DeleteRecordRequest,
+    ) -> Result<(), anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path("/xrpc/com.atproto.repo.deleteRecord");
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
+            .json(&record)
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::info_span!("delete_record"))
+            .await?;
+
+        tracing::info!(
+            "delete_record response status: {:?}",
+            http_response.status()
+        );
+
+        // A successful delete returns an optional commit reference that is
+        // not needed by callers.
+        if http_response.status().is_success() {
+            return Ok(());
+        }
+
+        let err = http_response
+            .json::<SimpleError>()
+            .await
+            .map_err(ClientError::DeleteRecordResponseFailure)?;
+
+        Err(ClientError::ServerError(err.error_message()).into())
+    }
+
+    /// Creates, updates and deletes records of the repository of the session
+    /// in a single commit. Either every write is applied or none are. Returns
+    /// the outcome of each write, in the order of the writes.
+    pub async fn apply_writes<T: DeserializeOwned + Serialize>(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        request: ApplyWritesRequest<T>,
+    ) -> Result<Vec<ApplyWriteResult>, anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path("/xrpc/com.atproto.repo.applyWrites");
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
+            .json(&request)
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::info_span!("apply_writes"))
+            .await?;
+
+        tracing::info!("apply_writes response status: {:?}", http_response.status());
+
+        if http_response.status().is_success() {
+            let output = http_response
+                .json::<ApplyWritesOutput>()
+                .await
+                .map_err(ClientError::ApplyWritesResponseFailure)?;
+            return Ok(output.results);
+        }
+
+        let err = http_response
+            .json::<SimpleError>()
+            .await
+            .map_err(ClientError::ApplyWritesResponseFailure)?;
+
+        Err(ClientError::ServerError(err.error_message()).into())
+    }
+
+    /// Uploads a blob to the repository of the session. The blob is removed
+    /// by the PDS unless a record that references it is created soon after.
+    pub async fn upload_blob(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        mime_type: &str,
+        content: Vec<u8>,
+    ) -> Result<Blob, anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path("/xrpc/com.atproto.repo.uploadBlob");
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
+            .header("Content-Type", mime_type)
+            .body(content)
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::info_span!("upload_blob"))
+            .await?;
+
+        tracing::info!("upload_blob response status: {:?}", http_response.status());
+
+        match http_response.json::<UploadBlobResponse>().await {
+            Ok(UploadBlobResponse::Output(output)) => Ok(output.blob),
+            Ok(UploadBlobResponse::Error(err)) => {
+                Err(ClientError::ServerError(err.error_message()).into())
+            }
+            Err(err) => Err(ClientError::UploadBlobResponseFailure(err).into()),
+        }
+    }
+
+    /// Calls a query of another service, which the PDS forwards with a
+    /// service token for the user. Each parameter may be given more than
+    /// once for array parameters.
+    pub async fn proxied_query<T: DeserializeOwned>(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        proxy: &ServiceProxy,
+        nsid: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path(&format!("/xrpc/{}", nsid));
+        for (key, value) in params {
+            url_builder.param(key, value);
+        }
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::GET, &url)?
+            .header(ATPROTO_PROXY_HEADER, proxy.header_value())
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::info_span!("proxied_query", nsid))
+            .await?;
+
+        proxied_response(http_response).await
+    }
+
+    /// Calls a procedure of another service, which the PDS forwards with a
+    /// service token for the user.
+    pub async fn proxied_procedure<I: Serialize, T: DeserializeOwned>(
+        &self,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
+        proxy: &ServiceProxy,
+        nsid: &str,
+        input: &I,
+    ) -> Result<T, anyhow::Error> {
+        let mut url_builder = URLBuilder::new(self.pds);
+        url_builder.path(&format!("/xrpc/{}", nsid));
+        let url = url_builder.build();
+
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::POST, &url)?
+            .header(ATPROTO_PROXY_HEADER, proxy.header_value())
+            .json(input)
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::info_span!("proxied_procedure", nsid))
+            .await?;
+
+        proxied_response(http_response).await
+    }
+
     pub async fn list_records<T: DeserializeOwned>(
         &self,
-        oauth_session: &impl OAuthSessionProvider,
+        oauth_session: &(impl OAuthSessionProvider + ?Sized),
         params: &ListRecordsParams,
     ) -> Result<ListRecordsResponse<T>, anyhow::Error> {
         let mut url_builder = URLBuilder::new(self.pds);
@@ -280,56 +652,157 @@ impl OAuthPdsClient<'_> {
 
         let url = url_builder.build();
 
-        let dpop_secret_key = oauth_session.dpop_secret();
-        let dpop_public_key = dpop_secret_key.public_key();
-        let oauth_issuer = oauth_session.oauth_issuer();
-        let oauth_access_token = oauth_session.oauth_access_token();
+        let http_response = self
+            .authorized_request(oauth_session, reqwest::Method::GET, &url)?
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .send()
+            .instrument(tracing::span!(tracing::Level::INFO, "list_records"))
+            .await?;
 
-        let now = chrono::Utc::now();
+        let result = http_response.json::<ListRecordsResponse<T>>().await?;
 
-        let dpop_proof_header = Header {
-            type_: Some("dpop+jwt".to_string()),
-            algorithm: Some("ES256".to_string()),
-            json_web_key: Some(dpop_public_key.to_jwk()),
-            ..Default::default()
-        };
+        Ok(result)
+    }
+}
 
-        let dpop_proof_claim = Claims::new(JoseClaims {
-            issuer: Some(oauth_issuer.clone()),
-            issued_at: Some(now.timestamp() as u64),
-            expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
-            json_web_token_id: Some(ulid::Ulid::new().to_string()),
-            http_method: Some("GET".to_string()),
-            http_uri: Some(url.clone()),
-            auth: Some(pkce_challenge(&oauth_access_token)),
+/// Lists the records of a collection in a repository. Repositories are
+/// public, so this doesn't need a session on the PDS.
+pub async fn list_public_records<T: DeserializeOwned>(
+    http_client: &reqwest::Client,
+    pds: &str,
+    params: &ListRecordsParams,
+) -> Result<ListRecordsResponse<T>, anyhow::Error> {
+    let mut url_builder = URLBuilder::new(pds);
+    url_builder.path("/xrpc/com.atproto.repo.listRecords");
+    url_builder.param("repo", &params.repo);
+    url_builder.param("collection", &params.collection);
+
+    if let Some(limit) = params.limit {
+        url_builder.param("limit", &limit.to_string());
+    }
 
-            ..Default::default()
-        });
-        let dpop_proof_token = mint_token(&dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)?;
+    if let Some(cursor) = &params.cursor {
+        url_builder.param("cursor", cursor);
+    }
 
-        let dpop_retry = DpopRetry::new(
-            dpop_proof_header.clone(),
-            dpop_proof_claim.clone(),
-            dpop_secret_key.clone(),
-        );
+    let url = url_builder.build();
 
-        let dpop_retry_client = ClientBuilder::new(self.http_client.clone())
-            .with(ChainMiddleware::new(dpop_retry.clone()))
-            .build();
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("list_public_records"))
+        .await?
+        .error_for_status()?;
 
-        let http_response = dpop_retry_client
-            .get(url)
-            .header("Authorization", &format!("DPoP {}", oauth_access_token))
-            .header("DPoP", dpop_proof_token.as_str())
-            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
-            .send()
-            .instrument(tracing::span!(tracing::Level::INFO, "list_records"))
-            .await?;
+    let result = http_response.json::<ListRecordsResponse<T>>().await?;
 
-        let result = http_response.json::<ListRecordsResponse<T>>().await?;
+    Ok(result)
+}
 
-        Ok(result)
+/// Downloads the repository of a DID as a CAR file. Repositories are public,
+/// so this doesn't need a session on the PDS. The download is stopped once it
+/// is larger than `max_bytes`.
+pub async fn get_repo(
+    http_client: &reqwest::Client,
+    pds: &str,
+    did: &str,
+    max_bytes: usize,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let mut url_builder = URLBuilder::new(pds);
+    url_builder.path("/xrpc/com.atproto.sync.getRepo");
+    url_builder.param("did", did);
+    let url = url_builder.build();
+
+    let mut http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(REPO_DOWNLOAD_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("get_repo"))
+        .await?
+        .error_for_status()?;
+
+    let mut content = vec![];
+    while let Some(chunk) = http_response.chunk().await? {
+        if content.len() + chunk.len() > max_bytes {
+            return Err(ClientError::RepositoryTooLarge(max_bytes).into());
+        }
+        content.extend_from_slice(&chunk);
     }
+
+    Ok(content)
+}
+
+/// Creates a session on a PDS with an app password.
+pub async fn create_session(
+    http_client: &reqwest::Client,
+    pds: &str,
+    request: &CreateSessionRequest,
+) -> Result<Session, anyhow::Error> {
+    let mut url_builder = URLBuilder::new(pds);
+    url_builder.path("/xrpc/com.atproto.server.createSession");
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .post(url)
+        .json(request)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("create_session"))
+        .await?;
+
+    session_response(http_response).await
+}
+
+/// Exchanges the refresh token of an app password session for new tokens.
+pub async fn refresh_session(
+    http_client: &reqwest::Client,
+    pds: &str,
+    refresh_jwt: &str,
+) -> Result<Session, anyhow::Error> {
+    let mut url_builder = URLBuilder::new(pds);
+    url_builder.path("/xrpc/com.atproto.server.refreshSession");
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .post(url)
+        .header("Authorization", &format!("Bearer {}", refresh_jwt))
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("refresh_session"))
+        .await?;
+
+    session_response(http_response).await
+}
+
+async fn session_response(http_response: reqwest::Response) -> Result<Session, anyhow::Error> {
+    match http_response.json::<SessionResponse>().await {
+        Ok(SessionResponse::Session(session)) => Ok(session),
+        Ok(SessionResponse::Error(err)) => {
+            Err(ClientError::ServerError(err.error_message()).into())
+        }
+        Err(err) => Err(ClientError::SessionResponseFailure(err).into()),
+    }
+}
+
+async fn proxied_response<T: DeserializeOwned>(
+    http_response: reqwest::Response,
+) -> Result<T, anyhow::Error> {
+    tracing::info!("proxied response status: {:?}", http_response.status());
+
+    if http_response.status().is_success() {
+        return Ok(http_response
+            .json::<T>()
+            .await
+            .map_err(ClientError::ServiceProxyResponseFailure)?);
+    }
+
+    let err = http_response
+        .json::<SimpleError>()
+        .await
+        .map_err(ClientError::ServiceProxyResponseFailure)?;
+
+    Err(ClientError::ServerError(err.error_message()).into())
 }
 
 #[cfg(test)]
@@ -374,4 +847,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn apply_writes_request() -> Result<()> {
+        let request: ApplyWritesRequest<serde_json::Value> = ApplyWritesRequest {
+            repo: "nick".to_string(),
+            validate: false,
+            writes: vec![
+                ApplyWrite::Create {
+                    collection: "stuff".to_string(),
+                    record_key: None,
+                    value: serde_json::json!({"name": "a"}),
+                },
+                ApplyWrite::Delete {
+                    collection: "stuff".to_string(),
+                    record_key: "3jzfcijpj2z2a".to_string(),
+                },
+            ],
+            swap_commit: None,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&request)?,
+            r#"{"repo":"nick","validate":false,"writes":[{"$type":"com.atproto.repo.applyWrites#create","collection":"stuff","value":{"name":"a"}},{"$type":"com.atproto.repo.applyWrites#delete","collection":"stuff","rkey":"3jzfcijpj2z2a"}]}"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn service_proxy() -> Result<()> {
+        let proxy: ServiceProxy = "did:web:api.bsky.app#bsky_appview".parse()?;
+        assert_eq!(
+            proxy,
+            ServiceProxy::new("did:web:api.bsky.app", "#bsky_appview")
+        );
+        assert_eq!(proxy.header_value(), "did:web:api.bsky.app#bsky_appview");
+
+        assert!("did:web:api.bsky.app".parse::<ServiceProxy>().is_err());
+        assert!("did:web:api.bsky.app#".parse::<ServiceProxy>().is_err());
+        assert!("api.bsky.app#bsky_appview".parse::<ServiceProxy>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply_writes_output() -> Result<()> {
+        let output: ApplyWritesOutput = serde_json::from_str(
+            r#"{"commit":{"cid":"bafyreib","rev":"3l"},"results":[{"$type":"com.atproto.repo.applyWrites#createResult","uri":"at://did:plc:a/stuff/3l","cid":"bafyreic","validationStatus":"unknown"},{"$type":"com.atproto.repo.applyWrites#deleteResult"}]}"#,
+        )?;
+
+        assert_eq!(output.results.len(), 2);
+        assert!(
+            matches!(&output.results[0], ApplyWriteResult::Create { uri, cid } if uri == "at://did:plc:a/stuff/3l" && cid == "bafyreic")
+        );
+        assert!(matches!(output.results[1], ApplyWriteResult::Delete {}));
+
+        Ok(())
+    }
 }
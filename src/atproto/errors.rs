@@ -13,6 +13,63 @@ pub enum ClientError {
 
     #[error("error-xrpc-client-4 Invalid record format: {0}")]
     InvalidRecordFormat(String),
+
+    #[error("error-xrpc-client-5 Malformed DeleteRecord response: {0:?}")]
+    DeleteRecordResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-6 Malformed CreateSession or RefreshSession response: {0:?}")]
+    SessionResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-7 Malformed UploadBlob response: {0:?}")]
+    UploadBlobResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-8 Malformed AppView response: {0:?}")]
+    AppViewResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-9 Malformed ApplyWrites response: {0:?}")]
+    ApplyWritesResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-10 Repository is larger than {0} bytes")]
+    RepositoryTooLarge(usize),
+
+    #[error("error-xrpc-client-11 Record was changed since it was loaded")]
+    InvalidSwap,
+
+    #[error("error-xrpc-client-12 Invalid service proxy, expected a DID and service id: {0}")]
+    InvalidServiceProxy(String),
+
+    #[error("error-xrpc-client-13 Malformed proxied service response: {0:?}")]
+    ServiceProxyResponseFailure(reqwest::Error),
+
+    #[error("error-xrpc-client-14 Malformed labeler response: {0:?}")]
+    LabelerResponseFailure(reqwest::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum CarError {
+    #[error("error-car-1 Repository file ends in the middle of a block")]
+    Truncated,
+
+    #[error("error-car-2 Repository file has an invalid length prefix")]
+    InvalidVarint,
+
+    #[error("error-car-3 Repository file has no root commit")]
+    MissingRoot,
+
+    #[error("error-car-4 Repository file uses an unsupported CID: {0}")]
+    UnsupportedCid(String),
+
+    #[error("error-car-5 Repository block does not match its CID: {0}")]
+    BlockHashMismatch(String),
+
+    #[error("error-car-6 Repository block is missing: {0}")]
+    MissingBlock(String),
+
+    #[error("error-car-7 Repository block is invalid: {0}")]
+    InvalidBlock(String),
+
+    #[error("error-car-8 Repository belongs to {0}, not {1}")]
+    RepositoryMismatch(String, String),
 }
 
 #[derive(Debug, Error)]
@@ -50,3 +107,30 @@ pub enum UriError {
     #[error("error-uri-11 Invalid AT-URI: rkey too long (max 512 chars)")]
     RkeyTooLong,
 }
+
+#[derive(Debug, Error)]
+pub enum RecordLimitError {
+    #[error("error-record-limit-1 Name is longer than {0} bytes")]
+    NameTooLong(usize),
+
+    #[error("error-record-limit-2 Description is longer than {0} bytes")]
+    DescriptionTooLong(usize),
+
+    #[error("error-record-limit-3 Events can have at most {0} locations")]
+    TooManyLocations(usize),
+
+    #[error("error-record-limit-4 Location {0} has a value longer than {1} bytes")]
+    LocationTooLong(usize, usize),
+
+    #[error("error-record-limit-5 Events can have at most {0} links")]
+    TooManyLinks(usize),
+
+    #[error("error-record-limit-6 Link {0} has a value longer than {1} bytes")]
+    LinkTooLong(usize, usize),
+
+    #[error("error-record-limit-7 Record is {0} bytes, larger than the {1} byte limit")]
+    RecordTooLarge(usize, usize),
+
+    #[error("error-record-limit-8 Record could not be serialized: {0}")]
+    SerializationFailed(serde_json::Error),
+}
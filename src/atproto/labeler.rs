@@ -0,0 +1,152 @@
+//! Queries of a labeler, a moderation service that publishes labels such as
+//! `spam` or `porn` for records and accounts.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::Instrument;
+
+use crate::atproto::errors::ClientError;
+use crate::atproto::xrpc::SimpleError;
+use crate::http::utils::URLBuilder;
+
+const HTTP_CLIENT_TIMEOUT_SECS: u64 = 8;
+
+/// The most labels `com.atproto.label.queryLabels` returns in one request.
+pub const MAX_LABELS_PER_REQUEST: u32 = 250;
+
+/// A label applied by a labeler to a record, by its AT-URI, or to an
+/// account, by its DID. A label with `neg` set removes an earlier label with
+/// the same source and value.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Label {
+    pub src: String,
+    pub uri: String,
+    pub val: String,
+    #[serde(default)]
+    pub neg: bool,
+    pub cts: DateTime<Utc>,
+    #[serde(default)]
+    pub exp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct QueryLabelsOutput {
+    pub cursor: Option<String>,
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+enum LabelerResponse {
+    Output(QueryLabelsOutput),
+    Error(SimpleError),
+}
+
+/// Lists the labels a labeler applied to subjects, a page at a time. Each
+/// pattern is an AT-URI or DID, or a prefix of one ending in `*`.
+pub async fn query_labels(
+    http_client: &reqwest::Client,
+    labeler_hostname: &str,
+    uri_patterns: &[String],
+    cursor: Option<&str>,
+) -> Result<QueryLabelsOutput> {
+    let mut url_builder = URLBuilder::new(labeler_hostname);
+    url_builder.path("/xrpc/com.atproto.label.queryLabels");
+    for uri_pattern in uri_patterns {
+        url_builder.param("uriPatterns", uri_pattern);
+    }
+    url_builder.param("limit", &MAX_LABELS_PER_REQUEST.to_string());
+    if let Some(cursor) = cursor {
+        url_builder.param("cursor", cursor);
+    }
+    let url = url_builder.build();
+
+    let http_response = http_client
+        .get(url)
+        .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+        .send()
+        .instrument(tracing::info_span!("query_labels"))
+        .await?;
+
+    match http_response.json::<LabelerResponse>().await {
+        Ok(LabelerResponse::Output(output)) => Ok(output),
+        Ok(LabelerResponse::Error(err)) => {
+            Err(ClientError::ServerError(err.error_message()).into())
+        }
+        Err(err) => Err(ClientError::LabelerResponseFailure(err).into()),
+    }
+}
+
+/// The labels that are in effect at a time. Labels are applied in the order
+/// they were created, so a negation removes the labels before it, and
+/// expired labels are left out.
+pub fn active_labels(mut labels: Vec<Label>, now: DateTime<Utc>) -> Vec<Label> {
+    labels.sort_by_key(|label| label.cts);
+
+    let mut active: HashMap<(String, String, String), Label> = HashMap::new();
+    for label in labels {
+        let key = (label.uri.clone(), label.src.clone(), label.val.clone());
+        if label.neg {
+            active.remove(&key);
+        } else {
+            active.insert(key, label);
+        }
+    }
+
+    let mut active = active
+        .into_values()
+        .filter(|label| label.exp.is_none_or(|exp| exp > now))
+        .collect::<Vec<_>>();
+    active.sort_by(|a, b| (&a.uri, &a.val, &a.src).cmp(&(&b.uri, &b.val, &b.src)));
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(uri: &str, val: &str, neg: bool, cts: &str) -> Label {
+        Label {
+            src: "did:plc:labeler".to_string(),
+            uri: uri.to_string(),
+            val: val.to_string(),
+            neg,
+            cts: cts.parse().unwrap(),
+            exp: None,
+        }
+    }
+
+    #[test]
+    fn test_active_labels() {
+        let now = "2025-06-01T00:00:00Z".parse().unwrap();
+        let event = "at://did:plc:a/community.lexicon.calendar.event/3l";
+
+        let mut expired = label(event, "porn", false, "2025-05-01T00:00:00Z");
+        expired.exp = Some("2025-05-02T00:00:00Z".parse().unwrap());
+
+        let labels = vec![
+            // Negated after it was applied
+            label(event, "spam", true, "2025-05-03T00:00:00Z"),
+            label(event, "spam", false, "2025-05-02T00:00:00Z"),
+            expired,
+            label("did:plc:a", "spam", false, "2025-05-01T00:00:00Z"),
+            label(event, "nudity", false, "2025-05-01T00:00:00Z"),
+        ];
+
+        let active = active_labels(labels, now)
+            .into_iter()
+            .map(|label| (label.uri, label.val))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            active,
+            vec![
+                (event.to_string(), "nudity".to_string()),
+                ("did:plc:a".to_string(), "spam".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atproto::datetime::format as datetime_format;
+
+pub const NSID: &str = "app.bsky.feed.post";
+
+/// A Bluesky post. Only the parts used to announce events are modeled.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type")]
+pub enum Post {
+    #[serde(rename = "app.bsky.feed.post")]
+    Current {
+        text: String,
+
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        facets: Vec<Facet>,
+
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        langs: Vec<String>,
+
+        #[serde(rename = "createdAt", with = "datetime_format")]
+        created_at: DateTime<Utc>,
+    },
+}
+
+/// Rich text annotation of a range of the post text.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Facet {
+    pub index: ByteSlice,
+    pub features: Vec<FacetFeature>,
+}
+
+/// A range of the post text, in UTF-8 bytes. The end is exclusive.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ByteSlice {
+    #[serde(rename = "byteStart")]
+    pub byte_start: usize,
+
+    #[serde(rename = "byteEnd")]
+    pub byte_end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type")]
+pub enum FacetFeature {
+    #[serde(rename = "app.bsky.richtext.facet#mention")]
+    Mention { did: String },
+
+    #[serde(rename = "app.bsky.richtext.facet#link")]
+    Link { uri: String },
+}
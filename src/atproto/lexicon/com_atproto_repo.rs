@@ -5,3 +5,48 @@ pub struct StrongRef {
     pub uri: String,
     pub cid: String,
 }
+
+/// A reference to a blob in a repository, as returned by
+/// `com.atproto.repo.uploadBlob` and embedded in records that use it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type", rename = "blob")]
+pub struct Blob {
+    #[serde(rename = "ref")]
+    pub reference: BlobLink,
+
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct BlobLink {
+    #[serde(rename = "$link")]
+    pub link: String,
+}
+
+impl Blob {
+    /// The CID of the blob content.
+    pub fn cid(&self) -> &str {
+        &self.reference.link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_record() {
+        let test_json = r#"{"$type":"blob","ref":{"$link":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"mimeType":"image/png","size":1024}"#;
+
+        let blob: Blob = serde_json::from_str(test_json).unwrap();
+        assert_eq!(
+            blob.cid(),
+            "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+        );
+        assert_eq!(blob.mime_type, "image/png");
+        assert_eq!(serde_json::to_string(&blob).unwrap(), test_json);
+    }
+}
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::atproto::lexicon::com::atproto::repo::Blob;
 use crate::atproto::lexicon::community::lexicon::location::{Address, Fsq, Geo, Hthree};
 use crate::atproto::{
     datetime::format as datetime_format, datetime::optional_format as optional_datetime_format,
@@ -83,6 +84,39 @@ pub enum EventLink {
 
 pub type EventLinks = Vec<EventLink>;
 
+/// The role of an image shown at the top of the event page.
+pub const MEDIA_ROLE_HEADER: &str = "header";
+
+/// The field of an event record, outside of the lexicon, with the IANA time
+/// zone the organizer picked for the event's times.
+pub const EXTRA_TIMEZONE: &str = "timezone";
+
+/// The field of an event record, outside of the lexicon, with when doors
+/// open or arrivals start, before the event starts.
+pub const EXTRA_DOORS_OPEN_AT: &str = "doorsOpenAt";
+
+/// The field of an event record, outside of the lexicon, with the mentions
+/// and links in the description as Bluesky rich text facets.
+pub const EXTRA_DESCRIPTION_FACETS: &str = "descriptionFacets";
+
+/// The field of an event record, outside of the lexicon, with the topics of
+/// the event, like the tags of a Bluesky post.
+pub const EXTRA_TAGS: &str = "tags";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type")]
+pub enum EventMedia {
+    #[serde(rename = "community.lexicon.calendar.event#media")]
+    Current {
+        role: String,
+
+        content: Blob,
+
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        alt: Option<String>,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(tag = "$type")]
 pub enum Event {
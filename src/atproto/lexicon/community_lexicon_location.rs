@@ -56,6 +56,32 @@ pub enum Fsq {
     },
 }
 
+impl Geo {
+    /// Returns the latitude and longitude as numbers, or `None` when either
+    /// is not a number or is out of range.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        let Geo::Current {
+            latitude,
+            longitude,
+            ..
+        } = self;
+
+        let latitude = latitude.trim().parse::<f64>().ok()?;
+        let longitude = longitude.trim().parse::<f64>().ok()?;
+
+        ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+            .then_some((latitude, longitude))
+    }
+}
+
+impl Fsq {
+    /// Returns the Foursquare page for the place.
+    pub fn place_url(&self) -> String {
+        let Fsq::Current { fsq_place_id, .. } = self;
+        format!("https://foursquare.com/v/{}", fsq_place_id)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(tag = "$type")]
 pub enum Hthree {
@@ -67,3 +93,50 @@ pub enum Hthree {
         name: Option<String>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geo_record() {
+        let test_json = r#"{"$type":"community.lexicon.location.geo","latitude":"49.2796","longitude":"-123.1157","name":"Central Library"}"#;
+
+        let geo: Geo = serde_json::from_str(test_json).unwrap();
+        assert_eq!(
+            geo,
+            Geo::Current {
+                latitude: "49.2796".to_string(),
+                longitude: "-123.1157".to_string(),
+                name: Some("Central Library".to_string()),
+            }
+        );
+        assert_eq!(serde_json::to_string(&geo).unwrap(), test_json);
+        assert_eq!(geo.coordinates(), Some((49.2796, -123.1157)));
+    }
+
+    #[test]
+    fn geo_coordinates_invalid() {
+        let geo = |latitude: &str, longitude: &str| Geo::Current {
+            latitude: latitude.to_string(),
+            longitude: longitude.to_string(),
+            name: None,
+        };
+
+        assert_eq!(geo("north", "0").coordinates(), None);
+        assert_eq!(geo("90.5", "0").coordinates(), None);
+        assert_eq!(geo("0", "-181").coordinates(), None);
+    }
+
+    #[test]
+    fn fsq_record() {
+        let test_json = r#"{"$type":"community.lexicon.location.fsq","fsq_place_id":"4b9d1c8af964a520bb9236e3"}"#;
+
+        let fsq: Fsq = serde_json::from_str(test_json).unwrap();
+        assert_eq!(serde_json::to_string(&fsq).unwrap(), test_json);
+        assert_eq!(
+            fsq.place_url(),
+            "https://foursquare.com/v/4b9d1c8af964a520bb9236e3"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atproto::datetime::format as datetime_format;
+use crate::atproto::lexicon::com::atproto::repo::StrongRef;
+
+pub const NSID: &str = "events.smokesignal.calendar";
+
+/// A series of events kept by an organizer, such as a monthly meetup. The
+/// record refers to the events of the series, which can be in any
+/// repository.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type")]
+pub enum Calendar {
+    #[serde(rename = "events.smokesignal.calendar")]
+    Current {
+        name: String,
+
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        description: Option<String>,
+
+        #[serde(default)]
+        events: Vec<StrongRef>,
+
+        #[serde(rename = "createdAt", with = "datetime_format")]
+        created_at: DateTime<Utc>,
+    },
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atproto::datetime::format as datetime_format;
+use crate::atproto::lexicon::com::atproto::repo::Blob;
+
+pub const NSID: &str = "events.smokesignal.media";
+
+/// An image in an organizer's media library. The record keeps the uploaded
+/// blob in the organizer's repository so that it can be used by any number
+/// of events.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "$type")]
+pub enum Media {
+    #[serde(rename = "events.smokesignal.media")]
+    Current {
+        name: String,
+
+        image: Blob,
+
+        #[serde(rename = "createdAt", with = "datetime_format")]
+        created_at: DateTime<Utc>,
+    },
+}
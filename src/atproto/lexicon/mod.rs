@@ -1,9 +1,22 @@
+mod app_bsky_feed_post;
 mod com_atproto_repo;
 mod community_lexicon_calendar_event;
 mod community_lexicon_calendar_rsvp;
 mod community_lexicon_location;
+mod events_smokesignal_calendar;
 mod events_smokesignal_calendar_event;
 mod events_smokesignal_calendar_rsvp;
+mod events_smokesignal_media;
+
+pub mod app {
+    pub mod bsky {
+        pub mod feed {
+            pub mod post {
+                pub use crate::atproto::lexicon::app_bsky_feed_post::*;
+            }
+        }
+    }
+}
 
 pub mod com {
     pub mod atproto {
@@ -29,10 +42,13 @@ pub mod community {
     }
 }
 
-// events.smokesignal.calendar.event
+// events.smokesignal.calendar, events.smokesignal.calendar.event and
+// events.smokesignal.media
 pub mod events {
     pub mod smokesignal {
         pub mod calendar {
+            pub use crate::atproto::lexicon::events_smokesignal_calendar::*;
+
             pub mod event {
                 pub use crate::atproto::lexicon::events_smokesignal_calendar_event::*;
             }
@@ -40,5 +56,8 @@ pub mod events {
                 pub use crate::atproto::lexicon::events_smokesignal_calendar_rsvp::*;
             }
         }
+        pub mod media {
+            pub use crate::atproto::lexicon::events_smokesignal_media::*;
+        }
     }
 }
@@ -1,7 +1,12 @@
+pub mod appview;
 pub mod auth;
+pub mod car;
 pub mod client;
 pub mod datetime;
 pub mod errors;
+pub mod labeler;
 pub mod lexicon;
+pub mod record_limits;
+pub mod tid;
 pub mod uri;
 pub mod xrpc;
@@ -0,0 +1,178 @@
+//! Limits checked on composed records before they are written to a PDS.
+//!
+//! A PDS rejects records that are too large with a generic server error,
+//! after the user has already waited on the round trip. Checking records
+//! against the same limits first means the error can name the part of the
+//! record that needs to change.
+
+use serde_json::Value;
+
+use crate::atproto::{
+    errors::RecordLimitError, lexicon::community::lexicon::calendar::event::Event,
+};
+
+/// Maximum size of an event name, matching the event form.
+pub const MAX_NAME_BYTES: usize = 500;
+
+/// Maximum size of an event description, matching the event form.
+pub const MAX_DESCRIPTION_BYTES: usize = 3000;
+
+/// Maximum number of locations on an event.
+pub const MAX_LOCATIONS: usize = 20;
+
+/// Maximum number of links on an event.
+pub const MAX_LINKS: usize = 20;
+
+/// Maximum size of any single value within a location or link.
+pub const MAX_VALUE_BYTES: usize = 1000;
+
+/// Maximum size of a serialized record. The reference PDS rejects request
+/// bodies over 150 KiB, and the request wraps the record with the repo,
+/// collection and swap fields.
+pub const MAX_RECORD_BYTES: usize = 100 * 1024;
+
+/// Checks that an event record is within the limits a PDS accepts. Values
+/// the record carries over from other clients count towards the record size.
+pub fn check_event_record(record: &Event) -> Result<(), RecordLimitError> {
+    let Event::Current {
+        name,
+        description,
+        locations,
+        uris,
+        ..
+    } = record;
+
+    if name.len() > MAX_NAME_BYTES {
+        return Err(RecordLimitError::NameTooLong(MAX_NAME_BYTES));
+    }
+
+    if description.len() > MAX_DESCRIPTION_BYTES {
+        return Err(RecordLimitError::DescriptionTooLong(MAX_DESCRIPTION_BYTES));
+    }
+
+    if locations.len() > MAX_LOCATIONS {
+        return Err(RecordLimitError::TooManyLocations(MAX_LOCATIONS));
+    }
+
+    for (index, location) in locations.iter().enumerate() {
+        let value =
+            serde_json::to_value(location).map_err(RecordLimitError::SerializationFailed)?;
+        if longest_string(&value) > MAX_VALUE_BYTES {
+            return Err(RecordLimitError::LocationTooLong(
+                index + 1,
+                MAX_VALUE_BYTES,
+            ));
+        }
+    }
+
+    if uris.len() > MAX_LINKS {
+        return Err(RecordLimitError::TooManyLinks(MAX_LINKS));
+    }
+
+    for (index, link) in uris.iter().enumerate() {
+        let value = serde_json::to_value(link).map_err(RecordLimitError::SerializationFailed)?;
+        if longest_string(&value) > MAX_VALUE_BYTES {
+            return Err(RecordLimitError::LinkTooLong(index + 1, MAX_VALUE_BYTES));
+        }
+    }
+
+    let size = serde_json::to_vec(record)
+        .map_err(RecordLimitError::SerializationFailed)?
+        .len();
+    if size > MAX_RECORD_BYTES {
+        return Err(RecordLimitError::RecordTooLarge(size, MAX_RECORD_BYTES));
+    }
+
+    Ok(())
+}
+
+/// Returns the size in bytes of the longest string anywhere in a value.
+fn longest_string(value: &Value) -> usize {
+    match value {
+        Value::String(value) => value.len(),
+        Value::Array(values) => values.iter().map(longest_string).max().unwrap_or(0),
+        Value::Object(values) => values.values().map(longest_string).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atproto::lexicon::community::lexicon::{
+        calendar::event::EventLocation, location::Address,
+    };
+    use crate::test_support::EventBuilder;
+
+    fn test_event() -> Event {
+        EventBuilder::default().build()
+    }
+
+    #[test]
+    fn test_event_within_limits() {
+        assert!(check_event_record(&test_event()).is_ok());
+    }
+
+    #[test]
+    fn test_event_string_limits() {
+        let mut event = test_event();
+        let Event::Current { name, .. } = &mut event;
+        *name = "a".repeat(MAX_NAME_BYTES + 1);
+        assert!(matches!(
+            check_event_record(&event),
+            Err(RecordLimitError::NameTooLong(_))
+        ));
+
+        let event = EventBuilder::default()
+            .location(EventLocation::Address(Address::Current {
+                country: "CA".to_string(),
+                postal_code: None,
+                region: None,
+                locality: None,
+                street: Some("a".repeat(MAX_VALUE_BYTES + 1)),
+                name: None,
+            }))
+            .build();
+        assert!(matches!(
+            check_event_record(&event),
+            Err(RecordLimitError::LocationTooLong(1, _))
+        ));
+
+        let event = EventBuilder::default()
+            .link("https://example.com/")
+            .link(format!(
+                "https://example.com/{}",
+                "a".repeat(MAX_VALUE_BYTES)
+            ))
+            .build();
+        assert!(matches!(
+            check_event_record(&event),
+            Err(RecordLimitError::LinkTooLong(2, _))
+        ));
+    }
+
+    #[test]
+    fn test_event_array_and_size_limits() {
+        let event = (0..=MAX_LINKS)
+            .fold(EventBuilder::default(), |builder, index| {
+                builder.link(format!("https://example.com/{}", index))
+            })
+            .build();
+        assert!(matches!(
+            check_event_record(&event),
+            Err(RecordLimitError::TooManyLinks(_))
+        ));
+
+        // Values carried over from other clients count towards the size
+        let mut event = test_event();
+        let Event::Current { extra, .. } = &mut event;
+        extra.insert(
+            "notes".to_string(),
+            Value::String("a".repeat(MAX_RECORD_BYTES)),
+        );
+        assert!(matches!(
+            check_event_record(&event),
+            Err(RecordLimitError::RecordTooLarge(_, MAX_RECORD_BYTES))
+        ));
+    }
+}
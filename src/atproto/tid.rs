@@ -0,0 +1,51 @@
+//! Timestamp identifiers, the record keys that a PDS picks when a record is
+//! created without one.
+//!
+//! A TID is 64 bits, written as 13 characters of base32 that sort in the
+//! same order as the values: the top bit is zero, then 53 bits of
+//! microseconds since the UNIX epoch, then a 10 bit clock identifier.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
+const ALPHABET: &[u8; 32] = b"234567abcdefghijklmnopqrstuvwxyz";
+
+/// Composes the TID of a moment and clock identifier. Only the low 10 bits
+/// of the clock identifier are used.
+pub fn tid(at: DateTime<Utc>, clock_id: u16) -> String {
+    let micros = (at.timestamp_micros().max(0) as u64) & ((1 << 53) - 1);
+    let value = (micros << 10) | (clock_id as u64 & 0x3ff);
+
+    (0..13)
+        .rev()
+        .map(|index| ALPHABET[((value >> (index * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Picks a record key for a record that is created now, for when the key has
+/// to be known before the record is written.
+pub fn new_tid() -> String {
+    tid(Utc::now(), rand::thread_rng().gen_range(0..1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tid() {
+        assert_eq!(tid(DateTime::UNIX_EPOCH, 0), "2222222222222");
+
+        let at = "2024-11-26T20:47:10.123456Z"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+        let value = tid(at, 1023);
+        assert_eq!(value.len(), 13);
+        assert!(value.starts_with('3'));
+        assert!(value.ends_with('z'));
+
+        // Later moments sort after earlier ones
+        let later = tid(at + chrono::Duration::microseconds(1), 0);
+        assert!(later > value);
+    }
+}
@@ -158,3 +158,66 @@ pub fn parse_aturi(uri: &str) -> Result<(String, String, String)> {
         rkey.to_string(),
     ))
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn repository() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "did:plc:[a-z2-7]{24}",
+            "did:web:[a-z0-9]{1,20}\\.[a-z]{2,6}",
+            "[a-z0-9]{1,20}\\.[a-z0-9]{1,20}\\.[a-z]{2,6}",
+        ]
+    }
+
+    fn collection() -> impl Strategy<Value = String> {
+        "[a-z][a-z0-9]{0,15}(\\.[a-z][a-zA-Z0-9]{0,15}){2,4}"
+    }
+
+    fn rkey() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9_~:-][A-Za-z0-9._~:-]{0,63}"
+            .prop_filter("no path traversal", |rkey| !rkey.contains(".."))
+    }
+
+    proptest! {
+        #[test]
+        fn parses_valid_uris(repository in repository(), collection in collection(), rkey in rkey()) {
+            let uri = format!("at://{}/{}/{}", repository, collection, rkey);
+            let parsed = parse_aturi(&uri);
+            prop_assert!(parsed.is_ok(), "{} failed: {:?}", uri, parsed);
+            prop_assert_eq!(parsed.unwrap(), (repository, collection, rkey));
+        }
+
+        #[test]
+        fn never_panics(uri in "\\PC{0,600}") {
+            let _ = parse_aturi(&uri);
+            let _ = parse_aturi(&format!("at://{}", uri));
+        }
+
+        #[test]
+        fn requires_scheme(uri in "[^a]\\PC{0,100}") {
+            prop_assert!(parse_aturi(&uri).is_err());
+        }
+
+        #[test]
+        fn rejects_long_rkeys(repository in repository(), collection in collection(), length in (MAX_RKEY_LENGTH + 1)..2048usize) {
+            let uri = format!("at://{}/{}/{}", repository, collection, "a".repeat(length));
+            prop_assert!(parse_aturi(&uri).is_err());
+        }
+
+        #[test]
+        fn rejects_non_ascii_repositories(repository in "[^/\\p{Cc}]{0,10}[^\\x00-\\x7F][^/\\p{Cc}]{0,10}", collection in collection(), rkey in rkey()) {
+            let uri = format!("at://{}/{}/{}", repository, collection, rkey);
+            prop_assert!(parse_aturi(&uri).is_err());
+        }
+
+        #[test]
+        fn rejects_path_traversal(repository in repository(), collection in collection(), prefix in "[a-z]{0,8}", suffix in "[a-z]{0,8}") {
+            let uri = format!("at://{}/{}/{}..{}", repository, collection, prefix, suffix);
+            prop_assert!(parse_aturi(&uri).is_err());
+        }
+    }
+}
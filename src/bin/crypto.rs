@@ -1,26 +1,143 @@
 use std::env;
 
+use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
 use rand::RngCore;
+use sqlx::PgPool;
 
-use smokesignal::jose::jwk;
+use smokesignal::{
+    config::{default_env, optional_env, DEFAULT_DATABASE_URL},
+    jose::jwk::{self, WrappedJsonWebKeySet},
+    storage::signing_key::{
+        oauth_signing_key_insert, oauth_signing_key_list, oauth_signing_key_retire,
+        oauth_signing_key_usage,
+    },
+};
 
-fn main() {
-    let mut rng = rand::thread_rng();
+const USAGE: &str = "usage:
+  crypto key
+  crypto jwk
+  crypto rotate-keys <keys.json>
+  crypto keys <keys.json>";
 
-    env::args().for_each(|arg| match arg.as_str() {
-        "key" => {
+/// Generates keys, and rotates the keys OAuth logins are signed with.
+///
+/// - `key` prints a random 64-byte key encoded in base64.
+/// - `jwk` prints a new P-256 JWK.
+/// - `rotate-keys` adds a new JWK to a keys file and schedules the keys in
+///   `OAUTH_ACTIVE_KEYS` to retire once the sessions started with them end.
+///   It prints the new `OAUTH_ACTIVE_KEYS` and `OAUTH_RETIRING_KEYS` values.
+/// - `keys` shows which keys of a keys file are active or retiring, and which
+///   can be removed.
+///
+/// `rotate-keys` and `keys` use `DATABASE_URL`.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["key"] => {
             let mut key: [u8; 64] = [0; 64];
-            rng.fill_bytes(&mut key);
+            rand::thread_rng().fill_bytes(&mut key);
             let encoded: String = general_purpose::STANDARD_NO_PAD.encode(key);
             println!("{encoded}");
         }
-        "jwk" => {
+        ["jwk"] => {
             let ec_jwk = jwk::generate();
             let serialized_value =
                 serde_json::to_string_pretty(&ec_jwk).expect("failed to serialize ec jwk");
             println!("{serialized_value}");
         }
-        _ => {}
-    });
+        ["rotate-keys", path] => rotate_keys(path).await?,
+        ["keys", path] => show_keys(path).await?,
+        _ => anyhow::bail!("{USAGE}"),
+    }
+
+    Ok(())
+}
+
+/// Adds a new key to the keys file, then records it and schedules the
+/// retirement of the keys it replaces. The file is written first so that a
+/// database error leaves an unused key rather than a missing one.
+async fn rotate_keys(path: &str) -> Result<()> {
+    let mut jwks = read_keys(path)?;
+
+    let active_keys = env_key_ids("OAUTH_ACTIVE_KEYS");
+    if active_keys.is_empty() {
+        anyhow::bail!("OAUTH_ACTIVE_KEYS must be set to the keys being replaced");
+    }
+
+    let new_key = jwk::generate();
+    let new_kid = new_key.kid.clone().expect("generated keys have an ID");
+    jwks.keys.push(new_key);
+
+    let temporary_path = format!("{path}.tmp");
+    std::fs::write(&temporary_path, serde_json::to_string_pretty(&jwks)?)?;
+    std::fs::rename(&temporary_path, path)?;
+    println!("added key {new_kid} to {path}");
+
+    let pool = PgPool::connect(&default_env("DATABASE_URL", DEFAULT_DATABASE_URL)).await?;
+    let now = Utc::now();
+
+    oauth_signing_key_insert(&pool, &new_kid, now).await?;
+
+    let mut retiring_keys = env_key_ids("OAUTH_RETIRING_KEYS");
+    for kid in active_keys {
+        let retire_after = oauth_signing_key_retire(&pool, &kid, now).await?;
+        println!("{kid} can be removed after {}", retire_after.to_rfc3339());
+        if !retiring_keys.contains(&kid) {
+            retiring_keys.push(kid);
+        }
+    }
+
+    println!();
+    println!("OAUTH_ACTIVE_KEYS={new_kid}");
+    println!("OAUTH_RETIRING_KEYS={}", retiring_keys.join(";"));
+
+    Ok(())
+}
+
+/// Prints the state of each key in the keys file.
+async fn show_keys(path: &str) -> Result<()> {
+    let jwks = read_keys(path)?;
+
+    let pool = PgPool::connect(&default_env("DATABASE_URL", DEFAULT_DATABASE_URL)).await?;
+    let now = Utc::now();
+
+    let active_keys = env_key_ids("OAUTH_ACTIVE_KEYS");
+    let recorded_keys = oauth_signing_key_list(&pool).await?;
+
+    for kid in jwks.keys.iter().filter_map(|key| key.kid.as_deref()) {
+        let in_use = oauth_signing_key_usage(&pool, kid, now).await?;
+        let retire_after = recorded_keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .and_then(|key| key.retire_after);
+
+        let state = match retire_after {
+            _ if active_keys.iter().any(|value| value == kid) => "active".to_string(),
+            Some(value) if value <= now && in_use == 0 => "can be removed".to_string(),
+            Some(value) => format!("retiring after {}", value.to_rfc3339()),
+            None => "not used for OAuth".to_string(),
+        };
+
+        println!("{kid}  in_use={in_use}  {state}");
+    }
+
+    Ok(())
+}
+
+fn read_keys(path: &str) -> Result<WrappedJsonWebKeySet> {
+    let content = std::fs::read(path)?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+fn env_key_ids(name: &str) -> Vec<String> {
+    optional_env(name)
+        .split(';')
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
 }
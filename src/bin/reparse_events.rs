@@ -0,0 +1,89 @@
+use std::env;
+
+use anyhow::Result;
+use smokesignal::{
+    config::{default_env, DEFAULT_DATABASE_URL},
+    storage::{
+        event::{event_count, event_list_batch, event_update_details, extract_event_details},
+        MIGRATOR,
+    },
+};
+use sqlx::PgPool;
+use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
+
+const DEFAULT_BATCH_SIZE: i64 = 200;
+
+/// Re-runs `extract_event_details` over every stored event and refreshes the
+/// derived columns. Run this after changing the event parser or lexicon types.
+///
+/// Usage: `reparse_events [--batch-size=N] [--dry-run]`
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+        ))
+        .with(tracing_subscriber::fmt::layer().pretty())
+        .init();
+
+    let mut batch_size = DEFAULT_BATCH_SIZE;
+    let mut dry_run = false;
+
+    for arg in env::args().skip(1) {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else if let Some(value) = arg.strip_prefix("--batch-size=") {
+            batch_size = value.parse::<i64>()?.max(1);
+        } else {
+            anyhow::bail!("unknown argument: {arg}");
+        }
+    }
+
+    let database_url = default_env("DATABASE_URL", DEFAULT_DATABASE_URL);
+    let pool = PgPool::connect(&database_url).await?;
+    MIGRATOR.run(&pool).await?;
+
+    let total = event_count(&pool).await?;
+    tracing::info!(total, batch_size, dry_run, "reparsing events");
+
+    let mut processed: i64 = 0;
+    let mut renamed: i64 = 0;
+    let mut failed: i64 = 0;
+    let mut after_aturi: Option<String> = None;
+
+    loop {
+        let events = event_list_batch(&pool, after_aturi.as_deref(), batch_size).await?;
+        if events.is_empty() {
+            break;
+        }
+
+        for event in &events {
+            let details = extract_event_details(event);
+
+            if details.name != event.name {
+                renamed += 1;
+            }
+
+            if !dry_run {
+                if let Err(err) = event_update_details(&pool, &event.aturi, &details).await {
+                    tracing::error!(aturi = event.aturi, err = ?err, "failed to update event");
+                    failed += 1;
+                }
+            }
+        }
+
+        processed += events.len() as i64;
+        tracing::info!(processed, total, renamed, failed, "progress");
+
+        match events.last() {
+            Some(event) if events.len() as i64 == batch_size => {
+                after_aturi = Some(event.aturi.clone());
+            }
+            _ => break,
+        }
+    }
+
+    tracing::info!(processed, renamed, failed, dry_run, "reparse complete");
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::env;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use smokesignal::{
+    config::{default_env, DEFAULT_DATABASE_URL},
+    storage::{
+        cache::{
+            create_cache_pool, refresh_queue_remove, refresh_queue_schedule, refresh_queue_scores,
+        },
+        handle::handle_for_handle,
+        oauth::{
+            oauth_session_delete, oauth_session_delete_for_did, oauth_session_expiry_times,
+            oauth_session_list_for_did, oauth_session_summary,
+        },
+        StoragePool,
+    },
+};
+use sqlx::PgPool;
+use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
+
+const USAGE: &str = "usage:
+  sessions list <did or handle>
+  sessions revoke <did or handle> [session group]
+  sessions expiry
+  sessions refresh <session group>";
+
+/// Upper bounds of the expiry distribution buckets, in minutes from now.
+const EXPIRY_BUCKETS: [i64; 6] = [0, 5, 15, 30, 60, 24 * 60];
+
+/// Inspects and manages OAuth sessions, to debug auth incidents without
+/// querying the database by hand.
+///
+/// - `list` shows the sessions of a DID and when each is next refreshed.
+/// - `revoke` removes every session of a DID, or one of its sessions, which
+///   signs them out.
+/// - `expiry` shows how many access tokens and sessions expire when.
+/// - `refresh` moves a session to the front of the refresh queue, so that
+///   the next refresh worker to check the queue refreshes it.
+///
+/// Usage: `sessions <command> [arguments]`
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+        ))
+        .with(tracing_subscriber::fmt::layer().pretty())
+        .init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let database_url = default_env("DATABASE_URL", DEFAULT_DATABASE_URL);
+    let pool = PgPool::connect(&database_url).await?;
+
+    let redis_url = default_env("REDIS_URL", "redis://valkey:6379/0");
+    let cache_pool = create_cache_pool(&redis_url)?;
+
+    let now = Utc::now();
+
+    match args.as_slice() {
+        ["list", subject] => {
+            let did = resolve_did(&pool, subject).await?;
+            let sessions = oauth_session_list_for_did(&pool, &did).await?;
+
+            let session_groups = sessions
+                .iter()
+                .map(|session| session.session_group.clone())
+                .collect::<Vec<_>>();
+            let scores = refresh_queue_scores(&cache_pool, &session_groups).await?;
+
+            println!("{} sessions for {}", sessions.len(), did);
+            for (session, score) in sessions.iter().zip(scores) {
+                let refresh_at = score
+                    .and_then(DateTime::<Utc>::from_timestamp_millis)
+                    .map(|value| format_relative(value, now))
+                    .unwrap_or_else(|| "not queued".to_string());
+                println!(
+                    "{}  issuer={}  created={}  token_expires={}  session_expires={}  refresh={}",
+                    session.session_group,
+                    session.issuer,
+                    session.created_at.to_rfc3339(),
+                    format_relative(session.access_token_expires_at, now),
+                    format_relative(session.not_after, now),
+                    refresh_at,
+                );
+            }
+        }
+        ["revoke", subject] => {
+            let did = resolve_did(&pool, subject).await?;
+            let session_groups = oauth_session_delete_for_did(&pool, &did).await?;
+            refresh_queue_remove(&cache_pool, &session_groups).await?;
+
+            tracing::info!(did, revoked = session_groups.len(), "revoked sessions");
+        }
+        ["revoke", subject, session_group] => {
+            let did = resolve_did(&pool, subject).await?;
+            match oauth_session_summary(&pool, session_group).await? {
+                Some(session) if session.did == did => {}
+                _ => anyhow::bail!("session group {session_group} is not a session of {did}"),
+            }
+
+            oauth_session_delete(&pool, session_group).await?;
+            refresh_queue_remove(&cache_pool, &[session_group.to_string()]).await?;
+
+            tracing::info!(did, session_group, "revoked session");
+        }
+        ["expiry"] => {
+            let expiry_times = oauth_session_expiry_times(&pool).await?;
+
+            let access_tokens = expiry_times
+                .iter()
+                .map(|(access_token_expires_at, _)| *access_token_expires_at)
+                .collect::<Vec<_>>();
+            let sessions = expiry_times
+                .iter()
+                .map(|(_, not_after)| *not_after)
+                .collect::<Vec<_>>();
+
+            println!("{} sessions", expiry_times.len());
+            println!(
+                "{:<16} {:>14} {:>10}",
+                "expires", "access tokens", "sessions"
+            );
+            for ((label, tokens), (_, sessions)) in expiry_distribution(&access_tokens, now)
+                .into_iter()
+                .zip(expiry_distribution(&sessions, now))
+            {
+                println!("{:<16} {:>14} {:>10}", label, tokens, sessions);
+            }
+        }
+        ["refresh", session_group] => {
+            if oauth_session_summary(&pool, session_group).await?.is_none() {
+                anyhow::bail!("session group {session_group} not found");
+            }
+
+            refresh_queue_schedule(&cache_pool, session_group, now).await?;
+
+            tracing::info!(session_group, "scheduled session for refresh");
+        }
+        _ => anyhow::bail!("{USAGE}"),
+    }
+
+    Ok(())
+}
+
+/// Finds the DID of a subject given as a DID or as a known handle.
+async fn resolve_did(pool: &StoragePool, subject: &str) -> Result<String> {
+    if subject.starts_with("did:") {
+        return Ok(subject.to_string());
+    }
+
+    let handle = handle_for_handle(pool, subject.trim_start_matches('@')).await?;
+    Ok(handle.did)
+}
+
+/// Counts the expiry times that fall in each bucket, from already expired to
+/// more than a day away.
+fn expiry_distribution(expiry_times: &[DateTime<Utc>], now: DateTime<Utc>) -> Vec<(String, usize)> {
+    let mut counts = vec![0; EXPIRY_BUCKETS.len() + 1];
+    for expires_at in expiry_times {
+        let bucket = EXPIRY_BUCKETS
+            .iter()
+            .position(|minutes| *expires_at <= now + Duration::minutes(*minutes))
+            .unwrap_or(EXPIRY_BUCKETS.len());
+        counts[bucket] += 1;
+    }
+
+    let mut labels = vec!["expired".to_string()];
+    for minutes in &EXPIRY_BUCKETS[1..] {
+        labels.push(format!("within {}", format_minutes(*minutes)));
+    }
+    labels.push(format!(
+        "after {}",
+        format_minutes(EXPIRY_BUCKETS[EXPIRY_BUCKETS.len() - 1])
+    ));
+
+    labels.into_iter().zip(counts).collect()
+}
+
+fn format_minutes(minutes: i64) -> String {
+    if minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+fn format_relative(value: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (value - now).num_minutes();
+    if minutes < 0 {
+        format!("{} ({}m ago)", value.to_rfc3339(), -minutes)
+    } else {
+        format!("{} (in {}m)", value.to_rfc3339(), minutes)
+    }
+}
@@ -1,21 +1,30 @@
 use anyhow::Result;
 use chrono::Duration;
 use smokesignal::{
+    config::{default_env, DEFAULT_DATABASE_URL},
     http::{
         context::{AppEngine, I18nContext, WebContext},
         server::build_router,
     },
     i18n::Locales,
     resolve::create_resolver,
-    storage::cache::create_cache_pool,
+    storage::{cache::create_cache_pool, MIGRATOR},
+    task_handle_refresh::{HandleRefreshTask, HandleRefreshTaskConfig},
+    task_import_jobs::{ImportJobsTask, ImportJobsTaskConfig},
+    task_integrity_report::{IntegrityReportTask, IntegrityReportTaskConfig},
+    task_label_refresh::{LabelRefreshTask, LabelRefreshTaskConfig},
+    task_oauth_cleanup::{OAuthCleanupTask, OAuthCleanupTaskConfig},
+    task_pds_resources::{PdsResourcesTask, PdsResourcesTaskConfig},
     task_refresh_tokens::{RefreshTokensTask, RefreshTokensTaskConfig},
+    task_rsvp_backfill::{RsvpBackfillTask, RsvpBackfillTaskConfig},
+    task_webhooks::{WebhooksTask, WebhooksTaskConfig},
+    telemetry::init_tracing,
 };
 use sqlx::PgPool;
 use std::{env, str::FromStr};
 use tokio::net::TcpListener;
 use tokio::signal;
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
-use tracing_subscriber::prelude::*;
 use unic_langid::LanguageIdentifier;
 
 #[cfg(feature = "embed")]
@@ -32,12 +41,7 @@ use smokesignal::http::templates::reload_env;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "smokesignal=debug,info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer().pretty())
-        .init();
+    let tracer_provider = init_tracing("smokesignal=debug,info")?;
 
     let version = smokesignal::config::version()?;
 
@@ -48,6 +52,19 @@ async fn main() -> Result<()> {
         }
     });
 
+    // `smokesignal migrate` brings the database up to date and exits. It only
+    // needs `DATABASE_URL`, so it can run before a deploy.
+    if env::args().nth(1).as_deref() == Some("migrate") {
+        let database_url = default_env("DATABASE_URL", DEFAULT_DATABASE_URL);
+        let pool = PgPool::connect(&database_url).await?;
+        MIGRATOR.run(&pool).await?;
+        tracing::info!(
+            migrations = MIGRATOR.iter().count(),
+            "database migrations are up to date"
+        );
+        return Ok(());
+    }
+
     let config = smokesignal::config::Config::new()?;
 
     let mut client_builder = reqwest::Client::builder();
@@ -62,7 +79,9 @@ async fn main() -> Result<()> {
     let http_client = client_builder.build()?;
 
     let pool = PgPool::connect(&config.database_url).await?;
-    sqlx::migrate!().run(&pool).await?;
+    if config.migrate_on_startup {
+        MIGRATOR.run(&pool).await?;
+    }
 
     let cache_pool = create_cache_pool(&config.redis_url)?;
 
@@ -89,7 +108,7 @@ async fn main() -> Result<()> {
         &http_client,
         config.clone(),
         I18nContext::new(supported_languages, locales),
-        dns_resolver,
+        dns_resolver.clone(),
     );
 
     let app = build_router(web_context.clone());
@@ -131,6 +150,7 @@ async fn main() -> Result<()> {
             sleep_interval: Duration::seconds(10),
             worker_id: "dev".to_string(),
             external_url_base: config.external_base.clone(),
+            plc_hostname: config.plc_hostname.clone(),
             signing_keys: config.signing_keys.clone(),
             oauth_active_keys: config.oauth_active_keys.clone(),
         };
@@ -151,6 +171,175 @@ async fn main() -> Result<()> {
         });
     }
 
+    {
+        let task_config = IntegrityReportTaskConfig {
+            initial_delay: Duration::minutes(5),
+            sleep_interval: Duration::hours(24),
+        };
+        let task = IntegrityReportTask::new(task_config, pool.clone(), token.clone());
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("Integrity report task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = OAuthCleanupTaskConfig {
+            initial_delay: Duration::minutes(1),
+            sleep_interval: Duration::hours(1),
+        };
+        let task =
+            OAuthCleanupTask::new(task_config, pool.clone(), cache_pool.clone(), token.clone());
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("OAuth cleanup task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = PdsResourcesTaskConfig {
+            sleep_interval: Duration::minutes(15),
+            popular_limit: 50,
+        };
+        let task = PdsResourcesTask::new(
+            task_config,
+            http_client.clone(),
+            cache_pool.clone(),
+            token.clone(),
+        );
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("PDS resources task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = RsvpBackfillTaskConfig {
+            sleep_interval: Duration::minutes(5),
+            recheck_interval: Duration::days(1),
+            appview_hostname: config.appview_hostname.clone(),
+            plc_hostname: config.plc_hostname.clone(),
+        };
+        let task = RsvpBackfillTask::new(
+            task_config,
+            http_client.clone(),
+            pool.clone(),
+            token.clone(),
+        );
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("RSVP backfill task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = HandleRefreshTaskConfig {
+            sleep_interval: Duration::minutes(1),
+            refresh_interval: Duration::days(7),
+            batch_size: 50,
+            plc_hostname: config.plc_hostname.clone(),
+            appview_hostname: config.appview_hostname.clone(),
+        };
+        let task = HandleRefreshTask::new(
+            task_config,
+            http_client.clone(),
+            dns_resolver,
+            pool.clone(),
+            token.clone(),
+        );
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("Handle refresh task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    if let Some(labeler_hostname) = &config.labeler_hostname {
+        let task_config = LabelRefreshTaskConfig {
+            sleep_interval: Duration::minutes(1),
+            batch_size: 25,
+            labeler_hostname: labeler_hostname.clone(),
+        };
+        let task = LabelRefreshTask::new(
+            task_config,
+            http_client.clone(),
+            pool.clone(),
+            token.clone(),
+        );
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("Label refresh task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = ImportJobsTaskConfig {
+            sleep_interval: Duration::seconds(5),
+            stale_after: Duration::minutes(5),
+        };
+        let task = ImportJobsTask::new(
+            task_config,
+            http_client.clone(),
+            pool.clone(),
+            web_context.geocoder.clone(),
+            token.clone(),
+        );
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("Import jobs task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
+    {
+        let task_config = WebhooksTaskConfig {
+            sleep_interval: Duration::seconds(15),
+            batch_size: 50,
+            lease: Duration::minutes(5),
+        };
+        // Webhook URLs are checked when they are added, so redirects, which
+        // could lead anywhere, aren't followed.
+        let webhook_client = reqwest::Client::builder()
+            .user_agent(config.user_agent.clone())
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let task = WebhooksTask::new(task_config, webhook_client, pool.clone(), token.clone());
+
+        let inner_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(err) = task.run().await {
+                tracing::error!("Webhooks task failed: {}", err);
+            }
+            inner_token.cancel();
+        });
+    }
+
     {
         let inner_config = config.clone();
         let http_port = *inner_config.http_port.as_ref();
@@ -179,5 +368,11 @@ async fn main() -> Result<()> {
 
     tracker.wait().await;
 
+    if let Some(tracer_provider) = tracer_provider {
+        if let Err(err) = tracer_provider.shutdown() {
+            tracing::error!("unable to flush trace spans: {}", err);
+        }
+    }
+
     Ok(())
 }
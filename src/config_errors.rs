@@ -125,4 +125,57 @@ pub enum ConfigError {
     /// that fail validation checks (such as having invalid format).
     #[error("error-config-17 Signing keys validation failed: {0:?}")]
     SigningKeysValidationFailed(Vec<String>),
+
+    /// Error when the service key is invalid.
+    ///
+    /// This error occurs when the SERVICE_KEY environment variable
+    /// does not reference a valid key in the SIGNING_KEYS file.
+    #[error("error-config-18 SERVICE_KEY must be a valid key in the SIGNING_KEYS file")]
+    InvalidServiceKey,
+
+    /// Error when a homepage section cannot be parsed.
+    ///
+    /// This error occurs when the HOMEPAGE_SECTIONS environment variable
+    /// contains an unknown section or a section with a missing or invalid
+    /// parameter.
+    #[error("error-config-19 Invalid homepage section: {0}")]
+    InvalidHomepageSection(String),
+
+    /// Error when a request body limit cannot be parsed.
+    ///
+    /// This error occurs when the HTTP_FORM_BODY_LIMIT or
+    /// HTTP_UPLOAD_BODY_LIMIT environment variable is not a number of bytes
+    /// with an optional `k` or `m` suffix.
+    #[error("error-config-20 Invalid body limit: {0}")]
+    InvalidBodyLimit(String),
+
+    /// Error when the app password encryption key is invalid.
+    ///
+    /// This error occurs when the APP_PASSWORD_KEY environment variable is
+    /// set but is not a base64 encoded 32 byte key.
+    #[error("error-config-21 APP_PASSWORD_KEY must be a base64 encoded 32 byte key")]
+    InvalidAppPasswordKey,
+
+    /// Error when the default event start time cannot be parsed.
+    ///
+    /// This error occurs when the DEFAULT_EVENT_START_TIME environment
+    /// variable is not a 24-hour time like `18:00`.
+    #[error("error-config-22 Invalid default event start time: {0}")]
+    InvalidDefaultEventStartTime(String),
+
+    /// Error when the default event duration cannot be parsed.
+    ///
+    /// This error occurs when the DEFAULT_EVENT_DURATION environment variable
+    /// is set but is not a positive duration like `2h` or `1h30m`.
+    #[error("error-config-23 Invalid default event duration: {0}")]
+    InvalidDefaultEventDuration(String),
+
+    /// Error when a retiring OAuth key is invalid.
+    ///
+    /// This error occurs when the OAUTH_RETIRING_KEYS environment variable
+    /// references a key that is not in the SIGNING_KEYS file.
+    #[error(
+        "error-config-25 OAUTH_RETIRING_KEYS must only contain keys in the SIGNING_KEYS file: {0}"
+    )]
+    InvalidRetiringKey(String),
 }
@@ -15,8 +15,9 @@ pub mod model {
     }
 
     #[derive(Clone, Deserialize, Debug)]
-    #[serde(tag = "type", rename_all = "camelCase")]
+    #[serde(tag = "type")]
     pub enum VerificationMethod {
+        #[serde(rename_all = "camelCase")]
         Multikey {
             id: String,
             controller: String,
@@ -36,6 +37,9 @@ pub mod model {
         pub id: String,
         pub also_known_as: Vec<String>,
         pub service: Vec<Service>,
+
+        #[serde(default)]
+        pub verification_method: Vec<VerificationMethod>,
     }
 
     impl Document {
@@ -55,6 +59,20 @@ pub mod model {
                 }
             })
         }
+
+        /// Returns the `publicKeyMultibase` of the `#atproto` signing key.
+        pub fn atproto_signing_key(&self) -> Option<&str> {
+            self.verification_method
+                .iter()
+                .find_map(|method| match method {
+                    VerificationMethod::Multikey {
+                        id,
+                        public_key_multibase,
+                        ..
+                    } if id.ends_with("#atproto") => Some(public_key_multibase.as_str()),
+                    _ => None,
+                })
+        }
     }
 
     #[cfg(test)]
@@ -70,6 +88,10 @@ pub mod model {
 
             let document = document.unwrap();
             assert_eq!(document.id, "did:plc:cbkjy5n7bk3ax2wplmtjofq2");
+            assert_eq!(
+                document.atproto_signing_key(),
+                Some("zQ3shXvCK2RyPrSLYQjBEw5CExZkUhJH3n1K2Mb9sC7JbvRMF")
+            );
         }
 
         #[test]
@@ -225,4 +247,4 @@ pub mod web {
             .map_err(|error| WebDIDError::DocumentParseFailed { url, error })
             .map_err(Into::into)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,251 @@
+//! Bluesky discussions of announced events.
+//!
+//! When an event was announced with a Bluesky post, the replies to that post
+//! are shown on the event page, so people can talk about an event without a
+//! comment system of its own. Threads are fetched from the AppView and
+//! cached for `POST_THREAD_CACHE_TTL_SECS`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::{
+        appview::{get_post_thread, ThreadItem, ThreadViewPost},
+        uri::parse_aturi,
+    },
+    storage::{
+        cache::{post_thread_get, post_thread_set},
+        CachePool,
+    },
+};
+
+/// How many levels of replies are shown.
+pub const THREAD_DEPTH: u32 = 3;
+
+/// The most replies shown. Longer discussions link to Bluesky.
+pub const MAX_REPLIES: usize = 50;
+
+/// The longest reply text kept. Posts are limited to 300 graphemes, so this
+/// only guards against records that don't follow the lexicon.
+const MAX_REPLY_CHARS: usize = 1000;
+
+/// A reply to an announcement, with how deeply it is nested.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscussionReply {
+    pub url: String,
+    pub handle: String,
+    pub display_name: Option<String>,
+    pub avatar: Option<String>,
+    pub text: String,
+    pub created_at: Option<String>,
+    pub like_count: u32,
+    pub depth: u32,
+}
+
+/// The replies to the post announcing an event, in thread order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Discussion {
+    pub post_url: String,
+    pub reply_count: u32,
+    pub replies: Vec<DiscussionReply>,
+}
+
+/// The address of a post on Bluesky.
+pub fn post_url(uri: &str) -> Option<String> {
+    let (repository, _, record_key) = parse_aturi(uri).ok()?;
+    Some(format!(
+        "https://bsky.app/profile/{}/post/{}",
+        repository, record_key
+    ))
+}
+
+/// Flattens a thread into its replies, depth first. Replies that are
+/// unavailable are left out, along with their own replies.
+pub fn discussion_from_thread(thread: &ThreadItem) -> Option<Discussion> {
+    let ThreadItem::Post(root) = thread else {
+        return None;
+    };
+
+    let mut replies = vec![];
+    collect_replies(root, 1, &mut replies);
+
+    Some(Discussion {
+        post_url: post_url(&root.post.uri)?,
+        reply_count: root.post.reply_count,
+        replies,
+    })
+}
+
+fn collect_replies(parent: &ThreadViewPost, depth: u32, replies: &mut Vec<DiscussionReply>) {
+    for item in &parent.replies {
+        if replies.len() >= MAX_REPLIES {
+            return;
+        }
+
+        let ThreadItem::Post(reply) = item else {
+            continue;
+        };
+        let Some(url) = post_url(&reply.post.uri) else {
+            continue;
+        };
+
+        let record = &reply.post.record;
+        replies.push(DiscussionReply {
+            url,
+            handle: reply.post.author.handle.clone(),
+            display_name: reply
+                .post
+                .author
+                .display_name
+                .clone()
+                .filter(|value| !value.trim().is_empty()),
+            avatar: reply
+                .post
+                .author
+                .avatar
+                .clone()
+                .filter(|value| value.starts_with("https://")),
+            text: record
+                .get("text")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_REPLY_CHARS)
+                .collect(),
+            created_at: record
+                .get("createdAt")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string),
+            like_count: reply.post.like_count,
+            depth,
+        });
+
+        collect_replies(reply, depth + 1, replies);
+    }
+}
+
+/// Gets the discussion of an announcement post, from the cache when it was
+/// fetched recently. Returns `None` when the AppView doesn't have the post.
+pub async fn announcement_discussion(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    cache_pool: &CachePool,
+    post_aturi: &str,
+) -> Result<Option<Discussion>> {
+    match post_thread_get(cache_pool, post_aturi).await {
+        Ok(Some(value)) => {
+            // Values that can't be read are fetched again.
+            if let Ok(discussion) = serde_json::from_str::<Option<Discussion>>(&value) {
+                return Ok(discussion);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to read cached post thread");
+        }
+    }
+
+    let thread = get_post_thread(http_client, appview_hostname, post_aturi, THREAD_DEPTH).await?;
+    let discussion = discussion_from_thread(&thread);
+
+    // Posts the AppView doesn't have are cached too, so that a deleted
+    // announcement isn't looked up on every view.
+    if let Err(err) =
+        post_thread_set(cache_pool, post_aturi, &serde_json::to_string(&discussion)?).await
+    {
+        tracing::warn!(error = ?err, "unable to cache post thread");
+    }
+
+    Ok(discussion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread() -> ThreadItem {
+        serde_json::from_value(serde_json::json!({
+            "$type": "app.bsky.feed.defs#threadViewPost",
+            "post": {
+                "uri": "at://did:plc:abc/app.bsky.feed.post/3lbs",
+                "cid": "bafyreia",
+                "author": { "did": "did:plc:abc", "handle": "organizer.example.com" },
+                "record": { "text": "Vancouver Rust Meetup", "createdAt": "2025-05-23T09:00:00.000Z" },
+                "replyCount": 3,
+                "likeCount": 5,
+                "indexedAt": "2025-05-23T09:00:01.000Z"
+            },
+            "replies": [
+                {
+                    "$type": "app.bsky.feed.defs#threadViewPost",
+                    "post": {
+                        "uri": "at://did:plc:def/app.bsky.feed.post/3lbt",
+                        "author": {
+                            "did": "did:plc:def",
+                            "handle": "attendee.example.com",
+                            "displayName": "Attendee",
+                            "avatar": "http://example.com/avatar.jpg"
+                        },
+                        "record": { "text": "See you there!", "createdAt": "2025-05-23T10:00:00.000Z" },
+                        "likeCount": 1
+                    },
+                    "replies": [
+                        {
+                            "$type": "app.bsky.feed.defs#threadViewPost",
+                            "post": {
+                                "uri": "at://did:plc:abc/app.bsky.feed.post/3lbu",
+                                "author": { "did": "did:plc:abc", "handle": "organizer.example.com" },
+                                "record": { "text": "Thanks!" }
+                            }
+                        }
+                    ]
+                },
+                {
+                    "$type": "app.bsky.feed.defs#blockedPost",
+                    "uri": "at://did:plc:ghi/app.bsky.feed.post/3lbv",
+                    "blocked": true
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_discussion_from_thread() {
+        let discussion = discussion_from_thread(&thread()).unwrap();
+
+        assert_eq!(
+            discussion.post_url,
+            "https://bsky.app/profile/did:plc:abc/post/3lbs"
+        );
+        assert_eq!(discussion.reply_count, 3);
+        assert_eq!(
+            discussion
+                .replies
+                .iter()
+                .map(|reply| (reply.handle.as_str(), reply.text.as_str(), reply.depth))
+                .collect::<Vec<_>>(),
+            vec![
+                ("attendee.example.com", "See you there!", 1),
+                ("organizer.example.com", "Thanks!", 2),
+            ]
+        );
+        assert_eq!(
+            discussion.replies[0].display_name.as_deref(),
+            Some("Attendee")
+        );
+        assert_eq!(discussion.replies[0].avatar, None);
+    }
+
+    #[test]
+    fn test_discussion_from_missing_thread() {
+        let thread: ThreadItem = serde_json::from_value(serde_json::json!({
+            "$type": "app.bsky.feed.defs#notFoundPost",
+            "uri": "at://did:plc:abc/app.bsky.feed.post/3lbs",
+            "notFound": true
+        }))
+        .unwrap();
+
+        assert_eq!(discussion_from_thread(&thread), None);
+    }
+}
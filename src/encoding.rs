@@ -6,11 +6,11 @@ use std::borrow::Cow;
 use crate::encoding_errors::EncodingError;
 
 pub trait ToBase64 {
-    fn to_base64(&self) -> Result<Cow<str>>;
+    fn to_base64(&self) -> Result<Cow<'_, str>>;
 }
 
 impl<T: Serialize> ToBase64 for T {
-    fn to_base64(&self) -> Result<Cow<str>> {
+    fn to_base64(&self) -> Result<Cow<'_, str>> {
         let json_bytes =
             serde_json::to_vec(&self).map_err(EncodingError::JsonSerializationFailed)?;
         let encoded_json_bytes = general_purpose::URL_SAFE_NO_PAD.encode(json_bytes);
@@ -0,0 +1,224 @@
+//! Renders the image shown when a link to an event is shared.
+//!
+//! The card is composed as an SVG document and drawn with resvg using the
+//! fonts installed on the server. SVG text doesn't wrap, so the event name is
+//! wrapped and long details are shortened before they are placed.
+
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use resvg::{
+    tiny_skia,
+    usvg::{self, fontdb},
+};
+
+use crate::event_card_errors::EventCardError;
+
+/// The size of a card, which is the size most platforms expect for a link
+/// preview image.
+pub const CARD_WIDTH: u32 = 1200;
+pub const CARD_HEIGHT: u32 = 630;
+
+const NAME_LINE_CHARS: usize = 26;
+const NAME_MAX_LINES: usize = 3;
+const DETAIL_LINE_CHARS: usize = 50;
+const ORGANIZER_CHARS: usize = 30;
+
+/// Families preferred for card text, in order. The default sans-serif family
+/// of fontdb is Arial, which servers rarely have.
+const PREFERRED_FAMILIES: [&str; 3] = ["DejaVu Sans", "Liberation Sans", "Noto Sans"];
+
+static FONTS: OnceCell<Arc<fontdb::Database>> = OnceCell::new();
+
+/// The text shown on an event card.
+pub struct EventCard<'a> {
+    pub name: &'a str,
+    pub starts_at: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub organizer: &'a str,
+}
+
+/// Loads the system fonts once and picks the family used for `sans-serif`.
+fn fonts() -> Arc<fontdb::Database> {
+    FONTS
+        .get_or_init(|| {
+            let mut database = fontdb::Database::new();
+            database.load_system_fonts();
+
+            let has_family = |family: &str| {
+                database
+                    .faces()
+                    .any(|face| face.families.iter().any(|(name, _)| name == family))
+            };
+            let family = PREFERRED_FAMILIES
+                .iter()
+                .find(|family| has_family(family))
+                .map(|family| family.to_string())
+                .or_else(|| {
+                    database
+                        .faces()
+                        .find_map(|face| face.families.first().map(|(name, _)| name.clone()))
+                });
+
+            match family {
+                Some(family) => database.set_sans_serif_family(family),
+                None => tracing::warn!("no fonts found, event cards will not have text"),
+            }
+
+            Arc::new(database)
+        })
+        .clone()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Shortens text to at most `max` characters, ending it with an ellipsis when
+/// it is cut.
+fn truncate(value: &str, max: usize) -> String {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if value.chars().count() <= max {
+        return value;
+    }
+    let kept: String = value.chars().take(max - 1).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Splits text into at most `max_lines` lines of at most `width` characters,
+/// breaking between words. The last line ends with an ellipsis when the text
+/// doesn't fit.
+fn wrap(value: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for word in value.split_whitespace() {
+        let word = truncate(word, width);
+        if current.is_empty() {
+            current = word;
+        } else if current.chars().count() + 1 + word.chars().count() <= width {
+            current.push(' ');
+            current.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut current, word));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            let kept: String = last.chars().take(width - 1).collect();
+            *last = format!("{}…", kept.trim_end());
+        }
+    }
+
+    lines
+}
+
+/// Composes the SVG document of an event card.
+pub fn event_card_svg(card: &EventCard) -> String {
+    let mut body = String::new();
+    let mut y = 150;
+
+    for line in wrap(card.name, NAME_LINE_CHARS, NAME_MAX_LINES) {
+        body.push_str(&format!(
+            r##"<text x="80" y="{}" font-size="56" font-weight="bold" fill="#ffffff">{}</text>"##,
+            y,
+            escape(&line)
+        ));
+        y += 70;
+    }
+
+    y += 30;
+    for detail in [card.starts_at, card.location].into_iter().flatten() {
+        if detail.trim().is_empty() {
+            continue;
+        }
+        body.push_str(&format!(
+            r##"<text x="80" y="{}" font-size="36" fill="#d5d9e0">{}</text>"##,
+            y,
+            escape(&truncate(detail, DETAIL_LINE_CHARS))
+        ));
+        y += 52;
+    }
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif"><rect width="{width}" height="{height}" fill="#1b1f2a"/><rect width="16" height="{height}" fill="#f14668"/>{body}<text x="80" y="570" font-size="32" font-weight="bold" fill="#f14668">Smoke Signal</text><text x="1120" y="570" font-size="28" fill="#9aa1ad" text-anchor="end">{organizer}</text></svg>"##,
+        width = CARD_WIDTH,
+        height = CARD_HEIGHT,
+        body = body,
+        organizer = escape(&truncate(card.organizer, ORGANIZER_CHARS)),
+    )
+}
+
+/// Renders an event card as a PNG image.
+pub fn render_event_card(card: &EventCard) -> Result<Vec<u8>, EventCardError> {
+    let options = usvg::Options {
+        fontdb: fonts(),
+        ..usvg::Options::default()
+    };
+
+    let tree = usvg::Tree::from_str(&event_card_svg(card), &options)
+        .map_err(|err| EventCardError::InvalidSvg(err.to_string()))?;
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(CARD_WIDTH, CARD_HEIGHT).ok_or(EventCardError::CanvasFailed)?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|err| EventCardError::EncodeFailed(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("Vancouver Rust Meetup: Async in Practice", 20, 3),
+            vec!["Vancouver Rust", "Meetup: Async in", "Practice"]
+        );
+        assert_eq!(
+            wrap("one two three four five six", 9, 2),
+            vec!["one two", "three…"]
+        );
+        assert_eq!(wrap("Supercalifragilistic", 10, 3), vec!["Supercali…"]);
+        assert!(wrap("   ", 10, 3).is_empty());
+    }
+
+    #[test]
+    fn test_event_card_svg_escapes_text() {
+        let svg = event_card_svg(&EventCard {
+            name: "Rust & <Friends>",
+            starts_at: Some("May 17, 2025 6:00 PM PDT"),
+            location: None,
+            organizer: "\"ngerakines\"",
+        });
+        assert!(svg.contains("Rust &amp; &lt;Friends&gt;"));
+        assert!(svg.contains("&quot;ngerakines&quot;"));
+        assert!(svg.contains("May 17, 2025 6:00 PM PDT"));
+        assert!(usvg::Tree::from_str(&svg, &usvg::Options::default()).is_ok());
+    }
+
+    #[test]
+    fn test_render_event_card() {
+        let png = render_event_card(&EventCard {
+            name: "Vancouver Rust Meetup",
+            starts_at: Some("May 17, 2025 6:00 PM PDT"),
+            location: Some("Vancouver, BC"),
+            organizer: "@smokesignal.events",
+        })
+        .unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
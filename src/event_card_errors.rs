@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while rendering an event card image.
+///
+/// These errors happen when the card composed for an event cannot be drawn
+/// or encoded as a PNG image.
+#[derive(Debug, Error)]
+pub enum EventCardError {
+    /// Error when the composed card cannot be read as an SVG document.
+    ///
+    /// This error occurs when the card template produces markup that the SVG
+    /// parser rejects.
+    #[error("error-event-card-1 Card could not be composed: {0}")]
+    InvalidSvg(String),
+
+    /// Error when the image to draw the card on cannot be allocated.
+    ///
+    /// This error occurs when the card size is zero or too large to
+    /// allocate.
+    #[error("error-event-card-2 Card image could not be allocated")]
+    CanvasFailed,
+
+    /// Error when the drawn card cannot be encoded as a PNG image.
+    ///
+    /// This error occurs when PNG encoding of the rendered pixels fails.
+    #[error("error-event-card-3 Card image could not be encoded: {0}")]
+    EncodeFailed(String),
+}
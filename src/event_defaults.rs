@@ -0,0 +1,245 @@
+//! Chooses the start time and length that new events are pre-filled with.
+//!
+//! The instance configures a default start time and, optionally, a default
+//! length. Organizers who have created events before get the start time and
+//! length they use most instead, read from their latest events in their own
+//! time zone.
+//!
+//! Events duplicated from another event keep its times, moved a week or a
+//! month later.
+
+use chrono::{DateTime, Days, Duration, Months, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// The number of latest events an organizer's defaults are learned from.
+pub const LEARN_FROM_EVENTS: i64 = 20;
+
+/// Learned lengths longer than this are ignored, since multi-day events say
+/// little about the length of the next one.
+const MAX_LEARNED_DURATION_HOURS: i64 = 24;
+
+/// The start time and length a new event is pre-filled with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventDefaults {
+    pub start_time: NaiveTime,
+    pub duration: Option<Duration>,
+}
+
+impl EventDefaults {
+    /// Learns defaults from the start and end of an organizer's events,
+    /// latest first. The start time is the most common local start time, with
+    /// ties going to the most recent. The length is the median length of the
+    /// events that have an end. Whatever can't be learned is taken from
+    /// `self`.
+    pub fn learn(self, past: &[(DateTime<Utc>, Option<DateTime<Utc>>)], tz: Tz) -> Self {
+        let mut start_times: Vec<(NaiveTime, usize)> = Vec::new();
+        for (starts_at, _) in past {
+            let time = starts_at.with_timezone(&tz).time();
+            match start_times.iter_mut().find(|(value, _)| *value == time) {
+                Some((_, count)) => *count += 1,
+                None => start_times.push((time, 1)),
+            }
+        }
+
+        // max_by_key returns the last of equal elements, so search from the
+        // oldest to prefer the most recent on a tie.
+        let start_time = start_times
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(time, _)| *time)
+            .unwrap_or(self.start_time);
+
+        let mut durations: Vec<Duration> = past
+            .iter()
+            .filter_map(|(starts_at, ends_at)| ends_at.map(|ends_at| ends_at - *starts_at))
+            .filter(|duration| {
+                *duration > Duration::zero()
+                    && *duration <= Duration::hours(MAX_LEARNED_DURATION_HOURS)
+            })
+            .collect();
+        durations.sort();
+
+        let duration = durations
+            .get(durations.len() / 2)
+            .copied()
+            .or(self.duration);
+
+        Self {
+            start_time,
+            duration,
+        }
+    }
+
+    /// The start of a new event created at `now`: today at the default start
+    /// time in `tz`. When that time doesn't exist on the day, because clocks
+    /// go forward, the event starts an hour later.
+    pub fn starts_at(&self, now: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local_date = now.with_timezone(&tz).date_naive();
+        from_local(local_date.and_time(self.start_time), tz)
+    }
+}
+
+/// How much later a duplicated event is than the event it copies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateShift {
+    #[default]
+    Week,
+    Month,
+}
+
+impl DuplicateShift {
+    /// Moves a time of the copied event later, keeping its local time of day
+    /// in `tz` when clocks change in between. Months that are too short end
+    /// the move on their last day, so an event on 31 January is copied to 28
+    /// February.
+    pub fn apply(self, value: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local = value.with_timezone(&tz).naive_local();
+        let shifted = match self {
+            DuplicateShift::Week => local.checked_add_days(Days::new(7)),
+            DuplicateShift::Month => local.checked_add_months(Months::new(1)),
+        }?;
+        from_local(shifted, tz)
+    }
+}
+
+/// Finds a local time in `tz`. When that time doesn't exist on the day,
+/// because clocks go forward, the time an hour later is used.
+fn from_local(naive_dt: NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+    naive_dt
+        .and_local_timezone(tz)
+        .earliest()
+        .or_else(|| {
+            (naive_dt + Duration::hours(1))
+                .and_local_timezone(tz)
+                .earliest()
+        })
+        .map(|local_dt| local_dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fallback() -> EventDefaults {
+        EventDefaults {
+            start_time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            duration: None,
+        }
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_learn_without_events() {
+        assert_eq!(fallback().learn(&[], Tz::America__Vancouver), fallback());
+    }
+
+    #[test]
+    fn test_learn_start_time_and_duration() {
+        // 7 PM and 6:30 PM in Vancouver, newest first
+        let past = [
+            (
+                utc("2025-05-20T02:00:00Z"),
+                Some(utc("2025-05-20T04:00:00Z")),
+            ),
+            (utc("2025-05-13T01:30:00Z"), None),
+            (
+                utc("2025-05-06T02:00:00Z"),
+                Some(utc("2025-05-06T05:00:00Z")),
+            ),
+            (
+                utc("2025-04-29T02:00:00Z"),
+                Some(utc("2025-04-29T03:30:00Z")),
+            ),
+        ];
+
+        let learned = fallback().learn(&past, Tz::America__Vancouver);
+        assert_eq!(
+            learned.start_time,
+            NaiveTime::from_hms_opt(19, 0, 0).unwrap()
+        );
+        assert_eq!(learned.duration, Some(Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_learn_prefers_recent_start_time_on_tie() {
+        let past = [
+            (utc("2025-05-20T17:00:00Z"), None),
+            (utc("2025-05-13T18:00:00Z"), None),
+        ];
+
+        let learned = fallback().learn(&past, Tz::UTC);
+        assert_eq!(
+            learned.start_time,
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_learn_ignores_unusual_durations() {
+        let past = [
+            (
+                utc("2025-05-20T17:00:00Z"),
+                Some(utc("2025-05-23T17:00:00Z")),
+            ),
+            (
+                utc("2025-05-13T17:00:00Z"),
+                Some(utc("2025-05-13T16:00:00Z")),
+            ),
+        ];
+
+        let defaults = EventDefaults {
+            duration: Some(Duration::minutes(90)),
+            ..fallback()
+        };
+        assert_eq!(
+            defaults.learn(&past, Tz::UTC).duration,
+            Some(Duration::minutes(90))
+        );
+    }
+
+    #[test]
+    fn test_starts_at() {
+        let now = utc("2025-05-17T20:00:00Z");
+        assert_eq!(
+            fallback().starts_at(now, Tz::America__Vancouver),
+            Some(utc("2025-05-18T01:00:00Z"))
+        );
+
+        // 2:30 AM doesn't exist in Vancouver on 9 March 2025
+        let defaults = EventDefaults {
+            start_time: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+            duration: None,
+        };
+        assert_eq!(
+            defaults.starts_at(utc("2025-03-09T20:00:00Z"), Tz::America__Vancouver),
+            Some(utc("2025-03-09T10:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_shift() {
+        // 7 PM in Vancouver, before clocks go forward on 9 March 2025
+        let starts_at = utc("2025-03-05T03:00:00Z");
+        assert_eq!(
+            DuplicateShift::Week.apply(starts_at, Tz::America__Vancouver),
+            Some(utc("2025-03-12T02:00:00Z"))
+        );
+        assert_eq!(
+            DuplicateShift::Month.apply(starts_at, Tz::America__Vancouver),
+            Some(utc("2025-04-05T02:00:00Z"))
+        );
+
+        assert_eq!(
+            DuplicateShift::Month.apply(utc("2025-01-31T18:00:00Z"), Tz::UTC),
+            Some(utc("2025-02-28T18:00:00Z"))
+        );
+    }
+}
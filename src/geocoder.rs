@@ -0,0 +1,211 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+
+use crate::atproto::lexicon::community::lexicon::{
+    calendar::event::EventLocation, location::Address,
+};
+use crate::geocoder_errors::GeocoderError;
+use crate::storage::{
+    event::{event_get, event_update_coordinates, extract_event_details},
+    StoragePool,
+};
+
+/// A point resolved from an address, in decimal degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct Place {
+    lat: String,
+    lon: String,
+}
+
+/// Resolves event addresses to coordinates using a Nominatim compatible
+/// search API. Requests are made one at a time, since public geocoders don't
+/// allow bulk use and imports can add many events at once.
+#[derive(Clone)]
+pub struct Geocoder {
+    http_client: reqwest::Client,
+    base_url: String,
+    permits: Arc<Semaphore>,
+}
+
+impl Geocoder {
+    pub fn new(http_client: &reqwest::Client, base_url: &str) -> Self {
+        Self {
+            http_client: http_client.clone(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            permits: Arc::new(Semaphore::new(1)),
+        }
+    }
+
+    /// Returns the coordinates of the best match for an address, or `None`
+    /// when the geocoder doesn't know the address.
+    pub async fn geocode(&self, address: &Address) -> Result<Option<Coordinates>, GeocoderError> {
+        // The semaphore is never closed, so acquiring a permit can't fail
+        let _permit = self.permits.acquire().await.ok();
+
+        let places = self
+            .http_client
+            .get(format!("{}/search", self.base_url))
+            .query(&address_query(address))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(GeocoderError::RequestFailed)?
+            .json::<Vec<Place>>()
+            .await
+            .map_err(GeocoderError::RequestFailed)?;
+
+        places
+            .first()
+            .map(|place| parse_coordinates(&place.lat, &place.lon))
+            .transpose()
+    }
+
+    /// Geocodes the first address of a stored event and saves its
+    /// coordinates. This runs in the background so that creating or editing
+    /// an event doesn't wait on the geocoder.
+    pub fn spawn_geocode_event(&self, pool: &StoragePool, aturi: &str) {
+        let geocoder = self.clone();
+        let pool = pool.clone();
+        let aturi = aturi.to_string();
+
+        tokio::spawn(async move {
+            let event = match event_get(&pool, &aturi).await {
+                Ok(value) => value,
+                Err(err) => {
+                    tracing::warn!(aturi, error = ?err, "unable to get event to geocode");
+                    return;
+                }
+            };
+
+            let address = extract_event_details(&event)
+                .locations
+                .into_iter()
+                .find_map(|location| match location {
+                    EventLocation::Address(address) => Some(address),
+                    _ => None,
+                });
+            let Some(address) = address else {
+                return;
+            };
+
+            let coordinates = match geocoder.geocode(&address).await {
+                Ok(Some(value)) => value,
+                Ok(None) => {
+                    tracing::debug!(aturi, "no geocoder result for event address");
+                    return;
+                }
+                Err(err) => {
+                    tracing::warn!(aturi, error = ?err, "unable to geocode event address");
+                    return;
+                }
+            };
+
+            if let Err(err) = event_update_coordinates(
+                &pool,
+                &aturi,
+                Some((coordinates.latitude, coordinates.longitude)),
+            )
+            .await
+            {
+                tracing::error!(aturi, error = ?err, "unable to store event coordinates");
+            }
+        });
+    }
+}
+
+/// Builds a structured search query for an address. Fields that are missing
+/// or empty are left out.
+fn address_query(address: &Address) -> Vec<(&'static str, String)> {
+    let Address::Current {
+        country,
+        postal_code,
+        region,
+        locality,
+        street,
+        ..
+    } = address;
+
+    let mut query = vec![("format", "jsonv2".to_string()), ("limit", "1".to_string())];
+
+    let fields = [
+        ("street", street.as_deref()),
+        ("city", locality.as_deref()),
+        ("state", region.as_deref()),
+        ("postalcode", postal_code.as_deref()),
+        ("country", Some(country.as_str())),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+            query.push((key, value.to_string()));
+        }
+    }
+
+    query
+}
+
+fn parse_coordinates(latitude: &str, longitude: &str) -> Result<Coordinates, GeocoderError> {
+    let invalid = || GeocoderError::InvalidCoordinates(latitude.to_string(), longitude.to_string());
+
+    let parsed_latitude = latitude.trim().parse::<f64>().map_err(|_| invalid())?;
+    let parsed_longitude = longitude.trim().parse::<f64>().map_err(|_| invalid())?;
+
+    if !(-90.0..=90.0).contains(&parsed_latitude) || !(-180.0..=180.0).contains(&parsed_longitude) {
+        return Err(invalid());
+    }
+
+    Ok(Coordinates {
+        latitude: parsed_latitude,
+        longitude: parsed_longitude,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_query() {
+        let address = Address::Current {
+            country: "CA".to_string(),
+            postal_code: None,
+            region: Some("BC".to_string()),
+            locality: Some("Vancouver".to_string()),
+            street: Some(" ".to_string()),
+            name: Some("Central Library".to_string()),
+        };
+
+        assert_eq!(
+            address_query(&address),
+            vec![
+                ("format", "jsonv2".to_string()),
+                ("limit", "1".to_string()),
+                ("city", "Vancouver".to_string()),
+                ("state", "BC".to_string()),
+                ("country", "CA".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(
+            parse_coordinates("49.2796", "-123.1157").unwrap(),
+            Coordinates {
+                latitude: 49.2796,
+                longitude: -123.1157,
+            }
+        );
+        assert!(parse_coordinates("north", "-123.1157").is_err());
+        assert!(parse_coordinates("91.0", "0.0").is_err());
+        assert!(parse_coordinates("0.0", "-180.5").is_err());
+    }
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while geocoding event addresses.
+///
+/// These errors happen when the configured geocoder cannot be reached or
+/// returns a result that cannot be used as coordinates.
+#[derive(Debug, Error)]
+pub enum GeocoderError {
+    /// Error when the geocoder request fails.
+    ///
+    /// This error occurs when the geocoder cannot be reached, responds with
+    /// an error status, or returns a body that is not a list of places.
+    #[error("error-geocoder-1 Geocoder request failed: {0:?}")]
+    RequestFailed(reqwest::Error),
+
+    /// Error when a place returned by the geocoder has invalid coordinates.
+    ///
+    /// This error occurs when the latitude or longitude of the first result
+    /// is not a number or is outside of the valid range.
+    #[error("error-geocoder-2 Invalid coordinates returned by geocoder: {0}, {1}")]
+    InvalidCoordinates(String, String),
+}
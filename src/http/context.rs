@@ -23,9 +23,11 @@ use minijinja::Environment;
 
 use crate::{
     config::Config,
+    geocoder::Geocoder,
     http::middleware_auth::Auth,
     http::middleware_i18n::Language,
     i18n::Locales,
+    notifications::NotificationRenderer,
     storage::handle::model::Handle,
     storage::{CachePool, StoragePool},
 };
@@ -46,6 +48,8 @@ pub struct InnerWebContext {
     pub config: Config,
     pub i18n_context: I18nContext,
     pub dns_resolver: hickory_resolver::TokioAsyncResolver,
+    pub geocoder: Option<Geocoder>,
+    pub notifications: NotificationRenderer,
 }
 
 #[derive(Clone, FromRef)]
@@ -69,6 +73,13 @@ impl WebContext {
         i18n_context: I18nContext,
         dns_resolver: TokioAsyncResolver,
     ) -> Self {
+        let geocoder = config
+            .geocoder_url
+            .as_deref()
+            .map(|base_url| Geocoder::new(http_client, base_url));
+
+        let notifications = NotificationRenderer::new(&config.external_base);
+
         Self(Arc::new(InnerWebContext {
             pool,
             cache_pool,
@@ -77,6 +88,8 @@ impl WebContext {
             config,
             i18n_context,
             dns_resolver,
+            geocoder,
+            notifications,
         }))
     }
 }
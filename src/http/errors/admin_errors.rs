@@ -25,3 +25,71 @@ pub enum AdminImportEventError {
     #[error("error-admin-import-event-1 Failed to insert event: {0}")]
     InsertFailed(String),
 }
+
+/// These errors relate to administrators managing denylist entries.
+#[derive(Debug, Error)]
+pub enum AdminDenylistError {
+    /// Error when a denylist category is not recognized.
+    ///
+    /// This error occurs when the submitted category is not one of the
+    /// categories supported by the denylist.
+    #[error("error-admin-denylist-1 Invalid category: {0}")]
+    InvalidCategory(String),
+
+    /// Error when a denylist expiry cannot be used.
+    ///
+    /// This error occurs when the submitted expiry date cannot be parsed or
+    /// is not in the future.
+    #[error("error-admin-denylist-2 Invalid expiry: {0}")]
+    InvalidExpiry(String),
+
+    /// Error when a denylist appeal URL cannot be used.
+    ///
+    /// This error occurs when the submitted appeal URL is not an absolute
+    /// http or https URL.
+    #[error("error-admin-denylist-3 Invalid appeal URL: {0}")]
+    InvalidAppealUrl(String),
+}
+
+/// These errors relate to administrators publishing site-wide banners.
+#[derive(Debug, Error)]
+pub enum AdminBannerError {
+    /// Error when a banner has no message.
+    ///
+    /// This error occurs when the submitted message is empty or only
+    /// whitespace.
+    #[error("error-admin-banner-1 Banner message cannot be empty")]
+    EmptyMessage,
+
+    /// Error when a banner level is not recognized.
+    ///
+    /// This error occurs when the submitted level is not one of the levels
+    /// banners can be shown with.
+    #[error("error-admin-banner-2 Invalid level: {0}")]
+    InvalidLevel(String),
+
+    /// Error when a banner schedule cannot be used.
+    ///
+    /// This error occurs when the submitted start or end cannot be parsed,
+    /// or when the banner would end before it starts or has already ended.
+    #[error("error-admin-banner-3 Invalid schedule: {0}")]
+    InvalidSchedule(String),
+}
+
+/// These errors relate to administrators reviewing reported events.
+#[derive(Debug, Error)]
+pub enum AdminReportError {
+    /// Error when a report cannot be found.
+    ///
+    /// This error occurs when the report being resolved doesn't exist, or
+    /// was already resolved by another admin.
+    #[error("error-admin-report-1 Report not found: {0}")]
+    ReportNotFound(String),
+
+    /// Error when a denylist category is not recognized.
+    ///
+    /// This error occurs when the creator of a reported event is denied with
+    /// a category the denylist doesn't support.
+    #[error("error-admin-report-2 Invalid category: {0}")]
+    InvalidCategory(String),
+}
@@ -0,0 +1,163 @@
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use http::StatusCode;
+use serde_json::json;
+use thiserror::Error;
+
+/// Represents errors returned by the JSON API served under `/api/v1`.
+///
+/// API errors are rendered as JSON bodies with an `error` name and a human
+/// readable `message`, in the same shape as XRPC errors so that clients can
+/// handle both the same way.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// Error when the request body is missing, malformed, or invalid.
+    ///
+    /// This error occurs when the body is not valid JSON, does not match the
+    /// expected shape, or fails validation.
+    #[error("error-api-1 Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Error when there is no session that can write to the caller's PDS.
+    ///
+    /// This error occurs when the caller has not logged in to this instance
+    /// or provided an app password, or all of their sessions have expired.
+    #[error("error-api-2 No active session for {0}, log in or add an app password first")]
+    SessionNotFound(String),
+
+    /// Error when the caller's PDS rejects a record write.
+    ///
+    /// This error occurs when the PDS cannot be reached or refuses to create
+    /// or update the record.
+    #[error("error-api-3 Record write failed: {0}")]
+    RecordWriteFailed(String),
+
+    /// Error when the request fails for an unexpected reason.
+    ///
+    /// This error occurs when storage or other internal operations fail
+    /// while servicing the request.
+    #[error("error-api-4 Internal server error: {0}")]
+    Internal(String),
+
+    /// Error when app password sessions are not enabled on this instance.
+    ///
+    /// This error occurs when an app password is provided but no
+    /// APP_PASSWORD_KEY is configured to encrypt the session.
+    #[error("error-api-5 App password sessions are not enabled")]
+    AppPasswordsDisabled,
+
+    /// Error when the caller's PDS does not accept their credentials.
+    ///
+    /// This error occurs when an app password is wrong or revoked, or
+    /// belongs to a different account than the caller.
+    #[error("error-api-6 Authentication failed: {0}")]
+    AuthenticationFailed(String),
+
+    /// Error when the requested event is not indexed by this instance.
+    ///
+    /// This error occurs when the AT-URI does not match an event that has
+    /// been created here or seen on the network.
+    #[error("error-api-7 Event not found: {0}")]
+    EventNotFound(String),
+
+    /// Error when RSVPs to an event have closed.
+    ///
+    /// This error occurs when an RSVP is posted after the organizer's RSVP
+    /// deadline, or after the event has started when no deadline is set.
+    #[error("error-api-8 RSVPs closed: {0} is no longer accepting RSVPs")]
+    RsvpsClosed(String),
+
+    /// Error when an event has no going spots left.
+    ///
+    /// This error occurs when a going RSVP is posted to an event that has
+    /// reached the organizer's capacity. The response names the waitlist
+    /// request the caller can make instead.
+    #[error("error-api-9 Event full: {0} has no spots left, join the waitlist instead")]
+    EventFull(String),
+
+    /// Error when joining the waitlist of an event that has spots left.
+    ///
+    /// This error occurs when the event has no capacity, or has not reached
+    /// it, so the caller can RSVP going instead.
+    #[error("error-api-10 Event not full: {0} has spots left, RSVP going instead")]
+    EventNotFull(String),
+}
+
+impl ApiError {
+    fn error_name(&self) -> &'static str {
+        match self {
+            ApiError::InvalidRequest(_) => "InvalidRequest",
+            ApiError::SessionNotFound(_) => "SessionNotFound",
+            ApiError::RecordWriteFailed(_) => "RecordWriteFailed",
+            ApiError::Internal(_) => "InternalServerError",
+            ApiError::AppPasswordsDisabled => "AppPasswordsDisabled",
+            ApiError::AuthenticationFailed(_) => "AuthenticationFailed",
+            ApiError::EventNotFound(_) => "EventNotFound",
+            ApiError::RsvpsClosed(_) => "RsvpsClosed",
+            ApiError::EventFull(_) => "EventFull",
+            ApiError::EventNotFull(_) => "EventNotFull",
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::SessionNotFound(_) => StatusCode::FORBIDDEN,
+            ApiError::RecordWriteFailed(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::AppPasswordsDisabled => StatusCode::NOT_FOUND,
+            ApiError::AuthenticationFailed(_) => StatusCode::UNAUTHORIZED,
+            ApiError::EventNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::RsvpsClosed(_) => StatusCode::CONFLICT,
+            ApiError::EventFull(_) => StatusCode::CONFLICT,
+            ApiError::EventNotFull(_) => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl From<crate::storage::errors::StorageError> for ApiError {
+    fn from(err: crate::storage::errors::StorageError) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
+
+impl From<crate::atproto::errors::RecordLimitError> for ApiError {
+    fn from(err: crate::atproto::errors::RecordLimitError) -> Self {
+        ApiError::InvalidRequest(err.to_string())
+    }
+}
+
+impl From<crate::app_password_errors::AppPasswordError> for ApiError {
+    fn from(err: crate::app_password_errors::AppPasswordError) -> Self {
+        use crate::app_password_errors::AppPasswordError;
+        match err {
+            AppPasswordError::SignInFailed(_) | AppPasswordError::DidMismatch(_, _) => {
+                ApiError::AuthenticationFailed(err.to_string())
+            }
+            _ => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let ApiError::Internal(_) = self {
+            tracing::error!(error = ?self, "api internal server error");
+        }
+        let mut body = json!({
+            "error": self.error_name(),
+            "message": self.to_string(),
+        });
+        // A full event names the request that joins its waitlist
+        if let ApiError::EventFull(aturi) = &self {
+            body["waitlist"] = json!({
+                "method": "POST",
+                "path": "/api/v1/waitlist",
+                "body": { "subject": aturi },
+            });
+        }
+        (self.status_code(), Json(body)).into_response()
+    }
+}
@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+/// Represents errors returned when a request body is rejected before it is
+/// read.
+///
+/// Body limits keep form posts and uploads from being used to exhaust
+/// memory. Forms have a small limit and upload endpoints a larger one.
+#[derive(Debug, Error)]
+pub enum BodyLimitError {
+    /// Error when a request body is larger than the route allows.
+    ///
+    /// This error occurs when the Content-Length of a request is larger than
+    /// the form or upload body limit configured for its route.
+    #[error("error-body-limit-1 The submitted data is too large: {0} bytes allowed")]
+    TooLarge(usize),
+
+    /// Error when a request body has no declared length.
+    ///
+    /// This error occurs when a request sends a body without a Content-Length
+    /// header, so its size cannot be checked before it is read.
+    #[error("error-body-limit-2 The submitted data must have a known length")]
+    LengthRequired,
+}
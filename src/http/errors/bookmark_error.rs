@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when saving events for later.
+///
+/// Bookmarks are private to the user and stored locally, so these errors
+/// relate only to validating the bookmarked event.
+#[derive(Debug, Error)]
+pub enum BookmarkError {
+    /// Error when the event being bookmarked cannot be found.
+    ///
+    /// This error occurs when a user attempts to save an event that is
+    /// not indexed by this instance, typically due to an invalid AT-URI.
+    #[error("error-bookmark-1 Event not found: {0}")]
+    EventNotFound(String),
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when organizers manage event series.
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    /// Error when a series is saved without a name.
+    ///
+    /// This error occurs when the series form is submitted with an empty
+    /// name.
+    #[error("error-calendar-1 Series name is required")]
+    NameRequired,
+
+    /// Error when a series can't be found.
+    ///
+    /// This error occurs when a series is viewed, edited or removed, but
+    /// isn't known to this instance or belongs to someone else.
+    #[error("error-calendar-2 Series not found: {0}")]
+    NotFound(String),
+
+    /// Error when an event added to a series can't be found.
+    ///
+    /// This error occurs when the submitted event isn't known to this
+    /// instance.
+    #[error("error-calendar-3 Event not found: {0}")]
+    EventNotFound(String),
+
+    /// Error when a series has too many events.
+    ///
+    /// This error occurs when more events are added to a series than it can
+    /// keep.
+    #[error("error-calendar-4 Series can have at most {0} events")]
+    TooManyEvents(usize),
+}
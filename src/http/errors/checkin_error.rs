@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when organizers check in attendees.
+#[derive(Debug, Error)]
+pub enum CheckinError {
+    /// Error when a user is not allowed to check in attendees of an event.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to open check-in or mark an attendee as checked in.
+    #[error("error-checkin-1 Not authorized to check in attendees of this event")]
+    NotAuthorized,
+
+    /// Error when the person being checked in isn't known.
+    ///
+    /// This error occurs when the submitted DID doesn't belong to an
+    /// identity this instance has seen.
+    #[error("error-checkin-2 Attendee not found: {0}")]
+    AttendeeNotFound(String),
+}
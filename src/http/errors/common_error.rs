@@ -65,4 +65,18 @@ pub enum CommonError {
     /// or appears to be corrupted or tampered with.
     #[error("error-common-9 Invalid event format or corrupted data")]
     InvalidEventFormat,
+
+    /// Error when a year or month in a URL is not a valid date.
+    ///
+    /// This error occurs when an archive of past events is requested for
+    /// a month that doesn't exist, such as month 13.
+    #[error("error-common-10 Invalid year or month")]
+    InvalidMonth,
+
+    /// Error when a year, month and day in a URL is not a valid date.
+    ///
+    /// This error occurs when the events of a day that doesn't exist, such
+    /// as the 31st of June, are requested.
+    #[error("error-common-11 Invalid date")]
+    InvalidDate,
 }
@@ -40,4 +40,48 @@ pub enum CreateEventError {
     /// supported values (e.g., "confirmed", "tentative", "cancelled").
     #[error("error-create-event-5 Invalid event status")]
     InvalidEventStatus,
+
+    /// Error when the event name is too short or too long.
+    ///
+    /// This error occurs when the trimmed name is shorter than 10 or longer
+    /// than 500 characters.
+    #[error("error-create-event-6 Invalid event name")]
+    InvalidName,
+
+    /// Error when the event description is too short or too long.
+    ///
+    /// This error occurs when the trimmed description is shorter than 10 or
+    /// longer than 3000 characters.
+    #[error("error-create-event-7 Invalid event description")]
+    InvalidDescription,
+
+    /// Error when an event location is invalid.
+    ///
+    /// This error occurs when a location has coordinates that are not numbers
+    /// or are out of range, or an address without a country.
+    #[error("error-create-event-8 Invalid event location")]
+    InvalidLocation,
+
+    /// Error when an event link is invalid.
+    ///
+    /// This error occurs when a link is empty, too long, or does not use an
+    /// http, https or at URI.
+    #[error("error-create-event-9 Invalid event link")]
+    InvalidLink,
+
+    /// Error when someone other than the organizer duplicates an event.
+    ///
+    /// This error occurs when the duplicate action is used on an event of
+    /// another organizer.
+    #[error("error-create-event-10 Only the organizer can duplicate an event")]
+    DuplicateNotAuthorized,
+
+    /// Error when the PDS doesn't report both records of an announced event.
+    ///
+    /// This error occurs when the event and its announcement are written
+    /// together and the response doesn't have a result for each of them.
+    #[error(
+        "error-create-event-11 Unexpected response when creating the event and its announcement"
+    )]
+    UnexpectedWriteResults,
 }
@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when deleting events.
+///
+/// These errors typically happen when users attempt to delete an event
+/// and encounter authorization or event type issues.
+#[derive(Debug, Error)]
+pub enum DeleteEventError {
+    /// Error when an invalid handle slug is provided.
+    ///
+    /// This error occurs when attempting to delete an event with a handle slug
+    /// that is not properly formatted or does not exist in the system.
+    #[error("error-delete-event-1 Invalid handle slug")]
+    InvalidHandleSlug,
+
+    /// Error when a user is not authorized to delete an event.
+    ///
+    /// This error occurs when a user attempts to delete an event that they
+    /// did not create.
+    #[error("error-delete-event-2 Not authorized to delete this event")]
+    NotAuthorized,
+
+    /// Error when attempting to delete an event from an unsupported collection.
+    ///
+    /// This error occurs when the collection is not one of the event
+    /// collections indexed by this instance.
+    #[error("error-delete-event-3 Unsupported event collection")]
+    UnsupportedCollection,
+
+    /// Error when the PDS rejects the record deletion.
+    ///
+    /// This error occurs when the user's PDS cannot delete the event record,
+    /// in which case the event is kept so that it stays consistent with the
+    /// network.
+    #[error("error-delete-event-4 Failed to delete event record: {0}")]
+    PdsDeleteFailed(String),
+}
@@ -51,4 +51,12 @@ pub enum EditEventError {
     /// that was not created with location information.
     #[error("error-edit-event-6 Cannot edit locations: Event has no locations")]
     NoLocationsPresent,
+
+    /// Error when the event was changed after the edit form was loaded.
+    ///
+    /// This error occurs when the event was edited elsewhere, such as in
+    /// another tab or app, between loading the form and submitting it. The
+    /// form is shown again so the organizer can review the changes.
+    #[error("error-edit-event-7 The event was changed since this form was loaded. Submit again to replace those changes with yours, or reload the page to see them")]
+    Conflict,
 }
@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when organizers post updates to the
+/// people going to their events.
+#[derive(Debug, Error)]
+pub enum EventUpdateError {
+    /// Error when a user is not allowed to post updates to an event.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to send an update to its attendees.
+    #[error("error-event-update-1 Not authorized to post updates to this event")]
+    NotAuthorized,
+
+    /// Error when an update has no message.
+    ///
+    /// This error occurs when the message of an update is empty or only
+    /// whitespace.
+    #[error("error-event-update-2 Updates must have a message")]
+    MessageRequired,
+
+    /// Error when the message of an update is too long.
+    ///
+    /// This error occurs when the message is longer than the number of
+    /// characters given.
+    #[error("error-event-update-3 Updates can be at most {0} characters")]
+    MessageTooLong(usize),
+}
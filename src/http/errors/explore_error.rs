@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when filtering the events on the
+/// explore page.
+#[derive(Debug, Error)]
+pub enum ExploreError {
+    /// Error when a date filter is not a `YYYY-MM-DD` date.
+    #[error("error-explore-1 Invalid date: {0}")]
+    InvalidDate(String),
+
+    /// Error when the mode filter is not a known event mode.
+    #[error("error-explore-2 Invalid mode: {0}")]
+    InvalidMode(String),
+
+    /// Error when the status filter is not a known event status.
+    #[error("error-explore-3 Invalid status: {0}")]
+    InvalidStatus(String),
+
+    /// Error when the end of the date range is before its start.
+    #[error("error-explore-4 The end date must not be before the start date")]
+    InvalidDateRange,
+}
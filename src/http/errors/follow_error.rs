@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when following organizers.
+///
+/// Follows are stored locally, so these errors relate only to validating
+/// the followed identity.
+#[derive(Debug, Error)]
+pub enum FollowError {
+    /// Error when the identity being followed cannot be found.
+    ///
+    /// This error occurs when a user attempts to follow a DID that this
+    /// instance has never seen.
+    #[error("error-follow-1 Identity not found: {0}")]
+    IdentityNotFound(String),
+
+    /// Error when a user attempts to follow themselves.
+    #[error("error-follow-2 You cannot follow yourself")]
+    CannotFollowSelf,
+}
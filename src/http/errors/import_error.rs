@@ -40,4 +40,39 @@ pub enum ImportError {
     /// type that isn't supported for import operations.
     #[error("error-import-5 Unsupported collection type: {0}")]
     UnsupportedCollectionType(String),
+
+    /// Error when no calendar file is uploaded.
+    ///
+    /// This error occurs when the calendar import form is submitted without
+    /// choosing a file, or the file is empty.
+    #[error("error-import-6 Choose an iCalendar file to import")]
+    CalendarFileMissing,
+
+    /// Error when a calendar file has too many events to preview.
+    ///
+    /// This error occurs when an uploaded calendar has more events than an
+    /// import plan can hold.
+    #[error("error-import-7 Calendar has more than {0} events, split it into smaller files")]
+    CalendarTooLarge(usize),
+
+    /// Error when an import plan can't be found.
+    ///
+    /// This error occurs when the plan doesn't exist, was discarded, or
+    /// belongs to another identity.
+    #[error("error-import-8 Import plan not found")]
+    PlanNotFound,
+
+    /// Error when the repository can't be downloaded.
+    ///
+    /// This error occurs when the PDS doesn't return the repository as a CAR
+    /// file, or the repository is larger than can be imported at once.
+    #[error("error-import-9 Failed to download repository: {0}")]
+    FailedToDownloadRepository(String),
+
+    /// Error when the downloaded repository can't be read.
+    ///
+    /// This error occurs when the CAR file is malformed, or holds the
+    /// repository of another identity.
+    #[error("error-import-10 Failed to read repository: {0}")]
+    FailedToReadRepository(String),
 }
@@ -21,6 +21,13 @@ pub enum LoginError {
     #[error("error-login-2 DID document does not contain an AT Protocol PDS endpoint")]
     NoPDS,
 
+    /// Error when the identity or its PDS is on the denylist.
+    ///
+    /// This error occurs during authentication when the user's DID, handle,
+    /// or PDS matches an active denylist entry.
+    #[error("error-login-3 Access denied")]
+    AccessDenied,
+
     /// Error when an OAuth callback is incomplete.
     ///
     /// This error occurs when the OAuth authentication flow callback
@@ -0,0 +1,18 @@
+use thiserror::Error;
+
+/// Represents errors returned while the instance is under maintenance.
+///
+/// These errors are produced by the read-only middleware instead of running
+/// the requested handler.
+#[derive(Debug, Error)]
+pub enum MaintenanceError {
+    /// Error when a write is attempted while the instance is read-only.
+    ///
+    /// This error occurs when a user submits a form or otherwise tries to
+    /// change data while an admin or the `READ_ONLY` setting has put the
+    /// instance into read-only mode.
+    #[error(
+        "error-maintenance-1 Smoke Signal is in read-only mode for maintenance. Events can still be viewed, but changes can't be saved right now"
+    )]
+    ReadOnly,
+}
@@ -1,30 +1,59 @@
 // Module definitions
 pub mod admin_errors;
+pub mod api_error;
+pub mod body_limit_error;
+pub mod bookmark_error;
+pub mod calendar_error;
+pub mod checkin_error;
 pub mod common_error;
 pub mod create_event_errors;
+pub mod delete_event_error;
 pub mod edit_event_error;
+pub mod event_update_error;
 pub mod event_view_errors;
+pub mod explore_error;
+pub mod follow_error;
 pub mod import_error;
 pub mod login_error;
+pub mod maintenance_error;
 pub mod middleware_errors;
 pub mod migrate_event_error;
 pub mod migrate_rsvp_error;
+pub mod report_error;
 pub mod rsvp_error;
+pub mod session_error;
 pub mod url_error;
 pub mod view_event_error;
 pub mod web_error;
+pub mod xrpc_error;
 
-pub use admin_errors::{AdminImportEventError, AdminImportRsvpError};
+pub use admin_errors::{
+    AdminBannerError, AdminDenylistError, AdminImportEventError, AdminImportRsvpError,
+    AdminReportError,
+};
+pub use api_error::ApiError;
+pub use body_limit_error::BodyLimitError;
+pub use bookmark_error::BookmarkError;
+pub use calendar_error::CalendarError;
+pub use checkin_error::CheckinError;
 pub use common_error::CommonError;
 pub use create_event_errors::CreateEventError;
+pub use delete_event_error::DeleteEventError;
 pub use edit_event_error::EditEventError;
+pub use event_update_error::EventUpdateError;
 pub use event_view_errors::EventViewError;
+pub use explore_error::ExploreError;
+pub use follow_error::FollowError;
 pub use import_error::ImportError;
 pub use login_error::LoginError;
+pub use maintenance_error::MaintenanceError;
 pub use middleware_errors::{AuthMiddlewareError, WebSessionError};
 pub use migrate_event_error::MigrateEventError;
 pub use migrate_rsvp_error::MigrateRsvpError;
+pub use report_error::ReportError;
 pub use rsvp_error::RSVPError;
+pub use session_error::SessionError;
 pub use url_error::UrlError;
 pub use view_event_error::ViewEventError;
 pub use web_error::WebError;
+pub use xrpc_error::XrpcError;
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when reporting an event to the admins.
+#[derive(Debug, Error)]
+pub enum ReportError {
+    /// Error when the event being reported cannot be found.
+    ///
+    /// This error occurs when a user reports an event that is not indexed
+    /// by this instance, typically due to an invalid AT-URI.
+    #[error("error-report-1 Event not found: {0}")]
+    EventNotFound(String),
+
+    /// Error when a report has no reason.
+    ///
+    /// This error occurs when the submitted reason is empty or only
+    /// whitespace.
+    #[error("error-report-2 Reason cannot be empty")]
+    EmptyReason,
+
+    /// Error when the reason of a report is too long.
+    ///
+    /// This error occurs when the submitted reason is longer than the
+    /// number of characters admins are shown.
+    #[error("error-report-3 Reason is too long")]
+    ReasonTooLong,
+}
@@ -13,4 +13,62 @@ pub enum RSVPError {
     /// invalid AT-URI.
     #[error("error-rsvps-1 RSVP Not Found: No RSVP found with provided AT-URI.")]
     NotFound,
+
+    /// Error when RSVPs to an event have closed.
+    ///
+    /// This error occurs when a user attempts to RSVP to an event after the
+    /// organizer's RSVP deadline, or after the event has started when no
+    /// deadline is set.
+    #[error("error-rsvps-2 RSVPs Closed: This event is no longer accepting RSVPs.")]
+    Closed,
+
+    /// Error when an RSVP deadline cannot be parsed.
+    ///
+    /// This error occurs when an organizer submits an RSVP deadline with a
+    /// date or time that is not valid in their time zone.
+    #[error("error-rsvps-3 Invalid RSVP Deadline: {0}")]
+    InvalidDeadline(String),
+
+    /// Error when a user is not allowed to change an event's RSVP deadline.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to set or clear the RSVP deadline.
+    #[error("error-rsvps-4 Not Authorized: Only the organizer can change the RSVP deadline.")]
+    DeadlineNotAuthorized,
+
+    /// Error when a user is not allowed to see an event's RSVP history.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to view when attendees changed their RSVPs.
+    #[error("error-rsvps-5 Not Authorized: Only the organizer can view the RSVP history.")]
+    HistoryNotAuthorized,
+
+    /// Error when an event has no going spots left.
+    ///
+    /// This error occurs when a user RSVPs going to an event that has
+    /// reached the organizer's capacity. The user can join the waitlist
+    /// instead.
+    #[error("error-rsvps-6 Event Full: This event has no spots left. Join the waitlist instead.")]
+    EventFull,
+
+    /// Error when an RSVP capacity cannot be parsed.
+    ///
+    /// This error occurs when an organizer submits a capacity that is not a
+    /// whole number greater than zero.
+    #[error("error-rsvps-7 Invalid RSVP Capacity: The capacity must be a whole number greater than zero.")]
+    InvalidCapacity,
+
+    /// Error when a user is not allowed to change an event's RSVP capacity.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to set or clear the RSVP capacity.
+    #[error("error-rsvps-8 Not Authorized: Only the organizer can change the RSVP capacity.")]
+    CapacityNotAuthorized,
+
+    /// Error when joining the waitlist of an event that has spots left.
+    ///
+    /// This error occurs when a user joins the waitlist of an event that has
+    /// no capacity, or has not reached it, so they can RSVP going instead.
+    #[error("error-rsvps-9 Event Not Full: This event has spots left, RSVP going instead.")]
+    NotFull,
 }
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when managing signed in sessions from
+/// settings.
+#[derive(Debug, Error)]
+pub enum SessionError {
+    /// Error when the session being signed out cannot be found.
+    ///
+    /// This error occurs when the session has already ended or belongs to
+    /// another identity.
+    #[error("error-session-1 Session not found: {0}")]
+    SessionNotFound(String),
+}
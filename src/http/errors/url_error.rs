@@ -12,4 +12,19 @@ pub enum UrlError {
     /// supported by the system, typically in an AT Protocol URI path.
     #[error("error-url-1 Unsupported collection type")]
     UnsupportedCollection,
+
+    /// Error when an outbound link isn't one of the links of the event.
+    ///
+    /// This error occurs when the link redirector is asked to follow a URL
+    /// that the event doesn't link to, so it can't be used as an open
+    /// redirect.
+    #[error("error-url-2 Link not found")]
+    LinkNotFound,
+
+    /// Error when an outbound link is on the denylist.
+    ///
+    /// This error occurs when the link redirector is asked to follow a URL
+    /// whose address or host has been denied by an admin.
+    #[error("error-url-3 Link is blocked")]
+    LinkBlocked,
 }
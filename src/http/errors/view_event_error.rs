@@ -26,4 +26,18 @@ pub enum ViewEventError {
     /// details for an event, such as RSVP counts or related data.
     #[error("error-view-event-3 Failed to fetch event details: {0}")]
     FetchEventDetailsFailed(String),
+
+    /// Error when a user is not allowed to see an event's analytics.
+    ///
+    /// This error occurs when someone other than the event organizer
+    /// attempts to view the page views and RSVP counts of an event.
+    #[error("error-view-event-4 Not authorized to view the analytics of this event")]
+    AnalyticsNotAuthorized,
+
+    /// Error when an event is hidden by the label policy.
+    ///
+    /// This error occurs when the event or its organizer carries a label
+    /// from the configured labeler that the label policy hides.
+    #[error("error-view-event-5 Event is hidden by moderation: {0}")]
+    EventHidden(String),
 }
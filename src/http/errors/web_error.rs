@@ -12,18 +12,30 @@ use axum::response::IntoResponse;
 use axum::response::Response;
 use thiserror::Error;
 
+use super::admin_errors::AdminBannerError;
+use super::admin_errors::AdminDenylistError;
 use super::admin_errors::AdminImportEventError;
 use super::admin_errors::AdminImportRsvpError;
+use super::admin_errors::AdminReportError;
+use super::bookmark_error::BookmarkError;
+use super::calendar_error::CalendarError;
+use super::checkin_error::CheckinError;
 use super::common_error::CommonError;
 use super::create_event_errors::CreateEventError;
+use super::delete_event_error::DeleteEventError;
 use super::edit_event_error::EditEventError;
+use super::event_update_error::EventUpdateError;
 use super::event_view_errors::EventViewError;
+use super::explore_error::ExploreError;
+use super::follow_error::FollowError;
 use super::import_error::ImportError;
 use super::login_error::LoginError;
 use super::middleware_errors::MiddlewareAuthError;
 use super::migrate_event_error::MigrateEventError;
 use super::migrate_rsvp_error::MigrateRsvpError;
+use super::report_error::ReportError;
 use super::rsvp_error::RSVPError;
+use super::session_error::SessionError;
 use super::url_error::UrlError;
 
 /// Represents all possible errors that can occur in the HTTP layer.
@@ -79,6 +91,13 @@ pub enum WebError {
     #[error(transparent)]
     EditEvent(#[from] EditEventError),
 
+    /// Event deletion errors.
+    ///
+    /// This error occurs when there are issues deleting an event, such as
+    /// permission problems or the PDS rejecting the deletion.
+    #[error(transparent)]
+    DeleteEvent(#[from] DeleteEventError),
+
     /// Event migration errors.
     ///
     /// This error occurs when there are issues migrating events between
@@ -107,6 +126,26 @@ pub enum WebError {
     #[error(transparent)]
     AdminImportEvent(#[from] AdminImportEventError),
 
+    /// Admin denylist errors.
+    ///
+    /// This error occurs when administrators submit invalid denylist
+    /// entries.
+    #[error(transparent)]
+    AdminDenylist(#[from] AdminDenylistError),
+
+    /// Admin banner errors.
+    ///
+    /// This error occurs when administrators submit invalid site-wide
+    /// banners.
+    #[error(transparent)]
+    AdminBanner(#[from] AdminBannerError),
+
+    /// Admin report review errors.
+    ///
+    /// This error occurs when administrators resolve reports of events.
+    #[error(transparent)]
+    AdminReport(#[from] AdminReportError),
+
     /// RSVP-related errors.
     ///
     /// This error occurs during RSVP operations such as creation, updating,
@@ -114,6 +153,58 @@ pub enum WebError {
     #[error(transparent)]
     RSVP(#[from] RSVPError),
 
+    /// Bookmark-related errors.
+    ///
+    /// This error occurs when users save or remove events from their
+    /// private saved events list.
+    #[error(transparent)]
+    Bookmark(#[from] BookmarkError),
+
+    /// Follow-related errors.
+    ///
+    /// This error occurs when users follow or unfollow organizers.
+    #[error(transparent)]
+    Follow(#[from] FollowError),
+
+    /// Explore page errors.
+    ///
+    /// This error occurs when the events on the explore page are filtered
+    /// with invalid values.
+    #[error(transparent)]
+    Explore(#[from] ExploreError),
+
+    /// Report-related errors.
+    ///
+    /// This error occurs when users report events to the admins.
+    #[error(transparent)]
+    Report(#[from] ReportError),
+
+    /// Session-related errors.
+    ///
+    /// This error occurs when users sign out of their sessions from settings.
+    #[error(transparent)]
+    Session(#[from] SessionError),
+
+    /// Check-in errors.
+    ///
+    /// This error occurs when organizers check in attendees at the door.
+    #[error(transparent)]
+    Checkin(#[from] CheckinError),
+
+    /// Event update errors.
+    ///
+    /// This error occurs when organizers post updates to the people going to
+    /// their events.
+    #[error(transparent)]
+    EventUpdate(#[from] EventUpdateError),
+
+    /// Event series errors.
+    ///
+    /// This error occurs when organizers create, edit or remove a series of
+    /// events, or a series is viewed.
+    #[error(transparent)]
+    Calendar(#[from] CalendarError),
+
     /// Cache operation errors.
     ///
     /// This error occurs when there are issues with cache operations such as
@@ -204,6 +295,27 @@ pub enum WebError {
     /// such as format incompatibilities or validation failures.
     #[error(transparent)]
     ImportError(#[from] ImportError),
+
+    /// Event card errors.
+    ///
+    /// This error occurs when the image shown for a shared event link
+    /// cannot be rendered.
+    #[error(transparent)]
+    EventCardError(#[from] crate::event_card_errors::EventCardError),
+
+    /// Media library errors.
+    ///
+    /// This error occurs when an uploaded image can't be added to a media
+    /// library, or an image picked for an event isn't in it.
+    #[error(transparent)]
+    MediaError(#[from] crate::media_errors::MediaError),
+
+    /// Webhook errors.
+    ///
+    /// This error occurs when a webhook URL can't be registered, or a
+    /// webhook being removed isn't one of the current user's.
+    #[error(transparent)]
+    WebhookError(#[from] crate::webhooks_errors::WebhookError),
 }
 
 /// Implementation of Axum's `IntoResponse` trait for WebError.
@@ -0,0 +1,90 @@
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use http::StatusCode;
+use serde_json::json;
+use thiserror::Error;
+
+/// Represents errors returned by the XRPC endpoints served by this instance.
+///
+/// XRPC errors are rendered as JSON bodies with an `error` name and a
+/// human readable `message`, as expected by standard ATProto clients.
+#[derive(Debug, Error)]
+pub enum XrpcError {
+    /// Error when a request parameter is missing or invalid.
+    ///
+    /// This error occurs when a required query parameter is not provided
+    /// or cannot be parsed, such as a malformed AT-URI or actor.
+    #[error("error-xrpc-1 Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Error when a requested record cannot be found.
+    ///
+    /// This error occurs when the requested event is not indexed by
+    /// this instance.
+    #[error("error-xrpc-2 Record not found: {0}")]
+    RecordNotFound(String),
+
+    /// Error when a requested actor cannot be found.
+    ///
+    /// This error occurs when the requested handle or DID has no
+    /// identity record on this instance.
+    #[error("error-xrpc-3 Actor not found: {0}")]
+    ActorNotFound(String),
+
+    /// Error when the request fails for an unexpected reason.
+    ///
+    /// This error occurs when storage or other internal operations fail
+    /// while servicing the request. The cause is logged rather than
+    /// returned, so database details are not sent to clients.
+    #[error("error-xrpc-4 Internal server error")]
+    Internal,
+
+    /// Error when a requested feed is not served by this instance.
+    ///
+    /// This error occurs when a feed generator URI does not reference one
+    /// of the configured feeds.
+    #[error("error-xrpc-5 Unknown feed: {0}")]
+    UnknownFeed(String),
+}
+
+impl XrpcError {
+    fn error_name(&self) -> &'static str {
+        match self {
+            XrpcError::InvalidRequest(_) => "InvalidRequest",
+            XrpcError::RecordNotFound(_) => "RecordNotFound",
+            XrpcError::ActorNotFound(_) => "ActorNotFound",
+            XrpcError::UnknownFeed(_) => "UnknownFeed",
+            XrpcError::Internal => "InternalServerError",
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            XrpcError::InvalidRequest(_) | XrpcError::UnknownFeed(_) => StatusCode::BAD_REQUEST,
+            XrpcError::RecordNotFound(_) | XrpcError::ActorNotFound(_) => StatusCode::NOT_FOUND,
+            XrpcError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<crate::storage::errors::StorageError> for XrpcError {
+    fn from(err: crate::storage::errors::StorageError) -> Self {
+        tracing::error!(error = ?err, "xrpc internal server error");
+        XrpcError::Internal
+    }
+}
+
+impl IntoResponse for XrpcError {
+    fn into_response(self) -> Response {
+        (
+            self.status_code(),
+            Json(json!({
+                "error": self.error_name(),
+                "message": self.to_string(),
+            })),
+        )
+            .into_response()
+    }
+}
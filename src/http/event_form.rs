@@ -1,7 +1,28 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{errors::expand_error, i18n::Locales};
+use crate::{
+    atproto::{
+        errors::RecordLimitError,
+        lexicon::community::lexicon::{
+            calendar::event::{
+                Event, EventLink, EventLocation, NamedUri, EXTRA_DOORS_OPEN_AT, EXTRA_TAGS,
+            },
+            location::{Address, Fsq, Geo, Hthree},
+        },
+        record_limits::check_event_record,
+    },
+    errors::expand_error,
+    i18n::Locales,
+    storage::{
+        event::format_location,
+        tag::{event_tag_keys, tag_slug, MAX_EVENT_TAGS, MAX_TAG_LENGTH},
+    },
+};
 
 use super::cache_countries::cached_countries;
 
@@ -57,6 +78,104 @@ pub enum BuildEventError {
 
     #[error("error-event-builder-17 Invalid Link Name")]
     InvalidLinkName,
+
+    #[error("error-event-builder-18 Invalid Geo Location Latitude")]
+    InvalidLocationGeoLatitude,
+
+    #[error("error-event-builder-19 Invalid Geo Location Longitude")]
+    InvalidLocationGeoLongitude,
+
+    #[error("error-event-builder-20 Invalid Foursquare Place ID")]
+    InvalidLocationFsqPlaceId,
+
+    #[error("error-event-builder-21 You Already Have An Event With This Name At This Time")]
+    DuplicateEvent,
+
+    #[error("error-event-builder-22 Choose An iCalendar File To Import")]
+    IcsFileMissing,
+
+    #[error("error-event-builder-23 Invalid Doors Open Time Format")]
+    InvalidDoorsOpenTime,
+
+    #[error("error-event-builder-24 Doors Open Time Must Be Before Start Date/Time")]
+    DoorsOpenAfterStart,
+
+    #[error("error-event-builder-25 Invalid Tags")]
+    InvalidTags,
+}
+
+/// The kinds of location that can be entered in the location form. Forms
+/// without a kind are treated as addresses.
+pub const LOCATION_KIND_ADDRESS: &str = "address";
+pub const LOCATION_KIND_GEO: &str = "geo";
+pub const LOCATION_KIND_FSQ: &str = "fsq";
+
+/// Encodes a location for a hidden form field.
+pub fn encode_location(location: &EventLocation) -> String {
+    serde_json::to_string(location).unwrap_or_default()
+}
+
+/// Decodes locations from hidden form fields, dropping any that can't be
+/// parsed.
+pub fn decode_locations(values: &[String]) -> Vec<EventLocation> {
+    values
+        .iter()
+        .filter_map(|value| serde_json::from_str::<EventLocation>(value).ok())
+        .collect()
+}
+
+/// Encodes a link for a hidden form field.
+pub fn encode_link(link: &EventLink) -> String {
+    serde_json::to_string(link).unwrap_or_default()
+}
+
+/// Decodes links from hidden form fields, dropping any that can't be parsed.
+pub fn decode_links(values: &[String]) -> Vec<EventLink> {
+    values
+        .iter()
+        .filter_map(|value| serde_json::from_str::<EventLink>(value).ok())
+        .collect()
+}
+
+/// Sets or removes the tags in the fields of an event record that aren't
+/// otherwise modeled.
+pub fn set_event_tags(extra: &mut HashMap<String, serde_json::Value>, tags: &[String]) {
+    if tags.is_empty() {
+        extra.remove(EXTRA_TAGS);
+    } else {
+        extra.insert(EXTRA_TAGS.to_string(), serde_json::json!(tags));
+    }
+}
+
+/// Sets or removes when doors open in the fields of an event record that
+/// aren't otherwise modeled.
+pub fn set_doors_open_at(
+    extra: &mut HashMap<String, serde_json::Value>,
+    doors_open_at: Option<DateTime<Utc>>,
+) {
+    match doors_open_at {
+        Some(value) => {
+            extra.insert(
+                EXTRA_DOORS_OPEN_AT.to_string(),
+                serde_json::json!(value.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            );
+        }
+        None => {
+            extra.remove(EXTRA_DOORS_OPEN_AT);
+        }
+    }
+}
+
+fn display_location(location: &EventLocation) -> String {
+    match location {
+        EventLocation::Uri(NamedUri::Current { uri, name }) => {
+            name.clone().unwrap_or_else(|| uri.clone())
+        }
+        EventLocation::Hthree(Hthree::Current { value, name }) => {
+            name.clone().unwrap_or_else(|| format!("H3 cell {}", value))
+        }
+        _ => format_location(location).unwrap_or_default(),
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
@@ -94,8 +213,15 @@ pub struct BuildStartsForm {
     pub ends_at: Option<String>,
     pub ends_at_error: Option<String>,
 
+    /// When doors open or arrivals start, as a time on the start day.
+    pub doors_time: Option<String>,
+    pub doors_time_error: Option<String>,
+
+    pub doors_open_at: Option<String>,
+
     pub starts_display: Option<String>,
     pub ends_display: Option<String>,
+    pub doors_display: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -119,6 +245,37 @@ pub struct BuildLocationForm {
 
     pub location_name: Option<String>,
     pub location_name_error: Option<String>,
+
+    pub location_kind: Option<String>,
+
+    pub location_latitude: Option<String>,
+    pub location_latitude_error: Option<String>,
+
+    pub location_longitude: Option<String>,
+    pub location_longitude_error: Option<String>,
+
+    pub location_fsq_place_id: Option<String>,
+    pub location_fsq_place_id_error: Option<String>,
+
+    /// Saved locations, each encoded as a JSON `EventLocation`.
+    #[serde(default)]
+    pub locations: Vec<String>,
+
+    /// The saved location that a row action applies to, or that is being
+    /// edited. New locations are added to the end of the list.
+    pub location_index: Option<usize>,
+    pub location_action: Option<String>,
+
+    #[serde(skip_deserializing, default)]
+    pub location_rows: Vec<LocationRow>,
+}
+
+/// A saved location as shown in the location list.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LocationRow {
+    pub value: String,
+    pub display: String,
+    pub editable: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -130,9 +287,29 @@ pub struct BuildLinkForm {
 
     pub link_value: Option<String>,
     pub link_value_error: Option<String>,
+
+    /// Saved links, each encoded as a JSON `EventLink`.
+    #[serde(default)]
+    pub links: Vec<String>,
+
+    /// The saved link that a row action applies to, or that is being edited.
+    /// New links are added to the end of the list.
+    pub link_index: Option<usize>,
+    pub link_action: Option<String>,
+
+    #[serde(skip_deserializing, default)]
+    pub link_rows: Vec<LinkRow>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A saved link as shown in the link list.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LinkRow {
+    pub value: String,
+    pub uri: String,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct BuildEventForm {
     pub build_state: Option<BuildEventContentState>,
 
@@ -151,9 +328,21 @@ pub struct BuildEventForm {
     pub ends_at: Option<String>,
     pub ends_at_error: Option<String>,
 
+    /// The time zone the start and end were picked in, which is kept with
+    /// the event so its times are shown the same way to everyone.
+    pub tz: Option<String>,
+
+    /// When doors open or arrivals start, which is before the start.
+    pub doors_open_at: Option<String>,
+    pub doors_open_at_error: Option<String>,
+
     pub mode: Option<String>,
     pub mode_error: Option<String>,
 
+    /// The topics of the event, separated by commas.
+    pub tags: Option<String>,
+    pub tags_error: Option<String>,
+
     pub location_country: Option<String>,
     pub location_country_error: Option<String>,
 
@@ -172,17 +361,53 @@ pub struct BuildEventForm {
     pub location_name: Option<String>,
     pub location_name_error: Option<String>,
 
+    pub location_kind: Option<String>,
+
+    pub location_latitude: Option<String>,
+    pub location_latitude_error: Option<String>,
+
+    pub location_longitude: Option<String>,
+    pub location_longitude_error: Option<String>,
+
+    pub location_fsq_place_id: Option<String>,
+    pub location_fsq_place_id_error: Option<String>,
+
+    #[serde(default)]
+    pub locations: Vec<String>,
+
     pub link_name: Option<String>,
     pub link_name_error: Option<String>,
 
     pub link_value: Option<String>,
     pub link_value_error: Option<String>,
+
+    #[serde(default)]
+    pub links: Vec<String>,
+
+    /// The blob CID of the header image, picked from the organizer's media
+    /// library.
+    pub header_image: Option<String>,
+    pub header_image_error: Option<String>,
+
+    /// Whether to announce the event in a Bluesky post once it's created.
+    pub announce: Option<bool>,
+
+    /// The CID of the event when the edit form was loaded. It is sent as the
+    /// swap record, so an edit made elsewhere in the meantime isn't
+    /// overwritten.
+    pub event_cid: Option<String>,
+
+    /// Problems with the composed record that don't belong to a single
+    /// field, such as too many locations or an oversized record.
+    pub record_error: Option<String>,
 }
 
 impl From<BuildEventForm> for BuildLocationForm {
     fn from(build_event_form: BuildEventForm) -> Self {
-        BuildLocationForm {
-            build_state: build_event_form.build_state,
+        // The location being edited isn't part of the event form, so the
+        // location form always starts out showing the saved locations.
+        let mut location_form = BuildLocationForm {
+            build_state: Some(BuildEventContentState::Reset),
             location_country: None,
             location_country_error: None,
             location_name: None,
@@ -195,7 +420,20 @@ impl From<BuildEventForm> for BuildLocationForm {
             location_region_error: None,
             location_postal_code: None,
             location_postal_code_error: None,
-        }
+            location_kind: None,
+            location_latitude: None,
+            location_latitude_error: None,
+            location_longitude: None,
+            location_longitude_error: None,
+            location_fsq_place_id: None,
+            location_fsq_place_id_error: None,
+            locations: build_event_form.locations,
+            location_index: None,
+            location_action: None,
+            location_rows: vec![],
+        };
+        location_form.update_rows();
+        location_form
     }
 }
 
@@ -203,7 +441,7 @@ impl From<BuildEventForm> for BuildStartsForm {
     fn from(build_event_form: BuildEventForm) -> Self {
         BuildStartsForm {
             build_state: build_event_form.build_state,
-            tz: None,
+            tz: build_event_form.tz,
             tz_error: None,
             starts_date: None,
             starts_date_error: None,
@@ -218,30 +456,150 @@ impl From<BuildEventForm> for BuildStartsForm {
             ends_time_error: None,
             ends_at: build_event_form.ends_at,
             ends_at_error: None,
+            doors_time: None,
+            doors_time_error: None,
+            doors_open_at: build_event_form.doors_open_at,
             starts_display: None,
             ends_display: None,
+            doors_display: None,
         }
     }
 }
 
 impl From<BuildEventForm> for BuildLinkForm {
     fn from(build_event_form: BuildEventForm) -> Self {
-        BuildLinkForm {
-            build_state: build_event_form.build_state,
+        // Like the location form, the link being edited isn't part of the
+        // event form, so the link form starts out showing the saved links.
+        let mut link_form = BuildLinkForm {
+            build_state: Some(BuildEventContentState::Reset),
             link_name: None,
             link_name_error: None,
             link_value: None,
             link_value_error: None,
-        }
+            links: build_event_form.links,
+            link_index: None,
+            link_action: None,
+            link_rows: vec![],
+        };
+        link_form.update_rows();
+        link_form
     }
 }
 
 impl BuildLocationForm {
+    /// Rebuilds the rows shown in the location list from the saved locations.
+    pub fn update_rows(&mut self) {
+        self.location_rows = decode_locations(&self.locations)
+            .iter()
+            .map(|location| LocationRow {
+                value: encode_location(location),
+                display: display_location(location),
+                editable: matches!(
+                    location,
+                    EventLocation::Address(_) | EventLocation::Geo(_) | EventLocation::Fsq(_)
+                ),
+            })
+            .collect();
+    }
+
+    /// Clears the location being added or edited, keeping the saved locations.
+    pub fn clear_location(&mut self) {
+        self.location_index = None;
+        self.location_kind = None;
+        self.location_country = None;
+        self.location_country_error = None;
+        self.location_street = None;
+        self.location_street_error = None;
+        self.location_locality = None;
+        self.location_locality_error = None;
+        self.location_region = None;
+        self.location_region_error = None;
+        self.location_postal_code = None;
+        self.location_postal_code_error = None;
+        self.location_name = None;
+        self.location_name_error = None;
+        self.location_latitude = None;
+        self.location_latitude_error = None;
+        self.location_longitude = None;
+        self.location_longitude_error = None;
+        self.location_fsq_place_id = None;
+        self.location_fsq_place_id_error = None;
+    }
+
+    /// Builds an event location from the location fields.
+    pub fn location(&self) -> Option<EventLocation> {
+        match self.location_kind.as_deref() {
+            Some(LOCATION_KIND_GEO) => Some(EventLocation::Geo(Geo::Current {
+                latitude: self.location_latitude.clone()?,
+                longitude: self.location_longitude.clone()?,
+                name: self.location_name.clone(),
+            })),
+            Some(LOCATION_KIND_FSQ) => Some(EventLocation::Fsq(Fsq::Current {
+                fsq_place_id: self.location_fsq_place_id.clone()?,
+                name: self.location_name.clone(),
+            })),
+            _ => Some(EventLocation::Address(Address::Current {
+                country: self.location_country.clone()?,
+                postal_code: self.location_postal_code.clone(),
+                region: self.location_region.clone(),
+                locality: self.location_locality.clone(),
+                street: self.location_street.clone(),
+                name: self.location_name.clone(),
+            })),
+        }
+    }
+
+    /// Populates the location fields from an existing location. Returns false
+    /// when the location can't be edited with the location form.
+    pub fn load_location(&mut self, location: &EventLocation) -> bool {
+        match location {
+            EventLocation::Address(Address::Current {
+                country,
+                postal_code,
+                region,
+                locality,
+                street,
+                name,
+            }) => {
+                self.location_kind = Some(LOCATION_KIND_ADDRESS.to_string());
+                self.location_country = Some(country.clone());
+                self.location_postal_code = postal_code.clone();
+                self.location_region = region.clone();
+                self.location_locality = locality.clone();
+                self.location_street = street.clone();
+                self.location_name = name.clone();
+            }
+            EventLocation::Geo(Geo::Current {
+                latitude,
+                longitude,
+                name,
+            }) => {
+                self.location_kind = Some(LOCATION_KIND_GEO.to_string());
+                self.location_latitude = Some(latitude.clone());
+                self.location_longitude = Some(longitude.clone());
+                self.location_name = name.clone();
+            }
+            EventLocation::Fsq(Fsq::Current { fsq_place_id, name }) => {
+                self.location_kind = Some(LOCATION_KIND_FSQ.to_string());
+                self.location_fsq_place_id = Some(fsq_place_id.clone());
+                self.location_name = name.clone();
+            }
+            EventLocation::Uri(_) | EventLocation::Hthree(_) => return false,
+        }
+        true
+    }
+
     pub fn validate(
         &mut self,
         locales: &Locales,
         language: &unic_langid::LanguageIdentifier,
     ) -> bool {
+        match self.location_kind.as_deref() {
+            Some(LOCATION_KIND_GEO) => return self.validate_geo(locales, language),
+            Some(LOCATION_KIND_FSQ) => return self.validate_fsq(locales, language),
+            _ => {}
+        }
+
         if let Some(location_country_value) = self.location_country.as_ref() {
             let all_countries = match cached_countries() {
                 Ok(value) => value,
@@ -338,6 +696,20 @@ impl BuildLocationForm {
             }
         }
 
+        if self.validate_name(locales, language) {
+            found_errors = true;
+        }
+
+        found_errors
+    }
+
+    fn validate_name(
+        &mut self,
+        locales: &Locales,
+        language: &unic_langid::LanguageIdentifier,
+    ) -> bool {
+        let mut found_errors = false;
+
         if let Some(user_value) = &self.location_name {
             let trimmed_user_value = user_value.trim();
             if trimmed_user_value.is_empty() || trimmed_user_value.len() > 200 {
@@ -357,9 +729,131 @@ impl BuildLocationForm {
 
         found_errors
     }
+
+    fn validate_geo(
+        &mut self,
+        locales: &Locales,
+        language: &unic_langid::LanguageIdentifier,
+    ) -> bool {
+        let mut found_errors = false;
+
+        let latitude = self
+            .location_latitude
+            .as_deref()
+            .map(str::trim)
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| (-90.0..=90.0).contains(value));
+        match latitude {
+            Some(value) => self.location_latitude = Some(value.to_string()),
+            None => {
+                let (err_bare, err_partial) =
+                    expand_error(BuildEventError::InvalidLocationGeoLatitude);
+                let error_message = locales.format_error(language, &err_bare, &err_partial);
+                self.location_latitude_error = Some(error_message);
+                found_errors = true;
+            }
+        }
+
+        let longitude = self
+            .location_longitude
+            .as_deref()
+            .map(str::trim)
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| (-180.0..=180.0).contains(value));
+        match longitude {
+            Some(value) => self.location_longitude = Some(value.to_string()),
+            None => {
+                let (err_bare, err_partial) =
+                    expand_error(BuildEventError::InvalidLocationGeoLongitude);
+                let error_message = locales.format_error(language, &err_bare, &err_partial);
+                self.location_longitude_error = Some(error_message);
+                found_errors = true;
+            }
+        }
+
+        if self.validate_name(locales, language) {
+            found_errors = true;
+        }
+
+        found_errors
+    }
+
+    fn validate_fsq(
+        &mut self,
+        locales: &Locales,
+        language: &unic_langid::LanguageIdentifier,
+    ) -> bool {
+        let mut found_errors = false;
+
+        // Foursquare place IDs are hex strings
+        let place_id = self
+            .location_fsq_place_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| {
+                !value.is_empty()
+                    && value.len() <= 200
+                    && value.chars().all(|c| c.is_ascii_alphanumeric())
+            });
+        match place_id {
+            Some(value) => self.location_fsq_place_id = Some(value.to_string()),
+            None => {
+                let (err_bare, err_partial) =
+                    expand_error(BuildEventError::InvalidLocationFsqPlaceId);
+                let error_message = locales.format_error(language, &err_bare, &err_partial);
+                self.location_fsq_place_id_error = Some(error_message);
+                found_errors = true;
+            }
+        }
+
+        if self.validate_name(locales, language) {
+            found_errors = true;
+        }
+
+        found_errors
+    }
 }
 
 impl BuildLinkForm {
+    /// Rebuilds the rows shown in the link list from the saved links.
+    pub fn update_rows(&mut self) {
+        self.link_rows = decode_links(&self.links)
+            .iter()
+            .map(|link| {
+                let EventLink::Current { uri, name } = link;
+                LinkRow {
+                    value: encode_link(link),
+                    uri: uri.clone(),
+                    name: name.clone(),
+                }
+            })
+            .collect();
+    }
+
+    /// Clears the link being added or edited, keeping the saved links.
+    pub fn clear_link(&mut self) {
+        self.link_index = None;
+        self.link_name = None;
+        self.link_name_error = None;
+        self.link_value = None;
+        self.link_value_error = None;
+    }
+
+    /// Builds an event link from the link fields.
+    pub fn link(&self) -> Option<EventLink> {
+        Some(EventLink::Current {
+            uri: self.link_value.clone()?,
+            name: self.link_name.clone().filter(|value| !value.is_empty()),
+        })
+    }
+
+    /// Populates the link fields from an existing link.
+    pub fn load_link(&mut self, link: &EventLink) {
+        let EventLink::Current { uri, name } = link;
+        self.link_value = Some(uri.clone());
+        self.link_name = name.clone();
+    }
+
     pub fn validate(
         &mut self,
         locales: &Locales,
@@ -371,10 +865,13 @@ impl BuildLinkForm {
         if let Some(link_value) = &self.link_value {
             let trimmed_value = link_value.trim();
 
-            // Check if the URL is valid
+            // Check if the URL is valid. AT-URIs are allowed so that an event
+            // can link to the record it was co-listed from.
             if trimmed_value.is_empty()
                 || trimmed_value.len() > 500
-                || (!trimmed_value.starts_with("http://") && !trimmed_value.starts_with("https://"))
+                || (!trimmed_value.starts_with("http://")
+                    && !trimmed_value.starts_with("https://")
+                    && !trimmed_value.starts_with("at://"))
             {
                 let (err_bare, err_partial) = expand_error(BuildEventError::InvalidLinkValue);
                 let error_message = locales.format_error(language, &err_bare, &err_partial);
@@ -420,6 +917,36 @@ impl BuildLinkForm {
 }
 
 impl BuildStartsForm {
+    /// Sets the start of the event, shown as a date and time in `tz`.
+    pub fn set_starts_at(&mut self, starts_at: DateTime<Utc>, tz: Tz) {
+        let local_dt = starts_at.with_timezone(&tz);
+
+        self.starts_date = Some(local_dt.format("%Y-%m-%d").to_string());
+        self.starts_time = Some(local_dt.format("%H:%M").to_string());
+        self.starts_at = Some(starts_at.to_string());
+        self.starts_display = Some(local_dt.format("%A, %B %-d, %Y %r %Z").to_string());
+    }
+
+    /// Sets the end of the event, shown as a date and time in `tz`.
+    pub fn set_ends_at(&mut self, ends_at: DateTime<Utc>, tz: Tz) {
+        let local_dt = ends_at.with_timezone(&tz);
+
+        self.include_ends = Some(true);
+        self.ends_date = Some(local_dt.format("%Y-%m-%d").to_string());
+        self.ends_time = Some(local_dt.format("%H:%M").to_string());
+        self.ends_at = Some(ends_at.to_string());
+        self.ends_display = Some(local_dt.format("%A, %B %-d, %Y %r %Z").to_string());
+    }
+
+    /// Sets when doors open, shown as a time in `tz`.
+    pub fn set_doors_open_at(&mut self, doors_open_at: DateTime<Utc>, tz: Tz) {
+        let local_dt = doors_open_at.with_timezone(&tz);
+
+        self.doors_time = Some(local_dt.format("%H:%M").to_string());
+        self.doors_open_at = Some(doors_open_at.to_string());
+        self.doors_display = Some(local_dt.format("%r %Z").to_string());
+    }
+
     pub fn validate(
         &mut self,
         locales: &Locales,
@@ -475,6 +1002,32 @@ impl BuildStartsForm {
             None
         };
 
+        // Doors open on the day the event starts, before it starts
+        self.doors_open_at = None;
+        self.doors_display = None;
+        if let Some(doors_time) = self.doors_time.clone().filter(|v| !v.is_empty()) {
+            let date_str = self.starts_date.clone().unwrap_or_default();
+            match crate::http::timezones::combine_html_datetime(&date_str, &doors_time, tz) {
+                Ok(utc_dt) if starts_at.is_some_and(|start| utc_dt < start) => {
+                    self.set_doors_open_at(utc_dt, tz);
+                }
+                Ok(_) => {
+                    found_errors = true;
+                    let (err_bare, err_partial) =
+                        expand_error(BuildEventError::DoorsOpenAfterStart);
+                    let error_message = locales.format_error(language, &err_bare, &err_partial);
+                    self.doors_time_error = Some(error_message);
+                }
+                Err(_) => {
+                    found_errors = true;
+                    let (err_bare, err_partial) =
+                        expand_error(BuildEventError::InvalidDoorsOpenTime);
+                    let error_message = locales.format_error(language, &err_bare, &err_partial);
+                    self.doors_time_error = Some(error_message);
+                }
+            }
+        }
+
         if self.include_ends.is_some_and(|v| v) {
             let has_ends = self.ends_date.is_some() && self.ends_time.is_some();
             if has_starts && !has_ends {
@@ -619,6 +1172,97 @@ impl BuildEventForm {
             self.mode = Some("inperson".to_string());
         }
 
+        // Validate doors open, which must be before the start
+        if let Some(doors_open_at) = self.doors_open_at.as_ref().filter(|v| !v.is_empty()) {
+            let doors_open_at = doors_open_at.parse::<DateTime<Utc>>().ok();
+            let starts_at = self
+                .starts_at
+                .as_ref()
+                .and_then(|v| v.parse::<DateTime<Utc>>().ok());
+            let error = match (doors_open_at, starts_at) {
+                (None, _) => Some(BuildEventError::InvalidDoorsOpenTime),
+                (Some(doors), Some(start)) if doors < start => None,
+                _ => Some(BuildEventError::DoorsOpenAfterStart),
+            };
+            if let Some(error) = error {
+                let (err_bare, err_partial) = expand_error(error);
+                let error_message = locales.format_error(language, &err_bare, &err_partial);
+                self.doors_open_at_error = Some(error_message);
+                found_errors = true;
+            }
+        }
+
+        // Validate tags, which are kept normalized
+        if let Some(tags) = &self.tags {
+            let values = tags
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>();
+            let keys = event_tag_keys(&values);
+            let valid = values.len() <= MAX_EVENT_TAGS
+                && values.iter().all(|value| {
+                    let key = tag_slug(value);
+                    !key.is_empty() && key.len() <= MAX_TAG_LENGTH
+                });
+            if valid {
+                self.tags = Some(keys.join(", "));
+            } else {
+                let (err_bare, err_partial) = expand_error(BuildEventError::InvalidTags);
+                let error_message = locales.format_error(language, &err_bare, &err_partial);
+                self.tags_error = Some(error_message);
+                found_errors = true;
+            }
+        }
+
         found_errors
     }
+
+    /// Checks a composed event record against the limits a PDS accepts, and
+    /// shows any problem on the field it belongs to. Returns `true` when
+    /// the record can be written.
+    pub fn check_record(
+        &mut self,
+        record: &Event,
+        locales: &Locales,
+        language: &unic_langid::LanguageIdentifier,
+    ) -> bool {
+        let Err(err) = check_event_record(record) else {
+            return true;
+        };
+
+        let (err_bare, err_partial) = expand_error(&err);
+        let error_message = locales.format_error(language, &err_bare, &err_partial);
+        match err {
+            RecordLimitError::NameTooLong(_) => self.name_error = Some(error_message),
+            RecordLimitError::DescriptionTooLong(_) => self.description_error = Some(error_message),
+            _ => self.record_error = Some(error_message),
+        }
+        self.build_state = Some(BuildEventContentState::Selecting);
+
+        false
+    }
+
+    /// Returns the saved event locations, in order. Rows that can't be
+    /// parsed are dropped.
+    pub fn locations(&self) -> Vec<EventLocation> {
+        decode_locations(&self.locations)
+    }
+
+    /// Returns the saved event links, in order. Rows that can't be parsed
+    /// are dropped.
+    pub fn links(&self) -> Vec<EventLink> {
+        decode_links(&self.links)
+    }
+
+    /// Returns the normalized tags of the event, in order.
+    pub fn tags(&self) -> Vec<String> {
+        let values = self
+            .tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .collect::<Vec<_>>();
+        event_tag_keys(&values)
+    }
 }
@@ -11,20 +11,26 @@ use crate::http::errors::EventViewError;
 use crate::{
     atproto::{
         lexicon::{
-            community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+            community::lexicon::calendar::event::{
+                EventLocation, NSID as LexiconCommunityEventNSID,
+            },
             events::smokesignal::calendar::event::NSID as SmokeSignalEventNSID,
         },
         uri::parse_aturi,
     },
+    config::{LabelAction, LabelPolicy},
     http::utils::truncate_text,
+    media::{blob_url, header_image},
+    richtext::{mentioned_dids, render_description},
     storage::{
-        errors::StorageError,
+        cache::{rsvp_counts_get, rsvp_counts_set},
         event::{
-            count_event_rsvps, extract_event_details, get_event_rsvp_counts,
+            extract_event_details, format_location, get_event_rsvp_counts,
             model::{Event, EventWithRole},
         },
         handle::{handles_by_did, model::Handle},
-        StoragePool,
+        label::label_values_by_subject,
+        CachePool, StoragePool,
     },
 };
 
@@ -38,11 +44,27 @@ pub struct EventView {
 
     pub organizer_did: String,
     pub organizer_display_name: String,
+    /// The organizer's Bluesky display name and avatar, when their profile
+    /// has been fetched.
+    pub organizer_name: Option<String>,
+    pub organizer_avatar: Option<String>,
 
     pub starts_at_machine: Option<String>,
     pub starts_at_human: Option<String>,
     pub ends_at_machine: Option<String>,
     pub ends_at_human: Option<String>,
+    pub rsvp_closes_at_human: Option<String>,
+    pub doors_open_at_human: Option<String>,
+    /// The organizer's limit on going RSVPs, if any.
+    pub rsvp_capacity: Option<i32>,
+
+    /// The time zone the organizer picked for the event, which its times
+    /// are shown in.
+    pub timezone: Option<String>,
+    /// The start and end in the viewer's own time zone, when it isn't the
+    /// time zone of the event.
+    pub viewer_starts_at_human: Option<String>,
+    pub viewer_ends_at_human: Option<String>,
 
     pub name: String,
     pub description: Option<String>,
@@ -55,7 +77,14 @@ pub struct EventView {
     pub mode: Option<String>,
     pub status: Option<String>,
     pub address_display: Option<String>,
+    pub location_url: Option<String>,
     pub links: Vec<(String, Option<String>)>, // (uri, name)
+    pub header_image_url: Option<String>,
+    /// The topics of the event, which link to their topic pages.
+    pub tags: Vec<String>,
+    /// A label on the event or its organizer that the label policy shows a
+    /// warning for.
+    pub content_warning: Option<String>,
 }
 
 impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
@@ -64,20 +93,27 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
     fn try_from(
         (viewer, organizer, event): (Option<&Handle>, Option<&Handle>, &Event),
     ) -> Result<Self, Self::Error> {
-        // Time zones are used to display date/time values from the perspective
-        // of the viewer. The timezone is selected with this priority:
-        // 1. If the viewer is a logged in user, use their time zone
-        // 2. If the event has a starts at, use the time zone associated with it (not possible with current model)
-        // 3. If the event has a ends at, use the time zone associated with it (not possible with current model)
-        // 4. If the event organizer is known and has a time zone set
-        // 5. UTC
-
-        let tz = match (viewer, organizer) {
-            (Some(handle), _) => handle.tz.parse::<Tz>().ok(),
-            (_, Some(handle)) => handle.tz.parse::<Tz>().ok(),
-            _ => None,
-        }
-        .unwrap_or(Tz::UTC);
+        // Extract event details using our new helper
+        let details = extract_event_details(event);
+
+        // Time zones are used to display date/time values. The timezone is
+        // selected with this priority:
+        // 1. If the organizer picked a time zone for the event, use it
+        // 2. If the viewer is a logged in user, use their time zone
+        // 3. If the event organizer is known and has a time zone set
+        // 4. UTC
+        // Viewers in another time zone also get the times in their own.
+
+        let viewer_tz = viewer.and_then(|handle| handle.tz.parse::<Tz>().ok());
+        let event_tz = details
+            .timezone
+            .as_deref()
+            .and_then(|value| value.parse::<Tz>().ok());
+
+        let tz = event_tz
+            .or(viewer_tz)
+            .or_else(|| organizer.and_then(|handle| handle.tz.parse::<Tz>().ok()))
+            .unwrap_or(Tz::UTC);
 
         let (repository, collection, rkey) = parse_aturi(event.aturi.as_str())?;
 
@@ -90,9 +126,8 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
         let organizer_display_name = organizer
             .map(|value| value.handle.clone())
             .unwrap_or_else(|| organizer_did.clone());
-
-        // Extract event details using our new helper
-        let details = extract_event_details(event);
+        let organizer_name = organizer.and_then(|value| value.display_name.clone());
+        let organizer_avatar = organizer.and_then(|value| value.avatar.clone());
 
         // Clean the name and description
         let event_name = Builder::new()
@@ -107,6 +142,14 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
                 .to_string(),
         );
 
+        // The description is shown with its mentions and links, which the
+        // short description used in previews and meta tags leaves out
+        let description_html = render_description(
+            &details.description,
+            &details.description_facets,
+            &Default::default(),
+        );
+
         // Simplify mode and status strings
         let mode = details.mode.as_deref().map(|mode_str| {
             if mode_str.contains("inperson") {
@@ -146,6 +189,7 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
         let description_short = description
             .as_ref()
             .map(|value| truncate_text(value, 200, Some("...".to_string())).to_string());
+        let description = Some(description_html);
 
         let starts_at_human = starts_at.as_ref().map(|value| {
             value
@@ -157,32 +201,68 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
             .as_ref()
             .map(|value| value.with_timezone(&tz).to_string());
 
-        let ends_at_machine = ends_at.as_ref().map(|value| {
+        let ends_at_human = ends_at.as_ref().map(|value| {
             value
                 .with_timezone(&tz)
                 .format("%e %B %Y %I:%M %P %Z")
                 .to_string()
         });
-        let ends_at_human = ends_at
+        let ends_at_machine = ends_at
             .as_ref()
             .map(|value| value.with_timezone(&tz).to_string());
 
+        let viewer_tz = viewer_tz.filter(|value| *value != tz);
+        let viewer_starts_at_human = starts_at.as_ref().zip(viewer_tz).map(|(value, viewer_tz)| {
+            value
+                .with_timezone(&viewer_tz)
+                .format("%e %B %Y %I:%M %P %Z")
+                .to_string()
+        });
+        let viewer_ends_at_human = ends_at.as_ref().zip(viewer_tz).map(|(value, viewer_tz)| {
+            value
+                .with_timezone(&viewer_tz)
+                .format("%e %B %Y %I:%M %P %Z")
+                .to_string()
+        });
+
+        let doors_open_at_human = details
+            .doors_open_at
+            .as_ref()
+            .map(|value| value.with_timezone(&tz).format("%I:%M %P %Z").to_string());
+
+        let rsvp_closes_at_human = event.rsvp_closes_at.as_ref().map(|value| {
+            value
+                .with_timezone(&tz)
+                .format("%e %B %Y %I:%M %P %Z")
+                .to_string()
+        });
+
         let site_url = if event.lexicon == LexiconCommunityEventNSID {
             format!("/{}/{}", repository, rkey)
         } else {
             format!("/{}/{}?collection={}", repository, rkey, event.lexicon)
         };
 
-        // Format address if an Address location is found
-        let address_display = details.locations.iter()
-            .filter_map(|loc| {
-                if let crate::atproto::lexicon::community::lexicon::calendar::event::EventLocation::Address(address) = loc {
-                    Some(crate::storage::event::format_address(address))
-                } else {
-                    None
-                }
+        // Format the first address, geo or Foursquare location found. Geo and
+        // Foursquare locations also link straight to the place.
+        let (address_display, location_url) = details
+            .locations
+            .iter()
+            .find_map(|loc| {
+                let display = format_location(loc)?;
+                let url = match loc {
+                    EventLocation::Geo(geo) => geo.coordinates().map(|(latitude, longitude)| {
+                        format!(
+                            "https://www.openstreetmap.org/?mlat={}&mlon={}#map=17/{}/{}",
+                            latitude, longitude, latitude, longitude
+                        )
+                    }),
+                    EventLocation::Fsq(fsq) => Some(fsq.place_url()),
+                    _ => None,
+                };
+                Some((Some(display), url))
             })
-            .next(); // Take the first address found
+            .unwrap_or_default();
 
         // Extract links from EventLink objects
         let links = details.uris.iter()
@@ -195,6 +275,12 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
             })
             .collect::<Vec<_>>();
 
+        // The header image is served by the organizer's PDS, so it can only be
+        // shown once the organizer is known.
+        let header_image_url = organizer.and_then(|handle| {
+            header_image(&event.record.0).map(|blob| blob_url(&handle.pds, &handle.did, blob.cid()))
+        });
+
         Ok(EventView {
             site_url,
             aturi: event.aturi.clone(),
@@ -203,10 +289,18 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
             collection,
             organizer_did,
             organizer_display_name,
+            organizer_name,
+            organizer_avatar,
             starts_at_machine,
             starts_at_human,
             ends_at_machine,
             ends_at_human,
+            rsvp_closes_at_human,
+            doors_open_at_human,
+            rsvp_capacity: event.rsvp_capacity,
+            timezone: event_tz.map(|value| value.name().to_string()),
+            viewer_starts_at_human,
+            viewer_ends_at_human,
             name,
             description,
             description_short,
@@ -216,7 +310,11 @@ impl TryFrom<(Option<&Handle>, Option<&Handle>, &Event)> for EventView {
             mode,
             status,
             address_display,
+            location_url,
             links,
+            header_image_url,
+            tags: details.tags.clone(),
+            content_warning: None,
         })
     }
 }
@@ -237,6 +335,76 @@ pub async fn hydrate_event_organizers(
         .map_err(|err| err.into())
 }
 
+/// Shows the mentions in the description of an event with the current
+/// handles of the mentioned accounts.
+pub async fn hydrate_event_description(
+    pool: &StoragePool,
+    event_view: &mut EventView,
+    event: &Event,
+) -> Result<()> {
+    let details = extract_event_details(event);
+    let dids = mentioned_dids(&details.description_facets);
+    if dids.is_empty() {
+        return Ok(());
+    }
+
+    let handles = handles_by_did(pool, dids)
+        .await?
+        .into_values()
+        .map(|handle| (handle.did, handle.handle))
+        .collect();
+
+    event_view.description = Some(render_description(
+        &details.description,
+        &details.description_facets,
+        &handles,
+    ));
+
+    Ok(())
+}
+
+/// Applies the label policy to events with the labels stored for them and
+/// their organizers. Events with a label that hides them are removed, and
+/// the others get the label that warns about them, if any.
+pub async fn hydrate_event_labels(
+    pool: &StoragePool,
+    policy: &LabelPolicy,
+    events: &mut Vec<EventView>,
+) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let subjects = events
+        .iter()
+        .flat_map(|event| [event.aturi.clone(), event.organizer_did.clone()])
+        .collect::<Vec<_>>();
+    let labels = label_values_by_subject(pool, &subjects).await?;
+    if labels.is_empty() {
+        return Ok(());
+    }
+
+    events.retain_mut(|event| {
+        let event_labels = [labels.get(&event.aturi), labels.get(&event.organizer_did)]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect::<Vec<&String>>();
+
+        match policy.action(&event_labels) {
+            Some((LabelAction::Hide, _)) => false,
+            Some((LabelAction::Warn, label)) => {
+                event.content_warning = Some(label);
+                true
+            }
+            None => true,
+        }
+    });
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(events = events.len()))]
 pub async fn hydrate_event_rsvp_counts(
     pool: &StoragePool,
     events: &mut [EventView],
@@ -245,36 +413,81 @@ pub async fn hydrate_event_rsvp_counts(
         return Ok(());
     }
     let aturis = events.iter().map(|e| e.aturi.clone()).collect::<Vec<_>>();
-    let res = get_event_rsvp_counts(pool, aturis).await;
-
-    match res {
-        Ok(counts) => {
-            for event in events.iter_mut() {
-                let key_going = (event.aturi.clone(), "going".to_string());
-                let key_interested = (event.aturi.clone(), "interested".to_string());
-                let key_notgoing = (event.aturi.clone(), "notgoing".to_string());
-
-                event.count_going = counts.get(&key_going).cloned().unwrap_or(0) as u32;
-                event.count_interested = counts.get(&key_interested).cloned().unwrap_or(0) as u32;
-                event.count_notgoing = counts.get(&key_notgoing).cloned().unwrap_or(0) as u32;
-            }
-            Ok(())
+    let counts = get_event_rsvp_counts(pool, aturis)
+        .await
+        .map_err(|err| EventViewError::FailedToHydrateRsvpCounts(err.to_string()))?;
+
+    for event in events.iter_mut() {
+        let key_going = (event.aturi.clone(), "going".to_string());
+        let key_interested = (event.aturi.clone(), "interested".to_string());
+        let key_notgoing = (event.aturi.clone(), "notgoing".to_string());
+
+        event.count_going = counts.get(&key_going).cloned().unwrap_or(0) as u32;
+        event.count_interested = counts.get(&key_interested).cloned().unwrap_or(0) as u32;
+        event.count_notgoing = counts.get(&key_notgoing).cloned().unwrap_or(0) as u32;
+    }
+
+    Ok(())
+}
+
+/// Hydrates RSVP counts from the Redis counters where they are cached, and
+/// counts the rest in the database, caching them for the next request. Hot
+/// events like the ones on the front page are then counted once every few
+/// minutes instead of on every view. If the cache can't be used, every event
+/// is counted in the database.
+pub async fn hydrate_event_rsvp_counts_cached(
+    pool: &StoragePool,
+    cache_pool: &CachePool,
+    events: &mut [EventView],
+) -> Result<(), anyhow::Error> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let aturis = events.iter().map(|e| e.aturi.clone()).collect::<Vec<_>>();
+
+    let mut cached = match rsvp_counts_get(cache_pool, &aturis).await {
+        Ok(values) => values,
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to read cached rsvp counts");
+            return hydrate_event_rsvp_counts(pool, events).await;
         }
-        Err(StorageError::CannotBeginDatabaseTransaction(_)) => {
-            // Fall back to individual counts if the batched query fails
-            for event in events.iter_mut() {
-                event.count_going = count_event_rsvps(pool, &event.aturi, "going")
-                    .await
-                    .unwrap_or_default();
-                event.count_interested = count_event_rsvps(pool, &event.aturi, "interested")
-                    .await
-                    .unwrap_or_default();
-                event.count_notgoing = count_event_rsvps(pool, &event.aturi, "notgoing")
-                    .await
-                    .unwrap_or_default();
+    };
+
+    let missing = aturis
+        .into_iter()
+        .filter(|aturi| !cached.contains_key(aturi))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        let counts = get_event_rsvp_counts(pool, missing.clone())
+            .await
+            .map_err(|err| EventViewError::FailedToHydrateRsvpCounts(err.to_string()))?;
+
+        let mut found: HashMap<String, HashMap<String, i64>> = missing
+            .into_iter()
+            .map(|aturi| (aturi, HashMap::default()))
+            .collect();
+        for ((aturi, status), count) in counts {
+            if let Some(status_counts) = found.get_mut(&aturi) {
+                status_counts.insert(status, count);
             }
-            Ok(())
         }
-        Err(e) => Err(EventViewError::FailedToHydrateRsvpCounts(e.to_string()).into()),
+
+        if let Err(err) = rsvp_counts_set(cache_pool, &found).await {
+            tracing::warn!(error = ?err, "unable to cache rsvp counts");
+        }
+        cached.extend(found);
     }
+
+    for event in events.iter_mut() {
+        let Some(counts) = cached.get(&event.aturi) else {
+            continue;
+        };
+        let count = |status: &str| counts.get(status).copied().unwrap_or(0).max(0) as u32;
+
+        event.count_going = count("going");
+        event.count_interested = count("interested");
+        event.count_notgoing = count("notgoing");
+    }
+
+    Ok(())
 }
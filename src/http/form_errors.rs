@@ -0,0 +1,255 @@
+//! Stable anchors for form fields and their validation errors.
+//!
+//! Forms keep the error of a field in a `{field}_error` member next to the
+//! field. Each form lists the fields that can have errors, and templates use
+//! the `field_id` and `error_id` functions to give inputs and error messages
+//! ids that don't depend on the locale, so that error summaries can link to
+//! failing fields and inputs can point `aria-describedby` at their errors.
+
+use minijinja::{Environment, Error, ErrorKind, Value};
+use serde::Serialize;
+
+use super::{
+    event_form::{BuildEventForm, BuildLinkForm, BuildLocationForm, BuildStartsForm},
+    rsvp_form::BuildRSVPForm,
+};
+
+/// A form whose fields can have validation errors.
+pub trait FormErrors: Serialize {
+    /// Prefixes the ids of the form's fields.
+    const FORM_ID: &'static str;
+
+    /// The fields that can have errors, in the order they appear in the form.
+    const FIELDS: &'static [&'static str];
+
+    /// Returns the errors of the form, in the order of `FIELDS`.
+    fn field_errors(&self) -> Vec<FieldError> {
+        collect_errors(Self::FORM_ID, Self::FIELDS, &Value::from_serialize(self))
+    }
+}
+
+/// An error of a form field, with the ids of the field and of its message.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub field_id: String,
+    pub error_id: String,
+    pub message: String,
+}
+
+impl FormErrors for BuildEventForm {
+    const FORM_ID: &'static str = "event";
+    const FIELDS: &'static [&'static str] = &[
+        "name",
+        "description",
+        "tags",
+        "status",
+        "doors_open_at",
+        "mode",
+        "header_image",
+    ];
+}
+
+impl FormErrors for BuildStartsForm {
+    const FORM_ID: &'static str = "starts";
+    const FIELDS: &'static [&'static str] =
+        &["tz", "starts_at", "doors_time", "ends_date", "ends_at"];
+}
+
+impl FormErrors for BuildLocationForm {
+    const FORM_ID: &'static str = "location";
+    const FIELDS: &'static [&'static str] = &[
+        "location_country",
+        "location_name",
+        "location_street",
+        "location_locality",
+        "location_region",
+        "location_postal_code",
+        "location_latitude",
+        "location_longitude",
+        "location_fsq_place_id",
+    ];
+}
+
+impl FormErrors for BuildLinkForm {
+    const FORM_ID: &'static str = "link";
+    const FIELDS: &'static [&'static str] = &["link_name", "link_value"];
+}
+
+impl FormErrors for BuildRSVPForm {
+    const FORM_ID: &'static str = "rsvp";
+    const FIELDS: &'static [&'static str] = &["subject_aturi", "subject_cid", "status"];
+}
+
+/// Returns the fields of the form with the given id, for templates that only
+/// have the serialized form.
+pub fn form_fields(form_id: &str) -> Option<&'static [&'static str]> {
+    match form_id {
+        BuildEventForm::FORM_ID => Some(BuildEventForm::FIELDS),
+        BuildStartsForm::FORM_ID => Some(BuildStartsForm::FIELDS),
+        BuildLocationForm::FORM_ID => Some(BuildLocationForm::FIELDS),
+        BuildLinkForm::FORM_ID => Some(BuildLinkForm::FIELDS),
+        BuildRSVPForm::FORM_ID => Some(BuildRSVPForm::FIELDS),
+        _ => None,
+    }
+}
+
+/// Returns the id of a field's input, such as `event-name`. Fields that
+/// repeat the form id, like `location_name` of the location form, don't
+/// repeat it in the id.
+pub fn field_id(form_id: &str, field: &str) -> String {
+    let field = field
+        .strip_prefix(form_id)
+        .and_then(|value| value.strip_prefix('_'))
+        .unwrap_or(field);
+    format!("{}-{}", form_id, field.replace('_', "-"))
+}
+
+/// Returns the id of the message shown for a field's error.
+pub fn error_id(form_id: &str, field: &str) -> String {
+    format!("{}-error", field_id(form_id, field))
+}
+
+fn collect_errors(form_id: &str, fields: &'static [&'static str], form: &Value) -> Vec<FieldError> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let message = form.get_attr(&format!("{}_error", field)).ok()?;
+            let message = message.as_str().filter(|value| !value.is_empty())?;
+            Some(FieldError {
+                field,
+                field_id: field_id(form_id, field),
+                error_id: error_id(form_id, field),
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Template filter that returns the errors of a serialized form, as in
+/// `build_event_form | form_errors("event")`.
+fn form_errors_filter(form: Value, form_id: &str) -> Result<Value, Error> {
+    let fields = form_fields(form_id).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("unknown form: {}", form_id),
+        )
+    })?;
+    Ok(Value::from_serialize(collect_errors(
+        form_id, fields, &form,
+    )))
+}
+
+/// Adds the `form_errors` filter and the `field_id` and `error_id` functions
+/// to a template environment.
+pub fn register_form_errors(env: &mut Environment<'_>) {
+    env.add_filter("form_errors", form_errors_filter);
+    env.add_function("field_id", |form_id: &str, field: &str| {
+        field_id(form_id, field)
+    });
+    env.add_function("error_id", |form_id: &str, field: &str| {
+        error_id(form_id, field)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_id() {
+        assert_eq!(field_id("event", "name"), "event-name");
+        assert_eq!(field_id("event", "header_image"), "event-header-image");
+        assert_eq!(field_id("location", "location_name"), "location-name");
+        assert_eq!(field_id("link", "link_value"), "link-value");
+        assert_eq!(field_id("starts", "starts_at"), "starts-at");
+        assert_eq!(field_id("rsvp", "subject_aturi"), "rsvp-subject-aturi");
+        assert_eq!(error_id("event", "name"), "event-name-error");
+    }
+
+    #[test]
+    fn test_fields_have_errors() {
+        let event_form = BuildEventForm::default();
+        let forms = [
+            (
+                BuildEventForm::FORM_ID,
+                serde_json::to_value(&event_form).unwrap(),
+            ),
+            (
+                BuildStartsForm::FORM_ID,
+                serde_json::to_value(BuildStartsForm::from(event_form.clone())).unwrap(),
+            ),
+            (
+                BuildLocationForm::FORM_ID,
+                serde_json::to_value(BuildLocationForm::from(event_form.clone())).unwrap(),
+            ),
+            (
+                BuildLinkForm::FORM_ID,
+                serde_json::to_value(BuildLinkForm::from(event_form)).unwrap(),
+            ),
+        ];
+
+        for (form_id, form) in forms {
+            for field in form_fields(form_id).unwrap() {
+                let key = format!("{}_error", field);
+                assert!(form.get(&key).is_some(), "{form_id} has no {key}");
+            }
+        }
+        assert!(form_fields("unknown").is_none());
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let event_form = BuildEventForm {
+            name_error: Some("Invalid Name".to_string()),
+            mode_error: Some("Invalid Mode".to_string()),
+            description_error: Some(String::new()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event_form.field_errors(),
+            vec![
+                FieldError {
+                    field: "name",
+                    field_id: "event-name".to_string(),
+                    error_id: "event-name-error".to_string(),
+                    message: "Invalid Name".to_string(),
+                },
+                FieldError {
+                    field: "mode",
+                    field_id: "event-mode".to_string(),
+                    error_id: "event-mode-error".to_string(),
+                    message: "Invalid Mode".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_form_errors_template() {
+        let mut env = Environment::new();
+        register_form_errors(&mut env);
+        env.add_template(
+            "form",
+            "{% for error in form | form_errors('event') %}\
+             <a href=\"#{{ error.field_id }}\">{{ error.message }}</a>\
+             {% endfor %}|{{ error_id('location', 'location_street') }}",
+        )
+        .unwrap();
+
+        let event_form = BuildEventForm {
+            status_error: Some("Invalid Status".to_string()),
+            ..Default::default()
+        };
+        let rendered = env
+            .get_template("form")
+            .unwrap()
+            .render(minijinja::context! { form => event_form })
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "<a href=\"#event-status\">Invalid Status</a>|location-street-error"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use axum::{extract::Query, response::IntoResponse};
+use axum_template::RenderHtml;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::{admin_template_context, AdminRequestContext},
+        errors::WebError,
+        pagination::{Pagination, PaginationView},
+    },
+    select_template,
+    storage::audit_log::{audit_log_list, model::AUDIT_ACTIONS},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub action: Option<String>,
+    pub actor: Option<String>,
+}
+
+pub async fn handle_admin_audit_log(
+    admin_ctx: AdminRequestContext,
+    pagination: Query<Pagination>,
+    Query(filter): Query<AuditLogQuery>,
+) -> Result<impl IntoResponse, WebError> {
+    let canonical_url = format!(
+        "https://{}/admin/audit-log",
+        admin_ctx.web_context.config.external_base
+    );
+    let default_context = admin_template_context(&admin_ctx, &canonical_url);
+
+    let render_template = select_template!("admin_audit_log", false, false, admin_ctx.language);
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let (page, page_size) = pagination.admin_clamped();
+
+    // Empty filters from the form show every entry
+    let action = filter
+        .action
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let actor = filter
+        .actor
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+
+    let entries = audit_log_list(&admin_ctx.web_context.pool, action, actor, page, page_size).await;
+    if let Err(err) = entries {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+    let (total_count, mut entries) = entries.unwrap();
+
+    let mut params: Vec<(&str, &str)> = vec![];
+    if let Some(action) = action {
+        params.push(("action", action));
+    }
+    if let Some(actor) = actor {
+        params.push(("actor", actor));
+    }
+
+    let pagination_view = PaginationView::new(page_size, entries.len() as i64, page, params);
+
+    if entries.len() > page_size as usize {
+        entries.truncate(page_size as usize);
+    }
+
+    Ok(RenderHtml(
+        &render_template,
+        admin_ctx.web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            entries,
+            total_count,
+            actions => AUDIT_ACTIONS,
+            action,
+            actor,
+            pagination => pagination_view,
+        }},
+    )
+    .into_response())
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use axum::{
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use axum_template::RenderHtml;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::{admin_template_context, AdminRequestContext},
+        errors::{AdminBannerError, WebError},
+    },
+    select_template,
+    storage::banner::{banner_delete, banner_insert, banner_list, model::BANNER_LEVELS},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct BannerAddForm {
+    pub message: String,
+    pub level: String,
+    /// Optional start from a `datetime-local` input, interpreted as UTC.
+    /// Banners without a start are shown right away.
+    pub starts_at: Option<String>,
+    /// Optional end from a `datetime-local` input, interpreted as UTC.
+    pub ends_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BannerRemoveForm {
+    pub id: String,
+}
+
+pub async fn handle_admin_banners(
+    admin_ctx: AdminRequestContext,
+) -> Result<impl IntoResponse, WebError> {
+    let canonical_url = format!(
+        "https://{}/admin/banners",
+        admin_ctx.web_context.config.external_base
+    );
+    let default_context = admin_template_context(&admin_ctx, &canonical_url);
+
+    let render_template = select_template!("admin_banners", false, false, admin_ctx.language);
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let banners = match banner_list(&admin_ctx.web_context.pool).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let now = Utc::now();
+    let banners = banners
+        .iter()
+        .map(|banner| {
+            let state = if banner.starts_at > now {
+                "scheduled"
+            } else if banner.ends_at.is_some_and(|ends_at| ends_at <= now) {
+                "ended"
+            } else {
+                "active"
+            };
+            template_context! {
+                state,
+                ..minijinja::Value::from_serialize(banner)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(RenderHtml(
+        &render_template,
+        admin_ctx.web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            banners,
+            levels => BANNER_LEVELS,
+        }},
+    )
+    .into_response())
+}
+
+pub async fn handle_admin_banners_add(
+    admin_ctx: AdminRequestContext,
+    Form(form): Form<BannerAddForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let validated = validate_banner_form(&form, Utc::now());
+    if let Err(err) = validated {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+    let (starts_at, ends_at) = validated.unwrap();
+
+    if let Err(err) = banner_insert(
+        &admin_ctx.web_context.pool,
+        form.message.trim(),
+        &form.level,
+        starts_at,
+        ends_at,
+        &admin_ctx.admin_handle.did,
+    )
+    .await
+    {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+
+    Ok(Redirect::to("/admin/banners").into_response())
+}
+
+pub async fn handle_admin_banners_remove(
+    admin_ctx: AdminRequestContext,
+    Form(form): Form<BannerRemoveForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    if let Err(err) = banner_delete(&admin_ctx.web_context.pool, &form.id).await {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+
+    Ok(Redirect::to("/admin/banners").into_response())
+}
+
+/// Checks the banner fields, returning when the banner starts and ends.
+fn validate_banner_form(
+    form: &BannerAddForm,
+    now: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, Option<DateTime<Utc>>), AdminBannerError> {
+    if form.message.trim().is_empty() {
+        return Err(AdminBannerError::EmptyMessage);
+    }
+
+    if !BANNER_LEVELS.contains(&form.level.as_str()) {
+        return Err(AdminBannerError::InvalidLevel(form.level.clone()));
+    }
+
+    let starts_at = parse_datetime(form.starts_at.as_deref())?.unwrap_or(now);
+    let ends_at = parse_datetime(form.ends_at.as_deref())?;
+
+    if let Some(ends_at) = ends_at {
+        if ends_at <= starts_at || ends_at <= now {
+            return Err(AdminBannerError::InvalidSchedule(
+                form.ends_at.clone().unwrap_or_default(),
+            ));
+        }
+    }
+
+    Ok((starts_at, ends_at))
+}
+
+fn parse_datetime(value: Option<&str>) -> Result<Option<DateTime<Utc>>, AdminBannerError> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+            .map(|value| Some(value.and_utc()))
+            .map_err(|_| AdminBannerError::InvalidSchedule(value.to_string())),
+    }
+}
@@ -5,6 +5,7 @@ use axum::{
     Form,
 };
 use axum_template::RenderHtml;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use minijinja::context as template_context;
 use serde::Deserialize;
 use std::borrow::Cow;
@@ -13,17 +14,29 @@ use crate::{
     contextual_error,
     http::{
         context::{admin_template_context, AdminRequestContext},
-        errors::WebError,
+        errors::{AdminDenylistError, WebError},
         pagination::{Pagination, PaginationView},
     },
     select_template,
-    storage::denylist::{denylist_add_or_update, denylist_list, denylist_remove},
+    storage::{
+        audit_log::{
+            audit_log_insert,
+            model::{AUDIT_ACTION_DENYLIST_ADD, AUDIT_ACTION_DENYLIST_REMOVE},
+        },
+        denylist::{
+            denylist_add_or_update, denylist_list, denylist_remove, model::DENYLIST_CATEGORIES,
+        },
+    },
 };
 
 #[derive(Debug, Deserialize)]
 pub struct DenylistAddForm {
     pub subject: String,
     pub reason: String,
+    pub category: String,
+    /// Optional expiry from a `datetime-local` input, interpreted as UTC.
+    pub expires_at: Option<String>,
+    pub appeal_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,9 +44,17 @@ pub struct DenylistRemoveForm {
     pub subject: String,
 }
 
+/// Prefills the add form, so that other admin pages can link to it with a
+/// DID or URI to block.
+#[derive(Debug, Deserialize)]
+pub struct DenylistPrefillQuery {
+    pub subject: Option<String>,
+}
+
 pub async fn handle_admin_denylist(
     admin_ctx: AdminRequestContext,
     pagination: Query<Pagination>,
+    Query(prefill): Query<DenylistPrefillQuery>,
 ) -> Result<impl IntoResponse, WebError> {
     let canonical_url = format!(
         "https://{}/admin/denylist",
@@ -66,13 +87,26 @@ pub async fn handle_admin_denylist(
         entries.truncate(page_size as usize);
     }
 
+    let now = Utc::now();
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            template_context! {
+                expired => entry.expires_at.is_some_and(|expires_at| expires_at <= now),
+                ..minijinja::Value::from_serialize(entry)
+            }
+        })
+        .collect::<Vec<_>>();
+
     Ok(RenderHtml(
         &render_template,
         admin_ctx.web_context.engine.clone(),
         template_context! { ..default_context, ..template_context! {
             entries,
             total_count,
+            categories => DENYLIST_CATEGORIES,
             pagination => pagination_view,
+            subject => prefill.subject,
         }},
     )
     .into_response())
@@ -84,10 +118,25 @@ pub async fn handle_admin_denylist_add(
 ) -> Result<impl IntoResponse, WebError> {
     let error_template = select_template!(false, false, admin_ctx.language);
 
+    let validated = validate_denylist_form(&form);
+    if let Err(err) = validated {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+    let (expires_at, appeal_url) = validated.unwrap();
+
     if let Err(err) = denylist_add_or_update(
         &admin_ctx.web_context.pool,
         Cow::Borrowed(&form.subject),
         Cow::Borrowed(&form.reason),
+        &form.category,
+        expires_at,
+        appeal_url.as_deref(),
     )
     .await
     {
@@ -100,6 +149,17 @@ pub async fn handle_admin_denylist_add(
         );
     }
 
+    if let Err(err) = audit_log_insert(
+        &admin_ctx.web_context.pool,
+        &admin_ctx.admin_handle.did,
+        AUDIT_ACTION_DENYLIST_ADD,
+        &form.subject,
+    )
+    .await
+    {
+        tracing::error!(error = ?err, "unable to record audit log entry");
+    }
+
     Ok(Redirect::to("/admin/denylist").into_response())
 }
 
@@ -119,5 +179,49 @@ pub async fn handle_admin_denylist_remove(
         );
     }
 
+    if let Err(err) = audit_log_insert(
+        &admin_ctx.web_context.pool,
+        &admin_ctx.admin_handle.did,
+        AUDIT_ACTION_DENYLIST_REMOVE,
+        &form.subject,
+    )
+    .await
+    {
+        tracing::error!(error = ?err, "unable to record audit log entry");
+    }
+
     Ok(Redirect::to("/admin/denylist").into_response())
 }
+
+/// Checks the optional denylist fields, returning the parsed expiry and the
+/// normalized appeal URL.
+fn validate_denylist_form(
+    form: &DenylistAddForm,
+) -> Result<(Option<DateTime<Utc>>, Option<String>), AdminDenylistError> {
+    if !DENYLIST_CATEGORIES.contains(&form.category.as_str()) {
+        return Err(AdminDenylistError::InvalidCategory(form.category.clone()));
+    }
+
+    let expires_at = match form.expires_at.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => {
+            let expires_at = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+                .map_err(|_| AdminDenylistError::InvalidExpiry(value.to_string()))?
+                .and_utc();
+            if expires_at <= Utc::now() {
+                return Err(AdminDenylistError::InvalidExpiry(value.to_string()));
+            }
+            Some(expires_at)
+        }
+    };
+
+    let appeal_url = match form.appeal_url.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => match url::Url::parse(value) {
+            Ok(url) if url.scheme() == "https" || url.scheme() == "http" => Some(url.to_string()),
+            _ => return Err(AdminDenylistError::InvalidAppealUrl(value.to_string())),
+        },
+    };
+
+    Ok((expires_at, appeal_url))
+}
@@ -1,22 +1,37 @@
 use anyhow::Result;
-use axum::{extract::Query, response::IntoResponse};
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use axum_htmx::{HxRedirect, HxRequest};
 use axum_template::RenderHtml;
+use http::StatusCode;
 use minijinja::context as template_context;
+use serde::Deserialize;
 
 use crate::{
     contextual_error,
     http::{
         context::AdminRequestContext,
         errors::WebError,
-        pagination::{Pagination, PaginationView},
+        pagination::{Cursor, CursorPagination},
     },
     select_template,
-    storage::event::event_list,
+    storage::{
+        audit_log::{audit_log_insert, model::AUDIT_ACTION_EVENT_DELETE},
+        event::{event_count, event_delete, event_list},
+    },
 };
 
+#[derive(Debug, Deserialize)]
+pub struct EventDeleteForm {
+    pub aturi: String,
+}
+
 pub async fn handle_admin_events(
     admin_ctx: AdminRequestContext,
-    pagination: Query<Pagination>,
+    pagination: Query<CursorPagination>,
 ) -> Result<impl IntoResponse, WebError> {
     let language = admin_ctx.language;
     let web_context = admin_ctx.web_context;
@@ -31,9 +46,19 @@ pub async fn handle_admin_events(
     let render_template = select_template!("admin_events", false, false, language);
     let error_template = select_template!(false, false, language);
 
-    let (page, page_size) = pagination.admin_clamped();
+    let limit = pagination.clamped_limit(20, 20, 100);
+
+    // Unreadable cursors start from the first page
+    let after = pagination.cursor.as_deref().and_then(Cursor::decode);
+    let is_first_page = after.is_none();
 
-    let events = event_list(&web_context.pool, page, page_size).await;
+    let events = event_list(
+        &web_context.pool,
+        None,
+        after.as_ref().map(Cursor::after),
+        limit,
+    )
+    .await;
     if let Err(err) = events {
         return contextual_error!(
             web_context,
@@ -43,15 +68,26 @@ pub async fn handle_admin_events(
             err
         );
     }
-    let (total_count, mut events) = events.unwrap();
-
-    let params: Vec<(&str, &str)> = vec![];
+    let mut events = events.unwrap();
 
-    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+    let total_count = match event_count(&web_context.pool).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                web_context,
+                language.0,
+                error_template,
+                default_context,
+                err
+            )
+        }
+    };
 
-    if events.len() > page_size as usize {
-        events.truncate(page_size as usize);
-    }
+    let next_cursor = Cursor::next_page(&mut events, limit, |event| {
+        event
+            .updated_at
+            .map(|updated_at| Cursor::new(updated_at, &event.aturi))
+    });
 
     Ok(RenderHtml(
         &render_template,
@@ -62,8 +98,66 @@ pub async fn handle_admin_events(
             canonical_url => canonical_url,
             events => events,
             total_count => total_count,
-            pagination => pagination_view,
+            is_first_page => is_first_page,
+            next_cursor => next_cursor,
         },
     )
     .into_response())
 }
+
+/// Removes an event and its RSVPs from the index. The record itself stays in
+/// the organizer's repository.
+pub async fn handle_admin_events_delete(
+    admin_ctx: AdminRequestContext,
+    HxRequest(hx_request): HxRequest,
+    Form(form): Form<EventDeleteForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    match event_delete(&admin_ctx.web_context.pool, &form.aturi).await {
+        Ok(rsvps_deleted) => {
+            tracing::info!(
+                aturi = form.aturi,
+                rsvps_deleted,
+                admin = admin_ctx.admin_handle.did,
+                "admin deleted event"
+            );
+            if let Err(err) = audit_log_insert(
+                &admin_ctx.web_context.pool,
+                &admin_ctx.admin_handle.did,
+                AUDIT_ACTION_EVENT_DELETE,
+                &form.aturi,
+            )
+            .await
+            {
+                tracing::error!(error = ?err, "unable to record audit log entry");
+            }
+        }
+        Err(err) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+    }
+
+    if hx_request {
+        let hx_redirect = HxRedirect::try_from("/admin/events");
+        if let Err(err) = hx_redirect {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+        let hx_redirect = hx_redirect.unwrap();
+        Ok((StatusCode::OK, hx_redirect, "").into_response())
+    } else {
+        Ok(Redirect::to("/admin/events").into_response())
+    }
+}
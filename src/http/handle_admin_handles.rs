@@ -13,15 +13,18 @@ use crate::{
     http::{
         context::{admin_template_context, AdminRequestContext},
         errors::WebError,
-        pagination::{Pagination, PaginationView},
+        pagination::{Cursor, CursorPagination},
     },
     select_template,
-    storage::handle::{handle_list, handle_nuke},
+    storage::{
+        audit_log::{audit_log_insert, model::AUDIT_ACTION_IDENTITY_NUKE},
+        handle::{handle_count, handle_list, handle_nuke},
+    },
 };
 
 pub async fn handle_admin_handles(
     admin_ctx: AdminRequestContext,
-    pagination: Query<Pagination>,
+    pagination: Query<CursorPagination>,
 ) -> Result<impl IntoResponse, WebError> {
     let canonical_url = format!(
         "https://{}/admin/handles",
@@ -32,9 +35,18 @@ pub async fn handle_admin_handles(
     let render_template = select_template!("admin_handles", false, false, admin_ctx.language);
     let error_template = select_template!(false, false, admin_ctx.language);
 
-    let (page, page_size) = pagination.admin_clamped();
+    let limit = pagination.clamped_limit(20, 20, 100);
+
+    // Unreadable cursors start from the first page
+    let after = pagination.cursor.as_deref().and_then(Cursor::decode);
+    let is_first_page = after.is_none();
 
-    let handles = handle_list(&admin_ctx.web_context.pool, page, page_size).await;
+    let handles = handle_list(
+        &admin_ctx.web_context.pool,
+        after.as_ref().map(Cursor::after),
+        limit,
+    )
+    .await;
     if let Err(err) = handles {
         return contextual_error!(
             admin_ctx.web_context,
@@ -44,15 +56,24 @@ pub async fn handle_admin_handles(
             err
         );
     }
-    let (total_count, mut handles) = handles.unwrap();
-
-    let params: Vec<(&str, &str)> = vec![];
+    let mut handles = handles.unwrap();
 
-    let pagination_view = PaginationView::new(page_size, handles.len() as i64, page, params);
+    let total_count = match handle_count(&admin_ctx.web_context.pool).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                default_context,
+                err
+            )
+        }
+    };
 
-    if handles.len() > page_size as usize {
-        handles.truncate(page_size as usize);
-    }
+    let next_cursor = Cursor::next_page(&mut handles, limit, |handle| {
+        Some(Cursor::new(handle.updated_at, &handle.did))
+    });
 
     Ok(RenderHtml(
         &render_template,
@@ -60,7 +81,8 @@ pub async fn handle_admin_handles(
         template_context! { ..default_context, ..template_context! {
             handles,
             total_count,
-            pagination => pagination_view,
+            is_first_page,
+            next_cursor,
         }},
     )
     .into_response())
@@ -101,6 +123,17 @@ pub async fn handle_admin_nuke_identity(
         );
     }
 
+    if let Err(err) = audit_log_insert(
+        &admin_ctx.web_context.pool,
+        &admin_ctx.admin_handle.did,
+        AUDIT_ACTION_IDENTITY_NUKE,
+        &did,
+    )
+    .await
+    {
+        tracing::error!(error = ?err, "unable to record audit log entry");
+    }
+
     if hx_request {
         let hx_redirect = HxRedirect::try_from("/admin/handles");
         if let Err(err) = hx_redirect {
@@ -20,7 +20,7 @@ use crate::{
     },
     resolve::{parse_input, resolve_subject, InputType},
     select_template,
-    storage::{event::event_insert_with_metadata, handle::handle_warm_up},
+    storage::{event::event_upsert_with_metadata, handle::handle_warm_up},
 };
 
 #[derive(Deserialize)]
@@ -192,8 +192,8 @@ pub async fn handle_admin_import_event(
             SmokeSignalEvent::Current { name, .. } => name.clone(),
         };
 
-        // Store event using the generic event_insert_with_metadata
-        match event_insert_with_metadata(
+        // Importing an event again only rewrites it when it has changed
+        match event_upsert_with_metadata(
             &admin_ctx.web_context.pool,
             aturi,
             &record.cid,
@@ -204,7 +204,14 @@ pub async fn handle_admin_import_event(
         )
         .await
         {
-            Ok(_) => Ok(Redirect::to("/admin/events").into_response()),
+            Ok(written) => {
+                if written {
+                    if let Some(geocoder) = &admin_ctx.web_context.geocoder {
+                        geocoder.spawn_geocode_event(&admin_ctx.web_context.pool, aturi);
+                    }
+                }
+                Ok(Redirect::to("/admin/events").into_response())
+            }
             Err(err) => {
                 contextual_error!(
                     admin_ctx.web_context,
@@ -241,8 +248,8 @@ pub async fn handle_admin_import_event(
             CommunityEventLexicon::Current { name, .. } => name.clone(),
         };
 
-        // Store event using the generic event_insert_with_metadata
-        match event_insert_with_metadata(
+        // Importing an event again only rewrites it when it has changed
+        match event_upsert_with_metadata(
             &admin_ctx.web_context.pool,
             aturi,
             &record.cid,
@@ -253,7 +260,14 @@ pub async fn handle_admin_import_event(
         )
         .await
         {
-            Ok(_) => Ok(Redirect::to("/admin/events").into_response()),
+            Ok(written) => {
+                if written {
+                    if let Some(geocoder) = &admin_ctx.web_context.geocoder {
+                        geocoder.spawn_geocode_event(&admin_ctx.web_context.pool, aturi);
+                    }
+                }
+                Ok(Redirect::to("/admin/events").into_response())
+            }
             Err(err) => {
                 contextual_error!(
                     admin_ctx.web_context,
@@ -3,7 +3,10 @@ use axum::response::IntoResponse;
 use axum_template::RenderHtml;
 use minijinja::context as template_context;
 
-use crate::http::context::{admin_template_context, AdminRequestContext};
+use crate::http::{
+    context::{admin_template_context, AdminRequestContext},
+    middleware_read_only::is_read_only,
+};
 
 use super::errors::WebError;
 
@@ -16,10 +19,14 @@ pub async fn handle_admin_index(
         admin_ctx.web_context.config.external_base
     );
 
+    let read_only = is_read_only(&admin_ctx.web_context).await;
+
     Ok(RenderHtml(
         "admin.en-us.html",
         admin_ctx.web_context.engine.clone(),
         template_context! {
+            read_only,
+            read_only_forced => admin_ctx.web_context.config.read_only,
             ..admin_template_context(&admin_ctx, &canonical_url),
         },
     )
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use axum::{extract::Query, response::IntoResponse};
+use axum_template::RenderHtml;
+use minijinja::context as template_context;
+use serde::Serialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::{admin_template_context, AdminRequestContext},
+        errors::WebError,
+        pagination::{Pagination, PaginationView},
+    },
+    select_template,
+    storage::integrity::{integrity_report_list, model::IntegrityReport},
+};
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReportView {
+    #[serde(flatten)]
+    pub report: IntegrityReport,
+
+    // Change in totals since the previous report
+    pub events_drift: Option<i64>,
+    pub rsvps_drift: Option<i64>,
+    pub handles_drift: Option<i64>,
+}
+
+pub async fn handle_admin_integrity(
+    admin_ctx: AdminRequestContext,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let canonical_url = format!(
+        "https://{}/admin/integrity",
+        admin_ctx.web_context.config.external_base
+    );
+    let default_context = admin_template_context(&admin_ctx, &canonical_url);
+
+    let render_template = select_template!("admin_integrity", false, false, admin_ctx.language);
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let (page, page_size) = pagination.admin_clamped();
+
+    let reports = match integrity_report_list(&admin_ctx.web_context.pool, page, page_size).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let params: Vec<(&str, &str)> = vec![];
+
+    let pagination_view = PaginationView::new(page_size, reports.len() as i64, page, params);
+
+    // Reports are newest first, so the previous report is the next one in the
+    // list. The extra row fetched for pagination provides the previous report
+    // for the last report on the page.
+    let reports = reports
+        .iter()
+        .enumerate()
+        .take(page_size as usize)
+        .map(|(index, report)| {
+            let previous = reports.get(index + 1);
+            IntegrityReportView {
+                report: report.clone(),
+                events_drift: previous
+                    .map(|prev| report.counts.total_events - prev.counts.total_events),
+                rsvps_drift: previous
+                    .map(|prev| report.counts.total_rsvps - prev.counts.total_rsvps),
+                handles_drift: previous
+                    .map(|prev| report.counts.total_handles - prev.counts.total_handles),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(RenderHtml(
+        &render_template,
+        admin_ctx.web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            reports,
+            pagination => pagination_view,
+        }},
+    )
+    .into_response())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use axum::{
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{context::AdminRequestContext, errors::WebError},
+    select_template,
+    storage::cache::read_only_set,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReadOnlyForm {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+pub async fn handle_admin_read_only(
+    admin_ctx: AdminRequestContext,
+    Form(form): Form<ReadOnlyForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    if let Err(err) = read_only_set(&admin_ctx.web_context.cache_pool, form.enabled).await {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+
+    tracing::info!(
+        admin_did = admin_ctx.admin_handle.did,
+        enabled = form.enabled,
+        "read-only mode changed"
+    );
+
+    Ok(Redirect::to("/admin").into_response())
+}
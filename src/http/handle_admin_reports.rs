@@ -0,0 +1,229 @@
+use anyhow::Result;
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use axum_template::RenderHtml;
+use chrono::Utc;
+use minijinja::context as template_context;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    atproto::uri::parse_aturi,
+    contextual_error,
+    http::{
+        context::{admin_template_context, AdminRequestContext},
+        errors::{AdminReportError, WebError},
+        pagination::{Pagination, PaginationView},
+    },
+    select_template,
+    storage::{
+        audit_log::{
+            audit_log_insert,
+            model::{AUDIT_ACTION_DENYLIST_ADD, AUDIT_ACTION_EVENT_DELETE},
+        },
+        denylist::{denylist_add_or_update, model::DENYLIST_CATEGORIES},
+        event::event_delete,
+        report::{report_get, report_list_open, report_resolve_event},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReportResolveForm {
+    pub id: String,
+    /// Removes the reported event and its RSVPs from the index.
+    #[serde(default)]
+    pub delete_event: bool,
+    /// Adds the creator of the reported event to the denylist.
+    #[serde(default)]
+    pub deny_creator: bool,
+    pub category: Option<String>,
+}
+
+pub async fn handle_admin_reports(
+    admin_ctx: AdminRequestContext,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let canonical_url = format!(
+        "https://{}/admin/reports",
+        admin_ctx.web_context.config.external_base
+    );
+    let default_context = admin_template_context(&admin_ctx, &canonical_url);
+
+    let render_template = select_template!("admin_reports", false, false, admin_ctx.language);
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let (page, page_size) = pagination.admin_clamped();
+
+    let reports = report_list_open(&admin_ctx.web_context.pool, page, page_size).await;
+    if let Err(err) = reports {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+    let (total_count, mut reports) = reports.unwrap();
+
+    let params: Vec<(&str, &str)> = vec![];
+
+    let pagination_view = PaginationView::new(page_size, reports.len() as i64, page, params);
+
+    if reports.len() > page_size as usize {
+        reports.truncate(page_size as usize);
+    }
+
+    Ok(RenderHtml(
+        &render_template,
+        admin_ctx.web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            reports,
+            total_count,
+            categories => DENYLIST_CATEGORIES,
+            pagination => pagination_view,
+        }},
+    )
+    .into_response())
+}
+
+/// Resolves a report and every other open report of the same event. The
+/// event can be deleted and its creator denied at the same time, and when
+/// neither is chosen the reports are dismissed.
+pub async fn handle_admin_reports_resolve(
+    admin_ctx: AdminRequestContext,
+    Form(form): Form<ReportResolveForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let error_template = select_template!(false, false, admin_ctx.language);
+
+    let report = match report_get(&admin_ctx.web_context.pool, &form.id).await {
+        Ok(Some(report)) if report.resolved_at.is_none() => report,
+        Ok(_) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                AdminReportError::ReportNotFound(form.id.clone())
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+    };
+
+    if form.deny_creator {
+        let category = form.category.as_deref().unwrap_or("other");
+        if !DENYLIST_CATEGORIES.contains(&category) {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                AdminReportError::InvalidCategory(category.to_string())
+            );
+        }
+
+        let creator_did = match parse_aturi(&report.event_aturi) {
+            Ok((did, _, _)) => did,
+            Err(err) => {
+                return contextual_error!(
+                    admin_ctx.web_context,
+                    admin_ctx.language,
+                    error_template,
+                    template_context! {},
+                    err
+                );
+            }
+        };
+
+        if let Err(err) = denylist_add_or_update(
+            &admin_ctx.web_context.pool,
+            Cow::Borrowed(&creator_did),
+            Cow::Borrowed("An event you published was reported to the admins"),
+            category,
+            None,
+            None,
+        )
+        .await
+        {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+
+        if let Err(err) = audit_log_insert(
+            &admin_ctx.web_context.pool,
+            &admin_ctx.admin_handle.did,
+            AUDIT_ACTION_DENYLIST_ADD,
+            &creator_did,
+        )
+        .await
+        {
+            tracing::error!(error = ?err, "unable to record audit log entry");
+        }
+    }
+
+    if form.delete_event {
+        if let Err(err) = event_delete(&admin_ctx.web_context.pool, &report.event_aturi).await {
+            return contextual_error!(
+                admin_ctx.web_context,
+                admin_ctx.language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+
+        if let Err(err) = audit_log_insert(
+            &admin_ctx.web_context.pool,
+            &admin_ctx.admin_handle.did,
+            AUDIT_ACTION_EVENT_DELETE,
+            &report.event_aturi,
+        )
+        .await
+        {
+            tracing::error!(error = ?err, "unable to record audit log entry");
+        }
+    }
+
+    let resolution = match (form.delete_event, form.deny_creator) {
+        (false, false) => "dismissed",
+        (true, false) => "deleted",
+        (false, true) => "denied",
+        (true, true) => "deleted_denied",
+    };
+
+    if let Err(err) = report_resolve_event(
+        &admin_ctx.web_context.pool,
+        &report.event_aturi,
+        &admin_ctx.admin_handle.did,
+        resolution,
+        Utc::now(),
+    )
+    .await
+    {
+        return contextual_error!(
+            admin_ctx.web_context,
+            admin_ctx.language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+
+    Ok(Redirect::to("/admin/reports").into_response())
+}
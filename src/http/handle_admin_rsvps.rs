@@ -9,16 +9,16 @@ use crate::{
     http::{
         context::AdminRequestContext,
         errors::WebError,
-        pagination::{Pagination, PaginationView},
+        pagination::{Cursor, CursorPagination},
     },
     select_template,
-    storage::event::rsvp_list,
+    storage::event::{rsvp_count, rsvp_list},
 };
 
 #[derive(Deserialize, Default)]
 pub struct AdminRsvpsParams {
     #[serde(flatten)]
-    pagination: Pagination,
+    pagination: CursorPagination,
 
     #[serde(default)]
     import_success: bool,
@@ -49,9 +49,13 @@ pub async fn handle_admin_rsvps(
     let render_template = select_template!("admin_rsvps", false, false, language);
     let error_template = select_template!(false, false, language);
 
-    let (page, page_size) = params.pagination.admin_clamped();
+    let limit = params.pagination.clamped_limit(20, 20, 100);
 
-    let rsvps = rsvp_list(&web_context.pool, page, page_size).await;
+    // Unreadable cursors start from the first page
+    let after = params.pagination.cursor.as_deref().and_then(Cursor::decode);
+    let is_first_page = after.is_none();
+
+    let rsvps = rsvp_list(&web_context.pool, after.as_ref().map(Cursor::after), limit).await;
     if let Err(err) = rsvps {
         return contextual_error!(
             web_context,
@@ -61,15 +65,25 @@ pub async fn handle_admin_rsvps(
             err
         );
     }
-    let (total_count, mut rsvps) = rsvps.unwrap();
-
-    let params: Vec<(&str, &str)> = vec![];
+    let mut rsvps = rsvps.unwrap();
 
-    let pagination_view = PaginationView::new(page_size, rsvps.len() as i64, page, params);
+    let total_count = match rsvp_count(&web_context.pool).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                web_context,
+                language.0,
+                error_template,
+                default_context,
+                err
+            )
+        }
+    };
 
-    if rsvps.len() > page_size as usize {
-        rsvps.truncate(page_size as usize);
-    }
+    let next_cursor = Cursor::next_page(&mut rsvps, limit, |rsvp| {
+        rsvp.updated_at
+            .map(|updated_at| Cursor::new(updated_at, &rsvp.aturi))
+    });
 
     Ok(RenderHtml(
         &render_template,
@@ -82,7 +96,8 @@ pub async fn handle_admin_rsvps(
             imported_aturi => imported_aturi,
             rsvps => rsvps,
             total_count => total_count,
-            pagination => pagination_view,
+            is_first_page => is_first_page,
+            next_cursor => next_cursor,
         },
     )
     .into_response())
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_password::app_password_sign_in,
+    did::{plc, web},
+    http::{context::WebContext, errors::ApiError, middleware_service_auth::ServiceAuth},
+    storage::{app_password::app_password_session_delete, handle::handle_warm_up},
+};
+
+// The methods that service auth tokens for `PUT /api/v1/app-password` and
+// `DELETE /api/v1/app-password` are bound to with their `lxm` claim.
+pub const SET_APP_PASSWORD_NSID: &str = "events.smokesignal.appPassword.set";
+pub const DELETE_APP_PASSWORD_NSID: &str = "events.smokesignal.appPassword.delete";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AppPasswordInput {
+    pub app_password: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AppPasswordOutput {
+    pub did: String,
+    pub handle: String,
+}
+
+/// Signs the caller in to their PDS with an app password and stores the
+/// session, so that the API can write records for accounts whose PDS does
+/// not support OAuth. Any existing app password session is replaced.
+pub async fn handle_api_app_password_set(
+    State(web_context): State<WebContext>,
+    service_auth: ServiceAuth,
+    payload: Result<Json<AppPasswordInput>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let key = web_context
+        .config
+        .app_password_key
+        .as_ref()
+        .ok_or(ApiError::AppPasswordsDisabled)?;
+
+    let Json(input) = payload.map_err(|err| ApiError::InvalidRequest(err.body_text()))?;
+    if input.app_password.trim().is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "appPassword cannot be empty".to_string(),
+        ));
+    }
+
+    let did = service_auth.issuer;
+
+    let document = if did.starts_with("did:plc:") {
+        plc::query(
+            &web_context.http_client,
+            &web_context.config.plc_hostname,
+            &did,
+        )
+        .await
+    } else {
+        web::query(&web_context.http_client, &did).await
+    }
+    .map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    let pds = document
+        .pds_endpoint()
+        .ok_or_else(|| ApiError::InvalidRequest(format!("{} has no PDS", did)))?;
+
+    let session = app_password_sign_in(
+        &web_context.http_client,
+        &web_context.pool,
+        key,
+        &did,
+        pds,
+        input.app_password.trim(),
+    )
+    .await?;
+
+    handle_warm_up(&web_context.pool, &did, &session.handle, pds).await?;
+
+    Ok(Json(AppPasswordOutput {
+        did,
+        handle: session.handle,
+    }))
+}
+
+/// Removes the caller's app password session. Events they created stay in
+/// place.
+pub async fn handle_api_app_password_delete(
+    State(web_context): State<WebContext>,
+    service_auth: ServiceAuth,
+) -> Result<impl IntoResponse, ApiError> {
+    app_password_session_delete(&web_context.pool, &service_auth.issuer).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
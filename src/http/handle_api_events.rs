@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{rejection::JsonRejection, Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_password::app_password_session,
+    atproto::{
+        auth::{OAuthSessionProvider, SimpleOAuthSessionProvider},
+        client::{CreateRecordRequest, OAuthPdsClient},
+        lexicon::community::lexicon::{
+            calendar::event::{
+                Event, EventLink, EventLinks, EventLocation, EventLocations, Mode, Status, NSID,
+            },
+            location::Address,
+        },
+        record_limits::check_event_record,
+        uri::parse_aturi,
+    },
+    http::{
+        context::WebContext,
+        errors::ApiError,
+        errors::CreateEventError,
+        handle_xrpc_events::{rsvp_counts_for, ActorView, RsvpCountsView},
+        middleware_service_auth::ServiceAuth,
+        pagination::{Cursor, CursorPagination},
+        utils::url_from_aturi,
+    },
+    storage::{
+        errors::StorageError,
+        event::{
+            event_get, event_insert, event_list, event_rsvp_deadline, event_rsvps_closed,
+            event_rsvps_full, extract_event_details, get_event_rsvp_counts,
+            model::Event as StoredEvent,
+        },
+        handle::{handle_for_did, handles_by_did, model::Handle},
+        oauth::oauth_session_for_did,
+        tag::tag_slug,
+    },
+};
+
+/// The method that service auth tokens for `POST /api/v1/events` are bound
+/// to with their `lxm` claim.
+pub const CREATE_EVENT_NSID: &str = "events.smokesignal.calendar.createEvent";
+
+const LIST_LIMIT_DEFAULT: i64 = 25;
+const LIST_LIMIT_MAX: i64 = 100;
+
+/// The body of `POST /api/v1/events`. Fields use the names and values of the
+/// `community.lexicon.calendar.event` lexicon, and `createdAt` is set by the
+/// server.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateEventInput {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub mode: Option<Mode>,
+    #[serde(default)]
+    pub status: Option<Status>,
+    #[serde(default)]
+    pub locations: EventLocations,
+    #[serde(default)]
+    pub uris: EventLinks,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CreateEventOutput {
+    pub uri: String,
+    pub cid: String,
+    pub url: String,
+}
+
+/// The body of `GET /api/v1/events/{aturi}`. Events of both lexicons are
+/// read into the same fields, and field names don't change with the lexicons.
+/// Mode and status are the token names, such as `inperson` and `scheduled`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDetailsOutput {
+    pub uri: String,
+    pub cid: String,
+    pub url: String,
+    pub organizer: ActorView,
+    pub name: String,
+    pub description: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub mode: Option<String>,
+    pub status: Option<String>,
+    pub locations: EventLocations,
+    pub uris: EventLinks,
+    pub counts: RsvpCountsView,
+    pub rsvp_closes_at: Option<DateTime<Utc>>,
+    pub rsvps_closed: bool,
+    pub rsvp_capacity: Option<i32>,
+    pub rsvps_full: bool,
+    pub tags: Vec<String>,
+}
+
+/// Returns the name of a lexicon token, such as `inperson` for
+/// `community.lexicon.calendar.event#inperson`.
+fn token_name(value: &str) -> String {
+    value.rsplit('#').next().unwrap_or(value).to_string()
+}
+
+/// Checks an API event against the same limits as the event form. Names and
+/// descriptions are trimmed in place.
+fn validate_event_input(input: &mut CreateEventInput) -> Result<(), CreateEventError> {
+    input.name = input.name.trim().to_string();
+    if input.name.len() < 10 || input.name.len() > 500 {
+        return Err(CreateEventError::InvalidName);
+    }
+
+    input.description = input.description.trim().to_string();
+    if input.description.len() < 10 || input.description.len() > 3000 {
+        return Err(CreateEventError::InvalidDescription);
+    }
+
+    if let (Some(starts_at), Some(ends_at)) = (input.starts_at, input.ends_at) {
+        if ends_at <= starts_at {
+            return Err(CreateEventError::InvalidEventDates);
+        }
+    }
+
+    for location in &input.locations {
+        let valid = match location {
+            EventLocation::Address(Address::Current { country, .. }) => !country.trim().is_empty(),
+            EventLocation::Geo(geo) => geo.coordinates().is_some(),
+            _ => true,
+        };
+        if !valid {
+            return Err(CreateEventError::InvalidLocation);
+        }
+    }
+
+    for link in &input.uris {
+        let EventLink::Current { uri, .. } = link;
+        let uri = uri.trim();
+        if uri.is_empty()
+            || uri.len() > 500
+            || (!uri.starts_with("http://")
+                && !uri.starts_with("https://")
+                && !uri.starts_with("at://"))
+        {
+            return Err(CreateEventError::InvalidLink);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds a session that can write to the caller's PDS. OAuth sessions from
+/// logging in to Smoke Signal are preferred, followed by the app password
+/// session the caller has opted in to, if any.
+pub(crate) async fn caller_session(
+    web_context: &WebContext,
+    did: &str,
+) -> Result<(String, Box<dyn OAuthSessionProvider + Send + Sync>), ApiError> {
+    if let Some((handle, oauth_session)) =
+        oauth_session_for_did(&web_context.pool, did, Utc::now()).await?
+    {
+        let client_auth = SimpleOAuthSessionProvider::try_from(oauth_session)
+            .map_err(|err| ApiError::Internal(err.to_string()))?;
+        return Ok((handle.pds, Box::new(client_auth)));
+    }
+
+    if let Some(key) = &web_context.config.app_password_key {
+        if let Some(client_auth) =
+            app_password_session(&web_context.http_client, &web_context.pool, key, did).await?
+        {
+            return Ok((client_auth.pds.clone(), Box::new(client_auth)));
+        }
+    }
+
+    Err(ApiError::SessionNotFound(did.to_string()))
+}
+
+/// Creates an event for the caller, authenticated with an ATProto service
+/// auth token addressed to this instance. The record is written to the
+/// caller's PDS with their most recent Smoke Signal session, or with their
+/// app password session, so the caller must have logged in or provided an
+/// app password first.
+pub async fn handle_api_create_event(
+    State(web_context): State<WebContext>,
+    service_auth: ServiceAuth,
+    payload: Result<Json<CreateEventInput>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Json(mut input) = payload.map_err(|err| ApiError::InvalidRequest(err.body_text()))?;
+
+    validate_event_input(&mut input).map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+
+    let (pds, client_auth) = caller_session(&web_context, &service_auth.issuer).await?;
+
+    let client = OAuthPdsClient {
+        http_client: &web_context.http_client,
+        pds: &pds,
+    };
+
+    let the_record = Event::Current {
+        name: input.name,
+        description: input.description,
+        created_at: Utc::now(),
+        starts_at: input.starts_at,
+        ends_at: input.ends_at,
+        mode: input.mode,
+        status: input.status,
+        locations: input.locations,
+        uris: input.uris,
+        extra: HashMap::default(),
+    };
+
+    check_event_record(&the_record)?;
+
+    let event_record = CreateRecordRequest {
+        repo: service_auth.issuer.clone(),
+        collection: NSID.to_string(),
+        validate: false,
+        record_key: None,
+        record: the_record.clone(),
+        swap_commit: None,
+    };
+
+    let create_record_result = client
+        .create_record(client_auth.as_ref(), event_record)
+        .await
+        .map_err(|err| ApiError::RecordWriteFailed(err.to_string()))?;
+
+    event_insert(
+        &web_context.pool,
+        &create_record_result.uri,
+        &create_record_result.cid,
+        &service_auth.issuer,
+        NSID,
+        &the_record,
+    )
+    .await?;
+
+    if let Some(geocoder) = &web_context.geocoder {
+        geocoder.spawn_geocode_event(&web_context.pool, &create_record_result.uri);
+    }
+
+    let url = url_from_aturi(&web_context.config.external_base, &create_record_result.uri)
+        .map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateEventOutput {
+            uri: create_record_result.uri,
+            cid: create_record_result.cid,
+            url,
+        }),
+    ))
+}
+
+impl EventDetailsOutput {
+    fn new(
+        external_base: &str,
+        event: &StoredEvent,
+        organizer: Option<&Handle>,
+        counts: &HashMap<(String, String), i64>,
+    ) -> Result<Self, ApiError> {
+        let url = url_from_aturi(external_base, &event.aturi)
+            .map_err(|err| ApiError::Internal(err.to_string()))?;
+
+        let details = extract_event_details(event);
+        let counts = rsvp_counts_for(counts, &event.aturi);
+
+        Ok(Self {
+            uri: event.aturi.clone(),
+            cid: event.cid.clone(),
+            url,
+            organizer: ActorView {
+                did: event.did.clone(),
+                handle: organizer.map(|handle| handle.handle.clone()),
+            },
+            name: details.name.to_string(),
+            description: details.description.to_string(),
+            created_at: details.created_at,
+            starts_at: details.starts_at,
+            ends_at: details.ends_at,
+            mode: details.mode.as_deref().map(token_name),
+            status: details.status.as_deref().map(token_name),
+            locations: details.locations,
+            uris: details.uris,
+            rsvp_closes_at: event_rsvp_deadline(event),
+            rsvps_closed: event_rsvps_closed(event, Utc::now()),
+            rsvp_capacity: event.rsvp_capacity,
+            rsvps_full: event_rsvps_full(event, i64::from(counts.going)),
+            counts,
+            tags: details.tags,
+        })
+    }
+}
+
+/// Returns an indexed event with its RSVP counts. The AT-URI may be given
+/// as-is or percent-encoded.
+pub async fn handle_api_get_event(
+    State(web_context): State<WebContext>,
+    Path(aturi): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    parse_aturi(&aturi).map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+
+    let event = event_get(&web_context.pool, &aturi)
+        .await
+        .map_err(|err| match err {
+            StorageError::RowNotFound(_, _) => ApiError::EventNotFound(aturi.clone()),
+            other => other.into(),
+        })?;
+
+    let organizer = handle_for_did(&web_context.pool, &event.did).await.ok();
+    let counts = get_event_rsvp_counts(&web_context.pool, vec![event.aturi.clone()]).await?;
+
+    Ok(Json(EventDetailsOutput::new(
+        &web_context.config.external_base,
+        &event,
+        organizer.as_ref(),
+        &counts,
+    )?))
+}
+
+#[derive(Serialize, Debug)]
+pub struct ListEventsOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub events: Vec<EventDetailsOutput>,
+}
+
+/// The filters of `GET /api/v1/events`.
+#[derive(Deserialize, Debug)]
+pub struct ListEventsFilter {
+    /// Only list events with this tag. It is normalized like the tags of
+    /// events, so `#Rust` lists events tagged `rust`.
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+/// Lists indexed events, most recently updated first. The `cursor` of a
+/// response returns the next page, and is left out on the last page.
+pub async fn handle_api_list_events(
+    State(web_context): State<WebContext>,
+    Query(params): Query<CursorPagination>,
+    Query(filter): Query<ListEventsFilter>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = params.clamped_limit(LIST_LIMIT_DEFAULT, 1, LIST_LIMIT_MAX);
+
+    let tag = match filter.tag.as_deref().map(tag_slug) {
+        Some(tag) if tag.is_empty() => return Err(ApiError::InvalidRequest("tag".to_string())),
+        tag => tag,
+    };
+
+    let after = match &params.cursor {
+        Some(cursor) => {
+            Some(Cursor::decode(cursor).ok_or(ApiError::InvalidRequest("cursor".to_string()))?)
+        }
+        None => None,
+    };
+
+    let mut events = event_list(
+        &web_context.pool,
+        tag.as_deref(),
+        after.as_ref().map(Cursor::after),
+        limit,
+    )
+    .await?;
+
+    let cursor = Cursor::next_page(&mut events, limit, |event| {
+        event
+            .updated_at
+            .map(|updated_at| Cursor::new(updated_at, &event.aturi))
+    });
+
+    let dids = events.iter().map(|event| event.did.clone()).collect();
+    let organizers = handles_by_did(&web_context.pool, dids).await?;
+
+    let aturis = events.iter().map(|event| event.aturi.clone()).collect();
+    let counts = get_event_rsvp_counts(&web_context.pool, aturis).await?;
+
+    let events = events
+        .iter()
+        .map(|event| {
+            EventDetailsOutput::new(
+                &web_context.config.external_base,
+                event,
+                organizers.get(&event.did),
+                &counts,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(ListEventsOutput { cursor, events }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: serde_json::Value) -> CreateEventInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_event_input() {
+        let mut valid = input(serde_json::json!({
+            "name": "  Vancouver Rust Meetup  ",
+            "description": "Talks and snacks for Rustaceans.",
+            "startsAt": "2025-06-01T18:00:00Z",
+            "endsAt": "2025-06-01T20:00:00Z",
+            "mode": "community.lexicon.calendar.event#inperson",
+            "locations": [{
+                "$type": "community.lexicon.location.address",
+                "country": "CA",
+                "locality": "Vancouver"
+            }],
+            "uris": [{
+                "$type": "community.lexicon.calendar.event#uri",
+                "uri": "https://example.com/rust"
+            }]
+        }));
+        assert!(validate_event_input(&mut valid).is_ok());
+        assert_eq!(valid.name, "Vancouver Rust Meetup");
+
+        let mut short_name = input(serde_json::json!({
+            "name": "Rust",
+            "description": "Talks and snacks for Rustaceans."
+        }));
+        assert!(matches!(
+            validate_event_input(&mut short_name),
+            Err(CreateEventError::InvalidName)
+        ));
+
+        let mut backwards = input(serde_json::json!({
+            "name": "Vancouver Rust Meetup",
+            "description": "Talks and snacks for Rustaceans.",
+            "startsAt": "2025-06-01T18:00:00Z",
+            "endsAt": "2025-06-01T17:00:00Z"
+        }));
+        assert!(matches!(
+            validate_event_input(&mut backwards),
+            Err(CreateEventError::InvalidEventDates)
+        ));
+
+        let mut bad_link = input(serde_json::json!({
+            "name": "Vancouver Rust Meetup",
+            "description": "Talks and snacks for Rustaceans.",
+            "uris": [{
+                "$type": "community.lexicon.calendar.event#uri",
+                "uri": "javascript:alert(1)"
+            }]
+        }));
+        assert!(matches!(
+            validate_event_input(&mut bad_link),
+            Err(CreateEventError::InvalidLink)
+        ));
+    }
+
+    #[test]
+    fn test_create_event_input_rejects_unknown_fields() {
+        let result = serde_json::from_value::<CreateEventInput>(serde_json::json!({
+            "name": "Vancouver Rust Meetup",
+            "description": "Talks and snacks for Rustaceans.",
+            "createdAt": "2025-06-01T18:00:00Z"
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_token_name() {
+        assert_eq!(
+            token_name("community.lexicon.calendar.event#inperson"),
+            "inperson"
+        );
+        assert_eq!(
+            token_name("events.smokesignal.calendar.event#scheduled"),
+            "scheduled"
+        );
+        assert_eq!(token_name("virtual"), "virtual");
+    }
+}
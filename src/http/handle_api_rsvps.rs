@@ -0,0 +1,261 @@
+use std::hash::Hasher;
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use metrohash::MetroHash64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::{
+        client::{OAuthPdsClient, PutRecordRequest},
+        lexicon::{
+            com::atproto::repo::StrongRef,
+            community::lexicon::calendar::rsvp::{Rsvp, RsvpStatus, NSID},
+        },
+        uri::parse_aturi,
+    },
+    http::{
+        context::WebContext, errors::ApiError, handle_api_events::caller_session,
+        handle_webhooks::notify_webhooks, middleware_service_auth::ServiceAuth,
+    },
+    storage::{
+        cache::rsvp_counts_adjust,
+        errors::StorageError,
+        event::{
+            event_get, event_going_count, event_lock_rsvp_capacity, event_rsvps_closed,
+            event_rsvps_full, get_user_rsvp, model::Event as StoredEvent, rsvp_insert,
+        },
+        waitlist::{waitlist_delete, waitlist_insert, waitlist_position},
+    },
+    webhooks::{WebhookKind, WebhookRsvp},
+};
+
+// The methods that service auth tokens for `POST /api/v1/rsvps` and
+// `POST /api/v1/waitlist` are bound to with their `lxm` claim.
+pub const CREATE_RSVP_NSID: &str = "events.smokesignal.calendar.createRsvp";
+pub const JOIN_WAITLIST_NSID: &str = "events.smokesignal.calendar.joinWaitlist";
+
+/// The body of `POST /api/v1/rsvps`. The status is a token name: `going`,
+/// `interested` or `notgoing`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateRsvpInput {
+    pub subject: String,
+    pub status: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CreateRsvpOutput {
+    pub uri: String,
+    pub cid: String,
+}
+
+/// The body of `POST /api/v1/waitlist`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct JoinWaitlistInput {
+    pub subject: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct JoinWaitlistOutput {
+    pub subject: String,
+    pub position: i64,
+}
+
+fn rsvp_status(value: &str) -> Result<RsvpStatus, ApiError> {
+    match value {
+        "going" => Ok(RsvpStatus::Going),
+        "interested" => Ok(RsvpStatus::Interested),
+        "notgoing" => Ok(RsvpStatus::NotGoing),
+        _ => Err(ApiError::InvalidRequest("status".to_string())),
+    }
+}
+
+/// Finds the event an RSVP or waitlist request is for.
+async fn subject_event(web_context: &WebContext, aturi: &str) -> Result<StoredEvent, ApiError> {
+    parse_aturi(aturi).map_err(|err| ApiError::InvalidRequest(err.to_string()))?;
+
+    event_get(&web_context.pool, aturi)
+        .await
+        .map_err(|err| match err {
+            StorageError::RowNotFound(_, _) => ApiError::EventNotFound(aturi.to_string()),
+            other => other.into(),
+        })
+}
+
+/// RSVPs to an event for the caller, authenticated the same way as creating
+/// events. Going RSVPs to an event that has reached its capacity are refused
+/// with an `EventFull` error that names the waitlist request to make instead.
+pub async fn handle_api_create_rsvp(
+    State(web_context): State<WebContext>,
+    service_auth: ServiceAuth,
+    payload: Result<Json<CreateRsvpInput>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Json(input) = payload.map_err(|err| ApiError::InvalidRequest(err.body_text()))?;
+
+    let status = rsvp_status(&input.status)?;
+    let mut event = subject_event(&web_context, &input.subject).await?;
+
+    let now = Utc::now();
+    if event_rsvps_closed(&event, now) {
+        return Err(ApiError::RsvpsClosed(event.aturi));
+    }
+
+    let (pds, client_auth) = caller_session(&web_context, &service_auth.issuer).await?;
+
+    let client = OAuthPdsClient {
+        http_client: &web_context.http_client,
+        pds: &pds,
+    };
+
+    // The RSVP is checked and stored in one transaction. Going RSVPs lock the
+    // event until the record is written and stored, so that two of them
+    // can't both take its last spot.
+    let mut tx = web_context
+        .pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    if input.status == "going" {
+        event.rsvp_capacity = event_lock_rsvp_capacity(tx.as_mut(), &event.aturi).await?;
+    }
+
+    let previous_status = get_user_rsvp(tx.as_mut(), &event.aturi, &service_auth.issuer).await?;
+
+    // People who are already going keep their spot
+    if input.status == "going" && previous_status.as_deref() != Some("going") {
+        let going_count = event_going_count(tx.as_mut(), &event.aturi).await?;
+        if event_rsvps_full(&event, going_count) {
+            return Err(ApiError::EventFull(event.aturi));
+        }
+    }
+
+    // The same record key as RSVPs from the web interface, so that either
+    // replaces the other
+    let mut h = MetroHash64::default();
+    h.write(event.aturi.as_bytes());
+    let record_key = crockford::encode(h.finish());
+
+    let the_record = Rsvp::Current {
+        created_at: now,
+        subject: StrongRef {
+            uri: event.aturi.clone(),
+            cid: event.cid.clone(),
+        },
+        status,
+    };
+
+    let rsvp_record = PutRecordRequest {
+        repo: service_auth.issuer.clone(),
+        collection: NSID.to_string(),
+        validate: false,
+        record_key,
+        record: the_record.clone(),
+        swap_commit: None,
+        swap_record: None,
+    };
+
+    let put_record_result = client
+        .put_record(client_auth.as_ref(), rsvp_record)
+        .await
+        .map_err(|err| ApiError::RecordWriteFailed(err.to_string()))?;
+
+    rsvp_insert(
+        tx.as_mut(),
+        &put_record_result.uri,
+        &put_record_result.cid,
+        &service_auth.issuer,
+        NSID,
+        &the_record,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    // Going takes the spot someone was waiting for
+    if input.status == "going" {
+        if let Err(err) =
+            waitlist_delete(&web_context.pool, &event.aturi, &service_auth.issuer).await
+        {
+            tracing::warn!(error = ?err, "unable to remove rsvp from waitlist");
+        }
+    }
+
+    if previous_status.as_ref() != Some(&input.status) {
+        // The counts are counted again when the cached ones expire, so a
+        // failed adjustment is only logged.
+        if let Err(err) = rsvp_counts_adjust(
+            &web_context.cache_pool,
+            &event.aturi,
+            previous_status.as_deref(),
+            Some(&input.status),
+        )
+        .await
+        {
+            tracing::warn!(error = ?err, "unable to adjust cached rsvp counts");
+        }
+
+        let kind = if previous_status.is_some() {
+            WebhookKind::RsvpUpdated
+        } else {
+            WebhookKind::RsvpCreated
+        };
+        notify_webhooks(
+            &web_context.pool,
+            &web_context.config.external_base,
+            &event.aturi,
+            kind,
+            Some(WebhookRsvp {
+                did: service_auth.issuer.clone(),
+                status: input.status,
+                previous_status,
+            }),
+        )
+        .await;
+    }
+
+    Ok(Json(CreateRsvpOutput {
+        uri: put_record_result.uri,
+        cid: put_record_result.cid,
+    }))
+}
+
+/// Puts the caller on the waitlist of a full event and returns their place
+/// in line. This is the follow up to an `EventFull` error.
+pub async fn handle_api_join_waitlist(
+    State(web_context): State<WebContext>,
+    service_auth: ServiceAuth,
+    payload: Result<Json<JoinWaitlistInput>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Json(input) = payload.map_err(|err| ApiError::InvalidRequest(err.body_text()))?;
+
+    let event = subject_event(&web_context, &input.subject).await?;
+
+    if event_rsvps_closed(&event, Utc::now()) {
+        return Err(ApiError::RsvpsClosed(event.aturi));
+    }
+
+    let going_count = event_going_count(&web_context.pool, &event.aturi).await?;
+    if !event_rsvps_full(&event, going_count) {
+        return Err(ApiError::EventNotFull(event.aturi));
+    }
+
+    waitlist_insert(&web_context.pool, &event.aturi, &service_auth.issuer).await?;
+
+    let position = waitlist_position(&web_context.pool, &event.aturi, &service_auth.issuer)
+        .await?
+        .ok_or_else(|| ApiError::Internal("waitlist entry not found".to_string()))?;
+
+    Ok(Json(JoinWaitlistOutput {
+        subject: event.aturi,
+        position,
+    }))
+}
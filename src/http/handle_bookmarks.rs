@@ -0,0 +1,206 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::{Form, Query};
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{BookmarkError, WebError},
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
+        pagination::{Pagination, PaginationView},
+    },
+    select_template,
+    storage::{
+        bookmark::{bookmark_delete, bookmark_insert, bookmark_list_events},
+        event::event_exists,
+    },
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BookmarkForm {
+    subject_aturi: String,
+    #[serde(default)]
+    saved: bool,
+}
+
+pub async fn handle_bookmarks(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/bookmarks")?;
+
+    let canonical_url = format!("https://{}/bookmarks", ctx.web_context.config.external_base);
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => canonical_url,
+    };
+
+    let render_template = select_template!("bookmarks", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let (page, page_size) = pagination.clamped();
+
+    let events =
+        match bookmark_list_events(&ctx.web_context.pool, &current_handle.did, page, page_size)
+            .await
+        {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    let organizer_handlers = hydrate_event_organizers(&ctx.web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            let event_view =
+                EventView::try_from((Some(&current_handle), organizer_maybe, &event_view.event));
+
+            match event_view {
+                Ok(event_view) => Some(event_view),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) = hydrate_event_rsvp_counts_cached(
+        &ctx.web_context.pool,
+        &ctx.web_context.cache_pool,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    let params: Vec<(&str, &str)> = vec![];
+
+    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+
+    if events.len() > page_size as usize {
+        events.truncate(page_size as usize);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &ctx.web_context.pool,
+        &ctx.web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                events,
+                pagination => pagination_view,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_bookmark_update(
+    ctx: UserRequestContext,
+    Form(bookmark_form): Form<BookmarkForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.bookmark.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_aturi = bookmark_form.subject_aturi;
+
+    match event_exists(&ctx.web_context.pool, &subject_aturi).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                BookmarkError::EventNotFound(subject_aturi.clone())
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    }
+
+    let result = if bookmark_form.saved {
+        bookmark_insert(&ctx.web_context.pool, &current_handle.did, &subject_aturi).await
+    } else {
+        bookmark_delete(&ctx.web_context.pool, &current_handle.did, &subject_aturi).await
+    };
+
+    if let Err(err) = result {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => subject_aturi },
+                is_bookmarked => bookmark_form.saved,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use axum::{
+    extract::Path,
+    response::{IntoResponse, Response},
+};
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Serialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{CommonError, WebError},
+        event_view::{hydrate_event_labels, hydrate_event_organizers, EventView},
+        handle_index::section_event_views,
+        timezones::start_of_day,
+    },
+    select_template,
+    storage::event::event_list_starts_between,
+};
+
+/// An event in a day of the month grid.
+#[derive(Debug, Serialize)]
+pub struct CalendarEventView {
+    pub name: String,
+    pub site_url: String,
+    pub time: String,
+    pub status: Option<String>,
+}
+
+/// A day of the month grid and the events that start on it.
+#[derive(Debug, Serialize)]
+pub struct CalendarDayView<T> {
+    pub day: u32,
+    pub url: String,
+    pub is_today: bool,
+    pub events: Vec<T>,
+}
+
+fn day_url(date: NaiveDate) -> String {
+    format!("/calendar/{}", date.format("%Y/%m/%d"))
+}
+
+fn month_url(date: NaiveDate) -> String {
+    format!("/calendar/{}", date.format("%Y/%m"))
+}
+
+/// Lays out the days of a month as weeks that start on Sunday, with the
+/// events of each day in the order given. The days of the months before and
+/// after that fill the first and last weeks are `None`.
+fn month_weeks<T>(
+    first: NaiveDate,
+    today: NaiveDate,
+    events: Vec<(NaiveDate, T)>,
+) -> Vec<Vec<Option<CalendarDayView<T>>>> {
+    let mut days = first
+        .iter_days()
+        .take_while(|date| date.month() == first.month())
+        .map(|date| CalendarDayView {
+            day: date.day(),
+            url: day_url(date),
+            is_today: date == today,
+            events: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for (date, event) in events {
+        if date.year() == first.year() && date.month() == first.month() {
+            days[date.day0() as usize].events.push(event);
+        }
+    }
+
+    let leading = first.weekday().num_days_from_sunday() as usize;
+    let mut cells = (0..leading).map(|_| None).collect::<Vec<_>>();
+    cells.extend(days.into_iter().map(Some));
+    while cells.len() % 7 != 0 {
+        cells.push(None);
+    }
+
+    let mut weeks = Vec::with_capacity(cells.len() / 7);
+    let mut cells = cells.into_iter();
+    loop {
+        let week = cells.by_ref().take(7).collect::<Vec<_>>();
+        if week.is_empty() {
+            break;
+        }
+        weeks.push(week);
+    }
+    weeks
+}
+
+fn viewer_tz(ctx: &UserRequestContext) -> Tz {
+    ctx.current_handle
+        .as_ref()
+        .and_then(|handle| handle.tz.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Shows the month grid of the current month.
+pub async fn handle_calendar(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let today = Utc::now().with_timezone(&viewer_tz(&ctx)).date_naive();
+    render_calendar_month(ctx, hx_boosted, today.year(), today.month()).await
+}
+
+/// Shows every event that starts in a month as a grid of days, in the time
+/// zone of the viewer. Month URLs share their shape with event series URLs,
+/// so they are routed through `handle_view_calendar`.
+pub(crate) async fn render_calendar_month(
+    ctx: UserRequestContext,
+    hx_boosted: bool,
+    year: i32,
+    month: u32,
+) -> Result<Response, WebError> {
+    let canonical_url = format!(
+        "https://{}/calendar/{}/{:02}",
+        ctx.web_context.config.external_base, year, month
+    );
+
+    let default_context = template_context! {
+        current_handle => ctx.current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url,
+    };
+
+    let render_template = select_template!("calendar_month", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let tz = viewer_tz(&ctx);
+
+    let Some((first, next)) = NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| Some((first, first.checked_add_months(Months::new(1))?)))
+    else {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            CommonError::InvalidMonth,
+            StatusCode::NOT_FOUND
+        );
+    };
+
+    let events = match event_list_starts_between(
+        &ctx.web_context.pool,
+        start_of_day(first, tz),
+        start_of_day(next, tz),
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&ctx.web_context.pool, &events).await?;
+
+    let starts_at = events
+        .iter()
+        .filter_map(|event_view| {
+            let starts_at = event_view.event.starts_at?.with_timezone(&tz);
+            Some((event_view.event.aturi.clone(), starts_at))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut event_views = events
+        .iter()
+        .filter(|event_view| starts_at.contains_key(&event_view.event.aturi))
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            match EventView::try_from((
+                ctx.current_handle.as_ref(),
+                organizer_maybe,
+                &event_view.event,
+            )) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) = hydrate_event_labels(
+        &ctx.web_context.pool,
+        &ctx.web_context.config.label_policy,
+        &mut event_views,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    let events = event_views
+        .into_iter()
+        .filter_map(|event_view| {
+            let starts_at = starts_at.get(&event_view.aturi)?;
+            Some((
+                starts_at.date_naive(),
+                CalendarEventView {
+                    name: event_view.name,
+                    site_url: event_view.site_url,
+                    time: starts_at.format("%l:%M %P").to_string().trim().to_string(),
+                    status: event_view.status,
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    let weeks = month_weeks(first, today, events);
+
+    let previous = first.checked_sub_months(Months::new(1));
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                month_label => first.format("%B %Y").to_string(),
+                weeks,
+                previous_url => previous.map(month_url),
+                next_url => month_url(next),
+                today_url => month_url(today),
+            }},
+        ),
+    )
+        .into_response())
+}
+
+/// Lists every event that starts on a day, in the time zone of the viewer.
+pub async fn handle_calendar_day(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path((year, month, day)): Path<(i32, u32, u32)>,
+) -> Result<impl IntoResponse, WebError> {
+    let canonical_url = format!(
+        "https://{}/calendar/{}/{:02}/{:02}",
+        ctx.web_context.config.external_base, year, month, day
+    );
+
+    let default_context = template_context! {
+        current_handle => ctx.current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url,
+    };
+
+    let render_template = select_template!("calendar_day", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let tz = viewer_tz(&ctx);
+
+    let Some((date, next)) = NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| Some((date, date.checked_add_days(Days::new(1))?)))
+    else {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            CommonError::InvalidDate,
+            StatusCode::NOT_FOUND
+        );
+    };
+
+    let events = match event_list_starts_between(
+        &ctx.web_context.pool,
+        start_of_day(date, tz),
+        start_of_day(next, tz),
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let events = section_event_views(&ctx.web_context, &ctx.auth, events).await?;
+
+    let previous = date.checked_sub_days(Days::new(1));
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                day_label => date.format("%A, %e %B %Y").to_string(),
+                month_label => date.format("%B %Y").to_string(),
+                month_url => month_url(date),
+                events,
+                previous_url => previous.map(day_url),
+                next_url => day_url(next),
+            }},
+        ),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_weeks() {
+        let first = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 5, 14).unwrap();
+        let events = vec![
+            (NaiveDate::from_ymd_opt(2025, 5, 14).unwrap(), "meetup"),
+            (NaiveDate::from_ymd_opt(2025, 5, 14).unwrap(), "social"),
+            (NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(), "next month"),
+        ];
+
+        let weeks = month_weeks(first, today, events);
+
+        // May 2025 starts on a Thursday and ends on a Saturday
+        assert_eq!(weeks.len(), 5);
+        assert!(weeks.iter().all(|week| week.len() == 7));
+        assert!(weeks[0][..4].iter().all(Option::is_none));
+
+        let first_day = weeks[0][4].as_ref().unwrap();
+        assert_eq!(first_day.day, 1);
+        assert_eq!(first_day.url, "/calendar/2025/05/01");
+
+        let last_day = weeks[4][6].as_ref().unwrap();
+        assert_eq!(last_day.day, 31);
+
+        let day = weeks[2][3].as_ref().unwrap();
+        assert_eq!(day.day, 14);
+        assert!(day.is_today);
+        assert_eq!(day.events, vec!["meetup", "social"]);
+        assert_eq!(
+            weeks
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|day| day.events.len())
+                .sum::<usize>(),
+            2
+        );
+    }
+}
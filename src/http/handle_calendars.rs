@@ -0,0 +1,579 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use axum::{
+    extract::Path,
+    response::{IntoResponse, Redirect},
+};
+use axum_extra::extract::Form;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::{
+        auth::SimpleOAuthSessionProvider,
+        client::{CreateRecordRequest, DeleteRecordRequest, OAuthPdsClient, PutRecordRequest},
+        lexicon::{
+            com::atproto::repo::StrongRef,
+            events::smokesignal::calendar::{Calendar as CalendarRecord, NSID},
+        },
+        uri::parse_aturi,
+    },
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{CalendarError, CommonError, WebError},
+    },
+    select_template,
+    storage::{
+        calendar::{
+            calendar_delete, calendar_events, calendar_get, calendar_list_did, calendar_upsert,
+            model::Calendar,
+        },
+        event::{event_get, event_list_did_recently_updated, model::Event},
+        handle::model::Handle,
+        StoragePool,
+    },
+};
+
+/// The most events a series can have.
+pub const MAX_CALENDAR_EVENTS: usize = 100;
+
+/// The most recent events of the organizer offered in the series form.
+const EVENT_CHOICES: i64 = 50;
+
+/// The longest name kept for a series. Longer names are shortened.
+const MAX_NAME_CHARS: usize = 256;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CalendarCreateForm {
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CalendarForm {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// The events of the series, by AT-URI.
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+/// A series as shown on the series list of an organizer.
+#[derive(Serialize, Debug)]
+pub struct CalendarView {
+    pub rkey: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String,
+}
+
+impl CalendarView {
+    pub fn new(calendar: &Calendar) -> Self {
+        let rkey = parse_aturi(&calendar.aturi)
+            .map(|(_, _, rkey)| rkey)
+            .unwrap_or_default();
+        Self {
+            url: format!("/calendar/{}/{}", calendar.did, rkey),
+            rkey,
+            name: calendar.name.clone(),
+            description: calendar.description.clone(),
+        }
+    }
+}
+
+/// An event that can be picked for a series.
+#[derive(Serialize, Debug)]
+pub struct CalendarEventChoice {
+    pub aturi: String,
+    pub name: String,
+    pub starts_at: Option<String>,
+    pub selected: bool,
+}
+
+impl CalendarEventChoice {
+    fn new(event: &Event, tz: &Tz, selected: bool) -> Self {
+        Self {
+            aturi: event.aturi.clone(),
+            name: event.name.clone(),
+            starts_at: event
+                .starts_at
+                .map(|value| value.with_timezone(tz).format("%e %B %Y").to_string()),
+            selected,
+        }
+    }
+}
+
+/// Trims the name and description of a series, which must have a name.
+fn clean_details(name: &str, description: &str) -> Result<(String, Option<String>), WebError> {
+    let name: String = name.trim().chars().take(MAX_NAME_CHARS).collect();
+    if name.is_empty() {
+        return Err(CalendarError::NameRequired.into());
+    }
+
+    let description = Some(description.trim().to_string()).filter(|value| !value.is_empty());
+
+    Ok((name, description))
+}
+
+/// Finds the series of the current user with the given record key.
+async fn owned_calendar(
+    pool: &StoragePool,
+    current_handle: &Handle,
+    calendar_rkey: &str,
+) -> Result<Calendar, WebError> {
+    let aturi = format!("at://{}/{}/{}", current_handle.did, NSID, calendar_rkey);
+    calendar_get(pool, &aturi)
+        .await?
+        .ok_or_else(|| CalendarError::NotFound(calendar_rkey.to_string()).into())
+}
+
+/// Looks up the events picked for a series, so that the record refers to the
+/// version of each event that was picked.
+async fn event_refs(pool: &StoragePool, aturis: &[String]) -> Result<Vec<StrongRef>, WebError> {
+    let mut seen = HashSet::new();
+    let aturis = aturis
+        .iter()
+        .filter(|value| !value.is_empty() && seen.insert(value.as_str()))
+        .collect::<Vec<_>>();
+
+    if aturis.len() > MAX_CALENDAR_EVENTS {
+        return Err(CalendarError::TooManyEvents(MAX_CALENDAR_EVENTS).into());
+    }
+
+    let mut refs = Vec::with_capacity(aturis.len());
+    for aturi in aturis {
+        let event = event_get(pool, aturi)
+            .await
+            .map_err(|_| CalendarError::EventNotFound(aturi.clone()))?;
+        refs.push(StrongRef {
+            uri: event.aturi,
+            cid: event.cid,
+        });
+    }
+
+    Ok(refs)
+}
+
+/// Shows the event series of an organizer, and a form to start another.
+pub async fn handle_calendars(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/calendars")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendars", ctx.web_context.config.external_base),
+    };
+
+    let render_template = select_template!("calendars", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let calendars = match calendar_list_did(&ctx.web_context.pool, &current_handle.did).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let calendars = calendars.iter().map(CalendarView::new).collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                calendars,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Starts an event series. The series is written to the PDS of the
+/// organizer without events, which are picked on the edit page it leads to.
+pub async fn handle_calendar_create(
+    ctx: UserRequestContext,
+    Form(calendar_form): Form<CalendarCreateForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/calendars")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendars", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let (name, description) = match clean_details(&calendar_form.name, &calendar_form.description) {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let auth_data = ctx.auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    let client = OAuthPdsClient {
+        http_client: &ctx.web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    let record = CalendarRecord::Current {
+        name,
+        description,
+        events: vec![],
+        created_at: Utc::now(),
+    };
+
+    let create_record_request = CreateRecordRequest {
+        repo: current_handle.did.clone(),
+        collection: NSID.to_string(),
+        validate: false,
+        record_key: None,
+        record: record.clone(),
+        swap_commit: None,
+    };
+
+    let created = match client
+        .create_record(&client_auth, create_record_request)
+        .await
+    {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    calendar_upsert(
+        &ctx.web_context.pool,
+        &created.uri,
+        &created.cid,
+        &current_handle.did,
+        &record,
+    )
+    .await?;
+
+    let rkey = parse_aturi(&created.uri)
+        .map(|(_, _, rkey)| rkey)
+        .unwrap_or_default();
+
+    Ok(Redirect::to(&format!("/calendars/{}/edit", rkey)).into_response())
+}
+
+/// Shows the form to rename an event series and pick its events, from the
+/// organizer's recent events and the events already in it.
+pub async fn handle_calendar_edit(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path(calendar_rkey): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/calendars")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendars/{}/edit", ctx.web_context.config.external_base, calendar_rkey),
+    };
+
+    let render_template = select_template!("calendar_edit", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let calendar =
+        match owned_calendar(&ctx.web_context.pool, &current_handle, &calendar_rkey).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err,
+                    StatusCode::NOT_FOUND
+                );
+            }
+        };
+
+    let members = match calendar_events(&ctx.web_context.pool, &calendar.aturi).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let recent = match event_list_did_recently_updated(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        1,
+        EVENT_CHOICES,
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let tz = current_handle.tz.parse::<Tz>().unwrap_or(Tz::UTC);
+
+    let mut events = members
+        .iter()
+        .map(|value| CalendarEventChoice::new(&value.event, &tz, true))
+        .collect::<Vec<_>>();
+    for value in &recent {
+        if !members
+            .iter()
+            .any(|member| member.event.aturi == value.event.aturi)
+        {
+            events.push(CalendarEventChoice::new(&value.event, &tz, false));
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                calendar => CalendarView::new(&calendar),
+                events,
+                max_events => MAX_CALENDAR_EVENTS,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Saves the name and events of an event series to the PDS of the organizer
+/// and shows the series.
+pub async fn handle_calendar_update(
+    ctx: UserRequestContext,
+    Path(calendar_rkey): Path<String>,
+    Form(calendar_form): Form<CalendarForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/calendars")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendars/{}/edit", ctx.web_context.config.external_base, calendar_rkey),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let calendar =
+        match owned_calendar(&ctx.web_context.pool, &current_handle, &calendar_rkey).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err,
+                    StatusCode::NOT_FOUND
+                );
+            }
+        };
+
+    let details = clean_details(&calendar_form.name, &calendar_form.description);
+    let (name, description) = match details {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let events = match event_refs(&ctx.web_context.pool, &calendar_form.events).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    // The creation time of the series is kept from the stored record.
+    let created_at = serde_json::from_value::<CalendarRecord>(calendar.record.0.clone())
+        .map(|CalendarRecord::Current { created_at, .. }| created_at)
+        .unwrap_or_else(|_| Utc::now());
+
+    let record = CalendarRecord::Current {
+        name,
+        description,
+        events,
+        created_at,
+    };
+
+    let auth_data = ctx.auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    let client = OAuthPdsClient {
+        http_client: &ctx.web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    let put_record_request = PutRecordRequest {
+        repo: current_handle.did.clone(),
+        collection: NSID.to_string(),
+        record_key: calendar_rkey.clone(),
+        validate: false,
+        record: record.clone(),
+        swap_commit: None,
+        swap_record: Some(calendar.cid.clone()),
+    };
+
+    let updated = match client.put_record(&client_auth, put_record_request).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    calendar_upsert(
+        &ctx.web_context.pool,
+        &updated.uri,
+        &updated.cid,
+        &current_handle.did,
+        &record,
+    )
+    .await?;
+
+    Ok(Redirect::to(&CalendarView::new(&calendar).url).into_response())
+}
+
+/// Removes an event series. The events of the series are left as they are.
+pub async fn handle_calendar_delete(
+    ctx: UserRequestContext,
+    Path(calendar_rkey): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/calendars")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendars", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let calendar =
+        match owned_calendar(&ctx.web_context.pool, &current_handle, &calendar_rkey).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err,
+                    StatusCode::NOT_FOUND
+                );
+            }
+        };
+
+    let auth_data = ctx.auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    let client = OAuthPdsClient {
+        http_client: &ctx.web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    let delete_record_request = DeleteRecordRequest {
+        repo: current_handle.did.clone(),
+        collection: NSID.to_string(),
+        record_key: calendar_rkey,
+        swap_record: Some(calendar.cid.clone()),
+        swap_commit: None,
+    };
+
+    if let Err(err) = client
+        .delete_record(&client_auth, delete_record_request)
+        .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    calendar_delete(&ctx.web_context.pool, &current_handle.did, &calendar.aturi).await?;
+
+    Ok(Redirect::to("/calendars").into_response())
+}
@@ -0,0 +1,436 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+};
+use axum_extra::extract::Form;
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::lexicon::community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+    contextual_error,
+    http::context::UserRequestContext,
+    http::errors::{CheckinError, CommonError, WebError},
+    http::utils::url_from_aturi,
+    resolve::{parse_input, InputType},
+    select_template,
+    storage::{
+        cache::handles_by_did_cached,
+        checkin::{
+            checkin_attendees, checkin_counts, checkin_delete, checkin_insert,
+            model::CheckinAttendee,
+        },
+        event::{event_get, model::Event},
+        handle::{handle_for_did, handle_for_handle, model::Handle},
+        StoragePool,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CheckinParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+    #[serde(default)]
+    q: String,
+}
+
+fn default_collection() -> String {
+    LexiconCommunityEventNSID.to_string()
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CheckinForm {
+    /// The person to check in. When it isn't given, the search is checked in
+    /// if it matches exactly one person, so that scanning a handle or typing
+    /// one and pressing enter checks them in.
+    did: Option<String>,
+    #[serde(default)]
+    q: String,
+    /// Set to undo a check-in.
+    undo: Option<String>,
+}
+
+/// Finds the event being checked in to, which only its organizer can do.
+async fn organizer_event(
+    pool: &StoragePool,
+    current_handle: &Handle,
+    handle_slug: &str,
+    event_rkey: &str,
+    collection: &str,
+) -> Result<Event, WebError> {
+    let profile = match parse_input(handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(pool, &handle).await?,
+        Ok(InputType::Plc(did) | InputType::Web(did)) => handle_for_did(pool, &did).await?,
+        _ => return Err(CommonError::InvalidHandleSlug.into()),
+    };
+
+    if profile.did != current_handle.did {
+        return Err(CheckinError::NotAuthorized.into());
+    }
+
+    let lookup_aturi = format!("at://{}/{}/{}", profile.did, collection, event_rkey);
+    Ok(event_get(pool, &lookup_aturi).await?)
+}
+
+/// A person shown on the check-in page.
+#[derive(Debug, Serialize)]
+struct AttendeeRow {
+    did: String,
+    handle: Option<String>,
+    display_name: Option<String>,
+    avatar: Option<String>,
+    status: Option<String>,
+    checked_in_at: Option<DateTime<Utc>>,
+}
+
+impl AttendeeRow {
+    fn new(attendee: CheckinAttendee, handle: Option<&Handle>) -> Self {
+        Self {
+            did: attendee.did,
+            handle: handle.map(|value| value.handle.clone()),
+            display_name: handle.and_then(|value| value.display_name.clone()),
+            avatar: handle.and_then(|value| value.avatar.clone()),
+            status: attendee.status,
+            checked_in_at: attendee.checked_in_at,
+        }
+    }
+}
+
+/// Lists the attendees of an event that match a search, with their handles.
+/// A search for a handle or DID that didn't RSVP finds them as a walk-in.
+async fn matching_attendees(
+    ctx: &UserRequestContext,
+    event: &Event,
+    query: &str,
+) -> Result<Vec<AttendeeRow>, WebError> {
+    let pool = &ctx.web_context.pool;
+    let attendees = checkin_attendees(pool, &event.aturi).await?;
+
+    let handles = handles_by_did_cached(
+        pool,
+        &ctx.web_context.cache_pool,
+        attendees.iter().map(|value| value.did.clone()).collect(),
+    )
+    .await?;
+
+    let query = query.trim().trim_start_matches('@').to_lowercase();
+    let mut rows = attendees
+        .into_iter()
+        .filter_map(|attendee| {
+            let handle = handles.get(&attendee.did);
+            let matches = query.is_empty()
+                || attendee.did == query
+                || handle.is_some_and(|value| {
+                    value.handle.to_lowercase().contains(&query)
+                        || value
+                            .display_name
+                            .as_ref()
+                            .is_some_and(|name| name.to_lowercase().contains(&query))
+                });
+            matches.then(|| AttendeeRow::new(attendee, handle))
+        })
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() && !query.is_empty() {
+        let walk_in = match parse_input(&query) {
+            Ok(InputType::Handle(handle)) => handle_for_handle(pool, &handle).await.ok(),
+            Ok(InputType::Plc(did) | InputType::Web(did)) => handle_for_did(pool, &did).await.ok(),
+            _ => None,
+        };
+        if let Some(walk_in) = walk_in {
+            let attendee = CheckinAttendee {
+                did: walk_in.did.clone(),
+                status: None,
+                checked_in_at: None,
+            };
+            rows.push(AttendeeRow::new(attendee, Some(&walk_in)));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// The door check-in page of an event. Organizers search for attendees and
+/// check them in, and the counts update as others check people in.
+pub async fn handle_checkin(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(checkin_param): Query<CheckinParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/")?;
+
+    let encoded_collection = urlencoding::encode(&checkin_param.collection).to_string();
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/{}/{}/checkin", ctx.web_context.config.external_base, handle_slug, event_rkey),
+        checkin_url => format!("/{}/{}/checkin?collection={}", handle_slug, event_rkey, encoded_collection),
+        counts_url => format!("/{}/{}/checkin/counts?collection={}", handle_slug, event_rkey, encoded_collection),
+        q => checkin_param.q.clone(),
+    };
+
+    let render_template = select_template!("checkin", hx_boosted, hx_request, ctx.language);
+    let error_template = select_template!(hx_boosted, hx_request, ctx.language);
+
+    let event = match organizer_event(
+        &ctx.web_context.pool,
+        &current_handle,
+        &handle_slug,
+        &event_rkey,
+        &checkin_param.collection,
+    )
+    .await
+    {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let event_url = url_from_aturi(&ctx.web_context.config.external_base, &event.aturi)?;
+
+    let attendees = match matching_attendees(&ctx, &event, &checkin_param.q).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let counts = match checkin_counts(&ctx.web_context.pool, &event.aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                event_name => event.name,
+                event_url,
+                attendees,
+                counts,
+            }},
+        ),
+    )
+        .into_response())
+}
+
+/// Checks a person in, or undoes their check-in, and shows the attendees
+/// matching the search again.
+pub async fn handle_checkin_update(
+    ctx: UserRequestContext,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(checkin_param): Query<CheckinParam>,
+    Form(checkin_form): Form<CheckinForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let encoded_collection = urlencoding::encode(&checkin_param.collection).to_string();
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        checkin_url => format!("/{}/{}/checkin?collection={}", handle_slug, event_rkey, encoded_collection),
+        q => checkin_form.q.clone(),
+    };
+
+    let render_template = format!(
+        "checkin.{}.partial.html",
+        ctx.language.to_string().to_lowercase()
+    );
+    let error_template = select_template!(false, true, ctx.language);
+
+    let event = match organizer_event(
+        &ctx.web_context.pool,
+        &current_handle,
+        &handle_slug,
+        &event_rkey,
+        &checkin_param.collection,
+    )
+    .await
+    {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let mut attendees = match matching_attendees(&ctx, &event, &checkin_form.q).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let did = match &checkin_form.did {
+        Some(did) => Some(did.clone()),
+        None if !checkin_form.q.trim().is_empty() && attendees.len() == 1 => {
+            Some(attendees[0].did.clone())
+        }
+        None => None,
+    };
+
+    if let Some(did) = did {
+        let result = if checkin_form.undo.is_some() {
+            checkin_delete(&ctx.web_context.pool, &event.aturi, &did)
+                .await
+                .map_err(WebError::from)
+        } else {
+            match handle_for_did(&ctx.web_context.pool, &did).await {
+                Ok(_) => checkin_insert(
+                    &ctx.web_context.pool,
+                    &event.aturi,
+                    &did,
+                    &current_handle.did,
+                    Utc::now(),
+                )
+                .await
+                .map_err(WebError::from),
+                Err(_) => Err(CheckinError::AttendeeNotFound(did.clone()).into()),
+            }
+        };
+
+        if let Err(err) = result {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+
+        attendees = match matching_attendees(&ctx, &event, &checkin_form.q).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                attendees,
+            }},
+        ),
+    )
+        .into_response())
+}
+
+/// The check-in counts of an event, which the check-in page polls so that
+/// organizers at different doors see each other's check-ins.
+pub async fn handle_checkin_counts(
+    ctx: UserRequestContext,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(checkin_param): Query<CheckinParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let encoded_collection = urlencoding::encode(&checkin_param.collection).to_string();
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        counts_url => format!("/{}/{}/checkin/counts?collection={}", handle_slug, event_rkey, encoded_collection),
+    };
+
+    let render_template = format!(
+        "checkin.{}.counts.html",
+        ctx.language.to_string().to_lowercase()
+    );
+    let error_template = select_template!(false, true, ctx.language);
+
+    let counts = match organizer_event(
+        &ctx.web_context.pool,
+        &current_handle,
+        &handle_slug,
+        &event_rkey,
+        &checkin_param.collection,
+    )
+    .await
+    {
+        Ok(event) => checkin_counts(&ctx.web_context.pool, &event.aturi)
+            .await
+            .map_err(WebError::from),
+        Err(err) => Err(err),
+    };
+
+    let counts = match counts {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                counts,
+            }},
+        ),
+    )
+        .into_response())
+}
@@ -2,12 +2,16 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 
 use anyhow::Result;
+use axum::extract::Multipart;
+use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
 use axum::response::IntoResponse;
+use axum::response::Redirect;
 use axum_extra::extract::Cached;
 use axum_extra::extract::Form;
 use axum_htmx::HxBoosted;
+use axum_htmx::HxRedirect;
 use axum_htmx::HxRequest;
 use axum_template::RenderHtml;
 use chrono::Utc;
@@ -16,34 +20,67 @@ use http::StatusCode;
 use minijinja::context as template_context;
 use serde::Deserialize;
 
+use crate::announcement::announcement_post;
 use crate::atproto::auth::SimpleOAuthSessionProvider;
+use crate::atproto::client::ApplyWrite;
+use crate::atproto::client::ApplyWriteResult;
+use crate::atproto::client::ApplyWritesRequest;
 use crate::atproto::client::CreateRecordRequest;
 use crate::atproto::client::OAuthPdsClient;
+use crate::atproto::lexicon::app::bsky::feed::post::NSID as POST_NSID;
+use crate::atproto::lexicon::com::atproto::repo::StrongRef;
 use crate::atproto::lexicon::community::lexicon::calendar::event::Event;
-use crate::atproto::lexicon::community::lexicon::calendar::event::EventLink;
-use crate::atproto::lexicon::community::lexicon::calendar::event::EventLocation;
 use crate::atproto::lexicon::community::lexicon::calendar::event::Mode;
 use crate::atproto::lexicon::community::lexicon::calendar::event::Status;
 use crate::atproto::lexicon::community::lexicon::calendar::event::NSID;
-use crate::atproto::lexicon::community::lexicon::location::Address;
+use crate::atproto::tid::new_tid;
 use crate::contextual_error;
+use crate::errors::expand_error;
+use crate::event_defaults::DuplicateShift;
+use crate::event_defaults::LEARN_FROM_EVENTS;
 use crate::http::context::WebContext;
 use crate::http::errors::CommonError;
 use crate::http::errors::CreateEventError;
 use crate::http::errors::WebError;
+use crate::http::event_form::set_doors_open_at;
+use crate::http::event_form::set_event_tags;
 use crate::http::event_form::BuildEventContentState;
+use crate::http::event_form::BuildEventError;
 use crate::http::event_form::BuildEventForm;
 use crate::http::event_form::BuildLinkForm;
 use crate::http::event_form::BuildStartsForm;
 use crate::http::middleware_auth::Auth;
 use crate::http::middleware_i18n::Language;
+use crate::http::timezones::set_event_timezone;
 use crate::http::timezones::supported_timezones;
 use crate::http::utils::url_from_aturi;
+use crate::ics::parse_first_event;
+use crate::import_plan::ics_event_places;
+use crate::media::header_image;
+use crate::media::set_header_image;
+use crate::resolve::parse_input;
+use crate::resolve::InputType;
+use crate::richtext::description_facets;
+use crate::richtext::set_description_facets;
 use crate::select_template;
+use crate::storage::announcement::announcement_insert;
+use crate::storage::event::event_find_duplicate;
+use crate::storage::event::event_get;
 use crate::storage::event::event_insert;
+use crate::storage::event::event_list_did_recent_times;
+use crate::storage::event::extract_event_details;
+use crate::storage::event_draft::event_draft_delete;
+use crate::storage::event_draft::event_draft_get;
+use crate::storage::event_draft::event_draft_upsert;
+use crate::storage::handle::handle_for_did;
+use crate::storage::handle::handle_for_handle;
 
 use super::cache_countries::cached_countries;
-use super::event_form::BuildLocationForm;
+use super::event_form::{
+    decode_links, decode_locations, encode_link, encode_location, BuildLocationForm,
+    LOCATION_KIND_ADDRESS,
+};
+use super::handle_media::{header_image_from_library, media_library_views};
 
 pub async fn handle_create_event(
     method: Method,
@@ -58,6 +95,8 @@ pub async fn handle_create_event(
 
     let is_development = cfg!(debug_assertions);
 
+    let media_library = media_library_views(&web_context.pool, &current_handle).await;
+
     let default_context = template_context! {
         current_handle,
         language => language.to_string(),
@@ -65,6 +104,7 @@ pub async fn handle_create_event(
         is_development,
         create_event => true,
         submit_url => format!("/event"),
+        media_library,
     };
     // <a href="/{{ handle_slug }}/{{ event_rkey }}" class="button">Cancel</a>
 
@@ -116,30 +156,87 @@ pub async fn handle_create_event(
         build_event_form.build_state = Some(BuildEventContentState::Selecting);
         starts_form.build_state = Some(BuildEventContentState::Selected);
 
-        // Set default start time to 6:00 PM, 6 hours from now
-        let now = Utc::now(); // + chrono::Duration::hours(6);
-
-        // Parse default timezone string to a Tz object
+        // Start today at the organizer's usual start time, or the instance
+        // default, and end after their usual event length if there is one
         let parsed_tz = default_tz
             .parse::<chrono_tz::Tz>()
             .unwrap_or(chrono_tz::UTC);
 
-        // Get the date in the target timezone
-        let local_date = now.with_timezone(&parsed_tz).date_naive();
+        let mut event_defaults = web_context.config.event_defaults();
+        match event_list_did_recent_times(&web_context.pool, &current_handle.did, LEARN_FROM_EVENTS)
+            .await
+        {
+            Ok(past) => event_defaults = event_defaults.learn(&past, parsed_tz),
+            Err(err) => tracing::warn!("Failed to learn event defaults: {}", err),
+        }
 
-        // Create a datetime at 6:00 PM on that date
-        if let Some(naive_dt) = local_date.and_hms_opt(18, 0, 0) {
-            // Convert to timezone-aware datetime
-            let local_dt = naive_dt.and_local_timezone(parsed_tz).single().unwrap();
-            let utc_dt = local_dt.with_timezone(&Utc);
+        if let Some(starts_at) = event_defaults.starts_at(Utc::now(), parsed_tz) {
+            starts_form.set_starts_at(starts_at, parsed_tz);
+            build_event_form.starts_at = starts_form.starts_at.clone();
 
-            // Format the date and time as expected by the form
-            starts_form.starts_date = Some(local_dt.format("%Y-%m-%d").to_string());
-            starts_form.starts_time = Some(local_dt.format("%H:%M").to_string());
-            starts_form.starts_at = Some(utc_dt.to_string());
-            starts_form.starts_display = Some(local_dt.format("%A, %B %-d, %Y %r %Z").to_string());
+            if let Some(duration) = event_defaults.duration {
+                starts_form.set_ends_at(starts_at + duration, parsed_tz);
+                build_event_form.ends_at = starts_form.ends_at.clone();
+            }
+        }
 
-            build_event_form.starts_at = starts_form.starts_at.clone();
+        // Pick up where the organizer left off if they saved a draft
+        let mut draft_saved_at = None;
+        match event_draft_get(&web_context.pool, &current_handle.did).await {
+            Ok(Some(draft)) => match serde_json::from_value::<BuildEventForm>(draft.form.0) {
+                Ok(draft_form) => {
+                    build_event_form = draft_form;
+                    build_event_form.build_state = Some(BuildEventContentState::Selecting);
+
+                    starts_form = BuildStartsForm::from(build_event_form.clone());
+                    if starts_form.tz.is_none() {
+                        starts_form.tz = Some(default_tz.to_string());
+                    }
+                    let parsed_tz = starts_form
+                        .tz
+                        .as_deref()
+                        .and_then(|value| value.parse::<chrono_tz::Tz>().ok())
+                        .unwrap_or(parsed_tz);
+                    starts_form.build_state = Some(BuildEventContentState::Selected);
+                    match build_event_form
+                        .starts_at
+                        .as_ref()
+                        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+                    {
+                        Some(starts_at) => starts_form.set_starts_at(starts_at, parsed_tz),
+                        None => starts_form.starts_display = Some("--".to_string()),
+                    }
+                    match build_event_form
+                        .ends_at
+                        .as_ref()
+                        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+                    {
+                        Some(ends_at) => starts_form.set_ends_at(ends_at, parsed_tz),
+                        None => starts_form.ends_display = Some("--".to_string()),
+                    }
+                    if let Some(doors_open_at) = build_event_form
+                        .doors_open_at
+                        .as_ref()
+                        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+                    {
+                        starts_form.set_doors_open_at(doors_open_at, parsed_tz);
+                    }
+
+                    location_form = BuildLocationForm::from(build_event_form.clone());
+                    link_form = BuildLinkForm::from(build_event_form.clone());
+
+                    draft_saved_at = Some(
+                        draft
+                            .updated_at
+                            .with_timezone(&parsed_tz)
+                            .format("%e %B %Y %I:%M %P %Z")
+                            .to_string(),
+                    );
+                }
+                Err(err) => tracing::warn!("Failed to restore event draft: {}", err),
+            },
+            Ok(None) => {}
+            Err(err) => tracing::warn!("Failed to get event draft: {}", err),
         }
 
         return Ok(RenderHtml(
@@ -151,6 +248,7 @@ pub async fn handle_create_event(
                 location_form,
                 link_form,
                 timezones,
+                draft_saved_at,
             }},
         )
         .into_response());
@@ -163,18 +261,90 @@ pub async fn handle_create_event(
             build_event_form.name_error = None;
             build_event_form.description = None;
             build_event_form.description_error = None;
+            build_event_form.tags = None;
+            build_event_form.tags_error = None;
             build_event_form.status = Some("planned".to_string());
             build_event_form.status_error = None;
             build_event_form.starts_at = None;
             build_event_form.starts_at_error = None;
             build_event_form.ends_at = None;
             build_event_form.ends_at_error = None;
+            build_event_form.doors_open_at = None;
+            build_event_form.doors_open_at_error = None;
             build_event_form.mode = Some("inperson".to_string());
             build_event_form.mode_error = None;
+            build_event_form.header_image = None;
+            build_event_form.header_image_error = None;
         }
         Some(BuildEventContentState::Selected) => {
-            let found_errors =
+            let mut found_errors =
                 build_event_form.validate(&web_context.i18n_context.locales, &language);
+
+            // The same name and start time from the same organizer is almost
+            // always a form that was submitted twice.
+            if let (false, Some(name)) = (found_errors, build_event_form.name.as_ref()) {
+                let starts_at = build_event_form
+                    .starts_at
+                    .as_ref()
+                    .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok());
+
+                match event_find_duplicate(&web_context.pool, &current_handle.did, name, starts_at)
+                    .await
+                {
+                    Ok(Some(_)) => {
+                        let (err_bare, err_partial) = expand_error(BuildEventError::DuplicateEvent);
+                        build_event_form.name_error =
+                            Some(web_context.i18n_context.locales.format_error(
+                                &language,
+                                &err_bare,
+                                &err_partial,
+                            ));
+                        found_errors = true;
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        return contextual_error!(
+                            web_context,
+                            language,
+                            error_template,
+                            default_context,
+                            err
+                        );
+                    }
+                }
+            }
+
+            // Only images in the organizer's media library can be picked
+            let header_image = match header_image_from_library(
+                &web_context.pool,
+                &current_handle.did,
+                build_event_form.header_image.as_deref(),
+            )
+            .await
+            {
+                Ok(value) => value,
+                Err(WebError::MediaError(err)) => {
+                    let (err_bare, err_partial) = expand_error(err);
+                    build_event_form.header_image_error =
+                        Some(web_context.i18n_context.locales.format_error(
+                            &language,
+                            &err_bare,
+                            &err_partial,
+                        ));
+                    found_errors = true;
+                    None
+                }
+                Err(err) => {
+                    return contextual_error!(
+                        web_context,
+                        language,
+                        error_template,
+                        default_context,
+                        err
+                    );
+                }
+            };
+
             if found_errors {
                 build_event_form.build_state = Some(BuildEventContentState::Selecting);
             } else {
@@ -227,26 +397,28 @@ pub async fn handle_create_event(
                     pds: &current_handle.pds,
                 };
 
-                let locations = match &build_event_form.location_country {
-                    Some(country) => vec![EventLocation::Address(Address::Current {
-                        country: country.clone(),
-                        postal_code: build_event_form.location_postal_code.clone(),
-                        region: build_event_form.location_region.clone(),
-                        locality: build_event_form.location_locality.clone(),
-                        street: build_event_form.location_street.clone(),
-                        name: build_event_form.location_name.clone(),
-                    })],
-                    None => vec![],
-                };
-
-                // Process link if provided
-                let links = match &build_event_form.link_value {
-                    Some(uri) => vec![EventLink::Current {
-                        uri: uri.clone(),
-                        name: build_event_form.link_name.clone(),
-                    }],
-                    None => vec![],
-                };
+                let locations = build_event_form.locations();
+
+                let links = build_event_form.links();
+
+                let mut extra = HashMap::default();
+                set_header_image(&mut extra, header_image);
+                set_event_timezone(&mut extra, build_event_form.tz.as_deref());
+                set_doors_open_at(
+                    &mut extra,
+                    build_event_form
+                        .doors_open_at
+                        .as_ref()
+                        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok()),
+                );
+                let facets = description_facets(
+                    &web_context.http_client,
+                    &web_context.dns_resolver,
+                    build_event_form.description.as_deref().unwrap_or_default(),
+                )
+                .await;
+                set_description_facets(&mut extra, &facets);
+                set_event_tags(&mut extra, &build_event_form.tags());
 
                 let the_record = Event::Current {
                     name: build_event_form
@@ -264,32 +436,69 @@ pub async fn handle_create_event(
                     status,
                     locations,
                     uris: links,
-                    extra: HashMap::default(),
-                };
-
-                let event_record = CreateRecordRequest {
-                    repo: current_handle.did.clone(),
-                    collection: NSID.to_string(),
-                    validate: false,
-                    record_key: None,
-                    record: the_record.clone(),
-                    swap_commit: None,
+                    extra,
                 };
 
-                let create_record_result = client.create_record(&client_auth, event_record).await;
-
-                if let Err(err) = create_record_result {
-                    return contextual_error!(
-                        web_context,
-                        language,
-                        error_template,
-                        default_context,
-                        err
-                    );
+                if !build_event_form.check_record(
+                    &the_record,
+                    &web_context.i18n_context.locales,
+                    &language,
+                ) {
+                    return Ok(RenderHtml(
+                        &render_template,
+                        web_context.engine.clone(),
+                        template_context! { ..default_context, ..template_context! {
+                            build_event_form,
+                            starts_form,
+                            timezones,
+                            location_form,
+                            link_form,
+                        }},
+                    )
+                    .into_response());
                 }
 
-                // create_record_result is guaranteed to be Ok since we checked for Err above
-                let create_record_result = create_record_result?;
+                // An announcement is written in the same commit as the event,
+                // so there is never one without the other.
+                let create_result = if build_event_form.announce.unwrap_or_default() {
+                    create_event_with_announcement(
+                        &client,
+                        &client_auth,
+                        &web_context.config.external_base,
+                        &current_handle.did,
+                        &the_record,
+                        &language.to_string(),
+                    )
+                    .await
+                    .map(|(event, post_uri)| (event, Some(post_uri)))
+                } else {
+                    let event_record = CreateRecordRequest {
+                        repo: current_handle.did.clone(),
+                        collection: NSID.to_string(),
+                        validate: false,
+                        record_key: None,
+                        record: the_record.clone(),
+                        swap_commit: None,
+                    };
+
+                    client
+                        .create_record(&client_auth, event_record)
+                        .await
+                        .map(|event| (event, None))
+                };
+
+                let (create_record_result, announcement_uri) = match create_result {
+                    Ok(value) => value,
+                    Err(err) => {
+                        return contextual_error!(
+                            web_context,
+                            language,
+                            error_template,
+                            default_context,
+                            err
+                        );
+                    }
+                };
 
                 let event_insert_result = event_insert(
                     &web_context.pool,
@@ -311,9 +520,36 @@ pub async fn handle_create_event(
                     );
                 }
 
+                if let Err(err) = event_draft_delete(&web_context.pool, &current_handle.did).await {
+                    tracing::warn!("Failed to delete event draft: {}", err);
+                }
+
+                if let Some(geocoder) = &web_context.geocoder {
+                    geocoder.spawn_geocode_event(&web_context.pool, &create_record_result.uri);
+                }
+
                 let event_url =
                     url_from_aturi(&web_context.config.external_base, &create_record_result.uri)?;
 
+                // Recorded announcements are served in the feeds and used to
+                // find RSVPs that weren't indexed.
+                if let Some(announcement_uri) = announcement_uri {
+                    if let Err(err) = announcement_insert(
+                        &web_context.pool,
+                        &announcement_uri,
+                        &create_record_result.uri,
+                        &current_handle.did,
+                    )
+                    .await
+                    {
+                        tracing::warn!(
+                            aturi = create_record_result.uri,
+                            "Failed to record announcement: {}",
+                            err
+                        );
+                    }
+                }
+
                 return Ok(RenderHtml(
                     &render_template,
                     web_context.engine.clone(),
@@ -346,6 +582,508 @@ pub async fn handle_create_event(
     .into_response())
 }
 
+/// Creates an event and the Bluesky post that announces it in a single
+/// commit, so that a failure can't leave a post that links to an event that
+/// doesn't exist. The event gets its record key up front, since the post
+/// links to it. Returns the event and the URI of the post.
+async fn create_event_with_announcement(
+    client: &OAuthPdsClient<'_>,
+    client_auth: &SimpleOAuthSessionProvider,
+    external_base: &str,
+    did: &str,
+    record: &Event,
+    language: &str,
+) -> Result<(StrongRef, String), anyhow::Error> {
+    let event_rkey = new_tid();
+    let event_url = url_from_aturi(
+        external_base,
+        &format!("at://{}/{}/{}", did, NSID, event_rkey),
+    )?;
+
+    let name = match record {
+        Event::Current { name, .. } => name,
+    };
+    let post = announcement_post(name, &event_url, language, Utc::now());
+
+    let apply_writes_request = ApplyWritesRequest {
+        repo: did.to_string(),
+        validate: false,
+        writes: vec![
+            ApplyWrite::Create {
+                collection: NSID.to_string(),
+                record_key: Some(event_rkey),
+                value: serde_json::to_value(record)?,
+            },
+            ApplyWrite::Create {
+                collection: POST_NSID.to_string(),
+                record_key: None,
+                value: serde_json::to_value(&post)?,
+            },
+        ],
+        swap_commit: None,
+    };
+
+    let results = client
+        .apply_writes(client_auth, apply_writes_request)
+        .await?;
+
+    match results.as_slice() {
+        [ApplyWriteResult::Create {
+            uri: event_uri,
+            cid: event_cid,
+        }, ApplyWriteResult::Create { uri: post_uri, .. }] => Ok((
+            StrongRef {
+                uri: event_uri.clone(),
+                cid: event_cid.clone(),
+            },
+            post_uri.clone(),
+        )),
+        _ => Err(CreateEventError::UnexpectedWriteResults.into()),
+    }
+}
+
+/// Fills in the create event form from the first event of an uploaded
+/// iCalendar file, so that events can be moved over from other tools. The
+/// form is only filled in; the organizer reviews it and submits it as usual.
+pub async fn handle_create_event_import(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    HxBoosted(hx_boosted): HxBoosted,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/event")?;
+
+    let is_development = cfg!(debug_assertions);
+
+    let media_library = media_library_views(&web_context.pool, &current_handle).await;
+
+    let default_context = template_context! {
+        current_handle,
+        language => language.to_string(),
+        canonical_url => format!("https://{}/event", web_context.config.external_base),
+        is_development,
+        create_event => true,
+        submit_url => format!("/event"),
+        media_library,
+    };
+
+    let render_template = select_template!("create_event", hx_boosted, hx_request, language);
+
+    let (default_tz, timezones) = supported_timezones(auth.0.as_ref());
+    let parsed_tz = default_tz
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+
+    let mut ics_content = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("ics_file") {
+            ics_content = field
+                .bytes()
+                .await
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string());
+            break;
+        }
+    }
+
+    let ics_event = match ics_content {
+        Some(content) => parse_first_event(&content, parsed_tz).map_err(expand_error),
+        None => Err(expand_error(BuildEventError::IcsFileMissing)),
+    };
+
+    let mut build_event_form = BuildEventForm {
+        build_state: Some(BuildEventContentState::Selecting),
+        status: Some("scheduled".to_string()),
+        mode: Some("inperson".to_string()),
+        ..Default::default()
+    };
+    let mut location_text = None;
+
+    let ics_error = match ics_event {
+        Ok(ics_event) => {
+            let places = ics_event_places(&ics_event);
+
+            build_event_form.name = ics_event.name;
+            build_event_form.description = ics_event.description;
+            build_event_form.starts_at = ics_event.starts_at.map(|value| value.to_string());
+            build_event_form.ends_at = ics_event.ends_at.map(|value| value.to_string());
+
+            for location in &places.locations {
+                build_event_form.locations.push(encode_location(location));
+            }
+            for link in &places.links {
+                build_event_form.links.push(encode_link(link));
+            }
+            location_text = places.location_text;
+
+            None
+        }
+        Err((err_bare, err_partial)) => Some(web_context.i18n_context.locales.format_error(
+            &language,
+            &err_bare,
+            &err_partial,
+        )),
+    };
+
+    let mut starts_form = BuildStartsForm::from(build_event_form.clone());
+    starts_form.tz = Some(default_tz.to_string());
+    starts_form.build_state = Some(BuildEventContentState::Selected);
+    match build_event_form
+        .starts_at
+        .as_ref()
+        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+    {
+        Some(starts_at) => starts_form.set_starts_at(starts_at, parsed_tz),
+        None => starts_form.starts_display = Some("--".to_string()),
+    }
+    match build_event_form
+        .ends_at
+        .as_ref()
+        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+    {
+        Some(ends_at) => starts_form.set_ends_at(ends_at, parsed_tz),
+        None => starts_form.ends_display = Some("--".to_string()),
+    }
+
+    // Calendar locations are free text, so they are put in the location form
+    // for the organizer to finish instead of being saved as an address.
+    let mut location_form = BuildLocationForm::from(build_event_form.clone());
+    if let Some(location_text) = location_text {
+        location_form.build_state = Some(BuildEventContentState::Selecting);
+        location_form.location_kind = Some(LOCATION_KIND_ADDRESS.to_string());
+        location_form.location_name = Some(location_text);
+    }
+
+    let link_form = BuildLinkForm::from(build_event_form.clone());
+
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            build_event_form,
+            starts_form,
+            location_form,
+            link_form,
+            timezones,
+            ics_error,
+        }},
+    )
+    .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateEventParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+    #[serde(default)]
+    shift: DuplicateShift,
+}
+
+fn default_collection() -> String {
+    NSID.to_string()
+}
+
+/// Fills in the event form from one of the organizer's events, with its
+/// times moved a week or a month later, for events that happen again like
+/// a monthly meetup. The form is only filled in; the organizer reviews it
+/// and submits it as usual.
+pub async fn handle_create_event_duplicate(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(duplicate_event_param): Query<DuplicateEventParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/event")?;
+
+    let is_development = cfg!(debug_assertions);
+
+    let media_library = media_library_views(&web_context.pool, &current_handle).await;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => language.to_string(),
+        canonical_url => format!("https://{}/event", web_context.config.external_base),
+        is_development,
+        create_event => true,
+        submit_url => format!("/event"),
+        media_library,
+    };
+
+    let render_template = select_template!("create_event", hx_boosted, hx_request, language);
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&web_context.pool, &handle)
+            .await
+            .map_err(WebError::from),
+        Ok(InputType::Plc(did) | InputType::Web(did)) => handle_for_did(&web_context.pool, &did)
+            .await
+            .map_err(WebError::from),
+        _ => Err(WebError::from(CommonError::InvalidHandleSlug)),
+    }?;
+
+    if profile.did != current_handle.did {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            CreateEventError::DuplicateNotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let lookup_aturi = format!(
+        "at://{}/{}/{}",
+        profile.did, duplicate_event_param.collection, event_rkey
+    );
+
+    let event = match event_get(&web_context.pool, &lookup_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                web_context,
+                language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let details = extract_event_details(&event);
+
+    // The copy keeps the time zone the organizer picked for the event
+    let (default_tz, mut timezones) = supported_timezones(auth.0.as_ref());
+    if let Some(value) = details.timezone.as_deref() {
+        if !timezones.contains(&value) {
+            timezones.push(value);
+            timezones.sort();
+        }
+    }
+    let default_tz = details.timezone.as_deref().unwrap_or(default_tz);
+    let parsed_tz = default_tz
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+    let shift = duplicate_event_param.shift;
+
+    // Legacy events name their mode and status with their own lexicon, so
+    // only the part after the hash is kept. A copy of a cancelled or moved
+    // event is scheduled.
+    let mode = details
+        .mode
+        .as_deref()
+        .and_then(|value| value.rsplit('#').next())
+        .filter(|value| ["inperson", "virtual", "hybrid"].contains(value))
+        .unwrap_or("inperson");
+    let status = details
+        .status
+        .as_deref()
+        .and_then(|value| value.rsplit('#').next())
+        .filter(|value| *value == "planned")
+        .unwrap_or("scheduled");
+
+    // The header image is only kept when it is in the organizer's media
+    // library, since only those images can be picked.
+    let header_image = match header_image(&event.record.0) {
+        Some(blob) => {
+            header_image_from_library(&web_context.pool, &current_handle.did, Some(blob.cid()))
+                .await
+                .ok()
+                .flatten()
+                .map(|blob| blob.cid().to_string())
+        }
+        None => None,
+    };
+
+    let build_event_form = BuildEventForm {
+        build_state: Some(BuildEventContentState::Selected),
+        name: Some(details.name.to_string()),
+        description: Some(details.description.to_string()),
+        tags: (!details.tags.is_empty()).then(|| details.tags.join(", ")),
+        status: Some(status.to_string()),
+        mode: Some(mode.to_string()),
+        starts_at: details
+            .starts_at
+            .and_then(|value| shift.apply(value, parsed_tz))
+            .map(|value| value.to_string()),
+        ends_at: details
+            .ends_at
+            .and_then(|value| shift.apply(value, parsed_tz))
+            .map(|value| value.to_string()),
+        doors_open_at: details
+            .doors_open_at
+            .and_then(|value| shift.apply(value, parsed_tz))
+            .map(|value| value.to_string()),
+        header_image,
+        locations: details.locations.iter().map(encode_location).collect(),
+        links: details.uris.iter().map(encode_link).collect(),
+        ..Default::default()
+    };
+
+    let mut starts_form = BuildStartsForm::from(build_event_form.clone());
+    starts_form.tz = Some(default_tz.to_string());
+    starts_form.build_state = Some(BuildEventContentState::Selected);
+    match build_event_form
+        .starts_at
+        .as_ref()
+        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+    {
+        Some(starts_at) => starts_form.set_starts_at(starts_at, parsed_tz),
+        None => starts_form.starts_display = Some("--".to_string()),
+    }
+    match build_event_form
+        .ends_at
+        .as_ref()
+        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+    {
+        Some(ends_at) => starts_form.set_ends_at(ends_at, parsed_tz),
+        None => starts_form.ends_display = Some("--".to_string()),
+    }
+    if let Some(doors_open_at) = build_event_form
+        .doors_open_at
+        .as_ref()
+        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok())
+    {
+        starts_form.set_doors_open_at(doors_open_at, parsed_tz);
+    }
+
+    let location_form = BuildLocationForm::from(build_event_form.clone());
+    let link_form = BuildLinkForm::from(build_event_form.clone());
+
+    let duplicated_from = template_context! {
+        name => event.name,
+        url => url_from_aturi(&web_context.config.external_base, &event.aturi)?,
+        shift => match shift {
+            DuplicateShift::Week => "week",
+            DuplicateShift::Month => "month",
+        },
+    };
+
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            build_event_form,
+            starts_form,
+            location_form,
+            link_form,
+            timezones,
+            duplicated_from,
+        }},
+    )
+    .into_response())
+}
+
+/// Saves the event form as the organizer's draft, so that it can be
+/// restored if they leave before creating the event.
+pub async fn handle_event_draft_save(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    Form(build_event_form): Form<BuildEventForm>,
+) -> Result<impl IntoResponse, WebError> {
+    if !hx_request {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let current_handle = match auth.require_flat() {
+        Ok(value) => value,
+        Err(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+    };
+
+    let render_template = format!(
+        "create_event.{}.draft.html",
+        language.to_string().to_lowercase()
+    );
+
+    let now = Utc::now();
+
+    if let Err(err) = event_draft_upsert(
+        &web_context.pool,
+        &current_handle.did,
+        &build_event_form,
+        now,
+    )
+    .await
+    {
+        tracing::error!("Failed to save event draft: {}", err);
+        return Ok(RenderHtml(
+            &render_template,
+            web_context.engine.clone(),
+            template_context! {
+                draft_failed => true,
+            },
+        )
+        .into_response());
+    }
+
+    let (default_tz, _) = supported_timezones(auth.0.as_ref());
+    let parsed_tz = default_tz
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! {
+            draft_saved_at => now
+                .with_timezone(&parsed_tz)
+                .format("%e %B %Y %I:%M %P %Z")
+                .to_string(),
+        },
+    )
+    .into_response())
+}
+
+/// Throws away the organizer's draft and starts a new event form.
+pub async fn handle_event_draft_delete(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/event")?;
+
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    if let Err(err) = event_draft_delete(&web_context.pool, &current_handle.did).await {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            template_context! {},
+            err
+        );
+    }
+
+    if hx_request {
+        let hx_redirect = HxRedirect::try_from("/event");
+        if let Err(err) = hx_redirect {
+            return contextual_error!(
+                web_context,
+                language,
+                error_template,
+                template_context! {},
+                err
+            );
+        }
+        let hx_redirect = hx_redirect.unwrap();
+        Ok((StatusCode::OK, hx_redirect, "").into_response())
+    } else {
+        Ok(Redirect::to("/event").into_response())
+    }
+}
+
 pub async fn handle_starts_at_builder(
     method: Method,
     State(web_context): State<WebContext>,
@@ -405,6 +1143,8 @@ pub async fn handle_starts_at_builder(
         starts_form.ends_time = None;
         starts_form.ends_date = None;
         starts_form.include_ends = None;
+        starts_form.doors_time = None;
+        starts_form.doors_open_at = None;
     }
 
     if starts_form
@@ -475,15 +1215,42 @@ pub async fn handle_location_at_builder(
         .into_response());
     }
 
+    let mut locations = decode_locations(&location_form.locations);
+
+    // Row actions apply to a saved location and leave the location being
+    // edited alone.
+    if let (Some(action), Some(index)) = (
+        location_form.location_action.take(),
+        location_form.location_index,
+    ) {
+        if index < locations.len() {
+            match action.as_str() {
+                "remove" => {
+                    locations.remove(index);
+                }
+                "up" if index > 0 => locations.swap(index, index - 1),
+                "down" if index + 1 < locations.len() => locations.swap(index, index + 1),
+                "edit" => {
+                    location_form.clear_location();
+                    if location_form.load_location(&locations[index]) {
+                        location_form.location_index = Some(index);
+                        location_form.build_state = Some(BuildEventContentState::Selecting);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if action != "edit" {
+            location_form.location_index = None;
+        }
+    }
+
     if location_form
         .build_state
         .as_ref()
         .is_some_and(|value| value == &BuildEventContentState::Reset)
     {
-        location_form.location_country = None;
-        location_form.location_country_error = None;
-        location_form.location_name = None;
-        location_form.location_name_error = None;
+        location_form.clear_location();
     }
 
     if location_form
@@ -494,11 +1261,20 @@ pub async fn handle_location_at_builder(
         let found_errors = location_form.validate(&web_context.i18n_context.locales, &language);
         if found_errors {
             location_form.build_state = Some(BuildEventContentState::Selecting);
-        } else {
-            location_form.build_state = Some(BuildEventContentState::Selected);
+        } else if let Some(location) = location_form.location() {
+            // Saving replaces the location being edited, or adds a new one
+            match location_form.location_index {
+                Some(index) if index < locations.len() => locations[index] = location,
+                _ => locations.push(location),
+            }
+            location_form.clear_location();
+            location_form.build_state = Some(BuildEventContentState::Reset);
         }
     }
 
+    location_form.locations = locations.iter().map(encode_location).collect();
+    location_form.update_rows();
+
     Ok(RenderHtml(
         &render_template,
         web_context.engine.clone(),
@@ -549,15 +1325,38 @@ pub async fn handle_link_at_builder(
         .into_response());
     }
 
+    let mut links = decode_links(&link_form.links);
+
+    // Row actions apply to a saved link and leave the link being edited
+    // alone.
+    if let (Some(action), Some(index)) = (link_form.link_action.take(), link_form.link_index) {
+        if index < links.len() {
+            match action.as_str() {
+                "remove" => {
+                    links.remove(index);
+                }
+                "up" if index > 0 => links.swap(index, index - 1),
+                "down" if index + 1 < links.len() => links.swap(index, index + 1),
+                "edit" => {
+                    link_form.clear_link();
+                    link_form.load_link(&links[index]);
+                    link_form.link_index = Some(index);
+                    link_form.build_state = Some(BuildEventContentState::Selecting);
+                }
+                _ => {}
+            }
+        }
+        if action != "edit" {
+            link_form.link_index = None;
+        }
+    }
+
     if link_form
         .build_state
         .as_ref()
         .is_some_and(|value| value == &BuildEventContentState::Reset)
     {
-        link_form.link_name = None;
-        link_form.link_name_error = None;
-        link_form.link_value = None;
-        link_form.link_value_error = None;
+        link_form.clear_link();
     }
 
     if link_form
@@ -568,11 +1367,20 @@ pub async fn handle_link_at_builder(
         let found_errors = link_form.validate(&web_context.i18n_context.locales, &language);
         if found_errors {
             link_form.build_state = Some(BuildEventContentState::Selecting);
-        } else {
-            link_form.build_state = Some(BuildEventContentState::Selected);
+        } else if let Some(link) = link_form.link() {
+            // Saving replaces the link being edited, or adds a new one
+            match link_form.link_index {
+                Some(index) if index < links.len() => links[index] = link,
+                _ => links.push(link),
+            }
+            link_form.clear_link();
+            link_form.build_state = Some(BuildEventContentState::Reset);
         }
     }
 
+    link_form.links = links.iter().map(encode_link).collect();
+    link_form.update_rows();
+
     Ok(RenderHtml(
         &render_template,
         web_context.engine.clone(),
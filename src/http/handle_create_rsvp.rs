@@ -19,16 +19,26 @@ use crate::{
         },
     },
     contextual_error,
+    errors::expand_error,
     http::{
         context::WebContext,
-        errors::WebError,
+        errors::{RSVPError, WebError},
+        handle_webhooks::notify_webhooks,
         middleware_auth::Auth,
         middleware_i18n::Language,
         rsvp_form::{BuildRSVPForm, BuildRsvpContentState},
         utils::url_from_aturi,
     },
     select_template,
-    storage::event::rsvp_insert,
+    storage::{
+        cache::rsvp_counts_adjust,
+        event::{
+            event_get, event_going_count, event_rsvps_closed, event_rsvps_full, get_user_rsvp,
+            rsvp_insert,
+        },
+        waitlist::waitlist_delete,
+    },
+    webhooks::{WebhookKind, WebhookRsvp},
 };
 
 pub async fn handle_create_rsvp(
@@ -115,6 +125,73 @@ pub async fn handle_create_rsvp(
             if !found_errors {
                 let now = Utc::now();
 
+                // Enforce the RSVP deadline and capacity before anything is
+                // written to the PDS.
+                if let Ok(event) = event_get(
+                    &web_context.pool,
+                    build_rsvp_form.subject_aturi.as_ref().unwrap(),
+                )
+                .await
+                {
+                    if event_rsvps_closed(&event, now) {
+                        return contextual_error!(
+                            web_context,
+                            language,
+                            error_template,
+                            default_context,
+                            RSVPError::Closed
+                        );
+                    }
+
+                    // People who are already going keep their spot
+                    if build_rsvp_form.status.as_deref() == Some("going")
+                        && event.rsvp_capacity.is_some()
+                    {
+                        let already_going =
+                            get_user_rsvp(&web_context.pool, &event.aturi, &current_handle.did)
+                                .await
+                                .ok()
+                                .flatten()
+                                .is_some_and(|status| status == "going");
+
+                        let going_count =
+                            match event_going_count(&web_context.pool, &event.aturi).await {
+                                Ok(value) => value,
+                                Err(err) => {
+                                    return contextual_error!(
+                                        web_context,
+                                        language,
+                                        error_template,
+                                        default_context,
+                                        err
+                                    );
+                                }
+                            };
+
+                        // A full event is answered with the waitlist as the
+                        // next step instead of a generic failure
+                        if !already_going && event_rsvps_full(&event, going_count) {
+                            let (err_bare, err_partial) = expand_error(RSVPError::EventFull);
+                            let event_full_message = web_context.i18n_context.locales.format_error(
+                                &language,
+                                &err_bare,
+                                &err_partial,
+                            );
+
+                            return Ok(RenderHtml(
+                                &render_template,
+                                web_context.engine.clone(),
+                                template_context! { ..default_context, ..template_context! {
+                                    build_rsvp_form,
+                                    event_full => true,
+                                    event_full_message,
+                                }},
+                            )
+                            .into_response());
+                        }
+                    }
+                }
+
                 let client_auth: SimpleOAuthSessionProvider =
                     SimpleOAuthSessionProvider::try_from(auth.1.unwrap())?;
 
@@ -135,6 +212,12 @@ pub async fn handle_create_rsvp(
                     _ => unreachable!(),
                 };
 
+                let previous_status =
+                    get_user_rsvp(&web_context.pool, &subject.uri, &current_handle.did)
+                        .await
+                        .ok()
+                        .flatten();
+
                 let mut h = MetroHash64::default();
                 h.write(subject.uri.clone().as_bytes());
 
@@ -190,6 +273,54 @@ pub async fn handle_create_rsvp(
                     );
                 }
 
+                let status = build_rsvp_form.status.clone().unwrap();
+
+                // Going takes the spot someone was waiting for
+                if status == "going" {
+                    if let Err(err) = waitlist_delete(
+                        &web_context.pool,
+                        build_rsvp_form.subject_aturi.as_ref().unwrap(),
+                        &current_handle.did,
+                    )
+                    .await
+                    {
+                        tracing::warn!(error = ?err, "unable to remove rsvp from waitlist");
+                    }
+                }
+
+                if previous_status.as_ref() != Some(&status) {
+                    // The counts are counted again when the cached ones
+                    // expire, so a failed adjustment is only logged.
+                    if let Err(err) = rsvp_counts_adjust(
+                        &web_context.cache_pool,
+                        build_rsvp_form.subject_aturi.as_ref().unwrap(),
+                        previous_status.as_deref(),
+                        Some(&status),
+                    )
+                    .await
+                    {
+                        tracing::warn!(error = ?err, "unable to adjust cached rsvp counts");
+                    }
+
+                    let kind = if previous_status.is_some() {
+                        WebhookKind::RsvpUpdated
+                    } else {
+                        WebhookKind::RsvpCreated
+                    };
+                    notify_webhooks(
+                        &web_context.pool,
+                        &web_context.config.external_base,
+                        build_rsvp_form.subject_aturi.as_ref().unwrap(),
+                        kind,
+                        Some(WebhookRsvp {
+                            did: current_handle.did.clone(),
+                            status,
+                            previous_status,
+                        }),
+                    )
+                    .await;
+                }
+
                 let event_url = url_from_aturi(
                     &web_context.config.external_base,
                     build_rsvp_form.subject_aturi.clone().unwrap().as_str(),
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Serialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext, errors::WebError, event_view::EventView,
+        handle_index::section_event_views,
+    },
+    select_template,
+    storage::{
+        event::{event_list_organized_by_did, event_list_rsvped_by_did},
+        event_draft::event_draft_get,
+    },
+};
+
+/// The most events shown in each section of the dashboard.
+const DASHBOARD_SECTION_SIZE: i64 = 20;
+
+#[derive(Debug, Serialize)]
+pub struct DashboardSectionView {
+    pub kind: &'static str,
+    pub events: Vec<EventView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DashboardDraftView {
+    pub name: Option<String>,
+    pub saved_at: String,
+}
+
+/// Shows the signed in identity the events they organize, are going to and
+/// are interested in that haven't finished yet, and the event they were
+/// creating when they left.
+pub async fn handle_dashboard(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/dashboard")?;
+
+    let canonical_url = format!("https://{}/dashboard", ctx.web_context.config.external_base);
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => canonical_url,
+    };
+
+    let render_template = select_template!("dashboard", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let pool = &ctx.web_context.pool;
+    let now = Utc::now();
+
+    let mut sections = Vec::with_capacity(3);
+    for kind in ["organizer", "going", "interested"] {
+        let events = if kind == "organizer" {
+            event_list_organized_by_did(pool, &current_handle.did, now, 1, DASHBOARD_SECTION_SIZE)
+                .await
+        } else {
+            event_list_rsvped_by_did(
+                pool,
+                &current_handle.did,
+                kind,
+                now,
+                1,
+                DASHBOARD_SECTION_SIZE,
+            )
+            .await
+        };
+
+        let events = match events {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+        let mut events = section_event_views(&ctx.web_context, &ctx.auth, events).await?;
+        events.truncate(DASHBOARD_SECTION_SIZE as usize);
+
+        sections.push(DashboardSectionView { kind, events });
+    }
+
+    let draft = match event_draft_get(pool, &current_handle.did).await {
+        Ok(value) => value.map(|draft| {
+            let tz = current_handle.tz.parse::<Tz>().unwrap_or(Tz::UTC);
+            DashboardDraftView {
+                name: draft.form.0["name"]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string),
+                saved_at: draft
+                    .updated_at
+                    .with_timezone(&tz)
+                    .format("%e %B %Y %I:%M %P %Z")
+                    .to_string(),
+            }
+        }),
+        Err(err) => {
+            tracing::warn!("Failed to get event draft: {}", err);
+            None
+        }
+    };
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                sections,
+                draft,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
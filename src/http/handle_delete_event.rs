@@ -0,0 +1,198 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use http::{Method, StatusCode};
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::{
+        auth::SimpleOAuthSessionProvider,
+        client::{DeleteRecordRequest, OAuthPdsClient},
+        lexicon::{
+            community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+            events::smokesignal::calendar::event::NSID as SMOKESIGNAL_EVENT_NSID,
+        },
+    },
+    contextual_error,
+    http::context::UserRequestContext,
+    http::errors::{DeleteEventError, WebError},
+    http::utils::url_from_aturi,
+    resolve::{parse_input, InputType},
+    select_template,
+    storage::{
+        event::{event_delete, event_get},
+        handle::{handle_for_did, handle_for_handle},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteEventParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+}
+
+fn default_collection() -> String {
+    LexiconCommunityEventNSID.to_string()
+}
+
+pub async fn handle_delete_event(
+    ctx: UserRequestContext,
+    method: Method,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(delete_event_param): Query<DeleteEventParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/")?;
+
+    let collection = delete_event_param.collection;
+    let encoded_collection = urlencoding::encode(&collection).to_string();
+
+    let default_context = template_context! {
+        current_handle,
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/{}/{}/delete", ctx.web_context.config.external_base, handle_slug, event_rkey),
+        submit_url => format!("/{}/{}/delete?collection={}", handle_slug, event_rkey, encoded_collection),
+        cancel_url => format!("/{}/{}?collection={}", handle_slug, event_rkey, encoded_collection),
+    };
+
+    let render_template = select_template!("delete_event", hx_boosted, hx_request, ctx.language);
+    let error_template = select_template!(hx_boosted, hx_request, ctx.language);
+
+    // Only event collections indexed by this instance can be deleted
+    if collection != LexiconCommunityEventNSID && collection != SMOKESIGNAL_EVENT_NSID {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            DeleteEventError::UnsupportedCollection,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    // Lookup the event
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&ctx.web_context.pool, &handle)
+            .await
+            .map_err(WebError::from),
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&ctx.web_context.pool, &did)
+                .await
+                .map_err(WebError::from)
+        }
+        _ => Err(WebError::from(DeleteEventError::InvalidHandleSlug)),
+    }?;
+
+    // Check if the user is authorized to delete this event (must be the creator)
+    if profile.did != current_handle.did {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            DeleteEventError::NotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let lookup_aturi = format!("at://{}/{}/{}", profile.did, collection, event_rkey);
+
+    let event = match event_get(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let event_url = url_from_aturi(&ctx.web_context.config.external_base, &lookup_aturi)?;
+
+    if method == Method::POST {
+        let client_auth: SimpleOAuthSessionProvider =
+            SimpleOAuthSessionProvider::try_from(ctx.auth.1.unwrap())?;
+
+        let client = OAuthPdsClient {
+            http_client: &ctx.web_context.http_client,
+            pds: &current_handle.pds,
+        };
+
+        // Remove the record from the PDS first so that the local index never
+        // drops an event that still exists on the network.
+        let delete_record_request = DeleteRecordRequest {
+            repo: current_handle.did.clone(),
+            collection: collection.clone(),
+            record_key: event_rkey.clone(),
+            swap_record: Some(event.cid.clone()),
+            swap_commit: None,
+        };
+
+        if let Err(err) = client
+            .delete_record(&client_auth, delete_record_request)
+            .await
+        {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                DeleteEventError::PdsDeleteFailed(err.to_string()),
+                StatusCode::OK
+            );
+        }
+
+        let removed_rsvps = match event_delete(&ctx.web_context.pool, &lookup_aturi).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err,
+                    StatusCode::OK
+                );
+            }
+        };
+
+        return Ok((
+            StatusCode::OK,
+            RenderHtml(
+                &render_template,
+                ctx.web_context.engine.clone(),
+                template_context! { ..default_context, ..template_context! {
+                    operation_completed => true,
+                    event_name => event.name,
+                    removed_rsvps,
+                }},
+            ),
+        )
+            .into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                event_name => event.name,
+                event_url,
+            }},
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::{
+        auth::SimpleOAuthSessionProvider,
+        client::{DeleteRecordRequest, OAuthPdsClient},
+        uri::parse_aturi,
+    },
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{RSVPError, WebError},
+    },
+    select_template,
+    storage::{
+        cache::rsvp_counts_adjust,
+        event::{get_user_rsvp_record, rsvp_delete},
+    },
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DeleteRsvpForm {
+    subject_aturi: String,
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_delete_rsvp(
+    ctx: UserRequestContext,
+    Form(delete_rsvp_form): Form<DeleteRsvpForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.rsvp_cleared.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let rsvp = match get_user_rsvp_record(
+        &ctx.web_context.pool,
+        &delete_rsvp_form.subject_aturi,
+        &current_handle.did,
+    )
+    .await
+    {
+        Ok(Some(value)) => value,
+        Ok(None) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                RSVPError::NotFound
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let (_, collection, record_key) = parse_aturi(&rsvp.aturi)?;
+
+    let client_auth: SimpleOAuthSessionProvider =
+        SimpleOAuthSessionProvider::try_from(ctx.auth.1.unwrap())?;
+
+    let client = OAuthPdsClient {
+        http_client: &ctx.web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    // Remove the record from the PDS first so that the RSVP is never dropped
+    // locally while it still exists on the network.
+    let delete_record_request = DeleteRecordRequest {
+        repo: current_handle.did.clone(),
+        collection,
+        record_key,
+        swap_record: Some(rsvp.cid.clone()),
+        swap_commit: None,
+    };
+
+    if let Err(err) = client
+        .delete_record(&client_auth, delete_record_request)
+        .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    if let Err(err) = rsvp_delete(&ctx.web_context.pool, &rsvp.aturi).await {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    if let Err(err) = rsvp_counts_adjust(
+        &ctx.web_context.cache_pool,
+        &rsvp.event_aturi,
+        Some(&rsvp.status),
+        None,
+    )
+    .await
+    {
+        tracing::warn!(error = ?err, "unable to adjust cached rsvp counts");
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => delete_rsvp_form.subject_aturi },
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,27 @@
+use axum::{extract::Path, response::IntoResponse};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use http::StatusCode;
+
+use super::middleware_banners::{dismiss_banner, dismissed_banners, COOKIE_DISMISSED_BANNERS};
+
+/// Remembers that the visitor dismissed a banner, so that it isn't shown to
+/// them again. The banner is swapped out with the empty response.
+pub async fn handle_dismiss_banner(
+    jar: CookieJar,
+    Path(banner_id): Path<String>,
+) -> impl IntoResponse {
+    if ulid::Ulid::from_string(&banner_id).is_err() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let value = dismiss_banner(dismissed_banners(&jar), &banner_id);
+
+    let mut cookie = Cookie::new(COOKIE_DISMISSED_BANNERS, value);
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    cookie.set_same_site(Some(SameSite::Lax));
+    cookie.set_max_age(Some(cookie::time::Duration::days(90)));
+
+    (jar.add(cookie), StatusCode::OK).into_response()
+}
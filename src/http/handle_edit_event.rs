@@ -11,27 +11,33 @@ use crate::{
     atproto::{
         auth::SimpleOAuthSessionProvider,
         client::{OAuthPdsClient, PutRecordRequest},
+        errors::ClientError,
         lexicon::community::lexicon::calendar::event::{
-            Event as LexiconCommunityEvent, EventLink, EventLocation, Mode, NamedUri, Status,
-            NSID as LexiconCommunityEventNSID,
+            Event as LexiconCommunityEvent, Mode, Status, NSID as LexiconCommunityEventNSID,
         },
-        lexicon::community::lexicon::location::Address,
     },
     contextual_error,
+    errors::expand_error,
     http::context::UserRequestContext,
     http::errors::EditEventError,
     http::errors::{CommonError, WebError},
-    http::event_form::BuildLocationForm,
+    http::event_form::{
+        encode_link, encode_location, set_doors_open_at, set_event_tags, BuildLocationForm,
+    },
     http::event_form::{BuildEventContentState, BuildEventForm, BuildLinkForm, BuildStartsForm},
-    http::location_edit_status::{check_location_edit_status, LocationEditStatus},
-    http::timezones::supported_timezones,
+    http::handle_media::{header_image_from_library, media_library_views},
+    http::handle_webhooks::notify_webhooks,
+    http::timezones::{set_event_timezone, supported_timezones},
     http::utils::url_from_aturi,
+    media::{header_image, set_header_image},
     resolve::{parse_input, InputType},
+    richtext::{description_facets, set_description_facets},
     select_template,
     storage::{
-        event::{event_get, event_update_with_metadata},
+        event::{event_get, event_update_with_metadata, extract_event_details},
         handle::{handle_for_did, handle_for_handle},
     },
+    webhooks::WebhookKind,
 };
 
 pub async fn handle_edit_event(
@@ -46,6 +52,8 @@ pub async fn handle_edit_event(
         .auth
         .require(&ctx.web_context.config.destination_key, "/")?;
 
+    let media_library = media_library_views(&ctx.web_context.pool, &current_handle).await;
+
     let default_context = template_context! {
         current_handle,
         language => ctx.language.to_string(),
@@ -53,6 +61,7 @@ pub async fn handle_edit_event(
         create_event => false,
         submit_url => format!("/{}/{}/edit", handle_slug, event_rkey),
         cancel_url => format!("/{}/{}", handle_slug, event_rkey),
+        media_library,
     };
 
     let render_template = select_template!("edit_event", hx_boosted, hx_request, ctx.language);
@@ -130,7 +139,30 @@ pub async fn handle_edit_event(
             }
         };
 
-    let (default_tz, timezones) = supported_timezones(ctx.current_handle.as_ref());
+    // The event keeps its header image when the image has since been removed
+    // from the media library, so it is offered in the form as well.
+    let current_header_image = header_image(&event.record.0);
+    let event_header_image = current_header_image
+        .as_ref()
+        .map(|blob| blob.cid().to_string())
+        .filter(|blob_cid| {
+            !media_library
+                .iter()
+                .any(|media| &media.blob_cid == blob_cid)
+        });
+
+    // Times are edited in the time zone the organizer picked for the event,
+    // when it has one
+    let event_details = extract_event_details(&event);
+    let event_timezone = event_details.timezone.clone();
+    let (default_tz, mut timezones) = supported_timezones(ctx.current_handle.as_ref());
+    if let Some(value) = event_timezone.as_deref() {
+        if !timezones.contains(&value) {
+            timezones.push(value);
+            timezones.sort();
+        }
+    }
+    let default_tz = event_timezone.as_deref().unwrap_or(default_tz);
 
     let parsed_tz = default_tz
         .parse::<chrono_tz::Tz>()
@@ -161,15 +193,6 @@ pub async fn handle_edit_event(
 
     let is_development = cfg!(debug_assertions);
 
-    // Check if event locations can be edited
-    let location_edit_status = match &community_event {
-        LexiconCommunityEvent::Current { locations, .. } => check_location_edit_status(locations),
-    };
-
-    // Set flags for template rendering
-    let locations_editable = location_edit_status.is_editable();
-    let location_edit_reason = location_edit_status.edit_reason();
-
     // For GET requests, populate the form with existing event data
     if method == Method::GET {
         // Extract data from the parsed community event
@@ -181,46 +204,28 @@ pub async fn handle_edit_event(
                 mode,
                 starts_at,
                 ends_at,
+                locations,
                 uris,
                 ..
             } => {
                 build_event_form.name = Some(name.clone());
                 build_event_form.description = Some(description.clone());
+                build_event_form.tags =
+                    (!event_details.tags.is_empty()).then(|| event_details.tags.join(", "));
+                build_event_form.header_image = current_header_image
+                    .as_ref()
+                    .map(|blob| blob.cid().to_string());
 
-                // If we have a single address location, populate the form fields with its data
-                if let LocationEditStatus::Editable(Address::Current {
-                    country,
-                    postal_code,
-                    region,
-                    locality,
-                    street,
-                    name,
-                }) = &location_edit_status
-                {
-                    build_event_form.location_country = Some(country.clone());
-                    build_event_form.location_postal_code = postal_code.clone();
-                    build_event_form.location_region = region.clone();
-                    build_event_form.location_locality = locality.clone();
-                    build_event_form.location_street = street.clone();
-                    build_event_form.location_name = name.clone();
-
-                    location_form.location_country = Some(country.clone());
-                    location_form.location_postal_code = postal_code.clone();
-                    location_form.location_region = region.clone();
-                    location_form.location_locality = locality.clone();
-                    location_form.location_street = street.clone();
-                    location_form.location_name = name.clone();
-                }
-
-                // If we have URIs, populate the link form with the first one
-                if !uris.is_empty() {
-                    let EventLink::Current { uri, name } = &uris[0];
-                    build_event_form.link_value = Some(uri.clone());
-                    build_event_form.link_name = name.clone();
+                // Every location is listed in the location form. Locations
+                // that the form can't edit are kept as they are.
+                build_event_form.locations = locations.iter().map(encode_location).collect();
+                location_form.locations = build_event_form.locations.clone();
+                location_form.update_rows();
 
-                    link_form.link_value = Some(uri.clone());
-                    link_form.link_name = name.clone();
-                }
+                // Every link is listed in the link form
+                build_event_form.links = uris.iter().map(encode_link).collect();
+                link_form.links = build_event_form.links.clone();
+                link_form.update_rows();
 
                 // Convert status enum to string
                 if let Some(status_val) = status {
@@ -250,138 +255,29 @@ pub async fn handle_edit_event(
 
                 // Set date/time fields
                 if let Some(start_time) = starts_at {
-                    let local_dt = start_time.with_timezone(&parsed_tz);
-
-                    starts_form.starts_date = Some(local_dt.format("%Y-%m-%d").to_string());
-                    starts_form.starts_time = Some(local_dt.format("%H:%M").to_string());
-                    starts_form.starts_at = Some(start_time.to_string());
-                    starts_form.starts_display =
-                        Some(local_dt.format("%A, %B %-d, %Y %r %Z").to_string());
-
+                    starts_form.set_starts_at(*start_time, parsed_tz);
                     build_event_form.starts_at = starts_form.starts_at.clone();
                 } else {
                     starts_form.starts_display = Some("--".to_string());
                 }
 
                 if let Some(end_time) = ends_at {
-                    let local_dt = end_time.with_timezone(&parsed_tz);
-
-                    starts_form.include_ends = Some(true);
-                    starts_form.ends_date = Some(local_dt.format("%Y-%m-%d").to_string());
-                    starts_form.ends_time = Some(local_dt.format("%H:%M").to_string());
-                    starts_form.ends_at = Some(end_time.to_string());
-                    starts_form.ends_display =
-                        Some(local_dt.format("%A, %B %-d, %Y %r %Z").to_string());
-
+                    starts_form.set_ends_at(*end_time, parsed_tz);
                     build_event_form.ends_at = starts_form.ends_at.clone();
                 } else {
                     starts_form.ends_display = Some("--".to_string());
                 }
+
+                if let Some(doors_open_at) = event_details.doors_open_at {
+                    starts_form.set_doors_open_at(doors_open_at, parsed_tz);
+                    build_event_form.doors_open_at = starts_form.doors_open_at.clone();
+                }
             }
         }
 
+        build_event_form.event_cid = Some(event.cid.clone());
         build_event_form.build_state = Some(BuildEventContentState::Selected);
         starts_form.build_state = Some(BuildEventContentState::Selected);
-        location_form.build_state = Some(BuildEventContentState::Selected);
-        link_form.build_state = Some(BuildEventContentState::Selected);
-
-        // Extract location information for template display
-        let location_display_info = match &community_event {
-            LexiconCommunityEvent::Current { locations, .. } => {
-                if locations.is_empty() {
-                    None
-                } else {
-                    // Format locations for display
-                    let mut formatted_locations = Vec::new();
-
-                    for loc in locations {
-                        match loc {
-                            EventLocation::Address(Address::Current {
-                                country,
-                                postal_code,
-                                region,
-                                locality,
-                                street,
-                                name,
-                            }) => {
-                                let mut data = serde_json::Map::new();
-                                data.insert(
-                                    "type".to_string(),
-                                    serde_json::Value::String("address".to_string()),
-                                );
-                                data.insert(
-                                    "country".to_string(),
-                                    serde_json::Value::String(country.clone()),
-                                );
-
-                                if let Some(n) = name {
-                                    data.insert(
-                                        "name".to_string(),
-                                        serde_json::Value::String(n.clone()),
-                                    );
-                                }
-                                if let Some(s) = street {
-                                    data.insert(
-                                        "street".to_string(),
-                                        serde_json::Value::String(s.clone()),
-                                    );
-                                }
-                                if let Some(l) = locality {
-                                    data.insert(
-                                        "locality".to_string(),
-                                        serde_json::Value::String(l.clone()),
-                                    );
-                                }
-                                if let Some(r) = region {
-                                    data.insert(
-                                        "region".to_string(),
-                                        serde_json::Value::String(r.clone()),
-                                    );
-                                }
-                                if let Some(pc) = postal_code {
-                                    data.insert(
-                                        "postal_code".to_string(),
-                                        serde_json::Value::String(pc.clone()),
-                                    );
-                                }
-
-                                formatted_locations.push(serde_json::Value::Object(data));
-                            }
-                            EventLocation::Uri(NamedUri::Current { uri, name }) => {
-                                let mut data = serde_json::Map::new();
-                                data.insert(
-                                    "type".to_string(),
-                                    serde_json::Value::String("uri".to_string()),
-                                );
-                                data.insert(
-                                    "uri".to_string(),
-                                    serde_json::Value::String(uri.clone()),
-                                );
-
-                                if let Some(n) = name {
-                                    data.insert(
-                                        "name".to_string(),
-                                        serde_json::Value::String(n.clone()),
-                                    );
-                                }
-
-                                formatted_locations.push(serde_json::Value::Object(data));
-                            }
-                            _ => {
-                                let mut data = serde_json::Map::new();
-                                data.insert(
-                                    "type".to_string(),
-                                    serde_json::Value::String("unknown".to_string()),
-                                );
-                                formatted_locations.push(serde_json::Value::Object(data));
-                            }
-                        }
-                    }
-
-                    Some(formatted_locations)
-                }
-            }
-        };
 
         return Ok((
             StatusCode::OK,
@@ -397,9 +293,7 @@ pub async fn handle_edit_event(
                     handle_slug,
                     timezones,
                     is_development,
-                    locations_editable,
-                    location_edit_reason,
-                    location_display_info,
+                    event_header_image,
                 }},
             ),
         )
@@ -414,38 +308,98 @@ pub async fn handle_edit_event(
             build_event_form.name_error = None;
             build_event_form.description = None;
             build_event_form.description_error = None;
+            build_event_form.tags = None;
+            build_event_form.tags_error = None;
             build_event_form.status = None;
             build_event_form.status_error = None;
             build_event_form.starts_at = None;
             build_event_form.starts_at_error = None;
             build_event_form.ends_at = None;
             build_event_form.ends_at_error = None;
+            build_event_form.doors_open_at = None;
+            build_event_form.doors_open_at_error = None;
             build_event_form.mode = None;
             build_event_form.mode_error = None;
+            build_event_form.header_image = None;
+            build_event_form.header_image_error = None;
+            build_event_form.locations.clear();
+            build_event_form.links.clear();
 
             // Regenerate starts_form from the updated build_event_form to ensure date/time fields are synced
             starts_form = BuildStartsForm::from(build_event_form.clone());
             starts_form.build_state = Some(BuildEventContentState::Selecting);
 
             location_form = BuildLocationForm::from(build_event_form.clone());
-            location_form.build_state = Some(BuildEventContentState::Selecting);
 
             link_form = BuildLinkForm::from(build_event_form.clone());
-            link_form.build_state = Some(BuildEventContentState::Selecting);
         }
         Some(BuildEventContentState::Selected) => {
-            let found_errors =
+            let mut found_errors =
                 build_event_form.validate(&ctx.web_context.i18n_context.locales, &ctx.language);
+
+            let picked_header_image = match build_event_form.header_image.as_deref() {
+                Some(blob_cid)
+                    if current_header_image
+                        .as_ref()
+                        .is_some_and(|blob| blob.cid() == blob_cid) =>
+                {
+                    Ok(current_header_image.clone())
+                }
+                blob_cid => {
+                    header_image_from_library(&ctx.web_context.pool, &current_handle.did, blob_cid)
+                        .await
+                }
+            };
+
+            let picked_header_image = match picked_header_image {
+                Ok(value) => value,
+                Err(WebError::MediaError(err)) => {
+                    let (err_bare, err_partial) = expand_error(err);
+                    build_event_form.header_image_error =
+                        Some(ctx.web_context.i18n_context.locales.format_error(
+                            &ctx.language,
+                            &err_bare,
+                            &err_partial,
+                        ));
+                    found_errors = true;
+                    None
+                }
+                Err(err) => {
+                    return contextual_error!(
+                        ctx.web_context,
+                        ctx.language,
+                        error_template,
+                        default_context,
+                        err
+                    );
+                }
+            };
+
             if found_errors {
                 build_event_form.build_state = Some(BuildEventContentState::Selecting);
             } else {
                 build_event_form.build_state = Some(BuildEventContentState::Selected);
             }
 
-            // TODO: Consider adding the event CID and rkey to the form and
-            // comparing it before submission. If the event CID is different
-            // than what is contained in the form, then it could help prevent
-            // race conditions where the event is double edited.
+            // The form carries the CID of the event as it was loaded. When
+            // the event has changed since, the form is shown again instead of
+            // replacing those changes. Submitting it again replaces them.
+            let swap_record = build_event_form
+                .event_cid
+                .clone()
+                .unwrap_or_else(|| event.cid.clone());
+            if !found_errors && swap_record != event.cid {
+                let (err_bare, err_partial) = expand_error(EditEventError::Conflict);
+                build_event_form.record_error =
+                    Some(ctx.web_context.i18n_context.locales.format_error(
+                        &ctx.language,
+                        &err_bare,
+                        &err_partial,
+                    ));
+                build_event_form.event_cid = Some(event.cid.clone());
+                build_event_form.build_state = Some(BuildEventContentState::Selecting);
+                found_errors = true;
+            }
 
             // Preserving "extra" fields from the original record to ensure
             // we don't lose any additional metadata during edits
@@ -492,82 +446,32 @@ pub async fn handle_edit_event(
                     pds: &current_handle.pds,
                 };
 
-                // Extract existing locations and URIs from the original record
-                let (locations, uris) = match &community_event {
-                    LexiconCommunityEvent::Current {
-                        locations, uris, ..
-                    } => {
-                        // Check if locations are editable
-                        let location_edit_status = check_location_edit_status(locations);
-
-                        // If locations aren't editable but the form has location data, return an error
-                        if !location_edit_status.is_editable()
-                            && (build_event_form.location_country.is_some()
-                                || build_event_form.location_street.is_some()
-                                || build_event_form.location_locality.is_some()
-                                || build_event_form.location_region.is_some()
-                                || build_event_form.location_postal_code.is_some()
-                                || build_event_form.location_name.is_some())
-                        {
-                            // Return appropriate error based on edit status
-                            // Note: NoLocations case removed since it's now handled as Editable
-                            let error = match location_edit_status {
-                                LocationEditStatus::MultipleLocations => {
-                                    EditEventError::MultipleLocationsPresent
-                                }
-                                LocationEditStatus::UnsupportedLocationType => {
-                                    EditEventError::UnsupportedLocationType
-                                }
-                                _ => unreachable!(),
-                            };
-
-                            return contextual_error!(
-                                ctx.web_context,
-                                ctx.language,
-                                error_template,
-                                default_context,
-                                error,
-                                StatusCode::BAD_REQUEST
-                            );
-                        }
-
-                        // Handle locations
-                        let updated_locations = if location_edit_status.is_editable()
-                            && build_event_form.location_country.is_some()
-                        {
-                            // Create a new Address from form data
-                            let address = Address::Current {
-                                country: build_event_form.location_country.clone().unwrap(),
-                                postal_code: build_event_form.location_postal_code.clone(),
-                                region: build_event_form.location_region.clone(),
-                                locality: build_event_form.location_locality.clone(),
-                                street: build_event_form.location_street.clone(),
-                                name: build_event_form.location_name.clone(),
-                            };
-
-                            vec![EventLocation::Address(address)]
-                        } else {
-                            // Preserve existing locations
-                            locations.clone()
-                        };
-
-                        // Handle links
-                        let updated_uris = if build_event_form.link_value.is_some() {
-                            let uri = build_event_form.link_value.clone().unwrap();
-                            let name = build_event_form.link_name.clone();
-                            vec![EventLink::Current { uri, name }]
-                        } else {
-                            uris.clone()
-                        };
-
-                        (updated_locations, updated_uris)
-                    }
-                };
+                // The location and link forms list every location and link
+                // of the event, so they replace the existing ones
+                let locations = build_event_form.locations();
+                let uris = build_event_form.links();
 
                 // Extract existing extra fields from the original record
-                let extra = match &community_event {
+                let mut extra = match &community_event {
                     LexiconCommunityEvent::Current { extra, .. } => extra.clone(),
                 };
+                set_header_image(&mut extra, picked_header_image);
+                set_event_timezone(&mut extra, build_event_form.tz.as_deref());
+                set_doors_open_at(
+                    &mut extra,
+                    build_event_form
+                        .doors_open_at
+                        .as_ref()
+                        .and_then(|v| v.parse::<chrono::DateTime<Utc>>().ok()),
+                );
+                let facets = description_facets(
+                    &ctx.web_context.http_client,
+                    &ctx.web_context.dns_resolver,
+                    build_event_form.description.as_deref().unwrap_or_default(),
+                )
+                .await;
+                set_description_facets(&mut extra, &facets);
+                set_event_tags(&mut extra, &build_event_form.tags());
 
                 let updated_record = LexiconCommunityEvent::Current {
                     name: build_event_form
@@ -590,6 +494,32 @@ pub async fn handle_edit_event(
                     extra, // Use the preserved extra fields
                 };
 
+                if !build_event_form.check_record(
+                    &updated_record,
+                    &ctx.web_context.i18n_context.locales,
+                    &ctx.language,
+                ) {
+                    return Ok((
+                        StatusCode::OK,
+                        RenderHtml(
+                            &render_template,
+                            ctx.web_context.engine.clone(),
+                            template_context! { ..default_context, ..template_context! {
+                                build_event_form,
+                                starts_form,
+                                location_form,
+                                link_form,
+                                event_rkey,
+                                handle_slug,
+                                timezones,
+                                is_development,
+                                event_header_image,
+                            }},
+                        ),
+                    )
+                        .into_response());
+                }
+
                 // Update the record in ATP
                 let update_record_request = PutRecordRequest {
                     repo: current_handle.did.clone(),
@@ -598,12 +528,51 @@ pub async fn handle_edit_event(
                     record: updated_record.clone(),
                     validate: false,
                     swap_commit: None,
-                    swap_record: Some(event.cid.clone()),
+                    swap_record: Some(swap_record),
                 };
 
                 let update_record_result =
                     client.put_record(&client_auth, update_record_request).await;
 
+                // The PDS can know of a change that hasn't reached this
+                // instance yet
+                if let Err(err) = &update_record_result {
+                    if matches!(
+                        err.downcast_ref::<ClientError>(),
+                        Some(ClientError::InvalidSwap)
+                    ) {
+                        let (err_bare, err_partial) = expand_error(EditEventError::Conflict);
+                        build_event_form.record_error =
+                            Some(ctx.web_context.i18n_context.locales.format_error(
+                                &ctx.language,
+                                &err_bare,
+                                &err_partial,
+                            ));
+                        build_event_form.event_cid = Some(event.cid.clone());
+                        build_event_form.build_state = Some(BuildEventContentState::Selecting);
+
+                        return Ok((
+                            StatusCode::OK,
+                            RenderHtml(
+                                &render_template,
+                                ctx.web_context.engine.clone(),
+                                template_context! { ..default_context, ..template_context! {
+                                    build_event_form,
+                                    starts_form,
+                                    location_form,
+                                    link_form,
+                                    event_rkey,
+                                    handle_slug,
+                                    timezones,
+                                    is_development,
+                                    event_header_image,
+                                }},
+                            ),
+                        )
+                            .into_response());
+                    }
+                }
+
                 if let Err(err) = update_record_result {
                     return contextual_error!(
                         ctx.web_context,
@@ -642,6 +611,19 @@ pub async fn handle_edit_event(
                     );
                 }
 
+                if let Some(geocoder) = &ctx.web_context.geocoder {
+                    geocoder.spawn_geocode_event(&ctx.web_context.pool, &lookup_aturi);
+                }
+
+                notify_webhooks(
+                    &ctx.web_context.pool,
+                    &ctx.web_context.config.external_base,
+                    &lookup_aturi,
+                    WebhookKind::EventUpdated,
+                    None,
+                )
+                .await;
+
                 let event_url =
                     url_from_aturi(&ctx.web_context.config.external_base, &lookup_aturi)?;
 
@@ -661,8 +643,7 @@ pub async fn handle_edit_event(
                             handle_slug,
                             timezones,
                             is_development,
-                            locations_editable,
-                            location_edit_reason,
+                            event_header_image,
                         }},
                     ),
                 )
@@ -686,8 +667,7 @@ pub async fn handle_edit_event(
                 handle_slug,
                 timezones,
                 is_development,
-                locations_editable,
-                location_edit_reason,
+                event_header_image,
             }},
         ),
     )
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::lexicon::community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+    contextual_error,
+    http::context::UserRequestContext,
+    http::errors::{CommonError, ViewEventError, WebError},
+    http::handle_rsvp_history::daily_totals,
+    http::utils::url_from_aturi,
+    resolve::{parse_input, InputType},
+    select_template,
+    storage::{
+        event::event_get,
+        event_view::{event_view_daily, event_view_referrers},
+        handle::{handle_for_did, handle_for_handle},
+        rsvp_history::rsvp_history_daily_totals,
+    },
+};
+
+/// The referring sites shown on the analytics page.
+const REFERRERS_LIMIT: i64 = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct EventAnalyticsParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+}
+
+fn default_collection() -> String {
+    LexiconCommunityEventNSID.to_string()
+}
+
+/// Shows the organizer of an event how many people viewed its page each day,
+/// which sites they came from, and how the RSVP counts changed over time.
+pub async fn handle_event_analytics(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(event_analytics_param): Query<EventAnalyticsParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/")?;
+
+    let collection = event_analytics_param.collection;
+    let encoded_collection = urlencoding::encode(&collection).to_string();
+
+    let default_context = template_context! {
+        current_handle,
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/{}/{}/analytics", ctx.web_context.config.external_base, handle_slug, event_rkey),
+        rsvp_history_url => format!("/{}/{}/rsvp-history?collection={}", handle_slug, event_rkey, encoded_collection),
+    };
+
+    let render_template = select_template!("event_analytics", hx_boosted, hx_request, ctx.language);
+    let error_template = select_template!(hx_boosted, hx_request, ctx.language);
+
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&ctx.web_context.pool, &handle)
+            .await
+            .map_err(WebError::from),
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&ctx.web_context.pool, &did)
+                .await
+                .map_err(WebError::from)
+        }
+        _ => Err(WebError::from(CommonError::InvalidHandleSlug)),
+    }?;
+
+    if profile.did != current_handle.did {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            ViewEventError::AnalyticsNotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let lookup_aturi = format!("at://{}/{}/{}", profile.did, collection, event_rkey);
+
+    let event = match event_get(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let event_url = url_from_aturi(&ctx.web_context.config.external_base, &lookup_aturi)?;
+
+    let views = match event_view_daily(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let referrers =
+        match event_view_referrers(&ctx.web_context.pool, &lookup_aturi, REFERRERS_LIMIT).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    let rsvp_totals = match rsvp_history_daily_totals(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let total_views: i64 = views.iter().map(|value| value.views).sum();
+    let max_views = views.iter().map(|value| value.views).max().unwrap_or(0);
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                event_name => event.name,
+                event_url,
+                views,
+                total_views,
+                max_views,
+                referrers,
+                rsvp_totals => daily_totals(&rsvp_totals),
+            }},
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use chrono_tz::Tz;
+use http::{
+    header::{CACHE_CONTROL, CONTENT_TYPE, ETAG},
+    HeaderValue, StatusCode,
+};
+
+use crate::{
+    atproto::lexicon::community::lexicon::calendar::event::NSID,
+    event_card::{render_event_card, EventCard},
+    http::{context::WebContext, errors::WebError},
+    resolve::{parse_input, InputType},
+    storage::{
+        event::{event_get, extract_event_details, format_location},
+        handle::{handle_for_did, handle_for_handle},
+    },
+};
+
+/// Renders the image shown when a link to an event is shared. The date is
+/// shown in the organizer's time zone, since the viewer isn't known.
+pub async fn handle_event_card(
+    State(web_context): State<WebContext>,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+) -> Result<Response, WebError> {
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&web_context.pool, &handle).await,
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&web_context.pool, &did).await
+        }
+        _ => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+    let Ok(profile) = profile else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let aturi = format!("at://{}/{}/{}", profile.did, NSID, event_rkey);
+    let Ok(event) = event_get(&web_context.pool, &aturi).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let details = extract_event_details(&event);
+    let tz = profile.tz.parse::<Tz>().unwrap_or(Tz::UTC);
+
+    let name = details.name.to_string();
+    let starts_at = details.starts_at.map(|value| {
+        value
+            .with_timezone(&tz)
+            .format("%e %B %Y %I:%M %P %Z")
+            .to_string()
+    });
+    let location = details.locations.iter().find_map(format_location);
+    let organizer = format!("@{}", profile.handle);
+
+    let png = tokio::task::spawn_blocking(move || {
+        render_event_card(&EventCard {
+            name: &name,
+            starts_at: starts_at.as_deref(),
+            location: location.as_deref(),
+            organizer: &organizer,
+        })
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    let mut response = Response::new(png.into());
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("image/png"));
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+    // The card only changes when the record does
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", event.cid)) {
+        headers.insert(ETAG, etag);
+    }
+
+    Ok(response)
+}
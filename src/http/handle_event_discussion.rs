@@ -0,0 +1,99 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+};
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::lexicon::community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+    discussion::announcement_discussion,
+    http::{context::UserRequestContext, errors::WebError},
+    resolve::{parse_input, InputType},
+    storage::{
+        announcement::announcement_get_for_event,
+        handle::{handle_for_did, handle_for_handle},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct EventDiscussionParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+}
+
+fn default_collection() -> String {
+    LexiconCommunityEventNSID.to_string()
+}
+
+/// Renders the Bluesky replies to the post that announced an event. The
+/// event page loads this after it is shown, so a slow AppView doesn't hold
+/// it up. Events that weren't announced, or whose discussion can't be
+/// fetched, get an empty response.
+pub async fn handle_event_discussion(
+    ctx: UserRequestContext,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(event_discussion_param): Query<EventDiscussionParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&ctx.web_context.pool, &handle).await,
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&ctx.web_context.pool, &did).await
+        }
+        _ => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+    let Ok(profile) = profile else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let lookup_aturi = format!(
+        "at://{}/{}/{}",
+        profile.did, event_discussion_param.collection, event_rkey
+    );
+
+    let post_aturi = match announcement_get_for_event(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(Some(value)) => value,
+        Ok(None) => return Ok(StatusCode::OK.into_response()),
+        Err(err) => {
+            tracing::error!(error = ?err, "unable to get event announcement");
+            return Ok(StatusCode::OK.into_response());
+        }
+    };
+
+    let discussion = match announcement_discussion(
+        &ctx.web_context.http_client,
+        &ctx.web_context.config.appview_hostname,
+        &ctx.web_context.cache_pool,
+        &post_aturi,
+    )
+    .await
+    {
+        Ok(Some(value)) => value,
+        Ok(None) => return Ok(StatusCode::OK.into_response()),
+        Err(err) => {
+            tracing::warn!(post_aturi, error = ?err, "unable to get event discussion");
+            return Ok(StatusCode::OK.into_response());
+        }
+    };
+
+    let render_template = format!(
+        "view_event.{}.discussion.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                language => ctx.language.to_string(),
+                discussion,
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+use unic_langid::LanguageIdentifier;
+
+use crate::{
+    atproto::uri::parse_aturi,
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{EventUpdateError, WebError},
+        utils::url_from_aturi,
+    },
+    notifications::NotificationKind,
+    select_template,
+    storage::{
+        event::event_get,
+        event_update::{event_update_insert, event_update_list, event_update_recipients},
+        notification::{notifications_insert, NotificationParams},
+    },
+};
+
+/// The longest message an update can have, in characters.
+pub(crate) const EVENT_UPDATE_MAX_LENGTH: usize = 1000;
+
+/// How many of the most recent updates are shown on the event page.
+pub(crate) const EVENT_UPDATE_LIST_LIMIT: i64 = 20;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EventUpdateForm {
+    subject_aturi: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Posts an update from the organizer of an event, and delivers it to the
+/// notifications of everyone going or interested.
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_event_update_create(
+    ctx: UserRequestContext,
+    Form(update_form): Form<EventUpdateForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.updates.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_aturi = update_form.subject_aturi;
+
+    // Only the organizer, who owns the repository the event lives in, can
+    // post updates.
+    let is_organizer = parse_aturi(&subject_aturi)
+        .is_ok_and(|(repository, _, _)| repository == current_handle.did);
+    if !is_organizer {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            EventUpdateError::NotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let event = match event_get(&ctx.web_context.pool, &subject_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let message = update_form.message.trim();
+    if message.is_empty() {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            EventUpdateError::MessageRequired
+        );
+    }
+    if message.chars().count() > EVENT_UPDATE_MAX_LENGTH {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            EventUpdateError::MessageTooLong(EVENT_UPDATE_MAX_LENGTH)
+        );
+    }
+
+    let now = Utc::now();
+
+    if let Err(err) = event_update_insert(
+        &ctx.web_context.pool,
+        &event.aturi,
+        &current_handle.did,
+        message,
+        now,
+    )
+    .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    // The update is saved and shown on the event page even if it can't be
+    // delivered to everyone.
+    let recipient_count = match deliver_event_update(
+        &ctx,
+        &current_handle.did,
+        &event.aturi,
+        &event.name,
+        message,
+    )
+    .await
+    {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to deliver event update");
+            0
+        }
+    };
+
+    let event_updates =
+        match event_update_list(&ctx.web_context.pool, &event.aturi, EVENT_UPDATE_LIST_LIMIT).await
+        {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => event.aturi },
+                event_updates,
+                can_edit => true,
+                update_sent => true,
+                recipient_count,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Renders an update in the language of each person going to or interested
+/// in the event and adds it to their notifications. Returns how many people
+/// it was delivered to.
+async fn deliver_event_update(
+    ctx: &UserRequestContext,
+    organizer_did: &str,
+    event_aturi: &str,
+    event_name: &str,
+    message: &str,
+) -> Result<usize> {
+    let recipients =
+        event_update_recipients(&ctx.web_context.pool, event_aturi, organizer_did).await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+
+    let event_url = url_from_aturi(&ctx.web_context.config.external_base, event_aturi)?;
+    let kind = NotificationKind::EventUpdated;
+
+    let mut rendered = Vec::with_capacity(recipients.len());
+    for recipient in &recipients {
+        // Recipients who never signed in get the default language
+        let language = recipient
+            .language
+            .as_deref()
+            .and_then(|value| value.parse::<LanguageIdentifier>().ok())
+            .unwrap_or_default();
+
+        match ctx.web_context.notifications.render(
+            kind,
+            &language,
+            template_context! {
+                event_name,
+                event_url,
+                message,
+            },
+        ) {
+            Ok(notification) => rendered.push((recipient.did.as_str(), notification)),
+            Err(err) => {
+                tracing::warn!(error = ?err, did = %recipient.did, "unable to render event update");
+            }
+        }
+    }
+
+    let notifications = rendered
+        .iter()
+        .map(|(did, notification)| NotificationParams {
+            did,
+            kind: kind.name(),
+            url: &event_url,
+            subject: &notification.subject,
+            text: &notification.text,
+            html: &notification.html,
+        })
+        .collect::<Vec<_>>();
+
+    notifications_insert(&ctx.web_context.pool, &notifications, Utc::now()).await?;
+
+    Ok(notifications.len())
+}
@@ -0,0 +1,446 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use axum_extra::extract::Cached;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::{ExploreError, WebError},
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
+        middleware_auth::Auth,
+        middleware_i18n::Language,
+        pagination::{Pagination, PaginationView},
+        timezones::start_of_day,
+    },
+    select_template,
+    storage::{
+        event_filter::{
+            event_list_filtered, EventFilter, EVENT_FILTER_MODES, EVENT_FILTER_STATUSES,
+        },
+        location::{event_list_upcoming_at_location, location_list_upcoming, location_slug},
+    },
+};
+
+#[derive(Deserialize)]
+pub struct ExplorePath {
+    country: String,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    locality: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ExploreFilters {
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    location: Option<String>,
+}
+
+impl ExploreFilters {
+    fn value(value: &Option<String>) -> Option<&str> {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// The filters as query parameters, to keep them when paging.
+    fn params(&self) -> Vec<(&str, &str)> {
+        [
+            ("from", &self.from),
+            ("to", &self.to),
+            ("mode", &self.mode),
+            ("status", &self.status),
+            ("location", &self.location),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, Self::value(value)?)))
+        .collect()
+    }
+
+    /// Builds the event filter. Dates are days in the time zone of the
+    /// viewer, and the range includes the last day. Without a start date,
+    /// events that haven't started yet are listed.
+    fn event_filter(&self, tz: Tz, now: DateTime<Utc>) -> Result<EventFilter, ExploreError> {
+        let start_of_day = |value: &str, days: u64| -> Result<DateTime<Utc>, ExploreError> {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.checked_add_days(chrono::Days::new(days)))
+                .ok_or_else(|| ExploreError::InvalidDate(value.to_string()))?;
+            Ok(start_of_day(date, tz))
+        };
+
+        let from = Self::value(&self.from)
+            .map(|value| start_of_day(value, 0))
+            .transpose()?;
+        let starts_before = Self::value(&self.to)
+            .map(|value| start_of_day(value, 1))
+            .transpose()?;
+        if let (Some(from), Some(starts_before)) = (from, starts_before) {
+            if starts_before <= from {
+                return Err(ExploreError::InvalidDateRange);
+            }
+        }
+        let starts_after = from.unwrap_or(now);
+
+        let mode = Self::value(&self.mode)
+            .map(|value| match EVENT_FILTER_MODES.contains(&value) {
+                true => Ok(value.to_string()),
+                false => Err(ExploreError::InvalidMode(value.to_string())),
+            })
+            .transpose()?;
+        let status = Self::value(&self.status)
+            .map(|value| match EVENT_FILTER_STATUSES.contains(&value) {
+                true => Ok(value.to_string()),
+                false => Err(ExploreError::InvalidStatus(value.to_string())),
+            })
+            .transpose()?;
+
+        let location = Self::value(&self.location)
+            .map(location_slug)
+            .filter(|value| !value.is_empty());
+
+        Ok(EventFilter {
+            starts_after: Some(starts_after),
+            starts_before,
+            mode,
+            status,
+            location,
+        })
+    }
+}
+
+/// Lists events across all locations, narrowed by date range, mode, status
+/// and location.
+pub async fn handle_explore_events(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    Query(filters): Query<ExploreFilters>,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let render_template = select_template!("explore_events", hx_boosted, false, language);
+    let error_template = select_template!(false, false, language);
+
+    let default_context = template_context! {
+        current_handle => auth.0.clone(),
+        language => language.to_string(),
+        canonical_url => format!("https://{}/explore", web_context.config.external_base),
+        from => ExploreFilters::value(&filters.from),
+        to => ExploreFilters::value(&filters.to),
+        mode => ExploreFilters::value(&filters.mode),
+        status => ExploreFilters::value(&filters.status),
+        location => ExploreFilters::value(&filters.location),
+        modes => EVENT_FILTER_MODES,
+        statuses => EVENT_FILTER_STATUSES,
+    };
+
+    let tz = auth
+        .0
+        .as_ref()
+        .and_then(|handle| handle.tz.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC);
+
+    let event_filter = match filters.event_filter(tz, Utc::now()) {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(web_context, language, render_template, default_context, err);
+        }
+    };
+
+    let (page, page_size) = pagination.clamped();
+
+    let events = match event_list_filtered(&web_context.pool, &event_filter, page, page_size).await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err);
+        }
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            let event_view =
+                EventView::try_from((auth.0.as_ref(), organizer_maybe, &event_view.event));
+
+            match event_view {
+                Ok(event_view) => Some(event_view),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) =
+        hydrate_event_rsvp_counts_cached(&web_context.pool, &web_context.cache_pool, &mut events)
+            .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    let pagination_view =
+        PaginationView::new(page_size, events.len() as i64, page, filters.params());
+
+    if events.len() > page_size as usize {
+        events.truncate(page_size as usize);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &web_context.pool,
+        &web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            web_context.engine.clone(),
+            template_context! {
+                events,
+                pagination => pagination_view,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Lists upcoming events in a country, region or locality, along with the
+/// regions or localities within it that have upcoming events.
+pub async fn handle_explore(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    Path(explore_path): Path<ExplorePath>,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let render_template = select_template!("explore", hx_boosted, false, language);
+    let error_template = select_template!(false, false, language);
+
+    let country = location_slug(&explore_path.country);
+    let region = explore_path.region.as_deref().map(location_slug);
+    let locality = explore_path.locality.as_deref().map(location_slug);
+
+    // Each level of the path links back to the explore page for it
+    let mut path = format!("/explore/{}", country);
+    let mut breadcrumbs = vec![template_context! { slug => country.clone(), path => path.clone() }];
+    for slug in [&region, &locality].into_iter().flatten() {
+        path = format!("{}/{}", path, slug);
+        breadcrumbs.push(template_context! { slug, path => path.clone() });
+    }
+
+    let canonical_url = format!("https://{}{}", web_context.config.external_base, path);
+
+    let default_context = template_context! {
+        current_handle => auth.0.clone(),
+        language => language.to_string(),
+        canonical_url => canonical_url.clone(),
+        breadcrumbs => breadcrumbs.clone(),
+    };
+
+    let (page, page_size) = pagination.clamped();
+    let now = Utc::now();
+
+    let events = match event_list_upcoming_at_location(
+        &web_context.pool,
+        now,
+        &country,
+        region.as_deref(),
+        locality.as_deref(),
+        page,
+        page_size,
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err);
+        }
+    };
+
+    // Localities are the most specific level, so they have nothing to list
+    let children = if locality.is_none() {
+        match location_list_upcoming(&web_context.pool, now, &country, region.as_deref()).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    web_context,
+                    language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            let event_view =
+                EventView::try_from((auth.0.as_ref(), organizer_maybe, &event_view.event));
+
+            match event_view {
+                Ok(event_view) => Some(event_view),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) =
+        hydrate_event_rsvp_counts_cached(&web_context.pool, &web_context.cache_pool, &mut events)
+            .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    let params: Vec<(&str, &str)> = vec![];
+
+    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+
+    if events.len() > page_size as usize {
+        events.truncate(page_size as usize);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &web_context.pool,
+        &web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            web_context.engine.clone(),
+            template_context! {
+                events,
+                children,
+                explore_path => path,
+                pagination => pagination_view,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explore_filters_event_filter() {
+        let now = "2025-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let tz = chrono_tz::America::Vancouver;
+
+        let filter = ExploreFilters::default().event_filter(tz, now).unwrap();
+        assert_eq!(
+            filter,
+            EventFilter {
+                starts_after: Some(now),
+                ..Default::default()
+            }
+        );
+
+        let filters = ExploreFilters {
+            from: Some("2025-06-10".to_string()),
+            to: Some("2025-06-10".to_string()),
+            mode: Some("hybrid".to_string()),
+            status: Some(" ".to_string()),
+            location: Some("British Columbia".to_string()),
+        };
+        let filter = filters.event_filter(tz, now).unwrap();
+        assert_eq!(
+            filter.starts_after,
+            Some("2025-06-10T07:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            filter.starts_before,
+            Some("2025-06-11T07:00:00Z".parse().unwrap())
+        );
+        assert_eq!(filter.mode.as_deref(), Some("hybrid"));
+        assert_eq!(filter.status, None);
+        assert_eq!(filter.location.as_deref(), Some("british-columbia"));
+        assert_eq!(
+            filters.params(),
+            vec![
+                ("from", "2025-06-10"),
+                ("to", "2025-06-10"),
+                ("mode", "hybrid"),
+                ("location", "British Columbia"),
+            ]
+        );
+
+        let invalid = |filters: ExploreFilters| filters.event_filter(tz, now).unwrap_err();
+        assert!(matches!(
+            invalid(ExploreFilters {
+                from: Some("June".to_string()),
+                ..Default::default()
+            }),
+            ExploreError::InvalidDate(_)
+        ));
+        assert!(matches!(
+            invalid(ExploreFilters {
+                from: Some("2025-06-10".to_string()),
+                to: Some("2025-06-09".to_string()),
+                ..Default::default()
+            }),
+            ExploreError::InvalidDateRange
+        ));
+        assert!(matches!(
+            invalid(ExploreFilters {
+                mode: Some("online".to_string()),
+                ..Default::default()
+            }),
+            ExploreError::InvalidMode(_)
+        ));
+    }
+}
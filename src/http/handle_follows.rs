@@ -0,0 +1,111 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{FollowError, WebError},
+    },
+    select_template,
+    storage::{
+        errors::StorageError,
+        follow::{follow_delete, follow_insert},
+        handle::handle_for_did,
+    },
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FollowForm {
+    subject_did: String,
+    #[serde(default)]
+    following: bool,
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_follow_update(
+    ctx: UserRequestContext,
+    Form(follow_form): Form<FollowForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "profile.{}.follow.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_did = follow_form.subject_did;
+
+    if subject_did == current_handle.did {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            FollowError::CannotFollowSelf
+        );
+    }
+
+    match handle_for_did(&ctx.web_context.pool, &subject_did).await {
+        Ok(_) => {}
+        Err(StorageError::HandleNotFound) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                FollowError::IdentityNotFound(subject_did.clone())
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    }
+
+    let result = if follow_form.following {
+        follow_insert(&ctx.web_context.pool, &current_handle.did, &subject_did).await
+    } else {
+        follow_delete(&ctx.web_context.pool, &current_handle.did, &subject_did).await
+    };
+
+    if let Err(err) = result {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                profile => template_context! { did => subject_did },
+                is_following => follow_form.following,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
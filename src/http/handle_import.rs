@@ -1,46 +1,39 @@
+use std::convert::Infallible;
+
 use axum::{
-    extract::{Form, State},
-    response::IntoResponse,
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
 use axum_extra::extract::Cached;
 use axum_htmx::{HxBoosted, HxRequest};
-use axum_template::RenderHtml;
+use axum_template::{RenderHtml, TemplateEngine};
+use chrono::Utc;
 use http::StatusCode;
 use minijinja::context as template_context;
-use serde::Deserialize;
 
 use crate::{
-    atproto::{
-        auth::SimpleOAuthSessionProvider,
-        client::{ListRecordsParams, OAuthPdsClient},
-        lexicon::{
-            community::lexicon::calendar::{
-                event::{Event as LexiconCommunityEvent, NSID as LEXICON_COMMUNITY_EVENT_NSID},
-                rsvp::{
-                    Rsvp as LexiconCommunityRsvp, RsvpStatus as LexiconCommunityRsvpStatus,
-                    NSID as LEXICON_COMMUNITY_RSVP_NSID,
-                },
-            },
-            events::smokesignal::calendar::{
-                event::{Event as SmokeSignalEvent, NSID as SMOKESIGNAL_EVENT_NSID},
-                rsvp::{
-                    Rsvp as SmokeSignalRsvp, RsvpStatus as SmokeSignalRsvpStatus,
-                    NSID as SMOKESIGNAL_RSVP_NSID,
-                },
-            },
-        },
-    },
     contextual_error,
     http::{
-        context::WebContext,
-        errors::{ImportError, WebError},
-        middleware_auth::Auth,
-        middleware_i18n::Language,
+        context::WebContext, errors::WebError, middleware_auth::Auth, middleware_i18n::Language,
     },
     select_template,
-    storage::event::{event_insert_with_metadata, rsvp_insert_with_metadata},
+    storage::{
+        import_job::{
+            import_job_get, import_job_start,
+            model::{
+                IMPORT_JOB_COMPLETED, IMPORT_JOB_FAILED, IMPORT_SOURCE_RECORDS, IMPORT_SOURCE_REPO,
+            },
+        },
+        import_plan::import_plan_list_pending,
+    },
 };
 
+/// How often the progress of a running import is checked for changes.
+const IMPORT_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 pub async fn handle_import(
     State(web_context): State<WebContext>,
     Language(language): Language,
@@ -58,27 +51,84 @@ pub async fn handle_import(
 
     let render_template = select_template!("import", hx_boosted, hx_request, language);
 
+    // Imports run in the background, so one started earlier may still be
+    // running, or have finished since the page was closed.
+    let import_job = match import_job_get(&web_context.pool, &current_handle.did).await {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::error!(?err, "unable to load import job");
+            None
+        }
+    };
+
+    // Calendar imports that were previewed but not confirmed yet
+    let pending_plans: Vec<minijinja::Value> =
+        match import_plan_list_pending(&web_context.pool, &current_handle.did).await {
+            Ok(plans) => plans
+                .into_iter()
+                .map(|plan| {
+                    template_context! {
+                        id => plan.id,
+                        source => plan.source,
+                        created_at => plan.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+                        event_count => plan.items.len(),
+                    }
+                })
+                .collect(),
+            Err(err) => {
+                tracing::error!(?err, "unable to load import plans");
+                vec![]
+            }
+        };
+
     Ok(RenderHtml(
         &render_template,
         web_context.engine.clone(),
-        default_context,
+        template_context! { ..default_context, ..template_context! {
+            pending_plans,
+            import_job,
+        }},
     )
     .into_response())
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ImportForm {
-    pub collection: Option<String>,
-    pub cursor: Option<String>,
+/// Starts a background import of the current identity's events and RSVPs, or
+/// picks up the one that is already running, and shows its progress.
+pub async fn handle_import_submit(
+    State(web_context): State<WebContext>,
+    language: Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+) -> Result<impl IntoResponse, WebError> {
+    start_import(
+        web_context,
+        language,
+        auth,
+        hx_request,
+        IMPORT_SOURCE_RECORDS,
+    )
+    .await
 }
 
-pub async fn handle_import_submit(
+/// Starts a background import of every event and RSVP of the current
+/// identity from a single download of their repository, instead of listing
+/// them a page at a time, and shows its progress like other imports.
+pub async fn handle_import_repo(
     State(web_context): State<WebContext>,
-    Language(language): Language,
+    language: Language,
     Cached(auth): Cached<Auth>,
     HxRequest(hx_request): HxRequest,
-    Form(import_form): Form<ImportForm>,
 ) -> Result<impl IntoResponse, WebError> {
+    start_import(web_context, language, auth, hx_request, IMPORT_SOURCE_REPO).await
+}
+
+async fn start_import(
+    web_context: WebContext,
+    Language(language): Language,
+    auth: Auth,
+    hx_request: bool,
+    source: &str,
+) -> Result<Response, WebError> {
     let current_handle = auth.require_flat()?;
 
     if !hx_request {
@@ -88,311 +138,94 @@ pub async fn handle_import_submit(
     let render_template = select_template!("import", false, true, language);
     let error_template = select_template!(false, hx_request, language);
 
-    let collections = [
-        LEXICON_COMMUNITY_EVENT_NSID,
-        LEXICON_COMMUNITY_RSVP_NSID,
-        SMOKESIGNAL_EVENT_NSID,
-        SMOKESIGNAL_RSVP_NSID,
-    ];
-
-    let collection = import_form.collection.unwrap_or(collections[0].to_string());
-    let cursor = import_form.cursor;
-
-    let client_auth: SimpleOAuthSessionProvider =
-        SimpleOAuthSessionProvider::try_from(auth.1.unwrap())?;
-    let client = OAuthPdsClient {
-        http_client: &web_context.http_client,
-        pds: &current_handle.pds,
-    };
-
-    const LIMIT: u32 = 20;
-
-    // Set up list records parameters to fetch records
-    let list_params = ListRecordsParams {
-        repo: current_handle.did.clone(),
-        collection: collection.clone(),
-        limit: Some(LIMIT),
-        cursor,
-        reverse: None,
-    };
-
-    let render_context = match collection.as_str() {
-        LEXICON_COMMUNITY_EVENT_NSID => {
-            let results = client
-                .list_records::<LexiconCommunityEvent>(&client_auth, &list_params)
-                .await;
-            match results {
-                Ok(list_records) => {
-                    let mut items = vec![];
-
-                    for event_record in list_records.records {
-                        let name = match &event_record.value {
-                            LexiconCommunityEvent::Current { name, .. } => name.clone(),
-                        };
-                        let event_insert_resp = event_insert_with_metadata(
-                            &web_context.pool,
-                            &event_record.uri,
-                            &event_record.cid,
-                            &current_handle.did,
-                            LEXICON_COMMUNITY_EVENT_NSID,
-                            &event_record.value,
-                            &name,
-                        )
-                        .await;
-
-                        let is_ok = if let Err(err) = event_insert_resp {
-                            tracing::error!(?err, "error inserting event");
-                            false
-                        } else {
-                            true
-                        };
-
-                        items.push(format!("{} - {}", event_record.uri, is_ok));
-                    }
-
-                    let (collection, cursor) = if items.len() == LIMIT as usize {
-                        (collection.to_string(), Some(list_records.cursor))
-                    } else {
-                        (LEXICON_COMMUNITY_RSVP_NSID.to_string(), None)
-                    };
-
-                    template_context! {
-                        cursor,
-                        items_paged => true,
-                        items,
-                        collection,
-                        completed => false,
-                    }
-                }
-                Err(err) => {
-                    return contextual_error!(
-                        web_context,
-                        language,
-                        error_template,
-                        template_context! {},
-                        ImportError::FailedToListCommunityEvents(err.to_string())
-                    )
-                }
+    let import_job =
+        match import_job_start(&web_context.pool, &current_handle.did, source, Utc::now()).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    web_context,
+                    language,
+                    error_template,
+                    template_context! {},
+                    err
+                )
             }
-        }
-        LEXICON_COMMUNITY_RSVP_NSID => {
-            let results = client
-                .list_records::<LexiconCommunityRsvp>(&client_auth, &list_params)
-                .await;
-            match results {
-                Ok(list_records) => {
-                    let mut items = vec![];
-
-                    for rsvp_record in list_records.records {
-                        let (event_uri, event_cid, status) = match &rsvp_record.value {
-                            LexiconCommunityRsvp::Current {
-                                subject, status, ..
-                            } => {
-                                let status_str = match status {
-                                    LexiconCommunityRsvpStatus::Going => "going",
-                                    LexiconCommunityRsvpStatus::Interested => "interested",
-                                    LexiconCommunityRsvpStatus::NotGoing => "notgoing",
-                                };
-                                (subject.uri.clone(), subject.cid.clone(), status_str)
-                            }
-                        };
+        };
 
-                        let rsvp_insert_resp = rsvp_insert_with_metadata(
-                            &web_context.pool,
-                            crate::storage::event::RsvpInsertParams {
-                                aturi: &rsvp_record.uri,
-                                cid: &rsvp_record.cid,
-                                did: &current_handle.did,
-                                lexicon: LEXICON_COMMUNITY_RSVP_NSID,
-                                record: &rsvp_record.value,
-                                event_aturi: &event_uri,
-                                event_cid: &event_cid,
-                                status,
-                            },
-                        )
-                        .await;
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! {
+            current_handle,
+            language => language.to_string(),
+            canonical_url => format!("https://{}/import", web_context.config.external_base),
+            import_job,
+        },
+    )
+    .into_response())
+}
 
-                        let is_ok = if let Err(err) = rsvp_insert_resp {
-                            tracing::error!(?err, "error inserting community RSVP");
-                            false
-                        } else {
-                            true
-                        };
+/// Streams the progress of the current identity's import as server-sent
+/// events. Each change is sent as a `progress` event holding the rendered
+/// progress, and a `done` event is sent once the import has finished.
+pub async fn handle_import_events(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require_flat()?;
 
-                        items.push(format!("{} - {}", rsvp_record.uri, is_ok));
-                    }
+    let progress_template = format!(
+        "import.{}.progress.html",
+        language.to_string().to_lowercase()
+    );
 
-                    let (collection, cursor) = if items.len() == LIMIT as usize {
-                        (collection.to_string(), Some(list_records.cursor))
-                    } else {
-                        (SMOKESIGNAL_EVENT_NSID.to_string(), None)
-                    };
+    let stream = async_stream::stream! {
+        let mut last_updated_at = None;
 
-                    template_context! {
-                        cursor,
-                        items_paged => true,
-                        items,
-                        collection,
-                        completed => false,
-                    }
-                }
+        loop {
+            let import_job = match import_job_get(&web_context.pool, &current_handle.did).await {
+                Ok(Some(value)) => value,
+                Ok(None) => break,
                 Err(err) => {
-                    return contextual_error!(
-                        web_context,
-                        language,
-                        error_template,
-                        template_context! {},
-                        ImportError::FailedToListCommunityRSVPs(err.to_string())
-                    )
+                    tracing::error!(?err, "unable to load import job");
+                    break;
                 }
-            }
-        }
-        SMOKESIGNAL_EVENT_NSID => {
-            let results = client
-                .list_records::<SmokeSignalEvent>(&client_auth, &list_params)
-                .await;
-            match results {
-                Ok(list_records) => {
-                    let mut items = vec![];
-
-                    for event_record in list_records.records {
-                        let name = match &event_record.value {
-                            SmokeSignalEvent::Current { name, .. } => name.clone(),
-                        };
-                        let event_insert_resp = event_insert_with_metadata(
-                            &web_context.pool,
-                            &event_record.uri,
-                            &event_record.cid,
-                            &current_handle.did,
-                            SMOKESIGNAL_EVENT_NSID,
-                            &event_record.value,
-                            &name,
-                        )
-                        .await;
-
-                        let is_ok = if let Err(err) = event_insert_resp {
-                            tracing::error!(?err, "error inserting Smokesignal event");
-                            false
-                        } else {
-                            true
-                        };
-
-                        items.push(format!("{} - {}", event_record.uri, is_ok));
+            };
+
+            if last_updated_at != Some(import_job.updated_at) {
+                last_updated_at = Some(import_job.updated_at);
+
+                let rendered = web_context.engine.render(
+                    &progress_template,
+                    template_context! { import_job => &import_job },
+                );
+                match rendered {
+                    Ok(value) => {
+                        yield Ok::<Event, Infallible>(
+                            Event::default().event("progress").data(value),
+                        );
                     }
-
-                    let (collection, cursor) = if items.len() == LIMIT as usize {
-                        (collection.to_string(), Some(list_records.cursor))
-                    } else {
-                        (SMOKESIGNAL_RSVP_NSID.to_string(), None)
-                    };
-
-                    template_context! {
-                        cursor,
-                        items_paged => true,
-                        items,
-                        collection,
-                        completed => false,
+                    Err(err) => {
+                        tracing::error!(?err, "unable to render import progress");
+                        break;
                     }
                 }
-                Err(err) => {
-                    return contextual_error!(
-                        web_context,
-                        language,
-                        error_template,
-                        template_context! {},
-                        ImportError::FailedToListSmokesignalEvents(err.to_string())
-                    )
-                }
             }
-        }
-        SMOKESIGNAL_RSVP_NSID => {
-            let results = client
-                .list_records::<SmokeSignalRsvp>(&client_auth, &list_params)
-                .await;
-            match results {
-                Ok(list_records) => {
-                    let mut items = vec![];
-
-                    for rsvp_record in list_records.records {
-                        let (event_uri, event_cid, status) = match &rsvp_record.value {
-                            SmokeSignalRsvp::Current {
-                                subject, status, ..
-                            } => {
-                                let status_str = match status {
-                                    SmokeSignalRsvpStatus::Going => "going",
-                                    SmokeSignalRsvpStatus::Interested => "interested",
-                                    SmokeSignalRsvpStatus::NotGoing => "notgoing",
-                                };
-                                (subject.uri.clone(), subject.cid.clone(), status_str)
-                            }
-                        };
-
-                        let rsvp_insert_resp = rsvp_insert_with_metadata(
-                            &web_context.pool,
-                            crate::storage::event::RsvpInsertParams {
-                                aturi: &rsvp_record.uri,
-                                cid: &rsvp_record.cid,
-                                did: &current_handle.did,
-                                lexicon: SMOKESIGNAL_RSVP_NSID,
-                                record: &rsvp_record.value,
-                                event_aturi: &event_uri,
-                                event_cid: &event_cid,
-                                status,
-                            },
-                        )
-                        .await;
-
-                        let is_ok = if let Err(err) = rsvp_insert_resp {
-                            tracing::error!(?err, "error inserting Smokesignal RSVP");
-                            false
-                        } else {
-                            true
-                        };
-
-                        items.push(format!("{} - {}", rsvp_record.uri, is_ok));
-                    }
-
-                    let completed = items.len() == LIMIT as usize;
 
-                    template_context! {
-                        cursor => list_records.cursor,
-                        items_paged => true,
-                        items,
-                        collection,
-                        completed,
-                    }
-                }
-                Err(err) => {
-                    return contextual_error!(
-                        web_context,
-                        language,
-                        error_template,
-                        template_context! {},
-                        ImportError::FailedToListSmokesignalRSVPs(err.to_string())
-                    )
-                }
+            if import_job.status == IMPORT_JOB_COMPLETED
+                || import_job.status == IMPORT_JOB_FAILED
+            {
+                break;
             }
+
+            tokio::time::sleep(IMPORT_PROGRESS_INTERVAL).await;
         }
-        _ => {
-            return contextual_error!(
-                web_context,
-                language,
-                error_template,
-                template_context! {},
-                ImportError::UnsupportedCollectionType(collection.clone())
-            )
-        }
+
+        yield Ok(Event::default().event("done").data(""));
     };
 
-    Ok(RenderHtml(
-        &render_template,
-        web_context.engine.clone(),
-        template_context! {
-            current_handle,
-            language => language.to_string(),
-            canonical_url => format!("https://{}/import", web_context.config.external_base),
-            ..render_context,
-        },
-    )
-    .into_response())
+    Ok(Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response())
 }
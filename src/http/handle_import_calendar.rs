@@ -0,0 +1,403 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    response::{IntoResponse, Redirect},
+};
+use axum_extra::extract::Cached;
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use minijinja::context as template_context;
+
+use crate::{
+    atproto::{
+        auth::SimpleOAuthSessionProvider,
+        client::{
+            ApplyWrite, ApplyWriteResult, ApplyWritesRequest, OAuthPdsClient, MAX_APPLY_WRITES,
+        },
+        lexicon::community::lexicon::calendar::event::{Event, NSID},
+        uri::parse_aturi,
+    },
+    contextual_error,
+    errors::expand_error,
+    http::{
+        context::WebContext,
+        errors::{CommonError, ImportError, WebError},
+        handle_webhooks::notify_webhooks,
+        middleware_auth::Auth,
+        middleware_i18n::Language,
+        timezones::supported_timezones,
+        utils::url_from_aturi,
+    },
+    ics::parse_events,
+    import_plan::{build_import_plan, ImportAction, ImportPlanItem},
+    import_plan_errors::ImportPlanError,
+    select_template,
+    storage::{
+        event::{event_get_cid, event_insert, event_update_with_metadata},
+        import_plan::{
+            import_plan_complete, import_plan_delete, import_plan_get, import_plan_insert,
+            import_source_event_upsert,
+        },
+    },
+    webhooks::WebhookKind,
+};
+
+/// The most events a calendar file can have. Each event is matched against
+/// existing events when the plan is made, and written when it is confirmed.
+const MAX_PLAN_EVENTS: usize = 200;
+
+/// Reads an uploaded calendar file and stores a plan of what importing it
+/// would do, without writing anything to the PDS. The organizer is sent to
+/// the plan to review it.
+pub async fn handle_import_calendar_upload(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/import")?;
+
+    let default_context = template_context! {
+        current_handle,
+        language => language.to_string(),
+        canonical_url => format!("https://{}/import", web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, language);
+
+    let mut upload = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("ics_file") {
+            let source = field.file_name().unwrap_or("calendar.ics").to_string();
+            upload = field
+                .bytes()
+                .await
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+                .map(|bytes| (source, String::from_utf8_lossy(&bytes).to_string()));
+            break;
+        }
+    }
+
+    let Some((source, content)) = upload else {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            ImportError::CalendarFileMissing
+        );
+    };
+
+    let (default_tz, _) = supported_timezones(auth.0.as_ref());
+    let tz = default_tz
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+
+    let events = match parse_events(&content, tz) {
+        Ok(events) => events,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err)
+        }
+    };
+
+    if events.len() > MAX_PLAN_EVENTS {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            ImportError::CalendarTooLarge(MAX_PLAN_EVENTS)
+        );
+    }
+
+    let items = build_import_plan(&web_context.pool, &current_handle.did, events, tz).await?;
+    let plan_id =
+        import_plan_insert(&web_context.pool, &current_handle.did, &source, &items).await?;
+
+    Ok(Redirect::to(&format!("/import/calendar/{}", plan_id)).into_response())
+}
+
+/// Shows what confirming an import plan does, or once it is confirmed, what
+/// it did.
+pub async fn handle_import_calendar_preview(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path(plan_id): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let plan_url = format!("/import/calendar/{}", plan_id);
+    let current_handle = auth.require(&web_context.config.destination_key, &plan_url)?;
+
+    let default_context = template_context! {
+        current_handle,
+        language => language.to_string(),
+        canonical_url => format!("https://{}{}", web_context.config.external_base, plan_url),
+    };
+
+    let render_template = select_template!("import_calendar", hx_boosted, hx_request, language);
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    let Some(plan) = import_plan_get(&web_context.pool, &current_handle.did, &plan_id).await?
+    else {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            ImportError::PlanNotFound
+        );
+    };
+
+    let format_error = |value: &Option<String>| {
+        value.as_ref().map(|value| {
+            let (err_bare, err_partial) = expand_error(value);
+            web_context
+                .i18n_context
+                .locales
+                .format_error(&language, &err_bare, &err_partial)
+        })
+    };
+
+    let count = |action: ImportAction| {
+        plan.items
+            .iter()
+            .filter(|item| item.action == action)
+            .count()
+    };
+
+    let items: Vec<minijinja::Value> = plan
+        .items
+        .iter()
+        .map(|item| {
+            template_context! {
+                name => item.name,
+                uid => item.uid,
+                action => item.action,
+                changes => item.changes,
+                location_text => item.location_text,
+                reason => format_error(&item.reason),
+                error => format_error(&item.error),
+                completed => item.completed,
+                event_url => item.aturi.as_ref().and_then(|aturi| {
+                    url_from_aturi(&web_context.config.external_base, aturi).ok()
+                }),
+            }
+        })
+        .collect();
+
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            plan_id => plan.id,
+            source => plan.source,
+            created_at => plan.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            confirmed => plan.confirmed_at.is_some(),
+            create_count => count(ImportAction::Create),
+            update_count => count(ImportAction::Update),
+            skip_count => count(ImportAction::Skip),
+            items,
+        }},
+    )
+    .into_response())
+}
+
+/// Composes the write of one planned event. Events that changed since the
+/// plan was previewed get an error instead, and aren't written.
+async fn plan_item_write(
+    web_context: &WebContext,
+    item: &mut ImportPlanItem,
+) -> Result<Option<ApplyWrite<Event>>, WebError> {
+    let Some(record) = item.record.clone() else {
+        return Ok(None);
+    };
+
+    match (item.action, item.aturi.as_deref()) {
+        (ImportAction::Create, _) => Ok(Some(ApplyWrite::Create {
+            collection: NSID.to_string(),
+            record_key: None,
+            value: record,
+        })),
+        (ImportAction::Update, Some(aturi)) => {
+            // Don't overwrite edits made after the plan was previewed
+            let current_cid = event_get_cid(&web_context.pool, aturi).await?;
+            if current_cid.is_none() || current_cid != item.cid {
+                item.error = Some(ImportPlanError::EventChanged.to_string());
+                return Ok(None);
+            }
+
+            match parse_aturi(aturi) {
+                Ok((_, _, rkey)) => Ok(Some(ApplyWrite::Update {
+                    collection: NSID.to_string(),
+                    record_key: rkey,
+                    value: record,
+                })),
+                Err(err) => {
+                    item.error =
+                        Some(ImportPlanError::RecordWriteFailed(err.to_string()).to_string());
+                    Ok(None)
+                }
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Records a planned event that was written to the PDS in the index.
+async fn complete_item(
+    web_context: &WebContext,
+    did: &str,
+    item: &mut ImportPlanItem,
+    result: ApplyWriteResult,
+) -> Result<(), WebError> {
+    let Some(record) = item.record.as_ref() else {
+        return Ok(());
+    };
+
+    let aturi = match result {
+        ApplyWriteResult::Create { uri, cid } => {
+            event_insert(&web_context.pool, &uri, &cid, did, NSID, record).await?;
+            uri
+        }
+        ApplyWriteResult::Update { uri, cid } => {
+            event_update_with_metadata(&web_context.pool, &uri, &cid, record, &item.name).await?;
+
+            notify_webhooks(
+                &web_context.pool,
+                &web_context.config.external_base,
+                &uri,
+                WebhookKind::EventUpdated,
+                None,
+            )
+            .await;
+
+            uri
+        }
+        ApplyWriteResult::Delete {} => return Ok(()),
+    };
+
+    if let Some(uid) = &item.uid {
+        import_source_event_upsert(&web_context.pool, did, uid, &aturi).await?;
+    }
+
+    if let Some(geocoder) = &web_context.geocoder {
+        geocoder.spawn_geocode_event(&web_context.pool, &aturi);
+    }
+
+    item.aturi = Some(aturi);
+    item.completed = true;
+
+    Ok(())
+}
+
+/// Writes the events of an import plan. Events that were skipped in the
+/// preview are not written, and the plan can only be confirmed once.
+pub async fn handle_import_calendar_confirm(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    Path(plan_id): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let plan_url = format!("/import/calendar/{}", plan_id);
+    let current_handle = auth.require(&web_context.config.destination_key, &plan_url)?;
+
+    let default_context = template_context! {
+        current_handle,
+        language => language.to_string(),
+        canonical_url => format!("https://{}{}", web_context.config.external_base, plan_url),
+    };
+
+    let error_template = select_template!(false, false, language);
+
+    let Some(plan) = import_plan_get(&web_context.pool, &current_handle.did, &plan_id).await?
+    else {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            ImportError::PlanNotFound
+        );
+    };
+
+    if plan.confirmed_at.is_some() {
+        return Ok(Redirect::to(&plan_url).into_response());
+    }
+
+    let auth_data = auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    let client = OAuthPdsClient {
+        http_client: &web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    let mut items = plan.items.0;
+
+    let mut pending = Vec::new();
+    for (index, item) in items.iter_mut().enumerate() {
+        if let Some(write) = plan_item_write(&web_context, item).await? {
+            pending.push((index, write));
+        }
+    }
+
+    // Each batch is written in a single commit, so a failure leaves none of
+    // its events half imported.
+    for batch in pending.chunks(MAX_APPLY_WRITES) {
+        let (indexes, writes): (Vec<usize>, Vec<ApplyWrite<Event>>) = batch.iter().cloned().unzip();
+
+        let apply_writes_request = ApplyWritesRequest {
+            repo: current_handle.did.clone(),
+            validate: false,
+            writes,
+            swap_commit: None,
+        };
+
+        let results = match client
+            .apply_writes(&client_auth, apply_writes_request)
+            .await
+        {
+            Ok(results) if results.len() == indexes.len() => Ok(results),
+            Ok(results) => Err(format!(
+                "expected {} results, got {}",
+                indexes.len(),
+                results.len()
+            )),
+            Err(err) => Err(err.to_string()),
+        };
+
+        match results {
+            Ok(results) => {
+                for (index, result) in indexes.into_iter().zip(results) {
+                    complete_item(&web_context, &current_handle.did, &mut items[index], result)
+                        .await?;
+                }
+            }
+            Err(err) => {
+                let err = ImportPlanError::RecordWriteFailed(err).to_string();
+                for index in indexes {
+                    items[index].error = Some(err.clone());
+                }
+            }
+        }
+    }
+
+    import_plan_complete(&web_context.pool, &current_handle.did, &plan_id, &items).await?;
+
+    Ok(Redirect::to(&plan_url).into_response())
+}
+
+/// Discards an import plan without writing any of its events.
+pub async fn handle_import_calendar_discard(
+    State(web_context): State<WebContext>,
+    Cached(auth): Cached<Auth>,
+    Path(plan_id): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/import")?;
+
+    import_plan_delete(&web_context.pool, &current_handle.did, &plan_id).await?;
+
+    Ok(Redirect::to("/import").into_response())
+}
@@ -2,29 +2,43 @@ use std::fmt;
 
 use anyhow::Result;
 use axum::{
-    extract::{Query, State},
+    extract::{OriginalUri, Query, State},
     response::IntoResponse,
 };
 use axum_extra::extract::Cached;
 use axum_htmx::HxBoosted;
 use axum_template::RenderHtml;
+use chrono::Utc;
+use http::HeaderMap;
 
 use minijinja::context as template_context;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    contextual_error,
+    config::HomepageSection,
     http::{
         context::WebContext,
         errors::WebError,
-        event_view::{hydrate_event_organizers, hydrate_event_rsvp_counts, EventView},
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
         middleware_auth::Auth,
         middleware_i18n::Language,
-        pagination::{Pagination, PaginationView},
+        page_cache::{cached_anonymous_page, render_anonymous_page},
+        pagination::{Pagination, PaginationView, PAGE_DEFAULT},
         tab_selector::TabSelector,
     },
     select_template,
-    storage::event::event_list_recently_updated,
+    storage::{
+        errors::StorageError,
+        event::{
+            event_list_featured, event_list_recently_added, event_list_recently_updated,
+            event_list_upcoming, model::EventWithRole,
+        },
+        follow::follow_list_upcoming_events,
+        tag::event_list_with_tag,
+    },
 };
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -46,41 +60,61 @@ impl From<TabSelector> for HomeTab {
     }
 }
 
-pub async fn handle_index(
-    State(web_context): State<WebContext>,
-    HxBoosted(hx_boosted): HxBoosted,
-    Language(language): Language,
-    Cached(auth): Cached<Auth>,
-    pagination: Query<Pagination>,
-    tab_selector: Query<TabSelector>,
-) -> Result<impl IntoResponse, WebError> {
-    let render_template = select_template!("index", hx_boosted, false, language);
-    let error_template = select_template!(false, false, language);
-
-    let (page, page_size) = pagination.clamped();
-    let tab: HomeTab = tab_selector.0.into();
-    let tab_name = tab.to_string();
+#[derive(Debug, Serialize)]
+pub struct HomepageSectionView {
+    pub kind: &'static str,
+    pub parameter: Option<String>,
+    pub events: Vec<EventView>,
+}
 
-    let events = {
-        let tab_events = match tab {
-            HomeTab::RecentlyUpdated => {
-                event_list_recently_updated(&web_context.pool, page, page_size).await
-            }
-        };
-        match tab_events {
-            Ok(values) => values,
-            Err(err) => {
-                return contextual_error!(
-                    web_context,
-                    language,
-                    error_template,
-                    template_context! {},
-                    err
-                );
-            }
+async fn section_events(
+    web_context: &WebContext,
+    section: &HomepageSection,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    let pool = &web_context.pool;
+    match section {
+        HomepageSection::Featured => {
+            let offset = ((page - 1) * page_size) as usize;
+            let aturis = web_context
+                .config
+                .featured_events
+                .as_ref()
+                .iter()
+                .skip(offset)
+                .take(page_size as usize + 1)
+                .cloned()
+                .collect::<Vec<String>>();
+            event_list_featured(pool, &aturis).await
         }
-    };
+        HomepageSection::Upcoming(locality) => {
+            event_list_upcoming(pool, Utc::now(), locality.as_deref(), page, page_size).await
+        }
+        HomepageSection::RecentlyAdded => event_list_recently_added(pool, page, page_size).await,
+        HomepageSection::RecentlyUpdated => {
+            event_list_recently_updated(pool, page, page_size).await
+        }
+        HomepageSection::Tag(tag) => event_list_with_tag(pool, tag, page, page_size).await,
+    }
+}
+
+fn section_kind(section: &HomepageSection) -> (&'static str, Option<String>) {
+    match section {
+        HomepageSection::Featured => ("featured", None),
+        HomepageSection::Upcoming(locality) => ("upcoming", locality.clone()),
+        HomepageSection::RecentlyAdded => ("added", None),
+        HomepageSection::RecentlyUpdated => ("updated", None),
+        HomepageSection::Tag(tag) => ("tag", Some(tag.clone())),
+    }
+}
 
+// Converts listed events into views with their organizers and RSVP counts.
+pub(crate) async fn section_event_views(
+    web_context: &WebContext,
+    auth: &Auth,
+    events: Vec<EventWithRole>,
+) -> Result<Vec<EventView>, WebError> {
     let organizer_handlers = hydrate_event_organizers(&web_context.pool, &events).await?;
 
     let mut events = events
@@ -100,32 +134,150 @@ pub async fn handle_index(
         })
         .collect::<Vec<EventView>>();
 
-    if let Err(err) = hydrate_event_rsvp_counts(&web_context.pool, &mut events).await {
+    if let Err(err) =
+        hydrate_event_rsvp_counts_cached(&web_context.pool, &web_context.cache_pool, &mut events)
+            .await
+    {
         tracing::warn!("Failed to hydrate event counts: {}", err);
     }
 
-    let params: Vec<(&str, &str)> = vec![("tab", &tab_name)];
+    if let Err(err) = hydrate_event_labels(
+        &web_context.pool,
+        &web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok(events)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_index(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    pagination: Query<Pagination>,
+    tab_selector: Query<TabSelector>,
+) -> Result<impl IntoResponse, WebError> {
+    let render_template = select_template!("index", hx_boosted, false, language);
+
+    // The homepage is served from the page cache to people who aren't signed
+    // in while it has it, without listing any events
+    let page_uri = uri.to_string();
+    if auth.0.is_none() {
+        if let Some(response) =
+            cached_anonymous_page(&web_context, &headers, &render_template, &page_uri).await
+        {
+            return Ok(response);
+        }
+    }
+
+    let homepage_sections = web_context.config.homepage_sections.as_ref();
+
+    // Pagination only applies when the homepage is a single list of events.
+    // Otherwise each section shows its first page.
+    let paginated = homepage_sections.len() == 1;
+
+    let (page, page_size) = pagination.clamped();
+    let page = if paginated { page } else { PAGE_DEFAULT };
+    let tab: HomeTab = tab_selector.0.into();
+    let tab_name = tab.to_string();
+
+    let mut sections = Vec::with_capacity(homepage_sections.len() + 1);
+    let mut has_more = false;
 
-    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+    // Signed in identities see the upcoming events of the organizers they
+    // follow first. The section is left out when there are none, and isn't
+    // paginated.
+    if let Some(current_handle) = auth.0.as_ref().filter(|_| page == PAGE_DEFAULT) {
+        let events = follow_list_upcoming_events(
+            &web_context.pool,
+            &current_handle.did,
+            Utc::now(),
+            PAGE_DEFAULT,
+            page_size,
+        )
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("Failed to list events from followed organizers: {}", err);
+            Vec::new()
+        });
 
-    if events.len() > page_size as usize {
-        events.truncate(page_size as usize);
+        if !events.is_empty() {
+            let mut events = section_event_views(&web_context, &auth, events).await?;
+            events.truncate(page_size as usize);
+            sections.push(HomepageSectionView {
+                kind: "following",
+                parameter: None,
+                events,
+            });
+        }
+    }
+
+    for section in homepage_sections {
+        // A section that can't be listed is shown empty rather than taking
+        // the whole homepage down with it
+        let events = section_events(&web_context, section, page, page_size)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::warn!(error = ?err, "Failed to list homepage section events");
+                Vec::new()
+            });
+
+        let mut events = section_event_views(&web_context, &auth, events).await?;
+
+        if events.len() > page_size as usize {
+            has_more = true;
+            events.truncate(page_size as usize);
+        }
+
+        let (kind, parameter) = section_kind(section);
+        sections.push(HomepageSectionView {
+            kind,
+            parameter,
+            events,
+        });
+    }
+
+    let pagination_view = if paginated {
+        let params: Vec<(&str, &str)> = vec![("tab", &tab_name)];
+        let total = page_size + if has_more { 1 } else { 0 };
+        Some(PaginationView::new(page_size, total, page, params))
+    } else {
+        None
+    };
+
+    let page_context = template_context! {
+        current_handle => auth.0,
+        language => language.to_string(),
+        canonical_url => format!("https://{}/", web_context.config.external_base),
+        tab => tab.to_string(),
+        sections,
+        pagination => pagination_view,
+    };
+
+    // The homepage is the same for everyone who isn't signed in, so it can be
+    // cached by browsers, shared caches and the page cache
+    if auth.0.is_none() {
+        return render_anonymous_page(
+            &web_context,
+            &headers,
+            &render_template,
+            &page_uri,
+            page_context,
+        )
+        .await;
     }
 
     Ok((
         http::StatusCode::OK,
-        RenderHtml(
-            &render_template,
-            web_context.engine.clone(),
-            template_context! {
-                current_handle => auth.0,
-                language => language.to_string(),
-                canonical_url => format!("https://{}/", web_context.config.external_base),
-                tab => tab.to_string(),
-                events,
-                pagination => pagination_view,
-            },
-        ),
+        RenderHtml(&render_template, web_context.engine.clone(), page_context),
     )
         .into_response())
 }
@@ -0,0 +1,446 @@
+use anyhow::Result;
+use axum::{
+    extract::{Multipart, Path},
+    response::{IntoResponse, Redirect},
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Serialize;
+
+use crate::{
+    atproto::{
+        auth::SimpleOAuthSessionProvider,
+        client::{CreateRecordRequest, DeleteRecordRequest, OAuthPdsClient},
+        lexicon::{
+            com::atproto::repo::Blob,
+            events::smokesignal::media::{Media as MediaRecord, NSID},
+        },
+        uri::parse_aturi,
+    },
+    contextual_error,
+    errors::expand_error,
+    http::{
+        context::{UserRequestContext, WebContext},
+        errors::{CommonError, WebError},
+    },
+    media::{blob_url, validate_image, MAX_LIBRARY_IMAGES},
+    media_errors::MediaError,
+    select_template,
+    storage::{
+        handle::model::Handle,
+        media::{
+            media_count, media_delete, media_get_by_blob, media_insert, media_list, model::Media,
+        },
+        StoragePool,
+    },
+};
+
+/// The longest name kept for an image. Longer names are shortened.
+const MAX_NAME_CHARS: usize = 128;
+
+/// An image of a media library as shown on the library page and in the event
+/// form.
+#[derive(Serialize, Debug)]
+pub struct MediaView {
+    pub rkey: String,
+    pub name: String,
+    pub blob_cid: String,
+    pub url: String,
+}
+
+impl MediaView {
+    pub fn new(handle: &Handle, media: &Media) -> Self {
+        let rkey = parse_aturi(&media.aturi)
+            .map(|(_, _, rkey)| rkey)
+            .unwrap_or_default();
+        Self {
+            rkey,
+            name: media.name.clone(),
+            blob_cid: media.blob_cid.clone(),
+            url: blob_url(&handle.pds, &handle.did, &media.blob_cid),
+        }
+    }
+}
+
+/// Lists the images in the media library of the current user, for the event
+/// form. Errors are logged and leave the list empty, since picking an image
+/// is optional.
+pub async fn media_library_views(pool: &StoragePool, handle: &Handle) -> Vec<MediaView> {
+    match media_list(pool, &handle.did).await {
+        Ok(media) => media
+            .iter()
+            .map(|media| MediaView::new(handle, media))
+            .collect(),
+        Err(err) => {
+            tracing::warn!("Failed to list media library: {}", err);
+            vec![]
+        }
+    }
+}
+
+/// Finds the header image picked in an event form in the media library of
+/// the organizer. Only images in the library can be picked, so that events
+/// only use blobs that are kept by the organizer's repository.
+pub async fn header_image_from_library(
+    pool: &StoragePool,
+    did: &str,
+    blob_cid: Option<&str>,
+) -> Result<Option<Blob>, WebError> {
+    let Some(blob_cid) = blob_cid.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+
+    match media_get_by_blob(pool, did, blob_cid).await? {
+        Some(media) => Ok(Some(media.blob())),
+        None => Err(MediaError::ImageNotFound(blob_cid.to_string()).into()),
+    }
+}
+
+/// Shows the images an organizer has uploaded, and a form to upload more.
+pub async fn handle_media(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/media")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/media", ctx.web_context.config.external_base),
+    };
+
+    let render_template = select_template!("media", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let media = match media_list(&ctx.web_context.pool, &current_handle.did).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let media = media
+        .iter()
+        .map(|media| MediaView::new(&current_handle, media))
+        .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                media,
+                max_images => MAX_LIBRARY_IMAGES,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Reads the image uploaded in a form field, along with its file name.
+async fn read_image_field(multipart: &mut Multipart, name: &str) -> Option<(String, Vec<u8>)> {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some(name) {
+            let file_name = field.file_name().unwrap_or("image").to_string();
+            return field
+                .bytes()
+                .await
+                .ok()
+                .map(|bytes| (file_name, bytes.to_vec()));
+        }
+    }
+    None
+}
+
+/// Uploads an image to the PDS of the organizer and adds it to their media
+/// library. The blob is kept by a media record, since PDSs remove blobs that
+/// no record uses.
+async fn add_to_library(
+    web_context: &WebContext,
+    handle: &Handle,
+    client_auth: &SimpleOAuthSessionProvider,
+    name: &str,
+    content: Vec<u8>,
+) -> Result<MediaView, WebError> {
+    let mime_type = validate_image(&content)?;
+
+    let count = media_count(&web_context.pool, &handle.did).await?;
+    if count >= MAX_LIBRARY_IMAGES {
+        return Err(MediaError::LibraryFull(MAX_LIBRARY_IMAGES).into());
+    }
+
+    let client = OAuthPdsClient {
+        http_client: &web_context.http_client,
+        pds: &handle.pds,
+    };
+
+    let blob = client.upload_blob(client_auth, mime_type, content).await?;
+
+    let name: String = name.trim().chars().take(MAX_NAME_CHARS).collect();
+    let created_at = Utc::now();
+
+    let media_record = CreateRecordRequest {
+        repo: handle.did.clone(),
+        collection: NSID.to_string(),
+        validate: false,
+        record_key: None,
+        record: MediaRecord::Current {
+            name: name.clone(),
+            image: blob.clone(),
+            created_at,
+        },
+        swap_commit: None,
+    };
+
+    let created = client.create_record(client_auth, media_record).await?;
+
+    media_insert(
+        &web_context.pool,
+        &created.uri,
+        &created.cid,
+        &handle.did,
+        &name,
+        &blob,
+        created_at,
+    )
+    .await?;
+
+    let rkey = parse_aturi(&created.uri)
+        .map(|(_, _, rkey)| rkey)
+        .unwrap_or_default();
+
+    Ok(MediaView {
+        rkey,
+        name,
+        url: blob_url(&handle.pds, &handle.did, &blob.reference.link),
+        blob_cid: blob.reference.link,
+    })
+}
+
+/// Uploads an image to the PDS of the current user and adds it to their media
+/// library.
+pub async fn handle_media_upload(
+    ctx: UserRequestContext,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/media")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/media", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let Some((name, content)) = read_image_field(&mut multipart, "image").await else {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            MediaError::ImageMissing
+        );
+    };
+
+    let auth_data = ctx.auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    if let Err(err) = add_to_library(
+        &ctx.web_context,
+        &current_handle,
+        &client_auth,
+        &name,
+        content,
+    )
+    .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    Ok(Redirect::to("/media").into_response())
+}
+
+/// Uploads an image from the event form into the media library and picks it
+/// as the header image of the event. The header image field of the form is
+/// swapped out with the response, which shows the problem with the upload
+/// when it fails.
+pub async fn handle_event_header_image(
+    ctx: UserRequestContext,
+    HxRequest(hx_request): HxRequest,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, WebError> {
+    if !hx_request {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let Ok(current_handle) = ctx.auth.require_flat() else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+
+    let render_template = format!(
+        "create_event.{}.header_image.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    // The whole event form is sent along with the upload, so only the fields
+    // of the header image are read from it.
+    let mut upload = None;
+    let mut header_image = None;
+    let mut event_header_image = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("header_image_upload") => {
+                let file_name = field.file_name().unwrap_or("image").to_string();
+                upload = field
+                    .bytes()
+                    .await
+                    .ok()
+                    .map(|bytes| (file_name, bytes.to_vec()));
+            }
+            Some("header_image") => {
+                header_image = field.text().await.ok().filter(|value| !value.is_empty());
+            }
+            Some("event_header_image") => {
+                event_header_image = field.text().await.ok().filter(|value| !value.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let result = match upload {
+        Some((name, content)) if !content.is_empty() => {
+            let auth_data = ctx.auth.1.clone().ok_or(CommonError::NotAuthorized)?;
+            let client_auth: SimpleOAuthSessionProvider =
+                SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+            add_to_library(
+                &ctx.web_context,
+                &current_handle,
+                &client_auth,
+                &name,
+                content,
+            )
+            .await
+        }
+        _ => Err(MediaError::ImageMissing.into()),
+    };
+
+    let header_image_error = match result {
+        Ok(media) => {
+            header_image = Some(media.blob_cid);
+            None
+        }
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to upload header image");
+            let (err_bare, err_partial) = expand_error(err.to_string());
+            Some(ctx.web_context.i18n_context.locales.format_error(
+                &ctx.language,
+                &err_bare,
+                &err_partial,
+            ))
+        }
+    };
+
+    let media_library = media_library_views(&ctx.web_context.pool, &current_handle).await;
+
+    Ok(RenderHtml(
+        &render_template,
+        ctx.web_context.engine.clone(),
+        template_context! {
+            build_event_form => template_context! {
+                header_image,
+                header_image_error,
+            },
+            event_header_image,
+            media_library,
+        },
+    )
+    .into_response())
+}
+
+/// Removes an image from the media library of the current user. Events that
+/// show the image keep it, since their records still use the blob.
+pub async fn handle_media_delete(
+    ctx: UserRequestContext,
+    Path(media_rkey): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/media")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/media", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let aturi = format!("at://{}/{}/{}", current_handle.did, NSID, media_rkey);
+
+    let media = media_list(&ctx.web_context.pool, &current_handle.did).await?;
+    let Some(media) = media.into_iter().find(|media| media.aturi == aturi) else {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            MediaError::ImageNotFound(media_rkey.clone())
+        );
+    };
+
+    let auth_data = ctx.auth.1.ok_or(CommonError::NotAuthorized)?;
+    let client_auth: SimpleOAuthSessionProvider = SimpleOAuthSessionProvider::try_from(auth_data)?;
+
+    let client = OAuthPdsClient {
+        http_client: &ctx.web_context.http_client,
+        pds: &current_handle.pds,
+    };
+
+    let delete_record_request = DeleteRecordRequest {
+        repo: current_handle.did.clone(),
+        collection: NSID.to_string(),
+        record_key: media_rkey,
+        swap_record: Some(media.cid.clone()),
+        swap_commit: None,
+    };
+
+    if let Err(err) = client
+        .delete_record(&client_auth, delete_record_request)
+        .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    media_delete(&ctx.web_context.pool, &current_handle.did, &aturi).await?;
+
+    Ok(Redirect::to("/media").into_response())
+}
@@ -25,6 +25,7 @@ use crate::{
                 NSID as SMOKESIGNAL_NSID,
             },
         },
+        record_limits::check_event_record,
     },
     contextual_error,
     http::{
@@ -34,7 +35,7 @@ use crate::{
     resolve::{parse_input, InputType},
     select_template,
     storage::{
-        event::{event_get, event_insert_with_metadata},
+        event::{event_get, event_insert_migrated, EventInsertParams},
         handle::{handle_for_did, handle_for_handle, model::Handle},
     },
 };
@@ -261,6 +262,19 @@ pub async fn handle_migrate_event(
         );
     }
 
+    // Legacy events had no size limits, so check the converted record before
+    // sending it
+    if let Err(err) = check_event_record(&new_event) {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            err,
+            StatusCode::OK
+        );
+    }
+
     // Set up XRPC client
     // Error if we don't have auth data
     let auth_data = auth.1.ok_or(MigrateEventError::NotAuthorized)?;
@@ -297,17 +311,19 @@ pub async fn handle_migrate_event(
     // update_record_result is guaranteed to be Ok at this point since we checked for Err above
     let update_record_result = update_record_result?;
 
-    // We already have the migrated AT-URI defined above
-
-    // Insert the migrated event into the database
-    let migrated_event_insert_result = event_insert_with_metadata(
+    // Insert the migrated event into the database, together with the
+    // migration so that RSVPs to the legacy event keep counting towards it
+    let migrated_event_insert_result = event_insert_migrated(
         &web_context.pool,
-        &migrated_aturi,
-        &update_record_result.cid,
-        &current_handle.did,
-        COMMUNITY_NSID,
-        &new_event,
-        &name,
+        &source_aturi,
+        EventInsertParams {
+            aturi: &migrated_aturi,
+            cid: &update_record_result.cid,
+            did: &current_handle.did,
+            lexicon: COMMUNITY_NSID,
+            record: &new_event,
+            name: &name,
+        },
     )
     .await;
 
@@ -323,6 +339,10 @@ pub async fn handle_migrate_event(
     }
 
     // Generate URL for the migrated event
+    if let Some(geocoder) = &web_context.geocoder {
+        geocoder.spawn_geocode_event(&web_context.pool, &migrated_aturi);
+    }
+
     let migrated_event_url = url_from_aturi(&web_context.config.external_base, &migrated_aturi)?;
 
     // Return success with migration complete template
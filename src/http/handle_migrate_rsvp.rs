@@ -30,6 +30,7 @@ use crate::{
     resolve::{parse_input, InputType},
     select_template,
     storage::{
+        cache::rsvp_counts_invalidate,
         event::{event_get, get_user_rsvp, rsvp_insert},
         handle::{handle_for_did, handle_for_handle, model::Handle},
     },
@@ -266,6 +267,12 @@ pub async fn handle_migrate_rsvp(
         return contextual_error!(web_context, language, error_template, default_context, err);
     }
 
+    // The attendee was already counted through the legacy event, so the
+    // cached counts are dropped and counted again rather than adjusted.
+    if let Err(err) = rsvp_counts_invalidate(&web_context.cache_pool, &standard_event_aturi).await {
+        tracing::warn!(error = ?err, "unable to invalidate cached rsvp counts");
+    }
+
     // Redirect to the event view page
     Ok(Redirect::to(&format!("/{}/{}", handle_slug, event_rkey)).into_response())
 }
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+
+use crate::{
+    contextual_error,
+    http::{context::UserRequestContext, errors::WebError},
+    select_template,
+    storage::notification::{notification_list, notifications_mark_read},
+};
+
+/// How many of the most recent notifications are listed.
+const NOTIFICATION_LIST_LIMIT: i64 = 50;
+
+/// Lists the notifications of the current identity, newest first, and marks
+/// them as read.
+pub async fn handle_notifications(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/notifications")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/notifications", ctx.web_context.config.external_base),
+    };
+
+    let render_template = select_template!("notifications", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let notifications = match notification_list(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        NOTIFICATION_LIST_LIMIT,
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    // The list is rendered from what was read above, so notifications that
+    // were unread are still highlighted this time.
+    if notifications
+        .iter()
+        .any(|notification| notification.read_at.is_none())
+    {
+        if let Err(err) =
+            notifications_mark_read(&ctx.web_context.pool, &current_handle.did, Utc::now()).await
+        {
+            tracing::warn!(error = ?err, "unable to mark notifications as read");
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                notifications,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -8,6 +8,7 @@ use axum_extra::extract::{
     Form, PrivateCookieJar,
 };
 use deadpool_redis::redis::AsyncCommands as _;
+use http::{header::USER_AGENT, HeaderMap};
 use minijinja::context as template_context;
 use p256::SecretKey;
 use serde::{Deserialize, Serialize};
@@ -21,10 +22,13 @@ use crate::{
     oauth::oauth_complete,
     select_template,
     storage::{
+        audit_log::{audit_log_insert, model::AUDIT_ACTION_ADMIN_LOGIN},
         cache::OAUTH_REFRESH_QUEUE,
         handle::handle_for_did,
+        import_job::import_job_enqueue,
         oauth::{oauth_request_get, oauth_request_remove, oauth_session_insert},
     },
+    task_refresh_tokens::rebind_pds,
 };
 
 use super::{
@@ -34,6 +38,9 @@ use super::{
     middleware_i18n::Language,
 };
 
+/// The longest `User-Agent` kept with a session.
+const MAX_USER_AGENT_CHARS: usize = 512;
+
 #[derive(Deserialize, Serialize)]
 pub struct OAuthCallbackForm {
     pub state: Option<String>,
@@ -45,6 +52,7 @@ pub async fn handle_oauth_callback(
     State(web_context): State<WebContext>,
     Language(language): Language,
     jar: PrivateCookieJar,
+    headers: HeaderMap,
     Form(callback_form): Form<OAuthCallbackForm>,
 ) -> Result<impl IntoResponse, WebError> {
     let default_context = template_context! {
@@ -92,6 +100,22 @@ pub async fn handle_oauth_callback(
 
     let handle = handle.unwrap();
 
+    // The PDS may have moved since the handle was stored
+    let handle = rebind_pds(
+        &web_context.http_client,
+        &web_context.pool,
+        &web_context.cache_pool,
+        &web_context.config.plc_hostname,
+        handle,
+        &oauth_request.issuer,
+    )
+    .await;
+    if let Err(err) = handle {
+        return contextual_error!(web_context, language, error_template, default_context, err);
+    }
+
+    let handle = handle.unwrap();
+
     let secret_signing_key = web_context
         .config
         .signing_keys
@@ -114,6 +138,7 @@ pub async fn handle_oauth_callback(
 
     let token_response = oauth_complete(
         &web_context.http_client,
+        &web_context.cache_pool,
         &web_context.config.external_base,
         (&oauth_request.secret_jwk_id, secret_signing_key),
         &callback_code,
@@ -148,6 +173,10 @@ pub async fn handle_oauth_callback(
             created_at: now,
             access_token_expires_at: now
                 + chrono::Duration::seconds(token_response.expires_in as i64),
+            user_agent: headers
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| Cow::Owned(value.chars().take(MAX_USER_AGENT_CHARS).collect())),
         },
     )
     .await
@@ -155,6 +184,25 @@ pub async fn handle_oauth_callback(
         return contextual_error!(web_context, language, error_template, default_context, err);
     }
 
+    if web_context.config.is_admin(&token_response.sub) {
+        if let Err(err) = audit_log_insert(
+            &web_context.pool,
+            &token_response.sub,
+            AUDIT_ACTION_ADMIN_LOGIN,
+            &session_group,
+        )
+        .await
+        {
+            tracing::error!(error = ?err, "unable to record audit log entry");
+        }
+    }
+
+    // Identities signing in for the first time have their existing events
+    // and RSVPs imported in the background.
+    if let Err(err) = import_job_enqueue(&web_context.pool, &token_response.sub, now).await {
+        tracing::error!(error = ?err, "unable to queue import job");
+    }
+
     {
         let mut conn = web_context
             .cache_pool
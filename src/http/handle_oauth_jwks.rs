@@ -9,14 +9,30 @@ use std::sync::Arc;
 use crate::http::{context::WebContext, errors::WebError};
 use crate::jose::jwk::{WrappedJsonWebKey, WrappedJsonWebKeySet};
 
-// Function to compute JWKS data and serialize to JSON string
+// Function to compute JWKS data and serialize to JSON string. Retiring keys
+// are published after the active ones, because authorization servers check
+// the client assertions of sessions started with them against this set.
 fn compute_jwks_json(web_context: &WebContext) -> Result<String, serde_json::Error> {
-    let mut keys = vec![];
+    let mut keys: Vec<WrappedJsonWebKey> = vec![];
     let signing_keys = web_context.config.signing_keys.as_ref();
 
-    for available_signing_key in web_context.config.oauth_active_keys.as_ref() {
+    let available_signing_keys = web_context
+        .config
+        .oauth_active_keys
+        .as_ref()
+        .iter()
+        .chain(web_context.config.oauth_retiring_keys.iter());
+
+    for available_signing_key in available_signing_keys {
         let available_signing_key = available_signing_key.clone();
 
+        if keys
+            .iter()
+            .any(|key| key.kid.as_deref() == Some(available_signing_key.as_str()))
+        {
+            continue;
+        }
+
         let signing_key = match signing_keys.get(&available_signing_key) {
             Some(key) => key.clone(),
             None => continue,
@@ -21,11 +21,11 @@ use crate::{
         middleware_i18n::Language, utils::stringify,
     },
     jose,
-    oauth::{oauth_init, pds_resources},
+    oauth::{cached_pds_resources, oauth_init},
     resolve::{parse_input, resolve_subject, InputType},
     select_template,
     storage::{
-        denylist::denylist_exists,
+        denylist::denylist_find,
         handle::handle_warm_up,
         oauth::{model::OAuthRequestState, oauth_request_insert},
     },
@@ -114,11 +114,14 @@ pub async fn handle_oauth_login(
         };
 
         let mut lookup_values: Vec<&str> = vec![&resolved_did, &did_document.id];
+        if let Some(handle) = did_document.primary_handle() {
+            lookup_values.push(handle);
+        }
         if let Some(pds) = did_document.pds_endpoint() {
             lookup_values.push(pds);
         }
 
-        let handle_denied = match denylist_exists(&web_context.pool, &lookup_values).await {
+        let denied_entry = match denylist_find(&web_context.pool, &lookup_values).await {
             Ok(value) => value,
             Err(err) => {
                 return contextual_error!(
@@ -131,7 +134,10 @@ pub async fn handle_oauth_login(
             }
         };
 
-        if handle_denied {
+        if let Some(denied_entry) = denied_entry {
+            let denied_until = denied_entry
+                .expires_at
+                .map(|value| value.format("%B %-d, %Y at %H:%M UTC").to_string());
             return contextual_error!(
                 web_context,
                 language,
@@ -139,8 +145,14 @@ pub async fn handle_oauth_login(
                 template_context! { ..default_context, ..template_context! {
                     handle_error => true,
                     handle_input => subject,
+                    denied => template_context! {
+                        reason => denied_entry.reason,
+                        category => denied_entry.category,
+                        until => denied_until,
+                        appeal_url => denied_entry.appeal_url,
+                    },
                 }},
-                "access-denied"
+                LoginError::AccessDenied
             );
         }
 
@@ -200,7 +212,8 @@ pub async fn handle_oauth_login(
             code_challenge,
         };
 
-        let pds_auth_resources = pds_resources(&web_context.http_client, pds).await;
+        let pds_auth_resources =
+            cached_pds_resources(&web_context.http_client, &web_context.cache_pool, pds).await;
 
         if let Err(err) = pds_auth_resources {
             return contextual_error!(web_context, language, error_template, default_context, err);
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Redirect},
+};
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{UrlError, WebError},
+        event_view::EventView,
+        middleware_read_only::is_read_only,
+    },
+    select_template,
+    storage::{denylist::denylist_exists, event::event_get, link_click::link_click_record},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct OutboundLinkParams {
+    pub url: String,
+    pub event: String,
+}
+
+/// Follows a link of an event, counting the click for the organizer. Only
+/// the links and place of the event can be followed, and links whose address
+/// or host is on the denylist are refused.
+pub async fn handle_outbound_link(
+    ctx: UserRequestContext,
+    Query(params): Query<OutboundLinkParams>,
+) -> Result<impl IntoResponse, WebError> {
+    let default_context = template_context! {
+        language => ctx.language.to_string(),
+        current_handle => ctx.current_handle,
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let event = match event_get(&ctx.web_context.pool, &params.event).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let is_event_link = EventView::try_from((None, None, &event))
+        .is_ok_and(|event_view| is_event_link(&event_view, &params.url));
+    if !is_event_link {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            UrlError::LinkNotFound,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    let mut subjects = vec![params.url.as_str()];
+    let host = url::Url::parse(&params.url)
+        .ok()
+        .and_then(|value| value.host_str().map(str::to_string));
+    if let Some(host) = &host {
+        subjects.push(host);
+    }
+
+    match denylist_exists(&ctx.web_context.pool, &subjects).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                UrlError::LinkBlocked,
+                StatusCode::FORBIDDEN
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    }
+
+    // A click that can't be counted shouldn't keep the visitor from the link
+    if !is_read_only(&ctx.web_context).await {
+        if let Err(err) =
+            link_click_record(&ctx.web_context.pool, &event.aturi, &params.url, Utc::now()).await
+        {
+            tracing::warn!(error = ?err, "unable to record link click");
+        }
+    }
+
+    Ok(Redirect::to(&params.url).into_response())
+}
+
+/// Returns true when the URL is one of the links of the event, or the link
+/// to its place, and can be followed from a browser.
+fn is_event_link(event: &EventView, url: &str) -> bool {
+    let linked = event.links.iter().any(|(link, _)| link == url)
+        || event.location_url.as_deref() == Some(url);
+    linked && (url.starts_with("https://") || url.starts_with("http://"))
+}
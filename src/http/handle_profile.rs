@@ -20,16 +20,24 @@ use crate::{
         tab_selector::{TabLink, TabSelector},
         utils::build_url,
     },
+    profile::{profile_is_stale, refresh_profile},
     select_template,
     storage::{
-        errors::StorageError,
         event::{event_list_did_recently_updated, model::EventWithRole},
-        handle::{handle_for_did, handle_for_handle},
+        follow::follow_exists,
+        handle::{handle_for_did, handle_for_handle, handle_refresh, model::Handle},
+        import_job::import_job_get,
+        StoragePool,
     },
 };
 
+use super::event_view::hydrate_event_labels;
 use super::event_view::hydrate_event_organizers;
 
+/// Viewed profiles whose DID documents were resolved longer ago than this
+/// are refreshed ahead of the handle refresh task's usual schedule.
+const PROFILE_REFRESH_TTL: chrono::Duration = chrono::Duration::hours(24);
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub enum ProfileTab {
     RecentlyUpdated,
@@ -49,6 +57,25 @@ impl From<TabSelector> for ProfileTab {
     }
 }
 
+/// Finds the identity of a profile URL, which is either `@handle` or a
+/// `did:plc` or `did:web` DID.
+pub(crate) async fn profile_for_slug(pool: &StoragePool, handle_slug: &str) -> Result<Handle> {
+    if !handle_slug.starts_with("did:web:")
+        && !handle_slug.starts_with("did:plc:")
+        && !handle_slug.starts_with('@')
+    {
+        return Err(CommonError::InvalidHandleSlug.into());
+    }
+
+    let profile = if let Some(handle_slug) = handle_slug.strip_prefix('@') {
+        handle_for_handle(pool, handle_slug).await?
+    } else {
+        handle_for_did(pool, handle_slug).await?
+    };
+
+    Ok(profile)
+}
+
 pub async fn handle_profile_view(
     ctx: UserRequestContext,
     HxRequest(hx_request): HxRequest,
@@ -65,54 +92,91 @@ pub async fn handle_profile_view(
     let render_template = select_template!("profile", hx_boosted, hx_request, ctx.language);
     let error_template = select_template!(false, hx_request, ctx.language);
 
-    if !handle_slug.starts_with("did:web:")
-        && !handle_slug.starts_with("did:plc:")
-        && !handle_slug.starts_with('@')
-    {
-        return contextual_error!(
-            ctx.web_context,
-            ctx.language,
-            error_template,
-            default_context,
-            CommonError::InvalidHandleSlug,
-            StatusCode::NOT_FOUND
-        );
-    }
-
-    let profile = {
-        if let Some(handle_slug) = handle_slug.strip_prefix('@') {
-            handle_for_handle(&ctx.web_context.pool, handle_slug).await
-        } else if handle_slug.starts_with("did:") {
-            handle_for_did(&ctx.web_context.pool, &handle_slug).await
-        } else {
-            Err(StorageError::HandleNotFound)
+    let profile = match profile_for_slug(&ctx.web_context.pool, &handle_slug).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
         }
     };
 
-    if let Err(err) = profile {
-        return contextual_error!(
-            ctx.web_context,
-            ctx.language,
-            error_template,
-            default_context,
-            err,
-            StatusCode::NOT_FOUND
-        );
+    if profile
+        .resolved_at
+        .is_some_and(|resolved_at| resolved_at < chrono::Utc::now() - PROFILE_REFRESH_TTL)
+    {
+        if let Err(err) = handle_refresh(&ctx.web_context.pool, &profile.did).await {
+            tracing::warn!(
+                did = profile.did,
+                "Failed to request handle refresh: {}",
+                err
+            );
+        }
     }
 
-    let profile = profile.unwrap();
+    let profile = if profile_is_stale(&profile, chrono::Utc::now()) {
+        match refresh_profile(
+            &ctx.web_context.http_client,
+            &ctx.web_context.config.appview_hostname,
+            &ctx.web_context.pool,
+            profile.clone(),
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::warn!(did = profile.did, "Failed to refresh profile: {}", err);
+                profile
+            }
+        }
+    } else {
+        profile
+    };
 
     let is_self = ctx
         .current_handle
         .clone()
         .is_some_and(|inner_current_entity| inner_current_entity.did == profile.did);
 
+    // Only the identity itself sees how the import started on its first
+    // sign in is going.
+    let import_job = if is_self {
+        match import_job_get(&ctx.web_context.pool, &profile.did).await {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::warn!(did = profile.did, "Failed to get import job: {}", err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let is_following = match ctx.current_handle.as_ref() {
+        Some(current_handle) if !is_self => {
+            follow_exists(&ctx.web_context.pool, &current_handle.did, &profile.did)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::error!("Error getting user follow status: {:?}", err);
+                    false
+                })
+        }
+        _ => false,
+    };
+
     let default_context = template_context! {
         current_handle => ctx.current_handle,
         language => ctx.language.to_string(),
         canonical_url => format!("https://{}/{}", ctx.web_context.config.external_base, profile.did),
         profile,
         is_self,
+        is_following,
+        import_job,
     };
 
     let _ = {
@@ -168,8 +232,12 @@ pub async fn handle_profile_view(
         })
         .collect::<Vec<EventView>>();
 
-    if let Err(err) =
-        super::event_view::hydrate_event_rsvp_counts(&ctx.web_context.pool, &mut events).await
+    if let Err(err) = super::event_view::hydrate_event_rsvp_counts_cached(
+        &ctx.web_context.pool,
+        &ctx.web_context.cache_pool,
+        &mut events,
+    )
+    .await
     {
         tracing::warn!("Failed to hydrate event counts: {}", err);
     }
@@ -182,6 +250,16 @@ pub async fn handle_profile_view(
         events.truncate(page_size as usize);
     }
 
+    if let Err(err) = hydrate_event_labels(
+        &ctx.web_context.pool,
+        &ctx.web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
     let tab_links = vec![TabLink {
         name: "recentlyupdated".to_string(),
         label: "Recently Updated".to_string(),
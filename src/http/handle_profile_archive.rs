@@ -0,0 +1,253 @@
+use anyhow::Result;
+use axum::{extract::Path, response::IntoResponse};
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Serialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{CommonError, WebError},
+        handle_index::section_event_views,
+        handle_profile::profile_for_slug,
+        timezones::start_of_day,
+    },
+    select_template,
+    storage::event::{event_list_did_starts_between, event_months_did_past},
+};
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveMonthView {
+    pub year: i32,
+    pub month: u32,
+    pub label: String,
+    pub count: i64,
+    pub url: String,
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveYearView {
+    pub year: i32,
+    pub months: Vec<ArchiveMonthView>,
+}
+
+/// The first moment of a month in a time zone.
+fn month_start(tz: Tz, year: i32, month: u32) -> Option<DateTime<Utc>> {
+    NaiveDate::from_ymd_opt(year, month, 1).map(|date| start_of_day(date, tz))
+}
+
+fn next_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+fn month_label(year: i32, month: u32) -> String {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|value| value.format("%B %Y").to_string())
+        .unwrap_or_default()
+}
+
+/// Shows the most recent month of an organizer's past events.
+pub async fn handle_profile_archive(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path(handle_slug): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    render_profile_archive(ctx, hx_boosted, handle_slug, None).await
+}
+
+/// Shows the events an organizer held in a month.
+pub async fn handle_profile_archive_month(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path((handle_slug, year, month)): Path<(String, i32, u32)>,
+) -> Result<impl IntoResponse, WebError> {
+    render_profile_archive(ctx, hx_boosted, handle_slug, Some((year, month))).await
+}
+
+async fn render_profile_archive(
+    ctx: UserRequestContext,
+    hx_boosted: bool,
+    handle_slug: String,
+    selected: Option<(i32, u32)>,
+) -> Result<axum::response::Response, WebError> {
+    let default_context = template_context! {
+        language => ctx.language.to_string(),
+        current_handle => ctx.current_handle,
+    };
+
+    let render_template = select_template!("profile_archive", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let profile = match profile_for_slug(&ctx.web_context.pool, &handle_slug).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    // Months are those of the organizer's time zone, so that an evening event
+    // on the last day of a month isn't listed in the next one.
+    let tz = profile.tz.parse::<Tz>().unwrap_or(Tz::UTC);
+    let now = Utc::now();
+
+    let months =
+        match event_months_did_past(&ctx.web_context.pool, &profile.did, tz.name(), now).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    let selected = selected.or_else(|| {
+        months
+            .first()
+            .map(|(year, month, _)| (*year, *month as u32))
+    });
+
+    let range = match selected {
+        Some((year, month)) => {
+            let (next_year, next_month) = next_month(year, month);
+            match (
+                month_start(tz, year, month),
+                month_start(tz, next_year, next_month),
+            ) {
+                (Some(starts_after), Some(starts_before)) => {
+                    Some((starts_after, starts_before.min(now)))
+                }
+                _ => {
+                    return contextual_error!(
+                        ctx.web_context,
+                        ctx.language,
+                        error_template,
+                        default_context,
+                        CommonError::InvalidMonth,
+                        StatusCode::NOT_FOUND
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    let events = match range {
+        Some((starts_after, starts_before)) if starts_after < starts_before => {
+            match event_list_did_starts_between(
+                &ctx.web_context.pool,
+                &profile.did,
+                starts_after,
+                starts_before,
+            )
+            .await
+            {
+                Ok(values) => values,
+                Err(err) => {
+                    return contextual_error!(
+                        ctx.web_context,
+                        ctx.language,
+                        error_template,
+                        default_context,
+                        err
+                    );
+                }
+            }
+        }
+        _ => Vec::new(),
+    };
+
+    let events = section_event_views(&ctx.web_context, &ctx.auth, events).await?;
+
+    let archive_url =
+        |year: i32, month: u32| format!("/{}/past/{}/{:02}", handle_slug, year, month);
+
+    let mut years: Vec<ArchiveYearView> = Vec::new();
+    for (year, month, count) in &months {
+        let month = *month as u32;
+        let month_view = ArchiveMonthView {
+            year: *year,
+            month,
+            label: NaiveDate::from_ymd_opt(*year, month, 1)
+                .map(|value| value.format("%B").to_string())
+                .unwrap_or_default(),
+            count: *count,
+            url: archive_url(*year, month),
+            active: selected == Some((*year, month)),
+        };
+        match years.last_mut() {
+            Some(year_view) if year_view.year == *year => year_view.months.push(month_view),
+            _ => years.push(ArchiveYearView {
+                year: *year,
+                months: vec![month_view],
+            }),
+        }
+    }
+
+    // The months are latest first, so the closest newer month with events is
+    // the last one after the selected month.
+    let month_key = |year: i32, month: i32| (year, month as u32);
+    let newer_url = selected.and_then(|selected| {
+        months
+            .iter()
+            .rev()
+            .find(|(year, month, _)| month_key(*year, *month) > selected)
+            .map(|(year, month, _)| archive_url(*year, *month as u32))
+    });
+    let older_url = selected.and_then(|selected| {
+        months
+            .iter()
+            .find(|(year, month, _)| month_key(*year, *month) < selected)
+            .map(|(year, month, _)| archive_url(*year, *month as u32))
+    });
+
+    let canonical_url = match selected {
+        Some((year, month)) => format!(
+            "https://{}/{}/past/{}/{:02}",
+            ctx.web_context.config.external_base, profile.did, year, month
+        ),
+        None => format!(
+            "https://{}/{}/past",
+            ctx.web_context.config.external_base, profile.did
+        ),
+    };
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                canonical_url,
+                profile,
+                profile_url => format!("/{}", handle_slug),
+                month_label => selected.map(|(year, month)| month_label(year, month)),
+                years,
+                events,
+                newer_url,
+                older_url,
+            }},
+        ),
+    )
+        .into_response())
+}
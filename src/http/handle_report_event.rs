@@ -0,0 +1,122 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{ReportError, WebError},
+    },
+    select_template,
+    storage::{event::event_exists, report::report_insert},
+};
+
+/// The longest reason kept with a report.
+pub const MAX_REPORT_REASON_CHARS: usize = 1000;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReportForm {
+    subject_aturi: String,
+    reason: String,
+}
+
+/// Reports an event to the admins, who review reports from `/admin/reports`.
+/// The report form is swapped out with a confirmation.
+pub async fn handle_report_event(
+    ctx: UserRequestContext,
+    Form(report_form): Form<ReportForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.report.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_aturi = report_form.subject_aturi;
+    let reason = report_form.reason.trim();
+
+    if reason.is_empty() {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            ReportError::EmptyReason
+        );
+    }
+
+    if reason.chars().count() > MAX_REPORT_REASON_CHARS {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            ReportError::ReasonTooLong
+        );
+    }
+
+    match event_exists(&ctx.web_context.pool, &subject_aturi).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                ReportError::EventNotFound(subject_aturi.clone())
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    }
+
+    if let Err(err) = report_insert(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        &subject_aturi,
+        reason,
+    )
+    .await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => subject_aturi },
+                is_reported => true,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
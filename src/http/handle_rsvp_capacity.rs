@@ -0,0 +1,138 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::uri::parse_aturi,
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{RSVPError, WebError},
+    },
+    select_template,
+    storage::event::{event_get, event_update_rsvp_capacity},
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RsvpCapacityForm {
+    subject_aturi: String,
+    #[serde(default)]
+    capacity: String,
+}
+
+/// Parses the capacity field of the capacity form. An empty field means
+/// there is no limit.
+pub(crate) fn parse_rsvp_capacity(value: &str) -> Result<Option<i32>, RSVPError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    match value.parse::<i32>() {
+        Ok(capacity) if capacity > 0 => Ok(Some(capacity)),
+        _ => Err(RSVPError::InvalidCapacity),
+    }
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_rsvp_capacity(
+    ctx: UserRequestContext,
+    Form(capacity_form): Form<RsvpCapacityForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.rsvp_capacity.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_aturi = capacity_form.subject_aturi;
+
+    // Only the organizer, who owns the repository the event lives in, can
+    // change the capacity.
+    let is_organizer = parse_aturi(&subject_aturi)
+        .is_ok_and(|(repository, _, _)| repository == current_handle.did);
+    if !is_organizer {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            RSVPError::CapacityNotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    if let Err(err) = event_get(&ctx.web_context.pool, &subject_aturi).await {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    let rsvp_capacity = match parse_rsvp_capacity(&capacity_form.capacity) {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    if let Err(err) =
+        event_update_rsvp_capacity(&ctx.web_context.pool, &subject_aturi, rsvp_capacity).await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => subject_aturi, rsvp_capacity },
+                capacity_saved => true,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rsvp_capacity() {
+        assert_eq!(parse_rsvp_capacity("").unwrap(), None);
+        assert_eq!(parse_rsvp_capacity(" 25 ").unwrap(), Some(25));
+        assert!(parse_rsvp_capacity("0").is_err());
+        assert!(parse_rsvp_capacity("-3").is_err());
+        assert!(parse_rsvp_capacity("ten").is_err());
+    }
+}
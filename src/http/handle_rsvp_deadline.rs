@@ -0,0 +1,150 @@
+use anyhow::Result;
+use axum::response::IntoResponse;
+use axum_extra::extract::Form;
+use axum_template::RenderHtml;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    atproto::uri::parse_aturi,
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{RSVPError, WebError},
+        timezones::combine_html_datetime,
+    },
+    select_template,
+    storage::event::{event_get, event_update_rsvp_deadline},
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RsvpDeadlineForm {
+    subject_aturi: String,
+    #[serde(default)]
+    closes_date: String,
+    #[serde(default)]
+    closes_time: String,
+}
+
+/// Splits an RSVP deadline into the values used by the date and time inputs
+/// of the deadline form, in the organizer's time zone.
+pub(crate) fn rsvp_deadline_form_values(
+    rsvp_closes_at: Option<DateTime<Utc>>,
+    tz: Tz,
+) -> (Option<String>, Option<String>) {
+    match rsvp_closes_at {
+        Some(value) => {
+            let local = value.with_timezone(&tz);
+            (
+                Some(local.format("%Y-%m-%d").to_string()),
+                Some(local.format("%H:%M").to_string()),
+            )
+        }
+        None => (None, None),
+    }
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_rsvp_deadline(
+    ctx: UserRequestContext,
+    Form(deadline_form): Form<RsvpDeadlineForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, true, ctx.language);
+    let render_template = format!(
+        "view_event.{}.rsvp_deadline.html",
+        ctx.language.to_string().to_lowercase()
+    );
+
+    let subject_aturi = deadline_form.subject_aturi;
+
+    // Only the organizer, who owns the repository the event lives in, can
+    // change the deadline.
+    let is_organizer = parse_aturi(&subject_aturi)
+        .is_ok_and(|(repository, _, _)| repository == current_handle.did);
+    if !is_organizer {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            RSVPError::DeadlineNotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    if let Err(err) = event_get(&ctx.web_context.pool, &subject_aturi).await {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    let tz = current_handle.tz.parse::<Tz>().unwrap_or(Tz::UTC);
+
+    // An empty date clears the deadline so that RSVPs close when the event
+    // starts.
+    let rsvp_closes_at = if deadline_form.closes_date.trim().is_empty() {
+        None
+    } else {
+        match combine_html_datetime(
+            deadline_form.closes_date.trim(),
+            deadline_form.closes_time.trim(),
+            tz,
+        ) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    RSVPError::InvalidDeadline(err.to_string())
+                );
+            }
+        }
+    };
+
+    if let Err(err) =
+        event_update_rsvp_deadline(&ctx.web_context.pool, &subject_aturi, rsvp_closes_at).await
+    {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    let (rsvp_closes_date, rsvp_closes_time) = rsvp_deadline_form_values(rsvp_closes_at, tz);
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                event => template_context! { aturi => subject_aturi },
+                rsvp_closes_date,
+                rsvp_closes_time,
+                rsvp_deadline_tz => tz.name(),
+                deadline_saved => true,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::lexicon::community::lexicon::calendar::event::NSID as LexiconCommunityEventNSID,
+    contextual_error,
+    http::context::UserRequestContext,
+    http::errors::{CommonError, RSVPError, WebError},
+    http::utils::url_from_aturi,
+    resolve::{parse_input, InputType},
+    select_template,
+    storage::{
+        event::event_get,
+        handle::{handle_for_did, handle_for_handle, handles_by_did},
+        rsvp_history::{model::RsvpStatusTotal, rsvp_history_daily_totals, rsvp_history_list},
+    },
+};
+
+/// The most recent changes shown on the RSVP history page.
+const RECENT_CHANGES_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct RsvpHistoryParam {
+    #[serde(default = "default_collection")]
+    collection: String,
+}
+
+fn default_collection() -> String {
+    LexiconCommunityEventNSID.to_string()
+}
+
+/// The RSVP totals of an event at the end of a day.
+#[derive(Debug, Serialize)]
+pub(crate) struct DailyTotals {
+    day: String,
+    going: i64,
+    interested: i64,
+    notgoing: i64,
+}
+
+/// Turns the totals of each status into one row per day. Totals only change
+/// on days with changes, so each row starts from the day before.
+pub(crate) fn daily_totals(totals: &[RsvpStatusTotal]) -> Vec<DailyTotals> {
+    let mut rows: Vec<DailyTotals> = vec![];
+
+    for total in totals {
+        let day = total.day.format("%Y-%m-%d").to_string();
+        if rows.last().is_none_or(|row| row.day != day) {
+            let previous = rows.last();
+            rows.push(DailyTotals {
+                day,
+                going: previous.map_or(0, |row| row.going),
+                interested: previous.map_or(0, |row| row.interested),
+                notgoing: previous.map_or(0, |row| row.notgoing),
+            });
+        }
+
+        let row = rows.last_mut().expect("a row was added for this day");
+        match total.status.as_str() {
+            "going" => row.going = total.count,
+            "interested" => row.interested = total.count,
+            "notgoing" => row.notgoing = total.count,
+            _ => {}
+        }
+    }
+
+    rows
+}
+
+/// Shows the organizer of an event when attendees RSVPed or changed their
+/// RSVP, and how many were going, interested, and not going each day.
+pub async fn handle_rsvp_history(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    Path((handle_slug, event_rkey)): Path<(String, String)>,
+    Query(rsvp_history_param): Query<RsvpHistoryParam>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/")?;
+
+    let collection = rsvp_history_param.collection;
+
+    let default_context = template_context! {
+        current_handle,
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/{}/{}/rsvp-history", ctx.web_context.config.external_base, handle_slug, event_rkey),
+    };
+
+    let render_template = select_template!("rsvp_history", hx_boosted, hx_request, ctx.language);
+    let error_template = select_template!(hx_boosted, hx_request, ctx.language);
+
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&ctx.web_context.pool, &handle)
+            .await
+            .map_err(WebError::from),
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&ctx.web_context.pool, &did)
+                .await
+                .map_err(WebError::from)
+        }
+        _ => Err(WebError::from(CommonError::InvalidHandleSlug)),
+    }?;
+
+    if profile.did != current_handle.did {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            RSVPError::HistoryNotAuthorized,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let lookup_aturi = format!("at://{}/{}/{}", profile.did, collection, event_rkey);
+
+    let event = match event_get(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let event_url = url_from_aturi(&ctx.web_context.config.external_base, &lookup_aturi)?;
+
+    let changes =
+        match rsvp_history_list(&ctx.web_context.pool, &lookup_aturi, RECENT_CHANGES_LIMIT).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    let totals = match rsvp_history_daily_totals(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    // Attendees are shown by handle when one is known
+    let handles = handles_by_did(
+        &ctx.web_context.pool,
+        changes.iter().map(|change| change.did.clone()).collect(),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        tracing::error!("Error getting RSVP history handles: {:?}", err);
+        Default::default()
+    });
+    let changes = changes
+        .into_iter()
+        .map(|change| {
+            let attendee = handles
+                .get(&change.did)
+                .map_or_else(|| change.did.clone(), |handle| handle.handle.clone());
+            template_context! {
+                attendee,
+                previous_status => change.previous_status,
+                status => change.status,
+                created_at => change.created_at,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                event_name => event.name,
+                event_url,
+                changes,
+                daily_totals => daily_totals(&totals),
+            }},
+        ),
+    )
+        .into_response())
+}
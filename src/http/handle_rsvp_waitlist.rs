@@ -0,0 +1,120 @@
+use anyhow::Result;
+use axum::{extract::State, response::IntoResponse};
+use axum_extra::extract::{Cached, Form};
+use axum_htmx::{HxBoosted, HxRequest};
+use axum_template::RenderHtml;
+use chrono::Utc;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::{RSVPError, WebError},
+        middleware_auth::Auth,
+        middleware_i18n::Language,
+        utils::url_from_aturi,
+    },
+    select_template,
+    storage::{
+        event::{event_get, event_going_count, event_rsvps_closed, event_rsvps_full},
+        waitlist::{waitlist_insert, waitlist_position},
+    },
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RsvpWaitlistForm {
+    subject_aturi: String,
+}
+
+/// Puts the current user on the waitlist of a full event. This is the follow
+/// up to an RSVP that was turned away because the event reached its capacity.
+pub async fn handle_rsvp_waitlist(
+    State(web_context): State<WebContext>,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    HxRequest(hx_request): HxRequest,
+    HxBoosted(hx_boosted): HxBoosted,
+    Form(waitlist_form): Form<RsvpWaitlistForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = auth.require(&web_context.config.destination_key, "/rsvp")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => language.to_string(),
+        canonical_url => format!("https://{}/rsvp", web_context.config.external_base),
+        hx_request,
+        hx_boosted,
+    };
+
+    let render_template = select_template!("create_rsvp", hx_boosted, hx_request, language);
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    let subject_aturi = waitlist_form.subject_aturi;
+
+    let event = match event_get(&web_context.pool, &subject_aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err);
+        }
+    };
+
+    if event_rsvps_closed(&event, Utc::now()) {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            RSVPError::Closed
+        );
+    }
+
+    let going_count = match event_going_count(&web_context.pool, &event.aturi).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err);
+        }
+    };
+
+    // The waitlist is only for events that have run out of spots
+    if !event_rsvps_full(&event, going_count) {
+        return contextual_error!(
+            web_context,
+            language,
+            error_template,
+            default_context,
+            RSVPError::NotFull
+        );
+    }
+
+    if let Err(err) = waitlist_insert(&web_context.pool, &event.aturi, &current_handle.did).await {
+        return contextual_error!(web_context, language, error_template, default_context, err);
+    }
+
+    let waitlist_position =
+        match waitlist_position(&web_context.pool, &event.aturi, &current_handle.did).await {
+            Ok(value) => value,
+            Err(err) => {
+                return contextual_error!(
+                    web_context,
+                    language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    let event_url = url_from_aturi(&web_context.config.external_base, &event.aturi)?;
+
+    Ok(RenderHtml(
+        &render_template,
+        web_context.engine.clone(),
+        template_context! { ..default_context, ..template_context! {
+            waitlist_position,
+            event_url,
+        }},
+    )
+    .into_response())
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use axum_extra::extract::Cached;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::WebError,
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
+        middleware_auth::Auth,
+        middleware_i18n::Language,
+        pagination::{Pagination, PaginationView},
+    },
+    normalize::normalize_text,
+    select_template,
+    storage::event::event_search,
+};
+
+#[derive(Deserialize)]
+pub struct SearchParams {
+    #[serde(default)]
+    q: Option<String>,
+}
+
+/// Lists events whose name matches a search query. Matching ignores case and
+/// diacritics, so "cafe" finds "Café".
+pub async fn handle_search(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    Query(search_params): Query<SearchParams>,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let render_template = select_template!("search", hx_boosted, false, language);
+    let error_template = select_template!(false, false, language);
+
+    let query = search_params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string();
+
+    let default_context = template_context! {
+        current_handle => auth.0.clone(),
+        language => language.to_string(),
+        canonical_url => format!("https://{}/search", web_context.config.external_base),
+        query => query.clone(),
+    };
+
+    // Queries that normalize to nothing would match every event, so the
+    // search form is shown on its own instead
+    if normalize_text(&query).is_empty() {
+        return Ok((
+            StatusCode::OK,
+            RenderHtml(
+                &render_template,
+                web_context.engine.clone(),
+                default_context,
+            ),
+        )
+            .into_response());
+    }
+
+    let (page, page_size) = pagination.clamped();
+
+    let events = match event_search(&web_context.pool, &query, page, page_size).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(web_context, language, error_template, default_context, err);
+        }
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            let event_view =
+                EventView::try_from((auth.0.as_ref(), organizer_maybe, &event_view.event));
+
+            match event_view {
+                Ok(event_view) => Some(event_view),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) =
+        hydrate_event_rsvp_counts_cached(&web_context.pool, &web_context.cache_pool, &mut events)
+            .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    let params: Vec<(&str, &str)> = vec![("q", &query)];
+
+    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+
+    if events.len() > page_size as usize {
+        events.truncate(page_size as usize);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &web_context.pool,
+        &web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            web_context.engine.clone(),
+            template_context! {
+                events,
+                searched => true,
+                pagination => pagination_view,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use axum::{response::IntoResponse, Json};
+use chrono::Utc;
+use http::{header, StatusCode};
+use minijinja::context as template_context;
+
+use crate::{
+    contextual_error,
+    http::{context::UserRequestContext, errors::WebError},
+    select_template,
+    storage::export::export_for_did,
+};
+
+/// Downloads everything stored on this instance about the current identity
+/// as a JSON file.
+pub async fn handle_settings_export(
+    ctx: UserRequestContext,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/settings/export")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let now = Utc::now();
+
+    let export = match export_for_did(&ctx.web_context.pool, &current_handle.did, now).await {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let content_disposition = format!(
+        "attachment; filename=\"smokesignal-export-{}.json\"",
+        now.format("%Y%m%d")
+    );
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_DISPOSITION, content_disposition)],
+        Json(export),
+    )
+        .into_response())
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use axum::response::{IntoResponse, Redirect};
+use axum_extra::extract::{cookie::Cookie, Form, PrivateCookieJar};
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::Deserialize;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{SessionError, WebError},
+        middleware_auth::AUTH_COOKIE_NAME,
+    },
+    select_template,
+    storage::{
+        cache::refresh_queue_remove,
+        oauth::{
+            oauth_session_delete, oauth_session_delete_for_did, oauth_session_summary,
+            oauth_sessions_for_did,
+        },
+    },
+};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SessionRevokeForm {
+    session_group: String,
+}
+
+pub async fn handle_settings_sessions(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require(
+        &ctx.web_context.config.destination_key,
+        "/settings/sessions",
+    )?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/settings/sessions", ctx.web_context.config.external_base),
+    };
+
+    let render_template = select_template!("settings_sessions", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let sessions = match oauth_sessions_for_did(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        Utc::now(),
+    )
+    .await
+    {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let current_session_group = ctx
+        .auth
+        .1
+        .as_ref()
+        .map(|oauth_session| oauth_session.session_group.clone());
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                sessions,
+                current_session_group,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Signs out one session of the current identity. Signing out the session
+/// making the request also removes its cookie.
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_settings_sessions_revoke(
+    ctx: UserRequestContext,
+    jar: PrivateCookieJar,
+    Form(revoke_form): Form<SessionRevokeForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let session_group = revoke_form.session_group;
+
+    match oauth_session_summary(&ctx.web_context.pool, &session_group).await {
+        Ok(Some(session)) if session.did == current_handle.did => {}
+        Ok(_) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                SessionError::SessionNotFound(session_group.clone())
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    }
+
+    if let Err(err) = oauth_session_delete(&ctx.web_context.pool, &session_group).await {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            err
+        );
+    }
+
+    if let Err(err) = refresh_queue_remove(
+        &ctx.web_context.cache_pool,
+        std::slice::from_ref(&session_group),
+    )
+    .await
+    {
+        tracing::warn!(error = ?err, "unable to remove session from refresh queue");
+    }
+
+    let is_current = ctx
+        .auth
+        .1
+        .as_ref()
+        .is_some_and(|oauth_session| oauth_session.session_group == session_group);
+
+    if is_current {
+        let updated_jar = jar.remove(Cookie::from(AUTH_COOKIE_NAME));
+        return Ok((updated_jar, Redirect::to("/")).into_response());
+    }
+
+    Ok(Redirect::to("/settings/sessions").into_response())
+}
+
+/// Signs out every session of the current identity, including the one making
+/// the request.
+#[tracing::instrument(skip_all, err)]
+pub async fn handle_settings_sessions_revoke_all(
+    ctx: UserRequestContext,
+    jar: PrivateCookieJar,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx.auth.require_flat()?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let session_groups =
+        match oauth_session_delete_for_did(&ctx.web_context.pool, &current_handle.did).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    ctx.web_context,
+                    ctx.language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        };
+
+    if let Err(err) = refresh_queue_remove(&ctx.web_context.cache_pool, &session_groups).await {
+        tracing::warn!(error = ?err, "unable to remove sessions from refresh queue");
+    }
+
+    let updated_jar = jar.remove(Cookie::from(AUTH_COOKIE_NAME));
+    Ok((updated_jar, Redirect::to("/")).into_response())
+}
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use axum::{extract::Path, response::IntoResponse};
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+
+use crate::{
+    atproto::lexicon::events::smokesignal::calendar::NSID,
+    contextual_error,
+    http::{
+        context::UserRequestContext,
+        errors::{CalendarError, CommonError, WebError},
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
+        handle_calendar_grid::render_calendar_month,
+        handle_calendars::CalendarView,
+    },
+    resolve::{parse_input, InputType},
+    select_template,
+    storage::{
+        calendar::{calendar_events, calendar_get},
+        handle::{handle_for_did, handle_for_handle},
+    },
+};
+
+/// Shows an event series with all of its events, in the order they start.
+pub async fn handle_view_calendar(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+    Path((handle_slug, calendar_rkey)): Path<(String, String)>,
+) -> Result<impl IntoResponse, WebError> {
+    // Month grids share the shape of series URLs, as `/calendar/2025/05`.
+    // Handles and DIDs are never just digits.
+    if let (Ok(year), Ok(month)) = (handle_slug.parse::<i32>(), calendar_rkey.parse::<u32>()) {
+        return render_calendar_month(ctx, hx_boosted, year, month).await;
+    }
+
+    let default_context = template_context! {
+        current_handle => ctx.current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/calendar/{}/{}", ctx.web_context.config.external_base, handle_slug, calendar_rkey),
+    };
+
+    let render_template = select_template!("view_calendar", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let profile = match parse_input(&handle_slug) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(&ctx.web_context.pool, &handle)
+            .await
+            .map_err(WebError::from),
+        Ok(InputType::Plc(did) | InputType::Web(did)) => {
+            handle_for_did(&ctx.web_context.pool, &did)
+                .await
+                .map_err(WebError::from)
+        }
+        _ => Err(WebError::from(CommonError::InvalidHandleSlug)),
+    };
+    let profile = match profile {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err,
+                StatusCode::NOT_FOUND
+            );
+        }
+    };
+
+    let lookup_aturi = format!("at://{}/{}/{}", profile.did, NSID, calendar_rkey);
+
+    let calendar = match calendar_get(&ctx.web_context.pool, &lookup_aturi).await {
+        Ok(Some(value)) => value,
+        Ok(None) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                CalendarError::NotFound(calendar_rkey.clone()),
+                StatusCode::NOT_FOUND
+            );
+        }
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let events = match calendar_events(&ctx.web_context.pool, &calendar.aturi).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&ctx.web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            EventView::try_from((
+                ctx.current_handle.as_ref(),
+                organizer_maybe,
+                &event_view.event,
+            ))
+            .ok()
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) = hydrate_event_rsvp_counts_cached(
+        &ctx.web_context.pool,
+        &ctx.web_context.cache_pool,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &ctx.web_context.pool,
+        &ctx.web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    let is_owner = ctx
+        .current_handle
+        .as_ref()
+        .is_some_and(|handle| handle.did == calendar.did);
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! { ..default_context, ..template_context! {
+                calendar => CalendarView::new(&calendar),
+                profile,
+                events,
+                is_owner,
+            }},
+        ),
+    )
+        .into_response())
+}
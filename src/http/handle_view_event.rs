@@ -1,41 +1,65 @@
+use std::collections::HashMap;
 use std::fmt;
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, Query},
+    extract::{OriginalUri, Path, Query},
     response::{IntoResponse, Redirect},
 };
 use axum_htmx::HxBoosted;
 use axum_template::RenderHtml;
-use http::StatusCode;
+use chrono::Utc;
+use chrono_tz::Tz;
+use http::{header::REFERER, HeaderMap, StatusCode};
 use minijinja::context as template_context;
 use serde::{Deserialize, Serialize};
 
 use crate::atproto::lexicon::community::lexicon::calendar::event::NSID;
 use crate::atproto::lexicon::events::smokesignal::calendar::event::NSID as SMOKESIGNAL_EVENT_NSID;
+use crate::config::LabelAction;
 use crate::contextual_error;
-use crate::http::context::UserRequestContext;
+use crate::http::context::{UserRequestContext, WebContext};
 use crate::http::errors::CommonError;
 use crate::http::errors::ViewEventError;
 use crate::http::errors::WebError;
-use crate::http::event_view::hydrate_event_rsvp_counts;
+use crate::http::event_view::hydrate_event_description;
+use crate::http::event_view::hydrate_event_rsvp_counts_cached;
 use crate::http::event_view::EventView;
+use crate::http::handle_event_updates::EVENT_UPDATE_LIST_LIMIT;
+use crate::http::handle_rsvp_deadline::rsvp_deadline_form_values;
+use crate::http::middleware_read_only::is_read_only;
+use crate::http::page_cache::{cached_anonymous_page, render_anonymous_page};
 use crate::http::pagination::Pagination;
 use crate::http::tab_selector::TabSelector;
 use crate::http::utils::url_from_aturi;
 use crate::resolve::parse_input;
 use crate::resolve::InputType;
 use crate::select_template;
-use crate::storage::event::count_event_rsvps;
+use crate::storage::bookmark::bookmark_exists;
+use crate::storage::cache::handles_by_did_cached;
+use crate::storage::event::count_colisted_event_rsvps;
 use crate::storage::event::event_exists;
 use crate::storage::event::event_get;
-use crate::storage::event::get_event_rsvps;
+use crate::storage::event::event_list_colisted;
+use crate::storage::event::event_rsvp_aturis;
+use crate::storage::event::event_rsvps_closed;
+use crate::storage::event::event_rsvps_full;
+use crate::storage::event::get_colisted_event_rsvps;
 use crate::storage::event::get_user_rsvp;
+use crate::storage::event_revision::event_revision_list;
+use crate::storage::event_update::event_update_list;
+use crate::storage::event_view::event_view_record;
 use crate::storage::handle::handle_for_did;
 use crate::storage::handle::handle_for_handle;
 use crate::storage::handle::model::Handle;
+use crate::storage::label::label_values_by_subject;
+use crate::storage::link_click::link_click_list;
+use crate::storage::waitlist::waitlist_position;
 use crate::storage::StoragePool;
 
+/// How many of the most recent changes to an event are shown on its page.
+const EVENT_REVISION_LIST_LIMIT: i64 = 20;
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub enum RSVPTab {
     Going,
@@ -73,6 +97,13 @@ fn default_collection() -> String {
     NSID.to_string()
 }
 
+/// Set once the viewer chose to see an event that has a content warning.
+#[derive(Debug, Deserialize, Default)]
+pub struct ContentWarningParam {
+    #[serde(default)]
+    show: bool,
+}
+
 /// Helper function to fetch the organizer's handle (which contains their time zone)
 /// This is used to implement the time zone selection logic.
 async fn fetch_organizer_handle(pool: &StoragePool, did: &str) -> Option<Handle> {
@@ -85,13 +116,48 @@ async fn fetch_organizer_handle(pool: &StoragePool, did: &str) -> Option<Handle>
     }
 }
 
+/// The host of the page that linked to an event, which is all that is kept
+/// of the referrer.
+fn referrer_host(headers: &HeaderMap) -> Option<String> {
+    let referrer = headers.get(REFERER)?.to_str().ok()?;
+    url::Url::parse(referrer)
+        .ok()?
+        .host_str()
+        .map(str::to_lowercase)
+}
+
+// Counts a view of an event for the organizer's analytics. A view that can't
+// be counted shouldn't keep the page from loading.
+async fn record_event_view(web_context: &WebContext, headers: &HeaderMap, event_aturi: &str) {
+    if is_read_only(web_context).await {
+        return;
+    }
+
+    let referrer = referrer_host(headers);
+    if let Err(err) = event_view_record(
+        &web_context.pool,
+        event_aturi,
+        referrer.as_deref(),
+        Utc::now(),
+    )
+    .await
+    {
+        tracing::warn!(error = ?err, "unable to record event view");
+    }
+}
+
+#[tracing::instrument(skip_all, err)]
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_view_event(
     ctx: UserRequestContext,
     HxBoosted(hx_boosted): HxBoosted,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
     Path((handle_slug, event_rkey)): Path<(String, String)>,
     pagination: Query<Pagination>,
     tab_selector: Query<TabSelector>,
     collection_param: Query<CollectionParam>,
+    content_warning_param: Query<ContentWarningParam>,
 ) -> Result<impl IntoResponse, WebError> {
     let default_context = template_context! {
         language => ctx.language.to_string(),
@@ -133,6 +199,18 @@ pub async fn handle_view_event(
     let collection = &collection_param.0.collection;
     let lookup_aturi = format!("at://{}/{}/{}", profile.did, collection, event_rkey);
 
+    // Pages shown to people who aren't signed in are served from the page
+    // cache while it has them. Views of cached pages are still counted.
+    let page_uri = uri.to_string();
+    if ctx.current_handle.is_none() {
+        if let Some(response) =
+            cached_anonymous_page(&ctx.web_context, &headers, &render_template, &page_uri).await
+        {
+            record_event_view(&ctx.web_context, &headers, &lookup_aturi).await;
+            return Ok(response);
+        }
+    }
+
     // Check if this is a legacy event (not using the standard community calendar collection)
     let is_legacy_event = collection != NSID;
 
@@ -263,12 +341,89 @@ pub async fn handle_view_event(
     //     tracing::warn!("Failed to hydrate event organizers: {}", err);
     // }
 
-    if let Err(err) = hydrate_event_rsvp_counts(&ctx.web_context.pool, &mut event_vec).await {
+    if let Err(err) = hydrate_event_rsvp_counts_cached(
+        &ctx.web_context.pool,
+        &ctx.web_context.cache_pool,
+        &mut event_vec,
+    )
+    .await
+    {
         tracing::warn!("Failed to hydrate event counts: {}", err);
     }
 
     event = event_vec.remove(0);
 
+    // Labels on the event or its organizer can hide it or put a warning
+    // in front of it
+    let labels = label_values_by_subject(
+        &ctx.web_context.pool,
+        &[event.aturi.clone(), profile.did.clone()],
+    )
+    .await
+    .unwrap_or_else(|err| {
+        tracing::warn!("Failed to get event labels: {}", err);
+        Default::default()
+    })
+    .into_values()
+    .flatten()
+    .collect::<Vec<String>>();
+    match ctx.web_context.config.label_policy.action(&labels) {
+        Some((LabelAction::Hide, label)) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                WebError::from(ViewEventError::EventHidden(label.clone())),
+                StatusCode::NOT_FOUND
+            );
+        }
+        Some((LabelAction::Warn, label)) => event.content_warning = Some(label),
+        None => {}
+    }
+
+    // Events with a content warning are shown once the viewer chooses to,
+    // except to their organizer
+    let is_organizer = ctx
+        .current_handle
+        .as_ref()
+        .is_some_and(|current_entity| current_entity.did == profile.did);
+    if let Some(content_warning) = &event.content_warning {
+        if !content_warning_param.show && !is_organizer {
+            let show_url = if is_legacy_event {
+                format!(
+                    "/{}/{}?collection={}&show=true",
+                    handle_slug,
+                    event_rkey,
+                    urlencoding::encode(collection)
+                )
+            } else {
+                format!("/{}/{}?show=true", handle_slug, event_rkey)
+            };
+            let render_template =
+                select_template!("content_warning", hx_boosted, false, ctx.language);
+            return Ok(RenderHtml(
+                &render_template,
+                ctx.web_context.engine.clone(),
+                template_context! {
+                    content_warning,
+                    show_url,
+                    event_name => event.name.clone(),
+                    ..default_context
+                },
+            )
+            .into_response());
+        }
+    }
+
+    if let Ok(stored_event) = &event_get_result {
+        if let Err(err) =
+            hydrate_event_description(&ctx.web_context.pool, &mut event, stored_event).await
+        {
+            tracing::warn!("Failed to hydrate event description: {}", err);
+        }
+    }
+
     let is_self = ctx
         .current_handle
         .clone()
@@ -286,6 +441,134 @@ pub async fn handle_view_event(
         .clone()
         .is_some_and(|current_entity| current_entity.did == profile.did);
 
+    // Views are counted for the organizer's analytics, except their own
+    if !can_edit {
+        record_event_view(&ctx.web_context, &headers, &event.aturi).await;
+    }
+
+    // RSVPs close at the organizer's deadline, or when the event starts
+    let rsvps_closed = event_get_result
+        .as_ref()
+        .is_ok_and(|stored_event| event_rsvps_closed(stored_event, Utc::now()));
+
+    // The organizer sees the deadline form prefilled in their own time zone
+    let (rsvp_closes_date, rsvp_closes_time) = match (&ctx.current_handle, &event_get_result) {
+        (Some(current_entity), Ok(stored_event)) if can_edit => rsvp_deadline_form_values(
+            stored_event.rsvp_closes_at,
+            current_entity.tz.parse::<Tz>().unwrap_or(Tz::UTC),
+        ),
+        _ => (None, None),
+    };
+    let rsvp_deadline_tz = ctx.current_handle.as_ref().map(|current_entity| {
+        current_entity
+            .tz
+            .parse::<Tz>()
+            .unwrap_or(Tz::UTC)
+            .name()
+            .to_string()
+    });
+
+    // Bookmarks are private to the viewer and available for all events
+    let is_bookmarked = if let Some(current_entity) = &ctx.current_handle {
+        bookmark_exists(&ctx.web_context.pool, &current_entity.did, &lookup_aturi)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::error!("Error getting user bookmark status: {:?}", err);
+                false
+            })
+    } else {
+        false
+    };
+
+    // The organizer sees how often each link was followed through `/out`
+    let link_redirector = ctx.web_context.config.link_redirector;
+    let link_clicks = if can_edit && link_redirector {
+        link_click_list(&ctx.web_context.pool, &lookup_aturi)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::error!("Error getting link clicks: {:?}", err);
+                Vec::new()
+            })
+            .into_iter()
+            .map(|value| (value.url, value.clicks))
+            .collect::<HashMap<_, _>>()
+    } else {
+        HashMap::new()
+    };
+
+    // Updates the organizer sent to attendees are shown to everyone
+    let event_updates = if is_legacy_event {
+        Vec::new()
+    } else {
+        event_update_list(
+            &ctx.web_context.pool,
+            &lookup_aturi,
+            EVENT_UPDATE_LIST_LIMIT,
+        )
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!("Error getting event updates: {:?}", err);
+            Vec::new()
+        })
+    };
+
+    // What the organizer changed about the event, so attendees can catch up
+    let event_revisions = if is_legacy_event {
+        Vec::new()
+    } else {
+        event_revision_list(
+            &ctx.web_context.pool,
+            &lookup_aturi,
+            EVENT_REVISION_LIST_LIMIT,
+        )
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!("Error getting event revisions: {:?}", err);
+            Vec::new()
+        })
+    };
+
+    // Events republished by other organizers that link back to this one share
+    // their RSVPs with it
+    let colisted_events = match &event_get_result {
+        Ok(stored_event) if !is_legacy_event => {
+            event_list_colisted(&ctx.web_context.pool, stored_event)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::error!("Error getting co-listed events: {:?}", err);
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
+
+    // RSVPs to the legacy event a standard event was migrated from are
+    // shown with it
+    let mut rsvp_aturis = if is_legacy_event {
+        vec![lookup_aturi.clone()]
+    } else {
+        event_rsvp_aturis(&ctx.web_context.pool, &lookup_aturi)
+            .await
+            .unwrap_or_else(|err| {
+                tracing::error!("Error getting migrated event RSVPs: {:?}", err);
+                vec![lookup_aturi.clone()]
+            })
+    };
+    let mut colisted = Vec::new();
+    for colisted_event in &colisted_events {
+        rsvp_aturis.push(colisted_event.aturi.clone());
+
+        let Ok(url) = url_from_aturi(&ctx.web_context.config.external_base, &colisted_event.aturi)
+        else {
+            continue;
+        };
+        let handle = fetch_organizer_handle(&ctx.web_context.pool, &colisted_event.did)
+            .await
+            .map(|handle| handle.handle)
+            .unwrap_or_else(|| colisted_event.did.clone());
+        colisted.push(template_context! { handle, url });
+    }
+
     // Variables for RSVP data
     let (
         user_rsvp_status,
@@ -311,62 +594,54 @@ pub async fn handle_view_event(
             None
         };
 
-        // Get counts for all RSVP statuses
-        let going_count = count_event_rsvps(&ctx.web_context.pool, &lookup_aturi, "going")
-            .await
-            .unwrap_or_default();
-
-        let interested_count =
-            count_event_rsvps(&ctx.web_context.pool, &lookup_aturi, "interested")
+        // An event without co-listings already has its (cached) counts.
+        // Otherwise, count all RSVP statuses across this event and its
+        // co-listings.
+        let (going_count, interested_count, notgoing_count) = if colisted_events.is_empty() {
+            (
+                event.count_going,
+                event.count_interested,
+                event.count_notgoing,
+            )
+        } else {
+            let counts = count_colisted_event_rsvps(&ctx.web_context.pool, &rsvp_aturis)
                 .await
-                .unwrap_or_default();
+                .unwrap_or_else(|err| {
+                    tracing::error!("Error counting co-listed event RSVPs: {:?}", err);
+                    Default::default()
+                });
+            let count = |status: &str| counts.get(status).copied().unwrap_or_default();
 
-        let notgoing_count = count_event_rsvps(&ctx.web_context.pool, &lookup_aturi, "notgoing")
+            (count("going"), count("interested"), count("notgoing"))
+        };
+
+        // Only get handles for the active tab
+        let status = match tab {
+            RSVPTab::Going => "going",
+            RSVPTab::Interested => "interested",
+            RSVPTab::NotGoing => "notgoing",
+        };
+        let rsvps = get_colisted_event_rsvps(&ctx.web_context.pool, &rsvp_aturis, status)
             .await
             .unwrap_or_default();
 
-        // Only get handles for the active tab
+        let rsvp_dids = rsvps.iter().map(|(did, _)| did.clone()).collect::<Vec<_>>();
+        let mut rsvp_handles = handles_by_did_cached(
+            &ctx.web_context.pool,
+            &ctx.web_context.cache_pool,
+            rsvp_dids,
+        )
+        .await
+        .unwrap_or_default();
+        let handles = rsvps
+            .iter()
+            .filter_map(|(did, _)| rsvp_handles.remove(did))
+            .collect::<Vec<_>>();
+
         let (going_handles, interested_handles, notgoing_handles) = match tab {
-            RSVPTab::Going => {
-                let rsvps = get_event_rsvps(&ctx.web_context.pool, &lookup_aturi, Some("going"))
-                    .await
-                    .unwrap_or_default();
-
-                let mut handles = Vec::new();
-                for (did, _) in &rsvps {
-                    if let Ok(handle) = handle_for_did(&ctx.web_context.pool, did).await {
-                        handles.push(handle.handle);
-                    }
-                }
-                (handles, Vec::new(), Vec::new())
-            }
-            RSVPTab::Interested => {
-                let rsvps =
-                    get_event_rsvps(&ctx.web_context.pool, &lookup_aturi, Some("interested"))
-                        .await
-                        .unwrap_or_default();
-
-                let mut handles = Vec::new();
-                for (did, _) in &rsvps {
-                    if let Ok(handle) = handle_for_did(&ctx.web_context.pool, did).await {
-                        handles.push(handle.handle);
-                    }
-                }
-                (Vec::new(), handles, Vec::new())
-            }
-            RSVPTab::NotGoing => {
-                let rsvps = get_event_rsvps(&ctx.web_context.pool, &lookup_aturi, Some("notgoing"))
-                    .await
-                    .unwrap_or_default();
-
-                let mut handles = Vec::new();
-                for (did, _) in &rsvps {
-                    if let Ok(handle) = handle_for_did(&ctx.web_context.pool, did).await {
-                        handles.push(handle.handle);
-                    }
-                }
-                (Vec::new(), Vec::new(), handles)
-            }
+            RSVPTab::Going => (handles, Vec::new(), Vec::new()),
+            RSVPTab::Interested => (Vec::new(), handles, Vec::new()),
+            RSVPTab::NotGoing => (Vec::new(), Vec::new(), handles),
         };
 
         (
@@ -395,8 +670,8 @@ pub async fn handle_view_event(
 
         // If this is a legacy event, check if the user already has an RSVP for the standard version
         // to avoid showing the migrate button unnecessarily
-        let user_has_standard_rsvp =
-            if standard_event_exists && user_rsvp.is_some() && ctx.current_handle.is_some() {
+        let user_has_standard_rsvp = match &ctx.current_handle {
+            Some(current_handle) if standard_event_exists && user_rsvp.is_some() => {
                 // Construct the standard event URI
                 let standard_event_uri = format!("at://{}/{}/{}", profile.did, NSID, event_rkey);
 
@@ -404,7 +679,7 @@ pub async fn handle_view_event(
                 match get_user_rsvp(
                     &ctx.web_context.pool,
                     &standard_event_uri,
-                    &ctx.current_handle.as_ref().unwrap().did,
+                    &current_handle.did,
                 )
                 .await
                 {
@@ -424,9 +699,9 @@ pub async fn handle_view_event(
                         false // Default to false to allow migration attempt if we can't determine
                     }
                 }
-            } else {
-                false
-            };
+            }
+            _ => false,
+        };
 
         tracing::info!("Legacy event detected, only fetching user RSVP status");
         (
@@ -441,44 +716,87 @@ pub async fn handle_view_event(
         )
     };
 
+    // Going RSVPs to a full event are offered the waitlist instead
+    let rsvps_full = event_get_result
+        .as_ref()
+        .is_ok_and(|stored_event| event_rsvps_full(stored_event, i64::from(going_count)));
+    let waitlist_position = match &ctx.current_handle {
+        Some(current_entity) if rsvps_full => {
+            waitlist_position(&ctx.web_context.pool, &lookup_aturi, &current_entity.did)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::error!("Error getting waitlist position: {:?}", err);
+                    None
+                })
+        }
+        _ => None,
+    };
+
     // Set counts on event
     let mut event_with_counts = event;
     event_with_counts.count_going = going_count;
     event_with_counts.count_interested = interested_count;
     event_with_counts.count_notgoing = notgoing_count;
 
+    let page_context = template_context! {
+        current_handle => ctx.current_handle,
+        language => ctx.language.to_string(),
+        canonical_url => event_url,
+        event => event_with_counts,
+        is_self,
+        can_edit,
+        going => going_handles,
+        interested => interested_handles,
+        notgoing => notgoing_handles,
+        active_tab => tab_name,
+        user_rsvp_status,
+        is_bookmarked,
+        link_redirector,
+        link_clicks,
+        colisted,
+        event_updates,
+        event_revisions,
+        rsvps_closed,
+        rsvps_full,
+        waitlist_position,
+        rsvp_closes_date,
+        rsvp_closes_time,
+        rsvp_deadline_tz,
+        handle_slug,
+        event_rkey,
+        collection => collection.clone(),
+        is_legacy_event,
+        standard_event_exists,
+        has_been_migrated,
+        user_has_standard_rsvp,
+        standard_event_url => if standard_event_exists {
+            Some(format!("/{}/{}", handle_slug, event_rkey))
+        } else {
+            None
+        },
+        SMOKESIGNAL_EVENT_NSID => SMOKESIGNAL_EVENT_NSID,
+        using_SMOKESIGNAL_EVENT_NSID => collection == SMOKESIGNAL_EVENT_NSID,
+    };
+
+    // Pages shown to people who aren't signed in are the same for everyone,
+    // so they can be cached by browsers, shared caches and the page cache
+    if ctx.current_handle.is_none() {
+        return render_anonymous_page(
+            &ctx.web_context,
+            &headers,
+            &render_template,
+            &page_uri,
+            page_context,
+        )
+        .await;
+    }
+
     Ok((
         StatusCode::OK,
         RenderHtml(
             &render_template,
             ctx.web_context.engine.clone(),
-            template_context! {
-                current_handle => ctx.current_handle,
-                language => ctx.language.to_string(),
-                canonical_url => event_url,
-                event => event_with_counts,
-                is_self,
-                can_edit,
-                going => going_handles,
-                interested => interested_handles,
-                notgoing => notgoing_handles,
-                active_tab => tab_name,
-                user_rsvp_status,
-                handle_slug,
-                event_rkey,
-                collection => collection.clone(),
-                is_legacy_event,
-                standard_event_exists,
-                has_been_migrated,
-                user_has_standard_rsvp,
-                standard_event_url => if standard_event_exists {
-                    Some(format!("/{}/{}", handle_slug, event_rkey))
-                } else {
-                    None
-                },
-                SMOKESIGNAL_EVENT_NSID => SMOKESIGNAL_EVENT_NSID,
-                using_SMOKESIGNAL_EVENT_NSID => collection == SMOKESIGNAL_EVENT_NSID,
-            },
+            page_context,
         ),
     )
         .into_response())
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use axum_extra::extract::Cached;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use http::StatusCode;
+use minijinja::context as template_context;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::WebError,
+        event_view::{
+            hydrate_event_labels, hydrate_event_organizers, hydrate_event_rsvp_counts_cached,
+            EventView,
+        },
+        middleware_auth::Auth,
+        middleware_i18n::Language,
+        pagination::{Pagination, PaginationView},
+    },
+    select_template,
+    storage::tag::{event_list_with_tag, tag_slug},
+};
+
+/// Lists the events with a tag, upcoming events first.
+pub async fn handle_view_topic(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    Language(language): Language,
+    Cached(auth): Cached<Auth>,
+    Path(tag): Path<String>,
+    pagination: Query<Pagination>,
+) -> Result<impl IntoResponse, WebError> {
+    let render_template = select_template!("view_topic", hx_boosted, false, language);
+    let error_template = select_template!(false, false, language);
+
+    let tag = tag_slug(&tag);
+
+    let canonical_url = format!(
+        "https://{}/topics/{}",
+        web_context.config.external_base,
+        urlencoding::encode(&tag)
+    );
+
+    let default_context = template_context! {
+        current_handle => auth.0.clone(),
+        language => language.to_string(),
+        canonical_url => canonical_url.clone(),
+        tag => tag.clone(),
+    };
+
+    let (page, page_size) = pagination.clamped();
+
+    let events = if tag.is_empty() {
+        Vec::new()
+    } else {
+        match event_list_with_tag(&web_context.pool, &tag, page, page_size).await {
+            Ok(values) => values,
+            Err(err) => {
+                return contextual_error!(
+                    web_context,
+                    language,
+                    error_template,
+                    default_context,
+                    err
+                );
+            }
+        }
+    };
+
+    let organizer_handlers = hydrate_event_organizers(&web_context.pool, &events).await?;
+
+    let mut events = events
+        .iter()
+        .filter_map(|event_view| {
+            let organizer_maybe = organizer_handlers.get(&event_view.event.did);
+            let event_view =
+                EventView::try_from((auth.0.as_ref(), organizer_maybe, &event_view.event));
+
+            match event_view {
+                Ok(event_view) => Some(event_view),
+                Err(err) => {
+                    tracing::warn!(err = ?err, "error converting event view");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<EventView>>();
+
+    if let Err(err) =
+        hydrate_event_rsvp_counts_cached(&web_context.pool, &web_context.cache_pool, &mut events)
+            .await
+    {
+        tracing::warn!("Failed to hydrate event counts: {}", err);
+    }
+
+    let params: Vec<(&str, &str)> = vec![];
+
+    let pagination_view = PaginationView::new(page_size, events.len() as i64, page, params);
+
+    if events.len() > page_size as usize {
+        events.truncate(page_size as usize);
+    }
+
+    if let Err(err) = hydrate_event_labels(
+        &web_context.pool,
+        &web_context.config.label_policy,
+        &mut events,
+    )
+    .await
+    {
+        tracing::warn!("Failed to hydrate event labels: {}", err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            web_context.engine.clone(),
+            template_context! {
+                events,
+                pagination => pagination_view,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use axum::{
+    extract::Path,
+    response::{IntoResponse, Redirect},
+};
+use axum_extra::extract::Form;
+use axum_htmx::HxBoosted;
+use axum_template::RenderHtml;
+use chrono::Utc;
+use http::StatusCode;
+use minijinja::context as template_context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::uri::parse_aturi,
+    contextual_error,
+    http::{context::UserRequestContext, errors::WebError, utils::url_from_aturi},
+    select_template,
+    storage::{
+        event::{event_get, event_list_did_recently_updated},
+        webhook::{
+            webhook_delete, webhook_deliveries_recent, webhook_enqueue, webhook_insert,
+            webhook_list,
+        },
+        StoragePool,
+    },
+    webhooks::{
+        generate_secret, validate_webhook_url, WebhookEvent, WebhookKind, WebhookPayload,
+        WebhookRsvp, MAX_WEBHOOKS,
+    },
+    webhooks_errors::WebhookError,
+};
+
+/// The most events offered in the webhook form.
+const EVENT_CHOICES: i64 = 50;
+
+/// The most recent deliveries shown on the webhooks page.
+const RECENT_DELIVERIES: i64 = 20;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WebhookForm {
+    url: String,
+    #[serde(default)]
+    event_aturi: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EventChoice {
+    pub aturi: String,
+    pub name: String,
+}
+
+/// Queues deliveries to the webhooks of an event's organizer. Errors are
+/// logged, since webhooks shouldn't keep RSVPs and edits from being saved.
+pub async fn notify_webhooks(
+    pool: &StoragePool,
+    external_base: &str,
+    event_aturi: &str,
+    kind: WebhookKind,
+    rsvp: Option<WebhookRsvp>,
+) {
+    let Ok((organizer_did, _, _)) = parse_aturi(event_aturi) else {
+        return;
+    };
+
+    let payload = WebhookPayload::new(
+        kind,
+        WebhookEvent {
+            uri: event_aturi.to_string(),
+            url: url_from_aturi(external_base, event_aturi).unwrap_or_default(),
+        },
+        rsvp,
+        Utc::now(),
+    );
+
+    if let Err(err) = webhook_enqueue(pool, &organizer_did, event_aturi, &payload).await {
+        tracing::warn!(event_aturi, "Failed to queue webhook deliveries: {}", err);
+    }
+}
+
+/// Shows the webhooks of the current user, their recent deliveries, and a
+/// form to register more.
+pub async fn handle_webhooks(
+    ctx: UserRequestContext,
+    HxBoosted(hx_boosted): HxBoosted,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/webhooks")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/webhooks", ctx.web_context.config.external_base),
+    };
+
+    let render_template = select_template!("webhooks", hx_boosted, false, ctx.language);
+    let error_template = select_template!(hx_boosted, false, ctx.language);
+
+    let webhooks = match webhook_list(&ctx.web_context.pool, &current_handle.did).await {
+        Ok(values) => values,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let deliveries = webhook_deliveries_recent(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        RECENT_DELIVERIES,
+    )
+    .await?;
+
+    let events = event_list_did_recently_updated(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        1,
+        EVENT_CHOICES,
+    )
+    .await?
+    .into_iter()
+    .map(|event_role| EventChoice {
+        aturi: event_role.event.aturi,
+        name: event_role.event.name,
+    })
+    .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::OK,
+        RenderHtml(
+            &render_template,
+            ctx.web_context.engine.clone(),
+            template_context! {
+                webhooks,
+                deliveries,
+                events,
+                max_webhooks => MAX_WEBHOOKS,
+                ..default_context
+            },
+        ),
+    )
+        .into_response())
+}
+
+/// Registers a webhook for one of the current user's events, or for all of
+/// them. Deliveries are signed with a secret shown on the webhooks page.
+pub async fn handle_webhook_create(
+    ctx: UserRequestContext,
+    Form(webhook_form): Form<WebhookForm>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/webhooks")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/webhooks", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    let url = match validate_webhook_url(&webhook_form.url) {
+        Ok(value) => value,
+        Err(err) => {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                err
+            );
+        }
+    };
+
+    let event_aturi = webhook_form
+        .event_aturi
+        .filter(|value| !value.trim().is_empty());
+
+    if let Some(event_aturi) = &event_aturi {
+        let is_organizer = event_get(&ctx.web_context.pool, event_aturi)
+            .await
+            .is_ok_and(|event| event.did == current_handle.did);
+        if !is_organizer {
+            return contextual_error!(
+                ctx.web_context,
+                ctx.language,
+                error_template,
+                default_context,
+                WebhookError::EventNotFound(event_aturi.clone())
+            );
+        }
+    }
+
+    let webhooks = webhook_list(&ctx.web_context.pool, &current_handle.did).await?;
+    if webhooks.len() as i64 >= MAX_WEBHOOKS {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            WebhookError::TooManyWebhooks(MAX_WEBHOOKS)
+        );
+    }
+
+    webhook_insert(
+        &ctx.web_context.pool,
+        &current_handle.did,
+        event_aturi.as_deref(),
+        &url,
+        &generate_secret(),
+    )
+    .await?;
+
+    Ok(Redirect::to("/webhooks").into_response())
+}
+
+/// Removes a webhook of the current user. Deliveries that haven't been sent
+/// yet are dropped.
+pub async fn handle_webhook_delete(
+    ctx: UserRequestContext,
+    Path(webhook_id): Path<String>,
+) -> Result<impl IntoResponse, WebError> {
+    let current_handle = ctx
+        .auth
+        .require(&ctx.web_context.config.destination_key, "/webhooks")?;
+
+    let default_context = template_context! {
+        current_handle => current_handle.clone(),
+        language => ctx.language.to_string(),
+        canonical_url => format!("https://{}/webhooks", ctx.web_context.config.external_base),
+    };
+
+    let error_template = select_template!(false, false, ctx.language);
+
+    if !webhook_delete(&ctx.web_context.pool, &current_handle.did, &webhook_id).await? {
+        return contextual_error!(
+            ctx.web_context,
+            ctx.language,
+            error_template,
+            default_context,
+            WebhookError::WebhookNotFound(webhook_id.clone())
+        );
+    }
+
+    Ok(Redirect::to("/webhooks").into_response())
+}
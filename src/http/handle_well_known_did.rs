@@ -0,0 +1,46 @@
+use anyhow::Result;
+use axum::{extract::State, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::http::{context::WebContext, errors::WebError};
+use crate::service_auth::{service_did, PublicSigningKey};
+
+/// Serves the `did:web` document for this instance.
+///
+/// The document publishes the service key used to sign inter-service auth
+/// tokens and the endpoints of the XRPC services and feed generator hosted by
+/// this instance.
+pub async fn handle_well_known_did(
+    State(web_context): State<WebContext>,
+) -> Result<impl IntoResponse, WebError> {
+    let did = service_did(&web_context.config.external_base);
+    let public_key = PublicSigningKey::P256(web_context.config.service_key.public_key());
+
+    Ok(Json(json!({
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/multikey/v1"
+        ],
+        "id": did,
+        "verificationMethod": [
+            {
+                "id": format!("{}#atproto", did),
+                "type": "Multikey",
+                "controller": did,
+                "publicKeyMultibase": public_key.to_multibase(),
+            }
+        ],
+        "service": [
+            {
+                "id": "#smokesignal_appview",
+                "type": "SmokeSignalAppView",
+                "serviceEndpoint": format!("https://{}", web_context.config.external_base),
+            },
+            {
+                "id": "#bsky_fg",
+                "type": "BskyFeedGenerator",
+                "serviceEndpoint": format!("https://{}", web_context.config.external_base),
+            }
+        ],
+    })))
+}
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use axum::{extract::State, response::IntoResponse, Json};
+use axum_extra::extract::Query;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::uri::parse_aturi,
+    http::{
+        context::WebContext,
+        errors::xrpc_error::XrpcError,
+        pagination::{Cursor, CursorPagination},
+    },
+    resolve::{parse_input, InputType},
+    storage::{
+        errors::StorageError,
+        event::{
+            event_exists, event_get, event_list, event_list_did_recently_updated,
+            event_rsvp_deadline, event_rsvps_closed, event_rsvps_full, get_event_rsvp_counts,
+            model::{Event, Rsvp},
+            rsvp_list_for_event,
+        },
+        handle::{handle_for_did, handle_for_handle, handles_by_did, model::Handle},
+        tag::tag_slug,
+        StoragePool,
+    },
+};
+
+pub const GET_EVENT_NSID: &str = "events.smokesignal.calendar.getEvent";
+pub const LIST_EVENTS_BY_ACTOR_NSID: &str = "events.smokesignal.calendar.listEventsByActor";
+pub const GET_RSVP_COUNTS_NSID: &str = "events.smokesignal.calendar.getRsvpCounts";
+
+// The read-only queries of the calendar lexicon, so that other ATProto
+// clients can use this instance as an AppView for calendar records.
+pub const CALENDAR_GET_EVENT_NSID: &str = "community.lexicon.calendar.getEvent";
+pub const CALENDAR_LIST_EVENTS_NSID: &str = "community.lexicon.calendar.listEvents";
+pub const CALENDAR_GET_RSVPS_NSID: &str = "community.lexicon.calendar.getRsvps";
+
+const LIST_LIMIT_DEFAULT: i64 = 50;
+const LIST_LIMIT_MAX: i64 = 100;
+const RSVP_COUNTS_URIS_MAX: usize = 25;
+const RSVP_STATUSES: [&str; 3] = ["going", "interested", "notgoing"];
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RsvpCountsView {
+    pub going: u32,
+    pub interested: u32,
+    pub not_going: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorView {
+    pub did: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecordView {
+    pub uri: String,
+    pub cid: String,
+    pub organizer: ActorView,
+    pub record: serde_json::Value,
+    pub counts: RsvpCountsView,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp_closes_at: Option<DateTime<Utc>>,
+    pub rsvps_closed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp_capacity: Option<i32>,
+    pub rsvps_full: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+impl EventRecordView {
+    fn new(
+        event: &Event,
+        organizer: Option<&Handle>,
+        counts: &HashMap<(String, String), i64>,
+    ) -> Self {
+        let counts = rsvp_counts_for(counts, &event.aturi);
+        Self {
+            uri: event.aturi.clone(),
+            cid: event.cid.clone(),
+            organizer: ActorView {
+                did: event.did.clone(),
+                handle: organizer.map(|handle| handle.handle.clone()),
+            },
+            record: event.record.0.clone(),
+            rsvp_closes_at: event_rsvp_deadline(event),
+            rsvps_closed: event_rsvps_closed(event, Utc::now()),
+            rsvp_capacity: event.rsvp_capacity,
+            rsvps_full: event_rsvps_full(event, i64::from(counts.going)),
+            counts,
+            indexed_at: event.updated_at,
+        }
+    }
+}
+
+pub(crate) fn rsvp_counts_for(
+    counts: &HashMap<(String, String), i64>,
+    aturi: &str,
+) -> RsvpCountsView {
+    let count = |status: &str| {
+        counts
+            .get(&(aturi.to_string(), status.to_string()))
+            .cloned()
+            .unwrap_or(0) as u32
+    };
+    RsvpCountsView {
+        going: count("going"),
+        interested: count("interested"),
+        not_going: count("notgoing"),
+    }
+}
+
+async fn resolve_actor(pool: &StoragePool, actor: &str) -> Result<Handle, XrpcError> {
+    let handle = match parse_input(actor) {
+        Ok(InputType::Handle(handle)) => handle_for_handle(pool, &handle).await,
+        Ok(InputType::Plc(did) | InputType::Web(did)) => handle_for_did(pool, &did).await,
+        Err(_) => return Err(XrpcError::InvalidRequest("actor".to_string())),
+    };
+    handle.map_err(|err| match err {
+        StorageError::HandleNotFound => XrpcError::ActorNotFound(actor.to_string()),
+        other => other.into(),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct GetEventParams {
+    pub uri: Option<String>,
+}
+
+/// `events.smokesignal.calendar.getEvent` and
+/// `community.lexicon.calendar.getEvent`: returns a single indexed event.
+pub async fn handle_xrpc_get_event(
+    State(web_context): State<WebContext>,
+    Query(params): Query<GetEventParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let uri = params
+        .uri
+        .filter(|value| !value.trim().is_empty())
+        .ok_or(XrpcError::InvalidRequest("uri is required".to_string()))?;
+
+    parse_aturi(&uri).map_err(|err| XrpcError::InvalidRequest(err.to_string()))?;
+
+    let event = event_get(&web_context.pool, &uri)
+        .await
+        .map_err(|err| match err {
+            StorageError::RowNotFound(_, _) => XrpcError::RecordNotFound(uri.clone()),
+            other => other.into(),
+        })?;
+
+    let organizer = handle_for_did(&web_context.pool, &event.did).await.ok();
+    let counts = get_event_rsvp_counts(&web_context.pool, vec![event.aturi.clone()]).await?;
+
+    Ok(Json(EventRecordView::new(
+        &event,
+        organizer.as_ref(),
+        &counts,
+    )))
+}
+
+#[derive(Deserialize)]
+pub struct ListEventsByActorParams {
+    pub actor: Option<String>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ListEventsByActorOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub events: Vec<EventRecordView>,
+}
+
+/// `events.smokesignal.calendar.listEventsByActor`: lists the events organized
+/// by an actor, most recently updated first.
+pub async fn handle_xrpc_list_events_by_actor(
+    State(web_context): State<WebContext>,
+    Query(params): Query<ListEventsByActorParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let actor = params
+        .actor
+        .filter(|value| !value.trim().is_empty())
+        .ok_or(XrpcError::InvalidRequest("actor is required".to_string()))?;
+
+    let limit = params
+        .limit
+        .unwrap_or(LIST_LIMIT_DEFAULT)
+        .clamp(1, LIST_LIMIT_MAX);
+
+    let page = match params.cursor {
+        Some(cursor) => cursor
+            .parse::<i64>()
+            .ok()
+            .filter(|page| *page >= 1)
+            .ok_or(XrpcError::InvalidRequest("cursor".to_string()))?,
+        None => 1,
+    };
+
+    let profile = resolve_actor(&web_context.pool, &actor).await?;
+
+    let mut events =
+        event_list_did_recently_updated(&web_context.pool, &profile.did, page, limit).await?;
+
+    let cursor = if events.len() > limit as usize {
+        events.truncate(limit as usize);
+        Some((page + 1).to_string())
+    } else {
+        None
+    };
+
+    let aturis = events
+        .iter()
+        .map(|event| event.event.aturi.clone())
+        .collect::<Vec<_>>();
+    let counts = get_event_rsvp_counts(&web_context.pool, aturis).await?;
+
+    let events = events
+        .iter()
+        .map(|event| EventRecordView::new(&event.event, Some(&profile), &counts))
+        .collect::<Vec<_>>();
+
+    Ok(Json(ListEventsByActorOutput { cursor, events }))
+}
+
+#[derive(Deserialize)]
+pub struct GetRsvpCountsParams {
+    #[serde(default)]
+    pub uris: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RsvpCountsForEventView {
+    pub uri: String,
+    #[serde(flatten)]
+    pub counts: RsvpCountsView,
+}
+
+#[derive(Serialize)]
+pub struct GetRsvpCountsOutput {
+    pub counts: Vec<RsvpCountsForEventView>,
+}
+
+/// `events.smokesignal.calendar.getRsvpCounts`: returns RSVP counts for up
+/// to 25 events.
+pub async fn handle_xrpc_get_rsvp_counts(
+    State(web_context): State<WebContext>,
+    Query(params): Query<GetRsvpCountsParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    if params.uris.is_empty() || params.uris.len() > RSVP_COUNTS_URIS_MAX {
+        return Err(XrpcError::InvalidRequest(format!(
+            "between 1 and {} uris are required",
+            RSVP_COUNTS_URIS_MAX
+        )));
+    }
+
+    for uri in &params.uris {
+        parse_aturi(uri).map_err(|err| XrpcError::InvalidRequest(err.to_string()))?;
+    }
+
+    let counts = get_event_rsvp_counts(&web_context.pool, params.uris.clone()).await?;
+
+    let counts = params
+        .uris
+        .iter()
+        .map(|uri| RsvpCountsForEventView {
+            uri: uri.clone(),
+            counts: rsvp_counts_for(&counts, uri),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(GetRsvpCountsOutput { counts }))
+}
+
+#[derive(Deserialize)]
+pub struct ListEventsParams {
+    pub tag: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ListEventsOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub events: Vec<EventRecordView>,
+}
+
+/// `community.lexicon.calendar.listEvents`: lists indexed events, most
+/// recently updated first, optionally only those with a tag.
+pub async fn handle_xrpc_list_events(
+    State(web_context): State<WebContext>,
+    Query(pagination): Query<CursorPagination>,
+    Query(params): Query<ListEventsParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let limit = pagination.clamped_limit(LIST_LIMIT_DEFAULT, 1, LIST_LIMIT_MAX);
+
+    let tag = match params.tag.as_deref().map(tag_slug) {
+        Some(tag) if tag.is_empty() => return Err(XrpcError::InvalidRequest("tag".to_string())),
+        tag => tag,
+    };
+
+    let after = match &pagination.cursor {
+        Some(cursor) => {
+            Some(Cursor::decode(cursor).ok_or(XrpcError::InvalidRequest("cursor".to_string()))?)
+        }
+        None => None,
+    };
+
+    let mut events = event_list(
+        &web_context.pool,
+        tag.as_deref(),
+        after.as_ref().map(Cursor::after),
+        limit,
+    )
+    .await?;
+
+    let cursor = Cursor::next_page(&mut events, limit, |event| {
+        event
+            .updated_at
+            .map(|updated_at| Cursor::new(updated_at, &event.aturi))
+    });
+
+    let dids = events.iter().map(|event| event.did.clone()).collect();
+    let organizers = handles_by_did(&web_context.pool, dids).await?;
+
+    let aturis = events.iter().map(|event| event.aturi.clone()).collect();
+    let counts = get_event_rsvp_counts(&web_context.pool, aturis).await?;
+
+    let events = events
+        .iter()
+        .map(|event| EventRecordView::new(event, organizers.get(&event.did), &counts))
+        .collect::<Vec<_>>();
+
+    Ok(Json(ListEventsOutput { cursor, events }))
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RsvpRecordView {
+    pub uri: String,
+    pub cid: String,
+    pub actor: ActorView,
+    pub record: serde_json::Value,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+impl RsvpRecordView {
+    fn new(rsvp: &Rsvp, actor: Option<&Handle>) -> Self {
+        Self {
+            uri: rsvp.aturi.clone(),
+            cid: rsvp.cid.clone(),
+            actor: ActorView {
+                did: rsvp.did.clone(),
+                handle: actor.map(|handle| handle.handle.clone()),
+            },
+            record: rsvp.record.0.clone(),
+            status: rsvp.status.clone(),
+            indexed_at: rsvp.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GetRsvpsParams {
+    pub uri: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GetRsvpsOutput {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub rsvps: Vec<RsvpRecordView>,
+}
+
+/// `community.lexicon.calendar.getRsvps`: lists the RSVPs to an indexed
+/// event, most recently updated first, optionally only those with a status
+/// of `going`, `interested` or `notgoing`.
+pub async fn handle_xrpc_get_rsvps(
+    State(web_context): State<WebContext>,
+    Query(pagination): Query<CursorPagination>,
+    Query(params): Query<GetRsvpsParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let uri = params
+        .uri
+        .filter(|value| !value.trim().is_empty())
+        .ok_or(XrpcError::InvalidRequest("uri is required".to_string()))?;
+
+    parse_aturi(&uri).map_err(|err| XrpcError::InvalidRequest(err.to_string()))?;
+
+    if let Some(status) = &params.status {
+        if !RSVP_STATUSES.contains(&status.as_str()) {
+            return Err(XrpcError::InvalidRequest("status".to_string()));
+        }
+    }
+
+    let limit = pagination.clamped_limit(LIST_LIMIT_DEFAULT, 1, LIST_LIMIT_MAX);
+
+    let after = match &pagination.cursor {
+        Some(cursor) => {
+            Some(Cursor::decode(cursor).ok_or(XrpcError::InvalidRequest("cursor".to_string()))?)
+        }
+        None => None,
+    };
+
+    if !event_exists(&web_context.pool, &uri).await? {
+        return Err(XrpcError::RecordNotFound(uri));
+    }
+
+    let mut rsvps = rsvp_list_for_event(
+        &web_context.pool,
+        &uri,
+        params.status.as_deref(),
+        after.as_ref().map(Cursor::after),
+        limit,
+    )
+    .await?;
+
+    let cursor = Cursor::next_page(&mut rsvps, limit, |rsvp| {
+        rsvp.updated_at
+            .map(|updated_at| Cursor::new(updated_at, &rsvp.aturi))
+    });
+
+    let dids = rsvps.iter().map(|rsvp| rsvp.did.clone()).collect();
+    let actors = handles_by_did(&web_context.pool, dids).await?;
+
+    let rsvps = rsvps
+        .iter()
+        .map(|rsvp| RsvpRecordView::new(rsvp, actors.get(&rsvp.did)))
+        .collect::<Vec<_>>();
+
+    Ok(Json(GetRsvpsOutput { uri, cursor, rsvps }))
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use axum::{extract::State, response::IntoResponse, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::uri::parse_aturi,
+    http::{context::WebContext, errors::xrpc_error::XrpcError},
+    service_auth::service_did,
+    storage::announcement::announcement_list_upcoming,
+};
+
+pub const GET_FEED_SKELETON_NSID: &str = "app.bsky.feed.getFeedSkeleton";
+pub const DESCRIBE_FEED_GENERATOR_NSID: &str = "app.bsky.feed.describeFeedGenerator";
+
+const FEED_GENERATOR_COLLECTION: &str = "app.bsky.feed.generator";
+const FEED_LIMIT_DEFAULT: i64 = 50;
+const FEED_LIMIT_MAX: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct GetFeedSkeletonParams {
+    pub feed: Option<String>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SkeletonFeedPost {
+    pub post: String,
+}
+
+#[derive(Serialize)]
+pub struct GetFeedSkeletonOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub feed: Vec<SkeletonFeedPost>,
+}
+
+/// `app.bsky.feed.getFeedSkeleton`: returns announcement posts for upcoming
+/// events, soonest first, optionally filtered by the feed's configured
+/// locality.
+pub async fn handle_xrpc_get_feed_skeleton(
+    State(web_context): State<WebContext>,
+    Query(params): Query<GetFeedSkeletonParams>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let feed = params
+        .feed
+        .filter(|value| !value.trim().is_empty())
+        .ok_or(XrpcError::InvalidRequest("feed is required".to_string()))?;
+
+    let (repository, collection, rkey) =
+        parse_aturi(&feed).map_err(|err| XrpcError::InvalidRequest(err.to_string()))?;
+
+    if collection != FEED_GENERATOR_COLLECTION {
+        return Err(XrpcError::UnknownFeed(feed));
+    }
+
+    if let Some(publisher_did) = &web_context.config.feed_publisher_did {
+        if &repository != publisher_did {
+            return Err(XrpcError::UnknownFeed(feed));
+        }
+    }
+
+    let locality = match web_context.config.feed_generators.as_ref().get(&rkey) {
+        Some(locality) => locality.clone(),
+        None => return Err(XrpcError::UnknownFeed(feed)),
+    };
+
+    let limit = params
+        .limit
+        .unwrap_or(FEED_LIMIT_DEFAULT)
+        .clamp(1, FEED_LIMIT_MAX);
+
+    let page = match params.cursor {
+        Some(cursor) => cursor
+            .parse::<i64>()
+            .ok()
+            .filter(|page| *page >= 1)
+            .ok_or(XrpcError::InvalidRequest("cursor".to_string()))?,
+        None => 1,
+    };
+
+    let mut posts =
+        announcement_list_upcoming(&web_context.pool, locality.as_deref(), page, limit).await?;
+
+    let cursor = if posts.len() > limit as usize {
+        posts.truncate(limit as usize);
+        Some((page + 1).to_string())
+    } else {
+        None
+    };
+
+    let feed = posts
+        .into_iter()
+        .map(|post| SkeletonFeedPost { post })
+        .collect::<Vec<_>>();
+
+    Ok(Json(GetFeedSkeletonOutput { cursor, feed }))
+}
+
+#[derive(Serialize)]
+pub struct FeedGeneratorView {
+    pub uri: String,
+}
+
+#[derive(Serialize)]
+pub struct DescribeFeedGeneratorOutput {
+    pub did: String,
+    pub feeds: Vec<FeedGeneratorView>,
+}
+
+/// `app.bsky.feed.describeFeedGenerator`: lists the feeds served by this
+/// instance.
+pub async fn handle_xrpc_describe_feed_generator(
+    State(web_context): State<WebContext>,
+) -> Result<impl IntoResponse, XrpcError> {
+    let did = service_did(&web_context.config.external_base);
+    let publisher_did = web_context
+        .config
+        .feed_publisher_did
+        .clone()
+        .unwrap_or_else(|| did.clone());
+
+    let feeds = web_context
+        .config
+        .feed_generators
+        .as_ref()
+        .keys()
+        .map(|rkey| FeedGeneratorView {
+            uri: format!(
+                "at://{}/{}/{}",
+                publisher_did, FEED_GENERATOR_COLLECTION, rkey
+            ),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(DescribeFeedGeneratorOutput { did, feeds }))
+}
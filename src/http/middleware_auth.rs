@@ -6,27 +6,37 @@ use axum::{
 };
 use axum_extra::extract::PrivateCookieJar;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
 use p256::{
     ecdsa::{signature::Signer, Signature, SigningKey},
     SecretKey,
 };
 use serde::{Deserialize, Serialize};
-use tracing::{debug, instrument, trace};
+use std::borrow::Cow;
+use tracing::{debug, error, instrument, trace, warn};
 
 use crate::{
     config::Config,
     encoding::ToBase64,
     http::context::WebContext,
     http::errors::{AuthMiddlewareError, WebSessionError},
+    oauth::client_oauth_refresh,
+    refresh_tokens_errors::RefreshError,
+    storage::cache::{refresh_queue_claim, refresh_queue_schedule},
     storage::handle::model::Handle,
     storage::oauth::model::OAuthSession,
-    storage::oauth::web_session_lookup,
+    storage::oauth::{oauth_session_update, web_session_lookup},
+    task_refresh_tokens::rebind_pds,
 };
 
 use super::errors::middleware_errors::MiddlewareAuthError;
 
 pub const AUTH_COOKIE_NAME: &str = "session1";
 
+/// Sessions whose access token expires within this many seconds are refreshed
+/// while handling the request, in case the refresh queue has fallen behind.
+pub const SESSION_REFRESH_THRESHOLD_SECS: i64 = 60;
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct WebSession {
     pub did: String,
@@ -182,9 +192,15 @@ where
             )
             .await
             {
-                Ok(record) => {
+                Ok((handle, oauth_session)) => {
                     debug!(?web_session.session_group, "Session validated");
-                    return Ok(Self(Some(record.0), Some(record.1)));
+                    let (handle, oauth_session) =
+                        if session_needs_refresh(&oauth_session, Utc::now()) {
+                            refresh_expiring_session(&web_context, handle, oauth_session).await
+                        } else {
+                            (handle, oauth_session)
+                        };
+                    return Ok(Self(Some(handle), Some(oauth_session)));
                 }
                 Err(err) => {
                     debug!(?web_session.session_group, ?err, "Invalid session");
@@ -197,3 +213,124 @@ where
         Ok(Self(None, None))
     }
 }
+
+/// Returns true when the access token of a session expires within
+/// `SESSION_REFRESH_THRESHOLD_SECS` of `now`.
+pub fn session_needs_refresh(oauth_session: &OAuthSession, now: DateTime<Utc>) -> bool {
+    oauth_session.access_token_expires_at <= now + Duration::seconds(SESSION_REFRESH_THRESHOLD_SECS)
+}
+
+/// Refreshes a session whose access token is about to expire.
+///
+/// The session group is claimed from the refresh queue first, so a request and
+/// the refresh task never both spend the same refresh token. When it can't be
+/// claimed the session is used as is, and when the refresh fails it is put back
+/// in the queue for the refresh task to retry.
+async fn refresh_expiring_session(
+    web_context: &WebContext,
+    handle: Handle,
+    oauth_session: OAuthSession,
+) -> (Handle, OAuthSession) {
+    match refresh_queue_claim(&web_context.cache_pool, &oauth_session.session_group).await {
+        Ok(true) => {}
+        Ok(false) => {
+            trace!(?oauth_session.session_group, "Session refresh already taken");
+            return (handle, oauth_session);
+        }
+        Err(err) => {
+            warn!(?oauth_session.session_group, ?err, "Unable to claim session refresh");
+            return (handle, oauth_session);
+        }
+    }
+
+    match refresh_oauth_session(web_context, &handle, &oauth_session).await {
+        Ok(refreshed) => {
+            debug!(?oauth_session.session_group, "Session refreshed");
+            refreshed
+        }
+        Err(err) => {
+            warn!(?oauth_session.session_group, ?err, "Unable to refresh session");
+            if let Err(err) = refresh_queue_schedule(
+                &web_context.cache_pool,
+                &oauth_session.session_group,
+                Utc::now(),
+            )
+            .await
+            {
+                error!(?oauth_session.session_group, ?err, "Unable to requeue session refresh");
+            }
+            (handle, oauth_session)
+        }
+    }
+}
+
+// Refreshes the tokens of a session with the PDS the identity is on now, and
+// returns the handle with that PDS along with the refreshed session.
+async fn refresh_oauth_session(
+    web_context: &WebContext,
+    handle: &Handle,
+    oauth_session: &OAuthSession,
+) -> Result<(Handle, OAuthSession)> {
+    let handle = rebind_pds(
+        &web_context.http_client,
+        &web_context.pool,
+        &web_context.cache_pool,
+        &web_context.config.plc_hostname,
+        handle.clone(),
+        &oauth_session.issuer,
+    )
+    .await?;
+
+    let secret_signing_key = web_context
+        .config
+        .signing_keys
+        .as_ref()
+        .get(&oauth_session.secret_jwk_id)
+        .cloned()
+        .ok_or(RefreshError::SecretSigningKeyNotFound)?;
+
+    let dpop_secret_key = SecretKey::from_jwk(&oauth_session.dpop_jwk.jwk)
+        .map_err(RefreshError::DpopProofCreationFailed)?;
+
+    let token_response = client_oauth_refresh(
+        &web_context.http_client,
+        &web_context.cache_pool,
+        &web_context.config.external_base,
+        (&oauth_session.secret_jwk_id, secret_signing_key),
+        &oauth_session.refresh_token,
+        &handle,
+        &dpop_secret_key,
+    )
+    .await?;
+
+    let now = Utc::now();
+    let access_token_expires_at = now + Duration::seconds(i64::from(token_response.expires_in));
+
+    oauth_session_update(
+        &web_context.pool,
+        Cow::Borrowed(&oauth_session.session_group),
+        Cow::Borrowed(&token_response.access_token),
+        Cow::Borrowed(&token_response.refresh_token),
+        access_token_expires_at,
+    )
+    .await?;
+
+    // Refresh again at 80% of the new lifetime, the same as after signing in
+    let modified_expires_at = (f64::from(token_response.expires_in) * 0.8).round() as i64;
+    refresh_queue_schedule(
+        &web_context.cache_pool,
+        &oauth_session.session_group,
+        now + Duration::seconds(modified_expires_at),
+    )
+    .await?;
+
+    Ok((
+        handle,
+        OAuthSession {
+            access_token: token_response.access_token,
+            refresh_token: token_response.refresh_token,
+            access_token_expires_at,
+            ..oauth_session.clone()
+        },
+    ))
+}
@@ -0,0 +1,203 @@
+use std::hash::Hasher;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use axum_extra::extract::cookie::CookieJar;
+use axum_htmx::{HxBoosted, HxRequest};
+use chrono::Utc;
+use http::Method;
+use metrohash::MetroHash64;
+use minijinja::{Environment, Value};
+
+use crate::{
+    http::context::WebContext,
+    storage::banner::{banner_list_active, model::SiteBanner},
+};
+
+pub const COOKIE_DISMISSED_BANNERS: &str = "dismissed_banners";
+
+/// The most banner dismissals remembered. Older dismissals are forgotten
+/// first, and banners rarely run long enough for that to matter.
+pub const MAX_DISMISSED_BANNERS: usize = 20;
+
+/// Paths that never render a page, so banners aren't looked up for them.
+const SKIPPED_PATH_PREFIXES: [&str; 6] = [
+    "/static/",
+    "/xrpc/",
+    "/api/",
+    "/oauth/",
+    "/.well-known/",
+    "/banners/",
+];
+
+tokio::task_local! {
+    static SITE_BANNERS: Vec<SiteBanner>;
+}
+
+/// Returns the ids of the banners dismissed in the dismissal cookie, ignoring
+/// anything that isn't a banner id.
+pub fn dismissed_banners(jar: &CookieJar) -> Vec<String> {
+    jar.get(COOKIE_DISMISSED_BANNERS)
+        .map(|cookie| {
+            cookie
+                .value()
+                .split(',')
+                .filter(|value| ulid::Ulid::from_string(value).is_ok())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Adds a banner to the dismissed banners, keeping the most recent
+/// `MAX_DISMISSED_BANNERS` of them, and returns the new cookie value.
+pub fn dismiss_banner(mut dismissed: Vec<String>, id: &str) -> String {
+    dismissed.retain(|value| value != id);
+    dismissed.push(id.to_string());
+    let skip = dismissed.len().saturating_sub(MAX_DISMISSED_BANNERS);
+    dismissed[skip..].join(",")
+}
+
+/// Looks up the banners shown to the current request, so that the base
+/// template can render them through the `site_banners` function. Banners
+/// are only looked up for full page loads, and banners the visitor
+/// dismissed are left out. If banners cannot be loaded, pages render
+/// without them.
+pub async fn site_banners_context(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if request.method() != Method::GET
+        || (hx_request && !hx_boosted)
+        || SKIPPED_PATH_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    {
+        return next.run(request).await;
+    }
+
+    let banners = match banner_list_active(&web_context.pool, Utc::now()).await {
+        Ok(values) => values,
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to load site banners");
+            vec![]
+        }
+    };
+
+    let dismissed = dismissed_banners(&CookieJar::from_headers(request.headers()));
+    let banners = banners
+        .into_iter()
+        .filter(|banner| !dismissed.contains(&banner.id))
+        .collect::<Vec<_>>();
+
+    SITE_BANNERS.scope(banners, next.run(request)).await
+}
+
+/// Returns a hash of the banners shown to the current request. Pages that are
+/// cached for visitors who aren't signed in are kept per hash, because
+/// visitors see different banners depending on which they dismissed.
+pub fn site_banners_hash() -> String {
+    let mut hasher = MetroHash64::default();
+    let _ = SITE_BANNERS.try_with(|banners| {
+        for banner in banners {
+            hasher.write(banner.id.as_bytes());
+            hasher.write(b",");
+        }
+    });
+    format!("{:016x}", hasher.finish())
+}
+
+/// Adds the `site_banners` function to a template environment. It returns
+/// the banners of the current request, or nothing outside of one.
+pub fn register_site_banners(env: &mut Environment<'_>) {
+    env.add_function("site_banners", || {
+        SITE_BANNERS
+            .try_with(|banners| Value::from_serialize(banners))
+            .unwrap_or_else(|_| Value::from(Vec::<Value>::new()))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::extract::cookie::Cookie;
+
+    use super::*;
+
+    #[test]
+    fn test_dismissed_banners() {
+        let first = ulid::Ulid::new().to_string();
+        let second = ulid::Ulid::new().to_string();
+
+        let jar = CookieJar::new().add(Cookie::new(
+            COOKIE_DISMISSED_BANNERS,
+            format!("{},not-a-banner,{}", first, second),
+        ));
+        assert_eq!(dismissed_banners(&jar), vec![first.clone(), second.clone()]);
+        assert!(dismissed_banners(&CookieJar::new()).is_empty());
+
+        assert_eq!(
+            dismiss_banner(vec![first.clone(), second.clone()], &first),
+            format!("{},{}", second, first)
+        );
+    }
+
+    #[test]
+    fn test_dismiss_banner_limit() {
+        let dismissed = (0..MAX_DISMISSED_BANNERS)
+            .map(|_| ulid::Ulid::new().to_string())
+            .collect::<Vec<_>>();
+        let id = ulid::Ulid::new().to_string();
+
+        let value = dismiss_banner(dismissed.clone(), &id);
+        let values = value.split(',').collect::<Vec<_>>();
+        assert_eq!(values.len(), MAX_DISMISSED_BANNERS);
+        assert_eq!(values[0], dismissed[1]);
+        assert_eq!(values[MAX_DISMISSED_BANNERS - 1], id);
+    }
+
+    #[tokio::test]
+    async fn test_site_banners_function() {
+        let mut env = Environment::new();
+        register_site_banners(&mut env);
+        env.add_template(
+            "banners",
+            "{% for banner in site_banners() %}{{ banner.message }};{% endfor %}",
+        )
+        .unwrap();
+
+        let render = || env.get_template("banners").unwrap().render(()).unwrap();
+        assert_eq!(render(), "");
+
+        let now = Utc::now();
+        let banner = SiteBanner {
+            id: ulid::Ulid::new().to_string(),
+            message: "Maintenance tonight".to_string(),
+            level: "warning".to_string(),
+            starts_at: now,
+            ends_at: None,
+            created_by: "did:plc:d5c1ed6d01421a67b96f68fa".to_string(),
+            created_at: now,
+        };
+        let rendered = SITE_BANNERS
+            .scope(vec![banner.clone()], async { render() })
+            .await;
+        assert_eq!(rendered, "Maintenance tonight;");
+
+        // Requests that dismissed the banner get another cached page
+        let shown = SITE_BANNERS
+            .scope(vec![banner], async { site_banners_hash() })
+            .await;
+        let dismissed = SITE_BANNERS
+            .scope(vec![], async { site_banners_hash() })
+            .await;
+        assert_ne!(shown, dismissed);
+        assert_eq!(dismissed, site_banners_hash());
+    }
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use http::{
+    header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+    StatusCode,
+};
+use minijinja::context as template_context;
+
+use crate::{
+    config::Config,
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::{BodyLimitError, WebError},
+        middleware_i18n::Language,
+    },
+    select_template,
+};
+
+/// Paths that accept uploads and are allowed the larger upload body limit.
+/// Every other route is limited to the form body limit.
+const UPLOAD_PATH_PREFIXES: [&str; 6] = [
+    "/admin/events/import",
+    "/admin/rsvps/import",
+    "/event/header-image",
+    "/event/import",
+    "/import/calendar",
+    "/media",
+];
+
+/// Returns the largest request body, in bytes, accepted for a path.
+pub fn body_limit_for_path(config: &Config, path: &str) -> usize {
+    if UPLOAD_PATH_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        *config.http_upload_body_limit.as_ref()
+    } else {
+        *config.http_form_body_limit.as_ref()
+    }
+}
+
+/// Rejects requests whose body is larger than their route allows, before the
+/// body is read. Bodies without a Content-Length can't be checked up front
+/// and are rejected too, so that a chunked body can't be used to get around
+/// the limit.
+pub async fn body_limit_guard(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    request: Request,
+    next: Next,
+) -> Result<Response, WebError> {
+    let limit = body_limit_for_path(&web_context.config, request.uri().path());
+
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    let (error, status_code) = match content_length {
+        Some(length) if length > limit => (
+            BodyLimitError::TooLarge(limit),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        None if request.headers().contains_key(TRANSFER_ENCODING) => {
+            (BodyLimitError::LengthRequired, StatusCode::LENGTH_REQUIRED)
+        }
+        _ => return Ok(next.run(request).await),
+    };
+
+    let (mut parts, _) = request.into_parts();
+    let language = match Language::from_request_parts(&mut parts, &web_context).await {
+        Ok(Language(value)) => value,
+        Err(response) => return Ok(response),
+    };
+
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    // htmx only swaps successful responses, so fragments are returned with a
+    // 200 status to make sure the error is shown in place of the form.
+    let status_code = if hx_boosted || hx_request {
+        StatusCode::OK
+    } else {
+        status_code
+    };
+
+    contextual_error!(
+        web_context,
+        language,
+        error_template,
+        template_context! {
+            language => language.to_string(),
+        },
+        error,
+        status_code
+    )
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use http::{Method, StatusCode};
+use minijinja::context as template_context;
+
+use crate::{
+    contextual_error,
+    http::{
+        context::WebContext,
+        errors::{MaintenanceError, WebError},
+        middleware_i18n::Language,
+    },
+    select_template,
+    storage::cache::read_only_enabled,
+};
+
+/// Paths that keep accepting writes in read-only mode so that admins can
+/// still sign in and switch it back off. Dismissing a banner only sets a
+/// cookie, so banners announcing the maintenance can still be dismissed, and
+/// sessions can still be signed out, such as those of a lost device.
+const WRITABLE_PATH_PREFIXES: [&str; 6] = [
+    "/oauth/",
+    "/logout",
+    "/language",
+    "/admin",
+    "/banners/",
+    "/settings/sessions/",
+];
+
+/// Returns true when the instance was started read-only or an admin has
+/// switched read-only mode on. If the flag cannot be read, writes are allowed.
+pub async fn is_read_only(web_context: &WebContext) -> bool {
+    if web_context.config.read_only {
+        return true;
+    }
+
+    match read_only_enabled(&web_context.cache_pool).await {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to check read-only mode");
+            false
+        }
+    }
+}
+
+/// Rejects requests that could change data while the instance is read-only.
+/// Pages and XRPC queries are only ever served over `GET`, so any other
+/// method outside of the writable paths is answered with a maintenance
+/// notice instead of reaching its handler.
+pub async fn read_only_guard(
+    State(web_context): State<WebContext>,
+    HxBoosted(hx_boosted): HxBoosted,
+    HxRequest(hx_request): HxRequest,
+    request: Request,
+    next: Next,
+) -> Result<Response, WebError> {
+    let method = request.method();
+    if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+        return Ok(next.run(request).await);
+    }
+
+    let path = request.uri().path();
+    if WRITABLE_PATH_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        return Ok(next.run(request).await);
+    }
+
+    if !is_read_only(&web_context).await {
+        return Ok(next.run(request).await);
+    }
+
+    let (mut parts, _) = request.into_parts();
+    let language = match Language::from_request_parts(&mut parts, &web_context).await {
+        Ok(Language(value)) => value,
+        Err(response) => return Ok(response),
+    };
+
+    let error_template = select_template!(hx_boosted, hx_request, language);
+
+    // htmx only swaps successful responses, so fragments are returned with a
+    // 200 status to make sure the notice is shown in place of the form.
+    let status_code = if hx_boosted || hx_request {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    contextual_error!(
+        web_context,
+        language,
+        error_template,
+        template_context! {
+            language => language.to_string(),
+            message_type => "warning",
+            message_title => "Read-only mode",
+        },
+        MaintenanceError::ReadOnly,
+        status_code
+    )
+}
@@ -0,0 +1,140 @@
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use http::{header::AUTHORIZATION, Method, StatusCode};
+use serde_json::json;
+use tracing::{debug, trace};
+
+use crate::{
+    http::{
+        context::WebContext,
+        handle_api_app_password::{DELETE_APP_PASSWORD_NSID, SET_APP_PASSWORD_NSID},
+        handle_api_events::CREATE_EVENT_NSID,
+        handle_api_rsvps::{CREATE_RSVP_NSID, JOIN_WAITLIST_NSID},
+    },
+    jose::jwt::Claims,
+    service_auth::{decode_unverified, resolve_issuer_key, service_did, verify_service_token},
+    service_auth_errors::ServiceAuthError,
+};
+
+/// An inter-service caller authenticated with an ATProto service auth token.
+///
+/// The token must be addressed to this instance's DID and bound to the method
+/// being invoked, which for XRPC requests is named by the path and for the
+/// JSON API is given by [`request_method`].
+#[derive(Clone, Debug)]
+pub struct ServiceAuth {
+    /// The DID of the calling account or service, without any fragment.
+    pub issuer: String,
+    pub claims: Claims,
+}
+
+impl<S> FromRequestParts<S> for ServiceAuth
+where
+    S: Send + Sync,
+    WebContext: FromRef<S>,
+{
+    type Rejection = ServiceAuthError;
+
+    async fn from_request_parts(parts: &mut Parts, context: &S) -> Result<Self, Self::Rejection> {
+        trace!("Extracting ServiceAuth from request");
+        let web_context = WebContext::from_ref(context);
+
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.trim().to_string())
+            .ok_or(ServiceAuthError::MissingBearerToken)?;
+
+        let (_, unverified_claims) = decode_unverified(&token)?;
+        let issuer = unverified_claims
+            .jose
+            .issuer
+            .ok_or(ServiceAuthError::MissingIssuer)?;
+
+        let method = request_method(&parts.method, parts.uri.path())
+            .ok_or_else(|| ServiceAuthError::MethodMismatch(parts.uri.path().to_string()))?;
+
+        let public_key = resolve_issuer_key(
+            &web_context.http_client,
+            &web_context.config.plc_hostname,
+            &issuer,
+        )
+        .await?;
+
+        let claims = verify_service_token(
+            &token,
+            &public_key,
+            &service_did(&web_context.config.external_base),
+            Some(method),
+        )?;
+
+        let issuer = issuer
+            .split_once('#')
+            .map_or(issuer.as_str(), |(did, _)| did)
+            .to_string();
+
+        debug!(issuer, method, "Service auth validated");
+        Ok(Self { issuer, claims })
+    }
+}
+
+/// Returns the NSID that the `lxm` claim of a token must match for a request.
+/// Routes outside of `/xrpc/` that accept service auth each have one, and
+/// tokens are refused on any other route.
+pub fn request_method<'a>(method: &Method, path: &'a str) -> Option<&'a str> {
+    if let Some(nsid) = path.strip_prefix("/xrpc/") {
+        return Some(nsid);
+    }
+
+    match (method, path) {
+        (&Method::POST, "/api/v1/events") => Some(CREATE_EVENT_NSID),
+        (&Method::POST, "/api/v1/rsvps") => Some(CREATE_RSVP_NSID),
+        (&Method::POST, "/api/v1/waitlist") => Some(JOIN_WAITLIST_NSID),
+        (&Method::PUT, "/api/v1/app-password") => Some(SET_APP_PASSWORD_NSID),
+        (&Method::DELETE, "/api/v1/app-password") => Some(DELETE_APP_PASSWORD_NSID),
+        _ => None,
+    }
+}
+
+impl IntoResponse for ServiceAuthError {
+    fn into_response(self) -> Response {
+        debug!(error = ?self, "service auth rejected");
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "error": "AuthenticationRequired",
+                "message": self.to_string(),
+            })),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_method() {
+        assert_eq!(
+            request_method(&Method::GET, "/xrpc/app.bsky.feed.getFeedSkeleton"),
+            Some("app.bsky.feed.getFeedSkeleton")
+        );
+        assert_eq!(
+            request_method(&Method::POST, "/api/v1/rsvps"),
+            Some(CREATE_RSVP_NSID)
+        );
+        assert_eq!(
+            request_method(&Method::DELETE, "/api/v1/app-password"),
+            Some(DELETE_APP_PASSWORD_NSID)
+        );
+        assert_eq!(request_method(&Method::GET, "/api/v1/rsvps"), None);
+        assert_eq!(request_method(&Method::POST, "/api/v1/other"), None);
+    }
+}
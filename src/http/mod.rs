@@ -3,6 +3,9 @@ pub mod context;
 pub mod errors;
 pub mod event_form;
 pub mod event_view;
+pub mod form_errors;
+pub mod handle_admin_audit_log;
+pub mod handle_admin_banners;
 pub mod handle_admin_denylist;
 pub mod handle_admin_event;
 pub mod handle_admin_events;
@@ -10,31 +13,74 @@ pub mod handle_admin_handles;
 pub mod handle_admin_import_event;
 pub mod handle_admin_import_rsvp;
 pub mod handle_admin_index;
+pub mod handle_admin_integrity;
+pub mod handle_admin_read_only;
+pub mod handle_admin_reports;
 pub mod handle_admin_rsvp;
 pub mod handle_admin_rsvps;
+pub mod handle_api_app_password;
+pub mod handle_api_events;
+pub mod handle_api_rsvps;
+pub mod handle_bookmarks;
+pub mod handle_calendar_grid;
+pub mod handle_calendars;
+pub mod handle_checkin;
 pub mod handle_create_event;
 pub mod handle_create_rsvp;
+pub mod handle_dashboard;
+pub mod handle_delete_event;
+pub mod handle_delete_rsvp;
+pub mod handle_dismiss_banner;
 pub mod handle_edit_event;
+pub mod handle_event_analytics;
+pub mod handle_event_card;
+pub mod handle_event_discussion;
+pub mod handle_event_updates;
+pub mod handle_explore;
+pub mod handle_follows;
 pub mod handle_import;
+pub mod handle_import_calendar;
 pub mod handle_index;
+pub mod handle_media;
 pub mod handle_migrate_event;
 pub mod handle_migrate_rsvp;
+pub mod handle_notifications;
 pub mod handle_oauth_callback;
 pub mod handle_oauth_jwks;
 pub mod handle_oauth_login;
 pub mod handle_oauth_logout;
 pub mod handle_oauth_metadata;
+pub mod handle_outbound_link;
 pub mod handle_policy;
 pub mod handle_profile;
+pub mod handle_profile_archive;
+pub mod handle_report_event;
+pub mod handle_rsvp_capacity;
+pub mod handle_rsvp_deadline;
+pub mod handle_rsvp_history;
+pub mod handle_rsvp_waitlist;
+pub mod handle_search;
 pub mod handle_set_language;
 pub mod handle_settings;
+pub mod handle_settings_export;
+pub mod handle_settings_sessions;
+pub mod handle_view_calendar;
 pub mod handle_view_event;
 pub mod handle_view_feed;
 pub mod handle_view_rsvp;
-pub mod location_edit_status;
+pub mod handle_view_topic;
+pub mod handle_webhooks;
+pub mod handle_well_known_did;
+pub mod handle_xrpc_events;
+pub mod handle_xrpc_feed;
 pub mod macros;
 pub mod middleware_auth;
+pub mod middleware_banners;
+pub mod middleware_body_limit;
 pub mod middleware_i18n;
+pub mod middleware_read_only;
+pub mod middleware_service_auth;
+pub mod page_cache;
 pub mod pagination;
 pub mod rsvp_form;
 pub mod server;
@@ -0,0 +1,167 @@
+use std::hash::Hasher;
+
+use axum::response::{Html, IntoResponse, Response};
+use axum_template::TemplateEngine;
+use http::{
+    header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH, VARY},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use metrohash::MetroHash64;
+use serde::Serialize;
+
+use crate::{
+    http::{context::WebContext, errors::WebError, middleware_banners::site_banners_hash},
+    storage::cache::{anonymous_page_get, anonymous_page_set},
+};
+
+/// How long browsers, shared caches and the page cache can reuse a page
+/// rendered for someone who isn't signed in before it is rendered again.
+pub(crate) const ANONYMOUS_PAGE_MAX_AGE_SECS: u64 = 60;
+
+/// Returns the cached page rendered with `template` for `uri`, if there is
+/// one, without rendering it again. Pages are cached separately for each set
+/// of site banners, since visitors can dismiss them. Cache failures are
+/// logged and treated as a miss.
+pub(crate) async fn cached_anonymous_page(
+    web_context: &WebContext,
+    request_headers: &HeaderMap,
+    template: &str,
+    uri: &str,
+) -> Option<Response> {
+    let banners = site_banners_hash();
+    match anonymous_page_get(&web_context.cache_pool, template, &banners, uri).await {
+        Ok(body) => body.map(|body| page_response(request_headers, body)),
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to get cached page");
+            None
+        }
+    }
+}
+
+/// Renders a page for someone who isn't signed in and caches it, so that the
+/// next requests for `uri` within `ANONYMOUS_PAGE_MAX_AGE_SECS` are served by
+/// [`cached_anonymous_page`] instead.
+pub(crate) async fn render_anonymous_page<S: Serialize>(
+    web_context: &WebContext,
+    request_headers: &HeaderMap,
+    template: &str,
+    uri: &str,
+    context: S,
+) -> Result<Response, WebError> {
+    let body = web_context
+        .engine
+        .render(template, context)
+        .map_err(anyhow::Error::from)?;
+
+    if let Err(err) = anonymous_page_set(
+        &web_context.cache_pool,
+        template,
+        &site_banners_hash(),
+        uri,
+        &body,
+        ANONYMOUS_PAGE_MAX_AGE_SECS,
+    )
+    .await
+    {
+        tracing::warn!(error = ?err, "unable to cache page");
+    }
+
+    Ok(page_response(request_headers, body))
+}
+
+// The ETag is a hash of the page, so it changes as soon as the cached page
+// does. A request whose `If-None-Match` still matches gets an empty 304
+// response.
+fn page_response(request_headers: &HeaderMap, body: String) -> Response {
+    let etag = page_etag(&body);
+
+    let mut response = if etag_matches(request_headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Html(body).into_response()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(ETAG, value);
+    }
+    if let Ok(value) =
+        HeaderValue::from_str(&format!("public, max-age={}", ANONYMOUS_PAGE_MAX_AGE_SECS))
+    {
+        headers.insert(CACHE_CONTROL, value);
+    }
+    // The language of the page comes from the language cookie or the
+    // browser's preferred languages
+    headers.append(VARY, HeaderValue::from_static("Cookie, Accept-Language"));
+
+    response
+}
+
+// A weak ETag, since the same page can be sent compressed or not
+fn page_etag(body: &str) -> String {
+    let mut hasher = MetroHash64::default();
+    hasher.write(body.as_bytes());
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+fn etag_matches(request_headers: &HeaderMap, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    request_headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = page_etag("<html></html>");
+        assert!(etag.starts_with("W/\""));
+        assert_ne!(etag, page_etag("<html><body></body></html>"));
+
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, &etag));
+
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_static("\"0000000000000000\""),
+        );
+        assert!(!etag_matches(&headers, &etag));
+
+        // Caches can strip the weak prefix, and send several ETags
+        let strong = etag.trim_start_matches("W/").to_string();
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"0000000000000000\", {}", strong)).unwrap(),
+        );
+        assert!(etag_matches(&headers, &etag));
+
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(etag_matches(&headers, &etag));
+    }
+
+    #[test]
+    fn test_page_response() {
+        let body = "<html></html>".to_string();
+
+        let response = page_response(&HeaderMap::new(), body.clone());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CACHE_CONTROL).unwrap(),
+            "public, max-age=60"
+        );
+        let etag = response.headers().get(ETAG).unwrap().clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, etag.clone());
+        let response = page_response(&headers, body);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(ETAG).unwrap(), &etag);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::http::utils::stringify;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 pub const PAGE_DEFAULT: i64 = 1;
@@ -21,6 +23,81 @@ pub struct Pagination {
     pub page_size: Option<i64>,
 }
 
+/// Query parameters of lists that are paged with a cursor instead of a page
+/// number.
+#[derive(Deserialize, Default)]
+pub struct CursorPagination {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl CursorPagination {
+    pub fn clamped_limit(&self, default: i64, min: i64, max: i64) -> i64 {
+        self.limit.unwrap_or(default).clamp(min, max)
+    }
+}
+
+/// A position in a list ordered by update time and then a unique key, such as
+/// an AT-URI or DID, newest first. It is given to clients as an opaque token.
+///
+/// Lists paged with a cursor continue after the last row of the previous
+/// page instead of skipping rows, so later pages cost as much as the first.
+#[derive(Debug, PartialEq)]
+pub struct Cursor {
+    pub updated_at: DateTime<Utc>,
+    pub key: String,
+}
+
+impl Cursor {
+    pub fn new(updated_at: DateTime<Utc>, key: &str) -> Self {
+        Self {
+            updated_at,
+            key: key.to_string(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(format!(
+            "{}|{}",
+            self.updated_at.timestamp_micros(),
+            self.key
+        ))
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let decoded = general_purpose::URL_SAFE_NO_PAD.decode(value).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (micros, key) = decoded.split_once('|')?;
+        if key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            updated_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            key: key.to_string(),
+        })
+    }
+
+    /// The position to continue a list after, as given to storage list
+    /// functions.
+    pub fn after(&self) -> (DateTime<Utc>, &str) {
+        (self.updated_at, self.key.as_str())
+    }
+
+    /// Trims the extra row that storage list functions fetch past `limit`,
+    /// and returns the encoded cursor of the next page when there was one.
+    pub fn next_page<T>(
+        rows: &mut Vec<T>,
+        limit: i64,
+        cursor: impl Fn(&T) -> Option<Cursor>,
+    ) -> Option<String> {
+        if rows.len() <= limit as usize {
+            return None;
+        }
+        rows.truncate(limit as usize);
+        rows.last().and_then(cursor).map(|cursor| cursor.encode())
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct PaginationView {
     pub previous: Option<i64>,
@@ -78,3 +155,39 @@ impl PaginationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let cursor = Cursor::new(
+            DateTime::from_timestamp_micros(1_747_000_000_123_456).unwrap(),
+            "at://did:plc:abc/community.lexicon.calendar.event/3lbsxygenau2c",
+        );
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        let no_key = general_purpose::URL_SAFE_NO_PAD.encode("1747000000123456|");
+        assert_eq!(Cursor::decode(&no_key), None);
+    }
+
+    #[test]
+    fn test_cursor_next_page() {
+        let updated_at = DateTime::from_timestamp_micros(1_747_000_000_000_000).unwrap();
+        let cursor = |key: &&str| Some(Cursor::new(updated_at, key));
+
+        let mut rows = vec!["a", "b", "c"];
+        let next = Cursor::next_page(&mut rows, 2, cursor);
+        assert_eq!(rows, vec!["a", "b"]);
+        assert_eq!(
+            next.as_deref().and_then(Cursor::decode),
+            Some(Cursor::new(updated_at, "b"))
+        );
+
+        let mut rows = vec!["a", "b"];
+        assert_eq!(Cursor::next_page(&mut rows, 2, cursor), None);
+        assert_eq!(rows, vec!["a", "b"]);
+    }
+}
@@ -1,14 +1,16 @@
 use std::time::Duration;
 
 use axum::{
+    extract::DefaultBodyLimit,
     http::HeaderValue,
-    routing::{get, post},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
     Router,
 };
 use axum_htmx::AutoVaryLayer;
 use http::{
     header::{ACCEPT, ACCEPT_LANGUAGE},
-    Method,
+    Method, StatusCode,
 };
 use tower_http::trace::TraceLayer;
 use tower_http::{classify::ServerErrorsFailureClass, timeout::TimeoutLayer};
@@ -17,43 +19,112 @@ use tracing::Span;
 
 use crate::http::{
     context::WebContext,
+    handle_admin_audit_log::handle_admin_audit_log,
+    handle_admin_banners::{
+        handle_admin_banners, handle_admin_banners_add, handle_admin_banners_remove,
+    },
     handle_admin_denylist::{
         handle_admin_denylist, handle_admin_denylist_add, handle_admin_denylist_remove,
     },
     handle_admin_event::handle_admin_event,
-    handle_admin_events::handle_admin_events,
+    handle_admin_events::{handle_admin_events, handle_admin_events_delete},
     handle_admin_handles::{handle_admin_handles, handle_admin_nuke_identity},
     handle_admin_import_event::handle_admin_import_event,
     handle_admin_import_rsvp::handle_admin_import_rsvp,
     handle_admin_index::handle_admin_index,
+    handle_admin_integrity::handle_admin_integrity,
+    handle_admin_read_only::handle_admin_read_only,
+    handle_admin_reports::{handle_admin_reports, handle_admin_reports_resolve},
     handle_admin_rsvp::handle_admin_rsvp,
     handle_admin_rsvps::handle_admin_rsvps,
+    handle_api_app_password::{handle_api_app_password_delete, handle_api_app_password_set},
+    handle_api_events::{handle_api_create_event, handle_api_get_event, handle_api_list_events},
+    handle_api_rsvps::{handle_api_create_rsvp, handle_api_join_waitlist},
+    handle_bookmarks::{handle_bookmark_update, handle_bookmarks},
+    handle_calendar_grid::{handle_calendar, handle_calendar_day},
+    handle_calendars::{
+        handle_calendar_create, handle_calendar_delete, handle_calendar_edit,
+        handle_calendar_update, handle_calendars,
+    },
+    handle_checkin::{handle_checkin, handle_checkin_counts, handle_checkin_update},
     handle_create_event::{
-        handle_create_event, handle_link_at_builder, handle_location_at_builder,
-        handle_location_datalist, handle_starts_at_builder,
+        handle_create_event, handle_create_event_duplicate, handle_create_event_import,
+        handle_event_draft_delete, handle_event_draft_save, handle_link_at_builder,
+        handle_location_at_builder, handle_location_datalist, handle_starts_at_builder,
     },
     handle_create_rsvp::handle_create_rsvp,
+    handle_dashboard::handle_dashboard,
+    handle_delete_event::handle_delete_event,
+    handle_delete_rsvp::handle_delete_rsvp,
+    handle_dismiss_banner::handle_dismiss_banner,
     handle_edit_event::handle_edit_event,
-    handle_import::{handle_import, handle_import_submit},
+    handle_event_analytics::handle_event_analytics,
+    handle_event_card::handle_event_card,
+    handle_event_discussion::handle_event_discussion,
+    handle_event_updates::handle_event_update_create,
+    handle_explore::{handle_explore, handle_explore_events},
+    handle_follows::handle_follow_update,
+    handle_import::{
+        handle_import, handle_import_events, handle_import_repo, handle_import_submit,
+    },
+    handle_import_calendar::{
+        handle_import_calendar_confirm, handle_import_calendar_discard,
+        handle_import_calendar_preview, handle_import_calendar_upload,
+    },
     handle_index::handle_index,
+    handle_media::{
+        handle_event_header_image, handle_media, handle_media_delete, handle_media_upload,
+    },
     handle_migrate_event::handle_migrate_event,
     handle_migrate_rsvp::handle_migrate_rsvp,
+    handle_notifications::handle_notifications,
     handle_oauth_callback::handle_oauth_callback,
     handle_oauth_jwks::handle_oauth_jwks,
     handle_oauth_login::handle_oauth_login,
     handle_oauth_logout::handle_logout,
     handle_oauth_metadata::handle_oauth_metadata,
+    handle_outbound_link::handle_outbound_link,
     handle_policy::{
         handle_acknowledgement, handle_cookie_policy, handle_privacy_policy,
         handle_terms_of_service,
     },
     handle_profile::handle_profile_view,
+    handle_profile_archive::{handle_profile_archive, handle_profile_archive_month},
+    handle_report_event::handle_report_event,
+    handle_rsvp_capacity::handle_rsvp_capacity,
+    handle_rsvp_deadline::handle_rsvp_deadline,
+    handle_rsvp_history::handle_rsvp_history,
+    handle_rsvp_waitlist::handle_rsvp_waitlist,
+    handle_search::handle_search,
     handle_set_language::handle_set_language,
     handle_settings::{handle_language_update, handle_settings, handle_timezone_update},
+    handle_settings_export::handle_settings_export,
+    handle_settings_sessions::{
+        handle_settings_sessions, handle_settings_sessions_revoke,
+        handle_settings_sessions_revoke_all,
+    },
+    handle_view_calendar::handle_view_calendar,
     handle_view_event::handle_view_event,
     handle_view_feed::handle_view_feed,
     handle_view_rsvp::handle_view_rsvp,
+    handle_view_topic::handle_view_topic,
+    handle_webhooks::{handle_webhook_create, handle_webhook_delete, handle_webhooks},
+    handle_well_known_did::handle_well_known_did,
+    handle_xrpc_events::{
+        handle_xrpc_get_event, handle_xrpc_get_rsvp_counts, handle_xrpc_get_rsvps,
+        handle_xrpc_list_events, handle_xrpc_list_events_by_actor, CALENDAR_GET_EVENT_NSID,
+        CALENDAR_GET_RSVPS_NSID, CALENDAR_LIST_EVENTS_NSID, GET_EVENT_NSID, GET_RSVP_COUNTS_NSID,
+        LIST_EVENTS_BY_ACTOR_NSID,
+    },
+    handle_xrpc_feed::{
+        handle_xrpc_describe_feed_generator, handle_xrpc_get_feed_skeleton,
+        DESCRIBE_FEED_GENERATOR_NSID, GET_FEED_SKELETON_NSID,
+    },
+    middleware_banners::site_banners_context,
+    middleware_body_limit::body_limit_guard,
+    middleware_read_only::read_only_guard,
 };
+use crate::telemetry::request_span;
 
 pub fn build_router(web_context: WebContext) -> Router {
     let serve_dir = ServeDir::new(web_context.config.http_static_path.clone());
@@ -73,14 +144,66 @@ pub fn build_router(web_context: WebContext) -> Router {
         .route("/admin/denylist", get(handle_admin_denylist))
         .route("/admin/denylist/add", post(handle_admin_denylist_add))
         .route("/admin/denylist/remove", post(handle_admin_denylist_remove))
+        .route("/admin/banners", get(handle_admin_banners))
+        .route("/admin/banners/add", post(handle_admin_banners_add))
+        .route("/admin/banners/remove", post(handle_admin_banners_remove))
+        .route("/admin/reports", get(handle_admin_reports))
+        .route("/admin/reports/resolve", post(handle_admin_reports_resolve))
         .route("/admin/events", get(handle_admin_events))
         .route("/admin/events/import", post(handle_admin_import_event))
+        .route("/admin/events/delete", post(handle_admin_events_delete))
         .route("/admin/event", get(handle_admin_event))
         .route("/admin/rsvps", get(handle_admin_rsvps))
         .route("/admin/rsvp", get(handle_admin_rsvp))
         .route("/admin/rsvps/import", post(handle_admin_import_rsvp))
+        .route("/admin/integrity", get(handle_admin_integrity))
+        .route("/admin/audit-log", get(handle_admin_audit_log))
+        .route("/admin/read-only", post(handle_admin_read_only))
+        .route(
+            &format!("/xrpc/{}", GET_EVENT_NSID),
+            get(handle_xrpc_get_event),
+        )
+        .route(
+            &format!("/xrpc/{}", LIST_EVENTS_BY_ACTOR_NSID),
+            get(handle_xrpc_list_events_by_actor),
+        )
+        .route(
+            &format!("/xrpc/{}", GET_RSVP_COUNTS_NSID),
+            get(handle_xrpc_get_rsvp_counts),
+        )
+        .route(
+            &format!("/xrpc/{}", CALENDAR_GET_EVENT_NSID),
+            get(handle_xrpc_get_event),
+        )
+        .route(
+            &format!("/xrpc/{}", CALENDAR_LIST_EVENTS_NSID),
+            get(handle_xrpc_list_events),
+        )
+        .route(
+            &format!("/xrpc/{}", CALENDAR_GET_RSVPS_NSID),
+            get(handle_xrpc_get_rsvps),
+        )
+        .route(
+            &format!("/xrpc/{}", GET_FEED_SKELETON_NSID),
+            get(handle_xrpc_get_feed_skeleton),
+        )
+        .route(
+            &format!("/xrpc/{}", DESCRIBE_FEED_GENERATOR_NSID),
+            get(handle_xrpc_describe_feed_generator),
+        )
+        .route("/api/v1/events", get(handle_api_list_events))
+        .route("/api/v1/events", post(handle_api_create_event))
+        .route("/api/v1/events/{*aturi}", get(handle_api_get_event))
+        .route("/api/v1/rsvps", post(handle_api_create_rsvp))
+        .route("/api/v1/waitlist", post(handle_api_join_waitlist))
+        .route("/api/v1/app-password", put(handle_api_app_password_set))
+        .route(
+            "/api/v1/app-password",
+            delete(handle_api_app_password_delete),
+        )
         .route("/oauth/client-metadata.json", get(handle_oauth_metadata))
         .route("/.well-known/jwks.json", get(handle_oauth_jwks))
+        .route("/.well-known/did.json", get(handle_well_known_did))
         .route("/oauth/login", get(handle_oauth_login))
         .route("/oauth/login", post(handle_oauth_login))
         .route("/oauth/callback", get(handle_oauth_callback))
@@ -89,13 +212,80 @@ pub fn build_router(web_context: WebContext) -> Router {
         .route("/settings", get(handle_settings))
         .route("/settings/timezone", post(handle_timezone_update))
         .route("/settings/language", post(handle_language_update))
+        .route("/settings/export", get(handle_settings_export))
+        .route("/settings/sessions", get(handle_settings_sessions))
+        .route(
+            "/settings/sessions/revoke",
+            post(handle_settings_sessions_revoke),
+        )
+        .route(
+            "/settings/sessions/revoke-all",
+            post(handle_settings_sessions_revoke_all),
+        )
         .route("/import", get(handle_import))
         .route("/import", post(handle_import_submit))
+        .route("/import/events", get(handle_import_events))
+        .route("/import/repo", post(handle_import_repo))
+        .route("/import/calendar", post(handle_import_calendar_upload))
+        .route(
+            "/import/calendar/{plan_id}",
+            get(handle_import_calendar_preview),
+        )
+        .route(
+            "/import/calendar/{plan_id}",
+            post(handle_import_calendar_confirm),
+        )
+        .route(
+            "/import/calendar/{plan_id}/discard",
+            post(handle_import_calendar_discard),
+        )
         .route("/event", get(handle_create_event))
         .route("/event", post(handle_create_event))
+        .route("/event/import", post(handle_create_event_import))
+        .route("/event/draft", post(handle_event_draft_save))
+        .route("/event/draft/delete", post(handle_event_draft_delete))
         .route("/rsvp", get(handle_create_rsvp))
         .route("/rsvp", post(handle_create_rsvp))
+        .route("/rsvp/deadline", post(handle_rsvp_deadline))
+        .route("/rsvp/capacity", post(handle_rsvp_capacity))
+        .route("/rsvp/waitlist", post(handle_rsvp_waitlist))
+        .route("/event/updates", post(handle_event_update_create))
+        .route("/rsvp/delete", post(handle_delete_rsvp))
         .route("/rsvps", get(handle_view_rsvp))
+        .route("/dashboard", get(handle_dashboard))
+        .route("/notifications", get(handle_notifications))
+        .route("/bookmarks", get(handle_bookmarks))
+        .route("/bookmarks", post(handle_bookmark_update))
+        .route("/follows", post(handle_follow_update))
+        .route("/report", post(handle_report_event))
+        .route("/media", get(handle_media))
+        .route("/media", post(handle_media_upload))
+        .route("/media/{media_rkey}/delete", post(handle_media_delete))
+        .route("/calendars", get(handle_calendars))
+        .route("/calendars", post(handle_calendar_create))
+        .route("/calendars/{calendar_rkey}/edit", get(handle_calendar_edit))
+        .route(
+            "/calendars/{calendar_rkey}/edit",
+            post(handle_calendar_update),
+        )
+        .route(
+            "/calendars/{calendar_rkey}/delete",
+            post(handle_calendar_delete),
+        )
+        .route("/webhooks", get(handle_webhooks))
+        .route("/webhooks", post(handle_webhook_create))
+        .route("/webhooks/{webhook_id}/delete", post(handle_webhook_delete))
+        .route("/banners/{banner_id}/dismiss", post(handle_dismiss_banner))
+        .route("/out", get(handle_outbound_link))
+        .route("/search", get(handle_search))
+        .route("/explore", get(handle_explore_events))
+        .route("/explore/{country}", get(handle_explore))
+        .route("/explore/{country}/{region}", get(handle_explore))
+        .route(
+            "/explore/{country}/{region}/{locality}",
+            get(handle_explore),
+        )
+        .route("/topics/{tag}", get(handle_view_topic))
         .route("/event/starts", get(handle_starts_at_builder))
         .route("/event/starts", post(handle_starts_at_builder))
         .route("/event/location", get(handle_location_at_builder))
@@ -103,8 +293,46 @@ pub fn build_router(web_context: WebContext) -> Router {
         .route("/event/location/datalist", get(handle_location_datalist))
         .route("/event/links", get(handle_link_at_builder))
         .route("/event/links", post(handle_link_at_builder))
+        .route("/event/header-image", post(handle_event_header_image))
+        .route(
+            "/{handle_slug}/{event_rkey}/card.png",
+            get(handle_event_card),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/duplicate",
+            get(handle_create_event_duplicate),
+        )
         .route("/{handle_slug}/{event_rkey}/edit", get(handle_edit_event))
         .route("/{handle_slug}/{event_rkey}/edit", post(handle_edit_event))
+        .route(
+            "/{handle_slug}/{event_rkey}/delete",
+            get(handle_delete_event),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/delete",
+            post(handle_delete_event),
+        )
+        .route("/{handle_slug}/{event_rkey}/checkin", get(handle_checkin))
+        .route(
+            "/{handle_slug}/{event_rkey}/checkin",
+            post(handle_checkin_update),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/checkin/counts",
+            get(handle_checkin_counts),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/discussion",
+            get(handle_event_discussion),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/analytics",
+            get(handle_event_analytics),
+        )
+        .route(
+            "/{handle_slug}/{event_rkey}/rsvp-history",
+            get(handle_rsvp_history),
+        )
         .route(
             "/{handle_slug}/{event_rkey}/migrate",
             get(handle_migrate_event),
@@ -113,19 +341,47 @@ pub fn build_router(web_context: WebContext) -> Router {
             "/{handle_slug}/{event_rkey}/migrate-rsvp",
             get(handle_migrate_rsvp),
         )
+        .route("/calendar", get(handle_calendar))
+        .route(
+            "/calendar/{handle_slug}/{calendar_rkey}",
+            get(handle_view_calendar),
+        )
+        .route("/calendar/{year}/{month}/{day}", get(handle_calendar_day))
         .route("/feed/{handle_slug}/{feed_rkey}", get(handle_view_feed))
         .route("/rsvp/{handle_slug}/{rsvp_rkey}", get(handle_view_rsvp))
+        .route("/{handle_slug}/past", get(handle_profile_archive))
+        .route(
+            "/{handle_slug}/past/{year}/{month}",
+            get(handle_profile_archive_month),
+        )
         .route("/{handle_slug}/{event_rkey}", get(handle_view_event))
         .route("/{handle_slug}", get(handle_profile_view))
         .nest_service("/static", serve_dir.clone())
         .fallback_service(serve_dir)
+        .layer(from_fn_with_state(
+            web_context.clone(),
+            site_banners_context,
+        ))
+        .layer(from_fn_with_state(web_context.clone(), read_only_guard))
+        .layer(from_fn_with_state(web_context.clone(), body_limit_guard))
+        .layer(DefaultBodyLimit::max(
+            *web_context.config.http_upload_body_limit.as_ref(),
+        ))
         .layer((
-            TraceLayer::new_for_http().on_failure(
-                |err: ServerErrorsFailureClass, _latency: Duration, _span: &Span| {
-                    tracing::error!(error = ?err, "Unhandled error: {err}");
-                },
-            ),
-            TimeoutLayer::new(Duration::from_secs(10)),
+            TraceLayer::new_for_http()
+                .make_span_with(request_span)
+                .on_response(
+                    |response: &http::Response<_>, latency: Duration, span: &Span| {
+                        span.record("status", response.status().as_u16());
+                        tracing::debug!(latency = ?latency, "finished processing request");
+                    },
+                )
+                .on_failure(
+                    |err: ServerErrorsFailureClass, _latency: Duration, _span: &Span| {
+                        tracing::error!(error = ?err, "Unhandled error: {err}");
+                    },
+                ),
+            TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, Duration::from_secs(10)),
         ))
         .layer(
             CorsLayer::new()
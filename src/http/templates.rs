@@ -24,6 +24,10 @@ pub mod reload_env {
     use minijinja::{path_loader, Environment};
     use minijinja_autoreload::AutoReloader;
 
+    use crate::http::{
+        form_errors::register_form_errors, middleware_banners::register_site_banners,
+    };
+
     pub fn build_env(http_external: &str, version: &str) -> AutoReloader {
         let http_external = http_external.to_string();
         let version = version.to_string();
@@ -34,6 +38,9 @@ pub mod reload_env {
             env.set_lstrip_blocks(true);
             env.add_global("base", format!("https://{}", http_external));
             env.add_global("version", version.clone());
+            register_form_errors(&mut env);
+            register_site_banners(&mut env);
+            register_site_banners(&mut env);
             env.set_loader(path_loader(&template_path));
             notifier.set_fast_reload(true);
             notifier.watch_path(&template_path, true);
@@ -46,12 +53,18 @@ pub mod reload_env {
 pub mod embed_env {
     use minijinja::Environment;
 
+    use crate::http::{
+        form_errors::register_form_errors, middleware_banners::register_site_banners,
+    };
+
     pub fn build_env(http_external: String, version: String) -> Environment<'static> {
         let mut env = Environment::new();
         env.set_trim_blocks(true);
         env.set_lstrip_blocks(true);
         env.add_global("base", format!("https://{}", http_external));
         env.add_global("version", version.clone());
+        register_form_errors(&mut env);
+        register_site_banners(&mut env);
         minijinja_embed::load_templates!(&mut env);
         env
     }
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use itertools::Itertools;
 
+use crate::atproto::lexicon::community::lexicon::calendar::event::EXTRA_TIMEZONE;
 use crate::storage::handle::model::Handle;
 
 pub fn supported_timezones(handle: Option<&Handle>) -> (&str, Vec<&str>) {
@@ -43,6 +46,19 @@ pub fn supported_timezones(handle: Option<&Handle>) -> (&str, Vec<&str>) {
     )
 }
 
+/// Sets or removes the time zone of an event in the fields of its record
+/// that aren't otherwise modeled. Unknown time zones are not kept.
+pub fn set_event_timezone(extra: &mut HashMap<String, serde_json::Value>, tz: Option<&str>) {
+    match tz.filter(|value| value.parse::<chrono_tz::Tz>().is_ok()) {
+        Some(value) => {
+            extra.insert(EXTRA_TIMEZONE.to_string(), serde_json::json!(value));
+        }
+        None => {
+            extra.remove(EXTRA_TIMEZONE);
+        }
+    }
+}
+
 /// Combines an HTML date input value and HTML time input value into a single
 /// UTC datetime, using the provided timezone.
 ///
@@ -91,6 +107,22 @@ pub fn combine_html_datetime(
     Ok(local_dt.with_timezone(&Utc))
 }
 
+/// The first moment of a day in a time zone. Days whose midnight is skipped
+/// by a clock change start an hour later.
+pub fn start_of_day(date: NaiveDate, timezone: chrono_tz::Tz) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    timezone
+        .from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|value| value.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +152,26 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_start_of_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(
+            start_of_day(date, chrono_tz::America::Vancouver),
+            Utc.with_ymd_and_hms(2025, 6, 1, 7, 0, 0).unwrap()
+        );
+        assert_eq!(
+            start_of_day(date, chrono_tz::UTC),
+            Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()
+        );
+
+        // Clocks in Havana skip from midnight to one in the morning
+        let date = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        assert_eq!(
+            start_of_day(date, chrono_tz::America::Havana),
+            Utc.with_ymd_and_hms(2025, 3, 9, 5, 0, 0).unwrap()
+        );
+    }
+
     #[test]
     fn test_combine_html_datetime_invalid_inputs() {
         let tz = "America/New_York".parse::<chrono_tz::Tz>().unwrap();
@@ -148,3 +200,91 @@ mod tests {
         assert_eq!(result, expected);
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use chrono::{NaiveDate, NaiveTime};
+    use chrono_tz::Tz;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn timezone() -> impl Strategy<Value = Tz> {
+        prop::sample::select(chrono_tz::TZ_VARIANTS.to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips_local_times(
+            year in 1900i32..2100,
+            month in 1u32..=12,
+            day in 1u32..=31,
+            hour in 0u32..24,
+            minute in 0u32..60,
+            tz in timezone(),
+        ) {
+            let Some(date) = NaiveDate::from_ymd_opt(year, month, day) else {
+                return Ok(());
+            };
+            let time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+            let date_str = date.format("%Y-%m-%d").to_string();
+            let time_str = time.format("%H:%M").to_string();
+
+            // Times skipped or repeated by a clock change are rejected, and
+            // every other time converts back to the same local time
+            match combine_html_datetime(&date_str, &time_str, tz) {
+                Ok(utc) => {
+                    let local = utc.with_timezone(&tz);
+                    prop_assert_eq!(local.format("%Y-%m-%d").to_string(), date_str);
+                    prop_assert_eq!(local.format("%H:%M").to_string(), time_str);
+                }
+                Err(_) => {
+                    prop_assert!(date.and_time(time).and_local_timezone(tz).single().is_none());
+                }
+            }
+        }
+
+        #[test]
+        fn utc_is_unchanged(
+            year in 1i32..9999,
+            ordinal in 1u32..=365,
+            hour in 0u32..24,
+            minute in 0u32..60,
+        ) {
+            let date = NaiveDate::from_yo_opt(year, ordinal).unwrap();
+            let time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+            let result = combine_html_datetime(
+                &date.format("%Y-%m-%d").to_string(),
+                &time.format("%H:%M").to_string(),
+                chrono_tz::UTC,
+            );
+            prop_assert_eq!(result.unwrap().naive_utc(), date.and_time(time));
+        }
+
+        #[test]
+        fn never_panics(date_str in "\\PC{0,40}", time_str in "\\PC{0,20}", tz in timezone()) {
+            let _ = combine_html_datetime(&date_str, &time_str, tz);
+        }
+
+        #[test]
+        fn rejects_out_of_range_values(
+            year in 1900i32..2100,
+            month in 13u32..100,
+            day in 32u32..100,
+            hour in 24u32..100,
+            minute in 60u32..100,
+            tz in timezone(),
+        ) {
+            let valid_date = format!("{:04}-01-01", year);
+            let bad_month = format!("{:04}-{:02}-01", year, month);
+            let bad_day = format!("{:04}-01-{:02}", year, day);
+            let bad_hour = format!("{:02}:00", hour);
+            let bad_minute = format!("12:{:02}", minute);
+
+            prop_assert!(combine_html_datetime(&bad_month, "12:00", tz).is_err());
+            prop_assert!(combine_html_datetime(&bad_day, "12:00", tz).is_err());
+            prop_assert!(combine_html_datetime(&valid_date, &bad_hour, tz).is_err());
+            prop_assert!(combine_html_datetime(&valid_date, &bad_minute, tz).is_err());
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! A small iCalendar (RFC 5545) reader used to import events.
+//!
+//! Only the properties of a `VEVENT` that map onto an event are read: its
+//! identifier, summary, description, start and end, location and URL.
+//! Recurrence rules, attendees and alarms are ignored.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::ics_errors::IcsError;
+
+/// The parts of a `VEVENT` that can be used to fill in an event.
+#[derive(Debug, Default, PartialEq)]
+pub struct IcsEvent {
+    /// The `UID` property, which stays the same when the event is changed.
+    pub uid: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    /// The latitude and longitude from the `GEO` property.
+    pub geo: Option<(String, String)>,
+}
+
+/// A content line, split into its name, parameters and value.
+struct ContentLine<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl ContentLine<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+}
+
+/// Joins folded lines. Lines that start with a space or tab continue the
+/// previous line.
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits `value` on `separator`, ignoring separators inside double quotes.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, ch) in value.char_indices() {
+        if ch == '"' {
+            quoted = !quoted;
+        } else if ch == separator && !quoted {
+            parts.push(&value[start..index]);
+            start = index + ch.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn parse_content_line(line: &str) -> Option<ContentLine<'_>> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, ch)| {
+        if ch == '"' {
+            quoted = !quoted;
+        }
+        (ch == ':' && !quoted).then_some(index)
+    })?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut head_parts = split_unquoted(head, ';').into_iter();
+    let name = head_parts.next()?.trim().to_ascii_uppercase();
+    let params = head_parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value))
+        .collect();
+
+    Some(ContentLine {
+        name,
+        params,
+        value,
+    })
+}
+
+/// Reverses the escaping of TEXT values.
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped.trim().to_string()
+}
+
+/// Parses a `DATE` or `DATE-TIME` value. Floating times, and dates of all
+/// day events, are read in the `TZID` of the property, or `default_tz` when
+/// it has none or it isn't a known time zone.
+fn parse_date_time(line: &ContentLine<'_>, default_tz: Tz) -> Result<DateTime<Utc>, IcsError> {
+    let value = line.value.trim();
+    let invalid = || IcsError::InvalidDate(value.to_string());
+
+    let tz = line
+        .param("TZID")
+        .and_then(|tzid| tzid.parse::<Tz>().ok())
+        .unwrap_or(default_tz);
+
+    let naive = if line.param("VALUE") == Some("DATE") || value.len() == 8 {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|_| invalid())?
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(invalid)?
+    } else if let Some(utc_value) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc_value, "%Y%m%dT%H%M%S")
+            .map(|naive| naive.and_utc())
+            .map_err(|_| invalid());
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?
+    };
+
+    // Times skipped by a daylight saving change don't exist, and times
+    // repeated by one are read as the first of the two.
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .ok_or_else(invalid)
+}
+
+/// Parses a `DURATION` value such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Result<Duration, IcsError> {
+    let value = value.trim();
+    let invalid = || IcsError::InvalidDate(value.to_string());
+
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for ch in rest.chars() {
+        let unit = match ch {
+            '0'..='9' => {
+                number.push(ch);
+                continue;
+            }
+            'T' if !in_time && number.is_empty() => {
+                in_time = true;
+                continue;
+            }
+            'W' if !in_time => 604_800,
+            'D' if !in_time => 86_400,
+            'H' if in_time => 3_600,
+            'M' if in_time => 60,
+            'S' if in_time => 1,
+            _ => return Err(invalid()),
+        };
+        let amount = number.parse::<i64>().map_err(|_| invalid())?;
+        seconds = amount
+            .checked_mul(unit)
+            .and_then(|value| seconds.checked_add(value))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(Duration::seconds(if negative { -seconds } else { seconds }))
+}
+
+/// Reads the events of an iCalendar file, stopping after `limit` events.
+fn read_events(input: &str, default_tz: Tz, limit: usize) -> Result<Vec<IcsEvent>, IcsError> {
+    let lines = unfold(input.trim_start_matches('\u{feff}'));
+
+    let is_calendar = lines
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"));
+    if !is_calendar {
+        return Err(IcsError::NotCalendar);
+    }
+
+    let mut events = Vec::new();
+    let mut event = IcsEvent::default();
+    let mut in_event = false;
+    // Components inside the event, such as alarms, have their own
+    // properties that must not be read as the event's.
+    let mut nested = 0;
+    let mut duration = None;
+
+    for line in &lines {
+        let Some(line) = parse_content_line(line) else {
+            continue;
+        };
+        let component = line.value.trim().to_ascii_uppercase();
+
+        if !in_event {
+            if line.name == "BEGIN" && component == "VEVENT" {
+                in_event = true;
+            }
+            continue;
+        }
+
+        match line.name.as_str() {
+            "BEGIN" => nested += 1,
+            "END" if nested > 0 => nested -= 1,
+            "END" if component == "VEVENT" => {
+                if event.ends_at.is_none() {
+                    if let (Some(starts_at), Some(duration)) = (event.starts_at, duration) {
+                        event.ends_at = Some(starts_at + duration);
+                    }
+                }
+                events.push(std::mem::take(&mut event));
+                if events.len() >= limit {
+                    break;
+                }
+                in_event = false;
+                duration = None;
+            }
+            _ if nested > 0 => {}
+            "UID" => event.uid = Some(line.value.trim().to_string()),
+            "SUMMARY" => event.name = Some(unescape_text(line.value)),
+            "DESCRIPTION" => event.description = Some(unescape_text(line.value)),
+            "LOCATION" => event.location = Some(unescape_text(line.value)),
+            "URL" => event.url = Some(line.value.trim().to_string()),
+            "GEO" => {
+                event.geo = line.value.split_once(';').map(|(latitude, longitude)| {
+                    (latitude.trim().to_string(), longitude.trim().to_string())
+                })
+            }
+            "DTSTART" => event.starts_at = Some(parse_date_time(&line, default_tz)?),
+            "DTEND" => event.ends_at = Some(parse_date_time(&line, default_tz)?),
+            "DURATION" => duration = Some(parse_duration(line.value)?),
+            _ => {}
+        }
+    }
+
+    if events.is_empty() {
+        return Err(IcsError::EventNotFound);
+    }
+
+    Ok(events)
+}
+
+/// Reads the first event of an iCalendar file.
+///
+/// # Arguments
+/// * `input` - The contents of the file
+/// * `default_tz` - The time zone of floating times and all day events
+///
+/// # Returns
+/// * The properties of the first `VEVENT` in the calendar
+pub fn parse_first_event(input: &str, default_tz: Tz) -> Result<IcsEvent, IcsError> {
+    read_events(input, default_tz, 1)?
+        .pop()
+        .ok_or(IcsError::EventNotFound)
+}
+
+/// Reads every event of an iCalendar file, in the order they appear.
+///
+/// # Arguments
+/// * `input` - The contents of the file
+/// * `default_tz` - The time zone of floating times and all day events
+///
+/// # Returns
+/// * The properties of each `VEVENT` in the calendar
+pub fn parse_events(input: &str, default_tz: Tz) -> Result<Vec<IcsEvent>, IcsError> {
+    read_events(input, default_tz, usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEETUP_ICS: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Example//Calendar//EN\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:America/Vancouver\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+UID:1234@example.com\r\n\
+SUMMARY:Vancouver Rust Meetup\\, June\r\n\
+DESCRIPTION:Talks and snacks.\\nBring a friend\r\n\
+\x20\x20and a laptop.\r\n\
+DTSTART;TZID=America/Vancouver:20250601T180000\r\n\
+DTEND;TZID=America/Vancouver:20250601T200000\r\n\
+LOCATION:Central Library\\; Room 2\r\n\
+GEO:49.2796;-123.1157\r\n\
+URL:https://example.com/rust\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+TRIGGER:-PT15M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Second event\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_first_event() {
+        let event = parse_first_event(MEETUP_ICS, chrono_tz::UTC).unwrap();
+
+        assert_eq!(event.uid.as_deref(), Some("1234@example.com"));
+        assert_eq!(event.name.as_deref(), Some("Vancouver Rust Meetup, June"));
+        assert_eq!(
+            event.description.as_deref(),
+            Some("Talks and snacks.\nBring a friend and a laptop.")
+        );
+        assert_eq!(
+            event.starts_at,
+            Some("2025-06-02T01:00:00Z".parse().unwrap())
+        );
+        assert_eq!(event.ends_at, Some("2025-06-02T03:00:00Z".parse().unwrap()));
+        assert_eq!(event.location.as_deref(), Some("Central Library; Room 2"));
+        assert_eq!(event.url.as_deref(), Some("https://example.com/rust"));
+        assert_eq!(
+            event.geo,
+            Some(("49.2796".to_string(), "-123.1157".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let events = parse_events(MEETUP_ICS, chrono_tz::UTC).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].name.as_deref(),
+            Some("Vancouver Rust Meetup, June")
+        );
+        assert_eq!(events[1].name.as_deref(), Some("Second event"));
+        // Properties of one event don't carry over to the next
+        assert_eq!(events[1].uid, None);
+        assert_eq!(events[1].starts_at, None);
+        assert_eq!(events[1].geo, None);
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Utc\nDTSTART:20250601T180000Z\nDURATION:PT1H30M\nEND:VEVENT\nEND:VCALENDAR";
+        let event = parse_first_event(ics, chrono_tz::America::Vancouver).unwrap();
+        assert_eq!(
+            event.starts_at,
+            Some("2025-06-01T18:00:00Z".parse().unwrap())
+        );
+        assert_eq!(event.ends_at, Some("2025-06-01T19:30:00Z".parse().unwrap()));
+
+        // All day events start at midnight in the default time zone
+        let ics =
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;VALUE=DATE:20250601\nEND:VEVENT\nEND:VCALENDAR";
+        let event = parse_first_event(ics, chrono_tz::America::Vancouver).unwrap();
+        assert_eq!(
+            event.starts_at,
+            Some("2025-06-01T07:00:00Z".parse().unwrap())
+        );
+
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:June 1st\nEND:VEVENT\nEND:VCALENDAR";
+        assert!(matches!(
+            parse_first_event(ics, chrono_tz::UTC),
+            Err(IcsError::InvalidDate(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("P1DT2H").unwrap(), Duration::hours(26));
+        assert_eq!(parse_duration("P2W").unwrap(), Duration::days(14));
+        assert_eq!(parse_duration("-PT15M").unwrap(), Duration::minutes(-15));
+        assert!(parse_duration("PT1X").is_err());
+        assert!(parse_duration("P1H").is_err());
+        assert!(parse_duration("1H").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_calendars() {
+        assert!(matches!(
+            parse_first_event("SUMMARY:Not a calendar", chrono_tz::UTC),
+            Err(IcsError::NotCalendar)
+        ));
+        assert!(matches!(
+            parse_first_event(
+                "BEGIN:VCALENDAR\nBEGIN:VTODO\nSUMMARY:Task\nEND:VTODO\nEND:VCALENDAR",
+                chrono_tz::UTC
+            ),
+            Err(IcsError::EventNotFound)
+        ));
+    }
+}
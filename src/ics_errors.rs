@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while reading an iCalendar file.
+///
+/// These errors happen when an uploaded `.ics` file is not a calendar, has
+/// no events, or has event dates that cannot be understood.
+#[derive(Debug, Error)]
+pub enum IcsError {
+    /// Error when the file is not an iCalendar file.
+    ///
+    /// This error occurs when the file does not start with a
+    /// `BEGIN:VCALENDAR` line.
+    #[error("error-ics-1 File is not an iCalendar file")]
+    NotCalendar,
+
+    /// Error when the calendar does not contain an event.
+    ///
+    /// This error occurs when the calendar has no complete `VEVENT`
+    /// component, for example when it only has to-dos or free/busy times.
+    #[error("error-ics-2 Calendar does not contain an event")]
+    EventNotFound,
+
+    /// Error when an event date or duration cannot be parsed.
+    ///
+    /// This error occurs when `DTSTART`, `DTEND` or `DURATION` is not in one
+    /// of the formats defined by RFC 5545.
+    #[error("error-ics-3 Invalid event date: {0}")]
+    InvalidDate(String),
+}
@@ -0,0 +1,492 @@
+//! Import plans preview what importing a calendar file would change.
+//!
+//! A plan lists, for each event in the file, whether it would create a new
+//! event, update an existing one or be skipped, along with the fields that
+//! would change. Plans are built without writing anything to the PDS and are
+//! stored, so that the organizer can review one and confirm it later.
+//!
+//! Events are matched to the events they were imported as before by their
+//! calendar `UID`, and otherwise to an event of the organizer with the same
+//! name and start time.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atproto::{
+        lexicon::community::lexicon::{
+            calendar::event::{
+                Event, EventLink, EventLinks, EventLocation, EventLocations, Mode, Status, NSID,
+            },
+            location::Geo,
+        },
+        record_limits::check_event_record,
+    },
+    ics::IcsEvent,
+    import_plan_errors::ImportPlanError,
+    storage::{
+        errors::StorageError,
+        event::{event_find_duplicate, event_get, format_location, model::Event as StoredEvent},
+        import_plan::import_source_event_get,
+        StoragePool,
+    },
+};
+
+/// What confirming a plan does with an event from the file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Skip,
+}
+
+/// A field that differs between the existing event and the imported one.
+/// New events list every field they set, with no previous value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// An event from the file and what confirming the plan does with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPlanItem {
+    pub uid: Option<String>,
+    pub name: String,
+    pub action: ImportAction,
+
+    /// The event that is updated, or once confirmed, the event that was
+    /// created.
+    pub aturi: Option<String>,
+
+    /// The CID of the event when the plan was made. Updates are only written
+    /// if the event hasn't changed since.
+    pub cid: Option<String>,
+
+    /// The record that is written.
+    pub record: Option<Event>,
+
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+
+    /// A location from the file that can't be saved as a place, because it
+    /// is only text.
+    pub location_text: Option<String>,
+
+    /// Why the event is skipped. Unchanged events are skipped without one.
+    pub reason: Option<String>,
+
+    /// Set once the plan is confirmed. Failed writes have an error instead.
+    #[serde(default)]
+    pub completed: bool,
+    pub error: Option<String>,
+}
+
+/// The places of a calendar event, in the shape of event locations and links.
+#[derive(Debug, Default, PartialEq)]
+pub struct IcsPlaces {
+    pub locations: EventLocations,
+    pub links: EventLinks,
+
+    /// Location text that isn't a link and has no coordinates.
+    pub location_text: Option<String>,
+
+    /// Whether the location is a link, as it is for online events.
+    pub online: bool,
+}
+
+/// Maps the location, coordinates and URL of a calendar event onto event
+/// locations and links. Online events often have their meeting link as the
+/// location, so a location that is a link is added as a link.
+pub fn ics_event_places(ics_event: &IcsEvent) -> IcsPlaces {
+    let mut places = IcsPlaces::default();
+
+    let (location_uri, location_name) = match &ics_event.location {
+        Some(location) if location.starts_with("https://") || location.starts_with("http://") => {
+            (Some(location.clone()), None)
+        }
+        location => (None, location.clone().filter(|value| !value.is_empty())),
+    };
+    places.online = location_uri.is_some();
+
+    if let Some((latitude, longitude)) = &ics_event.geo {
+        places.locations.push(EventLocation::Geo(Geo::Current {
+            latitude: latitude.clone(),
+            longitude: longitude.clone(),
+            name: location_name,
+        }));
+    } else {
+        places.location_text = location_name;
+    }
+
+    for uri in [ics_event.url.clone(), location_uri].into_iter().flatten() {
+        places.links.push(EventLink::Current { uri, name: None });
+    }
+
+    places
+}
+
+/// Composes the record of a new event from a calendar event.
+fn new_record(name: &str, ics_event: &IcsEvent, places: IcsPlaces, now: DateTime<Utc>) -> Event {
+    let mode = if places.online && places.locations.is_empty() {
+        Mode::Virtual
+    } else {
+        Mode::InPerson
+    };
+
+    Event::Current {
+        name: name.to_string(),
+        description: ics_event.description.clone().unwrap_or_default(),
+        created_at: now,
+        starts_at: ics_event.starts_at,
+        ends_at: ics_event.ends_at,
+        mode: Some(mode),
+        status: Some(Status::Scheduled),
+        locations: places.locations,
+        uris: places.links,
+        extra: HashMap::default(),
+    }
+}
+
+/// Applies a calendar event to an existing record. Fields the calendar event
+/// doesn't have are left as they are, as are the fields calendars don't
+/// carry, like the mode and status.
+fn merge_record(existing: &Event, name: &str, ics_event: &IcsEvent, places: IcsPlaces) -> Event {
+    let mut record = existing.clone();
+    let Event::Current {
+        name: record_name,
+        description,
+        starts_at,
+        ends_at,
+        locations,
+        uris,
+        ..
+    } = &mut record;
+
+    *record_name = name.to_string();
+    if let Some(value) = &ics_event.description {
+        *description = value.clone();
+    }
+    if ics_event.starts_at.is_some() {
+        *starts_at = ics_event.starts_at;
+    }
+    if ics_event.ends_at.is_some() {
+        *ends_at = ics_event.ends_at;
+    }
+    if !places.locations.is_empty() {
+        *locations = places.locations;
+    }
+    if !places.links.is_empty() {
+        *uris = places.links;
+    }
+
+    record
+}
+
+fn display_date(value: &Option<DateTime<Utc>>, tz: Tz) -> Option<String> {
+    value.map(|value| {
+        value
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string()
+    })
+}
+
+fn display_locations(locations: &EventLocations) -> Option<String> {
+    let values: Vec<String> = locations.iter().filter_map(format_location).collect();
+    (!values.is_empty()).then(|| values.join("; "))
+}
+
+fn display_links(links: &EventLinks) -> Option<String> {
+    let values: Vec<&str> = links
+        .iter()
+        .map(|EventLink::Current { uri, .. }| uri.as_str())
+        .collect();
+    (!values.is_empty()).then(|| values.join(" "))
+}
+
+/// Lists the fields that differ between two versions of a record, with
+/// times shown in `tz`. Every field that is set is listed when there is no
+/// previous version.
+pub fn record_changes(before: Option<&Event>, after: &Event, tz: Tz) -> Vec<FieldChange> {
+    let fields = |record: &Event| -> Vec<(&'static str, Option<String>)> {
+        let Event::Current {
+            name,
+            description,
+            starts_at,
+            ends_at,
+            locations,
+            uris,
+            ..
+        } = record;
+        vec![
+            ("name", Some(name.clone())),
+            (
+                "description",
+                Some(description.clone()).filter(|value| !value.is_empty()),
+            ),
+            ("starts_at", display_date(starts_at, tz)),
+            ("ends_at", display_date(ends_at, tz)),
+            ("locations", display_locations(locations)),
+            ("links", display_links(uris)),
+        ]
+    };
+
+    let before_fields = before.map(fields);
+    fields(after)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (field, after))| {
+            let before = before_fields
+                .as_ref()
+                .and_then(|values| values[index].1.clone());
+            (before != after).then(|| FieldChange {
+                field: field.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn skipped(uid: Option<String>, name: String, reason: ImportPlanError) -> ImportPlanItem {
+    ImportPlanItem {
+        uid,
+        name,
+        action: ImportAction::Skip,
+        aturi: None,
+        cid: None,
+        record: None,
+        changes: vec![],
+        location_text: None,
+        reason: Some(reason.to_string()),
+        completed: false,
+        error: None,
+    }
+}
+
+/// Finds the event a calendar event was imported as before, or an event of
+/// the organizer with the same name and start time.
+async fn find_existing_event(
+    pool: &StoragePool,
+    did: &str,
+    uid: Option<&str>,
+    name: &str,
+    starts_at: Option<DateTime<Utc>>,
+) -> Result<Option<StoredEvent>, StorageError> {
+    let imported = match uid {
+        Some(uid) => import_source_event_get(pool, did, uid).await?,
+        None => None,
+    };
+
+    let aturi = match imported {
+        Some(aturi) => Some(aturi),
+        None => event_find_duplicate(pool, did, name, starts_at).await?,
+    };
+
+    let Some(aturi) = aturi else {
+        return Ok(None);
+    };
+
+    // Events imported before may have been deleted since
+    match event_get(pool, &aturi).await {
+        Ok(event) if event.did == did => Ok(Some(event)),
+        Ok(_) | Err(StorageError::RowNotFound(_, _)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Plans the import of the events of a calendar file into an organizer's
+/// events. Nothing is written; the plan is stored and confirmed separately.
+///
+/// # Arguments
+/// * `pool` - The database used to find existing events
+/// * `did` - The organizer importing the file
+/// * `events` - The events read from the file
+/// * `tz` - The time zone that times are shown in
+pub async fn build_import_plan(
+    pool: &StoragePool,
+    did: &str,
+    events: Vec<IcsEvent>,
+    tz: Tz,
+) -> Result<Vec<ImportPlanItem>, StorageError> {
+    let now = Utc::now();
+    let mut seen_uids = HashSet::new();
+    let mut items = Vec::with_capacity(events.len());
+
+    for ics_event in events {
+        let uid = ics_event.uid.clone().filter(|value| !value.is_empty());
+        let name = ics_event.name.clone().unwrap_or_default();
+
+        if name.is_empty() {
+            items.push(skipped(uid, name, ImportPlanError::NameMissing));
+            continue;
+        }
+
+        if let Some(uid) = &uid {
+            if !seen_uids.insert(uid.clone()) {
+                let reason = ImportPlanError::DuplicateUid(uid.clone());
+                items.push(skipped(Some(uid.clone()), name, reason));
+                continue;
+            }
+        }
+
+        let places = ics_event_places(&ics_event);
+        let location_text = places.location_text.clone();
+
+        let existing =
+            find_existing_event(pool, did, uid.as_deref(), &name, ics_event.starts_at).await?;
+
+        let mut item = match existing {
+            None => {
+                let record = new_record(&name, &ics_event, places, now);
+                ImportPlanItem {
+                    uid,
+                    name,
+                    action: ImportAction::Create,
+                    aturi: None,
+                    cid: None,
+                    changes: record_changes(None, &record, tz),
+                    record: Some(record),
+                    location_text,
+                    reason: None,
+                    completed: false,
+                    error: None,
+                }
+            }
+            Some(existing) => {
+                let current = (existing.lexicon == NSID)
+                    .then(|| serde_json::from_value::<Event>(existing.record.0.clone()).ok())
+                    .flatten();
+                let Some(current) = current else {
+                    let mut item = skipped(uid, name, ImportPlanError::LegacyEvent);
+                    item.aturi = Some(existing.aturi);
+                    items.push(item);
+                    continue;
+                };
+
+                let record = merge_record(&current, &name, &ics_event, places);
+                let changes = record_changes(Some(&current), &record, tz);
+                ImportPlanItem {
+                    uid,
+                    name,
+                    action: if changes.is_empty() {
+                        ImportAction::Skip
+                    } else {
+                        ImportAction::Update
+                    },
+                    aturi: Some(existing.aturi),
+                    cid: Some(existing.cid),
+                    changes,
+                    record: Some(record),
+                    location_text,
+                    reason: None,
+                    completed: false,
+                    error: None,
+                }
+            }
+        };
+
+        if let Some(record) = &item.record {
+            if let Err(err) = check_event_record(record) {
+                item.action = ImportAction::Skip;
+                item.reason = Some(ImportPlanError::RecordLimit(err.to_string()).to_string());
+            }
+        }
+
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_ics_event() -> IcsEvent {
+        IcsEvent {
+            uid: Some("1234@example.com".to_string()),
+            name: Some("Vancouver Rust Meetup".to_string()),
+            description: Some("Talks and snacks.".to_string()),
+            starts_at: Some("2025-06-02T01:00:00Z".parse().unwrap()),
+            ends_at: None,
+            location: Some("Central Library".to_string()),
+            url: Some("https://example.com/rust".to_string()),
+            geo: None,
+        }
+    }
+
+    #[test]
+    fn test_ics_event_places() {
+        let places = ics_event_places(&test_ics_event());
+        assert!(places.locations.is_empty());
+        assert_eq!(places.location_text.as_deref(), Some("Central Library"));
+        assert_eq!(
+            display_links(&places.links).as_deref(),
+            Some("https://example.com/rust")
+        );
+        assert!(!places.online);
+
+        let mut ics_event = test_ics_event();
+        ics_event.geo = Some(("49.2796".to_string(), "-123.1157".to_string()));
+        let places = ics_event_places(&ics_event);
+        assert_eq!(places.locations.len(), 1);
+        assert_eq!(places.location_text, None);
+
+        let mut ics_event = test_ics_event();
+        ics_event.location = Some("https://meet.example.com/rust".to_string());
+        ics_event.url = None;
+        let places = ics_event_places(&ics_event);
+        assert!(places.online);
+        assert_eq!(
+            display_links(&places.links).as_deref(),
+            Some("https://meet.example.com/rust")
+        );
+    }
+
+    #[test]
+    fn test_record_changes() {
+        let ics_event = test_ics_event();
+        let created = new_record(
+            "Vancouver Rust Meetup",
+            &ics_event,
+            ics_event_places(&ics_event),
+            Utc::now(),
+        );
+
+        // New events list the fields they set
+        let changes = record_changes(None, &created, chrono_tz::America::Vancouver);
+        let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "description", "starts_at", "links"]);
+        assert_eq!(changes[2].after.as_deref(), Some("2025-06-01 18:00 PDT"));
+
+        // Fields the calendar doesn't have are kept when updating
+        let mut ics_event = test_ics_event();
+        ics_event.description = None;
+        ics_event.url = None;
+        ics_event.ends_at = Some("2025-06-02T03:00:00Z".parse().unwrap());
+        let updated = merge_record(
+            &created,
+            "Vancouver Rust Meetup",
+            &ics_event,
+            ics_event_places(&ics_event),
+        );
+        let changes = record_changes(Some(&created), &updated, chrono_tz::UTC);
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                field: "ends_at".to_string(),
+                before: None,
+                after: Some("2025-06-02 03:00 UTC".to_string()),
+            }]
+        );
+
+        assert!(record_changes(Some(&created), &created, chrono_tz::UTC).is_empty());
+    }
+}
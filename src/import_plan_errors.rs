@@ -0,0 +1,53 @@
+use thiserror::Error;
+
+/// Represents the reasons an event in an import plan is skipped, and errors
+/// that can occur while confirming a plan.
+///
+/// Skip reasons are stored with the plan and shown in its preview, so that
+/// the organizer can see why an event from the file won't be written.
+#[derive(Debug, Error)]
+pub enum ImportPlanError {
+    /// Error when an event in the file has no name.
+    ///
+    /// This error occurs when a `VEVENT` has no `SUMMARY` property, or the
+    /// summary is empty.
+    #[error("error-import-plan-1 Event has no name")]
+    NameMissing,
+
+    /// Error when more than one event in the file has the same `UID`.
+    ///
+    /// This error occurs for every event after the first with the same
+    /// identifier, such as changed occurrences of a recurring event.
+    #[error("error-import-plan-2 Event appears more than once in the file: {0}")]
+    DuplicateUid(String),
+
+    /// Error when the matching event uses the old Smoke Signal record format.
+    ///
+    /// This error occurs when the event the file would update was created
+    /// before events were migrated, and has to be migrated before it can be
+    /// updated.
+    #[error("error-import-plan-3 Matching event must be migrated before it can be updated")]
+    LegacyEvent,
+
+    /// Error when the composed record is outside the limits a PDS accepts.
+    ///
+    /// This error occurs when an event from the file is too large to write,
+    /// for example when its description is too long.
+    #[error("error-import-plan-4 Event can't be written: {0}")]
+    RecordLimit(String),
+
+    /// Error when the event changed after the plan was made.
+    ///
+    /// This error occurs when the matching event is edited between the
+    /// preview and the confirmation, so the update would overwrite the
+    /// edit.
+    #[error("error-import-plan-5 Event changed after the import was previewed")]
+    EventChanged,
+
+    /// Error when the PDS does not accept the record.
+    ///
+    /// This error occurs when the record write fails while the plan is
+    /// being confirmed.
+    #[error("error-import-plan-6 Record write failed: {0}")]
+    RecordWriteFailed(String),
+}
@@ -1,20 +1,56 @@
+pub mod announcement;
+pub mod app_password;
+pub mod app_password_errors;
 pub mod atproto;
 pub mod config;
 pub mod config_errors;
 pub mod did;
+pub mod discussion;
 pub mod encoding;
 pub mod encoding_errors;
 pub mod errors;
+pub mod event_card;
+pub mod event_card_errors;
+pub mod event_defaults;
+pub mod geocoder;
+pub mod geocoder_errors;
 pub mod http;
 pub mod i18n;
+pub mod ics;
+pub mod ics_errors;
+pub mod import_plan;
+pub mod import_plan_errors;
 pub mod jose;
 pub mod jose_errors;
+pub mod media;
+pub mod media_errors;
+pub mod normalize;
+pub mod notifications;
+pub mod notifications_errors;
 pub mod oauth;
 pub mod oauth_client_errors;
 pub mod oauth_errors;
+pub mod profile;
 pub mod refresh_tokens_errors;
+pub mod repo_import;
 pub mod resolve;
+pub mod richtext;
+pub mod service_auth;
+pub mod service_auth_errors;
 pub mod storage;
 // Removing storage_oauth_errors, consolidated with storage/oauth_model_errors
+pub mod task_handle_refresh;
+pub mod task_import_jobs;
+pub mod task_integrity_report;
+pub mod task_label_refresh;
+pub mod task_oauth_cleanup;
+pub mod task_pds_resources;
 pub mod task_refresh_tokens;
+pub mod task_rsvp_backfill;
+pub mod task_webhooks;
+pub mod telemetry;
+#[cfg(test)]
+pub mod test_support;
 pub mod validation;
+pub mod webhooks;
+pub mod webhooks_errors;
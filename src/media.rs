@@ -0,0 +1,179 @@
+//! Images that organizers keep in their media library and show on events.
+//!
+//! Uploaded images are stored as blobs in the organizer's repository, each
+//! kept by an `events.smokesignal.media` record, and indexed per DID so they
+//! can be picked again for later events. Events reference a library image
+//! as header media in the `media` field of the event record.
+
+use std::collections::HashMap;
+
+use crate::atproto::lexicon::{
+    com::atproto::repo::Blob,
+    community::lexicon::calendar::event::{EventMedia, MEDIA_ROLE_HEADER},
+};
+use crate::media_errors::MediaError;
+
+/// The largest image accepted into a media library, which is the largest
+/// image most PDSs and clients accept.
+pub const MAX_IMAGE_SIZE: usize = 1_000_000;
+
+/// The most images an organizer can keep in their media library.
+pub const MAX_LIBRARY_IMAGES: i64 = 50;
+
+/// Finds the type of an image from its first bytes. The type given by the
+/// browser isn't trusted, since it is only a guess from the file name.
+pub fn image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Checks that an upload is an image that can be kept in a media library,
+/// and returns its type.
+pub fn validate_image(bytes: &[u8]) -> Result<&'static str, MediaError> {
+    if bytes.is_empty() {
+        return Err(MediaError::ImageMissing);
+    }
+    if bytes.len() > MAX_IMAGE_SIZE {
+        return Err(MediaError::ImageTooLarge(MAX_IMAGE_SIZE));
+    }
+    image_type(bytes).ok_or(MediaError::UnsupportedImageType)
+}
+
+/// The URL a blob can be fetched from on the PDS of its repository.
+pub fn blob_url(pds: &str, did: &str, cid: &str) -> String {
+    format!(
+        "{}/xrpc/com.atproto.sync.getBlob?did={}&cid={}",
+        pds.trim_end_matches('/'),
+        urlencoding::encode(did),
+        urlencoding::encode(cid)
+    )
+}
+
+/// Gets the header image of a serialized event record.
+pub fn header_image(record: &serde_json::Value) -> Option<Blob> {
+    record
+        .get("media")?
+        .as_array()?
+        .iter()
+        .filter_map(|value| serde_json::from_value::<EventMedia>(value.clone()).ok())
+        .find_map(|media| match media {
+            EventMedia::Current { role, content, .. } if role == MEDIA_ROLE_HEADER => Some(content),
+            _ => None,
+        })
+}
+
+/// Sets or removes the header image in the fields of an event record that
+/// aren't otherwise modeled. Other media of the event is kept.
+pub fn set_header_image(extra: &mut HashMap<String, serde_json::Value>, image: Option<Blob>) {
+    let mut media: Vec<serde_json::Value> = extra
+        .remove("media")
+        .and_then(|value| match value {
+            serde_json::Value::Array(values) => Some(values),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    media.retain(|value| {
+        value.get("role").and_then(|role| role.as_str()) != Some(MEDIA_ROLE_HEADER)
+    });
+
+    if let Some(image) = image {
+        let header = EventMedia::Current {
+            role: MEDIA_ROLE_HEADER.to_string(),
+            content: image,
+            alt: None,
+        };
+        if let Ok(value) = serde_json::to_value(header) {
+            media.insert(0, value);
+        }
+    }
+
+    if !media.is_empty() {
+        extra.insert("media".to_string(), serde_json::Value::Array(media));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atproto::lexicon::com::atproto::repo::BlobLink;
+
+    fn test_blob(cid: &str) -> Blob {
+        Blob {
+            reference: BlobLink {
+                link: cid.to_string(),
+            },
+            mime_type: "image/png".to_string(),
+            size: 1024,
+        }
+    }
+
+    #[test]
+    fn test_validate_image() {
+        assert_eq!(
+            validate_image(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap(),
+            "image/png"
+        );
+        assert_eq!(
+            validate_image(b"RIFF\0\0\0\0WEBPVP8 ").unwrap(),
+            "image/webp"
+        );
+        assert!(matches!(
+            validate_image(b"<svg></svg>"),
+            Err(MediaError::UnsupportedImageType)
+        ));
+        assert!(matches!(validate_image(b""), Err(MediaError::ImageMissing)));
+
+        let mut large = b"\xff\xd8\xff".to_vec();
+        large.resize(MAX_IMAGE_SIZE + 1, 0);
+        assert!(matches!(
+            validate_image(&large),
+            Err(MediaError::ImageTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_header_image() {
+        let mut extra = HashMap::new();
+        extra.insert(
+            "media".to_string(),
+            serde_json::json!([{"$type": "community.lexicon.calendar.event#media", "role": "thumbnail", "content": test_blob("bafythumb")}]),
+        );
+
+        set_header_image(&mut extra, Some(test_blob("bafyheader")));
+        let record = serde_json::to_value(&extra).unwrap();
+        assert_eq!(header_image(&record), Some(test_blob("bafyheader")));
+        assert_eq!(record["media"].as_array().unwrap().len(), 2);
+
+        set_header_image(&mut extra, Some(test_blob("bafyother")));
+        let record = serde_json::to_value(&extra).unwrap();
+        assert_eq!(header_image(&record), Some(test_blob("bafyother")));
+        assert_eq!(record["media"].as_array().unwrap().len(), 2);
+
+        set_header_image(&mut extra, None);
+        let record = serde_json::to_value(&extra).unwrap();
+        assert_eq!(header_image(&record), None);
+        assert_eq!(record["media"].as_array().unwrap().len(), 1);
+
+        let mut empty = HashMap::new();
+        set_header_image(&mut empty, None);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_blob_url() {
+        assert_eq!(
+            blob_url("https://pds.examplepds.com/", "did:plc:abc", "bafyheader"),
+            "https://pds.examplepds.com/xrpc/com.atproto.sync.getBlob?did=did%3Aplc%3Aabc&cid=bafyheader"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while managing a media library.
+///
+/// These errors happen when an uploaded image can't be kept in the library,
+/// or when an image picked for an event isn't in it.
+#[derive(Debug, Error)]
+pub enum MediaError {
+    /// Error when an upload has no image.
+    ///
+    /// This error occurs when the upload form is submitted without a file,
+    /// or with an empty one.
+    #[error("error-media-1 No image was uploaded")]
+    ImageMissing,
+
+    /// Error when an uploaded image is too large.
+    ///
+    /// This error occurs when the image is larger than the size PDSs accept
+    /// for images.
+    #[error("error-media-2 Images can be at most {0} bytes")]
+    ImageTooLarge(usize),
+
+    /// Error when an upload isn't a supported image.
+    ///
+    /// This error occurs when the uploaded file isn't a PNG, JPEG, GIF or
+    /// WebP image.
+    #[error("error-media-3 Images must be PNG, JPEG, GIF or WebP")]
+    UnsupportedImageType,
+
+    /// Error when the media library is full.
+    ///
+    /// This error occurs when an organizer uploads an image while their
+    /// library already has the most images it can keep.
+    #[error("error-media-4 Media libraries can have at most {0} images")]
+    LibraryFull(i64),
+
+    /// Error when an image isn't in the media library.
+    ///
+    /// This error occurs when an image is picked for an event or removed,
+    /// but isn't in the organizer's media library.
+    #[error("error-media-5 Image not found in media library: {0}")]
+    ImageNotFound(String),
+}
@@ -0,0 +1,96 @@
+//! Text normalization used to compare names the way people type them.
+//!
+//! Names are case folded and stripped of diacritics so that "cafe" matches
+//! "Café" and "CAFÉ", both when searching and when looking for duplicates.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Folds letters that don't decompose into a base letter and a combining mark,
+/// and so aren't handled by stripping diacritics.
+fn fold_letter(ch: char) -> Option<&'static str> {
+    match ch {
+        'ß' => Some("ss"),
+        'æ' => Some("ae"),
+        'œ' => Some("oe"),
+        'ø' => Some("o"),
+        'đ' => Some("d"),
+        'ł' => Some("l"),
+        'ı' => Some("i"),
+        'ς' => Some("σ"),
+        _ => None,
+    }
+}
+
+/// Normalizes text for comparison.
+///
+/// The text is lowercased, decomposed (NFKD) with combining marks removed,
+/// and runs of whitespace are collapsed into a single space. Text is
+/// lowercased both before and after decomposing, since letters like the
+/// Turkish "İ" lowercase to a letter with a combining mark and compatibility
+/// characters like "№" decompose to uppercase letters.
+///
+/// # Arguments
+/// * `value` - The text to normalize
+///
+/// # Returns
+/// * The normalized text, which is empty when `value` only has whitespace
+#[must_use]
+pub fn normalize_text(value: &str) -> String {
+    let mut normalized = String::with_capacity(value.len());
+    let mut pending_space = false;
+
+    for ch in value.to_lowercase().nfkd().flat_map(char::to_lowercase) {
+        if is_combining_mark(ch) {
+            continue;
+        }
+
+        if ch.is_whitespace() {
+            pending_space = !normalized.is_empty();
+            continue;
+        }
+
+        if pending_space {
+            normalized.push(' ');
+            pending_space = false;
+        }
+
+        match fold_letter(ch) {
+            Some(folded) => normalized.push_str(folded),
+            None => normalized.push(ch),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text_strips_diacritics() {
+        assert_eq!(normalize_text("Café"), "cafe");
+        assert_eq!(normalize_text("CAFÉ"), "cafe");
+        // Decomposed input normalizes the same as precomposed input
+        assert_eq!(normalize_text("Cafe\u{301}"), "cafe");
+        assert_eq!(normalize_text("Montréal"), "montreal");
+        assert_eq!(normalize_text("Zürich Ōsaka"), "zurich osaka");
+    }
+
+    #[test]
+    fn test_normalize_text_folds_case() {
+        assert_eq!(normalize_text("Straße"), "strasse");
+        assert_eq!(normalize_text("STRASSE"), "strasse");
+        assert_eq!(normalize_text("İstanbul"), "istanbul");
+        assert_eq!(normalize_text("København"), "kobenhavn");
+        assert_eq!(normalize_text("ΣΟΦΟΣ"), normalize_text("σοφος"));
+    }
+
+    #[test]
+    fn test_normalize_text_whitespace() {
+        assert_eq!(normalize_text("  Rust   Meetup\t#12 "), "rust meetup #12");
+        assert_eq!(normalize_text(" \n "), "");
+        // Compatibility characters are decomposed too
+        assert_eq!(normalize_text("ﬁle №1"), "file no1");
+    }
+}
@@ -0,0 +1,332 @@
+use minijinja::{AutoEscape, Environment, ErrorKind, Value};
+use unic_langid::LanguageIdentifier;
+
+use crate::notifications_errors::NotificationError;
+
+/// Locale used when a notification has no template for the recipient's
+/// language.
+const DEFAULT_LOCALE: &str = "en-us";
+
+/// Notification templates are compiled into the binary so that background
+/// tasks can render messages without a template loader.
+const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "notifications/layout.en-us.html",
+        include_str!("../templates/notifications/layout.en-us.html"),
+    ),
+    (
+        "notifications/rsvp_received.en-us.subject.txt",
+        include_str!("../templates/notifications/rsvp_received.en-us.subject.txt"),
+    ),
+    (
+        "notifications/rsvp_received.en-us.txt",
+        include_str!("../templates/notifications/rsvp_received.en-us.txt"),
+    ),
+    (
+        "notifications/rsvp_received.en-us.html",
+        include_str!("../templates/notifications/rsvp_received.en-us.html"),
+    ),
+    (
+        "notifications/event_updated.en-us.subject.txt",
+        include_str!("../templates/notifications/event_updated.en-us.subject.txt"),
+    ),
+    (
+        "notifications/event_updated.en-us.txt",
+        include_str!("../templates/notifications/event_updated.en-us.txt"),
+    ),
+    (
+        "notifications/event_updated.en-us.html",
+        include_str!("../templates/notifications/event_updated.en-us.html"),
+    ),
+    (
+        "notifications/digest.en-us.subject.txt",
+        include_str!("../templates/notifications/digest.en-us.subject.txt"),
+    ),
+    (
+        "notifications/digest.en-us.txt",
+        include_str!("../templates/notifications/digest.en-us.txt"),
+    ),
+    (
+        "notifications/digest.en-us.html",
+        include_str!("../templates/notifications/digest.en-us.html"),
+    ),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// Sent to an organizer when someone RSVPs to their event.
+    RsvpReceived,
+    /// Sent to attendees when an organizer posts an update to an event.
+    EventUpdated,
+    /// A periodic summary of upcoming events.
+    Digest,
+}
+
+impl NotificationKind {
+    /// The name of the notification, which is also the prefix of its
+    /// templates.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationKind::RsvpReceived => "rsvp_received",
+            NotificationKind::EventUpdated => "event_updated",
+            NotificationKind::Digest => "digest",
+        }
+    }
+}
+
+/// A notification rendered for every channel. Email uses all three parts,
+/// web push uses the subject and text, and digests embed the HTML part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedNotification {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+pub struct NotificationRenderer {
+    env: Environment<'static>,
+}
+
+impl NotificationRenderer {
+    /// Creates a renderer whose templates link back to `http_external`.
+    ///
+    /// # Panics
+    /// Panics if one of the bundled templates contains a syntax error.
+    pub fn new(http_external: &str) -> Self {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        // Only the HTML part is escaped. Text parts are never interpreted
+        // as markup, so escaping them would leak entities into the message.
+        env.set_auto_escape_callback(|name| {
+            if name.ends_with(".html") {
+                AutoEscape::Html
+            } else {
+                AutoEscape::None
+            }
+        });
+        env.add_global("base", format!("https://{}", http_external));
+
+        for &(name, source) in TEMPLATES {
+            env.add_template(name, source)
+                .expect("bundled notification template is valid");
+        }
+
+        Self { env }
+    }
+
+    /// Renders the subject, text, and HTML parts of a notification in the
+    /// given language, falling back to the default locale for any part that
+    /// has not been translated.
+    ///
+    /// # Errors
+    /// Returns an error if a template is missing or fails to render.
+    pub fn render(
+        &self,
+        kind: NotificationKind,
+        language: &LanguageIdentifier,
+        context: Value,
+    ) -> Result<RenderedNotification, NotificationError> {
+        let locale = language.to_string().to_lowercase();
+
+        let subject = self.render_part(kind, &locale, "subject.txt", &context)?;
+        let text = self.render_part(kind, &locale, "txt", &context)?;
+        let html = self.render_part(kind, &locale, "html", &context)?;
+
+        // Subjects end up in mail headers and push titles, so they are
+        // always collapsed to a single line.
+        let subject = subject.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+        Ok(RenderedNotification {
+            subject,
+            text,
+            html,
+        })
+    }
+
+    fn render_part(
+        &self,
+        kind: NotificationKind,
+        locale: &str,
+        part: &str,
+        context: &Value,
+    ) -> Result<String, NotificationError> {
+        let template_name = format!("notifications/{}.{}.{}", kind.name(), locale, part);
+
+        let template = match self.env.get_template(&template_name) {
+            Ok(template) => template,
+            Err(err) if err.kind() == ErrorKind::TemplateNotFound && locale != DEFAULT_LOCALE => {
+                return self.render_part(kind, DEFAULT_LOCALE, part, context);
+            }
+            Err(err) if err.kind() == ErrorKind::TemplateNotFound => {
+                return Err(NotificationError::TemplateNotFound(template_name));
+            }
+            Err(err) => return Err(NotificationError::RenderFailed(err)),
+        };
+
+        template
+            .render(context)
+            .map_err(NotificationError::RenderFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+
+    fn language(value: &str) -> LanguageIdentifier {
+        value.parse::<LanguageIdentifier>().unwrap()
+    }
+
+    fn renderer() -> NotificationRenderer {
+        NotificationRenderer::new("smokesignal.example")
+    }
+
+    fn assert_snapshot(rendered: &RenderedNotification, subject: &str, text: &str, html: &str) {
+        assert_eq!(rendered.subject, subject.trim_end());
+        assert_eq!(rendered.text, text);
+        assert_eq!(rendered.html, html);
+    }
+
+    #[test]
+    fn test_rsvp_received_snapshot() {
+        let rendered = renderer()
+            .render(
+                NotificationKind::RsvpReceived,
+                &language("en-US"),
+                context! {
+                    event_name => "Vancouver Rust Meetup",
+                    event_url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+                    attendee_handle => "bob.example",
+                    status => "going",
+                },
+            )
+            .unwrap();
+
+        assert_snapshot(
+            &rendered,
+            include_str!("../testdata/notifications/rsvp_received.en-us.subject.txt"),
+            include_str!("../testdata/notifications/rsvp_received.en-us.txt"),
+            include_str!("../testdata/notifications/rsvp_received.en-us.html"),
+        );
+    }
+
+    #[test]
+    fn test_event_updated_snapshot() {
+        let rendered = renderer()
+            .render(
+                NotificationKind::EventUpdated,
+                &language("en-US"),
+                context! {
+                    event_name => "Vancouver Rust Meetup",
+                    event_url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+                    message => "We moved to the second floor.\nBring photo ID.",
+                },
+            )
+            .unwrap();
+
+        assert_snapshot(
+            &rendered,
+            include_str!("../testdata/notifications/event_updated.en-us.subject.txt"),
+            include_str!("../testdata/notifications/event_updated.en-us.txt"),
+            include_str!("../testdata/notifications/event_updated.en-us.html"),
+        );
+    }
+
+    #[test]
+    fn test_digest_snapshot() {
+        let rendered = renderer()
+            .render(
+                NotificationKind::Digest,
+                &language("en-US"),
+                context! {
+                    recipient_handle => "bob.example",
+                    events => vec![
+                        context! {
+                            name => "Vancouver Rust Meetup",
+                            url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+                            starts_at => "2025-06-12 18:30 PDT",
+                        },
+                        context! {
+                            name => "Stanley Park Cleanup",
+                            url => "https://smokesignal.example/carol.example/3lgahc7aq2s2x",
+                            starts_at => "2025-06-14 10:00 PDT",
+                        },
+                    ],
+                },
+            )
+            .unwrap();
+
+        assert_snapshot(
+            &rendered,
+            include_str!("../testdata/notifications/digest.en-us.subject.txt"),
+            include_str!("../testdata/notifications/digest.en-us.txt"),
+            include_str!("../testdata/notifications/digest.en-us.html"),
+        );
+    }
+
+    #[test]
+    fn test_html_part_is_escaped() {
+        let rendered = renderer()
+            .render(
+                NotificationKind::RsvpReceived,
+                &language("en-US"),
+                context! {
+                    event_name => "<script>alert(1)</script>",
+                    event_url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+                    attendee_handle => "bob.example",
+                    status => "interested",
+                },
+            )
+            .unwrap();
+
+        assert!(!rendered.html.contains("<script>"));
+        assert!(rendered.html.contains("&lt;script&gt;"));
+        // Text parts are not markup and must not contain HTML entities.
+        assert!(rendered.text.contains("<script>alert(1)</script>"));
+    }
+
+    #[test]
+    fn test_subject_is_single_line() {
+        let rendered = renderer()
+            .render(
+                NotificationKind::EventUpdated,
+                &language("en-US"),
+                context! {
+                    event_name => "Line one\r\nBcc: someone@example.com",
+                    event_url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+                    message => "Hello",
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            rendered.subject,
+            "Update for Line one Bcc: someone@example.com"
+        );
+    }
+
+    #[test]
+    fn test_unknown_locale_falls_back_to_default() {
+        let context = context! {
+            event_name => "Vancouver Rust Meetup",
+            event_url => "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+            attendee_handle => "bob.example",
+            status => "notgoing",
+        };
+
+        let english = renderer()
+            .render(
+                NotificationKind::RsvpReceived,
+                &language("en-US"),
+                context.clone(),
+            )
+            .unwrap();
+        let fallback = renderer()
+            .render(NotificationKind::RsvpReceived, &language("fr-CA"), context)
+            .unwrap();
+
+        assert_eq!(english, fallback);
+    }
+}
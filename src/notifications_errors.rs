@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while rendering notifications.
+///
+/// These errors happen when a notification template is missing or cannot
+/// be rendered with the context provided by the delivery channel.
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    /// Error when no template exists for a notification part.
+    ///
+    /// This error occurs when neither the requested locale nor the default
+    /// locale has a template for the notification part being rendered.
+    #[error("error-notification-1 Notification template not found: {0}")]
+    TemplateNotFound(String),
+
+    /// Error when a notification template fails to render.
+    ///
+    /// This error occurs when the template contains invalid syntax or the
+    /// context is missing values that the template requires.
+    #[error("error-notification-2 Unable to render notification template: {0:?}")]
+    RenderFailed(minijinja::Error),
+}
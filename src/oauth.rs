@@ -1,8 +1,5 @@
-use dpop::DpopRetry;
 use p256::SecretKey;
 use rand::distributions::{Alphanumeric, DistString};
-use reqwest_chain::ChainMiddleware;
-use reqwest_middleware::ClientBuilder;
 use std::time::Duration;
 
 use crate::oauth_client_errors::OAuthClientError;
@@ -10,18 +7,68 @@ use crate::oauth_errors::{AuthServerValidationError, ResourceValidationError};
 use model::{AuthorizationServer, OAuthProtectedResource, ParResponse, TokenResponse};
 
 use crate::{
+    atproto::client::DpopRequestBuilder,
     jose::{
         jwt::{Claims, Header, JoseClaims},
         mint_token,
     },
     storage::{
+        cache::{pds_resources_get, pds_resources_set},
         handle::model::Handle,
         oauth::model::{OAuthRequest, OAuthRequestState},
+        CachePool,
     },
 };
 
 const HTTP_CLIENT_TIMEOUT_SECS: u64 = 8;
 
+/// How long the OAuth metadata of a PDS is cached. Popular PDSes are fetched
+/// again before this runs out by the PDS resources task.
+pub const PDS_RESOURCES_TTL_SECS: u64 = 60 * 60;
+
+/// Gets the OAuth metadata of a PDS from the cache, fetching and caching it
+/// when it isn't cached. Cache failures are logged and the metadata is
+/// fetched instead, so that a cache outage doesn't stop sign-ins.
+pub async fn cached_pds_resources(
+    http_client: &reqwest::Client,
+    cache_pool: &CachePool,
+    pds: &str,
+) -> Result<(OAuthProtectedResource, AuthorizationServer), OAuthClientError> {
+    match pds_resources_get(cache_pool, pds).await {
+        Ok(Some(value)) => match serde_json::from_str(&value) {
+            Ok(resources) => return Ok(resources),
+            Err(err) => tracing::warn!(pds, err = ?err, "unable to parse cached pds resources"),
+        },
+        Ok(None) => {}
+        Err(err) => tracing::warn!(pds, err = ?err, "unable to read cached pds resources"),
+    }
+
+    refresh_pds_resources(http_client, cache_pool, pds).await
+}
+
+/// Fetches the OAuth metadata of a PDS and caches it for
+/// `PDS_RESOURCES_TTL_SECS`.
+pub async fn refresh_pds_resources(
+    http_client: &reqwest::Client,
+    cache_pool: &CachePool,
+    pds: &str,
+) -> Result<(OAuthProtectedResource, AuthorizationServer), OAuthClientError> {
+    let resources = pds_resources(http_client, pds).await?;
+
+    match serde_json::to_string(&resources) {
+        Ok(value) => {
+            if let Err(err) =
+                pds_resources_set(cache_pool, pds, &value, PDS_RESOURCES_TTL_SECS).await
+            {
+                tracing::warn!(pds, err = ?err, "unable to cache pds resources");
+            }
+        }
+        Err(err) => tracing::warn!(pds, err = ?err, "unable to serialize pds resources"),
+    }
+
+    Ok(resources)
+}
+
 pub async fn pds_resources(
     http_client: &reqwest::Client,
     pds: &str,
@@ -221,38 +268,6 @@ pub async fn oauth_init(
     )
     .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?;
 
-    let now = chrono::Utc::now();
-    let public_key = dpop_secret_key.public_key();
-
-    let dpop_proof_header = Header {
-        type_: Some("dpop+jwt".to_string()),
-        algorithm: Some("ES256".to_string()),
-        json_web_key: Some(public_key.to_jwk()),
-        ..Default::default()
-    };
-    let dpop_proof_jti = Alphanumeric.sample_string(&mut rand::thread_rng(), 30);
-
-    let dpop_proof_claim = Claims::new(JoseClaims {
-        json_web_token_id: Some(dpop_proof_jti),
-        http_method: Some("POST".to_string()),
-        http_uri: Some(par_url.clone()),
-        issued_at: Some(now.timestamp() as u64),
-        expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
-        ..Default::default()
-    });
-    let dpop_proof_token = mint_token(dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)
-        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?;
-
-    let dpop_retry = DpopRetry::new(
-        dpop_proof_header.clone(),
-        dpop_proof_claim.clone(),
-        dpop_secret_key.clone(),
-    );
-
-    let dpop_retry_client = ClientBuilder::new(http_client.clone())
-        .with(ChainMiddleware::new(dpop_retry.clone()))
-        .build();
-
     let params = [
         ("response_type", "code"),
         ("code_challenge", &oauth_request_state.code_challenge),
@@ -271,9 +286,9 @@ pub async fn oauth_init(
 
     tracing::warn!("params: {:?}", params);
 
-    dpop_retry_client
-        .post(par_url)
-        .header("DPoP", dpop_proof_token.as_str())
+    DpopRequestBuilder::new(http_client, dpop_secret_key)
+        .request(reqwest::Method::POST, &par_url)
+        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?
         .form(&params)
         .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
         .send()
@@ -284,8 +299,10 @@ pub async fn oauth_init(
         .map_err(OAuthClientError::MalformedPARResponse)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn oauth_complete(
     http_client: &reqwest::Client,
+    cache_pool: &CachePool,
     external_url_base: &str,
     (secret_key_id, secret_key): (&str, SecretKey),
     callback_code: &str,
@@ -293,7 +310,8 @@ pub async fn oauth_complete(
     handle: &Handle,
     dpop_secret_key: &SecretKey,
 ) -> Result<TokenResponse, OAuthClientError> {
-    let (_, authorization_server) = pds_resources(http_client, &handle.pds).await?;
+    let (_, authorization_server) =
+        cached_pds_resources(http_client, cache_pool, &handle.pds).await?;
 
     let client_assertion_header = Header {
         algorithm: Some("ES256".to_string()),
@@ -334,43 +352,11 @@ pub async fn oauth_complete(
         ("client_assertion", client_assertion_token.as_str()),
     ];
 
-    let public_key = dpop_secret_key.public_key();
-
     let token_endpoint = authorization_server.token_endpoint.clone();
 
-    let now = chrono::Utc::now();
-
-    let dpop_proof_header = Header {
-        type_: Some("dpop+jwt".to_string()),
-        algorithm: Some("ES256".to_string()),
-        json_web_key: Some(public_key.to_jwk()),
-        ..Default::default()
-    };
-    let dpop_proof_jti = Alphanumeric.sample_string(&mut rand::thread_rng(), 30);
-    let dpop_proof_claim = Claims::new(JoseClaims {
-        json_web_token_id: Some(dpop_proof_jti),
-        http_method: Some("POST".to_string()),
-        http_uri: Some(authorization_server.token_endpoint.clone()),
-        issued_at: Some(now.timestamp() as u64),
-        expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
-        ..Default::default()
-    });
-    let dpop_proof_token = mint_token(dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)
-        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?;
-
-    let dpop_retry = DpopRetry::new(
-        dpop_proof_header.clone(),
-        dpop_proof_claim.clone(),
-        dpop_secret_key.clone(),
-    );
-
-    let dpop_retry_client = ClientBuilder::new(http_client.clone())
-        .with(ChainMiddleware::new(dpop_retry.clone()))
-        .build();
-
-    dpop_retry_client
-        .post(token_endpoint)
-        .header("DPoP", dpop_proof_token.as_str())
+    DpopRequestBuilder::new(http_client, dpop_secret_key)
+        .request(reqwest::Method::POST, &token_endpoint)
+        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?
         .form(&params)
         .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
         .send()
@@ -383,13 +369,15 @@ pub async fn oauth_complete(
 
 pub async fn client_oauth_refresh(
     http_client: &reqwest::Client,
+    cache_pool: &CachePool,
     external_url_base: &str,
     (secret_key_id, secret_key): (&str, SecretKey),
     refresh_token: &str,
     handle: &Handle,
     dpop_secret_key: &SecretKey,
 ) -> Result<TokenResponse, OAuthClientError> {
-    let (_, authorization_server) = pds_resources(http_client, &handle.pds).await?;
+    let (_, authorization_server) =
+        cached_pds_resources(http_client, cache_pool, &handle.pds).await?;
 
     let client_assertion_header = Header {
         algorithm: Some("ES256".to_string()),
@@ -431,43 +419,11 @@ pub async fn client_oauth_refresh(
 
     tracing::info!("params: {:?}", params);
 
-    let public_key = dpop_secret_key.public_key();
-
     let token_endpoint = authorization_server.token_endpoint.clone();
 
-    let now = chrono::Utc::now();
-
-    let dpop_proof_header = Header {
-        type_: Some("dpop+jwt".to_string()),
-        algorithm: Some("ES256".to_string()),
-        json_web_key: Some(public_key.to_jwk()),
-        ..Default::default()
-    };
-    let dpop_proof_jti = Alphanumeric.sample_string(&mut rand::thread_rng(), 30);
-    let dpop_proof_claim = Claims::new(JoseClaims {
-        json_web_token_id: Some(dpop_proof_jti),
-        http_method: Some("POST".to_string()),
-        http_uri: Some(authorization_server.token_endpoint.clone()),
-        issued_at: Some(now.timestamp() as u64),
-        expiration: Some((now + chrono::Duration::seconds(30)).timestamp() as u64),
-        ..Default::default()
-    });
-    let dpop_proof_token = mint_token(dpop_secret_key, &dpop_proof_header, &dpop_proof_claim)
-        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?;
-
-    let dpop_retry = DpopRetry::new(
-        dpop_proof_header.clone(),
-        dpop_proof_claim.clone(),
-        dpop_secret_key.clone(),
-    );
-
-    let dpop_retry_client = ClientBuilder::new(http_client.clone())
-        .with(ChainMiddleware::new(dpop_retry.clone()))
-        .build();
-
-    dpop_retry_client
-        .post(token_endpoint)
-        .header("DPoP", dpop_proof_token.as_str())
+    DpopRequestBuilder::new(http_client, dpop_secret_key)
+        .request(reqwest::Method::POST, &token_endpoint)
+        .map_err(|jose_err| OAuthClientError::MintTokenFailed(jose_err.into()))?
         .form(&params)
         .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
         .send()
@@ -610,9 +566,9 @@ pub mod dpop {
 }
 
 pub mod model {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Clone, Deserialize)]
+    #[derive(Clone, Deserialize, Serialize)]
     pub struct OAuthProtectedResource {
         pub resource: String,
         pub authorization_servers: Vec<String>,
@@ -620,7 +576,7 @@ pub mod model {
         pub bearer_methods_supported: Vec<String>,
     }
 
-    #[derive(Clone, Deserialize, Default, Debug)]
+    #[derive(Clone, Deserialize, Serialize, Default, Debug)]
     pub struct AuthorizationServer {
         pub introspection_endpoint: String,
         pub authorization_endpoint: String,
@@ -661,4 +617,4 @@ pub mod model {
 // Use crate::oauth_client_errors::OAuthClientError instead.
 pub mod errors {
     pub use crate::oauth_client_errors::OAuthClientError;
-}
\ No newline at end of file
+}
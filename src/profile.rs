@@ -0,0 +1,77 @@
+//! Bluesky profiles of known identities, so that pages can show avatars and
+//! display names instead of bare handles.
+//!
+//! Profiles are fetched from the AppView and kept with the handle, so lists
+//! of events and RSVPs are hydrated without any requests. A stored profile
+//! is fetched again once it is older than `PROFILE_TTL`.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    atproto::appview::get_profile,
+    storage::{
+        handle::{handle_update_profile, model::Handle},
+        StoragePool,
+    },
+};
+
+/// How long a fetched profile is used before it is fetched again.
+pub const PROFILE_TTL: Duration = Duration::hours(24);
+
+/// The longest display name kept, matching the limit of
+/// `app.bsky.actor.profile`.
+const MAX_DISPLAY_NAME_CHARS: usize = 64;
+
+/// The longest description kept, matching the limit of
+/// `app.bsky.actor.profile`.
+const MAX_DESCRIPTION_CHARS: usize = 256;
+
+/// Returns true when the profile of a handle hasn't been fetched, or was
+/// fetched longer than `PROFILE_TTL` ago.
+pub fn profile_is_stale(handle: &Handle, now: DateTime<Utc>) -> bool {
+    handle
+        .profile_fetched_at
+        .is_none_or(|fetched_at| fetched_at < now - PROFILE_TTL)
+}
+
+/// Fetches the profile of a handle from the AppView and stores it, returning
+/// the handle with its new profile. When the profile can't be fetched, the
+/// stored profile is kept and only fetched again after `PROFILE_TTL`, so a
+/// slow or missing profile doesn't slow down every page view.
+pub async fn refresh_profile(
+    http_client: &reqwest::Client,
+    appview_hostname: &str,
+    pool: &StoragePool,
+    mut handle: Handle,
+) -> Result<Handle> {
+    let now = Utc::now();
+
+    match get_profile(http_client, appview_hostname, &handle.did).await {
+        Ok(profile) => {
+            handle.display_name = profile
+                .display_name
+                .map(|value| value.chars().take(MAX_DISPLAY_NAME_CHARS).collect());
+            handle.description = profile
+                .description
+                .map(|value| value.chars().take(MAX_DESCRIPTION_CHARS).collect());
+            handle.avatar = profile.avatar.filter(|value| value.starts_with("https://"));
+        }
+        Err(err) => {
+            tracing::debug!(did = handle.did, error = ?err, "failed to fetch profile");
+        }
+    }
+
+    handle_update_profile(
+        pool,
+        &handle.did,
+        handle.display_name.as_deref(),
+        handle.description.as_deref(),
+        handle.avatar.as_deref(),
+        now,
+    )
+    .await?;
+    handle.profile_fetched_at = Some(now);
+
+    Ok(handle)
+}
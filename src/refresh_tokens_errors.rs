@@ -26,4 +26,12 @@ pub enum RefreshError {
     /// used to manage session refresh operations.
     #[error("error-refresh-3 Failed to place session group into refresh queue: {0:?}")]
     PlaceInRefreshQueueFailed(deadpool_redis::redis::RedisError),
+
+    /// Error when a user's PDS has moved to a different authorization server.
+    ///
+    /// This error occurs when the DID document points to a new PDS whose
+    /// authorization server did not issue the session's refresh token, so the
+    /// user has to log in again.
+    #[error("error-refresh-4 Authorization server changed from {0} to {1}")]
+    AuthorizationServerChanged(String, String),
 }
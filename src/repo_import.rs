@@ -0,0 +1,306 @@
+//! Imports the events and RSVPs of a repository from a single download of
+//! the whole repository, instead of listing each collection a page at a
+//! time. This is much faster for repositories with many records.
+
+use serde::Serialize;
+
+use crate::{
+    atproto::{
+        car::Car,
+        client::get_repo,
+        lexicon::{
+            community::lexicon::calendar::{
+                event::{Event as LexiconCommunityEvent, NSID as LEXICON_COMMUNITY_EVENT_NSID},
+                rsvp::{
+                    Rsvp as LexiconCommunityRsvp, RsvpStatus as LexiconCommunityRsvpStatus,
+                    NSID as LEXICON_COMMUNITY_RSVP_NSID,
+                },
+            },
+            events::smokesignal::calendar::{
+                event::{Event as SmokeSignalEvent, NSID as SMOKESIGNAL_EVENT_NSID},
+                rsvp::{
+                    Rsvp as SmokeSignalRsvp, RsvpStatus as SmokeSignalRsvpStatus,
+                    NSID as SMOKESIGNAL_RSVP_NSID,
+                },
+            },
+        },
+    },
+    geocoder::Geocoder,
+    http::errors::ImportError,
+    storage::{
+        event::{
+            event_upsert_with_metadata, events_insert_many, rsvp_insert_with_metadata,
+            rsvps_insert_many, EventInsertParams, RsvpInsertParams,
+        },
+        handle::model::Handle,
+        StoragePool,
+    },
+};
+
+/// The largest repository imported at once. Repositories hold every record
+/// of an identity, not only events, so this is much larger than any calendar.
+pub const MAX_REPO_BYTES: usize = 256 * 1024 * 1024;
+
+/// The collections imported from a repository, events before RSVPs so that
+/// RSVPs to the identity's own events find them.
+pub const IMPORTED_COLLECTIONS: [&str; 4] = [
+    LEXICON_COMMUNITY_EVENT_NSID,
+    SMOKESIGNAL_EVENT_NSID,
+    LEXICON_COMMUNITY_RSVP_NSID,
+    SMOKESIGNAL_RSVP_NSID,
+];
+
+/// Records are stored in batches of this many, each in one transaction.
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// How many records an import wrote, and how many couldn't be read or
+/// stored.
+#[derive(Debug, Default, Serialize)]
+pub struct RepoImportSummary {
+    pub events: usize,
+    pub rsvps: usize,
+    pub failed: usize,
+}
+
+/// Downloads the repository of an identity and stores its events and RSVPs.
+/// Events that are already stored are updated. Records that can't be read or
+/// stored are logged and counted, and don't stop the import.
+pub async fn import_repo(
+    pool: &StoragePool,
+    http_client: &reqwest::Client,
+    geocoder: Option<&Geocoder>,
+    handle: &Handle,
+) -> Result<RepoImportSummary, ImportError> {
+    let content = get_repo(http_client, &handle.pds, &handle.did, MAX_REPO_BYTES)
+        .await
+        .map_err(|err| ImportError::FailedToDownloadRepository(err.to_string()))?;
+
+    // Walking the tree of a large repository is slow enough to hold up other
+    // requests, so it is done off the async workers.
+    let did = handle.did.clone();
+    let records = tokio::task::spawn_blocking(move || {
+        Car::read(&content).and_then(|car| car.records(&did, &IMPORTED_COLLECTIONS))
+    })
+    .await
+    .map_err(|err| ImportError::FailedToReadRepository(err.to_string()))?
+    .map_err(|err| ImportError::FailedToReadRepository(err.to_string()))?;
+
+    let mut events = vec![];
+    let mut rsvps = vec![];
+    let mut summary = RepoImportSummary::default();
+
+    for record in records {
+        let aturi = record.aturi(&handle.did);
+        match parse_record(&record.collection, record.value) {
+            Ok(ParsedRecord::Event {
+                record: value,
+                name,
+            }) => {
+                events.push((aturi, record.cid, record.collection, value, name));
+            }
+            Ok(ParsedRecord::Rsvp {
+                record: value,
+                event_aturi,
+                event_cid,
+                status,
+            }) => {
+                rsvps.push((
+                    aturi,
+                    record.cid,
+                    record.collection,
+                    value,
+                    event_aturi,
+                    event_cid,
+                    status,
+                ));
+            }
+            Err(err) => {
+                tracing::error!(aturi, ?err, "unable to read record");
+                summary.failed += 1;
+            }
+        }
+    }
+
+    // Events are stored before RSVPs so that RSVPs to the identity's own
+    // events find them.
+    for chunk in events.chunks(IMPORT_BATCH_SIZE) {
+        let params = chunk
+            .iter()
+            .map(|(aturi, cid, collection, record, name)| EventInsertParams {
+                aturi,
+                cid,
+                did: &handle.did,
+                lexicon: collection,
+                record,
+                name,
+            })
+            .collect::<Vec<_>>();
+
+        match events_insert_many(pool, &params).await {
+            Ok(()) => {
+                summary.events += params.len();
+                if let Some(geocoder) = geocoder {
+                    for event in &params {
+                        geocoder.spawn_geocode_event(pool, event.aturi);
+                    }
+                }
+            }
+            Err(err) => {
+                tracing::error!(?err, "unable to import events");
+                summary.failed += params.len();
+            }
+        }
+    }
+
+    for chunk in rsvps.chunks(IMPORT_BATCH_SIZE) {
+        let params = chunk
+            .iter()
+            .map(
+                |(aturi, cid, collection, record, event_aturi, event_cid, status)| {
+                    RsvpInsertParams {
+                        aturi,
+                        cid,
+                        did: &handle.did,
+                        lexicon: collection,
+                        record,
+                        event_aturi,
+                        event_cid,
+                        status,
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        match rsvps_insert_many(pool, &params).await {
+            Ok(()) => summary.rsvps += params.len(),
+            Err(err) => {
+                tracing::error!(?err, "unable to import rsvps");
+                summary.failed += params.len();
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// An event or RSVP record that was read, with the details stored next to it.
+enum ParsedRecord {
+    Event {
+        record: serde_json::Value,
+        name: String,
+    },
+    Rsvp {
+        record: serde_json::Value,
+        event_aturi: String,
+        event_cid: String,
+        status: &'static str,
+    },
+}
+
+/// Reads an event or RSVP record of one of the imported collections.
+fn parse_record(collection: &str, value: serde_json::Value) -> Result<ParsedRecord, anyhow::Error> {
+    match collection {
+        LEXICON_COMMUNITY_EVENT_NSID => {
+            let event: LexiconCommunityEvent = serde_json::from_value(value)?;
+            let LexiconCommunityEvent::Current { name, .. } = &event;
+            Ok(ParsedRecord::Event {
+                name: name.clone(),
+                record: serde_json::to_value(&event)?,
+            })
+        }
+        SMOKESIGNAL_EVENT_NSID => {
+            let event: SmokeSignalEvent = serde_json::from_value(value)?;
+            let SmokeSignalEvent::Current { name, .. } = &event;
+            Ok(ParsedRecord::Event {
+                name: name.clone(),
+                record: serde_json::to_value(&event)?,
+            })
+        }
+        LEXICON_COMMUNITY_RSVP_NSID => {
+            let rsvp: LexiconCommunityRsvp = serde_json::from_value(value)?;
+            let LexiconCommunityRsvp::Current {
+                subject, status, ..
+            } = &rsvp;
+            let status = match status {
+                LexiconCommunityRsvpStatus::Going => "going",
+                LexiconCommunityRsvpStatus::Interested => "interested",
+                LexiconCommunityRsvpStatus::NotGoing => "notgoing",
+            };
+            Ok(ParsedRecord::Rsvp {
+                event_aturi: subject.uri.clone(),
+                event_cid: subject.cid.clone(),
+                status,
+                record: serde_json::to_value(&rsvp)?,
+            })
+        }
+        SMOKESIGNAL_RSVP_NSID => {
+            let rsvp: SmokeSignalRsvp = serde_json::from_value(value)?;
+            let SmokeSignalRsvp::Current {
+                subject, status, ..
+            } = &rsvp;
+            let status = match status {
+                SmokeSignalRsvpStatus::Going => "going",
+                SmokeSignalRsvpStatus::Interested => "interested",
+                SmokeSignalRsvpStatus::NotGoing => "notgoing",
+            };
+            Ok(ParsedRecord::Rsvp {
+                event_aturi: subject.uri.clone(),
+                event_cid: subject.cid.clone(),
+                status,
+                record: serde_json::to_value(&rsvp)?,
+            })
+        }
+        collection => Err(ImportError::UnsupportedCollectionType(collection.to_string()).into()),
+    }
+}
+
+/// What storing one record of an identity did.
+pub enum ImportedRecord {
+    /// An event, and whether it was written. Events already stored with the
+    /// same CID are left as they are.
+    Event {
+        written: bool,
+    },
+    Rsvp,
+}
+
+/// Stores one event or RSVP record of an identity.
+pub async fn import_record(
+    pool: &StoragePool,
+    did: &str,
+    aturi: &str,
+    collection: &str,
+    cid: &str,
+    value: serde_json::Value,
+) -> Result<ImportedRecord, anyhow::Error> {
+    match parse_record(collection, value)? {
+        ParsedRecord::Event { record, name } => {
+            let written =
+                event_upsert_with_metadata(pool, aturi, cid, did, collection, &record, &name)
+                    .await?;
+            Ok(ImportedRecord::Event { written })
+        }
+        ParsedRecord::Rsvp {
+            record,
+            event_aturi,
+            event_cid,
+            status,
+        } => {
+            rsvp_insert_with_metadata(
+                pool,
+                RsvpInsertParams {
+                    aturi,
+                    cid,
+                    did,
+                    lexicon: collection,
+                    record: &record,
+                    event_aturi: &event_aturi,
+                    event_cid: &event_cid,
+                    status,
+                },
+            )
+            .await?;
+            Ok(ImportedRecord::Rsvp)
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! Mentions and links in event descriptions.
+//!
+//! Like Bluesky posts, the handles and URLs in a description are found when
+//! the event is saved and kept as rich text facets next to the text, with
+//! mentions resolved to DIDs. When the event is shown, mentions link to the
+//! current handle of the account, so they keep working after handle changes.
+
+use std::collections::{HashMap, HashSet};
+
+use ammonia::Builder;
+use hickory_resolver::TokioAsyncResolver;
+use minijinja::HtmlEscape;
+
+use crate::atproto::lexicon::{
+    app::bsky::feed::post::{ByteSlice, Facet, FacetFeature},
+    community::lexicon::calendar::event::EXTRA_DESCRIPTION_FACETS,
+};
+use crate::resolve::resolve_handle;
+
+/// What was found in a range of the text, before mentions are resolved.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DetectedFeature {
+    Mention(String),
+    Link(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DetectedFacet {
+    pub index: ByteSlice,
+    pub feature: DetectedFeature,
+}
+
+/// Finds the `@handle` mentions and `http(s)://` links in a text. Trailing
+/// punctuation isn't part of either, and neither is a closing parenthesis
+/// without an opening one.
+pub fn detect_facets(text: &str) -> Vec<DetectedFacet> {
+    let mut facets = Vec::new();
+    let mut word_start = None;
+
+    for (offset, value) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        if value.is_whitespace() {
+            if let Some(start) = word_start.take() {
+                if let Some(facet) = detect_word(&text[start..offset], start) {
+                    facets.push(facet);
+                }
+            }
+        } else if word_start.is_none() {
+            word_start = Some(offset);
+        }
+    }
+
+    facets
+}
+
+fn detect_word(word: &str, offset: usize) -> Option<DetectedFacet> {
+    let (word, offset) = match word.strip_prefix('(') {
+        Some(value) => (value, offset + 1),
+        None => (word, offset),
+    };
+
+    if let Some(handle) = word.strip_prefix('@') {
+        let handle =
+            trim_trailing_punctuation(trim_trailing_punctuation(handle).trim_end_matches(')'));
+        if !is_handle(handle) {
+            return None;
+        }
+        return Some(DetectedFacet {
+            index: ByteSlice {
+                byte_start: offset,
+                byte_end: offset + 1 + handle.len(),
+            },
+            feature: DetectedFeature::Mention(handle.to_lowercase()),
+        });
+    }
+
+    if word.starts_with("https://") || word.starts_with("http://") {
+        let mut uri = trim_trailing_punctuation(word);
+        while uri.ends_with(')') && !uri.contains('(') {
+            uri = trim_trailing_punctuation(&uri[..uri.len() - 1]);
+        }
+        let has_host = url::Url::parse(uri)
+            .ok()
+            .is_some_and(|value| value.host_str().is_some_and(|host| !host.is_empty()));
+        if !has_host {
+            return None;
+        }
+        return Some(DetectedFacet {
+            index: ByteSlice {
+                byte_start: offset,
+                byte_end: offset + uri.len(),
+            },
+            feature: DetectedFeature::Link(uri.to_string()),
+        });
+    }
+
+    None
+}
+
+fn trim_trailing_punctuation(value: &str) -> &str {
+    value.trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\''])
+}
+
+/// Returns true for a syntactically valid handle: at least two labels of
+/// letters, digits and inner hyphens, and a top level domain that starts
+/// with a letter.
+fn is_handle(value: &str) -> bool {
+    if value.len() > 253 || !value.contains('.') {
+        return false;
+    }
+    let valid_labels = value.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    valid_labels
+        && value
+            .rsplit('.')
+            .next()
+            .is_some_and(|tld| tld.starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// Finds the facets of a description, resolving each mentioned handle to
+/// its DID. Mentions of handles that don't resolve are left as plain text.
+pub async fn description_facets(
+    http_client: &reqwest::Client,
+    dns_resolver: &TokioAsyncResolver,
+    text: &str,
+) -> Vec<Facet> {
+    let detected = detect_facets(text);
+
+    let handles = detected
+        .iter()
+        .filter_map(|facet| match &facet.feature {
+            DetectedFeature::Mention(handle) => Some(handle.as_str()),
+            DetectedFeature::Link(_) => None,
+        })
+        .collect::<HashSet<&str>>();
+
+    let mut dids = HashMap::new();
+    for handle in handles {
+        match resolve_handle(http_client, dns_resolver, handle).await {
+            Ok(did) => {
+                dids.insert(handle.to_string(), did);
+            }
+            Err(err) => {
+                tracing::debug!(handle, error = ?err, "unable to resolve mentioned handle");
+            }
+        }
+    }
+
+    detected
+        .into_iter()
+        .filter_map(|facet| {
+            let feature = match facet.feature {
+                DetectedFeature::Mention(handle) => FacetFeature::Mention {
+                    did: dids.get(&handle)?.clone(),
+                },
+                DetectedFeature::Link(uri) => FacetFeature::Link { uri },
+            };
+            Some(Facet {
+                index: facet.index,
+                features: vec![feature],
+            })
+        })
+        .collect()
+}
+
+/// Sets or removes the description facets in the fields of an event record
+/// that aren't otherwise modeled.
+pub fn set_description_facets(extra: &mut HashMap<String, serde_json::Value>, facets: &[Facet]) {
+    match serde_json::to_value(facets) {
+        Ok(value) if !facets.is_empty() => {
+            extra.insert(EXTRA_DESCRIPTION_FACETS.to_string(), value);
+        }
+        _ => {
+            extra.remove(EXTRA_DESCRIPTION_FACETS);
+        }
+    }
+}
+
+/// The DIDs mentioned by a list of facets.
+pub fn mentioned_dids(facets: &[Facet]) -> Vec<String> {
+    let mut dids = Vec::new();
+    for feature in facets.iter().flat_map(|facet| facet.features.iter()) {
+        if let FacetFeature::Mention { did } = feature {
+            if !dids.contains(did) {
+                dids.push(did.clone());
+            }
+        }
+    }
+    dids
+}
+
+/// Renders a description as HTML, with its facets as links. Mentions show
+/// the current handle of the DID when it is in `handles`, and otherwise the
+/// text that was written. Facets that overlap an earlier one or don't fall
+/// on character boundaries are ignored.
+pub fn render_description(
+    text: &str,
+    facets: &[Facet],
+    handles: &HashMap<String, String>,
+) -> String {
+    let cleaner = {
+        let mut builder = Builder::new();
+        builder.tags(HashSet::new());
+        builder
+    };
+    let clean = |value: &str| cleaner.clean(value).to_string();
+
+    let mut facets = facets.iter().collect::<Vec<&Facet>>();
+    facets.sort_by_key(|facet| facet.index.byte_start);
+
+    let mut html = String::new();
+    let mut cursor = 0;
+    for facet in facets {
+        let ByteSlice {
+            byte_start,
+            byte_end,
+        } = facet.index;
+        if byte_start < cursor || byte_start >= byte_end {
+            continue;
+        }
+        let (Some(before), Some(segment)) =
+            (text.get(cursor..byte_start), text.get(byte_start..byte_end))
+        else {
+            continue;
+        };
+
+        let anchor = facet.features.iter().find_map(|feature| match feature {
+            FacetFeature::Mention { did } => Some(match handles.get(did) {
+                Some(handle) => format!(
+                    "<a href=\"/{}\">@{}</a>",
+                    HtmlEscape(handle),
+                    HtmlEscape(handle)
+                ),
+                None => format!("<a href=\"/{}\">{}</a>", HtmlEscape(did), clean(segment)),
+            }),
+            FacetFeature::Link { uri }
+                if uri.starts_with("https://") || uri.starts_with("http://") =>
+            {
+                Some(format!(
+                    "<a href=\"{}\" rel=\"nofollow\" target=\"blank\">{}</a>",
+                    HtmlEscape(uri),
+                    clean(segment)
+                ))
+            }
+            FacetFeature::Link { .. } => None,
+        });
+        let Some(anchor) = anchor else {
+            continue;
+        };
+
+        html.push_str(&clean(before));
+        html.push_str(&anchor);
+        cursor = byte_end;
+    }
+    html.push_str(&clean(&text[cursor..]));
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facet(byte_start: usize, byte_end: usize, feature: FacetFeature) -> Facet {
+        Facet {
+            index: ByteSlice {
+                byte_start,
+                byte_end,
+            },
+            features: vec![feature],
+        }
+    }
+
+    #[test]
+    fn test_detect_facets() {
+        let text = "Hosted by @Alice.example.com and (@bob.test). Café ☕ at https://example.com/a_(b), or https://example.com/c?d=e. Not @nodot or @-bad.com or email@x.com.";
+
+        let facets = detect_facets(text);
+        let found = facets
+            .iter()
+            .map(|facet| &text[facet.index.byte_start..facet.index.byte_end])
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            found,
+            vec![
+                "@Alice.example.com",
+                "@bob.test",
+                "https://example.com/a_(b)",
+                "https://example.com/c?d=e",
+            ]
+        );
+        assert_eq!(
+            facets[0].feature,
+            DetectedFeature::Mention("alice.example.com".to_string())
+        );
+        assert_eq!(
+            facets[3].feature,
+            DetectedFeature::Link("https://example.com/c?d=e".to_string())
+        );
+
+        assert_eq!(
+            detect_facets("(see https://example.com/x)")[0].feature,
+            DetectedFeature::Link("https://example.com/x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_description_facets() {
+        let mut extra = HashMap::new();
+        let facets = vec![facet(
+            0,
+            4,
+            FacetFeature::Mention {
+                did: "did:plc:abc".to_string(),
+            },
+        )];
+
+        set_description_facets(&mut extra, &facets);
+        assert_eq!(
+            extra[EXTRA_DESCRIPTION_FACETS],
+            serde_json::json!([{
+                "index": { "byteStart": 0, "byteEnd": 4 },
+                "features": [{ "$type": "app.bsky.richtext.facet#mention", "did": "did:plc:abc" }],
+            }])
+        );
+
+        set_description_facets(&mut extra, &[]);
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn test_render_description() {
+        let text = "Café with @old.example.com <b>& friends</b>, see https://example.com?a=1&b=2 or @gone.test";
+        let mention_start = text.find("@old").unwrap();
+        let link_start = text.find("https").unwrap();
+        let gone_start = text.find("@gone").unwrap();
+        let facets = vec![
+            facet(
+                gone_start,
+                text.len(),
+                FacetFeature::Mention {
+                    did: "did:plc:gone".to_string(),
+                },
+            ),
+            facet(
+                mention_start,
+                mention_start + 16,
+                FacetFeature::Mention {
+                    did: "did:plc:abc".to_string(),
+                },
+            ),
+            facet(
+                link_start,
+                link_start + 27,
+                FacetFeature::Link {
+                    uri: "https://example.com?a=1&b=2".to_string(),
+                },
+            ),
+            // Overlaps the link
+            facet(
+                link_start + 1,
+                link_start + 5,
+                FacetFeature::Link {
+                    uri: "https://example.net".to_string(),
+                },
+            ),
+        ];
+        let handles = HashMap::from([("did:plc:abc".to_string(), "new.example.com".to_string())]);
+
+        assert_eq!(
+            render_description(text, &facets, &handles),
+            "Café with <a href=\"/new.example.com\">@new.example.com</a> &amp; friends, see <a href=\"https:&#x2f;&#x2f;example.com?a=1&amp;b=2\" rel=\"nofollow\" target=\"blank\">https://example.com?a=1&amp;b=2</a> or <a href=\"/did:plc:gone\">@gone.test</a>"
+        );
+
+        // Facets outside of the text are ignored
+        let facets = vec![facet(
+            0,
+            400,
+            FacetFeature::Link {
+                uri: "https://example.com".to_string(),
+            },
+        )];
+        assert_eq!(render_description("a < b", &facets, &handles), "a &lt; b");
+    }
+}
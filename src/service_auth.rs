@@ -0,0 +1,332 @@
+//! ATProto inter-service authentication.
+//!
+//! Service auth tokens are short-lived JWTs where the issuer is the DID of the
+//! calling account or service, the audience is the DID of the receiving
+//! service, and the `lxm` claim binds the token to a single XRPC method. They
+//! are signed with the issuer's atproto signing key, which is published in the
+//! issuer's DID document as a `Multikey` verification method.
+//!
+//! This instance identifies itself as `did:web:<EXTERNAL_BASE>` and signs its
+//! own tokens with the configured service key.
+
+use base64::{engine::general_purpose, Engine as _};
+use elliptic_curve::sec1::ToEncodedPoint;
+use p256::SecretKey;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::did::{plc, web};
+use crate::jose::jwt::{Claims, Header, JoseClaims};
+use crate::jose::mint_token;
+use crate::service_auth_errors::ServiceAuthError;
+
+/// Default lifetime of service auth tokens minted by this instance.
+pub const SERVICE_TOKEN_LIFETIME_SECS: i64 = 60;
+
+/// Multicodec prefix (varint encoded) for a compressed P-256 public key.
+const MULTICODEC_P256_PUB: [u8; 2] = [0x80, 0x24];
+
+/// Multicodec prefix (varint encoded) for a compressed secp256k1 public key.
+const MULTICODEC_SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+
+/// Returns the `did:web` identifier of this instance.
+pub fn service_did(external_base: &str) -> String {
+    format!("did:web:{}", external_base)
+}
+
+/// A public key that can verify service auth token signatures.
+#[derive(Clone, Debug)]
+pub enum PublicSigningKey {
+    P256(p256::PublicKey),
+    K256(k256::PublicKey),
+}
+
+impl PublicSigningKey {
+    /// Decodes a `publicKeyMultibase` value from a DID document.
+    pub fn from_multibase(value: &str) -> Result<Self, ServiceAuthError> {
+        let encoded = value
+            .strip_prefix('z')
+            .ok_or(ServiceAuthError::InvalidPublicKey)?;
+        let decoded = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|_| ServiceAuthError::InvalidPublicKey)?;
+
+        if let Some(key_bytes) = decoded.strip_prefix(&MULTICODEC_P256_PUB) {
+            return p256::PublicKey::from_sec1_bytes(key_bytes)
+                .map(PublicSigningKey::P256)
+                .map_err(|_| ServiceAuthError::InvalidPublicKey);
+        }
+
+        if let Some(key_bytes) = decoded.strip_prefix(&MULTICODEC_SECP256K1_PUB) {
+            return k256::PublicKey::from_sec1_bytes(key_bytes)
+                .map(PublicSigningKey::K256)
+                .map_err(|_| ServiceAuthError::InvalidPublicKey);
+        }
+
+        Err(ServiceAuthError::InvalidPublicKey)
+    }
+
+    /// Encodes the key as a `publicKeyMultibase` value.
+    pub fn to_multibase(&self) -> String {
+        let mut bytes = Vec::with_capacity(35);
+        match self {
+            PublicSigningKey::P256(public_key) => {
+                bytes.extend_from_slice(&MULTICODEC_P256_PUB);
+                bytes.extend_from_slice(public_key.to_encoded_point(true).as_bytes());
+            }
+            PublicSigningKey::K256(public_key) => {
+                bytes.extend_from_slice(&MULTICODEC_SECP256K1_PUB);
+                bytes.extend_from_slice(public_key.to_encoded_point(true).as_bytes());
+            }
+        }
+        format!("z{}", bs58::encode(bytes).into_string())
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self {
+            PublicSigningKey::P256(_) => "ES256",
+            PublicSigningKey::K256(_) => "ES256K",
+        }
+    }
+
+    fn verify(&self, content: &[u8], signature: &[u8]) -> Result<(), ServiceAuthError> {
+        match self {
+            PublicSigningKey::P256(public_key) => {
+                use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+                let signature = Signature::from_slice(signature)
+                    .map_err(|_| ServiceAuthError::SignatureVerificationFailed)?;
+                VerifyingKey::from(public_key)
+                    .verify(content, &signature)
+                    .map_err(|_| ServiceAuthError::SignatureVerificationFailed)
+            }
+            PublicSigningKey::K256(public_key) => {
+                use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+                let signature = Signature::from_slice(signature)
+                    .map_err(|_| ServiceAuthError::SignatureVerificationFailed)?;
+                VerifyingKey::from(public_key)
+                    .verify(content, &signature)
+                    .map_err(|_| ServiceAuthError::SignatureVerificationFailed)
+            }
+        }
+    }
+}
+
+/// Mints a service auth token signed by this instance.
+///
+/// The `method` is the NSID of the XRPC method the token is bound to and is
+/// emitted as the `lxm` claim.
+pub fn mint_service_token(
+    secret_key: &SecretKey,
+    issuer: &str,
+    audience: &str,
+    method: Option<&str>,
+) -> Result<String, ServiceAuthError> {
+    let now = chrono::Utc::now();
+
+    let header = Header {
+        type_: Some("JWT".to_string()),
+        algorithm: Some("ES256".to_string()),
+        ..Default::default()
+    };
+
+    let mut claims = Claims::new(JoseClaims {
+        issuer: Some(issuer.to_string()),
+        audience: Some(audience.to_string()),
+        issued_at: Some(now.timestamp() as u64),
+        expiration: Some(
+            (now + chrono::Duration::seconds(SERVICE_TOKEN_LIFETIME_SECS)).timestamp() as u64,
+        ),
+        json_web_token_id: Some(ulid::Ulid::new().to_string()),
+        ..Default::default()
+    });
+
+    if let Some(method) = method {
+        claims.private.insert(
+            "lxm".to_string(),
+            serde_json::Value::String(method.to_string()),
+        );
+    }
+
+    mint_token(secret_key, &header, &claims)
+        .map_err(|err| ServiceAuthError::MintFailed(err.to_string()))
+}
+
+/// Decodes the header and claims of a token without verifying its signature.
+///
+/// This is used to discover the issuer so that its signing key can be
+/// resolved before verification.
+pub fn decode_unverified(token: &str) -> Result<(Header, Claims), ServiceAuthError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(ServiceAuthError::InvalidTokenFormat);
+    }
+
+    let header_bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(parts[0])
+        .map_err(|_| ServiceAuthError::InvalidHeader)?;
+    let header: Header =
+        serde_json::from_slice(&header_bytes).map_err(|_| ServiceAuthError::InvalidHeader)?;
+
+    let claims_bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(parts[1])
+        .map_err(|_| ServiceAuthError::InvalidClaims)?;
+    let claims: Claims =
+        serde_json::from_slice(&claims_bytes).map_err(|_| ServiceAuthError::InvalidClaims)?;
+
+    Ok((header, claims))
+}
+
+/// Verifies a service auth token against the issuer's public key.
+///
+/// The token must be signed by `public_key`, unexpired, addressed to
+/// `audience`, and, when `method` is set, bound to that XRPC method.
+pub fn verify_service_token(
+    token: &str,
+    public_key: &PublicSigningKey,
+    audience: &str,
+    method: Option<&str>,
+) -> Result<Claims, ServiceAuthError> {
+    let (header, claims) = decode_unverified(token)?;
+
+    let algorithm = header.algorithm.clone().unwrap_or_default();
+    if algorithm != public_key.algorithm() {
+        return Err(ServiceAuthError::UnsupportedAlgorithm(algorithm));
+    }
+
+    let (content, encoded_signature) = token
+        .rsplit_once('.')
+        .ok_or(ServiceAuthError::InvalidTokenFormat)?;
+    let signature = general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded_signature)
+        .map_err(|_| ServiceAuthError::SignatureVerificationFailed)?;
+
+    public_key.verify(content.as_bytes(), &signature)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| ServiceAuthError::TokenExpired)?
+        .as_secs();
+    match claims.jose.expiration {
+        Some(exp) if now < exp => {}
+        _ => return Err(ServiceAuthError::TokenExpired),
+    }
+
+    if claims.jose.issuer.as_deref().unwrap_or_default().is_empty() {
+        return Err(ServiceAuthError::MissingIssuer);
+    }
+
+    let token_audience = claims.jose.audience.clone().unwrap_or_default();
+    if token_audience != audience {
+        return Err(ServiceAuthError::AudienceMismatch(token_audience));
+    }
+
+    if let Some(method) = method {
+        let token_method = claims
+            .private
+            .get("lxm")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        if token_method != method {
+            return Err(ServiceAuthError::MethodMismatch(token_method.to_string()));
+        }
+    }
+
+    Ok(claims)
+}
+
+/// Resolves the atproto signing key of a token issuer.
+///
+/// Issuers may include a service fragment (`did:plc:abc#atproto_labeler`),
+/// which is ignored when resolving the DID document.
+pub async fn resolve_issuer_key(
+    http_client: &reqwest::Client,
+    plc_hostname: &str,
+    issuer: &str,
+) -> Result<PublicSigningKey, ServiceAuthError> {
+    let did = issuer.split_once('#').map_or(issuer, |(did, _)| did);
+
+    let document = if did.starts_with("did:plc:") {
+        plc::query(http_client, plc_hostname, did).await
+    } else if did.starts_with("did:web:") {
+        web::query(http_client, did).await
+    } else {
+        return Err(ServiceAuthError::IssuerResolutionFailed(did.to_string()));
+    }
+    .map_err(|err| ServiceAuthError::IssuerResolutionFailed(err.to_string()))?;
+
+    let multibase = document
+        .atproto_signing_key()
+        .ok_or(ServiceAuthError::SigningKeyNotFound)?;
+
+    PublicSigningKey::from_multibase(multibase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multibase_round_trip() {
+        let secret_key = SecretKey::random(&mut rand::thread_rng());
+        let public_key = PublicSigningKey::P256(secret_key.public_key());
+
+        let encoded = public_key.to_multibase();
+        assert!(encoded.starts_with("zDn"));
+
+        let decoded = PublicSigningKey::from_multibase(&encoded);
+        assert!(matches!(decoded, Ok(PublicSigningKey::P256(_))));
+    }
+
+    #[test]
+    fn test_decode_secp256k1_multibase() {
+        let decoded =
+            PublicSigningKey::from_multibase("zQ3shXvCK2RyPrSLYQjBEw5CExZkUhJH3n1K2Mb9sC7JbvRMF");
+        assert!(matches!(decoded, Ok(PublicSigningKey::K256(_))));
+    }
+
+    #[test]
+    fn test_mint_and_verify() {
+        let secret_key = SecretKey::random(&mut rand::thread_rng());
+        let public_key = PublicSigningKey::P256(secret_key.public_key());
+
+        let token = mint_service_token(
+            &secret_key,
+            "did:web:smokesignal.example",
+            "did:web:labeler.example",
+            Some("com.atproto.label.queryLabels"),
+        )
+        .unwrap();
+
+        let claims = verify_service_token(
+            &token,
+            &public_key,
+            "did:web:labeler.example",
+            Some("com.atproto.label.queryLabels"),
+        );
+        assert!(claims.is_ok());
+        assert_eq!(
+            claims.unwrap().jose.issuer.as_deref(),
+            Some("did:web:smokesignal.example")
+        );
+
+        assert!(matches!(
+            verify_service_token(&token, &public_key, "did:web:other.example", None),
+            Err(ServiceAuthError::AudienceMismatch(_))
+        ));
+        assert!(matches!(
+            verify_service_token(
+                &token,
+                &public_key,
+                "did:web:labeler.example",
+                Some("app.bsky.feed.getFeedSkeleton")
+            ),
+            Err(ServiceAuthError::MethodMismatch(_))
+        ));
+
+        let other_key =
+            PublicSigningKey::P256(SecretKey::random(&mut rand::thread_rng()).public_key());
+        assert!(matches!(
+            verify_service_token(&token, &other_key, "did:web:labeler.example", None),
+            Err(ServiceAuthError::SignatureVerificationFailed)
+        ));
+    }
+}
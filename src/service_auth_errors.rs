@@ -0,0 +1,107 @@
+use thiserror::Error;
+
+/// Represents errors that can occur when minting or verifying ATProto
+/// inter-service authentication tokens.
+///
+/// Service auth tokens are short-lived JWTs signed by the issuing service or
+/// account's atproto signing key and bound to a single audience and XRPC
+/// method.
+#[derive(Debug, Error)]
+pub enum ServiceAuthError {
+    /// Error when the Authorization header is missing or not a bearer token.
+    ///
+    /// This error occurs when an authenticated XRPC endpoint is called without
+    /// an `Authorization: Bearer <token>` header.
+    #[error("error-service-auth-1 Missing or malformed bearer token")]
+    MissingBearerToken,
+
+    /// Error when the token does not have three dot-separated parts.
+    ///
+    /// This error occurs when the provided token cannot be split into a
+    /// header, claims, and signature segment.
+    #[error("error-service-auth-2 Invalid token format")]
+    InvalidTokenFormat,
+
+    /// Error when the token header cannot be decoded.
+    ///
+    /// This error occurs when the header segment is not valid base64url
+    /// encoded JSON.
+    #[error("error-service-auth-3 Invalid token header")]
+    InvalidHeader,
+
+    /// Error when the token claims cannot be decoded.
+    ///
+    /// This error occurs when the claims segment is not valid base64url
+    /// encoded JSON.
+    #[error("error-service-auth-4 Invalid token claims")]
+    InvalidClaims,
+
+    /// Error when the token uses an algorithm that is not supported.
+    ///
+    /// Service auth tokens must be signed with ES256 (P-256) or ES256K
+    /// (secp256k1).
+    #[error("error-service-auth-5 Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    /// Error when the token signature is malformed or does not verify.
+    ///
+    /// This error occurs when the signature cannot be parsed or was not
+    /// produced by the issuer's atproto signing key.
+    #[error("error-service-auth-6 Signature verification failed")]
+    SignatureVerificationFailed,
+
+    /// Error when the token has expired or is missing an expiration.
+    ///
+    /// Service auth tokens are required to be short-lived and must always
+    /// include an `exp` claim.
+    #[error("error-service-auth-7 Token has expired")]
+    TokenExpired,
+
+    /// Error when the token is missing an issuer.
+    ///
+    /// This error occurs when the `iss` claim is absent or empty.
+    #[error("error-service-auth-8 Token issuer missing")]
+    MissingIssuer,
+
+    /// Error when the token audience does not match this service.
+    ///
+    /// This error occurs when a token minted for another service is
+    /// presented to this instance.
+    #[error("error-service-auth-9 Token audience mismatch: {0}")]
+    AudienceMismatch(String),
+
+    /// Error when the token is bound to a different XRPC method.
+    ///
+    /// This error occurs when the `lxm` claim is missing or does not match
+    /// the method being called.
+    #[error("error-service-auth-10 Token method mismatch: {0}")]
+    MethodMismatch(String),
+
+    /// Error when the issuer's DID document cannot be resolved.
+    ///
+    /// This error occurs when the PLC directory or did:web host cannot be
+    /// reached or returns an invalid document.
+    #[error("error-service-auth-11 Unable to resolve issuer DID document: {0}")]
+    IssuerResolutionFailed(String),
+
+    /// Error when the issuer's DID document has no usable signing key.
+    ///
+    /// This error occurs when the document has no `#atproto` verification
+    /// method or the key uses an unsupported multicodec.
+    #[error("error-service-auth-12 Issuer signing key not found")]
+    SigningKeyNotFound,
+
+    /// Error when a multibase encoded public key cannot be decoded.
+    ///
+    /// This error occurs when the `publicKeyMultibase` value is not base58btc
+    /// encoded or contains an invalid point.
+    #[error("error-service-auth-13 Invalid multibase public key")]
+    InvalidPublicKey,
+
+    /// Error when a service auth token cannot be minted.
+    ///
+    /// This error occurs when signing the token with the instance's service
+    /// key fails.
+    #[error("error-service-auth-14 Unable to mint service token: {0}")]
+    MintFailed(String),
+}
@@ -0,0 +1,197 @@
+use chrono::Utc;
+
+use crate::storage::{errors::StorageError, StoragePool};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventAnnouncement {
+        pub post_aturi: String,
+        pub event_aturi: String,
+        pub did: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Record a Bluesky post that announces an event
+pub async fn announcement_insert(
+    pool: &StoragePool,
+    post_aturi: &str,
+    event_aturi: &str,
+    did: &str,
+) -> Result<(), StorageError> {
+    if post_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Post URI cannot be empty".into(),
+        )));
+    }
+
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO event_announcements (post_aturi, event_aturi, did, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT(post_aturi) DO NOTHING
+        ",
+        post_aturi,
+        event_aturi,
+        did,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List announcement posts for events that have not started yet, soonest first.
+// When a locality is given, only events with a matching address are included.
+pub async fn announcement_list_upcoming(
+    pool: &StoragePool,
+    locality: Option<&str>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<String>, StorageError> {
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let posts = sqlx::query_scalar!(
+        r"SELECT
+    event_announcements.post_aturi
+FROM
+    event_announcements
+    INNER JOIN events ON events.aturi = event_announcements.event_aturi
+WHERE
+    events.starts_at >= NOW()
+    AND (
+        $1::text IS NULL
+        OR EXISTS (
+            SELECT 1 FROM json_array_elements(
+                CASE WHEN json_typeof(events.record->'locations') = 'array'
+                THEN events.record->'locations'
+                ELSE '[]'::json END
+            ) AS location
+            WHERE lower(location->>'locality') = lower($1)
+        )
+    )
+ORDER BY
+    events.starts_at ASC,
+    event_announcements.post_aturi ASC
+LIMIT $2
+OFFSET $3",
+        locality,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(posts)
+}
+
+// Get the first post that announced an event, if it was announced
+pub async fn announcement_get_for_event(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<Option<String>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let post_aturi = sqlx::query_scalar!(
+        "SELECT post_aturi FROM event_announcements WHERE event_aturi = $1 ORDER BY created_at ASC, post_aturi ASC LIMIT 1",
+        event_aturi,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(post_aturi)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_support::EventBuilder;
+
+    #[sqlx::test]
+    async fn test_announcement_list_upcoming(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        let later = EventBuilder::default()
+            .starts_at(Utc::now() + Duration::days(2))
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let sooner = EventBuilder::default()
+            .starts_at(Utc::now() + Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        let past = EventBuilder::default()
+            .starts_at(Utc::now() - Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2e")
+            .await?;
+
+        for (post, event) in [("post1", &later), ("post2", &sooner), ("post3", &past)] {
+            let post_aturi = format!("at://{}/app.bsky.feed.post/{}", organizer, post);
+            announcement_insert(&pool, &post_aturi, &event.uri, organizer).await?;
+        }
+
+        // A record with an unparseable start doesn't fail the whole feed
+        sqlx::query!(
+            "UPDATE events SET record = (record::jsonb || '{\"startsAt\": \"soon\"}')::json WHERE aturi = $1",
+            &later.uri,
+        )
+        .execute(&pool)
+        .await?;
+
+        let posts = announcement_list_upcoming(&pool, None, 1, 10).await?;
+        assert_eq!(
+            posts,
+            vec![
+                format!("at://{}/app.bsky.feed.post/post2", organizer),
+                format!("at://{}/app.bsky.feed.post/post1", organizer),
+            ]
+        );
+
+        Ok(())
+    }
+}
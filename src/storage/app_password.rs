@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::AppPasswordSession;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::Deserialize;
+    use sqlx::FromRow;
+
+    /// An app password session. Access and refresh tokens are stored
+    /// encrypted with the configured `APP_PASSWORD_KEY`.
+    #[derive(Clone, FromRow, Deserialize)]
+    pub struct AppPasswordSession {
+        pub did: String,
+        pub pds: String,
+        pub access_token: String,
+        pub refresh_token: String,
+        pub access_token_expires_at: DateTime<Utc>,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+    }
+}
+
+pub struct AppPasswordSessionParams<'a> {
+    pub did: &'a str,
+    pub pds: &'a str,
+    pub access_token: &'a str,
+    pub refresh_token: &'a str,
+    pub access_token_expires_at: DateTime<Utc>,
+}
+
+// Store the app password session of an identity, replacing any existing one
+pub async fn app_password_session_upsert(
+    pool: &StoragePool,
+    params: AppPasswordSessionParams<'_>,
+) -> Result<(), StorageError> {
+    if params.did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if params.pds.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "PDS cannot be empty".into(),
+        )));
+    }
+
+    if params.access_token.is_empty() || params.refresh_token.is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Session tokens cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let now = Utc::now();
+
+    sqlx::query!(
+        r"
+        INSERT INTO app_password_sessions (did, pds, access_token, refresh_token, access_token_expires_at, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        ON CONFLICT(did) DO UPDATE SET
+            pds = $2,
+            access_token = $3,
+            refresh_token = $4,
+            access_token_expires_at = $5,
+            updated_at = $6
+        ",
+        params.did,
+        params.pds,
+        params.access_token,
+        params.refresh_token,
+        params.access_token_expires_at,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the app password session of an identity, if it has one
+pub async fn app_password_session_get(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Option<AppPasswordSession>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let session = sqlx::query_as!(
+        AppPasswordSession,
+        "SELECT * FROM app_password_sessions WHERE did = $1",
+        did,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(session)
+}
+
+// Remove the app password session of an identity
+pub async fn app_password_session_delete(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!("DELETE FROM app_password_sessions WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
@@ -0,0 +1,172 @@
+use chrono::Utc;
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::AuditLogEntry;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    pub const AUDIT_ACTION_ADMIN_LOGIN: &str = "admin_login";
+    pub const AUDIT_ACTION_IDENTITY_NUKE: &str = "identity_nuke";
+    pub const AUDIT_ACTION_DENYLIST_ADD: &str = "denylist_add";
+    pub const AUDIT_ACTION_DENYLIST_REMOVE: &str = "denylist_remove";
+    pub const AUDIT_ACTION_EVENT_DELETE: &str = "event_delete";
+
+    /// The actions recorded in the audit log, which the admin view can be
+    /// filtered by.
+    pub const AUDIT_ACTIONS: [&str; 5] = [
+        AUDIT_ACTION_ADMIN_LOGIN,
+        AUDIT_ACTION_IDENTITY_NUKE,
+        AUDIT_ACTION_DENYLIST_ADD,
+        AUDIT_ACTION_DENYLIST_REMOVE,
+        AUDIT_ACTION_EVENT_DELETE,
+    ];
+
+    /// An admin or destructive action. Entries can't be changed or removed
+    /// once they are recorded.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct AuditLogEntry {
+        pub id: i64,
+        pub actor_did: String,
+        pub action: String,
+        pub target: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Record an action in the audit log
+pub async fn audit_log_insert(
+    pool: &StoragePool,
+    actor_did: &str,
+    action: &str,
+    target: &str,
+) -> Result<(), StorageError> {
+    if actor_did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Actor DID cannot be empty".into(),
+        )));
+    }
+
+    if !model::AUDIT_ACTIONS.contains(&action) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Action is not supported".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "INSERT INTO audit_log (actor_did, action, target, created_at) VALUES ($1, $2, $3, $4)",
+        actor_did,
+        action,
+        target,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List audit log entries with pagination, newest first, optionally only
+// those of one action or actor
+pub async fn audit_log_list(
+    pool: &StoragePool,
+    action: Option<&str>,
+    actor_did: Option<&str>,
+    page: i64,
+    page_size: i64,
+) -> Result<(i64, Vec<AuditLogEntry>), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM audit_log WHERE ($1::text IS NULL OR action = $1) AND ($2::text IS NULL OR actor_did = $2)"#,
+        action,
+        actor_did,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let offset = (page - 1) * page_size;
+
+    let entries = sqlx::query_as!(
+        AuditLogEntry,
+        "SELECT * FROM audit_log WHERE ($1::text IS NULL OR action = $1) AND ($2::text IS NULL OR actor_did = $2) ORDER BY created_at DESC, id DESC LIMIT $3 OFFSET $4",
+        action,
+        actor_did,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok((count, entries))
+}
+
+#[cfg(test)]
+pub mod test {
+    use sqlx::PgPool;
+
+    use crate::storage::audit_log::{
+        audit_log_insert, audit_log_list,
+        model::{AUDIT_ACTION_ADMIN_LOGIN, AUDIT_ACTION_DENYLIST_ADD},
+    };
+
+    #[sqlx::test]
+    async fn test_audit_log(pool: PgPool) -> anyhow::Result<()> {
+        let admin = "did:plc:tgudj2fjm77pzkuawquqhsxm";
+        let other_admin = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        audit_log_insert(&pool, admin, AUDIT_ACTION_ADMIN_LOGIN, admin).await?;
+        audit_log_insert(&pool, admin, AUDIT_ACTION_DENYLIST_ADD, "did:plc:spam").await?;
+        audit_log_insert(
+            &pool,
+            other_admin,
+            AUDIT_ACTION_DENYLIST_ADD,
+            "spam.example",
+        )
+        .await?;
+
+        assert!(audit_log_insert(&pool, admin, "event_edit", "at://")
+            .await
+            .is_err());
+
+        let (count, entries) = audit_log_list(&pool, None, None, 1, 10).await?;
+        assert_eq!(count, 3);
+        assert_eq!(entries[0].target, "spam.example");
+
+        let (count, entries) =
+            audit_log_list(&pool, Some(AUDIT_ACTION_DENYLIST_ADD), Some(admin), 1, 10).await?;
+        assert_eq!(count, 1);
+        assert_eq!(entries[0].target, "did:plc:spam");
+
+        // Entries can't be changed or removed
+        assert!(sqlx::query!("DELETE FROM audit_log")
+            .execute(&pool)
+            .await
+            .is_err());
+        assert!(sqlx::query!("UPDATE audit_log SET target = ''")
+            .execute(&pool)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
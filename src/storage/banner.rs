@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::SiteBanner;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// The levels a banner can be shown with, matching Bulma's notification
+    /// colors.
+    pub const BANNER_LEVELS: [&str; 3] = ["info", "warning", "danger"];
+
+    /// A message shown at the top of every page between `starts_at` and
+    /// `ends_at`, or until it is removed when `ends_at` isn't set.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct SiteBanner {
+        pub id: String,
+        pub message: String,
+        pub level: String,
+        pub starts_at: DateTime<Utc>,
+        pub ends_at: Option<DateTime<Utc>>,
+        pub created_by: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Schedule a banner and return its id
+pub async fn banner_insert(
+    pool: &StoragePool,
+    message: &str,
+    level: &str,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    created_by: &str,
+) -> Result<String, StorageError> {
+    if message.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Message cannot be empty".into(),
+        )));
+    }
+
+    if !model::BANNER_LEVELS.contains(&level) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Level is not supported".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let id = ulid::Ulid::new().to_string();
+
+    sqlx::query!(
+        "INSERT INTO site_banners (id, message, level, starts_at, ends_at, created_by, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        &id,
+        message,
+        level,
+        starts_at,
+        ends_at,
+        created_by,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(id)
+}
+
+// List all banners, including scheduled and ended ones, latest start first
+pub async fn banner_list(pool: &StoragePool) -> Result<Vec<SiteBanner>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let banners = sqlx::query_as!(
+        SiteBanner,
+        "SELECT * FROM site_banners ORDER BY starts_at DESC, id DESC",
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(banners)
+}
+
+// List the banners shown at the given time, oldest start first
+pub async fn banner_list_active(
+    pool: &StoragePool,
+    now: DateTime<Utc>,
+) -> Result<Vec<SiteBanner>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let banners = sqlx::query_as!(
+        SiteBanner,
+        "SELECT * FROM site_banners WHERE starts_at <= $1 AND (ends_at IS NULL OR ends_at > $1) ORDER BY starts_at ASC, id ASC",
+        now,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(banners)
+}
+
+// Remove a banner, returning whether it existed
+pub async fn banner_delete(pool: &StoragePool, id: &str) -> Result<bool, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!("DELETE FROM site_banners WHERE id = $1", id)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::storage::banner::{banner_delete, banner_insert, banner_list, banner_list_active};
+
+    #[sqlx::test]
+    async fn test_banner_schedule(pool: PgPool) -> anyhow::Result<()> {
+        let admin = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = Utc::now();
+
+        let current = banner_insert(
+            &pool,
+            "Maintenance tonight",
+            "warning",
+            now - Duration::hours(1),
+            Some(now + Duration::hours(1)),
+            admin,
+        )
+        .await?;
+        let open_ended = banner_insert(
+            &pool,
+            "New policy",
+            "info",
+            now - Duration::days(1),
+            None,
+            admin,
+        )
+        .await?;
+        banner_insert(
+            &pool,
+            "Upcoming",
+            "info",
+            now + Duration::hours(2),
+            None,
+            admin,
+        )
+        .await?;
+        banner_insert(
+            &pool,
+            "Over",
+            "danger",
+            now - Duration::days(2),
+            Some(now - Duration::days(1)),
+            admin,
+        )
+        .await?;
+
+        assert!(banner_insert(&pool, " ", "info", now, None, admin)
+            .await
+            .is_err());
+        assert!(banner_insert(&pool, "Hello", "primary", now, None, admin)
+            .await
+            .is_err());
+
+        assert_eq!(banner_list(&pool).await?.len(), 4);
+
+        let active = banner_list_active(&pool, now).await?;
+        assert_eq!(
+            active
+                .iter()
+                .map(|banner| banner.id.clone())
+                .collect::<Vec<_>>(),
+            vec![open_ended.clone(), current.clone()]
+        );
+
+        assert!(banner_delete(&pool, &current).await?);
+        assert!(!banner_delete(&pool, &current).await?);
+        assert_eq!(banner_list_active(&pool, now).await?.len(), 1);
+
+        Ok(())
+    }
+}
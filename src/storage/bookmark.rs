@@ -0,0 +1,212 @@
+use chrono::Utc;
+use sqlx::types::Json;
+
+use crate::storage::{
+    errors::StorageError,
+    event::model::{EventWithRole, EventWithRoleRow},
+    StoragePool,
+};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventBookmark {
+        pub did: String,
+        pub event_aturi: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Save an event for later. Bookmarks are private and never written to the PDS.
+pub async fn bookmark_insert(
+    pool: &StoragePool,
+    did: &str,
+    event_aturi: &str,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO event_bookmarks (did, event_aturi, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(did, event_aturi) DO NOTHING
+        ",
+        did,
+        event_aturi,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+pub async fn bookmark_delete(
+    pool: &StoragePool,
+    did: &str,
+    event_aturi: &str,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "DELETE FROM event_bookmarks WHERE did = $1 AND event_aturi = $2",
+        did,
+        event_aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+#[tracing::instrument(skip(pool))]
+pub async fn bookmark_exists(
+    pool: &StoragePool,
+    did: &str,
+    event_aturi: &str,
+) -> Result<bool, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM event_bookmarks WHERE did = $1 AND event_aturi = $2"#,
+        did,
+        event_aturi,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(total_count > 0)
+}
+
+// List the events a user has saved, most recently saved first. Bookmarks for
+// events that are no longer indexed are skipped.
+pub async fn bookmark_list_events(
+    pool: &StoragePool,
+    did: &str,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+    events.aturi,
+    events.cid,
+    events.did,
+    events.lexicon,
+    events.record AS "record: Json<serde_json::Value>",
+    events.name,
+    events.updated_at AS "updated_at?",
+    events.rsvp_closes_at,
+    events.rsvp_capacity,
+    events.starts_at,
+    events.ends_at,
+    events.mode,
+    events.status,
+    events.latitude,
+    events.longitude,
+    'saved' as "role!"
+FROM
+    event_bookmarks
+    INNER JOIN events ON events.aturi = event_bookmarks.event_aturi
+WHERE
+    event_bookmarks.did = $1
+ORDER BY
+    event_bookmarks.created_at DESC,
+    events.aturi ASC
+LIMIT $2
+OFFSET $3"#,
+        did,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_roles)
+}
@@ -1,21 +1,562 @@
 use anyhow::Result;
+use cityhasher::HashMap;
+use deadpool_redis::redis::{pipe, AsyncCommands as _, Script};
 use deadpool_redis::{Config, Pool, Runtime};
 
-use crate::storage::errors::CacheError;
+use crate::storage::{
+    errors::{CacheError, StorageError},
+    handle::{handles_by_did, model::Handle},
+    CachePool, StoragePool,
+};
 
 pub const OAUTH_REFRESH_QUEUE: &str = "auth_session:oauth:refresh";
 pub const OAUTH_REFRESH_HEARTBEATS: &str = "auth_session:oauth:refresh:workers";
 
+/// Set while an admin has put every instance into read-only mode.
+pub const READ_ONLY_MODE: &str = "instance:read_only";
+
+pub const OAUTH_PDS_RESOURCES: &str = "oauth:pds_resources";
+
+/// PDSes whose OAuth metadata was looked up since the PDS resources task
+/// last ran, scored by the number of lookups.
+pub const OAUTH_PDS_RESOURCES_POPULAR: &str = "oauth:pds_resources:popular";
+
+/// Handles cached by DID, so that pages listing many people don't look each
+/// of them up in the database.
+pub const HANDLE_BY_DID: &str = "handle:did";
+
+/// How long a handle is cached. Handles are not evicted when they change, so
+/// this is kept short.
+pub const HANDLE_CACHE_TTL_SECS: u64 = 60;
+
+/// Discussions of announced events, cached by the URI of the announcement
+/// post so that event pages don't query the AppView on every view.
+pub const POST_THREAD: &str = "bsky:thread";
+
+/// How long a discussion is cached. New replies show up after this long.
+pub const POST_THREAD_CACHE_TTL_SECS: u64 = 300;
+
+/// RSVP counts of events, cached by event AT-URI as a hash of RSVP status to
+/// count. Counters are adjusted as RSVPs are made on this instance.
+pub const RSVP_COUNTS: &str = "rsvp_counts";
+
+/// How long RSVP counts are cached before they are counted again in the
+/// database. This bounds how long counters that drifted, such as from RSVPs
+/// that were imported or backfilled, stay wrong.
+pub const RSVP_COUNTS_CACHE_TTL_SECS: u64 = 300;
+
+/// Pages rendered for people who aren't signed in, cached by template and
+/// request URI so that popular pages aren't rendered on every request.
+pub const ANONYMOUS_PAGE: &str = "page:anonymous";
+
+/// The RSVP statuses that are counted.
+const RSVP_COUNT_STATUSES: [&str; 3] = ["going", "interested", "notgoing"];
+
+/// Moves an RSVP from one status counter to another, but only when the
+/// event's counts are cached. Creating the hash here would store partial
+/// counts that look complete.
+const RSVP_COUNTS_ADJUST_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    if ARGV[1] ~= '' then redis.call('HINCRBY', KEYS[1], ARGV[1], -1) end
+    if ARGV[2] ~= '' then redis.call('HINCRBY', KEYS[1], ARGV[2], 1) end
+end
+return 0
+";
+
 pub fn build_worker_queue(worker_id: &str) -> String {
     format!("{}:{}", OAUTH_REFRESH_QUEUE, worker_id)
 }
 
+pub fn build_pds_resources_key(pds: &str) -> String {
+    format!("{}:{}", OAUTH_PDS_RESOURCES, pds)
+}
+
+pub fn build_handle_key(did: &str) -> String {
+    format!("{}:{}", HANDLE_BY_DID, did)
+}
+
+pub fn build_post_thread_key(uri: &str) -> String {
+    format!("{}:{}", POST_THREAD, uri)
+}
+
+pub fn build_rsvp_counts_key(event_aturi: &str) -> String {
+    format!("{}:{}", RSVP_COUNTS, event_aturi)
+}
+
+pub fn build_anonymous_page_key(template: &str, banners: &str, uri: &str) -> String {
+    format!("{}:{}:{}:{}", ANONYMOUS_PAGE, template, banners, uri)
+}
+
 pub fn create_cache_pool(redis_url: &str) -> Result<Pool> {
     let cfg = Config::from_url(redis_url);
     cfg.create_pool(Some(Runtime::Tokio1))
         .map_err(|err| CacheError::FailedToCreatePool(err).into())
 }
 
+// Check whether an admin has switched on read-only mode.
+pub async fn read_only_enabled(cache_pool: &CachePool) -> Result<bool, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.exists(READ_ONLY_MODE)
+        .await
+        .map_err(CacheError::FailedToAccessReadOnlyMode)
+}
+
+// Switch read-only mode on or off for every instance sharing the cache.
+pub async fn read_only_set(cache_pool: &CachePool, enabled: bool) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    if enabled {
+        conn.set(READ_ONLY_MODE, chrono::Utc::now().to_rfc3339())
+            .await
+            .map_err(CacheError::FailedToAccessReadOnlyMode)
+    } else {
+        conn.del(READ_ONLY_MODE)
+            .await
+            .map_err(CacheError::FailedToAccessReadOnlyMode)
+    }
+}
+
+// Look up when each session group is next due to be refreshed, in epoch
+// milliseconds. Session groups that a worker has already taken from the
+// queue have no time.
+pub async fn refresh_queue_scores(
+    cache_pool: &CachePool,
+    session_groups: &[String],
+) -> Result<Vec<Option<i64>>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let mut scores = Vec::with_capacity(session_groups.len());
+    for session_group in session_groups {
+        let score: Option<f64> = conn
+            .zscore(OAUTH_REFRESH_QUEUE, session_group)
+            .await
+            .map_err(CacheError::FailedToAccessRefreshQueue)?;
+        scores.push(score.map(|value| value as i64));
+    }
+
+    Ok(scores)
+}
+
+// Schedule a session group to be refreshed by the next worker that checks
+// the queue at or after `refresh_at`.
+pub async fn refresh_queue_schedule(
+    cache_pool: &CachePool,
+    session_group: &str,
+    refresh_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.zadd(
+        OAUTH_REFRESH_QUEUE,
+        session_group,
+        refresh_at.timestamp_millis(),
+    )
+    .await
+    .map_err(CacheError::FailedToPlaceInRefreshQueue)
+}
+
+// Take a session group out of the refresh queue so that it can be refreshed
+// outside of the refresh task. Returns false when it wasn't queued, such as
+// when a worker has already taken it.
+pub async fn refresh_queue_claim(
+    cache_pool: &CachePool,
+    session_group: &str,
+) -> Result<bool, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let removed: i64 = conn
+        .zrem(OAUTH_REFRESH_QUEUE, session_group)
+        .await
+        .map_err(CacheError::FailedToAccessRefreshQueue)?;
+
+    Ok(removed > 0)
+}
+
+// Take session groups out of the refresh queue, such as after their sessions
+// were removed.
+pub async fn refresh_queue_remove(
+    cache_pool: &CachePool,
+    session_groups: &[String],
+) -> Result<(), CacheError> {
+    if session_groups.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.zrem(OAUTH_REFRESH_QUEUE, session_groups)
+        .await
+        .map_err(CacheError::FailedToAccessRefreshQueue)
+}
+
+// Get the cached OAuth metadata of a PDS, and count the lookup towards the
+// PDS being refreshed before it expires.
+pub async fn pds_resources_get(
+    cache_pool: &CachePool,
+    pds: &str,
+) -> Result<Option<String>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let (value, _): (Option<String>, f64) = pipe()
+        .get(build_pds_resources_key(pds))
+        .zincr(OAUTH_PDS_RESOURCES_POPULAR, pds, 1)
+        .query_async(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessPdsResources)?;
+
+    Ok(value)
+}
+
+// Cache the OAuth metadata of a PDS for `ttl_secs` seconds.
+pub async fn pds_resources_set(
+    cache_pool: &CachePool,
+    pds: &str,
+    value: &str,
+    ttl_secs: u64,
+) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.set_ex(build_pds_resources_key(pds), value, ttl_secs)
+        .await
+        .map_err(CacheError::FailedToAccessPdsResources)
+}
+
+// Take the PDSes with the most lookups since the last call, most looked up
+// first. The lookup counts start over, so that PDSes nobody signs in with
+// anymore stop being refreshed.
+pub async fn pds_resources_take_popular(
+    cache_pool: &CachePool,
+    limit: isize,
+) -> Result<Vec<String>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let (popular, _): (Vec<String>, ()) = pipe()
+        .atomic()
+        .zrevrange(OAUTH_PDS_RESOURCES_POPULAR, 0, limit - 1)
+        .del(OAUTH_PDS_RESOURCES_POPULAR)
+        .query_async(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessPdsResources)?;
+
+    Ok(popular)
+}
+
+// Get the cached discussion of an announcement post.
+pub async fn post_thread_get(
+    cache_pool: &CachePool,
+    uri: &str,
+) -> Result<Option<String>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.get(build_post_thread_key(uri))
+        .await
+        .map_err(CacheError::FailedToAccessPostThreads)
+}
+
+// Cache the discussion of an announcement post for `POST_THREAD_CACHE_TTL_SECS`
+// seconds.
+pub async fn post_thread_set(
+    cache_pool: &CachePool,
+    uri: &str,
+    value: &str,
+) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.set_ex(
+        build_post_thread_key(uri),
+        value,
+        POST_THREAD_CACHE_TTL_SECS,
+    )
+    .await
+    .map_err(CacheError::FailedToAccessPostThreads)
+}
+
+// Get a cached page rendered with `template` and the banners hashed as
+// `banners` for `uri`.
+pub async fn anonymous_page_get(
+    cache_pool: &CachePool,
+    template: &str,
+    banners: &str,
+    uri: &str,
+) -> Result<Option<String>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.get(build_anonymous_page_key(template, banners, uri))
+        .await
+        .map_err(CacheError::FailedToAccessAnonymousPages)
+}
+
+// Cache a page rendered with `template` and the banners hashed as `banners`
+// for `uri` for `ttl_secs` seconds.
+pub async fn anonymous_page_set(
+    cache_pool: &CachePool,
+    template: &str,
+    banners: &str,
+    uri: &str,
+    body: &str,
+    ttl_secs: u64,
+) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.set_ex(
+        build_anonymous_page_key(template, banners, uri),
+        body,
+        ttl_secs,
+    )
+    .await
+    .map_err(CacheError::FailedToAccessAnonymousPages)
+}
+
+// Get the cached RSVP counts of events, keyed by event AT-URI and then by
+// status. Events whose counts aren't cached are left out.
+pub async fn rsvp_counts_get(
+    cache_pool: &CachePool,
+    event_aturis: &[String],
+) -> Result<HashMap<String, HashMap<String, i64>>, CacheError> {
+    if event_aturis.is_empty() {
+        return Ok(HashMap::default());
+    }
+
+    let mut pipeline = pipe();
+    for event_aturi in event_aturis {
+        pipeline.hgetall(build_rsvp_counts_key(event_aturi));
+    }
+
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let values: Vec<HashMap<String, i64>> = pipeline
+        .query_async(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessRsvpCounts)?;
+
+    Ok(event_aturis
+        .iter()
+        .cloned()
+        .zip(values)
+        .filter(|(_, counts)| !counts.is_empty())
+        .collect())
+}
+
+// Cache the RSVP counts of events, as counted in the database, for
+// `RSVP_COUNTS_CACHE_TTL_SECS` seconds. Statuses without a count are cached
+// as zero.
+pub async fn rsvp_counts_set(
+    cache_pool: &CachePool,
+    counts: &HashMap<String, HashMap<String, i64>>,
+) -> Result<(), CacheError> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipeline = pipe();
+    pipeline.atomic();
+    for (event_aturi, status_counts) in counts {
+        let key = build_rsvp_counts_key(event_aturi);
+        let values = RSVP_COUNT_STATUSES
+            .iter()
+            .map(|status| (*status, status_counts.get(*status).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        pipeline
+            .hset_multiple(&key, &values)
+            .ignore()
+            .expire(&key, RSVP_COUNTS_CACHE_TTL_SECS as i64)
+            .ignore();
+    }
+
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    pipeline
+        .query_async::<()>(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessRsvpCounts)
+}
+
+// Adjust the cached RSVP counts of an event for an RSVP that changed from
+// one status to another. A new RSVP has no previous status and a removed RSVP
+// has no status. Counts that aren't cached are left to be counted in the
+// database.
+pub async fn rsvp_counts_adjust(
+    cache_pool: &CachePool,
+    event_aturi: &str,
+    previous_status: Option<&str>,
+    status: Option<&str>,
+) -> Result<(), CacheError> {
+    if previous_status == status {
+        return Ok(());
+    }
+
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    Script::new(RSVP_COUNTS_ADJUST_SCRIPT)
+        .key(build_rsvp_counts_key(event_aturi))
+        .arg(previous_status.unwrap_or_default())
+        .arg(status.unwrap_or_default())
+        .invoke_async::<()>(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessRsvpCounts)
+}
+
+// Drop the cached RSVP counts of an event so that they are counted again in
+// the database the next time they are needed.
+pub async fn rsvp_counts_invalidate(
+    cache_pool: &CachePool,
+    event_aturi: &str,
+) -> Result<(), CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    conn.del(build_rsvp_counts_key(event_aturi))
+        .await
+        .map_err(CacheError::FailedToAccessRsvpCounts)
+}
+
+// Look up handles by DID in the cache, then in the database for the ones
+// that weren't cached, caching them for `HANDLE_CACHE_TTL_SECS` seconds. If
+// the cache can't be used, every handle is looked up in the database.
+#[tracing::instrument(skip_all, fields(dids = dids.len()))]
+pub async fn handles_by_did_cached(
+    pool: &StoragePool,
+    cache_pool: &CachePool,
+    dids: Vec<String>,
+) -> Result<HashMap<String, Handle>, StorageError> {
+    if dids.is_empty() {
+        return Ok(HashMap::default());
+    }
+
+    let mut handles = match handles_cache_get(cache_pool, &dids).await {
+        Ok(values) => values,
+        Err(err) => {
+            tracing::warn!(error = ?err, "unable to read cached handles");
+            return handles_by_did(pool, dids).await;
+        }
+    };
+
+    let missing = dids
+        .into_iter()
+        .filter(|did| !handles.contains_key(did))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(handles);
+    }
+
+    let found = handles_by_did(pool, missing).await?;
+    if let Err(err) = handles_cache_set(cache_pool, found.values()).await {
+        tracing::warn!(error = ?err, "unable to cache handles");
+    }
+    handles.extend(found);
+
+    Ok(handles)
+}
+
+async fn handles_cache_get(
+    cache_pool: &CachePool,
+    dids: &[String],
+) -> Result<HashMap<String, Handle>, CacheError> {
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    let keys = dids
+        .iter()
+        .map(|did| build_handle_key(did))
+        .collect::<Vec<_>>();
+    let values: Vec<Option<String>> = conn
+        .mget(keys)
+        .await
+        .map_err(CacheError::FailedToAccessHandles)?;
+
+    // Values that can't be read, such as ones cached before the handle model
+    // changed, are looked up again.
+    Ok(values
+        .into_iter()
+        .flatten()
+        .filter_map(|value| serde_json::from_str::<Handle>(&value).ok())
+        .map(|handle| (handle.did.clone(), handle))
+        .collect())
+}
+
+async fn handles_cache_set(
+    cache_pool: &CachePool,
+    handles: impl Iterator<Item = &Handle>,
+) -> Result<(), CacheError> {
+    let mut pipeline = pipe();
+    let mut empty = true;
+    for handle in handles {
+        let Ok(value) = serde_json::to_string(handle) else {
+            continue;
+        };
+        pipeline
+            .set_ex(build_handle_key(&handle.did), value, HANDLE_CACHE_TTL_SECS)
+            .ignore();
+        empty = false;
+    }
+    if empty {
+        return Ok(());
+    }
+
+    let mut conn = cache_pool
+        .get()
+        .await
+        .map_err(CacheError::FailedToGetConnection)?;
+
+    pipeline
+        .query_async::<()>(&mut conn)
+        .await
+        .map_err(CacheError::FailedToAccessHandles)
+}
+
 // Mock implementation for testing
 #[cfg(test)]
 pub struct MockCachePool {}
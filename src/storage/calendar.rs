@@ -0,0 +1,330 @@
+use chrono::Utc;
+use serde_json::json;
+use sqlx::{types::Json, Postgres, QueryBuilder};
+
+use crate::atproto::lexicon::events::smokesignal::calendar::Calendar as CalendarRecord;
+use crate::storage::{
+    errors::StorageError,
+    event::model::{EventWithRole, EventWithRoleRow},
+    StoragePool,
+};
+use model::Calendar;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A series of events, such as a monthly meetup. The events of a series
+    /// are kept in `calendar_events`.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Calendar {
+        pub aturi: String,
+        pub cid: String,
+        pub did: String,
+        pub name: String,
+        pub description: Option<String>,
+        pub record: sqlx::types::Json<serde_json::Value>,
+        pub updated_at: DateTime<Utc>,
+    }
+}
+
+// Insert or replace a calendar and the events that are part of it. The
+// record has already been written to the organizer's PDS.
+pub async fn calendar_upsert(
+    pool: &StoragePool,
+    aturi: &str,
+    cid: &str,
+    did: &str,
+    record: &CalendarRecord,
+) -> Result<(), StorageError> {
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Calendar URI cannot be empty".into(),
+        )));
+    }
+
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let CalendarRecord::Current {
+        name,
+        description,
+        events,
+        ..
+    } = record;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO calendars (aturi, cid, did, name, description, record, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT(aturi) DO UPDATE SET cid = $2, name = $4, description = $5, record = $6, updated_at = $7
+        ",
+        aturi,
+        cid,
+        did,
+        name,
+        description.as_deref(),
+        json!(record),
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        "DELETE FROM calendar_events WHERE calendar_aturi = $1",
+        aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    if !events.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO calendar_events (calendar_aturi, event_aturi) ");
+        query_builder.push_values(events, |mut row, event| {
+            row.push_bind(aturi).push_bind(&event.uri);
+        });
+        query_builder.push(" ON CONFLICT DO NOTHING");
+
+        query_builder
+            .build()
+            .execute(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get a calendar by its URI
+pub async fn calendar_get(
+    pool: &StoragePool,
+    aturi: &str,
+) -> Result<Option<Calendar>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let calendar = sqlx::query_as!(Calendar, "SELECT * FROM calendars WHERE aturi = $1", aturi)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(calendar)
+}
+
+// List the calendars of an organizer, most recently updated first.
+pub async fn calendar_list_did(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Vec<Calendar>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let calendars = sqlx::query_as!(
+        Calendar,
+        "SELECT * FROM calendars WHERE did = $1 ORDER BY updated_at DESC, aturi ASC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(calendars)
+}
+
+// List the events of a calendar that are known, in the order they start.
+// Events without a start are listed last.
+pub async fn calendar_events(
+    pool: &StoragePool,
+    calendar_aturi: &str,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let events = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+            events.aturi,
+            events.cid,
+            events.did,
+            events.lexicon,
+            events.record AS "record: Json<serde_json::Value>",
+            events.name,
+            events.updated_at AS "updated_at?",
+            events.rsvp_closes_at,
+            events.rsvp_capacity,
+            events.starts_at,
+            events.ends_at,
+            events.mode,
+            events.status,
+            events.latitude,
+            events.longitude,
+            'organizer' as "role!"
+        FROM
+            calendar_events
+            JOIN events ON events.aturi = calendar_events.event_aturi
+        WHERE
+            calendar_events.calendar_aturi = $1
+        ORDER BY
+            events.starts_at ASC NULLS LAST,
+            events.aturi ASC"#,
+        calendar_aturi,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(events)
+}
+
+// Remove a calendar of an organizer, along with its list of events. Returns
+// false when the organizer has no such calendar.
+pub async fn calendar_delete(
+    pool: &StoragePool,
+    did: &str,
+    aturi: &str,
+) -> Result<bool, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        "DELETE FROM calendars WHERE did = $1 AND aturi = $2",
+        did,
+        aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use crate::atproto::lexicon::com::atproto::repo::StrongRef;
+    use crate::atproto::lexicon::events::smokesignal::calendar::Calendar as CalendarRecord;
+    use crate::storage::calendar::{
+        calendar_delete, calendar_events, calendar_get, calendar_list_did, calendar_upsert,
+    };
+    use crate::test_support::{insert_handle, EventBuilder};
+
+    #[sqlx::test]
+    async fn test_calendars(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        insert_handle(&pool, organizer, "whole-crane.examplepds.com").await?;
+
+        let june = EventBuilder::default()
+            .name("June Meetup")
+            .starts_at("2025-06-02T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let may = EventBuilder::default()
+            .name("May Meetup")
+            .starts_at("2025-05-05T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        let unscheduled = EventBuilder::default()
+            .name("Next Meetup")
+            .insert(&pool, organizer, "3lbsxygenau2e")
+            .await?;
+
+        let aturi = format!(
+            "at://{}/events.smokesignal.calendar/3lbsz2aeu7w2c",
+            organizer
+        );
+        let record = |events: Vec<StrongRef>| CalendarRecord::Current {
+            name: "Vancouver Rust".to_string(),
+            description: Some("Monthly meetups.".to_string()),
+            events,
+            created_at: Utc::now(),
+        };
+
+        calendar_upsert(
+            &pool,
+            &aturi,
+            "bafyreia",
+            organizer,
+            &record(vec![june.clone(), unscheduled.clone(), may.clone()]),
+        )
+        .await?;
+
+        let events = calendar_events(&pool, &aturi).await?;
+        assert_eq!(
+            events
+                .iter()
+                .map(|value| value.event.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["May Meetup", "June Meetup", "Next Meetup"]
+        );
+
+        calendar_upsert(
+            &pool,
+            &aturi,
+            "bafyreib",
+            organizer,
+            &record(vec![june.clone()]),
+        )
+        .await?;
+
+        let calendar = calendar_get(&pool, &aturi).await?.unwrap();
+        assert_eq!(calendar.cid, "bafyreib");
+        assert_eq!(calendar.description.as_deref(), Some("Monthly meetups."));
+        assert_eq!(calendar_events(&pool, &aturi).await?.len(), 1);
+        assert_eq!(calendar_list_did(&pool, organizer).await?.len(), 1);
+
+        assert!(!calendar_delete(&pool, "did:plc:b10c457b287b3f06fd768504", &aturi).await?);
+        assert!(calendar_delete(&pool, organizer, &aturi).await?);
+        assert!(calendar_get(&pool, &aturi).await?.is_none());
+        assert!(calendar_events(&pool, &aturi).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{CheckinAttendee, CheckinCounts};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// Someone who RSVPed to an event or was checked in at the door. People
+    /// checked in without an RSVP have no status.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct CheckinAttendee {
+        pub did: String,
+        pub status: Option<String>,
+        pub checked_in_at: Option<DateTime<Utc>>,
+    }
+
+    /// How many people were checked in to an event, out of those going.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct CheckinCounts {
+        pub checked_in: i64,
+        pub going: i64,
+    }
+}
+
+// Check in a person at an event. Returns false when they already were.
+pub async fn checkin_insert(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+    checked_in_by: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, StorageError> {
+    if event_aturi.trim().is_empty() || did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI and DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        "INSERT INTO checkins (event_aturi, did, checked_in_by, created_at) VALUES ($1, $2, $3, $4) ON CONFLICT (event_aturi, did) DO NOTHING",
+        event_aturi,
+        did,
+        checked_in_by,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Undo the check-in of a person at an event. Returns false when they weren't
+// checked in.
+pub async fn checkin_delete(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+) -> Result<bool, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        "DELETE FROM checkins WHERE event_aturi = $1 AND did = $2",
+        event_aturi,
+        did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// List the people going to or interested in an event, and anyone checked in
+// without an RSVP, with when they were checked in.
+#[tracing::instrument(skip(pool))]
+pub async fn checkin_attendees(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<Vec<CheckinAttendee>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let attendees = sqlx::query_as!(
+        CheckinAttendee,
+        r#"SELECT COALESCE(r.did, c.did) AS "did!",
+            r.status AS "status?",
+            c.created_at AS "checked_in_at?"
+        FROM (
+            SELECT DISTINCT ON (did) did, status FROM rsvps
+            WHERE event_aturi = $1 AND status IN ('going', 'interested')
+            ORDER BY did, status ASC
+        ) r
+        FULL OUTER JOIN (SELECT did, created_at FROM checkins WHERE event_aturi = $1) c ON c.did = r.did
+        ORDER BY 1 ASC"#,
+        event_aturi,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(attendees)
+}
+
+// Count the people checked in to an event and the people going to it
+pub async fn checkin_counts(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<CheckinCounts, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let counts = sqlx::query_as!(
+        CheckinCounts,
+        r#"SELECT
+            (SELECT COUNT(*) FROM checkins WHERE event_aturi = $1) AS "checked_in!",
+            (SELECT COUNT(DISTINCT did) FROM rsvps WHERE event_aturi = $1 AND status = 'going') AS "going!""#,
+        event_aturi,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use crate::atproto::lexicon::community::lexicon::calendar::rsvp::RsvpStatus;
+    use crate::storage::checkin::{
+        checkin_attendees, checkin_counts, checkin_delete, checkin_insert,
+    };
+    use crate::test_support::{insert_handle, EventBuilder, RsvpBuilder};
+
+    #[sqlx::test]
+    async fn test_checkins(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        insert_handle(&pool, organizer, "whole-crane.examplepds.com").await?;
+
+        let event = EventBuilder::default()
+            .starts_at("2025-06-02T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        let going = "did:plc:b10c457b287b3f06fd768504";
+        let interested = "did:plc:c71dca8dfb0f126321f82435";
+        let walk_in = "did:plc:f263c822655b579fc8a79635";
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, going, "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, interested, "3lbsy2aeu7w2d")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::NotGoing)
+            .insert(&pool, "did:plc:a0ae4a2fd1b9c46af1b55b1e", "3lbsy2aeu7w2e")
+            .await?;
+
+        let now = Utc::now();
+        assert!(checkin_insert(&pool, &event.uri, going, organizer, now).await?);
+        assert!(!checkin_insert(&pool, &event.uri, going, organizer, now).await?);
+        assert!(checkin_insert(&pool, &event.uri, walk_in, organizer, now).await?);
+        assert!(checkin_insert(&pool, &event.uri, " ", organizer, now)
+            .await
+            .is_err());
+
+        let attendees = checkin_attendees(&pool, &event.uri).await?;
+        assert_eq!(
+            attendees
+                .iter()
+                .map(|value| (
+                    value.did.as_str(),
+                    value.status.as_deref(),
+                    value.checked_in_at.is_some()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (going, Some("going"), true),
+                (interested, Some("interested"), false),
+                (walk_in, None, true),
+            ]
+        );
+
+        let counts = checkin_counts(&pool, &event.uri).await?;
+        assert_eq!((counts.checked_in, counts.going), (2, 1));
+
+        assert!(checkin_delete(&pool, &event.uri, walk_in).await?);
+        assert!(!checkin_delete(&pool, &event.uri, walk_in).await?);
+        assert_eq!(checkin_counts(&pool, &event.uri).await?.checked_in, 1);
+
+        Ok(())
+    }
+}
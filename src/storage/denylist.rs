@@ -1,6 +1,5 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use metrohash::MetroHash64;
-use sqlx::{Postgres, QueryBuilder};
 use std::borrow::Cow;
 use std::hash::Hasher;
 
@@ -13,49 +12,76 @@ pub mod model {
     use serde::{Deserialize, Serialize};
     use sqlx::FromRow;
 
+    /// Categories an entry can be filed under. Entries created before
+    /// categories existed are `other`.
+    pub const DENYLIST_CATEGORIES: [&str; 5] = ["spam", "abuse", "impersonation", "legal", "other"];
+
     #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
     pub struct DenylistEntry {
         pub subject: String,
         pub reason: String,
+        pub category: String,
+        pub expires_at: Option<DateTime<Utc>>,
+        pub appeal_url: Option<String>,
         pub updated_at: DateTime<Utc>,
     }
 }
 
-// Add a new entry to the denylist or update an existing one
+// Hash a subject the same way it is stored so that raw values are never
+// kept in the denylist table.
+fn denylist_subject_hash(subject: &str) -> String {
+    let mut h = MetroHash64::default();
+    h.write(subject.as_bytes());
+    crockford::encode(h.finish())
+}
+
+// Add a new entry to the denylist or update an existing one. Entries with an
+// expiry stop matching once it has passed.
 pub async fn denylist_add_or_update(
     pool: &StoragePool,
     subject: Cow<'_, str>,
     reason: Cow<'_, str>,
+    category: &str,
+    expires_at: Option<DateTime<Utc>>,
+    appeal_url: Option<&str>,
 ) -> Result<(), StorageError> {
-    // Validate subject and reason before proceeding
+    // Validate subject and category before proceeding
     if subject.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
             "Subject cannot be empty".into(),
         )));
     }
 
+    if !model::DENYLIST_CATEGORIES.contains(&category) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Category is not supported".into(),
+        )));
+    }
+
     let mut tx = pool
         .begin()
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let mut h = MetroHash64::new();
-    h.write(subject.as_bytes());
-    let subject = crockford::encode(h.finish());
+    let subject = denylist_subject_hash(&subject);
+    let appeal_url = appeal_url.map(str::trim).filter(|value| !value.is_empty());
 
     let now = Utc::now();
 
-    sqlx::query(
+    sqlx::query!(
         r"
-        INSERT INTO denylist (subject, reason, updated_at)
-        VALUES ($1, $2, $3)
+        INSERT INTO denylist (subject, reason, category, expires_at, appeal_url, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
         ON CONFLICT(subject) DO UPDATE
-        SET reason = $2, updated_at = $3
+        SET reason = $2, category = $3, expires_at = $4, appeal_url = $5, updated_at = $6
         ",
+        subject,
+        reason.as_ref(),
+        category,
+        expires_at,
+        appeal_url,
+        now,
     )
-    .bind(subject)
-    .bind(reason)
-    .bind(now)
     .execute(tx.as_mut())
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
@@ -81,12 +107,9 @@ pub async fn denylist_remove(pool: &StoragePool, subject: &str) -> Result<(), St
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let mut h = MetroHash64::default();
-    h.write(subject.as_bytes());
-    let subject = crockford::encode(h.finish());
+    let subject = denylist_subject_hash(subject);
 
-    sqlx::query("DELETE FROM denylist WHERE subject = $1")
-        .bind(subject)
+    sqlx::query!("DELETE FROM denylist WHERE subject = $1", subject)
         .execute(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
@@ -98,7 +121,7 @@ pub async fn denylist_remove(pool: &StoragePool, subject: &str) -> Result<(), St
     Ok(())
 }
 
-// Check if a subject is in the denylist
+// Check if a subject is in the denylist. Expired entries are ignored.
 pub async fn denylist_check(pool: &StoragePool, subject: &str) -> Result<bool, StorageError> {
     // Validate subject before proceeding
     if subject.trim().is_empty() {
@@ -112,15 +135,16 @@ pub async fn denylist_check(pool: &StoragePool, subject: &str) -> Result<bool, S
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let mut h = MetroHash64::default();
-    h.write(subject.as_bytes());
-    let subject = crockford::encode(h.finish());
+    let subject = denylist_subject_hash(subject);
 
-    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM denylist WHERE subject = $1")
-        .bind(subject)
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM denylist WHERE subject = $1 AND (expires_at IS NULL OR expires_at > $2)"#,
+        subject,
+        Utc::now(),
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
@@ -140,18 +164,19 @@ pub async fn denylist_list(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM denylist")
+    let count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM denylist"#)
         .fetch_one(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
 
     let offset = (page - 1) * page_size;
 
-    let entries = sqlx::query_as::<_, model::DenylistEntry>(
+    let entries = sqlx::query_as!(
+        model::DenylistEntry,
         "SELECT * FROM denylist ORDER BY updated_at DESC LIMIT $1 OFFSET $2",
+        page_size + 1,
+        offset,
     )
-    .bind(page_size + 1)
-    .bind(offset)
     .fetch_all(tx.as_mut())
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
@@ -163,10 +188,21 @@ pub async fn denylist_list(
     Ok((count, entries))
 }
 
+// Check if any of the subjects are in the denylist. Expired entries are ignored.
 pub async fn denylist_exists(pool: &StoragePool, subjects: &[&str]) -> Result<bool, StorageError> {
-    // Validate input - empty array should return false, not error
+    Ok(denylist_find(pool, subjects).await?.is_some())
+}
+
+// Find the active denylist entry that matches any of the subjects. When more
+// than one entry matches, permanent entries win over ones that expire and
+// otherwise the entry that expires last is returned.
+pub async fn denylist_find(
+    pool: &StoragePool,
+    subjects: &[&str],
+) -> Result<Option<DenylistEntry>, StorageError> {
+    // Validate input - empty array should return nothing, not error
     if subjects.is_empty() {
-        return Ok(false);
+        return Ok(None);
     }
 
     // Validate that all subjects are non-empty
@@ -186,32 +222,25 @@ pub async fn denylist_exists(pool: &StoragePool, subjects: &[&str]) -> Result<bo
     // Process subjects to get hashed values first
     let hashed_subjects: Vec<String> = subjects
         .iter()
-        .map(|subject| {
-            let mut h = MetroHash64::default();
-            h.write(subject.as_bytes());
-            crockford::encode(h.finish())
-        })
+        .map(|subject| denylist_subject_hash(subject))
         .collect();
 
-    // Build the query with placeholders
-    let mut query_builder: QueryBuilder<Postgres> =
-        QueryBuilder::new("SELECT COUNT(*) FROM denylist WHERE subject IN (");
-    let mut separated = query_builder.separated(", ");
-    for hashed_subject in &hashed_subjects {
-        separated.push_bind(hashed_subject);
-    }
-    separated.push_unseparated(") ");
-
-    // Use build_query_scalar to correctly include the bindings
-    let query = query_builder.build_query_scalar::<i64>();
-    let count = query
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    let entry = sqlx::query_as!(
+        DenylistEntry,
+        r"SELECT * FROM denylist
+        WHERE subject = ANY($1) AND (expires_at IS NULL OR expires_at > $2)
+        ORDER BY expires_at DESC NULLS FIRST, updated_at DESC
+        LIMIT 1",
+        &hashed_subjects,
+        Utc::now(),
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
         .map_err(StorageError::CannotCommitDatabaseTransaction)?;
 
-    Ok(count > 0)
+    Ok(entry)
 }
@@ -129,4 +129,55 @@ pub enum CacheError {
     /// Redis-backed refresh queue, typically due to Redis errors or connectivity issues.
     #[error("error-cache-3 Failed to place session group into refresh queue: {0:?}")]
     FailedToPlaceInRefreshQueue(deadpool_redis::redis::RedisError),
+
+    /// Error when the read-only mode flag cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to check or change the
+    /// Redis-backed read-only mode flag, typically due to Redis errors or connectivity issues.
+    #[error("error-cache-4 Failed to access read-only mode flag: {0:?}")]
+    FailedToAccessReadOnlyMode(deadpool_redis::redis::RedisError),
+
+    /// Error when the refresh queue cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to look up or remove session
+    /// groups in the Redis-backed refresh queue, typically due to Redis errors or connectivity issues.
+    #[error("error-cache-5 Failed to access refresh queue: {0:?}")]
+    FailedToAccessRefreshQueue(deadpool_redis::redis::RedisError),
+
+    /// Error when cached OAuth metadata of a PDS cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to get or set the Redis-backed
+    /// OAuth metadata cache, typically due to Redis errors or connectivity issues.
+    #[error("error-cache-6 Failed to access cached PDS resources: {0:?}")]
+    FailedToAccessPdsResources(deadpool_redis::redis::RedisError),
+
+    /// Error when cached handles cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to get or set the Redis-backed
+    /// handle cache, typically due to Redis errors or connectivity issues.
+    #[error("error-cache-7 Failed to access cached handles: {0:?}")]
+    FailedToAccessHandles(deadpool_redis::redis::RedisError),
+
+    /// Error when cached post threads cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to get or set the Redis-backed
+    /// cache of event discussions, typically due to Redis errors or connectivity issues.
+    #[error("error-cache-8 Failed to access cached post threads: {0:?}")]
+    FailedToAccessPostThreads(deadpool_redis::redis::RedisError),
+
+    /// Error when cached RSVP counts cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to get, set or adjust the
+    /// Redis-backed RSVP counters of events, typically due to Redis errors or
+    /// connectivity issues.
+    #[error("error-cache-9 Failed to access cached RSVP counts: {0:?}")]
+    FailedToAccessRsvpCounts(deadpool_redis::redis::RedisError),
+
+    /// Error when cached anonymous pages cannot be read or updated.
+    ///
+    /// This error occurs when the system fails to get or set the Redis-backed
+    /// cache of pages rendered for people who aren't signed in, typically due
+    /// to Redis errors or connectivity issues.
+    #[error("error-cache-10 Failed to access cached pages: {0:?}")]
+    FailedToAccessAnonymousPages(deadpool_redis::redis::RedisError),
 }
@@ -1,19 +1,29 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::json;
-use sqlx::{Postgres, QueryBuilder};
+use sqlx::{types::Json, Acquire, PgExecutor, Postgres, QueryBuilder};
 
+use crate::atproto::lexicon::app::bsky::feed::post::Facet;
 use crate::atproto::lexicon::community::lexicon::calendar::event::Event as EventLexicon;
+use crate::atproto::lexicon::community::lexicon::calendar::event::EXTRA_DESCRIPTION_FACETS;
+use crate::atproto::lexicon::community::lexicon::calendar::event::EXTRA_DOORS_OPEN_AT;
+use crate::atproto::lexicon::community::lexicon::calendar::event::EXTRA_TAGS;
+use crate::atproto::lexicon::community::lexicon::calendar::event::EXTRA_TIMEZONE;
 use crate::atproto::lexicon::community::lexicon::calendar::rsvp::{
     Rsvp as RsvpLexicon, RsvpStatus as RsvpStatusLexicon,
 };
 
+use crate::normalize::normalize_text;
+
 use super::errors::StorageError;
+use super::event_revision::{event_record_changes, event_revision_insert};
+use super::location::{event_locations_replace, event_locations_replace_many};
+use super::tag::{event_tag_keys, event_tags_replace, event_tags_replace_many};
 use super::StoragePool;
-use model::{Event, EventWithRole, Rsvp};
+use model::{Event, EventWithRole, EventWithRoleRow, Rsvp};
 
 pub mod model {
     use chrono::{DateTime, Utc};
@@ -33,6 +43,34 @@ pub mod model {
         pub name: String,
 
         pub updated_at: Option<DateTime<Utc>>,
+
+        /// Organizer-configured RSVP close time. When unset, RSVPs close when
+        /// the event starts.
+        #[sqlx(default)]
+        pub rsvp_closes_at: Option<DateTime<Utc>>,
+
+        /// Organizer-configured limit on going RSVPs. When unset, any number
+        /// of people can RSVP going.
+        #[sqlx(default)]
+        pub rsvp_capacity: Option<i32>,
+
+        /// Details denormalized from the record when it is stored. Events
+        /// indexed before these were stored have them set by `reparse_events`.
+        #[sqlx(default)]
+        pub starts_at: Option<DateTime<Utc>>,
+        #[sqlx(default)]
+        pub ends_at: Option<DateTime<Utc>>,
+        #[sqlx(default)]
+        pub mode: Option<String>,
+        #[sqlx(default)]
+        pub status: Option<String>,
+
+        /// Coordinates of the first address, set by the geocoder when one is
+        /// configured.
+        #[sqlx(default)]
+        pub latitude: Option<f64>,
+        #[sqlx(default)]
+        pub longitude: Option<f64>,
     }
 
     #[derive(Clone, FromRow, Debug, Serialize)]
@@ -44,6 +82,53 @@ pub mod model {
         // pub event_handle: String,
     }
 
+    /// An event and its role as one row, for queries checked with
+    /// `sqlx::query_as!`, which can't fill the flattened `event` of
+    /// `EventWithRole`.
+    pub struct EventWithRoleRow {
+        pub aturi: String,
+        pub cid: String,
+        pub did: String,
+        pub lexicon: String,
+        pub record: sqlx::types::Json<serde_json::Value>,
+        pub name: String,
+        pub updated_at: Option<DateTime<Utc>>,
+        pub rsvp_closes_at: Option<DateTime<Utc>>,
+        pub rsvp_capacity: Option<i32>,
+        pub starts_at: Option<DateTime<Utc>>,
+        pub ends_at: Option<DateTime<Utc>>,
+        pub mode: Option<String>,
+        pub status: Option<String>,
+        pub latitude: Option<f64>,
+        pub longitude: Option<f64>,
+        pub role: String,
+    }
+
+    impl From<EventWithRoleRow> for EventWithRole {
+        fn from(row: EventWithRoleRow) -> Self {
+            Self {
+                event: Event {
+                    aturi: row.aturi,
+                    cid: row.cid,
+                    did: row.did,
+                    lexicon: row.lexicon,
+                    record: row.record,
+                    name: row.name,
+                    updated_at: row.updated_at,
+                    rsvp_closes_at: row.rsvp_closes_at,
+                    rsvp_capacity: row.rsvp_capacity,
+                    starts_at: row.starts_at,
+                    ends_at: row.ends_at,
+                    mode: row.mode,
+                    status: row.status,
+                    latitude: row.latitude,
+                    longitude: row.longitude,
+                },
+                role: row.role,
+            }
+        }
+    }
+
     #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
     pub struct Rsvp {
         pub aturi: String,
@@ -59,6 +144,10 @@ pub mod model {
         pub status: String,
 
         pub updated_at: Option<DateTime<Utc>>,
+
+        /// Set when the RSVP was received after the event's RSVP deadline.
+        #[sqlx(default)]
+        pub late: bool,
     }
 }
 
@@ -80,7 +169,7 @@ pub async fn event_insert(
 }
 
 pub async fn event_insert_with_metadata<T: serde::Serialize>(
-    pool: &StoragePool,
+    conn: impl Acquire<'_, Database = Postgres>,
     aturi: &str,
     cid: &str,
     did: &str,
@@ -88,6 +177,73 @@ pub async fn event_insert_with_metadata<T: serde::Serialize>(
     record: &T,
     name: &str,
 ) -> Result<(), StorageError> {
+    let mut tx = conn
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let now = Utc::now();
+
+    // Denormalize the parsed details so that list pages and time range
+    // queries don't need to parse the record.
+    let record = json!(record);
+    let details = extract_record_details(lexicon, &record, name);
+
+    sqlx::query!(
+        r"INSERT INTO events (aturi, cid, did, lexicon, record, name, updated_at, starts_at, ends_at, mode, status, locations, name_normalized)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+        aturi,
+        cid,
+        did,
+        lexicon,
+        &record,
+        name,
+        now,
+        details.starts_at,
+        details.ends_at,
+        details.mode.as_deref(),
+        details.status.as_deref(),
+        json!(details.locations),
+        normalize_text(name),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    event_locations_replace(tx.as_mut(), aturi, &details.locations).await?;
+    event_tags_replace(tx.as_mut(), aturi, &details.tags).await?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Insert an event, or rewrite it when it is already stored with another CID.
+// Records seen again with the same CID, such as when the firehose is replayed
+// or an import is repeated, are left as they are. The check and the write are
+// one statement, so concurrent upserts of the same record don't both write it.
+// Returns whether the event was written.
+pub async fn event_upsert_with_metadata<T: serde::Serialize>(
+    pool: &StoragePool,
+    aturi: &str,
+    cid: &str,
+    did: &str,
+    lexicon: &str,
+    record: &T,
+    name: &str,
+) -> Result<bool, StorageError> {
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if cid.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "CID cannot be empty".into(),
+        )));
+    }
+
     let mut tx = pool
         .begin()
         .await
@@ -95,17 +251,180 @@ pub async fn event_insert_with_metadata<T: serde::Serialize>(
 
     let now = Utc::now();
 
-    sqlx::query("INSERT INTO events (aturi, cid, did, lexicon, record, name, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)")
-        .bind(aturi)
-        .bind(cid)
-        .bind(did)
-        .bind(lexicon)
-        .bind(json!(record))
-        .bind(name)
-        .bind(now)
-        .execute(tx.as_mut())
+    let record = json!(record);
+    let details = extract_record_details(lexicon, &record, name);
+
+    // The previous record is read from the snapshot of the statement, before
+    // the update, so that edits can be recorded as revisions.
+    let written = sqlx::query!(
+        r#"WITH previous AS (SELECT record FROM events WHERE aturi = $1)
+        INSERT INTO events (aturi, cid, did, lexicon, record, name, updated_at, starts_at, ends_at, mode, status, locations, name_normalized)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (aturi) DO UPDATE
+        SET cid = EXCLUDED.cid, record = EXCLUDED.record, name = EXCLUDED.name, updated_at = EXCLUDED.updated_at,
+            starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at, mode = EXCLUDED.mode, status = EXCLUDED.status,
+            locations = EXCLUDED.locations, latitude = NULL, longitude = NULL, name_normalized = EXCLUDED.name_normalized
+        WHERE events.cid IS DISTINCT FROM EXCLUDED.cid
+        RETURNING (SELECT record FROM previous) AS "previous?: Json<serde_json::Value>""#,
+        aturi,
+        cid,
+        did,
+        lexicon,
+        &record,
+        name,
+        now,
+        details.starts_at,
+        details.ends_at,
+        details.mode.as_deref(),
+        details.status.as_deref(),
+        json!(details.locations),
+        normalize_text(name),
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let Some(written) = written else {
+        return Ok(false);
+    };
+
+    event_locations_replace(tx.as_mut(), aturi, &details.locations).await?;
+    event_tags_replace(tx.as_mut(), aturi, &details.tags).await?;
+
+    if let Some(Json(previous_record)) = &written.previous {
+        let changes = event_record_changes(lexicon, previous_record, &record);
+        if !changes.is_empty() {
+            event_revision_insert(
+                tx.as_mut(),
+                aturi,
+                cid,
+                previous_record,
+                &record,
+                &changes,
+                now,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit()
         .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(true)
+}
+
+/// The most rows written by one statement of `events_insert_many` and
+/// `rsvps_insert_many`. Postgres allows 65535 bound parameters per statement,
+/// so larger batches are written with several statements in one transaction.
+const MAX_ROWS_PER_STATEMENT: usize = 1000;
+
+pub struct EventInsertParams<'a, T: serde::Serialize> {
+    pub aturi: &'a str,
+    pub cid: &'a str,
+    pub did: &'a str,
+    pub lexicon: &'a str,
+    pub record: &'a T,
+    pub name: &'a str,
+}
+
+// Insert or update many events in one transaction, such as the events of an
+// import. Events that are already stored with another CID have their record
+// and details replaced like `event_update_with_metadata` does, and those
+// stored with the same CID are left as they are. When an event is given more
+// than once, the last one is stored.
+pub async fn events_insert_many<T: serde::Serialize>(
+    pool: &StoragePool,
+    events: &[EventInsertParams<'_, T>],
+) -> Result<(), StorageError> {
+    if events.iter().any(|event| event.aturi.trim().is_empty()) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    // One statement can't insert and then update the same row.
+    let mut seen = HashSet::new();
+    let mut events = events
+        .iter()
+        .rev()
+        .filter(|event| seen.insert(event.aturi))
+        .collect::<Vec<_>>();
+    events.reverse();
+
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+
+    let rows = events
+        .into_iter()
+        .map(|event| {
+            let record = json!(event.record);
+            let details = extract_record_details(event.lexicon, &record, event.name);
+            (event, record, details)
+        })
+        .collect::<Vec<_>>();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    for chunk in rows.chunks(MAX_ROWS_PER_STATEMENT) {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO events (aturi, cid, did, lexicon, record, name, updated_at, starts_at, ends_at, mode, status, locations, name_normalized) ",
+        );
+        query_builder.push_values(chunk, |mut row, (event, record, details)| {
+            row.push_bind(event.aturi)
+                .push_bind(event.cid)
+                .push_bind(event.did)
+                .push_bind(event.lexicon)
+                .push_bind(record)
+                .push_bind(event.name)
+                .push_bind(now)
+                .push_bind(details.starts_at)
+                .push_bind(details.ends_at)
+                .push_bind(details.mode.as_deref())
+                .push_bind(details.status.as_deref())
+                .push_bind(json!(details.locations))
+                .push_bind(normalize_text(event.name));
+        });
+        query_builder.push(
+            r" ON CONFLICT (aturi) DO UPDATE
+            SET cid = EXCLUDED.cid, record = EXCLUDED.record, name = EXCLUDED.name, updated_at = EXCLUDED.updated_at,
+                starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at, mode = EXCLUDED.mode, status = EXCLUDED.status,
+                locations = EXCLUDED.locations, latitude = NULL, longitude = NULL, name_normalized = EXCLUDED.name_normalized
+            WHERE events.cid IS DISTINCT FROM EXCLUDED.cid
+            RETURNING aturi",
+        );
+
+        let written = query_builder
+            .build_query_scalar::<String>()
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let written = chunk
+            .iter()
+            .filter(|(event, _, _)| written.contains(event.aturi))
+            .collect::<Vec<_>>();
+
+        let locations = written
+            .iter()
+            .map(|(event, _, details)| (event.aturi, details.locations.as_slice()))
+            .collect::<Vec<_>>();
+        event_locations_replace_many(tx.as_mut(), &locations).await?;
+
+        let tags = written
+            .iter()
+            .map(|(event, _, details)| (event.aturi, details.tags.as_slice()))
+            .collect::<Vec<_>>();
+        event_tags_replace_many(tx.as_mut(), &tags).await?;
+    }
 
     tx.commit()
         .await
@@ -124,29 +443,161 @@ pub struct RsvpInsertParams<'a, T: serde::Serialize> {
 }
 
 pub async fn rsvp_insert_with_metadata<T: serde::Serialize>(
-    pool: &StoragePool,
+    conn: impl Acquire<'_, Database = Postgres>,
     params: RsvpInsertParams<'_, T>,
 ) -> Result<(), StorageError> {
-    let mut tx = pool
+    let mut tx = conn
         .begin()
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
     let now = Utc::now();
 
-    sqlx::query("INSERT INTO rsvps (aturi, cid, did, lexicon, record, event_aturi, event_cid, status, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) ON CONFLICT (aturi) DO UPDATE SET record = $5, cid = $2, status = $8, updated_at = $9")
-            .bind(params.aturi)
-            .bind(params.cid)
-            .bind(params.did)
-            .bind(params.lexicon)
-            .bind(json!(params.record))
-            .bind(params.event_aturi)
-            .bind(params.event_cid)
-            .bind(params.status)
-            .bind(now)
+    let previous_status = sqlx::query_scalar!(
+        "SELECT status FROM rsvps WHERE aturi = $1 FOR UPDATE",
+        params.aturi,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    // RSVPs received after the event's deadline are still indexed, since they
+    // exist on the network, but are marked late. Re-indexing an RSVP without a
+    // status change keeps its original late flag.
+    sqlx::query!(
+        r"INSERT INTO rsvps (aturi, cid, did, lexicon, record, event_aturi, event_cid, status, updated_at, late)
+        VALUES (
+            $1, $2, $3, $4, $5, $6::text, $7, $8, $9,
+            COALESCE((SELECT $9 > COALESCE(rsvp_closes_at, starts_at) FROM events WHERE aturi = $6), FALSE)
+        )
+        ON CONFLICT (aturi) DO UPDATE SET record = $5, cid = $2, status = $8, updated_at = $9,
+            late = CASE WHEN rsvps.status = $8 THEN rsvps.late ELSE EXCLUDED.late END",
+        params.aturi,
+        params.cid,
+        params.did,
+        params.lexicon,
+        json!(params.record),
+        params.event_aturi,
+        params.event_cid,
+        params.status,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    // New RSVPs and status changes are kept in the RSVP history. Re-indexing
+    // an RSVP with the same status isn't a change.
+    if previous_status.as_deref() != Some(params.status) {
+        sqlx::query!(
+            "INSERT INTO rsvp_history (rsvp_aturi, event_aturi, did, previous_status, status, created_at) VALUES ($1, $2, $3, $4, $5, $6)",
+            params.aturi,
+            params.event_aturi,
+            params.did,
+            previous_status,
+            params.status,
+            now,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Insert or update many RSVPs in one transaction, such as the RSVPs of an
+// import. Each is marked late and recorded in the RSVP history like
+// `rsvp_insert_with_metadata` does. When an
+// RSVP is given more than once, the last one is stored.
+pub async fn rsvps_insert_many<T: serde::Serialize>(
+    pool: &StoragePool,
+    rsvps: &[RsvpInsertParams<'_, T>],
+) -> Result<(), StorageError> {
+    if rsvps.iter().any(|rsvp| rsvp.aturi.trim().is_empty()) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "RSVP URI cannot be empty".into(),
+        )));
+    }
+
+    // One statement can't insert and then update the same row.
+    let mut seen = HashSet::new();
+    let mut rsvps = rsvps
+        .iter()
+        .rev()
+        .filter(|rsvp| seen.insert(rsvp.aturi))
+        .collect::<Vec<_>>();
+    rsvps.reverse();
+
+    if rsvps.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    for chunk in rsvps.chunks(MAX_ROWS_PER_STATEMENT) {
+        // The history is written first, while the previous statuses are
+        // still stored.
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r"INSERT INTO rsvp_history (rsvp_aturi, event_aturi, did, previous_status, status, created_at)
+            SELECT v.aturi, v.event_aturi, v.did, r.status, v.status, v.created_at
+            FROM (",
+        );
+        query_builder.push_values(chunk, |mut row, rsvp| {
+            row.push_bind(rsvp.aturi)
+                .push_bind(rsvp.event_aturi)
+                .push_bind(rsvp.did)
+                .push_bind(rsvp.status)
+                .push_bind(now);
+        });
+        query_builder.push(
+            r") AS v (aturi, event_aturi, did, status, created_at)
+            LEFT JOIN rsvps r ON r.aturi = v.aturi
+            WHERE r.status IS DISTINCT FROM v.status",
+        );
+
+        query_builder
+            .build()
+            .execute(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r"INSERT INTO rsvps (aturi, cid, did, lexicon, record, event_aturi, event_cid, status, updated_at, late)
+            SELECT v.aturi, v.cid, v.did, v.lexicon, v.record, v.event_aturi, v.event_cid, v.status, v.updated_at,
+                COALESCE((SELECT v.updated_at > COALESCE(e.rsvp_closes_at, e.starts_at) FROM events e WHERE e.aturi = v.event_aturi), FALSE)
+            FROM (",
+        );
+        query_builder.push_values(chunk, |mut row, rsvp| {
+            row.push_bind(rsvp.aturi)
+                .push_bind(rsvp.cid)
+                .push_bind(rsvp.did)
+                .push_bind(rsvp.lexicon)
+                .push_bind(json!(rsvp.record))
+                .push_bind(rsvp.event_aturi)
+                .push_bind(rsvp.event_cid)
+                .push_bind(rsvp.status)
+                .push_bind(now);
+        });
+        query_builder.push(
+            r") AS v (aturi, cid, did, lexicon, record, event_aturi, event_cid, status, updated_at)
+            ON CONFLICT (aturi) DO UPDATE SET record = EXCLUDED.record, cid = EXCLUDED.cid, status = EXCLUDED.status, updated_at = EXCLUDED.updated_at,
+                late = CASE WHEN rsvps.status = EXCLUDED.status THEN rsvps.late ELSE EXCLUDED.late END",
+        );
+
+        query_builder
+            .build()
             .execute(tx.as_mut())
             .await
             .map_err(StorageError::UnableToExecuteQuery)?;
+    }
 
     tx.commit()
         .await
@@ -154,7 +605,7 @@ pub async fn rsvp_insert_with_metadata<T: serde::Serialize>(
 }
 
 pub async fn rsvp_insert(
-    pool: &StoragePool,
+    conn: impl Acquire<'_, Database = Postgres>,
     aturi: &str,
     cid: &str,
     did: &str,
@@ -179,7 +630,7 @@ pub async fn rsvp_insert(
 
     // Call the generic function with extracted values
     rsvp_insert_with_metadata(
-        pool,
+        conn,
         RsvpInsertParams {
             aturi,
             cid,
@@ -194,6 +645,21 @@ pub async fn rsvp_insert(
     .await
 }
 
+pub async fn event_upsert(
+    pool: &StoragePool,
+    aturi: &str,
+    cid: &str,
+    did: &str,
+    lexicon: &str,
+    record: &EventLexicon,
+) -> Result<bool, StorageError> {
+    let name = match record {
+        EventLexicon::Current { name, .. } => name,
+    };
+
+    event_upsert_with_metadata(pool, aturi, cid, did, lexicon, record, name).await
+}
+
 // Helper function to extract event information based on lexicon type
 // Helper function to format address information into a readable string
 pub fn format_address(
@@ -250,18 +716,71 @@ pub fn format_address(
     }
 }
 
+// Helper function to format a geo location into a readable string
+pub fn format_geo(geo: &crate::atproto::lexicon::community::lexicon::location::Geo) -> String {
+    match geo {
+        crate::atproto::lexicon::community::lexicon::location::Geo::Current {
+            latitude,
+            longitude,
+            name,
+        } => {
+            let coordinates = format!("{}, {}", latitude.trim(), longitude.trim());
+            match name.as_deref().map(str::trim) {
+                Some(name_val) if !name_val.is_empty() => format!("{} ({})", name_val, coordinates),
+                _ => coordinates,
+            }
+        }
+    }
+}
+
+// Helper function to format a Foursquare place into a readable string
+pub fn format_fsq(fsq: &crate::atproto::lexicon::community::lexicon::location::Fsq) -> String {
+    match fsq {
+        crate::atproto::lexicon::community::lexicon::location::Fsq::Current {
+            fsq_place_id,
+            name,
+        } => match name.as_deref().map(str::trim) {
+            Some(name_val) if !name_val.is_empty() => name_val.to_string(),
+            _ => format!("Foursquare place {}", fsq_place_id),
+        },
+    }
+}
+
+// Helper function to format any physical location into a readable string.
+// Returns None for locations that aren't places, like links.
+pub fn format_location(
+    location: &crate::atproto::lexicon::community::lexicon::calendar::event::EventLocation,
+) -> Option<String> {
+    use crate::atproto::lexicon::community::lexicon::calendar::event::EventLocation;
+
+    match location {
+        EventLocation::Address(address) => Some(format_address(address)),
+        EventLocation::Geo(geo) => Some(format_geo(geo)),
+        EventLocation::Fsq(fsq) => Some(format_fsq(fsq)),
+        EventLocation::Uri(_) | EventLocation::Hthree(_) => None,
+    }
+}
+
 pub fn extract_event_details(event: &Event) -> EventDetails {
+    extract_record_details(&event.lexicon, &event.record.0, &event.name)
+}
+
+// Parse the details of a serialized event record. The fallback name is used
+// when the record can't be parsed as the given lexicon.
+pub fn extract_record_details(
+    lexicon: &str,
+    record: &serde_json::Value,
+    fallback_name: &str,
+) -> EventDetails {
     use crate::atproto::lexicon::{
         community::lexicon::calendar::event::{Event as CommunityEvent, Mode, Status},
         events::smokesignal::calendar::event::Event as SmokeSignalEvent,
     };
 
     // Try to parse the record based on the lexicon
-    match event.lexicon.as_str() {
+    match lexicon {
         "community.lexicon.calendar.event" => {
-            if let Ok(community_event) =
-                serde_json::from_value::<CommunityEvent>(event.record.0.clone())
-            {
+            if let Ok(community_event) = serde_json::from_value::<CommunityEvent>(record.clone()) {
                 match community_event {
                     CommunityEvent::Current {
                         name,
@@ -273,7 +792,7 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                         status,
                         locations,
                         uris,
-                        ..
+                        extra,
                     } => EventDetails {
                         name: Cow::Owned(name.clone()),
                         description: Cow::Owned(description.clone()),
@@ -310,12 +829,16 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                         }),
                         locations,
                         uris,
+                        timezone: extra_timezone(&extra),
+                        doors_open_at: extra_doors_open_at(&extra),
+                        description_facets: extra_description_facets(&extra),
+                        tags: extra_tags(&extra),
                     },
                 }
             } else {
                 // Fallback to the event's direct name if parsing fails
                 EventDetails {
-                    name: Cow::Owned(event.name.clone()),
+                    name: Cow::Owned(fallback_name.to_string()),
                     description: Cow::Borrowed(""),
                     created_at: None,
                     starts_at: None,
@@ -324,12 +847,15 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                     status: None,
                     locations: vec![],
                     uris: vec![],
+                    timezone: None,
+                    doors_open_at: None,
+                    description_facets: vec![],
+                    tags: vec![],
                 }
             }
         }
         "events.smokesignal.calendar.event" => {
-            if let Ok(ss_event) = serde_json::from_value::<SmokeSignalEvent>(event.record.0.clone())
-            {
+            if let Ok(ss_event) = serde_json::from_value::<SmokeSignalEvent>(record.clone()) {
                 match ss_event {
                     SmokeSignalEvent::Current {
                         name,
@@ -420,13 +946,17 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                             status: status.map(Cow::Owned),
                             locations,
                             uris,
+                            timezone: extra_timezone(&extra),
+                            doors_open_at: extra_doors_open_at(&extra),
+                            description_facets: extra_description_facets(&extra),
+                            tags: extra_tags(&extra),
                         }
                     }
                 }
             } else {
                 // Fallback to the event's direct name if parsing fails
                 EventDetails {
-                    name: Cow::Owned(event.name.clone()),
+                    name: Cow::Owned(fallback_name.to_string()),
                     description: Cow::Borrowed(""),
                     created_at: None,
                     starts_at: None,
@@ -435,13 +965,17 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                     status: None,
                     locations: vec![],
                     uris: vec![],
+                    timezone: None,
+                    doors_open_at: None,
+                    description_facets: vec![],
+                    tags: vec![],
                 }
             }
         }
         _ => {
             // Unknown event type - use the stored name
             EventDetails {
-                name: Cow::Owned(event.name.clone()),
+                name: Cow::Owned(fallback_name.to_string()),
                 description: Cow::Borrowed(""),
                 created_at: None,
                 starts_at: None,
@@ -450,6 +984,10 @@ pub fn extract_event_details(event: &Event) -> EventDetails {
                 status: None,
                 locations: vec![],
                 uris: vec![],
+                timezone: None,
+                doors_open_at: None,
+                description_facets: vec![],
+                tags: vec![],
             }
         }
     }
@@ -467,9 +1005,90 @@ pub struct EventDetails {
     pub status: Option<Cow<'static, str>>,
     pub locations: Vec<crate::atproto::lexicon::community::lexicon::calendar::event::EventLocation>,
     pub uris: Vec<crate::atproto::lexicon::community::lexicon::calendar::event::EventLink>,
+    /// The time zone the organizer picked for the event, when it is a known
+    /// IANA time zone.
+    pub timezone: Option<String>,
+    /// When doors open or arrivals start, before the event starts.
+    pub doors_open_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The mentions and links in the description.
+    pub description_facets: Vec<Facet>,
+    /// The normalized topics of the event.
+    pub tags: Vec<String>,
+}
+
+// Get the time zone the organizer picked from the fields of an event record
+// that aren't otherwise modeled.
+fn extra_timezone(extra: &HashMap<String, serde_json::Value>) -> Option<String> {
+    extra
+        .get(EXTRA_TIMEZONE)
+        .and_then(|value| value.as_str())
+        .filter(|value| value.parse::<chrono_tz::Tz>().is_ok())
+        .map(ToString::to_string)
+}
+
+// Get when doors open from the fields of an event record that aren't
+// otherwise modeled.
+fn extra_doors_open_at(
+    extra: &HashMap<String, serde_json::Value>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    extra
+        .get(EXTRA_DOORS_OPEN_AT)
+        .and_then(|value| value.as_str())
+        .and_then(|value| chrono::DateTime::parse_from_rfc3339(value).ok())
+        .map(|value| value.with_timezone(&chrono::Utc))
+}
+
+// Get the mentions and links in the description from the fields of an
+// event record that aren't otherwise modeled. Facets that can't be read are
+// skipped.
+fn extra_description_facets(extra: &HashMap<String, serde_json::Value>) -> Vec<Facet> {
+    extra
+        .get(EXTRA_DESCRIPTION_FACETS)
+        .and_then(|value| value.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| serde_json::from_value::<Facet>(value.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Get the topics of an event from the fields of an event record that aren't
+// otherwise modeled, normalized like they are indexed.
+fn extra_tags(extra: &HashMap<String, serde_json::Value>) -> Vec<String> {
+    let tags = extra
+        .get(EXTRA_TAGS)
+        .and_then(|value| value.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    event_tag_keys(&tags)
+}
+
+// The time after which new RSVPs to the event are closed: the organizer's
+// configured deadline, or the event start.
+pub fn event_rsvp_deadline(event: &Event) -> Option<DateTime<Utc>> {
+    event
+        .rsvp_closes_at
+        .or(event.starts_at)
+        .or_else(|| extract_event_details(event).starts_at)
+}
+
+pub fn event_rsvps_closed(event: &Event, now: DateTime<Utc>) -> bool {
+    event_rsvp_deadline(event).is_some_and(|deadline| now > deadline)
 }
 
-pub async fn event_get(pool: &StoragePool, aturi: &str) -> Result<Event, StorageError> {
+// Set or clear the organizer-configured RSVP deadline for an event.
+pub async fn event_update_rsvp_deadline(
+    pool: &StoragePool,
+    aturi: &str,
+    rsvp_closes_at: Option<DateTime<Utc>>,
+) -> Result<(), StorageError> {
     // Validate aturi is not empty
     if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -482,23 +1101,34 @@ pub async fn event_get(pool: &StoragePool, aturi: &str) -> Result<Event, Storage
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let record = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE aturi = $1")
-        .bind(aturi)
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(|err| match err {
-            sqlx::Error::RowNotFound => StorageError::RowNotFound("event".to_string(), err),
-            other => StorageError::UnableToExecuteQuery(other),
-        })?;
+    sqlx::query!(
+        "UPDATE events SET rsvp_closes_at = $2 WHERE aturi = $1",
+        aturi,
+        rsvp_closes_at,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
 
-    Ok(record)
+// Whether the event has no going spots left for someone who isn't already
+// going.
+pub fn event_rsvps_full(event: &Event, going_count: i64) -> bool {
+    event
+        .rsvp_capacity
+        .is_some_and(|capacity| going_count >= i64::from(capacity))
 }
 
-pub async fn event_exists(pool: &StoragePool, aturi: &str) -> Result<bool, StorageError> {
+// Set or clear the organizer-configured limit on going RSVPs for an event.
+pub async fn event_update_rsvp_capacity(
+    pool: &StoragePool,
+    aturi: &str,
+    rsvp_capacity: Option<i32>,
+) -> Result<(), StorageError> {
     // Validate aturi is not empty
     if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -506,28 +1136,41 @@ pub async fn event_exists(pool: &StoragePool, aturi: &str) -> Result<bool, Stora
         )));
     }
 
+    // Validate capacity is positive
+    if rsvp_capacity.is_some_and(|capacity| capacity < 1) {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "RSVP capacity must be positive".into(),
+        )));
+    }
+
     let mut tx = pool
         .begin()
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let total_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM events WHERE aturi = $1")
-        .bind(aturi)
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    sqlx::query!(
+        "UPDATE events SET rsvp_capacity = $2 WHERE aturi = $1",
+        aturi,
+        rsvp_capacity,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(total_count > 0)
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
 }
 
-pub async fn event_get_cid(
-    pool: &StoragePool,
+// Lock an event until the end of the transaction and return its RSVP
+// capacity. Going RSVPs hold the lock until they are stored, so that two of
+// them can't both take the last spot. The going count has to be read after
+// the lock is taken, in a statement of its own, to see RSVPs stored while
+// waiting for it.
+pub async fn event_lock_rsvp_capacity(
+    executor: impl PgExecutor<'_>,
     aturi: &str,
-) -> Result<Option<String>, StorageError> {
+) -> Result<Option<i32>, StorageError> {
     // Validate aturi is not empty
     if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -535,91 +1178,98 @@ pub async fn event_get_cid(
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let record = sqlx::query_scalar::<_, String>("SELECT cid FROM events WHERE aturi = $1")
-        .bind(aturi)
-        .fetch_optional(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
-
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(record)
+    sqlx::query_scalar!(
+        "SELECT rsvp_capacity FROM events WHERE aturi = $1 FOR UPDATE",
+        aturi,
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)
 }
 
-pub async fn event_list_did_recently_updated(
-    pool: &StoragePool,
-    did: &str,
-    page: i64,
-    page_size: i64,
-) -> Result<Vec<EventWithRole>, StorageError> {
-    // Validate did is not empty
-    if did.trim().is_empty() {
+// Count the going RSVPs to an event, for comparing against its capacity.
+pub async fn event_going_count(
+    executor: impl PgExecutor<'_>,
+    aturi: &str,
+) -> Result<i64, StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "DID cannot be empty".into(),
+            "Event URI cannot be empty".into(),
         )));
     }
 
-    // Validate page and page_size are positive
-    if page < 1 || page_size < 1 {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM rsvps WHERE event_aturi = $1 AND status = 'going'"#,
+        aturi,
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)
+}
+
+// Fetch a batch of events ordered by AT-URI, starting after the given AT-URI.
+// Used by jobs that walk every stored event.
+pub async fn event_list_batch(
+    executor: impl PgExecutor<'_>,
+    after_aturi: Option<&str>,
+    batch_size: i64,
+) -> Result<Vec<Event>, StorageError> {
+    if batch_size < 1 {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Page and page size must be positive".into(),
+            "Batch size must be positive".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let offset = (page - 1) * page_size;
+    let events = sqlx::query_as!(
+        Event,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            name,
+            updated_at AS "updated_at?",
+            rsvp_closes_at,
+            rsvp_capacity,
+            starts_at,
+            ends_at,
+            mode,
+            status,
+            latitude,
+            longitude
+        FROM events WHERE $1::text IS NULL OR aturi > $1 ORDER BY aturi ASC LIMIT $2"#,
+        after_aturi,
+        batch_size,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
-    let events_query = r"SELECT
-    events.*,
-    'organizer' as role
-FROM
-    events
-WHERE
-    events.did = $1
-ORDER BY
-    events.updated_at DESC,
-    events.aturi ASC
-LIMIT
-$2
-OFFSET
-$3
-";
+    Ok(events)
+}
 
-    let event_roles = sqlx::query_as::<_, EventWithRole>(events_query)
-        .bind(did)
-        .bind(page_size + 1)
-        .bind(offset)
-        .fetch_all(tx.as_mut())
+pub async fn event_count(executor: impl PgExecutor<'_>) -> Result<i64, StorageError> {
+    let total_count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM events"#)
+        .fetch_one(executor)
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(event_roles)
+    Ok(total_count)
 }
 
-pub async fn event_list_recently_updated(
+// Refresh the columns derived from the event record. The record itself and
+// updated_at are left unchanged.
+pub async fn event_update_details(
     pool: &StoragePool,
-    page: i64,
-    page_size: i64,
-) -> Result<Vec<EventWithRole>, StorageError> {
-    // Validate page and page_size are positive
-    if page < 1 || page_size < 1 {
+    aturi: &str,
+    details: &EventDetails,
+) -> Result<(), StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Page and page size must be positive".into(),
+            "Event URI cannot be empty".into(),
         )));
     }
 
@@ -628,160 +1278,160 @@ pub async fn event_list_recently_updated(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let offset = (page - 1) * page_size;
-
-    let events_query = r"SELECT
-        events.*,
-        'organizer' as role
-    FROM
-        events
-    ORDER BY
-        events.updated_at DESC,
-        events.aturi ASC
-    LIMIT $1
-    OFFSET $2";
+    sqlx::query!(
+        r"UPDATE events
+        SET name = $2, starts_at = $3, ends_at = $4, mode = $5, status = $6, locations = $7, name_normalized = $8
+        WHERE aturi = $1",
+        aturi,
+        details.name.as_ref(),
+        details.starts_at,
+        details.ends_at,
+        details.mode.as_deref(),
+        details.status.as_deref(),
+        json!(details.locations),
+        normalize_text(&details.name),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
-    let event_roles = sqlx::query_as::<_, EventWithRole>(events_query)
-        .bind(page_size + 1)
-        .bind(offset)
-        .fetch_all(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    event_locations_replace(tx.as_mut(), aturi, &details.locations).await?;
+    event_tags_replace(tx.as_mut(), aturi, &details.tags).await?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(event_roles)
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
 }
 
-pub async fn get_event_rsvps(
+// Set or clear the coordinates of an event. Edits clear the coordinates so
+// that they are resolved again from the new address.
+pub async fn event_update_coordinates(
     pool: &StoragePool,
-    event_aturi: &str,
-    status: Option<&str>,
-) -> Result<Vec<(String, String)>, StorageError> {
-    // Validate event_aturi is not empty
-    if event_aturi.trim().is_empty() {
+    aturi: &str,
+    coordinates: Option<(f64, f64)>,
+) -> Result<(), StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
             "Event URI cannot be empty".into(),
         )));
     }
 
-    // If status is provided, validate it's not empty
-    if let Some(status_val) = status {
-        if status_val.trim().is_empty() {
-            return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-                "Status cannot be empty".into(),
-            )));
-        }
-    }
-
     let mut tx = pool
         .begin()
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let query = if status.is_some() {
-        "SELECT did, status FROM rsvps WHERE event_aturi = $1 AND status = $2"
-    } else {
-        "SELECT did, status FROM rsvps WHERE event_aturi = $1"
-    };
-
-    let rsvps = if let Some(status_value) = status {
-        sqlx::query_as::<_, (String, String)>(query)
-            .bind(event_aturi)
-            .bind(status_value)
-            .fetch_all(tx.as_mut())
-            .await
-    } else {
-        sqlx::query_as::<_, (String, String)>(query)
-            .bind(event_aturi)
-            .fetch_all(tx.as_mut())
-            .await
-    }
+    sqlx::query!(
+        "UPDATE events SET latitude = $2, longitude = $3 WHERE aturi = $1",
+        aturi,
+        coordinates.map(|(latitude, _)| latitude),
+        coordinates.map(|(_, longitude)| longitude),
+    )
+    .execute(tx.as_mut())
+    .await
     .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(rsvps)
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
 }
 
-pub async fn get_user_rsvp(
-    pool: &StoragePool,
-    event_aturi: &str,
-    did: &str,
-) -> Result<Option<String>, StorageError> {
-    // Validate event_aturi is not empty
-    if event_aturi.trim().is_empty() {
+#[tracing::instrument(skip(executor))]
+pub async fn event_get(executor: impl PgExecutor<'_>, aturi: &str) -> Result<Event, StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
             "Event URI cannot be empty".into(),
         )));
     }
 
-    // Validate did is not empty
-    if did.trim().is_empty() {
+    let record = sqlx::query_as!(
+        Event,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            name,
+            updated_at AS "updated_at?",
+            rsvp_closes_at,
+            rsvp_capacity,
+            starts_at,
+            ends_at,
+            mode,
+            status,
+            latitude,
+            longitude
+        FROM events WHERE aturi = $1"#,
+        aturi,
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => StorageError::RowNotFound("event".to_string(), err),
+        other => StorageError::UnableToExecuteQuery(other),
+    })?;
+
+    Ok(record)
+}
+
+#[tracing::instrument(skip(executor))]
+pub async fn event_exists(
+    executor: impl PgExecutor<'_>,
+    aturi: &str,
+) -> Result<bool, StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "DID cannot be empty".into(),
+            "Event URI cannot be empty".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let status = sqlx::query_scalar::<_, String>(
-        "SELECT status FROM rsvps WHERE event_aturi = $1 AND did = $2",
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM events WHERE aturi = $1"#,
+        aturi,
     )
-    .bind(event_aturi)
-    .bind(did)
-    .fetch_optional(tx.as_mut())
+    .fetch_one(executor)
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
-    Ok(status)
+    Ok(total_count > 0)
 }
 
-pub async fn rsvp_get(pool: &StoragePool, aturi: &str) -> Result<Option<Rsvp>, StorageError> {
+pub async fn event_get_cid(
+    executor: impl PgExecutor<'_>,
+    aturi: &str,
+) -> Result<Option<String>, StorageError> {
     // Validate aturi is not empty
     if aturi.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "RSVP URI cannot be empty".into(),
+            "Event URI cannot be empty".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let rsvp = sqlx::query_as::<_, Rsvp>("SELECT * FROM rsvps WHERE aturi = $1")
-        .bind(aturi)
-        .fetch_optional(tx.as_mut())
-        .await
-        .map_err(|err| match err {
-            sqlx::Error::RowNotFound => StorageError::RSVPNotFound,
-            other => StorageError::UnableToExecuteQuery(other),
-        })?;
-
-    tx.commit()
+    let record = sqlx::query_scalar!("SELECT cid FROM events WHERE aturi = $1", aturi)
+        .fetch_optional(executor)
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+        .map_err(StorageError::UnableToExecuteQuery)?;
 
-    Ok(rsvp)
+    Ok(record)
 }
 
-pub async fn rsvp_list(
-    pool: &StoragePool,
+pub async fn event_list_did_recently_updated(
+    executor: impl PgExecutor<'_>,
+    did: &str,
     page: i64,
     page_size: i64,
-) -> Result<(i64, Vec<Rsvp>), StorageError> {
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
     // Validate page and page_size are positive
     if page < 1 || page_size < 1 {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -789,125 +1439,1336 @@ pub async fn rsvp_list(
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let total_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM rsvps")
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
-
     let offset = (page - 1) * page_size;
 
-    let rsvps = sqlx::query_as::<_, Rsvp>(
-        r"SELECT * FROM rsvps ORDER BY rsvps.updated_at DESC LIMIT $1 OFFSET $2",
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+    events.aturi,
+    events.cid,
+    events.did,
+    events.lexicon,
+    events.record AS "record: Json<serde_json::Value>",
+    events.name,
+    events.updated_at AS "updated_at?",
+    events.rsvp_closes_at,
+    events.rsvp_capacity,
+    events.starts_at,
+    events.ends_at,
+    events.mode,
+    events.status,
+    events.latitude,
+    events.longitude,
+    'organizer' as "role!"
+FROM
+    events
+WHERE
+    events.did = $1
+ORDER BY
+    events.updated_at DESC,
+    events.aturi ASC
+LIMIT
+$2
+OFFSET
+$3
+"#,
+        did,
+        page_size + 1,
+        offset,
     )
-    .bind(page_size + 1) // Fetch one more to know if there are more entries
-    .bind(offset)
-    .fetch_all(tx.as_mut())
+    .fetch_all(executor)
     .await
-    .map_err(StorageError::UnableToExecuteQuery)?;
-
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
 
-    Ok((total_count, rsvps))
+    Ok(event_roles)
 }
 
-pub async fn event_update_with_metadata<T: serde::Serialize>(
-    pool: &StoragePool,
-    aturi: &str,
-    cid: &str,
-    record: &T,
-    name: &str,
-) -> Result<(), StorageError> {
-    // Validate inputs
-    if aturi.trim().is_empty() {
-        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Event URI cannot be empty".into(),
-        )));
-    }
-
-    if cid.trim().is_empty() {
+// List the events an organizer hasn't finished yet, soonest first. Events
+// without a start time are listed last.
+pub async fn event_list_organized_by_did(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+    after: DateTime<Utc>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "CID cannot be empty".into(),
+            "DID cannot be empty".into(),
         )));
     }
 
-    if name.trim().is_empty() {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Name cannot be empty".into(),
+            "Page and page size must be positive".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let now = Utc::now();
+    let offset = (page - 1) * page_size;
 
-    sqlx::query(
-        "UPDATE events SET cid = $1, record = $2, name = $3, updated_at = $4 WHERE aturi = $5",
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.did = $1
+        AND (
+            events.starts_at IS NULL
+            OR COALESCE(events.ends_at, events.starts_at) >= $2
+        )
+    ORDER BY
+        events.starts_at ASC NULLS LAST,
+        events.aturi ASC
+    LIMIT $3
+    OFFSET $4"#,
+        did,
+        after,
+        page_size + 1,
+        offset,
     )
-    .bind(cid)
-    .bind(json!(record))
-    .bind(name)
-    .bind(now)
-    .bind(aturi)
-    .execute(tx.as_mut())
+    .fetch_all(executor)
     .await
-    .map_err(StorageError::UnableToExecuteQuery)?;
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)
+    Ok(event_roles)
 }
 
-pub async fn count_event_rsvps(
-    pool: &StoragePool,
-    event_aturi: &str,
+// List the events that an identity has RSVP'd to with a status and that
+// haven't finished yet, soonest first. The role of each event is the status
+// of the RSVP.
+pub async fn event_list_rsvped_by_did(
+    executor: impl PgExecutor<'_>,
+    did: &str,
     status: &str,
-) -> Result<u32, StorageError> {
-    // Validate inputs
-    if event_aturi.trim().is_empty() {
+    after: DateTime<Utc>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Event URI cannot be empty".into(),
+            "DID cannot be empty".into(),
         )));
     }
 
-    if status.trim().is_empty() {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Status cannot be empty".into(),
+            "Page and page size must be positive".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        rsvps.status as role
+    FROM
+        rsvps
+        INNER JOIN events ON events.aturi = rsvps.event_aturi
+    WHERE
+        rsvps.did = $1
+        AND rsvps.status = $2
+        AND COALESCE(events.ends_at, events.starts_at) >= $3
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC
+    LIMIT $4
+    OFFSET $5"#,
+        did,
+        status,
+        after,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// Count an organizer's events that started before a time by the month they
+// started in, in the given time zone, latest month first.
+pub async fn event_months_did_past(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+    tz: &str,
+    before: DateTime<Utc>,
+) -> Result<Vec<(i32, i32, i64)>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let months = sqlx::query!(
+        r#"SELECT
+            EXTRACT(YEAR FROM starts_at AT TIME ZONE $2)::int AS "year!",
+            EXTRACT(MONTH FROM starts_at AT TIME ZONE $2)::int AS "month!",
+            COUNT(*) AS "count!"
+        FROM events
+        WHERE did = $1 AND starts_at < $3
+        GROUP BY 1, 2
+        ORDER BY 1 DESC, 2 DESC"#,
+        did,
+        tz,
+        before,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(|row| (row.year, row.month, row.count))
+    .collect::<Vec<_>>();
+
+    Ok(months)
+}
+
+// List an organizer's events that started in a range of time, in the order
+// they started.
+pub async fn event_list_did_starts_between(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+    starts_after: DateTime<Utc>,
+    starts_before: DateTime<Utc>,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.did = $1
+        AND events.starts_at >= $2
+        AND events.starts_at < $3
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC"#,
+        did,
+        starts_after,
+        starts_before,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// List all events that start in a range of time, in the order they start.
+// This is used for calendar views, so the range is expected to be a day or a
+// month.
+pub async fn event_list_starts_between(
+    executor: impl PgExecutor<'_>,
+    starts_after: DateTime<Utc>,
+    starts_before: DateTime<Utc>,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.starts_at >= $1
+        AND events.starts_at < $2
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC"#,
+        starts_after,
+        starts_before,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// Fetch the start and end of an organizer's events with the latest starts,
+// latest first.
+pub async fn event_list_did_recent_times(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+    limit: i64,
+) -> Result<Vec<(DateTime<Utc>, Option<DateTime<Utc>>)>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let times = sqlx::query!(
+        r#"SELECT starts_at AS "starts_at!", ends_at
+        FROM events
+        WHERE did = $1 AND starts_at IS NOT NULL
+        ORDER BY starts_at DESC
+        LIMIT $2"#,
+        did,
+        limit,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(|row| (row.starts_at, row.ends_at))
+    .collect::<Vec<_>>();
+
+    Ok(times)
+}
+
+pub async fn event_list_recently_updated(
+    executor: impl PgExecutor<'_>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    ORDER BY
+        events.updated_at DESC,
+        events.aturi ASC
+    LIMIT $1
+    OFFSET $2"#,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// List configured featured events in the order they were given.
+pub async fn event_list_featured(
+    executor: impl PgExecutor<'_>,
+    aturis: &[String],
+) -> Result<Vec<EventWithRole>, StorageError> {
+    if aturis.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.aturi = ANY($1::text[])
+    ORDER BY
+        array_position($1::text[], events.aturi::text) ASC"#,
+        aturis,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// List events that start at or after `after`, soonest first. When a locality
+// is given, only events with a matching address are included.
+pub async fn event_list_upcoming(
+    executor: impl PgExecutor<'_>,
+    after: DateTime<Utc>,
+    locality: Option<&str>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.starts_at >= $1
+        AND (
+            $2::text IS NULL
+            OR EXISTS (
+                SELECT 1 FROM json_array_elements(
+                    CASE WHEN json_typeof(events.record->'locations') = 'array'
+                    THEN events.record->'locations'
+                    ELSE '[]'::json END
+                ) AS location
+                WHERE lower(location->>'locality') = lower($2)
+            )
+        )
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC
+    LIMIT $3
+    OFFSET $4"#,
+        after,
+        locality,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// List events by the time they were first indexed, newest first. The record's
+// own createdAt isn't used since it is whatever the author's client wrote.
+pub async fn event_list_recently_added(
+    executor: impl PgExecutor<'_>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    ORDER BY
+        events.created_at DESC,
+        events.aturi ASC
+    LIMIT $1
+    OFFSET $2"#,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// List events whose name contains the search query, ignoring case and
+// diacritics, soonest upcoming first and then most recently started.
+pub async fn event_search(
+    executor: impl PgExecutor<'_>,
+    query: &str,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    let query = normalize_text(query);
+    if query.is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Search query cannot be empty".into(),
+        )));
+    }
+
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let offset = (page - 1) * page_size;
+
+    // The query is matched literally, so LIKE wildcards in it are escaped
+    let pattern = format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.name_normalized LIKE $1 ESCAPE '\'
+    ORDER BY
+        CASE WHEN events.starts_at >= $2 THEN 0 ELSE 1 END,
+        CASE WHEN events.starts_at >= $2 THEN events.starts_at END ASC,
+        events.starts_at DESC NULLS LAST,
+        events.aturi ASC
+    LIMIT $3
+    OFFSET $4"#,
+        &pattern,
+        Utc::now(),
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    Ok(event_roles)
+}
+
+// Find an event by the same organizer with the same name, ignoring case and
+// diacritics, that starts at the same time. Returns the AT-URI of the first
+// match.
+pub async fn event_find_duplicate(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+    name: &str,
+    starts_at: Option<DateTime<Utc>>,
+) -> Result<Option<String>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let aturi = sqlx::query_scalar!(
+        r"SELECT aturi FROM events
+        WHERE did = $1 AND name_normalized = $2 AND starts_at IS NOT DISTINCT FROM $3
+        ORDER BY aturi ASC
+        LIMIT 1",
+        did,
+        normalize_text(name),
+        starts_at,
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(aturi)
+}
+
+#[tracing::instrument(skip(executor))]
+pub async fn get_event_rsvps(
+    executor: impl PgExecutor<'_>,
+    event_aturi: &str,
+    status: Option<&str>,
+) -> Result<Vec<(String, String)>, StorageError> {
+    // Validate event_aturi is not empty
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    // If status is provided, validate it's not empty
+    if let Some(status_val) = status {
+        if status_val.trim().is_empty() {
+            return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+                "Status cannot be empty".into(),
+            )));
+        }
+    }
+
+    let rsvps = sqlx::query!(
+        "SELECT did, status FROM rsvps WHERE event_aturi = $1 AND ($2::text IS NULL OR status = $2)",
+        event_aturi,
+        status,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(|row| (row.did, row.status))
+    .collect::<Vec<_>>();
+
+    Ok(rsvps)
+}
+
+#[tracing::instrument(skip(executor))]
+pub async fn get_user_rsvp(
+    executor: impl PgExecutor<'_>,
+    event_aturi: &str,
+    did: &str,
+) -> Result<Option<String>, StorageError> {
+    // Validate event_aturi is not empty
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM rsvps WHERE event_aturi = $1 AND did = $2",
+        event_aturi,
+        did,
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(status)
+}
+
+// Fetch the full RSVP record a user made for an event, if any.
+pub async fn get_user_rsvp_record(
+    executor: impl PgExecutor<'_>,
+    event_aturi: &str,
+    did: &str,
+) -> Result<Option<Rsvp>, StorageError> {
+    // Validate event_aturi is not empty
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let rsvp = sqlx::query_as!(
+        Rsvp,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            event_aturi,
+            event_cid,
+            status,
+            updated_at AS "updated_at?",
+            late
+        FROM rsvps WHERE event_aturi = $1 AND did = $2 ORDER BY updated_at DESC LIMIT 1"#,
+        event_aturi,
+        did,
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(rsvp)
+}
+
+pub async fn rsvp_get(
+    executor: impl PgExecutor<'_>,
+    aturi: &str,
+) -> Result<Option<Rsvp>, StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "RSVP URI cannot be empty".into(),
+        )));
+    }
+
+    let rsvp = sqlx::query_as!(
+        Rsvp,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            event_aturi,
+            event_cid,
+            status,
+            updated_at AS "updated_at?",
+            late
+        FROM rsvps WHERE aturi = $1"#,
+        aturi,
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => StorageError::RSVPNotFound,
+        other => StorageError::UnableToExecuteQuery(other),
+    })?;
+
+    Ok(rsvp)
+}
+
+// Remove a withdrawn RSVP from the index.
+pub async fn rsvp_delete(pool: &StoragePool, aturi: &str) -> Result<(), StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "RSVP URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!("DELETE FROM rsvps WHERE aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List RSVPs, most recently updated first, continuing after the given
+// update time and AT-URI.
+pub async fn rsvp_list(
+    executor: impl PgExecutor<'_>,
+    after: Option<(DateTime<Utc>, &str)>,
+    limit: i64,
+) -> Result<Vec<Rsvp>, StorageError> {
+    // Validate limit is positive
+    if limit < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Limit must be positive".into(),
+        )));
+    }
+
+    let (after_updated_at, after_aturi) = after.unzip();
+
+    let rsvps = sqlx::query_as!(
+        Rsvp,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            event_aturi,
+            event_cid,
+            status,
+            updated_at AS "updated_at?",
+            late
+        FROM rsvps
+        WHERE $1::timestamptz IS NULL OR (updated_at, aturi) < ($1, $2)
+        ORDER BY updated_at DESC, aturi DESC
+        LIMIT $3"#,
+        after_updated_at,
+        after_aturi,
+        limit + 1,
+    ) // Fetch one more to know if there are more entries
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(rsvps)
+}
+
+// List the RSVPs to an event, most recently updated first, continuing after
+// the given update time and AT-URI. When a status is given, only RSVPs with
+// that status are listed.
+pub async fn rsvp_list_for_event(
+    executor: impl PgExecutor<'_>,
+    event_aturi: &str,
+    status: Option<&str>,
+    after: Option<(DateTime<Utc>, &str)>,
+    limit: i64,
+) -> Result<Vec<Rsvp>, StorageError> {
+    // Validate event_aturi is not empty
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    // Validate limit is positive
+    if limit < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Limit must be positive".into(),
+        )));
+    }
+
+    let (after_updated_at, after_aturi) = after.unzip();
+
+    let rsvps = sqlx::query_as!(
+        Rsvp,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            event_aturi,
+            event_cid,
+            status,
+            updated_at AS "updated_at?",
+            late
+        FROM rsvps
+        WHERE event_aturi = $1
+        AND ($2::text IS NULL OR status = $2)
+        AND ($3::timestamptz IS NULL OR (updated_at, aturi) < ($3, $4))
+        ORDER BY updated_at DESC, aturi DESC
+        LIMIT $5"#,
+        event_aturi,
+        status,
+        after_updated_at,
+        after_aturi,
+        limit + 1,
+    ) // Fetch one more to know if there are more entries
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(rsvps)
+}
+
+pub async fn rsvp_count(executor: impl PgExecutor<'_>) -> Result<i64, StorageError> {
+    let total_count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM rsvps"#)
+        .fetch_one(executor)
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(total_count)
+}
+
+pub async fn event_update_with_metadata<T: serde::Serialize>(
+    pool: &StoragePool,
+    aturi: &str,
+    cid: &str,
+    record: &T,
+    name: &str,
+) -> Result<(), StorageError> {
+    // Validate inputs
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if cid.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "CID cannot be empty".into(),
+        )));
+    }
+
+    if name.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Name cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let now = Utc::now();
+
+    let previous = sqlx::query!(
+        r#"SELECT lexicon, record AS "record: Json<serde_json::Value>" FROM events WHERE aturi = $1"#,
+        aturi,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .map(|row| (row.lexicon, row.record));
+
+    let lexicon = previous
+        .as_ref()
+        .map(|(lexicon, _)| lexicon.clone())
+        .unwrap_or_default();
+
+    let record = json!(record);
+    let details = extract_record_details(&lexicon, &record, name);
+
+    sqlx::query!(
+        r"UPDATE events
+        SET cid = $1, record = $2, name = $3, updated_at = $4, starts_at = $6, ends_at = $7, mode = $8, status = $9, locations = $10,
+            latitude = NULL, longitude = NULL, name_normalized = $11
+        WHERE aturi = $5",
+        cid,
+        &record,
+        name,
+        now,
+        aturi,
+        details.starts_at,
+        details.ends_at,
+        details.mode.as_deref(),
+        details.status.as_deref(),
+        json!(details.locations),
+        normalize_text(name),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    event_locations_replace(tx.as_mut(), aturi, &details.locations).await?;
+    event_tags_replace(tx.as_mut(), aturi, &details.tags).await?;
+
+    if let Some((_, previous_record)) = &previous {
+        let changes = event_record_changes(&lexicon, previous_record, &record);
+        if !changes.is_empty() {
+            event_revision_insert(
+                tx.as_mut(),
+                aturi,
+                cid,
+                previous_record,
+                &record,
+                &changes,
+                now,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Remove an event and the records that reference it. Returns the number of
+// RSVPs that were removed.
+pub async fn event_delete(pool: &StoragePool, aturi: &str) -> Result<u64, StorageError> {
+    // Validate aturi is not empty
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let rsvps_deleted = sqlx::query!("DELETE FROM rsvps WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?
+        .rows_affected();
+
+    sqlx::query!("DELETE FROM event_bookmarks WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM event_locations WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM event_tags WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        "DELETE FROM event_announcements WHERE event_aturi = $1",
+        aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM labels WHERE subject = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM event_updates WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM event_revisions WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM event_waitlist WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM checkins WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM calendar_events WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM reports WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
 
-    let count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM rsvps WHERE event_aturi = $1 AND status = $2",
+    // Webhooks for only this event are removed along with their deliveries,
+    // and deliveries about the event that webhooks for all events have not
+    // sent yet are dropped.
+    sqlx::query!("DELETE FROM webhooks WHERE event_aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        r"DELETE FROM webhook_deliveries
+        WHERE delivered_at IS NULL AND failed_at IS NULL AND payload->'event'->>'uri' = $1",
+        aturi,
     )
-    .bind(event_aturi)
-    .bind(status)
-    .fetch_one(tx.as_mut())
+    .execute(tx.as_mut())
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
 
+    sqlx::query!("DELETE FROM events WHERE aturi = $1", aturi)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
     tx.commit()
         .await
         .map_err(StorageError::CannotCommitDatabaseTransaction)?;
 
-    Ok(count as u32)
+    Ok(rsvps_deleted)
+}
+
+// List the events that are co-listed with an event. An event is co-listed
+// when another organizer republishes it in their own repo and both records
+// include a link to the other's AT-URI.
+#[tracing::instrument(skip_all, fields(aturi = %event.aturi))]
+pub async fn event_list_colisted(
+    executor: impl PgExecutor<'_>,
+    event: &Event,
+) -> Result<Vec<Event>, StorageError> {
+    use crate::atproto::lexicon::community::lexicon::calendar::event::EventLink;
+
+    let linked_aturis = extract_event_details(event)
+        .uris
+        .into_iter()
+        .filter_map(|link| match link {
+            EventLink::Current { uri, .. } if uri.starts_with("at://") && uri != event.aturi => {
+                Some(uri)
+            }
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+
+    if linked_aturis.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let events = sqlx::query_as!(
+        Event,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            name,
+            updated_at AS "updated_at?",
+            rsvp_closes_at,
+            rsvp_capacity,
+            starts_at,
+            ends_at,
+            mode,
+            status,
+            latitude,
+            longitude
+        FROM events
+        WHERE events.aturi = ANY($1)
+        AND events.did <> $3
+        AND EXISTS (
+            SELECT 1 FROM json_array_elements(
+                CASE WHEN json_typeof(events.record->'uris') = 'array'
+                THEN events.record->'uris'
+                ELSE '[]'::json END
+            ) AS link
+            WHERE link->>'uri' = $2
+        )
+        ORDER BY events.aturi ASC"#,
+        &linked_aturis,
+        &event.aturi,
+        &event.did,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(events)
+}
+
+// Count RSVPs by status across a set of co-listed events in one query. A
+// user who has RSVPed to more than one of the records is counted once, using
+// their most recent RSVP. Statuses nobody has are left out.
+#[tracing::instrument(skip(executor))]
+pub async fn count_colisted_event_rsvps(
+    executor: impl PgExecutor<'_>,
+    event_aturis: &[String],
+) -> Result<HashMap<String, u32>, StorageError> {
+    if event_aturis.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let counts = sqlx::query!(
+        r#"SELECT latest.status AS "status!", COUNT(*) AS "count!" FROM (
+            SELECT DISTINCT ON (did) did, status FROM rsvps
+            WHERE event_aturi = ANY($1)
+            ORDER BY did, updated_at DESC
+        ) AS latest
+        GROUP BY latest.status"#,
+        event_aturis,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(counts
+        .into_iter()
+        .map(|row| (row.status, row.count as u32))
+        .collect())
+}
+
+// Get the DIDs and statuses of RSVPs across a set of co-listed events, using
+// each user's most recent RSVP.
+#[tracing::instrument(skip(executor))]
+pub async fn get_colisted_event_rsvps(
+    executor: impl PgExecutor<'_>,
+    event_aturis: &[String],
+    status: &str,
+) -> Result<Vec<(String, String)>, StorageError> {
+    if event_aturis.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if status.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Status cannot be empty".into(),
+        )));
+    }
+
+    let rsvps = sqlx::query!(
+        r#"SELECT latest.did AS "did!", latest.status AS "status!" FROM (
+            SELECT DISTINCT ON (did) did, status FROM rsvps
+            WHERE event_aturi = ANY($1)
+            ORDER BY did, updated_at DESC
+        ) AS latest
+        WHERE latest.status = $2"#,
+        event_aturis,
+        status,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(|row| (row.did, row.status))
+    .collect::<Vec<_>>();
+
+    Ok(rsvps)
 }
 
 pub async fn get_event_rsvp_counts(
-    pool: &StoragePool,
+    executor: impl PgExecutor<'_>,
     aturis: Vec<String>,
 ) -> Result<HashMap<(std::string::String, std::string::String), i64>, StorageError> {
     // Handle empty list case
@@ -924,72 +2785,1042 @@ pub async fn get_event_rsvp_counts(
         }
     }
 
+    // RSVPs to legacy events count towards the events they were migrated to,
+    // once per attendee.
+    let values = sqlx::query!(
+        r#"SELECT latest.event_aturi AS "event_aturi!", latest.status AS "status!", COUNT(*) AS "count!" FROM (
+            SELECT DISTINCT ON (sources.event_aturi, rsvps.did) sources.event_aturi, rsvps.status
+            FROM (
+                SELECT aturi AS event_aturi, aturi AS source_aturi FROM UNNEST($1::VARCHAR[]) AS aturi
+                UNION ALL
+                SELECT migrated_aturi, legacy_aturi FROM event_migrations WHERE migrated_aturi = ANY($1)
+            ) AS sources
+            JOIN rsvps ON rsvps.event_aturi = sources.source_aturi
+            ORDER BY sources.event_aturi, rsvps.did, rsvps.updated_at DESC
+        ) AS latest
+        GROUP BY latest.event_aturi, latest.status"#,
+        &aturis,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(HashMap::from_iter(
+        values
+            .into_iter()
+            .map(|row| ((row.event_aturi, row.status), row.count)),
+    ))
+}
+
+// Record that a legacy event was migrated, so that its RSVPs are counted
+// and listed with the migrated event
+pub async fn event_migration_insert(
+    executor: impl PgExecutor<'_>,
+    legacy_aturi: &str,
+    migrated_aturi: &str,
+) -> Result<(), StorageError> {
+    if legacy_aturi.trim().is_empty() || migrated_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    sqlx::query!(
+        "INSERT INTO event_migrations (legacy_aturi, migrated_aturi, migrated_at) VALUES ($1, $2, $3) ON CONFLICT (legacy_aturi) DO UPDATE SET migrated_aturi = $2, migrated_at = $3",
+        legacy_aturi,
+        migrated_aturi,
+        Utc::now(),
+    )
+    .execute(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(())
+}
+
+// Store an event migrated from a legacy event and record the migration in one
+// transaction, so the migrated event never shows up without the legacy
+// event's RSVPs.
+pub async fn event_insert_migrated<T: serde::Serialize>(
+    pool: &StoragePool,
+    legacy_aturi: &str,
+    event: EventInsertParams<'_, T>,
+) -> Result<(), StorageError> {
     let mut tx = pool
         .begin()
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-        "SELECT event_aturi, status, COUNT(*) as count FROM rsvps WHERE event_aturi IN (",
-    );
-    let mut separated = query_builder.separated(", ");
-    for aturi in &aturis {
-        separated.push_bind(aturi);
-    }
-    separated.push_unseparated(") GROUP BY event_aturi, status");
-
-    // Use build_query_as to correctly include the bindings
-    let query = query_builder.build_query_as::<(String, String, i64)>();
-    let values = query
-        .fetch_all(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    event_insert_with_metadata(
+        &mut *tx,
+        event.aturi,
+        event.cid,
+        event.did,
+        event.lexicon,
+        event.record,
+        event.name,
+    )
+    .await?;
+    event_migration_insert(tx.as_mut(), legacy_aturi, event.aturi).await?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the AT-URIs that RSVPs to an event can reference: the event itself and
+// the legacy events that were migrated to it
+#[tracing::instrument(skip(executor))]
+pub async fn event_rsvp_aturis(
+    executor: impl PgExecutor<'_>,
+    aturi: &str,
+) -> Result<Vec<String>, StorageError> {
+    let legacy_aturis = sqlx::query_scalar!(
+        "SELECT legacy_aturi FROM event_migrations WHERE migrated_aturi = $1 ORDER BY legacy_aturi ASC",
+        aturi,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
-    Ok(HashMap::from_iter(values.iter().map(
-        |(aturi, status, count)| ((aturi.clone(), status.clone()), *count),
-    )))
+    let mut aturis = vec![aturi.to_string()];
+    aturis.extend(legacy_aturis);
+    Ok(aturis)
 }
 
+// List events most recently updated first, starting after the given
+// update time and AT-URI. Keyset pagination keeps later pages as fast as the
+// first. When a tag is given, only events with that tag are listed.
 pub async fn event_list(
-    pool: &StoragePool,
-    page: i64,
-    page_size: i64,
-) -> Result<(i64, Vec<Event>), StorageError> {
-    // Validate page and page_size are positive
-    if page < 1 || page_size < 1 {
+    executor: impl PgExecutor<'_>,
+    tag: Option<&str>,
+    after: Option<(DateTime<Utc>, &str)>,
+    limit: i64,
+) -> Result<Vec<Event>, StorageError> {
+    // Validate limit is positive
+    if limit < 1 {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
-            "Page and page size must be positive".into(),
+            "Limit must be positive".into(),
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let total_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM events")
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
-
-    let offset = (page - 1) * page_size;
+    let (after_updated_at, after_aturi) = after.unzip();
 
-    let events = sqlx::query_as::<_, Event>(
-        "SELECT * FROM events ORDER BY updated_at DESC LIMIT $1 OFFSET $2",
+    let events = sqlx::query_as!(
+        Event,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            name,
+            updated_at AS "updated_at?",
+            rsvp_closes_at,
+            rsvp_capacity,
+            starts_at,
+            ends_at,
+            mode,
+            status,
+            latitude,
+            longitude
+        FROM events
+        WHERE ($1::timestamptz IS NULL OR (updated_at, aturi) < ($1, $2))
+        AND ($4::text IS NULL OR EXISTS (
+            SELECT 1 FROM event_tags
+            WHERE event_tags.event_aturi = events.aturi AND event_tags.tag = $4
+        ))
+        ORDER BY updated_at DESC, aturi DESC
+        LIMIT $3"#,
+        after_updated_at,
+        after_aturi,
+        // Fetch one more to know if there are more entries
+        limit + 1,
+        tag,
     )
-    .bind(page_size + 1) // Fetch one more to know if there are more entries
-    .bind(offset)
-    .fetch_all(tx.as_mut())
+    .fetch_all(executor)
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+    Ok(events)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::atproto::lexicon::com::atproto::repo::StrongRef;
+    use crate::atproto::lexicon::community::lexicon::calendar::rsvp::RsvpStatus;
+    use crate::atproto::lexicon::events::smokesignal::calendar::Calendar as CalendarRecord;
+    use crate::storage::calendar::calendar_upsert;
+    use crate::storage::checkin::checkin_insert;
+    use crate::storage::event::event_delete;
+    use crate::storage::event::{
+        count_colisted_event_rsvps, event_get, event_going_count, event_insert_migrated,
+        event_list, event_list_did_starts_between, event_list_organized_by_did,
+        event_list_recently_added, event_list_rsvped_by_did, event_list_starts_between,
+        event_lock_rsvp_capacity, event_migration_insert, event_months_did_past, event_rsvp_aturis,
+        event_rsvps_full, event_update_rsvp_capacity, event_upsert, events_insert_many,
+        extract_record_details, get_event_rsvp_counts, rsvp_get, rsvp_insert, rsvp_list_for_event,
+        rsvps_insert_many, EventInsertParams, RsvpInsertParams,
+    };
+    use crate::storage::event_revision::event_revision_list;
+    use crate::storage::report::report_insert;
+    use crate::storage::waitlist::waitlist_insert;
+    use crate::storage::webhook::{webhook_enqueue, webhook_insert};
+    use crate::test_support::{insert_handle, EventBuilder, RsvpBuilder};
+    use crate::webhooks::{WebhookEvent, WebhookKind, WebhookPayload};
+
+    #[sqlx::test]
+    async fn test_count_colisted_event_rsvps(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let republisher = "did:plc:kkkcb7sys7623hcf7oefcffg";
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let colisted = EventBuilder::default()
+            .insert(&pool, republisher, "3lbsxygenau2d")
+            .await?;
+
+        // The attendee's most recent RSVP across the co-listings is counted
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, attendee, "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(colisted.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, attendee, "3lbsy2aeu7w2d")
+            .await?;
+        RsvpBuilder::new(colisted.clone())
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2e")
+            .await?;
+
+        let counts =
+            count_colisted_event_rsvps(&pool, &[event.uri.clone(), colisted.uri.clone()]).await?;
+        assert_eq!(counts.get("going"), Some(&1));
+        assert_eq!(counts.get("interested"), Some(&1));
+        assert_eq!(counts.get("notgoing"), None);
+
+        assert!(count_colisted_event_rsvps(&pool, &[]).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_event_rsvp_counts(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        insert_handle(&pool, organizer, "whole-crane.examplepds.com").await?;
+
+        let event = EventBuilder::default()
+            .starts_at("2025-06-02T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, "did:plc:c71dca8dfb0f126321f82435", "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2d")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, "did:plc:f263c822655b579fc8a79635", "3lbsy2aeu7w2e")
+            .await?;
+
+        let stored = event_get(&pool, &event.uri).await?;
+        assert_eq!(stored.name, "Vancouver Rust Meetup");
+        assert_eq!(stored.cid, event.cid);
+
+        let counts = get_event_rsvp_counts(&pool, vec![event.uri.clone()]).await?;
+        assert_eq!(
+            counts.get(&(event.uri.clone(), "going".to_string())),
+            Some(&2)
+        );
+        assert_eq!(
+            counts.get(&(event.uri.clone(), "interested".to_string())),
+            Some(&1)
+        );
+        assert_eq!(
+            counts.get(&(event.uri.clone(), "notgoing".to_string())),
+            None
+        );
+
+        let rsvps = rsvp_list_for_event(&pool, &event.uri, None, None, 2).await?;
+        assert_eq!(rsvps.len(), 3);
+
+        let last = &rsvps[1];
+        let updated_at = last.updated_at.expect("updated_at is set on insert");
+        let rest = rsvp_list_for_event(&pool, &event.uri, None, Some((updated_at, &last.aturi)), 2)
+            .await?;
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].aturi, rsvps[2].aturi);
+
+        let interested =
+            rsvp_list_for_event(&pool, &event.uri, Some("interested"), None, 10).await?;
+        assert_eq!(interested.len(), 1);
+        assert_eq!(interested[0].did, "did:plc:f263c822655b579fc8a79635");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_rsvp_capacity(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, "did:plc:c71dca8dfb0f126321f82435", "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, "did:plc:f263c822655b579fc8a79635", "3lbsy2aeu7w2d")
+            .await?;
+
+        let going_count = event_going_count(&pool, &event.uri).await?;
+        assert_eq!(going_count, 1);
+
+        // Events without a capacity are never full
+        let stored = event_get(&pool, &event.uri).await?;
+        assert_eq!(stored.rsvp_capacity, None);
+        assert!(!event_rsvps_full(&stored, going_count));
+
+        assert!(event_update_rsvp_capacity(&pool, &event.uri, Some(0))
+            .await
+            .is_err());
+
+        event_update_rsvp_capacity(&pool, &event.uri, Some(2)).await?;
+        let stored = event_get(&pool, &event.uri).await?;
+        assert_eq!(stored.rsvp_capacity, Some(2));
+        assert!(!event_rsvps_full(&stored, going_count));
+
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2e")
+            .await?;
+        let going_count = event_going_count(&pool, &event.uri).await?;
+        assert!(event_rsvps_full(&stored, going_count));
+
+        event_update_rsvp_capacity(&pool, &event.uri, None).await?;
+        let stored = event_get(&pool, &event.uri).await?;
+        assert!(!event_rsvps_full(&stored, going_count));
+
+        Ok(())
+    }
 
-    Ok((total_count, events))
+    #[sqlx::test]
+    async fn test_event_lock_rsvp_capacity(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        event_update_rsvp_capacity(&pool, &event.uri, Some(1)).await?;
+
+        let mut first = pool.begin().await?;
+        assert_eq!(
+            event_lock_rsvp_capacity(first.as_mut(), &event.uri).await?,
+            Some(1)
+        );
+
+        // A second RSVP waits for the first to be stored before counting
+        let second = tokio::spawn({
+            let pool = pool.clone();
+            let aturi = event.uri.clone();
+            async move {
+                let mut tx = pool.begin().await?;
+                event_lock_rsvp_capacity(tx.as_mut(), &aturi).await?;
+                let going_count = event_going_count(tx.as_mut(), &aturi).await?;
+                anyhow::Ok(going_count)
+            }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(!second.is_finished());
+
+        let rsvp_aturi = format!(
+            "at://{}/community.lexicon.calendar.rsvp/3lbsy2aeu7w2c",
+            attendee
+        );
+        rsvp_insert(
+            first.as_mut(),
+            &rsvp_aturi,
+            "bafyreirsvp",
+            attendee,
+            "community.lexicon.calendar.rsvp",
+            &RsvpBuilder::new(event.clone()).build(),
+        )
+        .await?;
+        first.commit().await?;
+
+        assert_eq!(second.await??, 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_list_by_did(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+        let now = chrono::Utc::now();
+
+        let upcoming = EventBuilder::default()
+            .starts_at(now + chrono::Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let ongoing = EventBuilder::default()
+            .starts_at(now - chrono::Duration::hours(1))
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        let past = EventBuilder::default()
+            .starts_at(now - chrono::Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2e")
+            .await?;
+        let unscheduled = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2f")
+            .await?;
+
+        let organized = event_list_organized_by_did(&pool, organizer, now, 1, 10).await?;
+        assert_eq!(
+            organized
+                .iter()
+                .map(|value| value.event.aturi.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                ongoing.uri.as_str(),
+                upcoming.uri.as_str(),
+                unscheduled.uri.as_str()
+            ]
+        );
+
+        RsvpBuilder::new(upcoming.clone())
+            .insert(&pool, attendee, "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(past.clone())
+            .insert(&pool, attendee, "3lbsy2aeu7w2d")
+            .await?;
+        RsvpBuilder::new(ongoing.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, attendee, "3lbsy2aeu7w2e")
+            .await?;
+
+        let going = event_list_rsvped_by_did(&pool, attendee, "going", now, 1, 10).await?;
+        assert_eq!(going.len(), 1);
+        assert_eq!(going[0].event.aturi, upcoming.uri);
+        assert_eq!(going[0].role, "going");
+
+        let interested =
+            event_list_rsvped_by_did(&pool, attendee, "interested", now, 1, 10).await?;
+        assert_eq!(interested.len(), 1);
+        assert_eq!(interested[0].event.aturi, ongoing.uri);
+
+        assert!(event_list_rsvped_by_did(&pool, " ", "going", now, 1, 10)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_months_did_past(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        let june = EventBuilder::default()
+            .starts_at("2024-06-12T01:30:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        EventBuilder::default()
+            .starts_at("2024-06-20T18:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        // The evening of the last day of June in Vancouver
+        let june_evening = EventBuilder::default()
+            .starts_at("2024-07-01T02:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2e")
+            .await?;
+        EventBuilder::default()
+            .starts_at("2025-01-15T18:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2f")
+            .await?;
+
+        let before = "2025-01-01T00:00:00Z".parse()?;
+        assert_eq!(
+            event_months_did_past(&pool, organizer, "America/Vancouver", before).await?,
+            vec![(2024, 6, 3)]
+        );
+        assert_eq!(
+            event_months_did_past(&pool, organizer, "UTC", before).await?,
+            vec![(2024, 7, 1), (2024, 6, 2)]
+        );
+
+        let events = event_list_did_starts_between(
+            &pool,
+            organizer,
+            "2024-06-01T07:00:00Z".parse()?,
+            "2024-07-01T07:00:00Z".parse()?,
+        )
+        .await?;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].event.aturi, june.uri);
+        assert_eq!(events[2].event.aturi, june_evening.uri);
+
+        let events = event_list_starts_between(
+            &pool,
+            "2024-07-01T00:00:00Z".parse()?,
+            "2024-08-01T00:00:00Z".parse()?,
+        )
+        .await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, june_evening.uri);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_migrated_event_rsvp_counts(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let migrated = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let legacy = StrongRef {
+            uri: format!(
+                "at://{}/events.smokesignal.calendar.event/3lbsxygenau2c",
+                organizer
+            ),
+            cid: migrated.cid.clone(),
+        };
+
+        // One attendee RSVPed to the legacy event only, and another moved
+        // their RSVP to the migrated event and is counted once
+        RsvpBuilder::new(legacy.clone())
+            .insert(&pool, "did:plc:c71dca8dfb0f126321f82435", "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(legacy.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2d")
+            .await?;
+        RsvpBuilder::new(migrated.clone())
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2e")
+            .await?;
+
+        let going = (migrated.uri.clone(), "going".to_string());
+        let counts = get_event_rsvp_counts(&pool, vec![migrated.uri.clone()]).await?;
+        assert_eq!(counts.get(&going), Some(&1));
+
+        event_migration_insert(&pool, &legacy.uri, &migrated.uri).await?;
+
+        let counts =
+            get_event_rsvp_counts(&pool, vec![migrated.uri.clone(), legacy.uri.clone()]).await?;
+        assert_eq!(counts.get(&going), Some(&2));
+        assert_eq!(
+            counts.get(&(migrated.uri.clone(), "interested".to_string())),
+            None
+        );
+        assert_eq!(
+            counts.get(&(legacy.uri.clone(), "interested".to_string())),
+            Some(&1)
+        );
+
+        assert_eq!(
+            event_rsvp_aturis(&pool, &migrated.uri).await?,
+            vec![migrated.uri.clone(), legacy.uri.clone()]
+        );
+        assert_eq!(
+            event_rsvp_aturis(&pool, &legacy.uri).await?,
+            vec![legacy.uri.clone()]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_insert_migrated(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let event_nsid = "community.lexicon.calendar.event";
+        let legacy_aturi = format!(
+            "at://{}/events.smokesignal.calendar.event/3lbsxygenau2c",
+            organizer
+        );
+        let aturi = format!("at://{}/{}/3lbsxygenau2c", organizer, event_nsid);
+        let record = EventBuilder::default().build();
+        let event = EventInsertParams {
+            aturi: &aturi,
+            cid: "bafyreimigrated",
+            did: organizer,
+            lexicon: event_nsid,
+            record: &record,
+            name: "Vancouver Rust Meetup",
+        };
+
+        // The event isn't stored when the migration can't be recorded
+        let result = event_insert_migrated(&pool, " ", event).await;
+        assert!(result.is_err());
+        assert!(event_get(&pool, &aturi).await.is_err());
+
+        let event = EventInsertParams {
+            aturi: &aturi,
+            cid: "bafyreimigrated",
+            did: organizer,
+            lexicon: event_nsid,
+            record: &record,
+            name: "Vancouver Rust Meetup",
+        };
+        event_insert_migrated(&pool, &legacy_aturi, event).await?;
+        assert_eq!(event_get(&pool, &aturi).await?.cid, "bafyreimigrated");
+        assert_eq!(
+            event_rsvp_aturis(&pool, &aturi).await?,
+            vec![aturi.clone(), legacy_aturi]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_list_pages(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        for rkey in ["3lbsxygenau2a", "3lbsxygenau2b", "3lbsxygenau2c"] {
+            EventBuilder::default()
+                .insert(&pool, organizer, rkey)
+                .await?;
+        }
+
+        let first = event_list(&pool, None, None, 2).await?;
+        assert_eq!(first.len(), 3);
+
+        let last = &first[1];
+        let updated_at = last.updated_at.expect("updated_at is set on insert");
+        let second = event_list(&pool, None, Some((updated_at, &last.aturi)), 2).await?;
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].aturi, first[2].aturi);
+
+        let tagged = EventBuilder::default()
+            .tag("Rust")
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        let with_tag = event_list(&pool, Some("rust"), None, 2).await?;
+        assert_eq!(with_tag.len(), 1);
+        assert_eq!(with_tag[0].aturi, tagged.uri);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_list_recently_added(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let older = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2a")
+            .await?;
+        let newer = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2b")
+            .await?;
+
+        // Updating an event doesn't move it up, and a record with an
+        // unparseable createdAt doesn't fail the list
+        sqlx::query!(
+            "UPDATE events SET record = (record::jsonb || '{\"createdAt\": \"today\"}')::json, updated_at = NOW() + interval '1 hour' WHERE aturi = $1",
+            &older.uri,
+        )
+        .execute(&pool)
+        .await?;
+
+        let events = event_list_recently_added(&pool, 1, 10).await?;
+        let aturis = events
+            .iter()
+            .map(|event| event.event.aturi.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(aturis, vec![newer.uri.as_str(), older.uri.as_str()]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_insert_many(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+        let event_nsid = "community.lexicon.calendar.event";
+        let rsvp_nsid = "community.lexicon.calendar.rsvp";
+
+        let existing = EventBuilder::default()
+            .starts_at("2025-06-02T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2a")
+            .await?;
+
+        let renamed = EventBuilder::default().name("Renamed Meetup").build();
+        let upcoming = EventBuilder::default()
+            .starts_at("2999-06-02T01:00:00Z".parse()?)
+            .build();
+        let upcoming_aturi = format!("at://{}/{}/3lbsxygenau2b", organizer, event_nsid);
+
+        // The existing event is given twice, and the last one is stored
+        events_insert_many(
+            &pool,
+            &[
+                EventInsertParams {
+                    aturi: &existing.uri,
+                    cid: "bafyreiold",
+                    did: organizer,
+                    lexicon: event_nsid,
+                    record: &upcoming,
+                    name: "Vancouver Rust Meetup",
+                },
+                EventInsertParams {
+                    aturi: &upcoming_aturi,
+                    cid: "bafyreiupcoming",
+                    did: organizer,
+                    lexicon: event_nsid,
+                    record: &upcoming,
+                    name: "Vancouver Rust Meetup",
+                },
+                EventInsertParams {
+                    aturi: &existing.uri,
+                    cid: "bafyreirenamed",
+                    did: organizer,
+                    lexicon: event_nsid,
+                    record: &renamed,
+                    name: "Renamed Meetup",
+                },
+            ],
+        )
+        .await?;
+
+        let stored = event_get(&pool, &existing.uri).await?;
+        assert_eq!(stored.name, "Renamed Meetup");
+        assert_eq!(stored.cid, "bafyreirenamed");
+        assert!(stored.starts_at.is_none());
+        assert!(event_get(&pool, &upcoming_aturi).await.is_ok());
+
+        // Events already stored with the same CID are left as they are
+        events_insert_many(
+            &pool,
+            &[EventInsertParams {
+                aturi: &existing.uri,
+                cid: "bafyreirenamed",
+                did: organizer,
+                lexicon: event_nsid,
+                record: &upcoming,
+                name: "Vancouver Rust Meetup",
+            }],
+        )
+        .await?;
+        let unchanged = event_get(&pool, &existing.uri).await?;
+        assert_eq!(unchanged.name, "Renamed Meetup");
+        assert_eq!(unchanged.updated_at, stored.updated_at);
+
+        let existing_rsvp = RsvpBuilder::new(existing.clone()).build();
+        let upcoming_rsvp = RsvpBuilder::new(StrongRef {
+            uri: upcoming_aturi.clone(),
+            cid: "bafyreiupcoming".to_string(),
+        })
+        .build();
+        let existing_rsvp_aturi = format!("at://{}/{}/3lbsy2aeu7w2a", attendee, rsvp_nsid);
+        let upcoming_rsvp_aturi = format!("at://{}/{}/3lbsy2aeu7w2b", attendee, rsvp_nsid);
+
+        rsvps_insert_many(
+            &pool,
+            &[
+                RsvpInsertParams {
+                    aturi: &existing_rsvp_aturi,
+                    cid: "bafyreiexisting",
+                    did: attendee,
+                    lexicon: rsvp_nsid,
+                    record: &existing_rsvp,
+                    event_aturi: &existing.uri,
+                    event_cid: "bafyreirenamed",
+                    status: "going",
+                },
+                RsvpInsertParams {
+                    aturi: &upcoming_rsvp_aturi,
+                    cid: "bafyreiupcoming",
+                    did: attendee,
+                    lexicon: rsvp_nsid,
+                    record: &upcoming_rsvp,
+                    event_aturi: &upcoming_aturi,
+                    event_cid: "bafyreiupcoming",
+                    status: "going",
+                },
+            ],
+        )
+        .await?;
+
+        let stored = rsvp_get(&pool, &upcoming_rsvp_aturi).await?.expect("rsvp");
+        assert_eq!(stored.status, "going");
+        assert!(!stored.late);
+
+        let counts = get_event_rsvp_counts(&pool, vec![existing.uri.clone()]).await?;
+        assert_eq!(
+            counts.get(&(existing.uri.clone(), "going".to_string())),
+            Some(&1)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_delete(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2a")
+            .await?;
+        let other = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2b")
+            .await?;
+
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, attendee, "3lbsy2aeu7w2a")
+            .await?;
+        waitlist_insert(&pool, &event.uri, attendee).await?;
+        checkin_insert(&pool, &event.uri, attendee, organizer, Utc::now()).await?;
+        report_insert(&pool, attendee, &event.uri, "Spam").await?;
+
+        let calendar_aturi = format!(
+            "at://{}/events.smokesignal.calendar/3lbsz2aeu7w2a",
+            organizer
+        );
+        calendar_upsert(
+            &pool,
+            &calendar_aturi,
+            "bafyreicalendar",
+            organizer,
+            &CalendarRecord::Current {
+                name: "Vancouver Rust".to_string(),
+                description: None,
+                events: vec![event.clone(), other.clone()],
+                created_at: Utc::now(),
+            },
+        )
+        .await?;
+
+        webhook_insert(
+            &pool,
+            organizer,
+            Some(&event.uri),
+            "https://a.example/",
+            "a",
+        )
+        .await?;
+        webhook_insert(&pool, organizer, None, "https://b.example/", "b").await?;
+        let payload = |uri: &str| {
+            WebhookPayload::new(
+                WebhookKind::EventUpdated,
+                WebhookEvent {
+                    uri: uri.to_string(),
+                    url: "https://smokesignal.events/".to_string(),
+                },
+                None,
+                Utc::now(),
+            )
+        };
+        webhook_enqueue(&pool, organizer, &event.uri, &payload(&event.uri)).await?;
+        webhook_enqueue(&pool, organizer, &other.uri, &payload(&other.uri)).await?;
+
+        assert_eq!(event_delete(&pool, &event.uri).await?, 1);
+        assert!(event_get(&pool, &event.uri).await.is_err());
+
+        let remaining = sqlx::query!(
+            r#"SELECT
+                (SELECT COUNT(*) FROM rsvps WHERE event_aturi = $1) AS "rsvps!",
+                (SELECT COUNT(*) FROM event_waitlist WHERE event_aturi = $1) AS "waitlist!",
+                (SELECT COUNT(*) FROM checkins WHERE event_aturi = $1) AS "checkins!",
+                (SELECT COUNT(*) FROM reports WHERE event_aturi = $1) AS "reports!",
+                (SELECT COUNT(*) FROM calendar_events WHERE event_aturi = $1) AS "calendar_events!",
+                (SELECT COUNT(*) FROM webhooks WHERE event_aturi = $1) AS "webhooks!",
+                (SELECT COUNT(*) FROM webhook_deliveries WHERE payload->'event'->>'uri' = $1) AS "webhook_deliveries!""#,
+            event.uri,
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(remaining.rsvps, 0);
+        assert_eq!(remaining.waitlist, 0);
+        assert_eq!(remaining.checkins, 0);
+        assert_eq!(remaining.reports, 0);
+        assert_eq!(remaining.calendar_events, 0);
+        assert_eq!(remaining.webhooks, 0);
+        assert_eq!(remaining.webhook_deliveries, 0);
+
+        // Rows of other events are kept
+        let kept = sqlx::query!(
+            r#"SELECT
+                (SELECT COUNT(*) FROM calendar_events WHERE event_aturi = $1) AS "calendar_events!",
+                (SELECT COUNT(*) FROM webhook_deliveries WHERE payload->'event'->>'uri' = $1) AS "webhook_deliveries!""#,
+            other.uri,
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(kept.calendar_events, 1);
+        assert_eq!(kept.webhook_deliveries, 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_upsert(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let lexicon = "community.lexicon.calendar.event";
+        let aturi = format!("at://{}/{}/3lbsxygenau2a", organizer, lexicon);
+        let record = EventBuilder::default().build();
+
+        assert!(event_upsert(&pool, &aturi, "bafyreifirst", organizer, lexicon, &record).await?);
+
+        // Seeing the same record again doesn't rewrite it
+        assert!(!event_upsert(&pool, &aturi, "bafyreifirst", organizer, lexicon, &record).await?);
+
+        let renamed = EventBuilder::default().name("Renamed Meetup").build();
+        assert!(event_upsert(&pool, &aturi, "bafyreisecond", organizer, lexicon, &renamed).await?);
+
+        let stored = event_get(&pool, &aturi).await?;
+        assert_eq!(stored.cid, "bafyreisecond");
+        assert_eq!(stored.name, "Renamed Meetup");
+
+        // The edit is recorded with the record it replaced
+        let revisions = event_revision_list(&pool, &aturi, 10).await?;
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].cid, "bafyreisecond");
+        assert_eq!(revisions[0].record.0["name"], "Renamed Meetup");
+        assert_ne!(revisions[0].previous_record.0["name"], "Renamed Meetup");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_timezone() -> anyhow::Result<()> {
+        let lexicon = "community.lexicon.calendar.event";
+        let mut record = serde_json::to_value(EventBuilder::default().build())?;
+        assert!(extract_record_details(lexicon, &record, "")
+            .timezone
+            .is_none());
+
+        record["timezone"] = json!("America/Vancouver");
+        let details = extract_record_details(lexicon, &record, "");
+        assert_eq!(details.timezone.as_deref(), Some("America/Vancouver"));
+
+        // Only known time zones are used
+        record["timezone"] = json!("Pacific/Atlantis");
+        assert!(extract_record_details(lexicon, &record, "")
+            .timezone
+            .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_doors_open_at() -> anyhow::Result<()> {
+        let lexicon = "community.lexicon.calendar.event";
+        let mut record = serde_json::to_value(EventBuilder::default().build())?;
+        record["doorsOpenAt"] = json!("2025-06-02T00:30:00.000Z");
+
+        let details = extract_record_details(lexicon, &record, "");
+        assert_eq!(details.doors_open_at, Some("2025-06-02T00:30:00Z".parse()?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_description_facets() -> anyhow::Result<()> {
+        let lexicon = "community.lexicon.calendar.event";
+        let mut record = serde_json::to_value(EventBuilder::default().build())?;
+        record["descriptionFacets"] = json!([
+            {
+                "index": { "byteStart": 0, "byteEnd": 10 },
+                "features": [{ "$type": "app.bsky.richtext.facet#mention", "did": "did:plc:abc" }],
+            },
+            {
+                "index": { "byteStart": 12, "byteEnd": 20 },
+                "features": [{ "$type": "app.bsky.richtext.facet#unknown" }],
+            },
+        ]);
+
+        let details = extract_record_details(lexicon, &record, "");
+        assert_eq!(details.description_facets.len(), 1);
+        assert_eq!(details.description_facets[0].index.byte_end, 10);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::format_address;
+    use crate::atproto::lexicon::community::lexicon::location::Address;
+
+    fn address(
+        name: Option<String>,
+        street: Option<String>,
+        locality: Option<String>,
+        region: Option<String>,
+        postal_code: Option<String>,
+        country: String,
+    ) -> Address {
+        Address::Current {
+            country,
+            postal_code,
+            region,
+            locality,
+            street,
+            name,
+        }
+    }
+
+    fn part() -> impl Strategy<Value = Option<String>> {
+        prop::option::of("[\\p{L}\\p{N}][\\p{L}\\p{N} ]{0,30}")
+    }
+
+    proptest! {
+        #[test]
+        fn joins_parts_in_order(
+            name in part(),
+            street in part(),
+            locality in part(),
+            region in part(),
+            postal_code in part(),
+            country in "[\\p{L}]{2,40}",
+        ) {
+            let expected: Vec<String> = [&name, &street, &locality, &region, &postal_code]
+                .into_iter()
+                .flatten()
+                .cloned()
+                .chain(std::iter::once(country.clone()))
+                .collect();
+
+            let formatted = format_address(&address(name, street, locality, region, postal_code, country));
+            prop_assert_eq!(formatted, expected.join(", "));
+        }
+
+        #[test]
+        fn omits_blank_parts(
+            blank in "[ \\t\\x{3000}]{0,5}",
+            locality in part(),
+            country in "[\\p{L}]{2,40}",
+        ) {
+            let with_blanks = format_address(&address(
+                Some(blank.clone()),
+                Some(blank.clone()),
+                locality.clone(),
+                Some(blank.clone()),
+                Some(blank),
+                country.clone(),
+            ));
+            let without = format_address(&address(None, None, locality, None, None, country));
+            prop_assert_eq!(with_blanks, without);
+        }
+
+        #[test]
+        fn keeps_long_and_unicode_values(
+            street in "\\PC{1000,4000}",
+            country in "\\PC{1,200}",
+        ) {
+            prop_assume!(!street.trim().is_empty());
+            let formatted = format_address(&address(None, Some(street.clone()), None, None, None, country.clone()));
+            prop_assert_eq!(formatted.len(), street.len() + 2 + country.len());
+            prop_assert!(formatted.starts_with(&street));
+            prop_assert!(formatted.ends_with(&country));
+        }
+    }
 }
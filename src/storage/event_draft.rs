@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::EventDraft;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// The event form an organizer was filling in when they last left it.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventDraft {
+        pub did: String,
+        pub form: sqlx::types::Json<serde_json::Value>,
+        pub updated_at: DateTime<Utc>,
+    }
+}
+
+// Save the event form an organizer is filling in, replacing their previous
+// draft.
+pub async fn event_draft_upsert<T: serde::Serialize>(
+    pool: &StoragePool,
+    did: &str,
+    form: &T,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "INSERT INTO event_drafts (did, form, updated_at) VALUES ($1, $2, $3) ON CONFLICT (did) DO UPDATE SET form = $2, updated_at = $3",
+        did,
+        json!(form),
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the draft event of an organizer
+pub async fn event_draft_get(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Option<EventDraft>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let draft = sqlx::query_as!(EventDraft, "SELECT * FROM event_drafts WHERE did = $1", did)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(draft)
+}
+
+// Remove the draft event of an organizer, once the event is created or the
+// draft is discarded. Returns false when there was no draft.
+pub async fn event_draft_delete(pool: &StoragePool, did: &str) -> Result<bool, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!("DELETE FROM event_drafts WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::storage::event_draft::{event_draft_delete, event_draft_get, event_draft_upsert};
+
+    #[sqlx::test]
+    async fn test_event_drafts(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        assert!(event_draft_get(&pool, organizer).await?.is_none());
+
+        event_draft_upsert(
+            &pool,
+            organizer,
+            &json!({ "name": "Vancouver Rust" }),
+            Utc::now(),
+        )
+        .await?;
+        event_draft_upsert(
+            &pool,
+            organizer,
+            &json!({ "name": "Vancouver Rust Meetup" }),
+            Utc::now(),
+        )
+        .await?;
+        assert!(event_draft_upsert(&pool, " ", &json!({}), Utc::now())
+            .await
+            .is_err());
+
+        let draft = event_draft_get(&pool, organizer).await?.unwrap();
+        assert_eq!(draft.form.0["name"], "Vancouver Rust Meetup");
+
+        assert!(event_draft_delete(&pool, organizer).await?);
+        assert!(!event_draft_delete(&pool, organizer).await?);
+        assert!(event_draft_get(&pool, organizer).await?.is_none());
+
+        Ok(())
+    }
+}
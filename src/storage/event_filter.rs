@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::storage::{errors::StorageError, event::model::EventWithRole, StoragePool};
+
+/// The modes events can be filtered by, as the fragment of their lexicon
+/// value, for example `community.lexicon.calendar.event#inperson`.
+pub const EVENT_FILTER_MODES: [&str; 3] = ["inperson", "virtual", "hybrid"];
+
+/// The statuses events can be filtered by, as the fragment of their lexicon
+/// value.
+pub const EVENT_FILTER_STATUSES: [&str; 5] = [
+    "planned",
+    "scheduled",
+    "rescheduled",
+    "cancelled",
+    "postponed",
+];
+
+/// A set of conditions that events must all meet to be listed. Conditions
+/// that aren't set match every event.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventFilter {
+    /// Events that start at or after this time.
+    pub starts_after: Option<DateTime<Utc>>,
+
+    /// Events that start before this time.
+    pub starts_before: Option<DateTime<Utc>>,
+
+    /// Events with this mode, one of `EVENT_FILTER_MODES`.
+    pub mode: Option<String>,
+
+    /// Events with this status, one of `EVENT_FILTER_STATUSES`.
+    pub status: Option<String>,
+
+    /// Events with an address in the country, region or locality with this
+    /// slug, as made by `location_slug`.
+    pub location: Option<String>,
+}
+
+impl EventFilter {
+    /// Adds the conditions of the filter to a query of the `events` table.
+    fn push_conditions(&self, query_builder: &mut QueryBuilder<Postgres>) {
+        query_builder.push(" WHERE TRUE");
+
+        if let Some(starts_after) = self.starts_after {
+            query_builder
+                .push(" AND events.starts_at >= ")
+                .push_bind(starts_after);
+        }
+
+        if let Some(starts_before) = self.starts_before {
+            query_builder
+                .push(" AND events.starts_at < ")
+                .push_bind(starts_before);
+        }
+
+        // Legacy events use another lexicon, so only the fragment is compared
+        if let Some(mode) = &self.mode {
+            query_builder
+                .push(" AND split_part(events.mode, '#', 2) = ")
+                .push_bind(mode.clone());
+        }
+
+        if let Some(status) = &self.status {
+            query_builder
+                .push(" AND split_part(events.status, '#', 2) = ")
+                .push_bind(status.clone());
+        }
+
+        if let Some(location) = &self.location {
+            query_builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM event_locations WHERE event_locations.event_aturi = events.aturi AND ",
+                )
+                .push_bind(location.clone())
+                .push(" IN (event_locations.country, event_locations.region, event_locations.locality))");
+        }
+    }
+}
+
+// List the events that match a filter, soonest first.
+pub async fn event_list_filtered(
+    pool: &StoragePool,
+    filter: &EventFilter,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let mut query_builder: QueryBuilder<Postgres> =
+        QueryBuilder::new("SELECT events.*, 'organizer' as role FROM events");
+    filter.push_conditions(&mut query_builder);
+    query_builder
+        .push(" ORDER BY events.starts_at ASC NULLS LAST, events.aturi ASC LIMIT ")
+        .push_bind(page_size + 1)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let event_roles = query_builder
+        .build_query_as::<EventWithRole>()
+        .fetch_all(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_roles)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::atproto::lexicon::community::lexicon::{
+        calendar::event::{EventLocation, Mode, Status},
+        location::Address,
+    };
+    use crate::test_support::EventBuilder;
+
+    #[sqlx::test]
+    async fn test_event_list_filtered(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = Utc::now();
+
+        let virtual_event = EventBuilder::default()
+            .starts_at(now + Duration::days(1))
+            .mode(Mode::Virtual)
+            .status(Status::Scheduled)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let cancelled = EventBuilder::default()
+            .starts_at(now + Duration::days(2))
+            .mode(Mode::InPerson)
+            .status(Status::Cancelled)
+            .location(EventLocation::Address(Address::Current {
+                country: "CA".to_string(),
+                postal_code: None,
+                region: Some("British Columbia".to_string()),
+                locality: Some("Vancouver".to_string()),
+                street: None,
+                name: None,
+            }))
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        EventBuilder::default()
+            .starts_at(now - Duration::days(1))
+            .mode(Mode::Virtual)
+            .insert(&pool, organizer, "3lbsxygenau2e")
+            .await?;
+
+        let upcoming = EventFilter {
+            starts_after: Some(now),
+            ..Default::default()
+        };
+        assert_eq!(event_list_filtered(&pool, &upcoming, 1, 10).await?.len(), 2);
+
+        let events = event_list_filtered(
+            &pool,
+            &EventFilter {
+                mode: Some("virtual".to_string()),
+                ..upcoming.clone()
+            },
+            1,
+            10,
+        )
+        .await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, virtual_event.uri);
+
+        let events = event_list_filtered(
+            &pool,
+            &EventFilter {
+                status: Some("cancelled".to_string()),
+                starts_before: Some(now + Duration::days(3)),
+                ..upcoming.clone()
+            },
+            1,
+            10,
+        )
+        .await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, cancelled.uri);
+
+        let events = event_list_filtered(
+            &pool,
+            &EventFilter {
+                location: Some("british-columbia".to_string()),
+                ..upcoming
+            },
+            1,
+            10,
+        )
+        .await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, cancelled.uri);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{types::Json, PgConnection};
+
+use crate::storage::{
+    errors::StorageError,
+    event::{extract_record_details, EventDetails},
+    StoragePool,
+};
+use model::EventRevision;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A change an organizer made to an event, with the record before and
+    /// after it and the names of the fields that changed.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventRevision {
+        pub id: i64,
+        pub event_aturi: String,
+        pub cid: String,
+        pub previous_record: sqlx::types::Json<serde_json::Value>,
+        pub record: sqlx::types::Json<serde_json::Value>,
+        pub changes: Vec<String>,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// The fields of an event that are compared between revisions, in the order
+// they are listed as changed.
+fn event_field_values(details: &EventDetails) -> [(&'static str, serde_json::Value); 11] {
+    [
+        ("name", json!(details.name)),
+        ("description", json!(details.description)),
+        ("starts_at", json!(details.starts_at)),
+        ("ends_at", json!(details.ends_at)),
+        ("doors_open_at", json!(details.doors_open_at)),
+        ("timezone", json!(details.timezone)),
+        ("mode", json!(details.mode)),
+        ("status", json!(details.status)),
+        ("locations", json!(details.locations)),
+        ("links", json!(details.uris)),
+        ("tags", json!(details.tags)),
+    ]
+}
+
+/// Returns the names of the fields that differ between two records of an
+/// event. Changes to fields that aren't shown, like the creation time, are
+/// left out.
+pub fn event_record_changes(
+    lexicon: &str,
+    previous_record: &serde_json::Value,
+    record: &serde_json::Value,
+) -> Vec<&'static str> {
+    let previous = event_field_values(&extract_record_details(lexicon, previous_record, ""));
+    let current = event_field_values(&extract_record_details(lexicon, record, ""));
+
+    previous
+        .into_iter()
+        .zip(current)
+        .filter(|((_, previous), (_, current))| previous != current)
+        .map(|((field, _), _)| field)
+        .collect()
+}
+
+// Record a change to an event. This is run inside of the transaction that
+// updates the event so the history never misses a change.
+pub(crate) async fn event_revision_insert(
+    conn: &mut PgConnection,
+    event_aturi: &str,
+    cid: &str,
+    previous_record: &serde_json::Value,
+    record: &serde_json::Value,
+    changes: &[&str],
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let changes = changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        r"
+        INSERT INTO event_revisions (event_aturi, cid, previous_record, record, changes, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ",
+        event_aturi,
+        cid,
+        previous_record,
+        record,
+        &changes,
+        now,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(())
+}
+
+// List the most recent changes to an event, newest first
+pub async fn event_revision_list(
+    pool: &StoragePool,
+    event_aturi: &str,
+    limit: i64,
+) -> Result<Vec<EventRevision>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let revisions = sqlx::query_as!(
+        EventRevision,
+        r#"
+        SELECT
+            id,
+            event_aturi,
+            cid,
+            previous_record AS "previous_record: Json<serde_json::Value>",
+            record AS "record: Json<serde_json::Value>",
+            changes,
+            created_at
+        FROM event_revisions
+        WHERE event_aturi = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2
+        "#,
+        event_aturi,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(revisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::atproto::lexicon::community::lexicon::calendar::event::NSID;
+    use crate::storage::event::event_update_with_metadata;
+    use crate::test_support::EventBuilder;
+
+    fn starts_at() -> DateTime<Utc> {
+        "2025-06-12T18:30:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_event_record_changes() {
+        let previous = json!(EventBuilder::default().starts_at(starts_at()).build());
+
+        assert!(event_record_changes(NSID, &previous, &previous).is_empty());
+
+        let record = json!(EventBuilder::default()
+            .starts_at(starts_at() + chrono::Duration::hours(1))
+            .link("https://rust.example/meetup")
+            .build());
+        assert_eq!(
+            event_record_changes(NSID, &previous, &record),
+            vec!["starts_at", "ends_at", "links"]
+        );
+
+        let record = json!(EventBuilder::default()
+            .name("Vancouver Rust Meetup: June")
+            .starts_at(starts_at())
+            .build());
+        assert_eq!(event_record_changes(NSID, &previous, &record), vec!["name"]);
+    }
+
+    #[sqlx::test]
+    async fn test_event_revisions(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        let event = EventBuilder::default()
+            .starts_at(starts_at())
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        // Writing the same record again isn't a change
+        let record = EventBuilder::default().starts_at(starts_at()).build();
+        event_update_with_metadata(&pool, &event.uri, "bafyreitest2", &record, "Meetup").await?;
+        assert!(event_revision_list(&pool, &event.uri, 10).await?.is_empty());
+
+        let record = EventBuilder::default()
+            .description("Talks and snacks at the community centre.")
+            .starts_at(starts_at())
+            .build();
+        event_update_with_metadata(&pool, &event.uri, "bafyreitest3", &record, "Meetup").await?;
+
+        let revisions = event_revision_list(&pool, &event.uri, 10).await?;
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].cid, "bafyreitest3");
+        assert_eq!(revisions[0].changes, vec!["description".to_string()]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{EventUpdate, EventUpdateRecipient};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A message from an organizer to the people going to or interested in
+    /// their event, like a change of venue.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventUpdate {
+        pub id: i64,
+        pub event_aturi: String,
+        pub did: String,
+        pub message: String,
+        pub created_at: DateTime<Utc>,
+    }
+
+    /// Someone an event update is sent to, with the language they chose if
+    /// they have signed in before.
+    #[derive(Clone, FromRow, Debug)]
+    pub struct EventUpdateRecipient {
+        pub did: String,
+        pub language: Option<String>,
+    }
+}
+
+// Store an update posted by the organizer of an event and return it
+pub async fn event_update_insert(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+    message: &str,
+    now: DateTime<Utc>,
+) -> Result<EventUpdate, StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if message.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Message cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let event_update = sqlx::query_as!(
+        EventUpdate,
+        r"
+        INSERT INTO event_updates (event_aturi, did, message, created_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        ",
+        event_aturi,
+        did,
+        message,
+        now,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_update)
+}
+
+// List the most recent updates of an event, newest first
+pub async fn event_update_list(
+    pool: &StoragePool,
+    event_aturi: &str,
+    limit: i64,
+) -> Result<Vec<EventUpdate>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let event_updates = sqlx::query_as!(
+        EventUpdate,
+        "SELECT * FROM event_updates WHERE event_aturi = $1 ORDER BY created_at DESC, id DESC LIMIT $2",
+        event_aturi,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_updates)
+}
+
+// List who an update to an event is sent to: everyone going or interested,
+// except the organizer posting it.
+pub async fn event_update_recipients(
+    pool: &StoragePool,
+    event_aturi: &str,
+    organizer_did: &str,
+) -> Result<Vec<EventUpdateRecipient>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    // Handles are left joined, so their language is null for recipients who
+    // never signed in
+    let recipients = sqlx::query_as!(
+        EventUpdateRecipient,
+        r#"
+        SELECT DISTINCT rsvps.did, handles.language AS "language?"
+        FROM rsvps
+        LEFT JOIN handles ON handles.did = rsvps.did
+        WHERE
+            rsvps.event_aturi = $1
+            AND rsvps.status IN ('going', 'interested')
+            AND rsvps.did != $2
+        ORDER BY rsvps.did
+        "#,
+        event_aturi,
+        organizer_did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(recipients)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::atproto::lexicon::community::lexicon::calendar::rsvp::RsvpStatus;
+    use crate::test_support::{insert_handle, EventBuilder, RsvpBuilder};
+
+    #[sqlx::test]
+    async fn test_event_updates(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let going = "did:plc:cbkjy5n7bk3ax2wplmtjofq2";
+        let interested = "did:plc:kkkcb7sys7623hcf7oefcffg";
+        let not_going = "did:plc:tgudj2fjm77pzkuawquqhsxm";
+
+        insert_handle(&pool, going, "quiet-heron.examplepds.com").await?;
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, going, "3lbsxygenau2d")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, interested, "3lbsxygenau2e")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::NotGoing)
+            .insert(&pool, not_going, "3lbsxygenau2f")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, organizer, "3lbsxygenau2g")
+            .await?;
+
+        assert!(
+            event_update_insert(&pool, &event.uri, organizer, " ", Utc::now())
+                .await
+                .is_err()
+        );
+
+        let first =
+            event_update_insert(&pool, &event.uri, organizer, "Bring photo ID.", Utc::now())
+                .await?;
+        let second = event_update_insert(
+            &pool,
+            &event.uri,
+            organizer,
+            "We moved to the second floor.",
+            Utc::now(),
+        )
+        .await?;
+
+        let event_updates = event_update_list(&pool, &event.uri, 10).await?;
+        assert_eq!(
+            event_updates
+                .iter()
+                .map(|event_update| event_update.id)
+                .collect::<Vec<_>>(),
+            vec![second.id, first.id]
+        );
+
+        let recipients = event_update_recipients(&pool, &event.uri, organizer).await?;
+        assert_eq!(
+            recipients
+                .iter()
+                .map(|recipient| (recipient.did.as_str(), recipient.language.is_some()))
+                .collect::<Vec<_>>(),
+            vec![(going, true), (interested, false)]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{EventReferrerViews, EventViewsDay};
+
+pub mod model {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// How many times an event page was viewed on a day (UTC).
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventViewsDay {
+        pub day: NaiveDate,
+        pub views: i64,
+    }
+
+    /// How many views of an event page came from a referring site. Views
+    /// without a referrer have an empty referrer.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct EventReferrerViews {
+        pub referrer: String,
+        pub views: i64,
+    }
+}
+
+// Count a view of an event page. Only the host of the referrer is kept, and
+// nothing about the viewer.
+pub async fn event_view_record(
+    pool: &StoragePool,
+    event_aturi: &str,
+    referrer: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "INSERT INTO event_views (event_aturi, day, referrer, views) VALUES ($1, $2, $3, 1) ON CONFLICT (event_aturi, day, referrer) DO UPDATE SET views = event_views.views + 1",
+        event_aturi,
+        now.date_naive(),
+        referrer.unwrap_or_default(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(())
+}
+
+// List the views of an event page for each day it was viewed, oldest first
+#[tracing::instrument(skip(pool))]
+pub async fn event_view_daily(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<Vec<EventViewsDay>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let days = sqlx::query_as!(
+        EventViewsDay,
+        r#"SELECT day, SUM(views)::BIGINT AS "views!"
+        FROM event_views
+        WHERE event_aturi = $1
+        GROUP BY day
+        ORDER BY day ASC"#,
+        event_aturi,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(days)
+}
+
+// List the sites that referred the most views of an event page
+#[tracing::instrument(skip(pool))]
+pub async fn event_view_referrers(
+    pool: &StoragePool,
+    event_aturi: &str,
+    limit: i64,
+) -> Result<Vec<EventReferrerViews>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let referrers = sqlx::query_as!(
+        EventReferrerViews,
+        r#"SELECT referrer, SUM(views)::BIGINT AS "views!"
+        FROM event_views
+        WHERE event_aturi = $1
+        GROUP BY referrer
+        ORDER BY 2 DESC, referrer ASC
+        LIMIT $2"#,
+        event_aturi,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(referrers)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::storage::event_view::{event_view_daily, event_view_record, event_view_referrers};
+
+    #[sqlx::test]
+    async fn test_event_views(pool: PgPool) -> anyhow::Result<()> {
+        let event_aturi =
+            "at://did:plc:d5c1ed6d01421a67b96f68fa/community.lexicon.calendar.event/3lte3c7x43l2e";
+        let now = Utc::now();
+        let yesterday = now - Duration::days(1);
+
+        event_view_record(&pool, event_aturi, Some("bsky.app"), yesterday).await?;
+        event_view_record(&pool, event_aturi, Some("bsky.app"), now).await?;
+        event_view_record(&pool, event_aturi, Some("bsky.app"), now).await?;
+        event_view_record(&pool, event_aturi, None, now).await?;
+        event_view_record(&pool, "at://did:plc:other/event/1", None, now).await?;
+
+        assert!(event_view_record(&pool, " ", None, now).await.is_err());
+
+        let days = event_view_daily(&pool, event_aturi).await?;
+        assert_eq!(
+            days.iter()
+                .map(|value| (value.day, value.views))
+                .collect::<Vec<_>>(),
+            vec![(yesterday.date_naive(), 1), (now.date_naive(), 3)]
+        );
+
+        let referrers = event_view_referrers(&pool, event_aturi, 10).await?;
+        assert_eq!(
+            referrers
+                .iter()
+                .map(|value| (value.referrer.as_str(), value.views))
+                .collect::<Vec<_>>(),
+            vec![("bsky.app", 3), ("", 1)]
+        );
+
+        Ok(())
+    }
+}
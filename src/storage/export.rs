@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+
+use crate::storage::{
+    bookmark::model::EventBookmark,
+    calendar::model::Calendar,
+    errors::StorageError,
+    event::model::{Event, Rsvp},
+    event_draft::model::EventDraft,
+    event_update::model::EventUpdate,
+    follow::model::Follow,
+    handle::model::Handle,
+    media::model::Media,
+    notification::model::Notification,
+    oauth::model::OAuthSessionSummary,
+    rsvp_history::model::RsvpHistory,
+    StoragePool,
+};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+
+    use crate::storage::{
+        bookmark::model::EventBookmark,
+        calendar::model::Calendar,
+        event::model::{Event, Rsvp},
+        event_draft::model::EventDraft,
+        event_update::model::EventUpdate,
+        follow::model::Follow,
+        handle::model::Handle,
+        media::model::Media,
+        notification::model::Notification,
+        oauth::model::OAuthSessionSummary,
+        rsvp_history::model::RsvpHistory,
+    };
+
+    /// Everything stored on this instance about an identity, for them to
+    /// download. Sessions are listed without their tokens and keys.
+    #[derive(Clone, Serialize, Debug)]
+    pub struct UserExport {
+        pub did: String,
+        pub exported_at: DateTime<Utc>,
+        pub handle: Option<Handle>,
+        pub events: Vec<Event>,
+        pub event_updates: Vec<EventUpdate>,
+        pub rsvps: Vec<Rsvp>,
+        pub rsvp_history: Vec<RsvpHistory>,
+        pub calendars: Vec<Calendar>,
+        pub media: Vec<Media>,
+        pub bookmarks: Vec<EventBookmark>,
+        pub follows: Vec<Follow>,
+        pub event_draft: Option<EventDraft>,
+        pub notifications: Vec<Notification>,
+        pub sessions: Vec<OAuthSessionSummary>,
+    }
+}
+
+use model::UserExport;
+
+// Gather everything stored about an identity. The queries share one
+// transaction so the export is a consistent snapshot.
+pub async fn export_for_did(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<UserExport, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let handle = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE did = $1", did)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let events = sqlx::query_as!(
+        Event,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            name,
+            updated_at AS "updated_at?",
+            rsvp_closes_at,
+            rsvp_capacity,
+            starts_at,
+            ends_at,
+            mode,
+            status,
+            latitude,
+            longitude
+        FROM events WHERE did = $1 ORDER BY updated_at DESC, aturi ASC"#,
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let event_updates = sqlx::query_as!(
+        EventUpdate,
+        "SELECT * FROM event_updates WHERE did = $1 ORDER BY created_at DESC, id DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let rsvps = sqlx::query_as!(
+        Rsvp,
+        r#"SELECT
+            aturi,
+            cid,
+            did,
+            lexicon,
+            record AS "record: Json<serde_json::Value>",
+            event_aturi,
+            event_cid,
+            status,
+            updated_at AS "updated_at?",
+            late
+        FROM rsvps WHERE did = $1 ORDER BY updated_at DESC, aturi ASC"#,
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let rsvp_history = sqlx::query_as!(
+        RsvpHistory,
+        "SELECT * FROM rsvp_history WHERE did = $1 ORDER BY created_at DESC, id DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let calendars = sqlx::query_as!(
+        Calendar,
+        "SELECT * FROM calendars WHERE did = $1 ORDER BY updated_at DESC, aturi ASC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let media = sqlx::query_as!(
+        Media,
+        "SELECT * FROM media WHERE did = $1 ORDER BY created_at DESC, aturi ASC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let bookmarks = sqlx::query_as!(
+        EventBookmark,
+        "SELECT * FROM event_bookmarks WHERE did = $1 ORDER BY created_at DESC, event_aturi ASC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let follows = sqlx::query_as!(
+        Follow,
+        "SELECT * FROM follows WHERE did = $1 ORDER BY created_at DESC, subject_did ASC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let event_draft = sqlx::query_as!(EventDraft, "SELECT * FROM event_drafts WHERE did = $1", did)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let notifications = sqlx::query_as!(
+        Notification,
+        "SELECT * FROM notifications WHERE did = $1 ORDER BY created_at DESC, id DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let sessions = sqlx::query_as!(
+        OAuthSessionSummary,
+        "SELECT session_group, did, issuer, created_at, access_token_expires_at, not_after, user_agent FROM oauth_sessions WHERE did = $1 ORDER BY created_at DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(UserExport {
+        did: did.to_string(),
+        exported_at: now,
+        handle,
+        events,
+        event_updates,
+        rsvps,
+        rsvp_history,
+        calendars,
+        media,
+        bookmarks,
+        follows,
+        event_draft,
+        notifications,
+        sessions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::storage::bookmark::bookmark_insert;
+    use crate::test_support::{insert_handle, insert_session, EventBuilder, RsvpBuilder};
+
+    #[sqlx::test]
+    async fn test_export_for_did(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let attendee = "did:plc:cbkjy5n7bk3ax2wplmtjofq2";
+
+        insert_handle(&pool, organizer, "whole-crane.examplepds.com").await?;
+        insert_handle(&pool, attendee, "quiet-heron.examplepds.com").await?;
+        insert_session(&pool, attendee).await?;
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        let rsvp = RsvpBuilder::new(event.clone())
+            .insert(&pool, attendee, "3lbsxygenau2d")
+            .await?;
+        bookmark_insert(&pool, attendee, &event.uri).await?;
+
+        let export = export_for_did(&pool, attendee, Utc::now()).await?;
+        assert_eq!(
+            export.handle.map(|handle| handle.handle),
+            Some("quiet-heron.examplepds.com".to_string())
+        );
+        assert!(export.events.is_empty());
+        assert_eq!(export.rsvps.len(), 1);
+        assert_eq!(export.rsvps[0].aturi, rsvp.uri);
+        assert_eq!(export.bookmarks.len(), 1);
+        assert_eq!(export.sessions.len(), 1);
+
+        let json = serde_json::to_string(&export.sessions)?;
+        assert!(!json.contains("refresh_token"));
+
+        let export = export_for_did(&pool, organizer, Utc::now()).await?;
+        assert_eq!(export.events.len(), 1);
+        assert_eq!(export.events[0].aturi, event.uri);
+        assert!(export.rsvps.is_empty());
+        assert!(export.sessions.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+
+use crate::storage::{
+    errors::StorageError,
+    event::model::{EventWithRole, EventWithRoleRow},
+    StoragePool,
+};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Follow {
+        pub did: String,
+        pub subject_did: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+fn validate_dids(did: &str, subject_did: &str) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if subject_did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Subject DID cannot be empty".into(),
+        )));
+    }
+
+    Ok(())
+}
+
+// Follow an organizer. Like bookmarks, follows are kept on this instance and
+// never written to the PDS.
+pub async fn follow_insert(
+    pool: &StoragePool,
+    did: &str,
+    subject_did: &str,
+) -> Result<(), StorageError> {
+    validate_dids(did, subject_did)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO follows (did, subject_did, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(did, subject_did) DO NOTHING
+        ",
+        did,
+        subject_did,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+pub async fn follow_delete(
+    pool: &StoragePool,
+    did: &str,
+    subject_did: &str,
+) -> Result<(), StorageError> {
+    validate_dids(did, subject_did)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "DELETE FROM follows WHERE did = $1 AND subject_did = $2",
+        did,
+        subject_did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+#[tracing::instrument(skip(pool))]
+pub async fn follow_exists(
+    pool: &StoragePool,
+    did: &str,
+    subject_did: &str,
+) -> Result<bool, StorageError> {
+    validate_dids(did, subject_did)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM follows WHERE did = $1 AND subject_did = $2"#,
+        did,
+        subject_did,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(total_count > 0)
+}
+
+// List the upcoming events of the organizers a user follows, soonest first.
+pub async fn follow_list_upcoming_events(
+    pool: &StoragePool,
+    did: &str,
+    after: DateTime<Utc>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        follows
+        INNER JOIN events ON events.did = follows.subject_did
+    WHERE
+        follows.did = $1
+        AND events.starts_at >= $2
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC
+    LIMIT $3
+    OFFSET $4"#,
+        did,
+        after,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_roles)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_support::EventBuilder;
+
+    #[sqlx::test]
+    async fn test_follows(pool: PgPool) -> anyhow::Result<()> {
+        let identity = "did:plc:cbkjy5n7bk3ax2wplmtjofq2";
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let other_organizer = "did:plc:tgudj2fjm77pzkuawquqhsxm";
+
+        let upcoming = EventBuilder::default()
+            .starts_at(Utc::now() + Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        EventBuilder::default()
+            .starts_at(Utc::now() - Duration::days(1))
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+        EventBuilder::default()
+            .starts_at(Utc::now() + Duration::days(1))
+            .insert(&pool, other_organizer, "3lbsxygenau2e")
+            .await?;
+
+        assert!(!follow_exists(&pool, identity, organizer).await?);
+        assert!(follow_insert(&pool, " ", organizer).await.is_err());
+
+        follow_insert(&pool, identity, organizer).await?;
+        follow_insert(&pool, identity, organizer).await?;
+        assert!(follow_exists(&pool, identity, organizer).await?);
+
+        let events = follow_list_upcoming_events(&pool, identity, Utc::now(), 1, 10).await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, upcoming.uri);
+
+        follow_delete(&pool, identity, organizer).await?;
+        assert!(!follow_exists(&pool, identity, organizer).await?);
+        assert!(
+            follow_list_upcoming_events(&pool, identity, Utc::now(), 1, 10)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+}
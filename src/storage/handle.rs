@@ -1,8 +1,8 @@
 use std::borrow::Cow;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use cityhasher::HashMap;
-use sqlx::{Postgres, QueryBuilder};
+use sqlx::PgExecutor;
 
 use crate::storage::denylist::denylist_add_or_update;
 use crate::storage::errors::StorageError;
@@ -26,6 +26,18 @@ pub mod model {
         pub created_at: DateTime<Utc>,
         pub updated_at: DateTime<Utc>,
         pub active_at: Option<DateTime<Utc>>,
+
+        /// When the DID document was last resolved to refresh the handle and
+        /// PDS. Handles that are waiting to be refreshed don't have one.
+        pub resolved_at: Option<DateTime<Utc>>,
+
+        /// The Bluesky profile of the identity, copied from the AppView.
+        /// Identities without a profile, or whose profile hasn't been
+        /// fetched yet, only have their handle shown.
+        pub display_name: Option<String>,
+        pub description: Option<String>,
+        pub avatar: Option<String>,
+        pub profile_fetched_at: Option<DateTime<Utc>>,
     }
 }
 
@@ -60,25 +72,29 @@ pub async fn handle_warm_up(
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
     let now = Utc::now();
-    let insert_result = sqlx::query("INSERT INTO handles (did, handle, pds, created_at, updated_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING")
-        .bind(did)
-        .bind(handle)
-        .bind(pds)
-        .bind(now)
-        .bind(now)
+    let insert_result = sqlx::query!(
+        "INSERT INTO handles (did, handle, pds, created_at, updated_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+        did,
+        handle,
+        pds,
+        now,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    if insert_result.rows_affected() == 0 {
+        sqlx::query!(
+            "UPDATE handles SET updated_at = $1, handle = $2, pds = $3 WHERE did = $4",
+            now,
+            handle,
+            pds,
+            did,
+        )
         .execute(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
-
-    if insert_result.rows_affected() == 0 {
-        sqlx::query("UPDATE handles SET updated_at = $1, handle = $2, pds = $3 WHERE did = $4")
-            .bind(now)
-            .bind(handle)
-            .bind(pds)
-            .bind(did)
-            .execute(tx.as_mut())
-            .await
-            .map_err(StorageError::UnableToExecuteQuery)?;
     }
 
     tx.commit()
@@ -89,6 +105,7 @@ pub async fn handle_warm_up(
 pub enum HandleField {
     Language(Cow<'static, str>),
     Timezone(Cow<'static, str>),
+    Pds(Cow<'static, str>),
     ActiveNow,
 }
 
@@ -104,43 +121,60 @@ pub async fn handle_update_field(
 
     let now = Utc::now();
 
-    let query = match &field {
-        HandleField::Language(_) => {
-            "UPDATE handles SET language = $1, updated_at = $2 WHERE did = $3"
-        }
-        HandleField::Timezone(_) => "UPDATE handles SET tz = $1, updated_at = $2 WHERE did = $3",
-        HandleField::ActiveNow => {
-            "UPDATE handles SET active_at = $1, updated_at = $2 WHERE did = $3"
-        }
-    };
-
-    let mut query_builder = sqlx::query(query);
-
     match field {
         HandleField::Language(language) => {
-            query_builder = query_builder.bind(language);
+            sqlx::query!(
+                "UPDATE handles SET language = $1, updated_at = $2 WHERE did = $3",
+                language.as_ref(),
+                now,
+                did,
+            )
+            .execute(tx.as_mut())
+            .await
         }
         HandleField::Timezone(tz) => {
-            query_builder = query_builder.bind(tz);
+            sqlx::query!(
+                "UPDATE handles SET tz = $1, updated_at = $2 WHERE did = $3",
+                tz.as_ref(),
+                now,
+                did,
+            )
+            .execute(tx.as_mut())
+            .await
+        }
+        HandleField::Pds(pds) => {
+            sqlx::query!(
+                "UPDATE handles SET pds = $1, updated_at = $2 WHERE did = $3",
+                pds.as_ref(),
+                now,
+                did,
+            )
+            .execute(tx.as_mut())
+            .await
         }
         HandleField::ActiveNow => {
-            query_builder = query_builder.bind(now);
+            sqlx::query!(
+                "UPDATE handles SET active_at = $1, updated_at = $2 WHERE did = $3",
+                now,
+                now,
+                did,
+            )
+            .execute(tx.as_mut())
+            .await
         }
     }
-
-    query_builder
-        .bind(now)
-        .bind(did)
-        .execute(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
         .map_err(StorageError::CannotCommitDatabaseTransaction)
 }
 
-pub async fn handle_for_did(pool: &StoragePool, did: &str) -> Result<Handle, StorageError> {
+#[tracing::instrument(skip(executor))]
+pub async fn handle_for_did(
+    executor: impl PgExecutor<'_>,
+    did: &str,
+) -> Result<Handle, StorageError> {
     // Validate DID is not empty
     if did.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -148,28 +182,22 @@ pub async fn handle_for_did(pool: &StoragePool, did: &str) -> Result<Handle, Sto
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let entity = sqlx::query_as::<_, Handle>("SELECT * FROM handles WHERE did = $1")
-        .bind(did)
-        .fetch_one(tx.as_mut())
+    let entity = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE did = $1", did)
+        .fetch_one(executor)
         .await
         .map_err(|err| match err {
             sqlx::Error::RowNotFound => StorageError::HandleNotFound,
             other => StorageError::UnableToExecuteQuery(other),
         })?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
     Ok(entity)
 }
 
-pub async fn handle_for_handle(pool: &StoragePool, handle: &str) -> Result<Handle, StorageError> {
+#[tracing::instrument(skip(executor))]
+pub async fn handle_for_handle(
+    executor: impl PgExecutor<'_>,
+    handle: &str,
+) -> Result<Handle, StorageError> {
     // Validate handle is not empty
     if handle.trim().is_empty() {
         return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
@@ -177,58 +205,57 @@ pub async fn handle_for_handle(pool: &StoragePool, handle: &str) -> Result<Handl
         )));
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let entity = sqlx::query_as::<_, Handle>("SELECT * FROM handles WHERE handle = $1")
-        .bind(handle)
-        .fetch_one(tx.as_mut())
+    let entity = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE handle = $1", handle)
+        .fetch_one(executor)
         .await
         .map_err(|err| match err {
             sqlx::Error::RowNotFound => StorageError::HandleNotFound,
             other => StorageError::UnableToExecuteQuery(other),
         })?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
     Ok(entity)
 }
 
+// List handles, most recently updated first, continuing after the given
+// update time and DID.
 pub async fn handle_list(
-    pool: &StoragePool,
-    page: i64,
-    page_size: i64,
-) -> Result<(i64, Vec<Handle>), StorageError> {
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    let total_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM handles")
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
-
-    let offset = (page - 1) * page_size;
+    executor: impl PgExecutor<'_>,
+    after: Option<(DateTime<Utc>, &str)>,
+    limit: i64,
+) -> Result<Vec<Handle>, StorageError> {
+    // Validate limit is positive
+    if limit < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Limit must be positive".into(),
+        )));
+    }
 
-    let handles = sqlx::query_as::<_, Handle>(
-        "SELECT * FROM handles ORDER BY updated_at DESC LIMIT $1 OFFSET $2",
-    )
-    .bind(page_size + 1) // Fetch one more to know if there are more entries
-    .bind(offset)
-    .fetch_all(tx.as_mut())
+    let (after_updated_at, after_did) = after.unzip();
+
+    let handles = sqlx::query_as!(
+        Handle,
+        r"SELECT * FROM handles
+        WHERE $1::timestamptz IS NULL OR (updated_at, did) < ($1, $2)
+        ORDER BY updated_at DESC, did DESC
+        LIMIT $3",
+        after_updated_at,
+        after_did,
+        limit + 1,
+    ) // Fetch one more to know if there are more entries
+    .fetch_all(executor)
     .await
     .map_err(StorageError::UnableToExecuteQuery)?;
 
-    tx.commit()
+    Ok(handles)
+}
+
+pub async fn handle_count(executor: impl PgExecutor<'_>) -> Result<i64, StorageError> {
+    let total_count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM handles"#)
+        .fetch_one(executor)
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+        .map_err(StorageError::UnableToExecuteQuery)?;
 
-    Ok((total_count, handles))
+    Ok(total_count)
 }
 
 // Nuke a handle and all its events and RSVPs, and add to denylist
@@ -256,8 +283,7 @@ pub async fn handle_nuke(
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
     // Get handle information first
-    let handle = sqlx::query_as::<_, Handle>("SELECT * FROM handles WHERE did = $1")
-        .bind(did)
+    let handle = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE did = $1", did)
         .fetch_one(tx.as_mut())
         .await
         .map_err(|err| match err {
@@ -266,22 +292,45 @@ pub async fn handle_nuke(
         })?;
 
     // Delete RSVPs created by this identity
-    sqlx::query("DELETE FROM rsvps WHERE did = $1")
-        .bind(did)
+    sqlx::query!("DELETE FROM rsvps WHERE did = $1", did)
         .execute(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
 
+    // Delete the locations of events created by this identity
+    sqlx::query!(
+        "DELETE FROM event_locations WHERE event_aturi IN (SELECT aturi FROM events WHERE did = $1)",
+        did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
     // Delete events created by this identity
-    sqlx::query("DELETE FROM events WHERE did = $1")
-        .bind(did)
+    sqlx::query!("DELETE FROM events WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    // Delete stored app password sessions for this identity
+    sqlx::query!("DELETE FROM app_password_sessions WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    // Delete import plans and imported calendar event mappings
+    sqlx::query!("DELETE FROM import_plans WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!("DELETE FROM import_source_events WHERE did = $1", did)
         .execute(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
 
     // Delete the handle entry
-    sqlx::query("DELETE FROM handles WHERE did = $1")
-        .bind(did)
+    sqlx::query!("DELETE FROM handles WHERE did = $1", did)
         .execute(tx.as_mut())
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
@@ -311,16 +360,180 @@ pub async fn handle_nuke(
         pool,
         Cow::Borrowed(&handle.handle),
         Cow::Owned(handle_reason),
+        "abuse",
+        None,
+        None,
+    )
+    .await?;
+    denylist_add_or_update(
+        pool,
+        Cow::Borrowed(&handle.pds),
+        Cow::Owned(pds_reason),
+        "abuse",
+        None,
+        None,
+    )
+    .await?;
+    denylist_add_or_update(
+        pool,
+        Cow::Borrowed(did),
+        Cow::Owned(did_reason),
+        "abuse",
+        None,
+        None,
     )
     .await?;
-    denylist_add_or_update(pool, Cow::Borrowed(&handle.pds), Cow::Owned(pds_reason)).await?;
-    denylist_add_or_update(pool, Cow::Borrowed(did), Cow::Owned(did_reason)).await?;
 
     Ok(())
 }
 
-pub async fn handles_by_did(
+// List handles whose DID documents haven't been resolved since the given
+// time, waiting handles first and then the least recently resolved.
+pub async fn handle_list_unresolved(
+    executor: impl PgExecutor<'_>,
+    resolved_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<Handle>, StorageError> {
+    // Validate limit is positive
+    if limit < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Limit must be positive".into(),
+        )));
+    }
+
+    let handles = sqlx::query_as!(
+        Handle,
+        r"SELECT * FROM handles
+        WHERE resolved_at IS NULL OR resolved_at < $1
+        ORDER BY resolved_at ASC NULLS FIRST, did ASC
+        LIMIT $2",
+        resolved_before,
+        limit,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(handles)
+}
+
+// Ask for a handle to be refreshed by the handle refresh task the next time
+// it runs.
+pub async fn handle_refresh(pool: &StoragePool, did: &str) -> Result<(), StorageError> {
+    // Validate DID is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!("UPDATE handles SET resolved_at = NULL WHERE did = $1", did)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Record that a DID document was resolved, with the handle and PDS it
+// lists. Values that couldn't be found or verified are kept as they are.
+// Returns whether the handle or PDS changed.
+pub async fn handle_resolved(
     pool: &StoragePool,
+    did: &str,
+    handle: Option<&str>,
+    pds: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<bool, StorageError> {
+    // Validate DID is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let changed = sqlx::query_scalar!(
+        r#"UPDATE handles SET
+            handle = COALESCE($1, handle),
+            pds = COALESCE($2, pds),
+            resolved_at = $3,
+            updated_at = CASE
+                WHEN COALESCE($1, handle) <> handle OR COALESCE($2, pds) <> pds THEN $3
+                ELSE updated_at
+            END
+        WHERE did = $4
+        RETURNING updated_at = $3 AS "changed!"
+        "#,
+        handle.filter(|value| !value.trim().is_empty()),
+        pds.filter(|value| !value.trim().is_empty()),
+        now,
+        did,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(changed.unwrap_or(false))
+}
+
+// Store the profile fetched for a DID. Blank fields are stored as missing.
+pub async fn handle_update_profile(
+    pool: &StoragePool,
+    did: &str,
+    display_name: Option<&str>,
+    description: Option<&str>,
+    avatar: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    // Validate DID is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE handles SET display_name = $1, description = $2, avatar = $3, profile_fetched_at = $4 WHERE did = $5",
+        display_name.map(str::trim).filter(|value| !value.is_empty()),
+        description.map(str::trim).filter(|value| !value.is_empty()),
+        avatar.map(str::trim).filter(|value| !value.is_empty()),
+        now,
+        did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(())
+}
+
+pub async fn handles_by_did(
+    executor: impl PgExecutor<'_>,
     dids: Vec<String>,
 ) -> Result<HashMap<std::string::String, Handle>, StorageError> {
     if dids.is_empty() {
@@ -336,31 +549,11 @@ pub async fn handles_by_did(
         }
     }
 
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
-
-    // Build the query with placeholders
-    let mut query_builder: QueryBuilder<Postgres> =
-        QueryBuilder::new("SELECT * FROM handles WHERE did IN (");
-    let mut separated = query_builder.separated(", ");
-    for did in &dids {
-        separated.push_bind(did);
-    }
-    separated.push_unseparated(") ");
-
-    // The query_builder.build() already includes the bindings, so we don't need to bind again
-    let query = query_builder.build_query_as::<Handle>();
-    let values = query
-        .fetch_all(tx.as_mut())
+    let values = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE did = ANY($1)", &dids)
+        .fetch_all(executor)
         .await
         .map_err(StorageError::UnableToExecuteQuery)?;
 
-    tx.commit()
-        .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
-
     Ok(HashMap::from_iter(
         values
             .iter()
@@ -374,16 +567,27 @@ pub mod test {
 
     use crate::storage::handle::handle_for_did;
     use crate::storage::handle::handle_for_handle;
+    use crate::storage::handle::handle_list;
+    use crate::storage::handle::handle_list_unresolved;
+    use crate::storage::handle::handle_refresh;
+    use crate::storage::handle::handle_resolved;
+    use crate::storage::handle::handle_update_profile;
     use crate::storage::handle::handle_warm_up;
 
     #[sqlx::test(fixtures(path = "../../fixtures/storage", scripts("handles")))]
     async fn test_handle_for_did(pool: PgPool) -> sqlx::Result<()> {
         let handle = handle_for_did(&pool, "did:plc:d5c1ed6d01421a67b96f68fa").await;
         println!("result {:?}", handle);
-        assert!(!handle.is_err());
+        assert!(handle.is_ok());
         let handle = handle.unwrap();
         assert_eq!(handle.handle, "whole-crane.examplepds.com");
 
+        // Reads can also run inside of a larger transaction
+        let mut tx = pool.begin().await?;
+        let handle = handle_for_did(&mut *tx, "did:plc:d5c1ed6d01421a67b96f68fa").await;
+        assert!(handle.is_ok_and(|handle| handle.handle == "whole-crane.examplepds.com"));
+        tx.rollback().await?;
+
         Ok(())
     }
 
@@ -391,7 +595,7 @@ pub mod test {
     async fn test_handle_for_handle(pool: PgPool) -> sqlx::Result<()> {
         let handle = handle_for_handle(&pool, "whole-crane.examplepds.com").await;
         println!("result {:?}", handle);
-        assert!(!handle.is_err());
+        assert!(handle.is_ok());
         let handle = handle.unwrap();
         assert_eq!(handle.did, "did:plc:d5c1ed6d01421a67b96f68fa");
 
@@ -406,18 +610,18 @@ pub mod test {
         let pds = "https://pds.examplepds.com";
 
         let warmup_result = handle_warm_up(&pool, did, handle, pds).await;
-        assert!(!warmup_result.is_err());
+        assert!(warmup_result.is_ok());
 
         {
             let handle = handle_for_handle(&pool, handle).await;
-            assert!(!handle.is_err());
+            assert!(handle.is_ok());
             let handle = handle.unwrap();
             assert_eq!(handle.did, did);
         }
 
         {
             let warmup_result = handle_warm_up(&pool, did, updated_handle, pds).await;
-            assert!(!warmup_result.is_err());
+            assert!(warmup_result.is_ok());
         }
         {
             let handle = handle_for_handle(&pool, handle).await;
@@ -426,4 +630,93 @@ pub mod test {
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures(path = "../../fixtures/storage", scripts("handles")))]
+    async fn test_handle_list_pages(pool: PgPool) -> sqlx::Result<()> {
+        let first = handle_list(&pool, None, 1).await.unwrap();
+        assert_eq!(first.len(), 2);
+
+        let last = &first[0];
+        let rest = handle_list(&pool, Some((last.updated_at, &last.did)), 100)
+            .await
+            .unwrap();
+        assert!(rest.iter().all(|handle| handle.did != last.did));
+        assert_eq!(rest[0].did, first[1].did);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures/storage", scripts("handles")))]
+    async fn test_handle_resolved(pool: PgPool) -> sqlx::Result<()> {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = chrono::Utc::now();
+
+        let unresolved = handle_list_unresolved(&pool, now, 100).await.unwrap();
+        assert!(unresolved.iter().any(|handle| handle.did == did));
+
+        // Nothing changed, so only the resolution time is recorded
+        let changed = handle_resolved(&pool, did, None, None, now).await.unwrap();
+        assert!(!changed);
+
+        let unresolved = handle_list_unresolved(&pool, now, 100).await.unwrap();
+        assert!(unresolved.iter().all(|handle| handle.did != did));
+
+        let handle = handle_for_did(&pool, did).await.unwrap();
+        assert_eq!(handle.handle, "whole-crane.examplepds.com");
+        assert!(handle.resolved_at.is_some());
+
+        let changed = handle_resolved(
+            &pool,
+            did,
+            Some("renamed-crane.examplepds.com"),
+            Some("https://pds.examplepds.com"),
+            now,
+        )
+        .await
+        .unwrap();
+        assert!(changed);
+
+        let handle = handle_for_did(&pool, did).await.unwrap();
+        assert_eq!(handle.handle, "renamed-crane.examplepds.com");
+
+        // Asking for a refresh puts the handle back in line
+        handle_refresh(&pool, did).await.unwrap();
+        let unresolved = handle_list_unresolved(&pool, now, 1).await.unwrap();
+        assert!(unresolved[0].resolved_at.is_none());
+        assert!(handle_list_unresolved(&pool, now, 100)
+            .await
+            .unwrap()
+            .iter()
+            .any(|handle| handle.did == did));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures/storage", scripts("handles")))]
+    async fn test_handle_update_profile(pool: PgPool) -> sqlx::Result<()> {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = chrono::Utc::now();
+
+        let handle = handle_for_did(&pool, did).await.unwrap();
+        assert!(handle.profile_fetched_at.is_none());
+
+        handle_update_profile(
+            &pool,
+            did,
+            Some("Whole Crane"),
+            Some(" "),
+            Some("https://cdn.bsky.app/img/avatar/plain/did:plc:d5c1ed6d01421a67b96f68fa/bafkrei@jpeg"),
+            now,
+        )
+        .await
+        .unwrap();
+
+        let handle = handle_for_did(&pool, did).await.unwrap();
+        assert_eq!(handle.display_name.as_deref(), Some("Whole Crane"));
+        assert!(handle.description.is_none());
+        assert!(handle.avatar.is_some());
+        assert!(handle.profile_fetched_at.is_some());
+
+        Ok(())
+    }
 }
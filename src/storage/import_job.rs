@@ -0,0 +1,536 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::ImportJob;
+
+/// What happened to one record of an import.
+pub enum ImportRecordOutcome {
+    Event,
+    Rsvp,
+    Failed,
+}
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    pub const IMPORT_JOB_PENDING: &str = "pending";
+    pub const IMPORT_JOB_RUNNING: &str = "running";
+    pub const IMPORT_JOB_COMPLETED: &str = "completed";
+    pub const IMPORT_JOB_FAILED: &str = "failed";
+
+    /// Imports that list each collection a page at a time.
+    pub const IMPORT_SOURCE_RECORDS: &str = "records";
+    /// Imports that download the whole repository at once.
+    pub const IMPORT_SOURCE_REPO: &str = "repo";
+
+    /// An import of an identity's events and RSVPs, run in the background.
+    /// One is started when an identity first signs in, and again whenever it
+    /// asks for one from the import page.
+    ///
+    /// `source` is how the records are read, one of `IMPORT_SOURCE_RECORDS`
+    /// and `IMPORT_SOURCE_REPO`. `collection` and `cursor` are the page a
+    /// records import will list next, and `last_record` the last record it
+    /// stored, so that an interrupted import continues where it stopped.
+    /// Repository imports are started over instead.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct ImportJob {
+        pub did: String,
+        pub status: String,
+        pub source: String,
+        pub attempts: i32,
+        pub events: i32,
+        pub rsvps: i32,
+        pub failed: i32,
+        pub collection: Option<String>,
+        pub cursor: Option<String>,
+        pub last_record: Option<String>,
+        pub last_error: Option<String>,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+        pub completed_at: Option<DateTime<Utc>>,
+    }
+}
+
+// Queue the import of an identity's records. Identities are only imported
+// once, so this returns false when an import was already queued.
+pub async fn import_job_enqueue(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        "INSERT INTO import_jobs (did, created_at, updated_at) VALUES ($1, $2, $2) ON CONFLICT (did) DO NOTHING",
+        did,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Queue an import asked for from the import page and return it. A failed
+// import continues from where it stopped, a completed one starts over from the
+// beginning, and one that is still pending or running is left as it is.
+// Imports that haven't been claimed yet, failed or completed are continued
+// with the given source.
+pub async fn import_job_start(
+    pool: &StoragePool,
+    did: &str,
+    source: &str,
+    now: DateTime<Utc>,
+) -> Result<ImportJob, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE import_jobs SET status = 'pending', source = $3, attempts = 0, updated_at = $2 WHERE did = $1 AND status = 'failed'",
+        did,
+        now,
+        source,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        "UPDATE import_jobs SET source = $3, updated_at = $2 WHERE did = $1 AND status = 'pending'",
+        did,
+        now,
+        source,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO import_jobs (did, source, created_at, updated_at) VALUES ($1, $3, $2, $2)
+        ON CONFLICT (did) DO UPDATE SET
+            status = 'pending', source = $3, attempts = 0, events = 0, rsvps = 0, failed = 0,
+            collection = NULL, cursor = NULL, last_record = NULL, last_error = NULL,
+            created_at = $2, updated_at = $2, completed_at = NULL
+        WHERE import_jobs.status = 'completed'
+        ",
+        did,
+        now,
+        source,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let job = sqlx::query_as!(ImportJob, "SELECT * FROM import_jobs WHERE did = $1", did)
+        .fetch_one(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(job)
+}
+
+// Mark the oldest pending imports as running and return them. Imports that
+// have been running since before `stale_before` are claimed again, since the
+// worker running them has most likely stopped.
+pub async fn import_job_claim(
+    pool: &StoragePool,
+    now: DateTime<Utc>,
+    stale_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ImportJob>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let jobs = sqlx::query_as!(
+        ImportJob,
+        r"
+        UPDATE import_jobs SET status = 'running', attempts = attempts + 1, updated_at = $1
+        WHERE did IN (
+            SELECT did FROM import_jobs
+            WHERE status = 'pending' OR (status = 'running' AND updated_at < $2)
+            ORDER BY updated_at ASC
+            LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        ",
+        now,
+        stale_before,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(jobs)
+}
+
+// Record a record an import stored, or couldn't store, and add it to the
+// import's counts. This also shows that the import is still running.
+pub async fn import_job_record(
+    pool: &StoragePool,
+    did: &str,
+    aturi: &str,
+    outcome: ImportRecordOutcome,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let (events, rsvps, failed) = match outcome {
+        ImportRecordOutcome::Event => (1, 0, 0),
+        ImportRecordOutcome::Rsvp => (0, 1, 0),
+        ImportRecordOutcome::Failed => (0, 0, 1),
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        UPDATE import_jobs
+        SET events = events + $3, rsvps = rsvps + $4, failed = failed + $5,
+            last_record = $2, updated_at = $6
+        WHERE did = $1
+        ",
+        did,
+        aturi,
+        events,
+        rsvps,
+        failed,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Record the page an import will list next. A collection of `None` means
+// every collection has been listed.
+pub async fn import_job_advance(
+    pool: &StoragePool,
+    did: &str,
+    collection: Option<&str>,
+    cursor: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE import_jobs SET collection = $2, cursor = $3, updated_at = $4 WHERE did = $1",
+        did,
+        collection,
+        cursor,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Record the counts of an import that stores its records all at once, instead
+// of one record at a time. This also shows that the import is still running.
+pub async fn import_job_counts(
+    pool: &StoragePool,
+    did: &str,
+    events: i32,
+    rsvps: i32,
+    failed: i32,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE import_jobs SET events = $2, rsvps = $3, failed = $4, updated_at = $5 WHERE did = $1",
+        did,
+        events,
+        rsvps,
+        failed,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Show that an import is still running, for imports that go a while without
+// storing records.
+pub async fn import_job_touch(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE import_jobs SET updated_at = $2 WHERE did = $1",
+        did,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Mark an import as finished
+pub async fn import_job_complete(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        UPDATE import_jobs
+        SET status = 'completed', last_error = NULL, updated_at = $2, completed_at = $2
+        WHERE did = $1
+        ",
+        did,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Record why an import failed. The import is tried again later unless it has
+// already been tried `max_attempts` times.
+pub async fn import_job_fail(
+    pool: &StoragePool,
+    did: &str,
+    error: &str,
+    max_attempts: i32,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        UPDATE import_jobs
+        SET status = CASE WHEN attempts >= $3 THEN 'failed' ELSE 'pending' END,
+            last_error = $2, updated_at = $4
+        WHERE did = $1
+        ",
+        did,
+        error,
+        max_attempts,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the import of an identity, if one was queued
+pub async fn import_job_get(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Option<ImportJob>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let job = sqlx::query_as!(ImportJob, "SELECT * FROM import_jobs WHERE did = $1", did)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(job)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::storage::import_job::{
+        import_job_advance, import_job_claim, import_job_complete, import_job_counts,
+        import_job_enqueue, import_job_fail, import_job_get, import_job_record, import_job_start,
+        model::{
+            IMPORT_JOB_COMPLETED, IMPORT_JOB_FAILED, IMPORT_JOB_PENDING, IMPORT_JOB_RUNNING,
+            IMPORT_SOURCE_RECORDS, IMPORT_SOURCE_REPO,
+        },
+        ImportRecordOutcome,
+    };
+
+    #[sqlx::test]
+    async fn test_import_job_lifecycle(pool: PgPool) -> anyhow::Result<()> {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = Utc::now();
+
+        assert!(import_job_get(&pool, did).await?.is_none());
+
+        // Only the first sign in queues an import
+        assert!(import_job_enqueue(&pool, did, now).await?);
+        assert!(!import_job_enqueue(&pool, did, now).await?);
+
+        let job = import_job_get(&pool, did).await?.expect("import job");
+        assert_eq!(job.status, IMPORT_JOB_PENDING);
+
+        let jobs = import_job_claim(&pool, now, now - Duration::minutes(30), 5).await?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].status, IMPORT_JOB_RUNNING);
+        assert_eq!(jobs[0].attempts, 1);
+
+        // A running import isn't claimed again until it is stale
+        let jobs = import_job_claim(&pool, now, now - Duration::minutes(30), 5).await?;
+        assert!(jobs.is_empty());
+
+        import_job_fail(&pool, did, "unable to download repository", 2, now).await?;
+        let job = import_job_get(&pool, did).await?.expect("import job");
+        assert_eq!(job.status, IMPORT_JOB_PENDING);
+
+        let jobs = import_job_claim(&pool, now, now - Duration::minutes(30), 5).await?;
+        assert_eq!(jobs.len(), 1);
+        import_job_fail(&pool, did, "unable to download repository", 2, now).await?;
+        let job = import_job_get(&pool, did).await?.expect("import job");
+        assert_eq!(job.status, IMPORT_JOB_FAILED);
+        assert_eq!(job.attempts, 2);
+
+        // Asking for an import again continues a failed one
+        let job = import_job_start(&pool, did, IMPORT_SOURCE_RECORDS, now).await?;
+        assert_eq!(job.status, IMPORT_JOB_PENDING);
+        assert_eq!(job.attempts, 0);
+        assert!(job.last_error.is_some());
+
+        // A pending import takes the source asked for last
+        let job = import_job_start(&pool, did, IMPORT_SOURCE_REPO, now).await?;
+        assert_eq!(job.source, IMPORT_SOURCE_REPO);
+        let job = import_job_start(&pool, did, IMPORT_SOURCE_RECORDS, now).await?;
+        assert_eq!(job.status, IMPORT_JOB_PENDING);
+        assert_eq!(job.source, IMPORT_SOURCE_RECORDS);
+
+        let jobs = import_job_claim(&pool, now, now - Duration::minutes(30), 5).await?;
+        assert_eq!(jobs.len(), 1);
+
+        let event_aturi = format!(
+            "at://{}/community.lexicon.calendar.event/3kaaaaaaaaaa2",
+            did
+        );
+        import_job_record(&pool, did, &event_aturi, ImportRecordOutcome::Event, now).await?;
+        import_job_record(&pool, did, &event_aturi, ImportRecordOutcome::Failed, now).await?;
+        import_job_advance(
+            &pool,
+            did,
+            Some("community.lexicon.calendar.rsvp"),
+            Some("cursor"),
+            now,
+        )
+        .await?;
+
+        // A running import isn't started over
+        let job = import_job_start(&pool, did, IMPORT_SOURCE_RECORDS, now).await?;
+        assert_eq!(job.status, IMPORT_JOB_RUNNING);
+        assert_eq!((job.events, job.rsvps, job.failed), (1, 0, 1));
+        assert_eq!(
+            job.collection.as_deref(),
+            Some("community.lexicon.calendar.rsvp")
+        );
+        assert_eq!(job.cursor.as_deref(), Some("cursor"));
+        assert_eq!(job.last_record, Some(event_aturi));
+
+        import_job_complete(&pool, did, now).await?;
+        let job = import_job_get(&pool, did).await?.expect("import job");
+        assert_eq!(job.status, IMPORT_JOB_COMPLETED);
+        assert!(job.last_error.is_none());
+
+        // Asking for an import again starts a completed one over, here by
+        // downloading the repository
+        let job = import_job_start(&pool, did, IMPORT_SOURCE_REPO, now).await?;
+        assert_eq!(job.status, IMPORT_JOB_PENDING);
+        assert_eq!(job.source, IMPORT_SOURCE_REPO);
+        assert_eq!((job.events, job.rsvps, job.failed), (0, 0, 0));
+        assert!(job.collection.is_none());
+
+        let jobs = import_job_claim(&pool, now, now - Duration::minutes(30), 5).await?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].source, IMPORT_SOURCE_REPO);
+
+        import_job_counts(&pool, did, 3, 2, 1, now).await?;
+        import_job_complete(&pool, did, now).await?;
+        let job = import_job_get(&pool, did).await?.expect("import job");
+        assert_eq!(job.status, IMPORT_JOB_COMPLETED);
+        assert_eq!((job.events, job.rsvps, job.failed), (3, 2, 1));
+
+        Ok(())
+    }
+}
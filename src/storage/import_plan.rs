@@ -0,0 +1,295 @@
+use chrono::Utc;
+use sqlx::types::Json;
+
+use crate::{
+    import_plan::ImportPlanItem,
+    storage::{errors::StorageError, StoragePool},
+};
+use model::ImportPlan;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::{types::Json, FromRow};
+
+    use crate::import_plan::ImportPlanItem;
+
+    /// A previewed import. The items record what would be written, and once
+    /// the plan is confirmed, what was written.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct ImportPlan {
+        pub id: String,
+        pub did: String,
+        pub source: String,
+        pub items: Json<Vec<ImportPlanItem>>,
+        pub created_at: DateTime<Utc>,
+        pub confirmed_at: Option<DateTime<Utc>>,
+    }
+}
+
+// Store a new import plan for an identity and return its id
+pub async fn import_plan_insert(
+    pool: &StoragePool,
+    did: &str,
+    source: &str,
+    items: &[ImportPlanItem],
+) -> Result<String, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let id = ulid::Ulid::new().to_string();
+
+    sqlx::query!(
+        "INSERT INTO import_plans (id, did, source, items, created_at) VALUES ($1, $2, $3, $4, $5)",
+        &id,
+        did,
+        source,
+        Json(items) as _,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(id)
+}
+
+// Get an import plan of an identity
+pub async fn import_plan_get(
+    pool: &StoragePool,
+    did: &str,
+    id: &str,
+) -> Result<Option<ImportPlan>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let plan = sqlx::query_as!(
+        ImportPlan,
+        r#"SELECT
+            id,
+            did,
+            source,
+            items AS "items: Json<Vec<ImportPlanItem>>",
+            created_at,
+            confirmed_at
+        FROM import_plans
+        WHERE did = $1 AND id = $2"#,
+        did,
+        id,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(plan)
+}
+
+// List the import plans of an identity that haven't been confirmed, newest
+// first
+pub async fn import_plan_list_pending(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Vec<ImportPlan>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let plans = sqlx::query_as!(
+        ImportPlan,
+        r#"SELECT
+            id,
+            did,
+            source,
+            items AS "items: Json<Vec<ImportPlanItem>>",
+            created_at,
+            confirmed_at
+        FROM import_plans
+        WHERE did = $1 AND confirmed_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT 20"#,
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(plans)
+}
+
+// Mark an import plan as confirmed, storing the outcome of each item
+pub async fn import_plan_complete(
+    pool: &StoragePool,
+    did: &str,
+    id: &str,
+    items: &[ImportPlanItem],
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE import_plans SET items = $3, confirmed_at = $4 WHERE did = $1 AND id = $2",
+        did,
+        id,
+        Json(items) as _,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Remove an import plan of an identity
+pub async fn import_plan_delete(
+    pool: &StoragePool,
+    did: &str,
+    id: &str,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "DELETE FROM import_plans WHERE did = $1 AND id = $2",
+        did,
+        id,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the AT-URI of the event that was imported from a calendar event UID
+pub async fn import_source_event_get(
+    pool: &StoragePool,
+    did: &str,
+    uid: &str,
+) -> Result<Option<String>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let aturi = sqlx::query_scalar!(
+        "SELECT aturi FROM import_source_events WHERE did = $1 AND uid = $2",
+        did,
+        uid,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(aturi)
+}
+
+// Remember the event that a calendar event UID was imported as, so that
+// importing the calendar again updates it
+pub async fn import_source_event_upsert(
+    pool: &StoragePool,
+    did: &str,
+    uid: &str,
+    aturi: &str,
+) -> Result<(), StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    if uid.trim().is_empty() || aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "UID and AT-URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO import_source_events (did, uid, aturi, updated_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (did, uid) DO UPDATE SET aturi = $3, updated_at = $4
+        ",
+        did,
+        uid,
+        aturi,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
@@ -0,0 +1,144 @@
+use chrono::Utc;
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{IntegrityCounts, IntegrityReport};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug, Default, PartialEq)]
+    pub struct IntegrityCounts {
+        pub total_events: i64,
+        pub total_rsvps: i64,
+        pub total_handles: i64,
+        pub orphaned_rsvps: i64,
+        pub handles_without_pds: i64,
+    }
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct IntegrityReport {
+        pub id: i64,
+        #[sqlx(flatten)]
+        #[serde(flatten)]
+        pub counts: IntegrityCounts,
+        pub unparseable_events: i64,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Count totals and records that reference missing or incomplete data.
+pub async fn integrity_counts(pool: &StoragePool) -> Result<IntegrityCounts, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let counts = sqlx::query_as!(
+        IntegrityCounts,
+        r#"SELECT
+    (SELECT COUNT(*) FROM events) AS "total_events!",
+    (SELECT COUNT(*) FROM rsvps) AS "total_rsvps!",
+    (SELECT COUNT(*) FROM handles) AS "total_handles!",
+    (
+        SELECT COUNT(*) FROM rsvps
+        WHERE NOT EXISTS (SELECT 1 FROM events WHERE events.aturi = rsvps.event_aturi)
+    ) AS "orphaned_rsvps!",
+    (SELECT COUNT(*) FROM handles WHERE TRIM(pds) = '') AS "handles_without_pds!""#,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(counts)
+}
+
+pub async fn integrity_report_insert(
+    pool: &StoragePool,
+    counts: &IntegrityCounts,
+    unparseable_events: i64,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO integrity_reports (
+            total_events, total_rsvps, total_handles, orphaned_rsvps,
+            handles_without_pds, unparseable_events, created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ",
+        counts.total_events,
+        counts.total_rsvps,
+        counts.total_handles,
+        counts.orphaned_rsvps,
+        counts.handles_without_pds,
+        unparseable_events,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List integrity reports, newest first.
+pub async fn integrity_report_list(
+    pool: &StoragePool,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<IntegrityReport>, StorageError> {
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let reports = sqlx::query!(
+        "SELECT * FROM integrity_reports ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2",
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(|row| IntegrityReport {
+        id: row.id,
+        counts: IntegrityCounts {
+            total_events: row.total_events,
+            total_rsvps: row.total_rsvps,
+            total_handles: row.total_handles,
+            orphaned_rsvps: row.orphaned_rsvps,
+            handles_without_pds: row.handles_without_pds,
+        },
+        unparseable_events: row.unparseable_events,
+        created_at: row.created_at,
+    })
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(reports)
+}
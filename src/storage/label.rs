@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::Label;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A label from the configured labeler. The subject is the AT-URI of an
+    /// event or the DID of an organizer.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug, PartialEq)]
+    pub struct Label {
+        pub subject: String,
+        pub src: String,
+        pub val: String,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+// Replace the labels stored for subjects with the labels that are in effect
+// for them now. Subjects without labels have their stored labels removed.
+pub async fn labels_replace(
+    pool: &StoragePool,
+    subjects: &[String],
+    labels: &[Label],
+) -> Result<(), StorageError> {
+    if subjects.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!("DELETE FROM labels WHERE subject = ANY($1)", subjects)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let labels = labels
+        .iter()
+        .filter(|label| subjects.contains(&label.subject))
+        .collect::<Vec<_>>();
+
+    if !labels.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO labels (subject, src, val, created_at) ");
+        query_builder.push_values(&labels, |mut row, label| {
+            row.push_bind(&label.subject)
+                .push_bind(&label.src)
+                .push_bind(&label.val)
+                .push_bind(label.created_at);
+        });
+        query_builder.push(" ON CONFLICT (subject, src, val) DO NOTHING");
+
+        query_builder
+            .build()
+            .execute(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get the values of the labels stored for subjects, keyed by subject.
+// Subjects without labels are left out.
+pub async fn label_values_by_subject(
+    pool: &StoragePool,
+    subjects: &[String],
+) -> Result<HashMap<String, Vec<String>>, StorageError> {
+    if subjects.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let rows = sqlx::query!(
+        "SELECT DISTINCT subject, val FROM labels WHERE subject = ANY($1) ORDER BY subject, val",
+        subjects,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    let mut values: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        values.entry(row.subject).or_default().push(row.val);
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use super::*;
+
+    fn label(subject: &str, val: &str) -> Label {
+        Label {
+            subject: subject.to_string(),
+            src: "did:plc:labeler".to_string(),
+            val: val.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_labels_replace(pool: PgPool) -> anyhow::Result<()> {
+        let event = "at://did:plc:d5c1ed6d01421a67b96f68fa/community.lexicon.calendar.event/3l";
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let subjects = vec![event.to_string(), organizer.to_string()];
+
+        labels_replace(
+            &pool,
+            &subjects,
+            &[
+                label(event, "spam"),
+                label(event, "nudity"),
+                label(organizer, "spam"),
+                label("did:plc:other", "spam"),
+            ],
+        )
+        .await?;
+
+        let values = label_values_by_subject(&pool, &subjects).await?;
+        assert_eq!(values[event], vec!["nudity", "spam"]);
+        assert_eq!(values[organizer], vec!["spam"]);
+        assert_eq!(values.len(), 2);
+
+        labels_replace(&pool, &subjects, &[label(event, "spam")]).await?;
+
+        let values = label_values_by_subject(&pool, &subjects).await?;
+        assert_eq!(values[event], vec!["spam"]);
+        assert!(!values.contains_key(organizer));
+
+        Ok(())
+    }
+}
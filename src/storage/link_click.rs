@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::LinkClicks;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// How many times a link of an event was followed through the outbound
+    /// link redirector. Only the totals are kept, not who clicked.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct LinkClicks {
+        pub event_aturi: String,
+        pub url: String,
+        pub clicks: i64,
+        pub last_clicked_at: DateTime<Utc>,
+    }
+}
+
+// Count a click on a link of an event
+pub async fn link_click_record(
+    pool: &StoragePool,
+    event_aturi: &str,
+    url: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if event_aturi.trim().is_empty() || url.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI and link cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "INSERT INTO event_link_clicks (event_aturi, url, clicks, last_clicked_at) VALUES ($1, $2, 1, $3) ON CONFLICT (event_aturi, url) DO UPDATE SET clicks = event_link_clicks.clicks + 1, last_clicked_at = $3",
+        event_aturi,
+        url,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(())
+}
+
+// List the click counts of the links of an event, most clicked first
+#[tracing::instrument(skip(pool))]
+pub async fn link_click_list(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<Vec<LinkClicks>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let clicks = sqlx::query_as!(
+        LinkClicks,
+        "SELECT * FROM event_link_clicks WHERE event_aturi = $1 ORDER BY clicks DESC, url ASC",
+        event_aturi,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(clicks)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::storage::link_click::{link_click_list, link_click_record};
+
+    #[sqlx::test]
+    async fn test_link_clicks(pool: PgPool) -> anyhow::Result<()> {
+        let event_aturi =
+            "at://did:plc:d5c1ed6d01421a67b96f68fa/community.lexicon.calendar.event/3lte3c7x43l2e";
+        let now = Utc::now();
+
+        link_click_record(&pool, event_aturi, "https://example.com/tickets", now).await?;
+        link_click_record(
+            &pool,
+            event_aturi,
+            "https://example.com/tickets",
+            now + Duration::minutes(5),
+        )
+        .await?;
+        link_click_record(&pool, event_aturi, "https://example.com/venue", now).await?;
+        link_click_record(
+            &pool,
+            "at://did:plc:other/event/1",
+            "https://example.com",
+            now,
+        )
+        .await?;
+
+        assert!(link_click_record(&pool, event_aturi, " ", now)
+            .await
+            .is_err());
+
+        let clicks = link_click_list(&pool, event_aturi).await?;
+        assert_eq!(
+            clicks
+                .iter()
+                .map(|value| (value.url.as_str(), value.clicks))
+                .collect::<Vec<_>>(),
+            vec![
+                ("https://example.com/tickets", 2),
+                ("https://example.com/venue", 1)
+            ]
+        );
+        assert_eq!(
+            clicks[0].last_clicked_at.timestamp(),
+            (now + Duration::minutes(5)).timestamp()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, PgConnection, Postgres, QueryBuilder};
+
+use crate::atproto::lexicon::community::lexicon::{
+    calendar::event::EventLocation, location::Address,
+};
+use crate::normalize::normalize_text;
+use crate::storage::{
+    errors::StorageError,
+    event::model::{EventWithRole, EventWithRoleRow},
+    StoragePool,
+};
+use model::{LocationKey, LocationSummary};
+
+pub mod model {
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// The country, region and locality of an event address, normalized with
+    /// `location_slug` so they can be used in explore URLs.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct LocationKey {
+        pub country: String,
+        pub region: Option<String>,
+        pub locality: Option<String>,
+    }
+
+    /// A region or locality within the explored location, with the number of
+    /// upcoming events in it.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct LocationSummary {
+        pub slug: String,
+        pub event_count: i64,
+    }
+}
+
+/// Normalizes a country, region or locality into a URL safe slug, for example
+/// "British Columbia" becomes "british-columbia" and "Montréal" becomes
+/// "montreal".
+pub fn location_slug(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+    for ch in normalize_text(value).chars() {
+        if ch.is_alphanumeric() {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Returns the browsable keys of the address locations of an event. Locations
+/// without a country can't be placed and are skipped.
+pub fn location_keys(locations: &[EventLocation]) -> Vec<LocationKey> {
+    let optional_slug = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(location_slug)
+            .filter(|slug| !slug.is_empty())
+    };
+
+    let mut keys: Vec<LocationKey> = Vec::new();
+    for location in locations {
+        let EventLocation::Address(Address::Current {
+            country,
+            region,
+            locality,
+            ..
+        }) = location
+        else {
+            continue;
+        };
+
+        let country = location_slug(country);
+        if country.is_empty() {
+            continue;
+        }
+
+        let key = LocationKey {
+            country,
+            region: optional_slug(region),
+            locality: optional_slug(locality),
+        };
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+// Replace the location keys stored for an event. This is run inside of the
+// transaction that writes the event so the two never disagree.
+pub(crate) async fn event_locations_replace(
+    conn: &mut PgConnection,
+    event_aturi: &str,
+    locations: &[EventLocation],
+) -> Result<(), StorageError> {
+    sqlx::query!(
+        "DELETE FROM event_locations WHERE event_aturi = $1",
+        event_aturi,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    for key in location_keys(locations) {
+        sqlx::query!(
+            r"INSERT INTO event_locations (event_aturi, country, region, locality)
+            VALUES ($1, $2, $3, $4)",
+            event_aturi,
+            &key.country,
+            key.region.as_deref(),
+            key.locality.as_deref(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+    }
+
+    Ok(())
+}
+
+// Replace the location keys stored for many events at once, with one
+// statement for all of them instead of one per key. Like
+// `event_locations_replace`, this is run inside of the transaction that writes
+// the events.
+pub(crate) async fn event_locations_replace_many(
+    conn: &mut PgConnection,
+    events: &[(&str, &[EventLocation])],
+) -> Result<(), StorageError> {
+    let event_aturis = events
+        .iter()
+        .map(|(event_aturi, _)| event_aturi.to_string())
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        "DELETE FROM event_locations WHERE event_aturi = ANY($1)",
+        &event_aturis,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let rows = events
+        .iter()
+        .flat_map(|(event_aturi, locations)| {
+            location_keys(locations)
+                .into_iter()
+                .map(move |key| (*event_aturi, key))
+        })
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder: QueryBuilder<Postgres> =
+        QueryBuilder::new("INSERT INTO event_locations (event_aturi, country, region, locality) ");
+    query_builder.push_values(&rows, |mut row, (event_aturi, key)| {
+        row.push_bind(*event_aturi)
+            .push_bind(&key.country)
+            .push_bind(key.region.as_deref())
+            .push_bind(key.locality.as_deref());
+    });
+
+    query_builder
+        .build()
+        .execute(&mut *conn)
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(())
+}
+
+// List events starting at or after a time at a location, soonest first. The
+// region and locality narrow the results when they are given.
+pub async fn event_list_upcoming_at_location(
+    pool: &StoragePool,
+    after: DateTime<Utc>,
+    country: &str,
+    region: Option<&str>,
+    locality: Option<&str>,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    if country.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Country cannot be empty".into(),
+        )));
+    }
+
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+    WHERE
+        events.starts_at >= $1
+        AND EXISTS (
+            SELECT 1 FROM event_locations
+            WHERE event_locations.event_aturi = events.aturi
+            AND event_locations.country = $2
+            AND ($3::text IS NULL OR event_locations.region = $3)
+            AND ($4::text IS NULL OR event_locations.locality = $4)
+        )
+    ORDER BY
+        events.starts_at ASC,
+        events.aturi ASC
+    LIMIT $5
+    OFFSET $6"#,
+        after,
+        country,
+        region,
+        locality,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_roles)
+}
+
+// List the regions of a country, or the localities of a region, that have
+// events starting at or after a time, busiest first.
+pub async fn location_list_upcoming(
+    pool: &StoragePool,
+    after: DateTime<Utc>,
+    country: &str,
+    region: Option<&str>,
+) -> Result<Vec<LocationSummary>, StorageError> {
+    if country.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Country cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let locations = sqlx::query_as!(
+        LocationSummary,
+        r#"SELECT slug AS "slug!", COUNT(DISTINCT event_aturi) AS "event_count!"
+        FROM (
+            SELECT
+                event_locations.event_aturi,
+                CASE WHEN $3::text IS NULL
+                THEN event_locations.region
+                ELSE event_locations.locality END AS slug
+            FROM
+                event_locations
+                INNER JOIN events ON events.aturi = event_locations.event_aturi
+            WHERE
+                events.starts_at >= $1
+                AND event_locations.country = $2
+                AND ($3::text IS NULL OR event_locations.region = $3)
+        ) AS children
+        WHERE slug IS NOT NULL
+        GROUP BY slug
+        ORDER BY 2 DESC, slug ASC"#,
+        after,
+        country,
+        region,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(locations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atproto::lexicon::community::lexicon::calendar::event::NamedUri;
+
+    fn address(country: &str, region: Option<&str>, locality: Option<&str>) -> EventLocation {
+        EventLocation::Address(Address::Current {
+            country: country.to_string(),
+            postal_code: None,
+            region: region.map(str::to_string),
+            locality: locality.map(str::to_string),
+            street: None,
+            name: None,
+        })
+    }
+
+    #[test]
+    fn test_location_slug() {
+        assert_eq!(location_slug("CA"), "ca");
+        assert_eq!(location_slug(" British Columbia "), "british-columbia");
+        assert_eq!(
+            location_slug("Saint-Jean-sur-Richelieu"),
+            "saint-jean-sur-richelieu"
+        );
+        assert_eq!(location_slug("Montréal, QC"), "montreal-qc");
+        assert_eq!(location_slug("MONTREAL, qc"), "montreal-qc");
+        assert_eq!(location_slug("  --  "), "");
+    }
+
+    #[test]
+    fn test_location_keys() {
+        let locations = vec![
+            address("CA", Some("BC"), Some("Vancouver")),
+            address("ca", Some("bc"), Some("vancouver")),
+            address("CA", None, Some("  ")),
+            address(" ", Some("BC"), None),
+            EventLocation::Uri(NamedUri::Current {
+                uri: "https://example.com".to_string(),
+                name: None,
+            }),
+        ];
+
+        assert_eq!(
+            location_keys(&locations),
+            vec![
+                LocationKey {
+                    country: "ca".to_string(),
+                    region: Some("bc".to_string()),
+                    locality: Some("vancouver".to_string()),
+                },
+                LocationKey {
+                    country: "ca".to_string(),
+                    region: None,
+                    locality: None,
+                },
+            ]
+        );
+    }
+}
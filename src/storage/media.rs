@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+
+use crate::atproto::lexicon::com::atproto::repo::{Blob, BlobLink};
+use crate::storage::{errors::StorageError, StoragePool};
+use model::Media;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Media {
+        pub aturi: String,
+        pub cid: String,
+        pub did: String,
+        pub name: String,
+        pub blob_cid: String,
+        pub mime_type: String,
+        pub size: i64,
+        pub created_at: DateTime<Utc>,
+    }
+}
+
+impl Media {
+    /// The blob reference used to show the image on an event.
+    pub fn blob(&self) -> Blob {
+        Blob {
+            reference: BlobLink {
+                link: self.blob_cid.clone(),
+            },
+            mime_type: self.mime_type.clone(),
+            size: self.size as u64,
+        }
+    }
+}
+
+// Index an image of an organizer's media library. The record that keeps the
+// blob has already been written to their PDS.
+pub async fn media_insert(
+    pool: &StoragePool,
+    aturi: &str,
+    cid: &str,
+    did: &str,
+    name: &str,
+    blob: &Blob,
+    created_at: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Media URI cannot be empty".into(),
+        )));
+    }
+
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO media (aturi, cid, did, name, blob_cid, mime_type, size, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT(aturi) DO UPDATE SET cid = $2, name = $4, blob_cid = $5, mime_type = $6, size = $7
+        ",
+        aturi,
+        cid,
+        did,
+        name,
+        blob.cid(),
+        &blob.mime_type,
+        blob.size as i64,
+        created_at,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List the images of an organizer's media library, newest first.
+pub async fn media_list(pool: &StoragePool, did: &str) -> Result<Vec<Media>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let media = sqlx::query_as!(
+        Media,
+        "SELECT * FROM media WHERE did = $1 ORDER BY created_at DESC, aturi DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(media)
+}
+
+pub async fn media_count(pool: &StoragePool, did: &str) -> Result<i64, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM media WHERE did = $1"#,
+        did,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(total_count)
+}
+
+// Find an image of an organizer's media library by the CID of its blob.
+pub async fn media_get_by_blob(
+    pool: &StoragePool,
+    did: &str,
+    blob_cid: &str,
+) -> Result<Option<Media>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let media = sqlx::query_as!(
+        Media,
+        "SELECT * FROM media WHERE did = $1 AND blob_cid = $2 ORDER BY created_at DESC LIMIT 1",
+        did,
+        blob_cid,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(media)
+}
+
+// Remove an image from the index of an organizer's media library.
+pub async fn media_delete(pool: &StoragePool, did: &str, aturi: &str) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "DELETE FROM media WHERE did = $1 AND aturi = $2",
+        did,
+        aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use crate::atproto::lexicon::com::atproto::repo::{Blob, BlobLink};
+    use crate::storage::media::{
+        media_count, media_delete, media_get_by_blob, media_insert, media_list,
+    };
+
+    #[sqlx::test]
+    async fn test_media_library(pool: PgPool) -> anyhow::Result<()> {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let aturi = format!("at://{}/events.smokesignal.media/3lbsxygenau2c", did);
+        let blob = Blob {
+            reference: BlobLink {
+                link: "bafkreibanner".to_string(),
+            },
+            mime_type: "image/png".to_string(),
+            size: 2048,
+        };
+
+        media_insert(
+            &pool,
+            &aturi,
+            "bafyreimedia",
+            did,
+            "banner.png",
+            &blob,
+            Utc::now(),
+        )
+        .await?;
+
+        let library = media_list(&pool, did).await?;
+        assert_eq!(library.len(), 1);
+        assert_eq!(library[0].blob(), blob);
+        assert_eq!(media_count(&pool, did).await?, 1);
+        assert_eq!(media_count(&pool, "did:plc:other").await?, 0);
+
+        let found = media_get_by_blob(&pool, did, "bafkreibanner").await?;
+        assert_eq!(found.map(|media| media.aturi), Some(aturi.clone()));
+        assert!(media_get_by_blob(&pool, "did:plc:other", "bafkreibanner")
+            .await?
+            .is_none());
+
+        media_delete(&pool, did, &aturi).await?;
+        assert!(media_list(&pool, did).await?.is_empty());
+
+        Ok(())
+    }
+}
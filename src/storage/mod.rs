@@ -1,9 +1,42 @@
+pub mod announcement;
+pub mod app_password;
+pub mod audit_log;
+pub mod banner;
+pub mod bookmark;
 pub mod cache;
+pub mod calendar;
+pub mod checkin;
 pub mod denylist;
 pub mod errors;
 pub mod event;
+pub mod event_draft;
+pub mod event_filter;
+pub mod event_revision;
+pub mod event_update;
+pub mod event_view;
+pub mod export;
+pub mod follow;
 pub mod handle;
+pub mod import_job;
+pub mod import_plan;
+pub mod integrity;
+pub mod label;
+pub mod link_click;
+pub mod location;
+pub mod media;
+pub mod notification;
 pub mod oauth;
+pub mod report;
+pub mod rsvp_backfill;
+pub mod rsvp_history;
+pub mod signing_key;
+pub mod tag;
 pub mod types;
+pub mod waitlist;
+pub mod webhook;
 
 pub use types::*;
+
+/// The database migrations in `migrations/`, embedded in the binary so that
+/// the server and tools can bring the database up to date themselves.
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
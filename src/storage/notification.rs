@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::Notification;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A notification delivered to someone's inbox on this instance, already
+    /// rendered in their language.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Notification {
+        pub id: i64,
+        pub did: String,
+        pub kind: String,
+        pub url: String,
+        pub subject: String,
+        pub text: String,
+        pub html: String,
+        pub created_at: DateTime<Utc>,
+        pub read_at: Option<DateTime<Utc>>,
+    }
+}
+
+/// The most notifications inserted by a single statement, to stay well under
+/// the limit on bind parameters.
+const INSERT_BATCH_SIZE: usize = 1000;
+
+pub struct NotificationParams<'a> {
+    pub did: &'a str,
+    pub kind: &'a str,
+    pub url: &'a str,
+    pub subject: &'a str,
+    pub text: &'a str,
+    pub html: &'a str,
+}
+
+// Deliver notifications to the inboxes of their recipients
+pub async fn notifications_insert(
+    pool: &StoragePool,
+    notifications: &[NotificationParams<'_>],
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if notifications.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    for batch in notifications.chunks(INSERT_BATCH_SIZE) {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO notifications (did, kind, url, subject, text, html, created_at) ",
+        );
+        query_builder.push_values(batch, |mut row, notification| {
+            row.push_bind(notification.did)
+                .push_bind(notification.kind)
+                .push_bind(notification.url)
+                .push_bind(notification.subject)
+                .push_bind(notification.text)
+                .push_bind(notification.html)
+                .push_bind(now);
+        });
+
+        query_builder
+            .build()
+            .execute(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List the most recent notifications of an identity, newest first
+pub async fn notification_list(
+    pool: &StoragePool,
+    did: &str,
+    limit: i64,
+) -> Result<Vec<Notification>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let notifications = sqlx::query_as!(
+        Notification,
+        "SELECT * FROM notifications WHERE did = $1 ORDER BY created_at DESC, id DESC LIMIT $2",
+        did,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(notifications)
+}
+
+// Mark every unread notification of an identity as read
+pub async fn notifications_mark_read(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "UPDATE notifications SET read_at = $2 WHERE did = $1 AND read_at IS NULL",
+        did,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    fn notification(did: &str) -> NotificationParams<'_> {
+        NotificationParams {
+            did,
+            kind: "event_updated",
+            url: "https://smokesignal.example/alice.example/3lgahbs5ltk2n",
+            subject: "Update for Vancouver Rust Meetup",
+            text: "Bring photo ID.",
+            html: "<p>Bring photo ID.</p>",
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_notifications(pool: PgPool) -> anyhow::Result<()> {
+        let recipient = "did:plc:cbkjy5n7bk3ax2wplmtjofq2";
+        let other = "did:plc:kkkcb7sys7623hcf7oefcffg";
+
+        notifications_insert(
+            &pool,
+            &[notification(recipient), notification(other)],
+            Utc::now(),
+        )
+        .await?;
+
+        let notifications = notification_list(&pool, recipient, 10).await?;
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].kind, "event_updated");
+        assert!(notifications[0].read_at.is_none());
+
+        notifications_mark_read(&pool, recipient, Utc::now()).await?;
+
+        let notifications = notification_list(&pool, recipient, 10).await?;
+        assert!(notifications[0].read_at.is_some());
+
+        let notifications = notification_list(&pool, other, 10).await?;
+        assert!(notifications[0].read_at.is_none());
+
+        Ok(())
+    }
+}
@@ -2,12 +2,13 @@ use std::borrow::Cow;
 
 use chrono::{DateTime, Utc};
 use serde_json::json;
+use sqlx::types::Json;
 
 use crate::{
     jose::jwk::WrappedJsonWebKey,
     storage::{errors::StorageError, handle::model::Handle, StoragePool},
 };
-use model::{OAuthRequest, OAuthSession};
+use model::{OAuthRequest, OAuthSession, OAuthSessionSummary};
 
 pub struct OAuthRequestParams {
     pub oauth_state: Cow<'static, str>,
@@ -73,20 +74,25 @@ pub async fn oauth_request_insert(
         .map(|jwk| json!(jwk))
         .unwrap_or_else(|| json!({}));
 
-    sqlx::query("INSERT INTO oauth_requests (oauth_state, issuer, did, nonce, pkce_verifier, secret_jwk_id, dpop_jwk, destination, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)")
-        .bind(&params.oauth_state)
-        .bind(&params.issuer)
-        .bind(&params.did)
-        .bind(&params.nonce)
-        .bind(&params.pkce_verifier)
-        .bind(&params.secret_jwk_id)
-        .bind(dpop_jwk_value)
-        .bind(params.destination)
-        .bind(params.created_at)
-        .bind(params.expires_at)
-        .execute(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    sqlx::query!(
+        r"INSERT INTO oauth_requests (
+            oauth_state, issuer, did, nonce, pkce_verifier, secret_jwk_id, dpop_jwk, destination,
+            created_at, expires_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        params.oauth_state.as_ref(),
+        params.issuer.as_ref(),
+        params.did.as_ref(),
+        params.nonce.as_ref(),
+        params.pkce_verifier.as_ref(),
+        params.secret_jwk_id.as_ref(),
+        dpop_jwk_value,
+        params.destination.as_deref(),
+        params.created_at,
+        params.expires_at,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
@@ -109,15 +115,29 @@ pub async fn oauth_request_get(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    let record =
-        sqlx::query_as::<_, OAuthRequest>("SELECT * FROM oauth_requests WHERE oauth_state = $1")
-            .bind(oauth_state)
-            .fetch_one(tx.as_mut())
-            .await
-            .map_err(|err| match err {
-                sqlx::Error::RowNotFound => StorageError::OAuthRequestNotFound,
-                other => StorageError::UnableToExecuteQuery(other),
-            })?;
+    let record = sqlx::query_as!(
+        OAuthRequest,
+        r#"SELECT
+            oauth_state,
+            issuer,
+            did,
+            nonce,
+            pkce_verifier,
+            secret_jwk_id,
+            destination,
+            dpop_jwk AS "dpop_jwk: Json<WrappedJsonWebKey>",
+            created_at,
+            expires_at
+        FROM oauth_requests
+        WHERE oauth_state = $1"#,
+        oauth_state,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => StorageError::OAuthRequestNotFound,
+        other => StorageError::UnableToExecuteQuery(other),
+    })?;
 
     tx.commit()
         .await
@@ -142,11 +162,13 @@ pub async fn oauth_request_remove(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    sqlx::query("DELETE FROM oauth_requests WHERE oauth_state = $1")
-        .bind(oauth_state)
-        .execute(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    sqlx::query!(
+        "DELETE FROM oauth_requests WHERE oauth_state = $1",
+        oauth_state,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
@@ -163,6 +185,8 @@ pub struct OAuthSessionParams {
     pub dpop_jwk: WrappedJsonWebKey,
     pub created_at: DateTime<Utc>,
     pub access_token_expires_at: DateTime<Utc>,
+    /// The `User-Agent` of the browser that signed in, to tell sessions apart.
+    pub user_agent: Option<Cow<'static, str>>,
 }
 
 pub async fn oauth_session_insert(
@@ -211,19 +235,25 @@ pub async fn oauth_session_insert(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    sqlx::query("INSERT INTO oauth_sessions (session_group, access_token, did, issuer, refresh_token, secret_jwk_id, dpop_jwk, created_at, access_token_expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)")
-        .bind(&params.session_group)
-        .bind(&params.access_token)
-        .bind(&params.did)
-        .bind(&params.issuer)
-        .bind(&params.refresh_token)
-        .bind(&params.secret_jwk_id)
-        .bind(json!(params.dpop_jwk))
-        .bind(params.created_at)
-        .bind(params.access_token_expires_at)
-        .execute(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    sqlx::query!(
+        r"INSERT INTO oauth_sessions (
+            session_group, access_token, did, issuer, refresh_token, secret_jwk_id, dpop_jwk,
+            created_at, access_token_expires_at, user_agent
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        params.session_group.as_ref(),
+        params.access_token.as_ref(),
+        params.did.as_ref(),
+        params.issuer.as_ref(),
+        params.refresh_token.as_ref(),
+        params.secret_jwk_id.as_ref(),
+        json!(params.dpop_jwk),
+        params.created_at,
+        params.access_token_expires_at,
+        params.user_agent.as_deref(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
@@ -261,14 +291,18 @@ pub async fn oauth_session_update(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    sqlx::query("UPDATE oauth_sessions SET access_token = $1, refresh_token = $2, access_token_expires_at = $3 WHERE session_group = $4")
-        .bind(access_token)
-        .bind(refresh_token)
-        .bind(access_token_expires_at)
-        .bind(session_group)
-        .execute(tx.as_mut())
-        .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+    sqlx::query!(
+        r"UPDATE oauth_sessions
+        SET access_token = $1, refresh_token = $2, access_token_expires_at = $3
+        WHERE session_group = $4",
+        access_token.as_ref(),
+        refresh_token.as_ref(),
+        access_token_expires_at,
+        session_group.as_ref(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
@@ -292,15 +326,51 @@ pub async fn oauth_session_delete(
         .await
         .map_err(StorageError::CannotBeginDatabaseTransaction)?;
 
-    sqlx::query("DELETE FROM oauth_sessions WHERE session_group = $1")
-        .bind(session_group)
+    sqlx::query!(
+        "DELETE FROM oauth_sessions WHERE session_group = $1",
+        session_group,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Remove OAuth requests that were never completed and sessions that have
+// outlived the web session cookie, along with the DPoP keys stored on them.
+// Returns the number of requests removed and the session groups removed so
+// that they can also be taken out of the refresh queue.
+pub async fn oauth_cleanup_expired(
+    pool: &StoragePool,
+    now: DateTime<Utc>,
+) -> Result<(u64, Vec<String>), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let requests_removed = sqlx::query!("DELETE FROM oauth_requests WHERE expires_at < $1", now)
         .execute(tx.as_mut())
         .await
-        .map_err(StorageError::UnableToExecuteQuery)?;
+        .map_err(StorageError::UnableToExecuteQuery)?
+        .rows_affected();
+
+    let session_groups = sqlx::query_scalar!(
+        "DELETE FROM oauth_sessions WHERE not_after < $1 RETURNING session_group",
+        now,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
 
     tx.commit()
         .await
-        .map_err(StorageError::CannotCommitDatabaseTransaction)
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok((requests_removed, session_groups))
 }
 
 /// Look up a web session by session group and optionally filter by DID.
@@ -332,19 +402,47 @@ pub async fn web_session_lookup(
 
     let oauth_session = match did {
         Some(did_value) => {
-            sqlx::query_as::<_, OAuthSession>(
-                "SELECT * FROM oauth_sessions WHERE session_group = $1 AND did = $2 ORDER BY created_at DESC LIMIT 1",
+            sqlx::query_as!(
+                OAuthSession,
+                r#"SELECT
+            session_group,
+            access_token,
+            did,
+            issuer,
+            refresh_token,
+            secret_jwk_id,
+            dpop_jwk AS "dpop_jwk: Json<WrappedJsonWebKey>",
+            created_at,
+            access_token_expires_at
+        FROM oauth_sessions
+        WHERE session_group = $1 AND did = $2
+        ORDER BY created_at DESC
+        LIMIT 1"#,
+                session_group,
+                did_value,
             )
-            .bind(session_group)
-            .bind(did_value)
             .fetch_one(tx.as_mut())
             .await
-        },
+        }
         None => {
-            sqlx::query_as::<_, OAuthSession>(
-                "SELECT * FROM oauth_sessions WHERE session_group = $1 ORDER BY created_at DESC LIMIT 1",
+            sqlx::query_as!(
+                OAuthSession,
+                r#"SELECT
+            session_group,
+            access_token,
+            did,
+            issuer,
+            refresh_token,
+            secret_jwk_id,
+            dpop_jwk AS "dpop_jwk: Json<WrappedJsonWebKey>",
+            created_at,
+            access_token_expires_at
+        FROM oauth_sessions
+        WHERE session_group = $1
+        ORDER BY created_at DESC
+        LIMIT 1"#,
+                session_group,
             )
-            .bind(session_group)
             .fetch_one(tx.as_mut())
             .await
         }
@@ -356,14 +454,17 @@ pub async fn web_session_lookup(
 
     let did_for_handle = did.unwrap_or(&oauth_session.did);
 
-    let handle = sqlx::query_as::<_, Handle>("SELECT * FROM handles WHERE did = $1")
-        .bind(did_for_handle)
-        .fetch_one(tx.as_mut())
-        .await
-        .map_err(|err| match err {
-            sqlx::Error::RowNotFound => StorageError::HandleNotFound,
-            other => StorageError::UnableToExecuteQuery(other),
-        })?;
+    let handle = sqlx::query_as!(
+        Handle,
+        "SELECT * FROM handles WHERE did = $1",
+        did_for_handle,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => StorageError::HandleNotFound,
+        other => StorageError::UnableToExecuteQuery(other),
+    })?;
 
     tx.commit()
         .await
@@ -372,11 +473,230 @@ pub async fn web_session_lookup(
     Ok((handle, oauth_session))
 }
 
+/// Look up the most recently created OAuth session of a DID whose access
+/// token is still valid, for writing to the PDS on behalf of a caller that
+/// isn't using a web session.
+pub async fn oauth_session_for_did(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<(Handle, OAuthSession)>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let oauth_session = sqlx::query_as!(
+        OAuthSession,
+        r#"SELECT
+            session_group,
+            access_token,
+            did,
+            issuer,
+            refresh_token,
+            secret_jwk_id,
+            dpop_jwk AS "dpop_jwk: Json<WrappedJsonWebKey>",
+            created_at,
+            access_token_expires_at
+        FROM oauth_sessions
+        WHERE did = $1 AND access_token_expires_at > $2
+        ORDER BY created_at DESC
+        LIMIT 1"#,
+        did,
+        now,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let Some(oauth_session) = oauth_session else {
+        return Ok(None);
+    };
+
+    let handle = sqlx::query_as!(Handle, "SELECT * FROM handles WHERE did = $1", did)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(handle.map(|handle| (handle, oauth_session)))
+}
+
+/// List the OAuth sessions of a DID, newest first, without their tokens.
+pub async fn oauth_session_list_for_did(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Vec<OAuthSessionSummary>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let sessions = sqlx::query_as!(
+        OAuthSessionSummary,
+        "SELECT session_group, did, issuer, created_at, access_token_expires_at, not_after, user_agent FROM oauth_sessions WHERE did = $1 ORDER BY created_at DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(sessions)
+}
+
+/// List the OAuth sessions of a DID that haven't ended, newest first, without
+/// their tokens.
+pub async fn oauth_sessions_for_did(
+    pool: &StoragePool,
+    did: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<OAuthSessionSummary>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let sessions = sqlx::query_as!(
+        OAuthSessionSummary,
+        "SELECT session_group, did, issuer, created_at, access_token_expires_at, not_after, user_agent FROM oauth_sessions WHERE did = $1 AND not_after > $2 ORDER BY created_at DESC",
+        did,
+        now,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(sessions)
+}
+
+/// Look up an OAuth session by its session group, without its tokens.
+pub async fn oauth_session_summary(
+    pool: &StoragePool,
+    session_group: &str,
+) -> Result<Option<OAuthSessionSummary>, StorageError> {
+    // Validate session_group is not empty
+    if session_group.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Session group cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let session = sqlx::query_as!(
+        OAuthSessionSummary,
+        "SELECT session_group, did, issuer, created_at, access_token_expires_at, not_after, user_agent FROM oauth_sessions WHERE session_group = $1",
+        session_group,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(session)
+}
+
+/// Delete every OAuth session of a DID. Returns the session groups removed so
+/// that they can also be taken out of the refresh queue.
+pub async fn oauth_session_delete_for_did(
+    pool: &StoragePool,
+    did: &str,
+) -> Result<Vec<String>, StorageError> {
+    // Validate did is not empty
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let session_groups = sqlx::query_scalar!(
+        "DELETE FROM oauth_sessions WHERE did = $1 RETURNING session_group",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(session_groups)
+}
+
+/// List when the access token and the session of every OAuth session expire.
+pub async fn oauth_session_expiry_times(
+    pool: &StoragePool,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let expiry_times =
+        sqlx::query!("SELECT access_token_expires_at, not_after FROM oauth_sessions")
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(StorageError::UnableToExecuteQuery)?
+            .into_iter()
+            .map(|row| (row.access_token_expires_at, row.not_after))
+            .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(expiry_times)
+}
+
 pub mod model {
     use anyhow::Error;
     use chrono::{DateTime, Utc};
     use p256::SecretKey;
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
     use sqlx::FromRow;
 
     use crate::{
@@ -417,6 +737,19 @@ pub mod model {
         pub access_token_expires_at: DateTime<Utc>,
     }
 
+    /// An OAuth session without its tokens and keys, for inspecting sessions
+    /// from the command line and listing them in settings.
+    #[derive(Clone, FromRow, Serialize, Debug)]
+    pub struct OAuthSessionSummary {
+        pub session_group: String,
+        pub did: String,
+        pub issuer: String,
+        pub created_at: DateTime<Utc>,
+        pub access_token_expires_at: DateTime<Utc>,
+        pub not_after: DateTime<Utc>,
+        pub user_agent: Option<String>,
+    }
+
     impl TryFrom<OAuthSession> for SimpleOAuthSessionProvider {
         type Error = Error;
 
@@ -440,7 +773,9 @@ pub mod test {
     use crate::{
         jose,
         storage::oauth::{
-            oauth_request_get, oauth_request_insert, oauth_request_remove, oauth_session_insert,
+            oauth_request_get, oauth_request_insert, oauth_request_remove,
+            oauth_session_delete_for_did, oauth_session_expiry_times, oauth_session_insert,
+            oauth_session_list_for_did, oauth_session_summary, oauth_sessions_for_did,
             web_session_lookup, OAuthRequestParams, OAuthSessionParams,
         },
     };
@@ -449,7 +784,7 @@ pub mod test {
     async fn test_oauth_request(pool: PgPool) -> anyhow::Result<()> {
         let dpop_jwk = jose::jwk::generate();
         let created_at = chrono::Utc::now();
-        let expires_at = created_at + chrono::Duration::seconds(60 as i64);
+        let expires_at = created_at + chrono::Duration::seconds(60);
 
         let res = oauth_request_insert(
             &pool,
@@ -468,17 +803,17 @@ pub mod test {
         )
         .await;
 
-        assert!(!res.is_err());
+        assert!(res.is_ok());
 
         let oauth_request = oauth_request_get(&pool, "oauth_state").await;
-        assert!(!oauth_request.is_err());
+        assert!(oauth_request.is_ok());
         let oauth_request = oauth_request.unwrap();
 
         assert_eq!(oauth_request.did, "did:plc:d5c1ed6d01421a67b96f68fa");
         assert_eq!(oauth_request.dpop_jwk.as_ref(), &dpop_jwk);
 
         let res = oauth_request_remove(&pool, "oauth_state").await;
-        assert!(!res.is_err());
+        assert!(res.is_ok());
 
         {
             let oauth_request = oauth_request_get(&pool, "oauth_state").await;
@@ -506,12 +841,13 @@ pub mod test {
                 secret_jwk_id: "secret_jwk_id".to_string().into(),
                 dpop_jwk: dpop_jwk.clone(),
                 created_at: now,
-                access_token_expires_at: now + chrono::Duration::seconds(60 as i64),
+                access_token_expires_at: now + chrono::Duration::seconds(60),
+                user_agent: None,
             },
         )
         .await;
 
-        assert!(!insert_session_res.is_err());
+        assert!(insert_session_res.is_ok());
 
         let web_session = web_session_lookup(
             &pool,
@@ -519,7 +855,66 @@ pub mod test {
             Some("did:plc:d5c1ed6d01421a67b96f68fa"),
         )
         .await;
-        assert!(!web_session.is_err());
+        assert!(web_session.is_ok());
+
+        Ok(())
+    }
+    #[sqlx::test(fixtures(path = "../../fixtures/storage", scripts("handles")))]
+    async fn test_oauth_session_inspection(pool: PgPool) -> anyhow::Result<()> {
+        let did = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = chrono::Utc::now();
+
+        let mut session_groups = vec![];
+        for _ in 0..2 {
+            let session_group = ulid::Ulid::new().to_string();
+            oauth_session_insert(
+                &pool,
+                OAuthSessionParams {
+                    session_group: session_group.clone().into(),
+                    access_token: "access_token".to_string().into(),
+                    did: did.to_string().into(),
+                    issuer: "pds.examplepds.com".to_string().into(),
+                    refresh_token: "refresh_token".to_string().into(),
+                    secret_jwk_id: "secret_jwk_id".to_string().into(),
+                    dpop_jwk: jose::jwk::generate(),
+                    created_at: now,
+                    access_token_expires_at: now + chrono::Duration::seconds(60),
+                    user_agent: Some("Mozilla/5.0".into()),
+                },
+            )
+            .await?;
+            session_groups.push(session_group);
+        }
+
+        let sessions = oauth_session_list_for_did(&pool, did).await?;
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|session| session.did == did));
+
+        let sessions = oauth_sessions_for_did(&pool, did, now).await?;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].user_agent.as_deref(), Some("Mozilla/5.0"));
+        assert!(
+            oauth_sessions_for_did(&pool, did, now + chrono::Duration::days(2))
+                .await?
+                .is_empty()
+        );
+
+        let session = oauth_session_summary(&pool, &session_groups[0]).await?;
+        assert_eq!(
+            session.map(|session| session.session_group),
+            Some(session_groups[0].clone())
+        );
+
+        assert_eq!(oauth_session_expiry_times(&pool).await?.len(), 2);
+
+        let mut removed = oauth_session_delete_for_did(&pool, did).await?;
+        removed.sort();
+        session_groups.sort();
+        assert_eq!(removed, session_groups);
+        assert!(oauth_session_list_for_did(&pool, did).await?.is_empty());
+        assert!(oauth_session_summary(&pool, &session_groups[0])
+            .await?
+            .is_none());
 
         Ok(())
     }
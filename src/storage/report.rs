@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::Report;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// A report of an event by a signed in user, waiting for an admin until
+    /// `resolved_at` is set.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Report {
+        pub id: String,
+        pub reporter_did: String,
+        pub event_aturi: String,
+        pub reason: String,
+        pub created_at: DateTime<Utc>,
+        pub resolved_at: Option<DateTime<Utc>>,
+        pub resolved_by: Option<String>,
+        pub resolution: Option<String>,
+
+        /// The name of the reported event, when it is still indexed.
+        #[sqlx(default)]
+        pub event_name: Option<String>,
+    }
+}
+
+// Report an event. A reporter has at most one open report per event, and
+// reporting it again replaces the reason but keeps its place in the queue.
+pub async fn report_insert(
+    pool: &StoragePool,
+    reporter_did: &str,
+    event_aturi: &str,
+    reason: &str,
+) -> Result<(), StorageError> {
+    if reporter_did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Reporter DID cannot be empty".into(),
+        )));
+    }
+
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if reason.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Reason cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO reports (id, reporter_did, event_aturi, reason, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (reporter_did, event_aturi) WHERE resolved_at IS NULL
+        DO UPDATE SET reason = EXCLUDED.reason
+        ",
+        ulid::Ulid::new().to_string(),
+        reporter_did,
+        event_aturi,
+        reason,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Get a report by id
+pub async fn report_get(pool: &StoragePool, id: &str) -> Result<Option<Report>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let report = sqlx::query_as!(
+        Report,
+        r#"
+        SELECT reports.*, events.name AS "event_name?"
+        FROM reports
+        LEFT JOIN events ON events.aturi = reports.event_aturi
+        WHERE reports.id = $1
+        "#,
+        id,
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(report)
+}
+
+// List the open reports with pagination, oldest first
+pub async fn report_list_open(
+    pool: &StoragePool,
+    page: i64,
+    page_size: i64,
+) -> Result<(i64, Vec<Report>), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM reports WHERE resolved_at IS NULL"#,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let offset = (page - 1) * page_size;
+
+    let reports = sqlx::query_as!(
+        Report,
+        r#"
+        SELECT reports.*, events.name AS "event_name?"
+        FROM reports
+        LEFT JOIN events ON events.aturi = reports.event_aturi
+        WHERE reports.resolved_at IS NULL
+        ORDER BY reports.created_at ASC, reports.id ASC
+        LIMIT $1 OFFSET $2
+        "#,
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok((count, reports))
+}
+
+// Resolve every open report of an event, returning how many were resolved
+pub async fn report_resolve_event(
+    pool: &StoragePool,
+    event_aturi: &str,
+    resolved_by: &str,
+    resolution: &str,
+    now: DateTime<Utc>,
+) -> Result<u64, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        "UPDATE reports SET resolved_at = $1, resolved_by = $2, resolution = $3 WHERE event_aturi = $4 AND resolved_at IS NULL",
+        now,
+        resolved_by,
+        resolution,
+        event_aturi,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use crate::storage::report::{
+        report_get, report_insert, report_list_open, report_resolve_event,
+    };
+
+    #[sqlx::test]
+    async fn test_report_queue(pool: PgPool) -> anyhow::Result<()> {
+        let event_aturi =
+            "at://did:plc:d5c1ed6d01421a67b96f68fa/community.lexicon.calendar.event/3lte3c7x43l2e";
+        let other_aturi =
+            "at://did:plc:d5c1ed6d01421a67b96f68fa/community.lexicon.calendar.event/3lte3c7x43l2f";
+        let admin = "did:plc:tgudj2fjm77pzkuawquqhsxm";
+
+        report_insert(&pool, "did:plc:reporter1", event_aturi, "Spam").await?;
+        report_insert(&pool, "did:plc:reporter1", event_aturi, "Scam tickets").await?;
+        report_insert(&pool, "did:plc:reporter2", event_aturi, "Spam").await?;
+        report_insert(&pool, "did:plc:reporter1", other_aturi, "Wrong venue").await?;
+
+        assert!(report_insert(&pool, "did:plc:reporter1", event_aturi, " ")
+            .await
+            .is_err());
+
+        let (count, reports) = report_list_open(&pool, 1, 10).await?;
+        assert_eq!(count, 3);
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].reason, "Scam tickets");
+        assert!(reports[0].event_name.is_none());
+
+        assert_eq!(
+            report_resolve_event(&pool, event_aturi, admin, "dismissed", Utc::now()).await?,
+            2
+        );
+
+        let resolved = report_get(&pool, &reports[0].id).await?.expect("report");
+        assert_eq!(resolved.resolved_by.as_deref(), Some(admin));
+        assert_eq!(resolved.resolution.as_deref(), Some("dismissed"));
+
+        let (count, _) = report_list_open(&pool, 1, 10).await?;
+        assert_eq!(count, 1);
+
+        // A resolved report doesn't stop the event being reported again
+        report_insert(&pool, "did:plc:reporter1", event_aturi, "Still spam").await?;
+        let (count, _) = report_list_open(&pool, 1, 10).await?;
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
+}
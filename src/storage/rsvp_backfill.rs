@@ -0,0 +1,332 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{RsvpBackfill, RsvpBackfillCandidate};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct RsvpBackfill {
+        pub event_aturi: String,
+        pub post_aturi: String,
+        pub like_count: i32,
+        pub rsvp_count: i32,
+        pub source: String,
+        pub cursor: Option<String>,
+        pub repos_checked: i32,
+        pub rsvps_found: i32,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+        pub completed_at: Option<DateTime<Utc>>,
+    }
+
+    /// An announced event that could be backfilled, with the number of RSVPs
+    /// stored for it.
+    #[derive(Clone, FromRow, Debug)]
+    pub struct RsvpBackfillCandidate {
+        pub post_aturi: String,
+        pub event_aturi: String,
+        pub rsvp_count: i64,
+    }
+}
+
+// List announcement posts of events that start after `starts_after`, or
+// have no start, and that aren't being backfilled. Events whose backfill
+// completed before `completed_before` are listed again.
+pub async fn rsvp_backfill_candidates(
+    pool: &StoragePool,
+    starts_after: DateTime<Utc>,
+    completed_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<RsvpBackfillCandidate>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let candidates = sqlx::query_as!(
+        RsvpBackfillCandidate,
+        r#"
+        SELECT a.post_aturi, a.event_aturi,
+            (SELECT COUNT(*) FROM rsvps r WHERE r.event_aturi = a.event_aturi) AS "rsvp_count!"
+        FROM event_announcements a
+        JOIN events e ON e.aturi = a.event_aturi
+        LEFT JOIN rsvp_backfills b ON b.event_aturi = a.event_aturi
+        WHERE (e.starts_at IS NULL OR e.starts_at > $1)
+            AND (b.event_aturi IS NULL OR b.completed_at < $2)
+        ORDER BY a.created_at DESC
+        LIMIT $3
+        "#,
+        starts_after,
+        completed_before,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(candidates)
+}
+
+// Schedule a backfill of an event's RSVPs, starting over when the event was
+// backfilled before.
+pub async fn rsvp_backfill_schedule(
+    pool: &StoragePool,
+    event_aturi: &str,
+    post_aturi: &str,
+    like_count: i32,
+    rsvp_count: i32,
+    source: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO rsvp_backfills (event_aturi, post_aturi, like_count, rsvp_count, source, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        ON CONFLICT (event_aturi) DO UPDATE SET
+            post_aturi = $2, like_count = $3, rsvp_count = $4, source = $5, cursor = NULL,
+            repos_checked = 0, rsvps_found = 0, created_at = $6, updated_at = $6, completed_at = NULL
+        ",
+        event_aturi,
+        post_aturi,
+        like_count,
+        rsvp_count,
+        source,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List backfills that haven't completed, least recently advanced first, so
+// that every backfill makes progress.
+pub async fn rsvp_backfill_list_pending(
+    pool: &StoragePool,
+    limit: i64,
+) -> Result<Vec<RsvpBackfill>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let backfills = sqlx::query_as!(
+        RsvpBackfill,
+        "SELECT * FROM rsvp_backfills WHERE completed_at IS NULL ORDER BY updated_at ASC LIMIT $1",
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(backfills)
+}
+
+// Record the position a backfill has reached and add to its counts. A
+// completed backfill isn't listed as pending anymore.
+#[allow(clippy::too_many_arguments)]
+pub async fn rsvp_backfill_advance(
+    pool: &StoragePool,
+    event_aturi: &str,
+    source: &str,
+    cursor: Option<&str>,
+    repos_checked: i32,
+    rsvps_found: i32,
+    completed: bool,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        UPDATE rsvp_backfills SET
+            source = $2, cursor = $3, repos_checked = repos_checked + $4,
+            rsvps_found = rsvps_found + $5, updated_at = $6,
+            completed_at = CASE WHEN $7 THEN $6::timestamptz ELSE NULL END
+        WHERE event_aturi = $1
+        ",
+        event_aturi,
+        source,
+        cursor,
+        repos_checked,
+        rsvps_found,
+        now,
+        completed,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List DIDs that RSVPed to other events of the same organizer but have no
+// RSVP stored for this event, in DID order after `after_did`.
+pub async fn rsvp_backfill_known_attendees(
+    pool: &StoragePool,
+    event_aturi: &str,
+    after_did: Option<&str>,
+    limit: i64,
+) -> Result<Vec<String>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let dids = sqlx::query_scalar!(
+        r"
+        SELECT DISTINCT r.did FROM rsvps r
+        JOIN events e ON e.aturi = r.event_aturi
+        WHERE e.did = (SELECT did FROM events WHERE aturi = $1)
+            AND r.event_aturi != $1
+            AND r.did > $2
+            AND NOT EXISTS (SELECT 1 FROM rsvps o WHERE o.event_aturi = $1 AND o.did = r.did)
+        ORDER BY r.did
+        LIMIT $3
+        ",
+        event_aturi,
+        after_did.unwrap_or_default(),
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(dids)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use crate::storage::announcement::announcement_insert;
+    use crate::storage::rsvp_backfill::{
+        rsvp_backfill_advance, rsvp_backfill_candidates, rsvp_backfill_known_attendees,
+        rsvp_backfill_list_pending, rsvp_backfill_schedule,
+    };
+    use crate::test_support::{EventBuilder, RsvpBuilder};
+
+    #[sqlx::test]
+    async fn test_rsvp_backfill(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let now = Utc::now();
+
+        let past = EventBuilder::default()
+            .starts_at(now - chrono::Duration::days(30))
+            .insert(&pool, organizer, "3lbsxygenau2a")
+            .await?;
+        let upcoming = EventBuilder::default()
+            .starts_at(now + chrono::Duration::days(7))
+            .insert(&pool, organizer, "3lbsxygenau2b")
+            .await?;
+
+        for (did, rkey) in [
+            ("did:plc:c71dca8dfb0f126321f82435", "3lbsy2aeu7w2a"),
+            ("did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2b"),
+        ] {
+            RsvpBuilder::new(past.clone())
+                .insert(&pool, did, rkey)
+                .await?;
+        }
+        RsvpBuilder::new(upcoming.clone())
+            .insert(&pool, "did:plc:c71dca8dfb0f126321f82435", "3lbsy2aeu7w2c")
+            .await?;
+
+        // Only attendees of other events without an RSVP to this one
+        let attendees = rsvp_backfill_known_attendees(&pool, &upcoming.uri, None, 10).await?;
+        assert_eq!(attendees, vec!["did:plc:b10c457b287b3f06fd768504"]);
+        let attendees = rsvp_backfill_known_attendees(
+            &pool,
+            &upcoming.uri,
+            Some("did:plc:b10c457b287b3f06fd768504"),
+            10,
+        )
+        .await?;
+        assert!(attendees.is_empty());
+
+        let post_aturi = format!("at://{}/app.bsky.feed.post/3lbsz", organizer);
+        announcement_insert(&pool, &post_aturi, &upcoming.uri, organizer).await?;
+        announcement_insert(
+            &pool,
+            &format!("at://{}/app.bsky.feed.post/3lbsy", organizer),
+            &past.uri,
+            organizer,
+        )
+        .await?;
+
+        let candidates = rsvp_backfill_candidates(&pool, now, now, 10).await?;
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].event_aturi, upcoming.uri);
+        assert_eq!(candidates[0].rsvp_count, 1);
+
+        rsvp_backfill_schedule(&pool, &upcoming.uri, &post_aturi, 40, 1, "attendees", now).await?;
+        assert!(rsvp_backfill_candidates(&pool, now, now, 10)
+            .await?
+            .is_empty());
+
+        rsvp_backfill_advance(
+            &pool,
+            &upcoming.uri,
+            "likes",
+            Some("cursor"),
+            1,
+            0,
+            false,
+            now,
+        )
+        .await?;
+        let pending = rsvp_backfill_list_pending(&pool, 10).await?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].source, "likes");
+        assert_eq!(pending[0].cursor.as_deref(), Some("cursor"));
+        assert_eq!(pending[0].repos_checked, 1);
+
+        rsvp_backfill_advance(&pool, &upcoming.uri, "likes", None, 5, 2, true, now).await?;
+        assert!(rsvp_backfill_list_pending(&pool, 10).await?.is_empty());
+
+        // Completed backfills are candidates again once they are old enough
+        let later = now + chrono::Duration::days(1);
+        assert_eq!(
+            rsvp_backfill_candidates(&pool, now, later, 10).await?.len(),
+            1
+        );
+
+        Ok(())
+    }
+}
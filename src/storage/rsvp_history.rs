@@ -0,0 +1,166 @@
+use crate::storage::{errors::StorageError, StoragePool};
+use model::{RsvpHistory, RsvpStatusTotal};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// An RSVP being created or changing status. Entries are only added, so
+    /// they show how attendance changed over time.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct RsvpHistory {
+        pub id: i64,
+        pub rsvp_aturi: String,
+        pub event_aturi: String,
+        pub did: String,
+
+        /// Unset when the RSVP was created.
+        pub previous_status: Option<String>,
+        pub status: String,
+
+        pub created_at: DateTime<Utc>,
+    }
+
+    /// How many RSVPs of an event had a status at the end of a day (UTC).
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct RsvpStatusTotal {
+        pub day: DateTime<Utc>,
+        pub status: String,
+        pub count: i64,
+    }
+}
+
+// List the most recent RSVP changes of an event, newest first
+#[tracing::instrument(skip(pool))]
+pub async fn rsvp_history_list(
+    pool: &StoragePool,
+    event_aturi: &str,
+    limit: i64,
+) -> Result<Vec<RsvpHistory>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let entries = sqlx::query_as!(
+        RsvpHistory,
+        "SELECT * FROM rsvp_history WHERE event_aturi = $1 ORDER BY created_at DESC, id DESC LIMIT $2",
+        event_aturi,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(entries)
+}
+
+// Total the RSVPs of an event by status at the end of each day with changes.
+// A change counts towards its new status and away from its previous one.
+#[tracing::instrument(skip(pool))]
+pub async fn rsvp_history_daily_totals(
+    pool: &StoragePool,
+    event_aturi: &str,
+) -> Result<Vec<RsvpStatusTotal>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let totals = sqlx::query_as!(
+        RsvpStatusTotal,
+        r#"WITH changes AS (
+            SELECT date_trunc('day', created_at, 'UTC') AS day, status, 1 AS delta
+            FROM rsvp_history WHERE event_aturi = $1
+            UNION ALL
+            SELECT date_trunc('day', created_at, 'UTC') AS day, previous_status AS status, -1 AS delta
+            FROM rsvp_history WHERE event_aturi = $1 AND previous_status IS NOT NULL
+        ), daily AS (
+            SELECT day, status, SUM(delta) AS delta FROM changes GROUP BY day, status
+        )
+        SELECT
+            day AS "day!",
+            status AS "status!",
+            (SUM(delta) OVER (PARTITION BY status ORDER BY day))::BIGINT AS "count!"
+        FROM daily ORDER BY day ASC, status ASC"#,
+        event_aturi,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(totals)
+}
+
+#[cfg(test)]
+pub mod test {
+    use sqlx::PgPool;
+
+    use crate::atproto::lexicon::community::lexicon::calendar::rsvp::RsvpStatus;
+    use crate::storage::rsvp_history::{rsvp_history_daily_totals, rsvp_history_list};
+    use crate::test_support::{insert_handle, EventBuilder, RsvpBuilder};
+
+    #[sqlx::test]
+    async fn test_rsvp_history(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        insert_handle(&pool, organizer, "whole-crane.examplepds.com").await?;
+
+        let event = EventBuilder::default()
+            .starts_at("2025-06-02T01:00:00Z".parse()?)
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        let attendee = "did:plc:c71dca8dfb0f126321f82435";
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Interested)
+            .insert(&pool, attendee, "3lbsy2aeu7w2c")
+            .await?;
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Going)
+            .insert(&pool, attendee, "3lbsy2aeu7w2c")
+            .await?;
+
+        // Re-indexing without a status change isn't recorded
+        RsvpBuilder::new(event.clone())
+            .status(RsvpStatus::Going)
+            .insert(&pool, attendee, "3lbsy2aeu7w2c")
+            .await?;
+
+        RsvpBuilder::new(event.clone())
+            .insert(&pool, "did:plc:b10c457b287b3f06fd768504", "3lbsy2aeu7w2d")
+            .await?;
+
+        let entries = rsvp_history_list(&pool, &event.uri, 10).await?;
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.previous_status.as_deref(), entry.status.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, "going"),
+                (Some("interested"), "going"),
+                (None, "interested"),
+            ]
+        );
+
+        let totals = rsvp_history_daily_totals(&pool, &event.uri).await?;
+        assert_eq!(
+            totals
+                .iter()
+                .map(|total| (total.status.as_str(), total.count))
+                .collect::<Vec<_>>(),
+            vec![("going", 2), ("interested", 0)]
+        );
+
+        Ok(())
+    }
+}
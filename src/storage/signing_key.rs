@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+
+use crate::storage::{errors::StorageError, StoragePool};
+use model::OAuthSigningKey;
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::FromRow;
+
+    /// When a key of the signing keys file started and stopped being used for
+    /// new OAuth logins. A retiring key can be removed from the file once
+    /// `retire_after` has passed and nothing started with it is left.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct OAuthSigningKey {
+        pub kid: String,
+        pub created_at: DateTime<Utc>,
+        pub retiring_at: Option<DateTime<Utc>>,
+        pub retire_after: Option<DateTime<Utc>>,
+    }
+}
+
+// Record a key that new OAuth logins are signed with
+pub async fn oauth_signing_key_insert(
+    pool: &StoragePool,
+    kid: &str,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    if kid.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Key ID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "INSERT INTO oauth_signing_keys (kid, created_at) VALUES ($1, $2) ON CONFLICT (kid) DO NOTHING",
+        kid,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Stop a key being used for new OAuth logins and schedule its retirement for
+// when the last session and login request started with it ends. Returns when
+// the key can be retired.
+pub async fn oauth_signing_key_retire(
+    pool: &StoragePool,
+    kid: &str,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, StorageError> {
+    if kid.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Key ID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let retire_after = sqlx::query_scalar!(
+        r#"
+        SELECT GREATEST(
+            $2,
+            (SELECT MAX(not_after) FROM oauth_sessions WHERE secret_jwk_id = $1),
+            (SELECT MAX(expires_at) FROM oauth_requests WHERE secret_jwk_id = $1)
+        ) AS "retire_after!"
+        "#,
+        kid,
+        now,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO oauth_signing_keys (kid, created_at, retiring_at, retire_after)
+        VALUES ($1, $2, $2, $3)
+        ON CONFLICT (kid) DO UPDATE
+        SET retiring_at = COALESCE(oauth_signing_keys.retiring_at, EXCLUDED.retiring_at),
+            retire_after = EXCLUDED.retire_after
+        ",
+        kid,
+        now,
+        retire_after,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(retire_after)
+}
+
+// List the recorded keys, oldest first
+pub async fn oauth_signing_key_list(
+    pool: &StoragePool,
+) -> Result<Vec<OAuthSigningKey>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let keys = sqlx::query_as!(
+        OAuthSigningKey,
+        "SELECT * FROM oauth_signing_keys ORDER BY created_at ASC, kid ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(keys)
+}
+
+// Count the sessions and login requests started with a key that haven't
+// ended yet
+pub async fn oauth_signing_key_usage(
+    pool: &StoragePool,
+    kid: &str,
+    now: DateTime<Utc>,
+) -> Result<i64, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM oauth_sessions WHERE secret_jwk_id = $1 AND not_after > $2)
+            + (SELECT COUNT(*) FROM oauth_requests WHERE secret_jwk_id = $1 AND expires_at > $2)
+            AS "count!"
+        "#,
+        kid,
+        now,
+    )
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::{
+        jose,
+        storage::{
+            oauth::{oauth_session_insert, OAuthSessionParams},
+            signing_key::{
+                oauth_signing_key_insert, oauth_signing_key_list, oauth_signing_key_retire,
+                oauth_signing_key_usage,
+            },
+        },
+    };
+
+    #[sqlx::test]
+    async fn test_oauth_signing_key_rotation(pool: PgPool) -> anyhow::Result<()> {
+        let old_kid = "01J7PM272ZF0DYZAPR3499VBTM";
+        let new_kid = "01JF2QS2S86SG2R23HTZ0JKB76";
+        let now = Utc::now();
+
+        oauth_session_insert(
+            &pool,
+            OAuthSessionParams {
+                session_group: ulid::Ulid::new().to_string().into(),
+                access_token: "access_token".to_string().into(),
+                did: "did:plc:d5c1ed6d01421a67b96f68fa".to_string().into(),
+                issuer: "pds.examplepds.com".to_string().into(),
+                refresh_token: "refresh_token".to_string().into(),
+                secret_jwk_id: old_kid.to_string().into(),
+                dpop_jwk: jose::jwk::generate(),
+                created_at: now,
+                access_token_expires_at: now + Duration::seconds(60),
+                user_agent: None,
+            },
+        )
+        .await?;
+
+        assert_eq!(oauth_signing_key_usage(&pool, old_kid, now).await?, 1);
+        assert_eq!(oauth_signing_key_usage(&pool, new_kid, now).await?, 0);
+
+        oauth_signing_key_insert(&pool, new_kid, now).await?;
+
+        // The old key is kept until the session started with it ends
+        let retire_after = oauth_signing_key_retire(&pool, old_kid, now).await?;
+        assert!(retire_after > now + Duration::hours(23));
+
+        let keys = oauth_signing_key_list(&pool).await?;
+        assert_eq!(keys.len(), 2);
+        let old_key = keys.iter().find(|key| key.kid == old_kid).expect("old key");
+        assert_eq!(old_key.retire_after, Some(retire_after));
+        let new_key = keys.iter().find(|key| key.kid == new_kid).expect("new key");
+        assert!(new_key.retiring_at.is_none());
+
+        // A key without sessions can be retired right away
+        let retire_after = oauth_signing_key_retire(&pool, new_kid, now).await?;
+        assert!(retire_after < now + Duration::seconds(1));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use chrono::Utc;
+use sqlx::{types::Json, PgConnection, Postgres, QueryBuilder};
+
+use crate::storage::{
+    errors::StorageError,
+    event::model::{EventWithRole, EventWithRoleRow},
+    location::location_slug,
+    StoragePool,
+};
+
+/// The most tags an event can have.
+pub const MAX_EVENT_TAGS: usize = 8;
+
+/// The longest a tag can be, in bytes.
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// Normalizes a tag into a URL safe slug the same way as locations, for
+/// example "#Rust Lang" becomes "rust-lang" and "Café" becomes "cafe".
+pub fn tag_slug(value: &str) -> String {
+    location_slug(value)
+}
+
+/// Returns the normalized tags of an event, in order and without
+/// duplicates. Tags that are empty or too long once normalized are skipped,
+/// and only the first `MAX_EVENT_TAGS` are kept.
+pub fn event_tag_keys<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for tag in tags {
+        let key = tag_slug(tag.as_ref());
+        if key.is_empty() || key.len() > MAX_TAG_LENGTH || keys.contains(&key) {
+            continue;
+        }
+        keys.push(key);
+        if keys.len() == MAX_EVENT_TAGS {
+            break;
+        }
+    }
+    keys
+}
+
+// Replace the tags stored for an event. This is run inside of the
+// transaction that writes the event so the two never disagree.
+pub(crate) async fn event_tags_replace(
+    conn: &mut PgConnection,
+    event_aturi: &str,
+    tags: &[String],
+) -> Result<(), StorageError> {
+    event_tags_replace_many(conn, &[(event_aturi, tags)]).await
+}
+
+// Replace the tags stored for many events at once, with one statement for all
+// of them. Like `event_tags_replace`, this is run inside of the transaction
+// that writes the events.
+pub(crate) async fn event_tags_replace_many(
+    conn: &mut PgConnection,
+    events: &[(&str, &[String])],
+) -> Result<(), StorageError> {
+    let event_aturis = events
+        .iter()
+        .map(|(event_aturi, _)| event_aturi.to_string())
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        "DELETE FROM event_tags WHERE event_aturi = ANY($1)",
+        &event_aturis,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    let rows = events
+        .iter()
+        .flat_map(|(event_aturi, tags)| {
+            event_tag_keys(tags)
+                .into_iter()
+                .map(move |tag| (*event_aturi, tag))
+        })
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder: QueryBuilder<Postgres> =
+        QueryBuilder::new("INSERT INTO event_tags (event_aturi, tag) ");
+    query_builder.push_values(&rows, |mut row, (event_aturi, tag)| {
+        row.push_bind(*event_aturi).push_bind(tag);
+    });
+
+    query_builder
+        .build()
+        .execute(&mut *conn)
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(())
+}
+
+// List the events with a tag, soonest upcoming first and then most recently
+// started.
+pub async fn event_list_with_tag(
+    pool: &StoragePool,
+    tag: &str,
+    page: i64,
+    page_size: i64,
+) -> Result<Vec<EventWithRole>, StorageError> {
+    if tag.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Tag cannot be empty".into(),
+        )));
+    }
+
+    // Validate page and page_size are positive
+    if page < 1 || page_size < 1 {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Page and page size must be positive".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let offset = (page - 1) * page_size;
+
+    let event_roles = sqlx::query_as!(
+        EventWithRoleRow,
+        r#"SELECT
+        events.aturi,
+        events.cid,
+        events.did,
+        events.lexicon,
+        events.record AS "record: Json<serde_json::Value>",
+        events.name,
+        events.updated_at AS "updated_at?",
+        events.rsvp_closes_at,
+        events.rsvp_capacity,
+        events.starts_at,
+        events.ends_at,
+        events.mode,
+        events.status,
+        events.latitude,
+        events.longitude,
+        'organizer' as "role!"
+    FROM
+        events
+        INNER JOIN event_tags ON event_tags.event_aturi = events.aturi
+    WHERE
+        event_tags.tag = $1
+    ORDER BY
+        CASE WHEN events.starts_at >= $2 THEN 0 ELSE 1 END,
+        CASE WHEN events.starts_at >= $2 THEN events.starts_at END ASC,
+        events.starts_at DESC NULLS LAST,
+        events.aturi ASC
+    LIMIT $3
+    OFFSET $4"#,
+        tag,
+        Utc::now(),
+        page_size + 1,
+        offset,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?
+    .into_iter()
+    .map(EventWithRole::from)
+    .collect::<Vec<_>>();
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(event_roles)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::storage::event::event_delete;
+    use crate::test_support::EventBuilder;
+
+    #[test]
+    fn test_tag_slug() {
+        assert_eq!(tag_slug("rust"), "rust");
+        assert_eq!(tag_slug("#Rust Lang"), "rust-lang");
+        assert_eq!(tag_slug("Café"), "cafe");
+        assert_eq!(tag_slug(" #-- "), "");
+    }
+
+    #[test]
+    fn test_event_tag_keys() {
+        assert_eq!(
+            event_tag_keys(&["Rust", "#rust", " ", "Open Source", "x".repeat(65).as_str()]),
+            vec!["rust".to_string(), "open-source".to_string()]
+        );
+
+        let many = (0..10).map(|i| format!("tag{}", i)).collect::<Vec<_>>();
+        assert_eq!(event_tag_keys(&many).len(), MAX_EVENT_TAGS);
+    }
+
+    #[sqlx::test]
+    async fn test_event_list_with_tag(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+
+        let tagged = EventBuilder::default()
+            .tag("Rust")
+            .tag("meetup")
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+        EventBuilder::default()
+            .tag("python")
+            .insert(&pool, organizer, "3lbsxygenau2d")
+            .await?;
+
+        let events = event_list_with_tag(&pool, "rust", 1, 10).await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.aturi, tagged.uri);
+
+        event_delete(&pool, &tagged.uri).await?;
+        assert!(event_list_with_tag(&pool, "rust", 1, 10).await?.is_empty());
+
+        Ok(())
+    }
+}
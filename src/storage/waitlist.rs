@@ -0,0 +1,162 @@
+use crate::storage::{errors::StorageError, StoragePool};
+
+// Add someone to the waitlist of a full event. The waitlist is kept by this
+// instance and never written to the PDS. Joining again keeps their place.
+pub async fn waitlist_insert(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+) -> Result<(), StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        INSERT INTO event_waitlist (event_aturi, did)
+        VALUES ($1, $2)
+        ON CONFLICT(event_aturi, did) DO NOTHING
+        ",
+        event_aturi,
+        did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Take someone off the waitlist of an event, such as when they RSVP going.
+pub async fn waitlist_delete(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+) -> Result<(), StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        "DELETE FROM event_waitlist WHERE event_aturi = $1 AND did = $2",
+        event_aturi,
+        did,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// Find someone's place on the waitlist of an event, starting at 1 for whoever
+// joined first, or `None` when they aren't on it.
+#[tracing::instrument(skip(pool))]
+pub async fn waitlist_position(
+    pool: &StoragePool,
+    event_aturi: &str,
+    did: &str,
+) -> Result<Option<i64>, StorageError> {
+    if event_aturi.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "Event URI cannot be empty".into(),
+        )));
+    }
+
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let position = sqlx::query_scalar!(
+        r#"
+        SELECT position AS "position!" FROM (
+            SELECT did, ROW_NUMBER() OVER (ORDER BY created_at ASC, did ASC) AS position
+            FROM event_waitlist
+            WHERE event_aturi = $1
+        ) AS waitlist
+        WHERE did = $2
+        "#,
+        event_aturi,
+        did,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    Ok(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_support::EventBuilder;
+
+    #[sqlx::test]
+    async fn test_waitlist(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let identity = "did:plc:cbkjy5n7bk3ax2wplmtjofq2";
+        let other_identity = "did:plc:tgudj2fjm77pzkuawquqhsxm";
+
+        let event = EventBuilder::default()
+            .insert(&pool, organizer, "3lbsxygenau2c")
+            .await?;
+
+        assert_eq!(waitlist_position(&pool, &event.uri, identity).await?, None);
+        assert!(waitlist_insert(&pool, &event.uri, " ").await.is_err());
+
+        waitlist_insert(&pool, &event.uri, identity).await?;
+        waitlist_insert(&pool, &event.uri, other_identity).await?;
+        waitlist_insert(&pool, &event.uri, identity).await?;
+        assert_eq!(
+            waitlist_position(&pool, &event.uri, identity).await?,
+            Some(1)
+        );
+        assert_eq!(
+            waitlist_position(&pool, &event.uri, other_identity).await?,
+            Some(2)
+        );
+
+        waitlist_delete(&pool, &event.uri, identity).await?;
+        assert_eq!(waitlist_position(&pool, &event.uri, identity).await?, None);
+        assert_eq!(
+            waitlist_position(&pool, &event.uri, other_identity).await?,
+            Some(1)
+        );
+
+        Ok(())
+    }
+}
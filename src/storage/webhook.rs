@@ -0,0 +1,396 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+
+use crate::{
+    storage::{errors::StorageError, StoragePool},
+    webhooks::WebhookPayload,
+};
+use model::{Webhook, WebhookDelivery, WebhookDeliveryJob};
+
+pub mod model {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sqlx::{types::Json, FromRow};
+
+    /// A URL an organizer receives deliveries at, for one of their events or
+    /// for all of them when `event_aturi` isn't set.
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct Webhook {
+        pub id: String,
+        pub did: String,
+        pub event_aturi: Option<String>,
+        pub url: String,
+        pub secret: String,
+        pub created_at: DateTime<Utc>,
+    }
+
+    #[derive(Clone, FromRow, Deserialize, Serialize, Debug)]
+    pub struct WebhookDelivery {
+        pub id: i64,
+        pub webhook_id: String,
+        pub kind: String,
+        pub attempts: i32,
+        pub last_error: Option<String>,
+        pub next_attempt_at: DateTime<Utc>,
+        pub created_at: DateTime<Utc>,
+        pub delivered_at: Option<DateTime<Utc>>,
+        pub failed_at: Option<DateTime<Utc>>,
+    }
+
+    /// A delivery claimed for an attempt, with what is needed to post it.
+    #[derive(Clone, FromRow, Debug)]
+    pub struct WebhookDeliveryJob {
+        pub id: i64,
+        pub webhook_id: String,
+        pub url: String,
+        pub secret: String,
+        pub kind: String,
+        pub payload: Json<serde_json::Value>,
+        pub attempts: i32,
+    }
+}
+
+// Register a webhook for an organizer and return its id
+pub async fn webhook_insert(
+    pool: &StoragePool,
+    did: &str,
+    event_aturi: Option<&str>,
+    url: &str,
+    secret: &str,
+) -> Result<String, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let id = ulid::Ulid::new().to_string();
+
+    sqlx::query!(
+        "INSERT INTO webhooks (id, did, event_aturi, url, secret, created_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        &id,
+        did,
+        event_aturi,
+        url,
+        secret,
+        Utc::now(),
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(id)
+}
+
+// List the webhooks of an organizer, newest first
+pub async fn webhook_list(pool: &StoragePool, did: &str) -> Result<Vec<Webhook>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let webhooks = sqlx::query_as!(
+        Webhook,
+        "SELECT * FROM webhooks WHERE did = $1 ORDER BY created_at DESC, id DESC",
+        did,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(webhooks)
+}
+
+// Remove a webhook of an organizer and its queued deliveries. Returns false
+// when the organizer has no such webhook.
+pub async fn webhook_delete(pool: &StoragePool, did: &str, id: &str) -> Result<bool, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!("DELETE FROM webhooks WHERE did = $1 AND id = $2", did, id)
+        .execute(tx.as_mut())
+        .await
+        .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Queue a delivery of a payload to every webhook of an organizer that is for
+// the event or for all of their events. Returns the number of deliveries
+// queued.
+pub async fn webhook_enqueue(
+    pool: &StoragePool,
+    did: &str,
+    event_aturi: &str,
+    payload: &WebhookPayload,
+) -> Result<u64, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let result = sqlx::query!(
+        r"
+        INSERT INTO webhook_deliveries (webhook_id, kind, payload, next_attempt_at, created_at)
+        SELECT id, $3, $4, $5, $5 FROM webhooks
+        WHERE did = $1 AND (event_aturi IS NULL OR event_aturi = $2)
+        ",
+        did,
+        event_aturi,
+        payload.kind,
+        Json(payload) as _,
+        payload.created_at,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(result.rows_affected())
+}
+
+// Claim deliveries that are due for an attempt. Claimed deliveries aren't due
+// again until `lease_until`, so that other instances don't post them too, and
+// a delivery whose attempt never finished is retried after the lease.
+pub async fn webhook_deliveries_claim(
+    pool: &StoragePool,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<WebhookDeliveryJob>, StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let jobs = sqlx::query_as!(
+        WebhookDeliveryJob,
+        r"
+        WITH claimed AS (
+            UPDATE webhook_deliveries SET next_attempt_at = $2
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE delivered_at IS NULL AND failed_at IS NULL AND next_attempt_at <= $1
+                ORDER BY next_attempt_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, webhook_id, kind, payload, attempts
+        )
+        SELECT c.id, c.webhook_id, w.url, w.secret, c.kind, c.payload, c.attempts
+        FROM claimed c
+        JOIN webhooks w ON w.id = c.webhook_id
+        ",
+        now,
+        lease_until,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(jobs)
+}
+
+// Record the outcome of a delivery attempt. A failed delivery is attempted
+// again at `retry_at`, or given up on when it isn't set.
+pub async fn webhook_delivery_attempted(
+    pool: &StoragePool,
+    id: i64,
+    error: Option<&str>,
+    retry_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    sqlx::query!(
+        r"
+        UPDATE webhook_deliveries SET
+            attempts = attempts + 1, last_error = $2,
+            next_attempt_at = COALESCE($3, next_attempt_at),
+            delivered_at = CASE WHEN $2::varchar IS NULL THEN $4::timestamptz END,
+            failed_at = CASE WHEN $2::varchar IS NOT NULL AND $3::timestamptz IS NULL THEN $4::timestamptz END
+        WHERE id = $1
+        ",
+        id,
+        error,
+        retry_at,
+        now,
+    )
+    .execute(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)
+}
+
+// List the most recent deliveries to the webhooks of an organizer, newest
+// first
+pub async fn webhook_deliveries_recent(
+    pool: &StoragePool,
+    did: &str,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, StorageError> {
+    if did.trim().is_empty() {
+        return Err(StorageError::UnableToExecuteQuery(sqlx::Error::Protocol(
+            "DID cannot be empty".into(),
+        )));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(StorageError::CannotBeginDatabaseTransaction)?;
+
+    let deliveries = sqlx::query_as!(
+        WebhookDelivery,
+        r"
+        SELECT d.id, d.webhook_id, d.kind, d.attempts, d.last_error, d.next_attempt_at,
+            d.created_at, d.delivered_at, d.failed_at
+        FROM webhook_deliveries d
+        JOIN webhooks w ON w.id = d.webhook_id
+        WHERE w.did = $1
+        ORDER BY d.created_at DESC, d.id DESC
+        LIMIT $2
+        ",
+        did,
+        limit,
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(StorageError::UnableToExecuteQuery)?;
+
+    tx.commit()
+        .await
+        .map_err(StorageError::CannotCommitDatabaseTransaction)?;
+
+    Ok(deliveries)
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::storage::webhook::{
+        webhook_delete, webhook_deliveries_claim, webhook_deliveries_recent,
+        webhook_delivery_attempted, webhook_enqueue, webhook_insert, webhook_list,
+    };
+    use crate::webhooks::{WebhookEvent, WebhookKind, WebhookPayload};
+
+    #[sqlx::test]
+    async fn test_webhook_deliveries(pool: PgPool) -> anyhow::Result<()> {
+        let organizer = "did:plc:d5c1ed6d01421a67b96f68fa";
+        let event_aturi = format!("at://{}/community.lexicon.calendar.event/3lbs", organizer);
+        let other_aturi = format!("at://{}/community.lexicon.calendar.event/3lbt", organizer);
+
+        let all_events = webhook_insert(&pool, organizer, None, "https://a.example/", "a").await?;
+        let one_event = webhook_insert(
+            &pool,
+            organizer,
+            Some(&other_aturi),
+            "https://b.example/",
+            "b",
+        )
+        .await?;
+        assert_eq!(webhook_list(&pool, organizer).await?.len(), 2);
+
+        let now = Utc::now();
+        let payload = WebhookPayload::new(
+            WebhookKind::EventUpdated,
+            WebhookEvent {
+                uri: event_aturi.clone(),
+                url: "https://smokesignal.events/".to_string(),
+            },
+            None,
+            now,
+        );
+
+        // Only webhooks for all events and for this event get a delivery
+        assert_eq!(
+            webhook_enqueue(&pool, organizer, &event_aturi, &payload).await?,
+            1
+        );
+
+        let jobs = webhook_deliveries_claim(&pool, now, now + Duration::minutes(5), 10).await?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].webhook_id, all_events);
+        assert_eq!(jobs[0].url, "https://a.example/");
+        assert_eq!(jobs[0].payload.0["type"], "event.updated");
+
+        // Claimed deliveries aren't claimed again during their lease
+        assert!(
+            webhook_deliveries_claim(&pool, now, now + Duration::minutes(5), 10)
+                .await?
+                .is_empty()
+        );
+
+        let retry_at = now + Duration::minutes(1);
+        webhook_delivery_attempted(&pool, jobs[0].id, Some("timeout"), Some(retry_at), now).await?;
+        let jobs = webhook_deliveries_claim(&pool, retry_at, retry_at, 10).await?;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].attempts, 1);
+
+        webhook_delivery_attempted(&pool, jobs[0].id, None, None, now).await?;
+        let deliveries = webhook_deliveries_recent(&pool, organizer, 10).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert!(deliveries[0].delivered_at.is_some());
+        assert!(deliveries[0].last_error.is_none());
+
+        assert!(webhook_delete(&pool, organizer, &one_event).await?);
+        assert!(!webhook_delete(&pool, "did:plc:other", &all_events).await?);
+        assert_eq!(webhook_list(&pool, organizer).await?.len(), 1);
+
+        Ok(())
+    }
+}
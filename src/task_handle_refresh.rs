@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use hickory_resolver::TokioAsyncResolver;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    did::{model::Document, plc, web},
+    profile::{profile_is_stale, refresh_profile},
+    resolve::resolve_handle,
+    storage::{
+        handle::{handle_list_unresolved, handle_resolved, model::Handle},
+        StoragePool,
+    },
+};
+
+pub struct HandleRefreshTaskConfig {
+    pub sleep_interval: Duration,
+    pub refresh_interval: Duration,
+    pub batch_size: i64,
+    pub plc_hostname: String,
+    pub appview_hostname: String,
+}
+
+/// Periodically resolves the DID documents of stored handles, so that
+/// handles and PDSes stay current when people change their handle or move
+/// to another PDS. Bluesky profiles are fetched along the way, so that lists
+/// of events and RSVPs can show avatars and display names.
+///
+/// Handles are refreshed once the refresh interval has passed, and sooner
+/// when `handle_refresh` asks for it. A handle from a DID document is only
+/// stored once it resolves back to the same DID.
+pub struct HandleRefreshTask {
+    pub config: HandleRefreshTaskConfig,
+    pub http_client: reqwest::Client,
+    pub dns_resolver: TokioAsyncResolver,
+    pub storage_pool: StoragePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl HandleRefreshTask {
+    #[must_use]
+    pub fn new(
+        config: HandleRefreshTaskConfig,
+        http_client: reqwest::Client,
+        dns_resolver: TokioAsyncResolver,
+        storage_pool: StoragePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            dns_resolver,
+            storage_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the handle refresh task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("HandleRefreshTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("HandleRefreshTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("HandleRefreshTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let now = Utc::now();
+        let handles = handle_list_unresolved(
+            &self.storage_pool,
+            now - self.config.refresh_interval,
+            self.config.batch_size,
+        )
+        .await?;
+
+        let mut resolved = 0;
+        let mut changed = 0;
+        let mut failed = 0;
+        let mut profiles = 0;
+
+        for handle in &handles {
+            let (new_handle, pds) = match self.resolve(handle).await {
+                Ok(value) => {
+                    resolved += 1;
+                    value
+                }
+                Err(err) => {
+                    tracing::debug!(did = handle.did, error = ?err, "failed to resolve DID");
+                    failed += 1;
+                    (None, None)
+                }
+            };
+
+            // Failures are recorded as resolved too, so that a DID that
+            // can't be resolved waits for the refresh interval to be tried
+            // again.
+            if handle_resolved(
+                &self.storage_pool,
+                &handle.did,
+                new_handle.as_deref(),
+                pds.as_deref(),
+                Utc::now(),
+            )
+            .await?
+            {
+                tracing::info!(
+                    did = handle.did,
+                    handle = new_handle.as_deref().unwrap_or(&handle.handle),
+                    pds = pds.as_deref().unwrap_or(&handle.pds),
+                    "handle refreshed"
+                );
+                changed += 1;
+            }
+
+            if profile_is_stale(handle, Utc::now()) {
+                match refresh_profile(
+                    &self.http_client,
+                    &self.config.appview_hostname,
+                    &self.storage_pool,
+                    handle.clone(),
+                )
+                .await
+                {
+                    Ok(_) => profiles += 1,
+                    Err(err) => {
+                        tracing::debug!(did = handle.did, error = ?err, "failed to refresh profile");
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            resolved,
+            changed,
+            failed,
+            profiles,
+            "handle refresh"
+        );
+
+        Ok(())
+    }
+
+    /// Resolves the DID document of a handle, and returns its verified
+    /// handle and its PDS.
+    async fn resolve(&self, handle: &Handle) -> Result<(Option<String>, Option<String>)> {
+        let document = self.did_document(&handle.did).await?;
+        let pds = document.pds_endpoint().map(str::to_string);
+
+        let verified_handle = match document.primary_handle() {
+            Some(primary_handle) if primary_handle == handle.handle => {
+                Some(primary_handle.to_string())
+            }
+            Some(primary_handle) => {
+                match resolve_handle(&self.http_client, &self.dns_resolver, primary_handle).await {
+                    Ok(did) if did == handle.did => Some(primary_handle.to_string()),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        Ok((verified_handle, pds))
+    }
+
+    async fn did_document(&self, did: &str) -> Result<Document> {
+        if did.starts_with("did:plc:") {
+            plc::query(&self.http_client, &self.config.plc_hostname, did).await
+        } else if did.starts_with("did:web:") {
+            web::query(&self.http_client, did).await
+        } else {
+            Err(anyhow!("unsupported DID method: {}", did))
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    atproto::client::{list_public_records, ListRecordsParams},
+    geocoder::Geocoder,
+    repo_import::{import_record, import_repo, ImportedRecord, IMPORTED_COLLECTIONS},
+    storage::{
+        handle::{handle_for_did, model::Handle},
+        import_job::{
+            import_job_advance, import_job_claim, import_job_complete, import_job_counts,
+            import_job_fail, import_job_record, import_job_touch,
+            model::{ImportJob, IMPORT_SOURCE_REPO},
+            ImportRecordOutcome,
+        },
+        StoragePool,
+    },
+};
+
+const IMPORT_BATCH_SIZE: i64 = 2;
+const RECORDS_PAGE_SIZE: u32 = 50;
+
+/// An import is tried this many times before it is marked as failed.
+const MAX_ATTEMPTS: i32 = 3;
+
+/// How often a repository import shows that it is still running while the
+/// repository is downloaded and stored, which is done without progress.
+const REPO_IMPORT_HEARTBEAT: std::time::Duration = std::time::Duration::from_secs(30);
+
+pub struct ImportJobsTaskConfig {
+    pub sleep_interval: Duration,
+
+    /// Running imports record their progress after every record. Imports
+    /// that haven't for this long are assumed to have been interrupted, and
+    /// are continued from where they stopped.
+    pub stale_after: Duration,
+}
+
+/// Imports the events and RSVPs of identities that signed in for the first
+/// time or asked for an import from the import page. Collections are listed a
+/// page at a time, and progress is recorded after every record so that the
+/// import page can show it as it happens. Imports of a whole repository
+/// download it at once and record their counts when they are done.
+pub struct ImportJobsTask {
+    pub config: ImportJobsTaskConfig,
+    pub http_client: reqwest::Client,
+    pub storage_pool: StoragePool,
+    pub geocoder: Option<Geocoder>,
+    pub cancellation_token: CancellationToken,
+}
+
+impl ImportJobsTask {
+    #[must_use]
+    pub fn new(
+        config: ImportJobsTaskConfig,
+        http_client: reqwest::Client,
+        storage_pool: StoragePool,
+        geocoder: Option<Geocoder>,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            storage_pool,
+            geocoder,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the import jobs task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("ImportJobsTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("ImportJobsTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("ImportJobsTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let now = Utc::now();
+        let jobs = import_job_claim(
+            &self.storage_pool,
+            now,
+            now - self.config.stale_after,
+            IMPORT_BATCH_SIZE,
+        )
+        .await?;
+
+        let mut completed = 0;
+        for job in &jobs {
+            match self.run_job(job).await {
+                Ok(true) => completed += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::warn!(did = job.did, err = ?err, "unable to import repository");
+                    import_job_fail(
+                        &self.storage_pool,
+                        &job.did,
+                        &err.to_string(),
+                        MAX_ATTEMPTS,
+                        Utc::now(),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            claimed = jobs.len(),
+            completed,
+            "import jobs"
+        );
+
+        Ok(())
+    }
+
+    /// Lists and stores the records of each imported collection, starting
+    /// from the page and record an earlier attempt stopped at. Returns
+    /// whether the import completed, which it doesn't when the task is
+    /// stopping.
+    async fn run_job(&self, job: &ImportJob) -> Result<bool> {
+        let handle = handle_for_did(&self.storage_pool, &job.did).await?;
+
+        if job.source == IMPORT_SOURCE_REPO {
+            return self.run_repo_job(&handle).await;
+        }
+
+        let mut collection_index = job
+            .collection
+            .as_deref()
+            .and_then(|value| {
+                IMPORTED_COLLECTIONS
+                    .iter()
+                    .position(|collection| *collection == value)
+            })
+            .unwrap_or(0);
+        let mut cursor = job.cursor.clone();
+        let mut resume_after = job.last_record.clone();
+
+        while let Some(collection) = IMPORTED_COLLECTIONS.get(collection_index) {
+            if self.cancellation_token.is_cancelled() {
+                return Ok(false);
+            }
+
+            let params = ListRecordsParams {
+                repo: handle.did.clone(),
+                collection: collection.to_string(),
+                limit: Some(RECORDS_PAGE_SIZE),
+                cursor: cursor.clone(),
+                reverse: None,
+            };
+            let page =
+                list_public_records::<serde_json::Value>(&self.http_client, &handle.pds, &params)
+                    .await?;
+
+            let page_len = page.records.len();
+            let mut records = page.records;
+
+            // Records up to the last one stored by an interrupted attempt
+            // were already imported and counted.
+            if let Some(last_record) = resume_after.take() {
+                if let Some(position) = records.iter().position(|record| record.uri == last_record)
+                {
+                    records.drain(..=position);
+                }
+            }
+
+            for record in records {
+                let outcome = match import_record(
+                    &self.storage_pool,
+                    &handle.did,
+                    &record.uri,
+                    collection,
+                    &record.cid,
+                    record.value,
+                )
+                .await
+                {
+                    Ok(ImportedRecord::Event { written }) => {
+                        if written {
+                            if let Some(geocoder) = &self.geocoder {
+                                geocoder.spawn_geocode_event(&self.storage_pool, &record.uri);
+                            }
+                        }
+                        ImportRecordOutcome::Event
+                    }
+                    Ok(ImportedRecord::Rsvp) => ImportRecordOutcome::Rsvp,
+                    Err(err) => {
+                        tracing::error!(aturi = record.uri, ?err, "unable to import record");
+                        ImportRecordOutcome::Failed
+                    }
+                };
+
+                import_job_record(
+                    &self.storage_pool,
+                    &handle.did,
+                    &record.uri,
+                    outcome,
+                    Utc::now(),
+                )
+                .await?;
+            }
+
+            match page.cursor {
+                Some(next) if page_len > 0 => cursor = Some(next),
+                _ => {
+                    collection_index += 1;
+                    cursor = None;
+                }
+            }
+
+            import_job_advance(
+                &self.storage_pool,
+                &handle.did,
+                IMPORTED_COLLECTIONS.get(collection_index).copied(),
+                cursor.as_deref(),
+                Utc::now(),
+            )
+            .await?;
+        }
+
+        import_job_complete(&self.storage_pool, &handle.did, Utc::now()).await?;
+
+        Ok(true)
+    }
+
+    /// Downloads and stores the repository of an identity in one go. The
+    /// import is marked as running every `REPO_IMPORT_HEARTBEAT` until it
+    /// is done, so that it isn't claimed again as stale.
+    async fn run_repo_job(&self, handle: &Handle) -> Result<bool> {
+        let import = import_repo(
+            &self.storage_pool,
+            &self.http_client,
+            self.geocoder.as_ref(),
+            handle,
+        );
+        tokio::pin!(import);
+
+        let mut heartbeat = tokio::time::interval(REPO_IMPORT_HEARTBEAT);
+        let summary = loop {
+            tokio::select! {
+                () = self.cancellation_token.cancelled() => return Ok(false),
+                result = &mut import => break result?,
+                _ = heartbeat.tick() => {
+                    import_job_touch(&self.storage_pool, &handle.did, Utc::now()).await?;
+                }
+            }
+        };
+
+        import_job_counts(
+            &self.storage_pool,
+            &handle.did,
+            i32::try_from(summary.events).unwrap_or(i32::MAX),
+            i32::try_from(summary.rsvps).unwrap_or(i32::MAX),
+            i32::try_from(summary.failed).unwrap_or(i32::MAX),
+            Utc::now(),
+        )
+        .await?;
+        import_job_complete(&self.storage_pool, &handle.did, Utc::now()).await?;
+
+        Ok(true)
+    }
+}
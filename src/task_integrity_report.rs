@@ -0,0 +1,145 @@
+use anyhow::Result;
+use chrono::Duration;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    atproto::lexicon::{
+        community::lexicon::calendar::event::{
+            Event as CommunityEvent, NSID as COMMUNITY_EVENT_NSID,
+        },
+        events::smokesignal::calendar::event::{
+            Event as SmokeSignalEvent, NSID as SMOKESIGNAL_EVENT_NSID,
+        },
+    },
+    storage::{
+        event::{event_list_batch, model::Event},
+        integrity::{integrity_counts, integrity_report_insert},
+        StoragePool,
+    },
+};
+
+const EVENT_BATCH_SIZE: i64 = 500;
+
+pub struct IntegrityReportTaskConfig {
+    pub initial_delay: Duration,
+    pub sleep_interval: Duration,
+}
+
+/// Periodically checks the database for data-quality problems and records
+/// the results as an integrity report.
+pub struct IntegrityReportTask {
+    pub config: IntegrityReportTaskConfig,
+    pub storage_pool: StoragePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl IntegrityReportTask {
+    #[must_use]
+    pub fn new(
+        config: IntegrityReportTaskConfig,
+        storage_pool: StoragePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            storage_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the integrity report task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the initial delay or sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("IntegrityReportTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(self.config.initial_delay.to_std()?);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("IntegrityReportTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("IntegrityReportTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let counts = integrity_counts(&self.storage_pool).await?;
+        let unparseable_events = self.count_unparseable_events().await?;
+
+        integrity_report_insert(&self.storage_pool, &counts, unparseable_events).await?;
+
+        // Emitted as a single structured event so that log based metrics can
+        // alert on data-quality regressions.
+        tracing::info!(
+            target: "smokesignal::metrics",
+            total_events = counts.total_events,
+            total_rsvps = counts.total_rsvps,
+            total_handles = counts.total_handles,
+            orphaned_rsvps = counts.orphaned_rsvps,
+            handles_without_pds = counts.handles_without_pds,
+            unparseable_events,
+            "integrity report"
+        );
+
+        Ok(())
+    }
+
+    async fn count_unparseable_events(&self) -> Result<i64> {
+        let mut unparseable_events = 0;
+        let mut after_aturi: Option<String> = None;
+
+        loop {
+            if self.cancellation_token.is_cancelled() {
+                break;
+            }
+
+            let events =
+                event_list_batch(&self.storage_pool, after_aturi.as_deref(), EVENT_BATCH_SIZE)
+                    .await?;
+
+            for event in &events {
+                if !is_parseable(event) {
+                    tracing::debug!(aturi = event.aturi, "unparseable event record");
+                    unparseable_events += 1;
+                }
+            }
+
+            match events.last() {
+                Some(event) if events.len() as i64 == EVENT_BATCH_SIZE => {
+                    after_aturi = Some(event.aturi.clone());
+                }
+                _ => break,
+            }
+        }
+
+        Ok(unparseable_events)
+    }
+}
+
+/// Returns true if the stored record deserializes into the lexicon it was
+/// indexed as.
+fn is_parseable(event: &Event) -> bool {
+    let record = event.record.0.clone();
+    match event.lexicon.as_str() {
+        COMMUNITY_EVENT_NSID => serde_json::from_value::<CommunityEvent>(record).is_ok(),
+        SMOKESIGNAL_EVENT_NSID => serde_json::from_value::<SmokeSignalEvent>(record).is_ok(),
+        _ => false,
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    atproto::labeler::{active_labels, query_labels},
+    storage::{
+        event::event_list_batch,
+        label::{labels_replace, model::Label},
+        StoragePool,
+    },
+};
+
+/// The most pages of labels fetched for one batch of events.
+const MAX_LABEL_PAGES: usize = 5;
+
+pub struct LabelRefreshTaskConfig {
+    pub sleep_interval: Duration,
+    pub batch_size: i64,
+    pub labeler_hostname: String,
+}
+
+/// Periodically fetches the labels that the configured labeler applied to
+/// stored events and their organizers, and stores the labels that are in
+/// effect so that lists and event pages can apply the label policy without
+/// asking the labeler.
+///
+/// Each run checks the next batch of events in AT-URI order, starting over
+/// once every event has been checked.
+pub struct LabelRefreshTask {
+    pub config: LabelRefreshTaskConfig,
+    pub http_client: reqwest::Client,
+    pub storage_pool: StoragePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl LabelRefreshTask {
+    #[must_use]
+    pub fn new(
+        config: LabelRefreshTaskConfig,
+        http_client: reqwest::Client,
+        storage_pool: StoragePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            storage_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the label refresh task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("LabelRefreshTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        let mut after_aturi: Option<String> = None;
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    match self.process_work(after_aturi.as_deref()).await {
+                        Ok(next_aturi) => after_aturi = next_aturi,
+                        Err(err) => tracing::error!("LabelRefreshTask failed: {}", err),
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("LabelRefreshTask stopped");
+
+        Ok(())
+    }
+
+    /// Refreshes the labels of the events after `after_aturi` and of their
+    /// organizers. Returns where the next run continues from, or None once
+    /// the last event was checked.
+    async fn process_work(&self, after_aturi: Option<&str>) -> Result<Option<String>> {
+        let events =
+            event_list_batch(&self.storage_pool, after_aturi, self.config.batch_size).await?;
+        if events.is_empty() {
+            return Ok(None);
+        }
+
+        let mut subjects = events
+            .iter()
+            .map(|event| event.aturi.clone())
+            .collect::<Vec<_>>();
+        for event in &events {
+            if !subjects.contains(&event.did) {
+                subjects.push(event.did.clone());
+            }
+        }
+
+        let mut labels = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LABEL_PAGES {
+            let output = query_labels(
+                &self.http_client,
+                &self.config.labeler_hostname,
+                &subjects,
+                cursor.as_deref(),
+            )
+            .await?;
+
+            let done = output.labels.is_empty() || output.cursor.is_none();
+            labels.extend(output.labels);
+            cursor = output.cursor;
+            if done {
+                break;
+            }
+        }
+
+        let labels = active_labels(labels, Utc::now())
+            .into_iter()
+            .map(|label| Label {
+                subject: label.uri,
+                src: label.src,
+                val: label.val,
+                created_at: label.cts,
+            })
+            .collect::<Vec<_>>();
+
+        labels_replace(&self.storage_pool, &subjects, &labels).await?;
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            events = events.len(),
+            labels = labels.len(),
+            "label refresh"
+        );
+
+        if events.len() < self.config.batch_size as usize {
+            return Ok(None);
+        }
+
+        Ok(events.last().map(|event| event.aturi.clone()))
+    }
+}
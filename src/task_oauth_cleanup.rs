@@ -0,0 +1,96 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use deadpool_redis::redis::AsyncCommands;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::storage::{
+    cache::OAUTH_REFRESH_QUEUE, oauth::oauth_cleanup_expired, CachePool, StoragePool,
+};
+
+pub struct OAuthCleanupTaskConfig {
+    pub initial_delay: Duration,
+    pub sleep_interval: Duration,
+}
+
+/// Periodically removes OAuth requests and sessions that can no longer be
+/// used, so that their DPoP keys and tokens are not kept at rest.
+///
+/// A request is unused once it has expired without the callback completing
+/// it, and a session once it has outlived the web session cookie that
+/// references it.
+pub struct OAuthCleanupTask {
+    pub config: OAuthCleanupTaskConfig,
+    pub storage_pool: StoragePool,
+    pub cache_pool: CachePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl OAuthCleanupTask {
+    #[must_use]
+    pub fn new(
+        config: OAuthCleanupTaskConfig,
+        storage_pool: StoragePool,
+        cache_pool: CachePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            storage_pool,
+            cache_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the OAuth cleanup task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the initial delay or sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("OAuthCleanupTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(self.config.initial_delay.to_std()?);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("OAuthCleanupTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("OAuthCleanupTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let (requests_removed, session_groups) =
+            oauth_cleanup_expired(&self.storage_pool, Utc::now()).await?;
+
+        // Removed sessions would otherwise be picked up by the refresh task
+        // and fail to load.
+        if !session_groups.is_empty() {
+            let mut conn = self.cache_pool.get().await?;
+            let _: () = conn.zrem(OAUTH_REFRESH_QUEUE, &session_groups).await?;
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            requests_removed,
+            sessions_removed = session_groups.len(),
+            "oauth cleanup"
+        );
+
+        Ok(())
+    }
+}
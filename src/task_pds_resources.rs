@@ -0,0 +1,97 @@
+use anyhow::Result;
+use chrono::Duration;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    oauth::refresh_pds_resources,
+    storage::{cache::pds_resources_take_popular, CachePool},
+};
+
+pub struct PdsResourcesTaskConfig {
+    pub sleep_interval: Duration,
+    pub popular_limit: isize,
+}
+
+/// Periodically fetches the OAuth metadata of the PDSes that were looked up
+/// the most since the last run, so that it is cached again before it expires
+/// and sign-ins and token refreshes for popular PDSes don't wait on it.
+///
+/// The sleep interval should be shorter than `PDS_RESOURCES_TTL_SECS`.
+pub struct PdsResourcesTask {
+    pub config: PdsResourcesTaskConfig,
+    pub http_client: reqwest::Client,
+    pub cache_pool: CachePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl PdsResourcesTask {
+    #[must_use]
+    pub fn new(
+        config: PdsResourcesTaskConfig,
+        http_client: reqwest::Client,
+        cache_pool: CachePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            cache_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the PDS resources task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("PdsResourcesTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("PdsResourcesTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("PdsResourcesTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let popular =
+            pds_resources_take_popular(&self.cache_pool, self.config.popular_limit).await?;
+
+        let mut failed = 0;
+        for pds in &popular {
+            if let Err(err) = refresh_pds_resources(&self.http_client, &self.cache_pool, pds).await
+            {
+                tracing::warn!(pds, err = ?err, "unable to refresh pds resources");
+                failed += 1;
+            }
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            refreshed = popular.len() - failed,
+            failed,
+            "pds resources refresh"
+        );
+
+        Ok(())
+    }
+}
@@ -8,10 +8,13 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{
     config::{OAuthActiveKeys, SigningKeys},
-    oauth::client_oauth_refresh,
+    did::{plc::query as plc_query, web::query as web_query},
+    oauth::{client_oauth_refresh, refresh_pds_resources},
     refresh_tokens_errors::RefreshError,
+    resolve::{parse_input, InputType},
     storage::{
         cache::{build_worker_queue, OAUTH_REFRESH_HEARTBEATS, OAUTH_REFRESH_QUEUE},
+        handle::{handle_update_field, model::Handle, HandleField},
         oauth::{oauth_session_delete, oauth_session_update, web_session_lookup},
         CachePool, StoragePool,
     },
@@ -21,6 +24,7 @@ pub struct RefreshTokensTaskConfig {
     pub sleep_interval: Duration,
     pub worker_id: String,
     pub external_url_base: String,
+    pub plc_hostname: String,
     pub signing_keys: SigningKeys,
     pub oauth_active_keys: OAuthActiveKeys,
 }
@@ -180,6 +184,16 @@ impl RefreshTokensTask {
         let (handle, oauth_session) =
             web_session_lookup(&self.storage_pool, session_group, None).await?;
 
+        let handle = rebind_pds(
+            &self.http_client,
+            &self.storage_pool,
+            &self.cache_pool,
+            &self.config.plc_hostname,
+            handle,
+            &oauth_session.issuer,
+        )
+        .await?;
+
         let secret_signing_key = self
             .config
             .signing_keys
@@ -196,6 +210,7 @@ impl RefreshTokensTask {
 
         let token_response = client_oauth_refresh(
             &self.http_client,
+            &self.cache_pool,
             &self.config.external_url_base,
             (&oauth_session.secret_jwk_id, secret_signing_key.unwrap()),
             oauth_session.refresh_token.as_str(),
@@ -226,3 +241,65 @@ impl RefreshTokensTask {
         Ok(())
     }
 }
+
+/// Re-resolves the DID document so that a PDS migration is picked up
+/// before signing in or refreshing, instead of calling the old PDS until
+/// requests fail.
+///
+/// Resolution failures are logged and the stored PDS is kept, so that a
+/// PLC directory outage doesn't end every session.
+pub async fn rebind_pds(
+    http_client: &reqwest::Client,
+    storage_pool: &StoragePool,
+    cache_pool: &CachePool,
+    plc_hostname: &str,
+    mut handle: Handle,
+    issuer: &str,
+) -> Result<Handle> {
+    let did_document = match parse_input(&handle.did) {
+        Ok(InputType::Plc(did)) => plc_query(http_client, plc_hostname, &did).await,
+        Ok(InputType::Web(did)) => web_query(http_client, &did).await,
+        _ => return Ok(handle),
+    };
+
+    let pds = match did_document {
+        Ok(ref value) => value.pds_endpoint(),
+        Err(err) => {
+            tracing::warn!(did = handle.did, err = ?err, "unable to resolve DID document");
+            return Ok(handle);
+        }
+    };
+
+    let pds = match pds {
+        Some(value) if value != handle.pds => value.to_string(),
+        _ => return Ok(handle),
+    };
+
+    tracing::info!(
+        did = handle.did,
+        old_pds = handle.pds,
+        new_pds = pds,
+        "PDS changed, rebinding handle"
+    );
+
+    handle_update_field(
+        storage_pool,
+        &handle.did,
+        HandleField::Pds(Cow::Owned(pds.clone())),
+    )
+    .await?;
+
+    // Tokens are only valid with the authorization server that issued them.
+    // A PDS that moved to a different one requires a new login.
+    let (_, authorization_server) = refresh_pds_resources(http_client, cache_pool, &pds).await?;
+    if authorization_server.issuer != issuer {
+        return Err(RefreshError::AuthorizationServerChanged(
+            issuer.to_string(),
+            authorization_server.issuer,
+        )
+        .into());
+    }
+
+    handle.pds = pds;
+    Ok(handle)
+}
@@ -0,0 +1,407 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    atproto::{
+        appview::{post_like_counts, post_likers, MAX_POSTS_PER_REQUEST},
+        client::{list_public_records, ListRecord, ListRecordsParams},
+        lexicon::{
+            community::lexicon::calendar::rsvp::{
+                Rsvp as CommunityRsvp, RsvpStatus as CommunityRsvpStatus,
+                NSID as COMMUNITY_RSVP_NSID,
+            },
+            events::smokesignal::calendar::rsvp::{
+                Rsvp as SmokeSignalRsvp, RsvpStatus as SmokeSignalRsvpStatus,
+                NSID as SMOKESIGNAL_RSVP_NSID,
+            },
+        },
+    },
+    did::{plc, web},
+    storage::{
+        event::{rsvp_insert_with_metadata, RsvpInsertParams},
+        handle::handle_for_did,
+        rsvp_backfill::{
+            model::RsvpBackfill, rsvp_backfill_advance, rsvp_backfill_candidates,
+            rsvp_backfill_known_attendees, rsvp_backfill_list_pending, rsvp_backfill_schedule,
+        },
+        StoragePool,
+    },
+};
+
+/// Backfills start with the organizer's past attendees, then move on to the
+/// accounts that liked the announcement post.
+const SOURCE_ATTENDEES: &str = "attendees";
+const SOURCE_LIKES: &str = "likes";
+
+/// Announcements with fewer likes than this aren't backfilled.
+const MIN_LIKES: u32 = 10;
+
+const CANDIDATE_BATCH_SIZE: i64 = 100;
+const BACKFILL_BATCH_SIZE: i64 = 5;
+const REPOS_PER_STEP: i64 = 25;
+
+/// A backfill stops after checking this many repositories.
+const MAX_REPOS_CHECKED: i32 = 500;
+
+const RECORDS_PAGE_SIZE: u32 = 100;
+const MAX_RECORD_PAGES: usize = 5;
+
+pub struct RsvpBackfillTaskConfig {
+    pub sleep_interval: Duration,
+    pub recheck_interval: Duration,
+    pub appview_hostname: String,
+    pub plc_hostname: String,
+}
+
+/// Periodically looks for upcoming events whose RSVP count looks incomplete,
+/// such as events whose announcement post has many likes but few RSVPs are
+/// stored, and crawls the repositories of likely attendees for RSVPs to them
+/// that weren't indexed.
+///
+/// Each run advances every pending backfill by a few repositories, so that
+/// crawling is spread out over time and no PDS is asked for much at once.
+pub struct RsvpBackfillTask {
+    pub config: RsvpBackfillTaskConfig,
+    pub http_client: reqwest::Client,
+    pub storage_pool: StoragePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl RsvpBackfillTask {
+    #[must_use]
+    pub fn new(
+        config: RsvpBackfillTaskConfig,
+        http_client: reqwest::Client,
+        storage_pool: StoragePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            storage_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the RSVP backfill task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("RsvpBackfillTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("RsvpBackfillTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("RsvpBackfillTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let scheduled = self.schedule_backfills().await?;
+
+        let pending = rsvp_backfill_list_pending(&self.storage_pool, BACKFILL_BATCH_SIZE).await?;
+
+        let mut repos_checked = 0;
+        let mut rsvps_found = 0;
+        for backfill in &pending {
+            match self.advance_backfill(backfill).await {
+                Ok((repos, rsvps)) => {
+                    repos_checked += repos;
+                    rsvps_found += rsvps;
+                }
+                Err(err) => {
+                    tracing::warn!(event_aturi = backfill.event_aturi, err = ?err, "unable to advance rsvp backfill");
+                }
+            }
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            scheduled,
+            advanced = pending.len(),
+            repos_checked,
+            rsvps_found,
+            "rsvp backfill"
+        );
+
+        Ok(())
+    }
+
+    /// Compares the likes of announcement posts to the RSVPs stored for
+    /// their events, and schedules a backfill for events that look
+    /// incomplete.
+    async fn schedule_backfills(&self) -> Result<usize> {
+        let now = Utc::now();
+        let candidates = rsvp_backfill_candidates(
+            &self.storage_pool,
+            now,
+            now - self.config.recheck_interval,
+            CANDIDATE_BATCH_SIZE,
+        )
+        .await?;
+
+        let mut scheduled = 0;
+        for chunk in candidates.chunks(MAX_POSTS_PER_REQUEST) {
+            let post_aturis = chunk
+                .iter()
+                .map(|candidate| candidate.post_aturi.clone())
+                .collect::<Vec<_>>();
+            let like_counts = post_like_counts(
+                &self.http_client,
+                &self.config.appview_hostname,
+                &post_aturis,
+            )
+            .await?;
+
+            for (post_aturi, like_count) in like_counts {
+                let Some(candidate) = chunk
+                    .iter()
+                    .find(|candidate| candidate.post_aturi == post_aturi)
+                else {
+                    continue;
+                };
+                if !looks_incomplete(like_count, candidate.rsvp_count) {
+                    continue;
+                }
+
+                rsvp_backfill_schedule(
+                    &self.storage_pool,
+                    &candidate.event_aturi,
+                    &post_aturi,
+                    i32::try_from(like_count).unwrap_or(i32::MAX),
+                    i32::try_from(candidate.rsvp_count).unwrap_or(i32::MAX),
+                    SOURCE_ATTENDEES,
+                    now,
+                )
+                .await?;
+                scheduled += 1;
+            }
+        }
+
+        Ok(scheduled)
+    }
+
+    /// Checks the next few repositories of a backfill for RSVPs to its event
+    /// and records how far it got. Returns the number of repositories checked
+    /// and RSVPs found.
+    async fn advance_backfill(&self, backfill: &RsvpBackfill) -> Result<(i32, i32)> {
+        let (dids, source, cursor, exhausted) = if backfill.source == SOURCE_ATTENDEES {
+            let dids = rsvp_backfill_known_attendees(
+                &self.storage_pool,
+                &backfill.event_aturi,
+                backfill.cursor.as_deref(),
+                REPOS_PER_STEP,
+            )
+            .await?;
+
+            if (dids.len() as i64) < REPOS_PER_STEP {
+                (dids, SOURCE_LIKES, None, false)
+            } else {
+                let cursor = dids.last().cloned();
+                (dids, SOURCE_ATTENDEES, cursor, false)
+            }
+        } else {
+            let (dids, cursor) = post_likers(
+                &self.http_client,
+                &self.config.appview_hostname,
+                &backfill.post_aturi,
+                backfill.cursor.as_deref(),
+                REPOS_PER_STEP as u32,
+            )
+            .await?;
+            let exhausted = cursor.is_none();
+            (dids, SOURCE_LIKES, cursor, exhausted)
+        };
+
+        let mut rsvps_found = 0;
+        for did in &dids {
+            match self.backfill_repository(did, &backfill.event_aturi).await {
+                Ok(found) => rsvps_found += found,
+                Err(err) => {
+                    tracing::debug!(did, err = ?err, "unable to check repository for rsvps");
+                }
+            }
+        }
+
+        let repos_checked = dids.len() as i32;
+        let completed = exhausted || backfill.repos_checked + repos_checked >= MAX_REPOS_CHECKED;
+
+        rsvp_backfill_advance(
+            &self.storage_pool,
+            &backfill.event_aturi,
+            source,
+            cursor.as_deref(),
+            repos_checked,
+            rsvps_found,
+            completed,
+            Utc::now(),
+        )
+        .await?;
+
+        Ok((repos_checked, rsvps_found))
+    }
+
+    /// Lists the RSVP records of a repository and stores the ones for the
+    /// event. Returns the number of RSVPs stored.
+    async fn backfill_repository(&self, did: &str, event_aturi: &str) -> Result<i32> {
+        let pds = self.pds_for_did(did).await?;
+
+        let mut found = 0;
+
+        for record in self
+            .list_rsvp_records::<CommunityRsvp>(&pds, did, COMMUNITY_RSVP_NSID)
+            .await?
+        {
+            let CommunityRsvp::Current {
+                subject, status, ..
+            } = &record.value;
+            if subject.uri != event_aturi {
+                continue;
+            }
+            let status = match status {
+                CommunityRsvpStatus::Going => "going",
+                CommunityRsvpStatus::Interested => "interested",
+                CommunityRsvpStatus::NotGoing => "notgoing",
+            };
+
+            rsvp_insert_with_metadata(
+                &self.storage_pool,
+                RsvpInsertParams {
+                    aturi: &record.uri,
+                    cid: &record.cid,
+                    did,
+                    lexicon: COMMUNITY_RSVP_NSID,
+                    record: &record.value,
+                    event_aturi: &subject.uri,
+                    event_cid: &subject.cid,
+                    status,
+                },
+            )
+            .await?;
+            found += 1;
+        }
+
+        for record in self
+            .list_rsvp_records::<SmokeSignalRsvp>(&pds, did, SMOKESIGNAL_RSVP_NSID)
+            .await?
+        {
+            let SmokeSignalRsvp::Current {
+                subject, status, ..
+            } = &record.value;
+            if subject.uri != event_aturi {
+                continue;
+            }
+            let status = match status {
+                SmokeSignalRsvpStatus::Going => "going",
+                SmokeSignalRsvpStatus::Interested => "interested",
+                SmokeSignalRsvpStatus::NotGoing => "notgoing",
+            };
+
+            rsvp_insert_with_metadata(
+                &self.storage_pool,
+                RsvpInsertParams {
+                    aturi: &record.uri,
+                    cid: &record.cid,
+                    did,
+                    lexicon: SMOKESIGNAL_RSVP_NSID,
+                    record: &record.value,
+                    event_aturi: &subject.uri,
+                    event_cid: &subject.cid,
+                    status,
+                },
+            )
+            .await?;
+            found += 1;
+        }
+
+        Ok(found)
+    }
+
+    /// Lists the records of an RSVP collection, a few pages at most. Records
+    /// that don't parse are skipped.
+    async fn list_rsvp_records<T: serde::de::DeserializeOwned>(
+        &self,
+        pds: &str,
+        did: &str,
+        collection: &str,
+    ) -> Result<Vec<ListRecord<T>>> {
+        let mut records = vec![];
+        let mut cursor = None;
+
+        for _ in 0..MAX_RECORD_PAGES {
+            let params = ListRecordsParams {
+                repo: did.to_string(),
+                collection: collection.to_string(),
+                limit: Some(RECORDS_PAGE_SIZE),
+                cursor: cursor.take(),
+                reverse: None,
+            };
+            let page =
+                list_public_records::<serde_json::Value>(&self.http_client, pds, &params).await?;
+
+            let page_len = page.records.len();
+            records.extend(page.records.into_iter().filter_map(|record| {
+                let value = serde_json::from_value::<T>(record.value).ok()?;
+                Some(ListRecord {
+                    uri: record.uri,
+                    cid: record.cid,
+                    value,
+                })
+            }));
+
+            match page.cursor {
+                Some(next) if page_len > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Finds the PDS of a DID, from its stored handle or else from its DID
+    /// document.
+    async fn pds_for_did(&self, did: &str) -> Result<String> {
+        if let Ok(handle) = handle_for_did(&self.storage_pool, did).await {
+            return Ok(handle.pds);
+        }
+
+        let document = if did.starts_with("did:plc:") {
+            plc::query(&self.http_client, &self.config.plc_hostname, did).await?
+        } else if did.starts_with("did:web:") {
+            web::query(&self.http_client, did).await?
+        } else {
+            return Err(anyhow!("unsupported DID method: {}", did));
+        };
+
+        document
+            .pds_endpoint()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("no PDS in DID document: {}", did))
+    }
+}
+
+/// An event's RSVPs look incomplete when its announcement post has many
+/// likes but less than half as many RSVPs are stored.
+fn looks_incomplete(like_count: u32, rsvp_count: i64) -> bool {
+    like_count >= MIN_LIKES && rsvp_count * 2 < i64::from(like_count)
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    storage::{
+        webhook::{
+            model::WebhookDeliveryJob, webhook_deliveries_claim, webhook_delivery_attempted,
+        },
+        StoragePool,
+    },
+    webhooks::{
+        retry_delay, sign_payload, KIND_HEADER, MAX_DELIVERY_ATTEMPTS, SIGNATURE_HEADER,
+        TIMESTAMP_HEADER,
+    },
+    webhooks_errors::WebhookError,
+};
+
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// The longest error kept for a failed attempt.
+const MAX_ERROR_CHARS: usize = 512;
+
+pub struct WebhooksTaskConfig {
+    pub sleep_interval: Duration,
+    pub batch_size: i64,
+    pub lease: Duration,
+}
+
+/// Periodically posts queued webhook deliveries. Failed deliveries are
+/// retried with exponential backoff until `MAX_DELIVERY_ATTEMPTS` attempts
+/// have failed.
+///
+/// Deliveries are claimed for the lease before they are posted, so that
+/// several instances can run the task. The lease should be longer than a
+/// batch takes to post.
+pub struct WebhooksTask {
+    pub config: WebhooksTaskConfig,
+    pub http_client: reqwest::Client,
+    pub storage_pool: StoragePool,
+    pub cancellation_token: CancellationToken,
+}
+
+impl WebhooksTask {
+    #[must_use]
+    pub fn new(
+        config: WebhooksTaskConfig,
+        http_client: reqwest::Client,
+        storage_pool: StoragePool,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            config,
+            http_client,
+            storage_pool,
+            cancellation_token,
+        }
+    }
+
+    /// Runs the webhooks task as a long-running process
+    ///
+    /// # Errors
+    /// Returns an error if the sleep interval cannot be converted
+    pub async fn run(&self) -> Result<()> {
+        tracing::debug!("WebhooksTask started");
+
+        let interval = self.config.sleep_interval.to_std()?;
+
+        let sleeper = sleep(interval);
+        tokio::pin!(sleeper);
+
+        loop {
+            tokio::select! {
+            () = self.cancellation_token.cancelled() => {
+                break;
+            },
+            () = &mut sleeper => {
+                    if let Err(err) = self.process_work().await {
+                        tracing::error!("WebhooksTask failed: {}", err);
+                    }
+                sleeper.as_mut().reset(Instant::now() + interval);
+            }
+            }
+        }
+
+        tracing::info!("WebhooksTask stopped");
+
+        Ok(())
+    }
+
+    async fn process_work(&self) -> Result<()> {
+        let now = Utc::now();
+        let jobs = webhook_deliveries_claim(
+            &self.storage_pool,
+            now,
+            now + self.config.lease,
+            self.config.batch_size,
+        )
+        .await?;
+
+        let mut delivered = 0;
+        let mut retried = 0;
+        let mut failed = 0;
+
+        for job in &jobs {
+            let result = self.deliver(job).await;
+
+            let attempts = job.attempts + 1;
+            let now = Utc::now();
+
+            match result {
+                Ok(()) => {
+                    webhook_delivery_attempted(&self.storage_pool, job.id, None, None, now).await?;
+                    delivered += 1;
+                }
+                Err(err) => {
+                    let error: String = err.to_string().chars().take(MAX_ERROR_CHARS).collect();
+                    let retry_at =
+                        (attempts < MAX_DELIVERY_ATTEMPTS).then(|| now + retry_delay(attempts));
+
+                    tracing::debug!(
+                        webhook_id = job.webhook_id,
+                        attempts,
+                        error,
+                        "webhook delivery failed"
+                    );
+
+                    webhook_delivery_attempted(
+                        &self.storage_pool,
+                        job.id,
+                        Some(&error),
+                        retry_at,
+                        now,
+                    )
+                    .await?;
+
+                    if retry_at.is_some() {
+                        retried += 1;
+                    } else {
+                        failed += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            target: "smokesignal::metrics",
+            delivered,
+            retried,
+            failed,
+            "webhook deliveries"
+        );
+
+        Ok(())
+    }
+
+    /// Posts a delivery to its webhook, signed with the webhook's secret.
+    async fn deliver(&self, job: &WebhookDeliveryJob) -> Result<()> {
+        let body = serde_json::to_vec(&job.payload.0)?;
+        let timestamp = Utc::now().timestamp();
+        let signature = sign_payload(&job.secret, timestamp, &body);
+
+        let response = self
+            .http_client
+            .post(&job.url)
+            .header("Content-Type", "application/json")
+            .header(KIND_HEADER, &job.kind)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .timeout(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(WebhookError::UnexpectedStatus(response.status().as_u16()).into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use http::{HeaderMap, Request};
+use opentelemetry::{global, propagation::Extractor, trace::TracerProvider as _};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_subscriber::prelude::*;
+
+use crate::config::default_env;
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Installs the global tracing subscriber. Spans are always logged, and when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set they are also exported over OTLP/HTTP
+/// so a request can be followed across its database and XRPC calls.
+///
+/// The returned provider, if any, should be shut down before exiting so the
+/// last batch of spans is flushed.
+pub fn init_tracing(default_filter: &str) -> Result<Option<SdkTracerProvider>> {
+    let provider = match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) if !endpoint.is_empty() => {
+            // The exporter reads the endpoint, headers and timeout from the
+            // standard `OTEL_EXPORTER_OTLP_*` variables.
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .build()?;
+            let resource = Resource::builder()
+                .with_service_name(default_env("OTEL_SERVICE_NAME", "smokesignal"))
+                .build();
+            Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(resource)
+                    .build(),
+            )
+        }
+        _ => None,
+    };
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let otel_layer = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer("smokesignal")));
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| default_filter.into()),
+        ))
+        .with(tracing_subscriber::fmt::layer().pretty())
+        .with(otel_layer)
+        .init();
+
+    Ok(provider)
+}
+
+/// The span each HTTP request is handled in. A W3C `traceparent` header sent
+/// by a proxy or load balancer makes the request part of that trace.
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = request.uri().path(),
+        status = tracing::field::Empty,
+    );
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    let _ = span.set_parent(parent);
+    span
+}
@@ -0,0 +1,242 @@
+//! Builders for the records and rows that tests need.
+//!
+//! Storage and handler tests use these instead of copying SQL fixture files,
+//! so that fixtures keep up with changes to the lexicons and tables. Rows are
+//! written with the same storage functions the application uses.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    atproto::lexicon::{
+        com::atproto::repo::StrongRef,
+        community::lexicon::calendar::{
+            event::{
+                Event, EventLink, EventLocation, Mode, Status, EXTRA_TAGS, NSID as EVENT_NSID,
+            },
+            rsvp::{Rsvp, RsvpStatus, NSID as RSVP_NSID},
+        },
+    },
+    jose,
+    storage::{
+        errors::StorageError,
+        event::{event_insert, rsvp_insert},
+        handle::handle_warm_up,
+        oauth::{oauth_session_insert, OAuthSessionParams},
+        StoragePool,
+    },
+};
+
+/// The PDS of identities created by [`insert_handle`].
+pub const TEST_PDS: &str = "https://pds.examplepds.com";
+
+/// The time records are created at unless a builder is given another.
+fn test_created_at() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2025-05-01T17:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// A stand-in CID for a test record. It is stable for a record key, which is
+/// enough for the index, but is not the hash of the record.
+fn test_cid(rkey: &str) -> String {
+    format!("bafyreitest{}", rkey.to_lowercase())
+}
+
+/// Builds `community.lexicon.calendar.event` records. The defaults are a
+/// valid event with a name and description and nothing else.
+pub struct EventBuilder {
+    name: String,
+    description: String,
+    created_at: DateTime<Utc>,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+    mode: Option<Mode>,
+    status: Option<Status>,
+    locations: Vec<EventLocation>,
+    uris: Vec<EventLink>,
+    tags: Vec<String>,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        Self {
+            name: "Vancouver Rust Meetup".to_string(),
+            description: "Talks and snacks at the library.".to_string(),
+            created_at: test_created_at(),
+            starts_at: None,
+            ends_at: None,
+            mode: None,
+            status: None,
+            locations: vec![],
+            uris: vec![],
+            tags: vec![],
+        }
+    }
+}
+
+impl EventBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the start, and an end two hours later.
+    pub fn starts_at(mut self, starts_at: DateTime<Utc>) -> Self {
+        self.starts_at = Some(starts_at);
+        self.ends_at = Some(starts_at + Duration::hours(2));
+        self
+    }
+
+    pub fn ends_at(mut self, ends_at: Option<DateTime<Utc>>) -> Self {
+        self.ends_at = ends_at;
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn location(mut self, location: EventLocation) -> Self {
+        self.locations.push(location);
+        self
+    }
+
+    pub fn link(mut self, uri: impl Into<String>) -> Self {
+        self.uris.push(EventLink::Current {
+            uri: uri.into(),
+            name: None,
+        });
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn build(self) -> Event {
+        let mut extra = HashMap::default();
+        if !self.tags.is_empty() {
+            extra.insert(EXTRA_TAGS.to_string(), serde_json::json!(self.tags));
+        }
+
+        Event::Current {
+            name: self.name,
+            description: self.description,
+            created_at: self.created_at,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            mode: self.mode,
+            status: self.status,
+            locations: self.locations,
+            uris: self.uris,
+            extra,
+        }
+    }
+
+    /// Indexes the event as a record of `did` and returns a reference to it,
+    /// which can be used as the subject of RSVPs.
+    pub async fn insert(
+        self,
+        pool: &StoragePool,
+        did: &str,
+        rkey: &str,
+    ) -> Result<StrongRef, StorageError> {
+        let uri = format!("at://{}/{}/{}", did, EVENT_NSID, rkey);
+        let cid = test_cid(rkey);
+        event_insert(pool, &uri, &cid, did, EVENT_NSID, &self.build()).await?;
+        Ok(StrongRef { uri, cid })
+    }
+}
+
+/// Builds `community.lexicon.calendar.rsvp` records. RSVPs are for going
+/// unless another status is given.
+pub struct RsvpBuilder {
+    subject: StrongRef,
+    status: RsvpStatus,
+    created_at: DateTime<Utc>,
+}
+
+impl RsvpBuilder {
+    pub fn new(subject: StrongRef) -> Self {
+        Self {
+            subject,
+            status: RsvpStatus::Going,
+            created_at: test_created_at(),
+        }
+    }
+
+    pub fn status(mut self, status: RsvpStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn build(self) -> Rsvp {
+        Rsvp::Current {
+            subject: self.subject,
+            status: self.status,
+            created_at: self.created_at,
+        }
+    }
+
+    /// Indexes the RSVP as a record of `did` and returns a reference to it.
+    pub async fn insert(
+        self,
+        pool: &StoragePool,
+        did: &str,
+        rkey: &str,
+    ) -> Result<StrongRef, StorageError> {
+        let uri = format!("at://{}/{}/{}", did, RSVP_NSID, rkey);
+        let cid = test_cid(rkey);
+        rsvp_insert(pool, &uri, &cid, did, RSVP_NSID, &self.build()).await?;
+        Ok(StrongRef { uri, cid })
+    }
+}
+
+/// Stores an identity with a handle on [`TEST_PDS`].
+pub async fn insert_handle(
+    pool: &StoragePool,
+    did: &str,
+    handle: &str,
+) -> Result<(), StorageError> {
+    handle_warm_up(pool, did, handle, TEST_PDS).await
+}
+
+/// Stores an OAuth session for an identity, valid for an hour, and returns
+/// its session group as it would be found in the session cookie.
+pub async fn insert_session(pool: &StoragePool, did: &str) -> Result<String, StorageError> {
+    let session_group = ulid::Ulid::new().to_string();
+    let now = Utc::now();
+
+    oauth_session_insert(
+        pool,
+        OAuthSessionParams {
+            session_group: session_group.clone().into(),
+            access_token: "access_token".to_string().into(),
+            did: did.to_string().into(),
+            issuer: TEST_PDS.to_string().into(),
+            refresh_token: "refresh_token".to_string().into(),
+            secret_jwk_id: "secret_jwk_id".to_string().into(),
+            dpop_jwk: jose::jwk::generate(),
+            created_at: now,
+            access_token_expires_at: now + Duration::hours(1),
+            user_agent: None,
+        },
+    )
+    .await?;
+
+    Ok(session_group)
+}
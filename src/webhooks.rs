@@ -0,0 +1,269 @@
+//! Signed HTTP notifications that organizers receive when their events change.
+//!
+//! Organizers register webhook URLs for one of their events or for all of
+//! them. When someone RSVPs, changes their RSVP, or the event is edited, a
+//! delivery is queued for each matching webhook and posted by a background
+//! task, which retries failed deliveries with exponential backoff.
+//!
+//! Each delivery is signed with the webhook's secret, so that endpoints can
+//! check it came from this instance: the signature is the hex HMAC-SHA256 of
+//! the timestamp, a `.`, and the body.
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Serialize;
+use sha2::Sha256;
+use std::net::IpAddr;
+use url::{Host, Url};
+
+use crate::webhooks_errors::WebhookError;
+
+/// The most webhooks an organizer can register.
+pub const MAX_WEBHOOKS: i64 = 10;
+
+/// Deliveries are given up on after this many failed attempts, which spans
+/// about 15 hours with `retry_delay`.
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+
+pub const SIGNATURE_HEADER: &str = "X-Smokesignal-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Smokesignal-Timestamp";
+pub const KIND_HEADER: &str = "X-Smokesignal-Webhook";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookKind {
+    /// Someone RSVPed to the event for the first time.
+    RsvpCreated,
+    /// Someone changed their RSVP to the event.
+    RsvpUpdated,
+    /// The organizer edited the event.
+    EventUpdated,
+}
+
+impl WebhookKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookKind::RsvpCreated => "rsvp.created",
+            WebhookKind::RsvpUpdated => "rsvp.updated",
+            WebhookKind::EventUpdated => "event.updated",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WebhookEvent {
+    pub uri: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookRsvp {
+    pub did: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<String>,
+}
+
+/// The body of a webhook delivery.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub created_at: DateTime<Utc>,
+    pub event: WebhookEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp: Option<WebhookRsvp>,
+}
+
+impl WebhookPayload {
+    pub fn new(
+        kind: WebhookKind,
+        event: WebhookEvent,
+        rsvp: Option<WebhookRsvp>,
+        created_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            kind: kind.as_str(),
+            created_at,
+            event,
+            rsvp,
+        }
+    }
+}
+
+/// Creates the secret that deliveries of a new webhook are signed with.
+pub fn generate_secret() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    format!("whsec_{}", secret)
+}
+
+/// Signs the body of a delivery sent at `timestamp`, in seconds since the
+/// epoch. Signing the timestamp lets endpoints reject replayed deliveries.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// How long to wait before attempting a delivery again, after `attempts`
+/// failed attempts. Doubles from one minute, up to six hours.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 10) - 1;
+    std::cmp::min(Duration::minutes(1 << exponent), Duration::hours(6))
+}
+
+/// Checks that a URL can be used as a webhook, and returns it normalized.
+/// Webhooks are posted from the server, so they must use HTTPS and can't
+/// point at addresses of the server's own network.
+pub fn validate_webhook_url(value: &str) -> Result<String, WebhookError> {
+    let url = Url::parse(value.trim()).map_err(|err| WebhookError::InvalidUrl(err.to_string()))?;
+
+    if url.scheme() != "https" {
+        return Err(WebhookError::InsecureUrl);
+    }
+
+    let is_private = match url.host() {
+        None => return Err(WebhookError::InvalidUrl(value.to_string())),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.');
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Some(Host::Ipv4(address)) => is_private_address(IpAddr::V4(address)),
+        Some(Host::Ipv6(address)) => is_private_address(IpAddr::V6(address)),
+    };
+    if is_private {
+        return Err(WebhookError::PrivateAddress);
+    }
+
+    Ok(url.to_string())
+}
+
+fn is_private_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+        }
+        IpAddr::V6(address) => {
+            if let Some(address) = address.to_ipv4_mapped() {
+                return is_private_address(IpAddr::V4(address));
+            }
+            let first = address.segments()[0];
+            address.is_loopback()
+                || address.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        // HMAC-SHA256 of "1700000000.{}" with the key "secret"
+        let signature = sign_payload("secret", 1_700_000_000, b"{}");
+        assert_eq!(
+            signature,
+            "b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163"
+        );
+        assert_ne!(signature, sign_payload("secret", 1_700_000_001, b"{}"));
+        assert_ne!(signature, sign_payload("other", 1_700_000_000, b"{}"));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::minutes(1));
+        assert_eq!(retry_delay(2), Duration::minutes(2));
+        assert_eq!(retry_delay(5), Duration::minutes(16));
+        assert_eq!(retry_delay(MAX_DELIVERY_ATTEMPTS), Duration::hours(6));
+        assert_eq!(retry_delay(100), Duration::hours(6));
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert_eq!(
+            validate_webhook_url(" https://hooks.example.com/smokesignal ").unwrap(),
+            "https://hooks.example.com/smokesignal"
+        );
+        assert!(matches!(
+            validate_webhook_url("http://hooks.example.com/"),
+            Err(WebhookError::InsecureUrl)
+        ));
+        assert!(matches!(
+            validate_webhook_url("hooks.example.com"),
+            Err(WebhookError::InvalidUrl(_))
+        ));
+        for url in [
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.1.2.3/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(
+                matches!(validate_webhook_url(url), Err(WebhookError::PrivateAddress)),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let created_at = DateTime::parse_from_rfc3339("2025-05-22T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let payload = WebhookPayload::new(
+            WebhookKind::RsvpUpdated,
+            WebhookEvent {
+                uri: "at://did:plc:abc/community.lexicon.calendar.event/3lbs".to_string(),
+                url: "https://smokesignal.events/did:plc:abc/3lbs".to_string(),
+            },
+            Some(WebhookRsvp {
+                did: "did:plc:def".to_string(),
+                status: "going".to_string(),
+                previous_status: Some("interested".to_string()),
+            }),
+            created_at,
+        );
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "type": "rsvp.updated",
+                "createdAt": "2025-05-22T09:00:00Z",
+                "event": {
+                    "uri": "at://did:plc:abc/community.lexicon.calendar.event/3lbs",
+                    "url": "https://smokesignal.events/did:plc:abc/3lbs",
+                },
+                "rsvp": {
+                    "did": "did:plc:def",
+                    "status": "going",
+                    "previousStatus": "interested",
+                },
+            })
+        );
+    }
+}
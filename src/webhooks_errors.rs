@@ -0,0 +1,57 @@
+use thiserror::Error;
+
+/// Represents errors that can occur while managing or delivering webhooks.
+///
+/// These errors happen when an organizer registers a webhook URL that can't
+/// be used, or when a webhook endpoint doesn't accept a delivery.
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    /// Error when a webhook URL can't be parsed.
+    ///
+    /// This error occurs when the URL entered in the webhook form isn't an
+    /// absolute URL.
+    #[error("error-webhook-1 Invalid webhook URL: {0}")]
+    InvalidUrl(String),
+
+    /// Error when a webhook URL doesn't use HTTPS.
+    ///
+    /// This error occurs when the URL uses another scheme, since payloads
+    /// would otherwise be sent in the clear.
+    #[error("error-webhook-2 Webhook URLs must use https")]
+    InsecureUrl,
+
+    /// Error when a webhook URL points at a private address.
+    ///
+    /// This error occurs when the host of the URL is a loopback, private or
+    /// link-local address, which would let webhooks reach internal services.
+    #[error("error-webhook-3 Webhook URLs can't point at private addresses")]
+    PrivateAddress,
+
+    /// Error when an organizer has too many webhooks.
+    ///
+    /// This error occurs when an organizer registers a webhook while they
+    /// already have the most webhooks they can keep.
+    #[error("error-webhook-4 Organizers can have at most {0} webhooks")]
+    TooManyWebhooks(i64),
+
+    /// Error when a webhook doesn't exist.
+    ///
+    /// This error occurs when a webhook is removed but isn't one of the
+    /// current user's webhooks.
+    #[error("error-webhook-5 Webhook not found: {0}")]
+    WebhookNotFound(String),
+
+    /// Error when a webhook endpoint rejects a delivery.
+    ///
+    /// This error occurs when the endpoint responds with a status other than
+    /// 2xx, and the delivery is retried later.
+    #[error("error-webhook-6 Webhook endpoint responded with status {0}")]
+    UnexpectedStatus(u16),
+
+    /// Error when a webhook is registered for an event of someone else.
+    ///
+    /// This error occurs when the event picked in the webhook form doesn't
+    /// exist or isn't one of the current user's events.
+    #[error("error-webhook-7 Event not found: {0}")]
+    EventNotFound(String),
+}
@@ -0,0 +1,539 @@
+//! End-to-end smoke tests. The app is booted against disposable Postgres and
+//! Redis containers and a mock PDS, and driven through the same requests a
+//! browser makes, so that mistakes in how handlers are wired together are
+//! caught before a deploy.
+//!
+//! The tests need Docker, so they are ignored by default:
+//!
+//! ```sh
+//! cargo test --test smoke -- --ignored
+//! ```
+//!
+//! `SMOKE_DATABASE_URL` and `SMOKE_REDIS_URL` point the tests at services that
+//! are already running instead. The database is migrated and should be empty.
+
+mod mock_pds;
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use reqwest::{header, redirect::Policy, Response, StatusCode};
+use serde_json::Value;
+use smokesignal::{
+    config::Config,
+    http::{
+        context::{AppEngine, I18nContext, WebContext},
+        server::build_router,
+    },
+    i18n::Locales,
+    jose::jwk::{self, WrappedJsonWebKeySet},
+    resolve::create_resolver,
+    service_auth::{mint_service_token, service_did},
+    storage::{banner::banner_insert, cache::create_cache_pool, MIGRATOR},
+};
+use sqlx::PgPool;
+use testcontainers_modules::{
+    postgres::Postgres,
+    redis::{Redis, REDIS_PORT},
+    testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt},
+};
+use tokio::net::TcpListener;
+use unic_langid::LanguageIdentifier;
+
+#[cfg(feature = "embed")]
+use smokesignal::{http::templates::embed_env, i18n::embed::populate_locale};
+
+#[cfg(feature = "reload")]
+use smokesignal::{http::templates::reload_env, i18n::reload::populate_locale};
+
+use mock_pds::MockPds;
+
+const DID: &str = "did:plc:smoketestaliceaaaaaaaaaa";
+const HANDLE: &str = "alice.smoke.test";
+const EXTERNAL_BASE: &str = "smoke.test";
+
+const EVENT_NSID: &str = "community.lexicon.calendar.event";
+const RSVP_NSID: &str = "community.lexicon.calendar.rsvp";
+
+/// Postgres and Redis for one test run, either containers or services that
+/// are already running.
+struct Services {
+    database_url: String,
+    redis_url: String,
+    _postgres: Option<ContainerAsync<Postgres>>,
+    _redis: Option<ContainerAsync<Redis>>,
+}
+
+impl Services {
+    async fn start() -> Result<Self> {
+        if let (Ok(database_url), Ok(redis_url)) = (
+            std::env::var("SMOKE_DATABASE_URL"),
+            std::env::var("SMOKE_REDIS_URL"),
+        ) {
+            return Ok(Self {
+                database_url,
+                redis_url,
+                _postgres: None,
+                _redis: None,
+            });
+        }
+
+        // The same images as the development environment
+        let postgres = Postgres::default().with_tag("17-alpine").start().await?;
+        let redis = Redis::default()
+            .with_name("valkey/valkey")
+            .with_tag("8-alpine")
+            .start()
+            .await?;
+
+        let database_url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            postgres.get_host().await?,
+            postgres.get_host_port_ipv4(5432).await?
+        );
+        let redis_url = format!(
+            "redis://{}:{}/0",
+            redis.get_host().await?,
+            redis.get_host_port_ipv4(REDIS_PORT).await?
+        );
+
+        Ok(Self {
+            database_url,
+            redis_url,
+            _postgres: Some(postgres),
+            _redis: Some(redis),
+        })
+    }
+}
+
+/// The app served on a local port, and a client that signs in to it like a
+/// browser would.
+struct SmokeApp {
+    base: String,
+    client: reqwest::Client,
+    session_cookie: Option<String>,
+    pds: MockPds,
+    pool: PgPool,
+    _services: Services,
+}
+
+impl SmokeApp {
+    async fn start() -> Result<Self> {
+        let services = Services::start().await?;
+        let pds = MockPds::start(DID, HANDLE).await?;
+
+        let mut cookie_key = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut cookie_key);
+        let signing_key = jwk::generate();
+        let key_id = signing_key.kid.clone().unwrap();
+        let signing_keys = serde_json::to_vec(&WrappedJsonWebKeySet {
+            keys: vec![signing_key],
+        })?;
+
+        // The configuration is read from the environment the same way as in
+        // production. Every test in this binary sets the same values.
+        for (name, value) in [
+            (
+                "HTTP_COOKIE_KEY",
+                general_purpose::STANDARD_NO_PAD.encode(cookie_key),
+            ),
+            ("EXTERNAL_BASE", EXTERNAL_BASE.to_string()),
+            (
+                "SIGNING_KEYS",
+                general_purpose::STANDARD.encode(signing_keys),
+            ),
+            ("OAUTH_ACTIVE_KEYS", key_id.clone()),
+            ("DESTINATION_KEY", key_id),
+            ("DATABASE_URL", services.database_url.clone()),
+            ("REDIS_URL", services.redis_url.clone()),
+            ("PLC_HOSTNAME", pds.host.clone()),
+            ("APPVIEW_HOSTNAME", pds.host.clone()),
+        ] {
+            std::env::set_var(name, value);
+        }
+        let config = Config::new()?;
+
+        // The mock PDS has a self-signed certificate
+        let certificate = reqwest::Certificate::from_pem(pds.certificate_pem.as_bytes())?;
+        let http_client = reqwest::Client::builder()
+            .add_root_certificate(certificate.clone())
+            .user_agent(config.user_agent.clone())
+            .build()?;
+
+        let pool = PgPool::connect(&config.database_url).await?;
+        MIGRATOR.run(&pool).await?;
+
+        let cache_pool = create_cache_pool(&config.redis_url)?;
+
+        let supported_languages = vec![LanguageIdentifier::from_str("en-us")?];
+        let mut locales = Locales::new(supported_languages.clone());
+        populate_locale(&supported_languages, &mut locales)?;
+
+        #[cfg(feature = "embed")]
+        let jinja = embed_env::build_env(config.external_base.clone(), config.version.clone());
+
+        #[cfg(feature = "reload")]
+        let jinja = reload_env::build_env(&config.external_base, &config.version);
+
+        let dns_resolver = create_resolver(config.dns_nameservers.clone());
+
+        let web_context = WebContext::new(
+            pool.clone(),
+            cache_pool,
+            AppEngine::from(jinja),
+            &http_client,
+            config,
+            I18nContext::new(supported_languages, locales),
+            dns_resolver,
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base = format!("http://{}", listener.local_addr()?);
+        let app = build_router(web_context);
+        tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                eprintln!("smoke test app stopped: {err}");
+            }
+        });
+
+        // Redirects are followed by hand, because signing in goes through
+        // the mock PDS and back
+        let client = reqwest::Client::builder()
+            .add_root_certificate(certificate)
+            .redirect(Policy::none())
+            .build()?;
+
+        Ok(Self {
+            base,
+            client,
+            session_cookie: None,
+            pds,
+            pool,
+            _services: services,
+        })
+    }
+
+    fn with_session(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.session_cookie {
+            Some(cookie) => request.header(header::COOKIE, cookie),
+            None => request,
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<Response> {
+        let request = self.client.get(format!("{}{}", self.base, path));
+        Ok(self.with_session(request).send().await?)
+    }
+
+    async fn post_form(&self, path: &str, form: &[(&str, &str)]) -> Result<Response> {
+        let request = self
+            .client
+            .post(format!("{}{}", self.base, path))
+            .form(form);
+        Ok(self.with_session(request).send().await?)
+    }
+
+    /// Signs in with the identity of the mock PDS. The session cookie is
+    /// sent with every request after this.
+    async fn login(&mut self) -> Result<()> {
+        let response = self.post_form("/oauth/login", &[("handle", DID)]).await?;
+        assert!(
+            response.status().is_redirection(),
+            "login was not sent to the authorization server: {}",
+            response.text().await?
+        );
+        let authorize_url = location(&response)?;
+        assert!(authorize_url.starts_with(&self.pds.base));
+
+        let response = self.client.get(&authorize_url).send().await?;
+        assert!(response.status().is_redirection());
+        let callback_url = location(&response)?;
+        let callback_path = callback_url
+            .strip_prefix(&format!("https://{EXTERNAL_BASE}"))
+            .ok_or_else(|| anyhow!("unexpected callback url {callback_url}"))?;
+
+        let response = self.get(callback_path).await?;
+        assert!(
+            response.status().is_redirection(),
+            "callback did not sign in: {}",
+            response.text().await?
+        );
+        let session_cookie = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .find(|value| value.starts_with("session1="))
+            .ok_or_else(|| anyhow!("callback did not set a session cookie"))?;
+        self.session_cookie = Some(session_cookie.to_string());
+
+        Ok(())
+    }
+
+    /// Creates an event and returns the record the PDS was sent.
+    async fn create_event(&self, name: &str) -> Result<mock_pds::StoredRecord> {
+        let starts_at = (Utc::now() + Duration::days(7)).to_rfc3339();
+        let response = self
+            .post_form(
+                "/event",
+                &[
+                    ("build_state", "Selected"),
+                    ("name", name),
+                    ("description", "An evening of smoke tests and snacks."),
+                    ("status", "scheduled"),
+                    ("mode", "inperson"),
+                    ("starts_at", &starts_at),
+                    ("tz", "UTC"),
+                ],
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.text().await?;
+
+        let event = self
+            .pds
+            .records(EVENT_NSID)
+            .into_iter()
+            .find(|record| record.value["name"] == name)
+            .ok_or_else(|| anyhow!("event was not written to the pds: {body}"))?;
+        Ok(event)
+    }
+}
+
+fn location(response: &Response) -> Result<String> {
+    response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("response has no location"))
+}
+
+/// The path of the page of an event, from its AT-URI.
+fn event_path(aturi: &str) -> String {
+    let parts = aturi
+        .strip_prefix("at://")
+        .unwrap_or(aturi)
+        .split('/')
+        .collect::<Vec<_>>();
+    format!("/{}/{}", parts[0], parts[2])
+}
+
+#[tokio::test]
+#[ignore = "needs Docker for Postgres and Redis"]
+async fn test_login_create_rsvp_edit_view() -> Result<()> {
+    let mut app = SmokeApp::start().await?;
+
+    // Signed out visitors are sent to sign in before creating events
+    let response = app.get("/event").await?;
+    assert!(response.status().is_redirection());
+
+    app.login().await?;
+
+    let response = app.get("/event").await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let event = app.create_event("Smoke Test Meetup").await?;
+    let path = event_path(&event.uri);
+
+    let response = app.get(&path).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await?.contains("Smoke Test Meetup"));
+
+    // RSVP going
+    let response = app
+        .post_form(
+            "/rsvp",
+            &[
+                ("build_state", "Review"),
+                ("subject_aturi", &event.uri),
+                ("subject_cid", &event.cid),
+                ("status", "going"),
+            ],
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await?;
+
+    let rsvps = app.pds.records(RSVP_NSID);
+    let rsvp = rsvps
+        .first()
+        .ok_or_else(|| anyhow!("rsvp was not written to the pds: {body}"))?;
+    assert_eq!(rsvp.value["subject"]["uri"], Value::from(event.uri.clone()));
+    assert!(rsvp.value["status"]
+        .as_str()
+        .is_some_and(|status| status.ends_with("#going")));
+
+    // Edit the name of the event
+    let starts_at = event.value["startsAt"].as_str().unwrap_or_default();
+    let response = app
+        .post_form(
+            &format!("{path}/edit"),
+            &[
+                ("build_state", "Selected"),
+                ("name", "Smoke Test Meetup, Renamed"),
+                ("description", "An evening of smoke tests and snacks."),
+                ("status", "scheduled"),
+                ("mode", "inperson"),
+                ("starts_at", starts_at),
+                ("tz", "UTC"),
+                ("event_cid", &event.cid),
+            ],
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await?;
+
+    let edited = app
+        .pds
+        .records(EVENT_NSID)
+        .into_iter()
+        .find(|record| record.uri == event.uri)
+        .ok_or_else(|| anyhow!("event is missing from the pds"))?;
+    assert_ne!(edited.cid, event.cid, "event was not updated: {body}");
+    assert_eq!(edited.value["name"], "Smoke Test Meetup, Renamed");
+
+    // The page shows the edit and the RSVP, signed in and signed out
+    let response = app.get(&path).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await?;
+    assert!(body.contains("Smoke Test Meetup, Renamed"));
+
+    app.session_cookie = None;
+    let response = app.get(&path).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await?;
+    assert!(body.contains("Smoke Test Meetup, Renamed"));
+    assert!(body.contains(HANDLE));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker for Postgres and Redis"]
+async fn test_api_service_auth_method() -> Result<()> {
+    let app = SmokeApp::start().await?;
+    let audience = service_did(EXTERNAL_BASE);
+
+    let create_rsvp = |lxm: Option<&str>| -> Result<reqwest::RequestBuilder> {
+        let token = mint_service_token(&app.pds.signing_key, DID, &audience, lxm)?;
+        Ok(app
+            .client
+            .post(format!("{}/api/v1/rsvps", app.base))
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "subject": format!("at://{DID}/{EVENT_NSID}/missing"),
+                "status": "going",
+            })))
+    };
+
+    // Tokens bound to another route, or to none, are refused
+    for lxm in [Some("events.smokesignal.calendar.createEvent"), None] {
+        let response = create_rsvp(lxm)?.send().await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // A token bound to the route is accepted, and the missing event is
+    // reported instead
+    let response = create_rsvp(Some("events.smokesignal.calendar.createRsvp"))?
+        .send()
+        .await?;
+    assert_ne!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "{}",
+        response.text().await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker for Postgres and Redis"]
+async fn test_header_image_upload_limit() -> Result<()> {
+    let mut app = SmokeApp::start().await?;
+    app.login().await?;
+
+    // Header images are larger than the form body limit
+    let boundary = "smoke-test-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"header_image_upload\"; filename=\"header.png\"\r\nContent-Type: image/png\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend(vec![0u8; 100 * 1024]);
+    body.extend(format!("\r\n--{boundary}--\r\n").into_bytes());
+
+    let request = app
+        .client
+        .post(format!("{}/event/header-image", app.base))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(body);
+    let response = app.with_session(request).send().await?;
+
+    // The upload reaches the handler, which only answers htmx requests
+    assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker for Postgres and Redis"]
+async fn test_cached_page_banners() -> Result<()> {
+    let app = SmokeApp::start().await?;
+
+    let message = "Smoke test maintenance tonight";
+    let id = banner_insert(&app.pool, message, "info", Utc::now(), None, DID).await?;
+
+    // The first visit caches the home page with the banner
+    let response = app.get("/").await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await?.contains(message));
+
+    // Visitors who dismissed the banner don't get that page
+    let response = app
+        .client
+        .get(format!("{}/", app.base))
+        .header(header::COOKIE, format!("dismissed_banners={id}"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.text().await?.contains(message));
+
+    let response = app.get("/").await?;
+    assert!(response.text().await?.contains(message));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker for Postgres and Redis"]
+async fn test_import_repo_queued() -> Result<()> {
+    let mut app = SmokeApp::start().await?;
+    app.login().await?;
+
+    // The repository is imported in the background, and the request only
+    // queues it and shows its progress
+    let request = app
+        .client
+        .post(format!("{}/import/repo", app.base))
+        .header("HX-Request", "true");
+    let response = app.with_session(request).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await?;
+    assert!(body.contains("id=\"importRecords\""), "{body}");
+
+    let source = sqlx::query_scalar!("SELECT source FROM import_jobs WHERE did = $1", DID)
+        .fetch_one(&app.pool)
+        .await?;
+    assert_eq!(source, "repo");
+
+    Ok(())
+}
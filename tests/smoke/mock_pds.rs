@@ -0,0 +1,369 @@
+//! A PLC directory, PDS and authorization server for a single identity, in one
+//! server. It is served over HTTPS because the app only makes `https://`
+//! requests to them.
+//!
+//! Only what signing in, writing records and minting service auth tokens
+//! needs is implemented. Tokens, DPoP proofs and client assertions are
+//! accepted without being checked.
+
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use p256::SecretKey;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use smokesignal::service_auth::PublicSigningKey;
+
+/// A record written to the mock PDS.
+#[derive(Clone, Debug)]
+pub struct StoredRecord {
+    pub uri: String,
+    pub cid: String,
+    pub value: Value,
+}
+
+#[derive(Default)]
+struct MockState {
+    base: String,
+    did: String,
+    handle: String,
+    /// The `#atproto` key of the DID document, in multibase.
+    signing_key: String,
+    sequence: u64,
+    /// The state and redirect URI of pushed authorization requests, by
+    /// request URI.
+    requests: HashMap<String, (String, String)>,
+    codes: Vec<String>,
+    /// Records by collection and record key.
+    records: HashMap<(String, String), StoredRecord>,
+}
+
+impl MockState {
+    fn next(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+pub struct MockPds {
+    /// The `host:port` of the server, used as the PLC hostname.
+    pub host: String,
+    /// The `https://` URL of the server, used as the PDS and the issuer.
+    pub base: String,
+    /// The self-signed certificate of the server, in PEM.
+    pub certificate_pem: String,
+    /// The key published as the identity's `#atproto` signing key, used to
+    /// mint service auth tokens.
+    pub signing_key: SecretKey,
+    state: SharedState,
+    handle: Handle,
+}
+
+impl MockPds {
+    pub async fn start(did: &str, handle: &str) -> Result<Self> {
+        let certified_key = rcgen::generate_simple_self_signed(vec!["127.0.0.1".to_string()])?;
+        let certificate_pem = certified_key.cert.pem();
+        let tls_config = RustlsConfig::from_pem(
+            certificate_pem.clone().into_bytes(),
+            certified_key.key_pair.serialize_pem().into_bytes(),
+        )
+        .await?;
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let host = listener.local_addr()?.to_string();
+        let base = format!("https://{host}");
+
+        let signing_key = SecretKey::random(&mut rand::thread_rng());
+
+        let state = Arc::new(Mutex::new(MockState {
+            base: base.clone(),
+            did: did.to_string(),
+            handle: handle.to_string(),
+            signing_key: PublicSigningKey::P256(signing_key.public_key()).to_multibase(),
+            ..Default::default()
+        }));
+
+        let app = Router::new()
+            .route(
+                "/.well-known/oauth-protected-resource",
+                get(handle_protected_resource),
+            )
+            .route(
+                "/.well-known/oauth-authorization-server",
+                get(handle_authorization_server),
+            )
+            .route("/oauth/par", post(handle_par))
+            .route("/oauth/authorize", get(handle_authorize))
+            .route("/oauth/token", post(handle_token))
+            .route(
+                "/xrpc/com.atproto.repo.createRecord",
+                post(handle_create_record),
+            )
+            .route("/xrpc/com.atproto.repo.putRecord", post(handle_put_record))
+            .route("/{did}", get(handle_did_document))
+            .with_state(state.clone());
+
+        let server_handle = Handle::new();
+        let server = axum_server::from_tcp_rustls(listener, tls_config)
+            .handle(server_handle.clone())
+            .serve(app.into_make_service());
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                eprintln!("mock pds stopped: {err}");
+            }
+        });
+
+        Ok(Self {
+            host,
+            base,
+            certificate_pem,
+            signing_key,
+            state,
+            handle: server_handle,
+        })
+    }
+
+    /// Returns the records written to a collection, oldest first.
+    pub fn records(&self, collection: &str) -> Vec<StoredRecord> {
+        let state = self.state.lock().unwrap();
+        let mut records = state
+            .records
+            .iter()
+            .filter(|((record_collection, _), _)| record_collection == collection)
+            .map(|(_, record)| record.clone())
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| a.cid.cmp(&b.cid));
+        records
+    }
+}
+
+impl Drop for MockPds {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+async fn handle_did_document(
+    State(state): State<SharedState>,
+    Path(did): Path<String>,
+) -> Response {
+    let state = state.lock().unwrap();
+    if did != state.did {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    Json(json!({
+        "id": state.did,
+        "alsoKnownAs": [format!("at://{}", state.handle)],
+        "verificationMethod": [{
+            "id": format!("{}#atproto", state.did),
+            "type": "Multikey",
+            "controller": state.did,
+            "publicKeyMultibase": state.signing_key,
+        }],
+        "service": [{
+            "id": "#atproto_pds",
+            "type": "AtprotoPersonalDataServer",
+            "serviceEndpoint": state.base,
+        }],
+    }))
+    .into_response()
+}
+
+async fn handle_protected_resource(State(state): State<SharedState>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    Json(json!({
+        "resource": state.base,
+        "authorization_servers": [state.base],
+        "scopes_supported": [],
+        "bearer_methods_supported": ["header"],
+    }))
+}
+
+async fn handle_authorization_server(State(state): State<SharedState>) -> Json<Value> {
+    let base = state.lock().unwrap().base.clone();
+    Json(json!({
+        "issuer": base,
+        "introspection_endpoint": format!("{base}/oauth/introspect"),
+        "authorization_endpoint": format!("{base}/oauth/authorize"),
+        "pushed_authorization_request_endpoint": format!("{base}/oauth/par"),
+        "token_endpoint": format!("{base}/oauth/token"),
+        "authorization_response_iss_parameter_supported": true,
+        "client_id_metadata_document_supported": true,
+        "request_parameter_supported": true,
+        "require_pushed_authorization_requests": true,
+        "code_challenge_methods_supported": ["S256"],
+        "dpop_signing_alg_values_supported": ["ES256"],
+        "grant_types_supported": ["authorization_code", "refresh_token"],
+        "response_types_supported": ["code"],
+        "scopes_supported": ["atproto", "transition:generic"],
+        "token_endpoint_auth_methods_supported": ["none", "private_key_jwt"],
+        "token_endpoint_auth_signing_alg_values_supported": ["ES256"],
+    }))
+}
+
+#[derive(Deserialize)]
+struct ParForm {
+    state: String,
+    redirect_uri: String,
+}
+
+async fn handle_par(
+    State(state): State<SharedState>,
+    Form(form): Form<ParForm>,
+) -> impl IntoResponse {
+    let mut state = state.lock().unwrap();
+    let request_uri = format!("urn:ietf:params:oauth:request_uri:req-{}", state.next());
+    state
+        .requests
+        .insert(request_uri.clone(), (form.state, form.redirect_uri));
+
+    (
+        StatusCode::CREATED,
+        Json(json!({ "request_uri": request_uri, "expires_in": 299 })),
+    )
+}
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    request_uri: String,
+}
+
+/// Approves every request, as if the person signing in had agreed, and
+/// sends them back to the app with a code.
+async fn handle_authorize(
+    State(state): State<SharedState>,
+    Query(query): Query<AuthorizeQuery>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some((oauth_state, redirect_uri)) = state.requests.remove(&query.request_uri) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    let code = format!("code-{}", state.next());
+    state.codes.push(code.clone());
+
+    let destination = format!(
+        "{redirect_uri}?state={}&iss={}&code={}",
+        urlencoding::encode(&oauth_state),
+        urlencoding::encode(&state.base),
+        urlencoding::encode(&code),
+    );
+    Redirect::to(&destination).into_response()
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    grant_type: String,
+    code: Option<String>,
+}
+
+async fn handle_token(State(state): State<SharedState>, Form(form): Form<TokenForm>) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if form.grant_type == "authorization_code" {
+        let known_code = form.code.is_some_and(|code| {
+            let position = state.codes.iter().position(|value| *value == code);
+            position.map(|index| state.codes.remove(index)).is_some()
+        });
+        if !known_code {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        }
+    }
+
+    let sequence = state.next();
+    Json(json!({
+        "access_token": format!("access-{sequence}"),
+        "token_type": "DPoP",
+        "refresh_token": format!("refresh-{sequence}"),
+        "scope": "atproto transition:generic",
+        "expires_in": 3600,
+        "sub": state.did,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteRecordInput {
+    repo: String,
+    collection: String,
+    rkey: Option<String>,
+    record: Value,
+    swap_record: Option<String>,
+}
+
+fn write_record(state: &mut MockState, input: WriteRecordInput, record_key: String) -> Response {
+    if input.repo != state.did {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "InvalidRequest", "message": "unknown repo" })),
+        )
+            .into_response();
+    }
+
+    let key = (input.collection.clone(), record_key.clone());
+    if let Some(swap_record) = input.swap_record {
+        let current_cid = state.records.get(&key).map(|record| record.cid.as_str());
+        if current_cid != Some(swap_record.as_str()) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "InvalidSwap", "message": "record was modified" })),
+            )
+                .into_response();
+        }
+    }
+
+    // Sequence numbers are padded so that CIDs sort in the order records
+    // were written
+    let record = StoredRecord {
+        uri: format!("at://{}/{}/{}", state.did, input.collection, record_key),
+        cid: format!("bafyreismoke{:08}", state.next()),
+        value: input.record,
+    };
+    state.records.insert(key, record.clone());
+
+    Json(json!({ "uri": record.uri, "cid": record.cid })).into_response()
+}
+
+async fn handle_create_record(
+    State(state): State<SharedState>,
+    Json(input): Json<WriteRecordInput>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let record_key = match input.rkey.clone() {
+        Some(value) => value,
+        None => format!("3lsmoke{:06}", state.next()),
+    };
+    write_record(&mut state, input, record_key)
+}
+
+async fn handle_put_record(
+    State(state): State<SharedState>,
+    Json(input): Json<WriteRecordInput>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(record_key) = input.rkey.clone() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    write_record(&mut state, input, record_key)
+}